@@ -0,0 +1,121 @@
+#!/bin/sh
+# Mock makemkvcon used by the `virtual-disc` cargo feature.
+#
+# Prints canned robot output for the two commands Reelix runs:
+#   makemkvcon -r info disc:N
+#   makemkvcon mkv file:<mount point> <title id> <output dir> ...
+#
+# Environment:
+#   REELIX_MOCK_SPEED  seconds per progress step while ripping (default 0.2)
+#   REELIX_MOCK_FAIL   set to 1 to make every rip fail with read errors
+#   REELIX_MOCK_MB     size of the ripped file in megabytes (default 8)
+
+SPEED="${REELIX_MOCK_SPEED:-0.2}"
+SIZE_MB="${REELIX_MOCK_MB:-8}"
+DRIVE='BD-RE VIRTUAL DRIVE 1.00 REELIX000'
+
+msg() {
+  # MSG:code,flags,count,message,format
+  printf 'MSG:%s,0,0,"%s","%s"\n' "$1" "$2" "$2"
+}
+
+started() {
+  msg 1005 "MakeMKV v1.17.7 linux(x64-release) started"
+}
+
+# file:<mount point> ends in the label, disc:N is the Nth label of REELIX_VIRTUAL_DISCS
+label_for() {
+  case "$1" in
+    file:*) basename "${1#file:}" ;;
+    disc:*)
+      echo "${REELIX_VIRTUAL_DISCS:-THE_MARTIAN}" | tr ',' '\n' | sed 's/^ *//;s/ *$//' |
+        grep -v '^$' | sed -n "$(( ${1#disc:} + 1 ))p"
+      ;;
+    *) echo "VIRTUAL_DISC" ;;
+  esac
+}
+
+title() {
+  # title id, duration, chapters, bytes, description
+  printf 'TINFO:%s,2,0,"%s"\n' "$1" "$LABEL"
+  printf 'TINFO:%s,8,0,"%s"\n' "$1" "$3"
+  printf 'TINFO:%s,9,0,"%s"\n' "$1" "$2"
+  printf 'TINFO:%s,10,0,"%s GB"\n' "$1" "$(($4 / 1000000000))"
+  printf 'TINFO:%s,11,0,"%s"\n' "$1" "$4"
+  printf 'TINFO:%s,16,0,"%05d.mpls"\n' "$1" "$1"
+  printf 'TINFO:%s,27,0,"%s_t%02d.mkv"\n' "$1" "$LABEL" "$1"
+  printf 'TINFO:%s,30,0,"%s"\n' "$1" "$5"
+  printf 'SINFO:%s,0,19,0,"1920x1080"\n' "$1"
+}
+
+info() {
+  started
+  msg 3007 "Using direct disc access mode"
+  index="${DISC_INDEX:-0}"
+  printf 'DRV:%s,2,999,1,"%s","%s","/dev/virtual%s"\n' "$index" "$DRIVE$index" "$LABEL" "$index"
+  msg 3025 "Title #00800.mpls has length of 12 seconds which is less than minimum title length of 45 seconds and was therefore skipped"
+  printf 'TCOUNT:3\n'
+  printf 'CINFO:2,0,"%s"\n' "$LABEL"
+  title 0 "2:21:17" 24 32505856000 "Main feature"
+  title 1 "0:43:02" 8 9011200000 "Episode length extra"
+  title 2 "0:05:30" 1 1153433600 "Trailer"
+  msg 5011 "Operation successfully completed"
+}
+
+rip() {
+  title_id="$1"
+  dir="$2"
+  started
+  printf 'PRGT:5018,0,"Saving to MKV file"\n'
+  printf 'PRGC:5017,0,"Saving to MKV file"\n'
+  step=0
+  while [ "$step" -le 20 ]; do
+    printf 'PRGV:%s,%s,65536\n' $((step * 3276)) $((step * 3276))
+    if [ "${REELIX_MOCK_FAIL:-0}" = "1" ] && [ "$step" -eq 10 ]; then
+      msg 2003 "Error 'Scsi error - MEDIUM ERROR:L-EC UNCORRECTABLE ERROR' occurred while reading '/BDMV/STREAM/00001.m2ts' at offset '1048576'"
+      msg 5003 "Failed to save title $title_id to file $dir/${LABEL}_t$(printf '%02d' "$title_id").mkv"
+      msg 5037 "Copy complete. 0 titles saved, 1 failed."
+      exit 0
+    fi
+    sleep "$SPEED"
+    step=$((step + 1))
+  done
+  mkdir -p "$dir"
+  dd if=/dev/zero of="$dir/${LABEL}_t$(printf '%02d' "$title_id").mkv" bs=1048576 count="$SIZE_MB" 2>/dev/null
+  msg 5036 "Copy complete. 1 titles saved."
+}
+
+# Flags can come before or after the command, only the positional arguments matter
+command=""
+disc=""
+title_id=""
+dir=""
+for arg in "$@"; do
+  case "$arg" in
+    -*) continue ;;
+  esac
+  if [ -z "$command" ]; then
+    command="$arg"
+  elif [ -z "$disc" ]; then
+    disc="$arg"
+  elif [ -z "$title_id" ]; then
+    title_id="$arg"
+  elif [ -z "$dir" ]; then
+    dir="$arg"
+  fi
+done
+
+LABEL="$(label_for "$disc")"
+[ -n "$LABEL" ] || LABEL="VIRTUAL_DISC"
+case "$disc" in
+  disc:*) DISC_INDEX="${disc#disc:}" ;;
+esac
+
+case "$command" in
+  info) info ;;
+  mkv) rip "$title_id" "$dir" ;;
+  *)
+    msg 5010 "Mock makemkvcon does not support '$command'"
+    exit 1
+    ;;
+esac
@@ -9,20 +9,84 @@ macro_rules! all_commands {
         tauri::generate_handler!(
             $crate::commands::general::index,
             $crate::commands::general::movie,
+            $crate::commands::general::open_deep_link,
             $crate::commands::general::open_url,
+            $crate::commands::general::person,
+            $crate::commands::general::ui_state_get,
+            $crate::commands::general::ui_state_set,
             $crate::commands::general::search,
+            $crate::commands::general::search_from_photo,
             $crate::commands::general::suggestion,
+            $crate::commands::general::search_suggest,
             $crate::commands::general::season,
             $crate::commands::disk::selected_disk,
             $crate::commands::disk::eject_disk,
+            $crate::commands::disk::close_tray,
+            $crate::commands::disk::lock_tray,
+            $crate::commands::disk::tray_state,
+            $crate::commands::disk::archive_disc,
+            $crate::commands::disk::cancel_archive,
+            $crate::commands::disk::stream_disc,
+            $crate::commands::disk::preview_title,
+            $crate::commands::disk::stop_stream,
+            $crate::commands::disk::drive_health,
+            $crate::commands::disk::audit_library,
+            $crate::commands::disk::fix_library_issues,
+            $crate::commands::disk::benchmark_drive,
             $crate::commands::general::tv,
             $crate::commands::rip::assign_episode_to_title,
+            $crate::commands::rip::apply_assignment_suggestions,
             $crate::commands::rip::rip_movie,
+            $crate::commands::rip::home_video,
+            $crate::commands::rip::rip_personal,
             $crate::commands::rip::rip_season,
             $crate::commands::rip::reorder_tv_episodes_on_ftp,
             $crate::commands::rip::set_auto_rip,
+            $crate::commands::rip::rip_plan,
+            $crate::commands::rip::add_movie_to_rip_plan,
+            $crate::commands::rip::add_season_to_rip_plan,
+            $crate::commands::rip::remove_rip_plan_item,
+            $crate::commands::rip::complete_rip_plan_item,
+            $crate::commands::rip::set_rip_plan_drive,
+            $crate::commands::rip::clear_finished_rip_plan,
+            $crate::commands::rip::retry_job,
+            $crate::commands::rip::approve_upload,
+            $crate::commands::rip::reject_upload,
+            $crate::commands::rip::retry_failed_titles,
+            $crate::commands::rip::fix_match,
+            $crate::commands::rip::fix_match_search,
+            $crate::commands::rip::apply_fix_match,
+            $crate::commands::rip::notification_action,
+            $crate::commands::rip::update_job_note,
+            $crate::commands::rip::requeue_job,
+            $crate::commands::rip::pending_uploads,
+            $crate::commands::rip::retry_pending_upload,
+            $crate::commands::rip::remove_pending_upload,
             $crate::commands::setting::update_ftp_settings,
+            $crate::commands::setting::update_season_poster_settings,
             $crate::commands::setting::ftp_settings,
+            $crate::commands::setting::settings,
+            $crate::commands::setting::update_naming_settings,
+            $crate::commands::setting::update_post_process_settings,
+            $crate::commands::setting::update_concurrency_settings,
+            $crate::commands::setting::update_network_settings,
+            $crate::commands::setting::update_review_settings,
+            $crate::commands::setting::update_remote_api_settings,
+            $crate::commands::setting::update_library_profiles,
+            $crate::commands::setting::update_ripper_settings,
+            $crate::commands::setting::update_tray_settings,
+            $crate::commands::setting::update_archive_settings,
+            $crate::commands::setting::update_library_dirs,
+            $crate::commands::setting::pick_library_dir,
+            $crate::commands::setting::import_collection,
+            $crate::commands::setting::update_notification_settings,
+            $crate::commands::setting::update_do_not_disturb,
+            $crate::commands::setting::update_locale_settings,
+            $crate::commands::setting::update_push_settings,
+            $crate::commands::setting::update_arr_settings,
+            $crate::commands::setting::update_trakt_settings,
+            $crate::commands::setting::authorize_trakt,
+            $crate::commands::setting::disconnect_trakt,
             $crate::commands::setting::the_movie_db,
         )
     };
@@ -1,28 +1,35 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use crate::models::optical_disk_info::DiskId;
-use crate::services::disk_manager;
+use crate::models::title_info::TitleId;
+use crate::reelix_error::ReelixError;
+use crate::services::{
+    archive, blocking, disk_manager, drive_benchmark, library_audit, makemkv_check, makemkvcon,
+    notifications,
+};
 use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::drive_stats_state::DriveStatsState;
+use crate::state::job_state::{JobStatus, JobType};
+use crate::state::locks::RwLockExt;
 use crate::state::AppState;
-use crate::templates::{self, render_error};
-use tauri::State;
+use crate::templates::toast::Toast;
+use crate::templates::{self, render_reelix_error};
+use std::path::PathBuf;
+use tauri::{Manager, State};
+use tauri_plugin_opener::OpenerExt;
 
 #[tauri::command]
 pub fn selected_disk(
     disk_id: u32,
     state: State<'_, AppState>,
-    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<String, templates::Error> {
     let id = DiskId::from(disk_id);
 
-    let mut selected_optical_disk_id = state
-        .selected_optical_disk_id
-        .write()
-        .expect("failed to lock selected disk ID");
+    let mut selected_optical_disk_id = state.selected_optical_disk_id.write_or_recover();
     *selected_optical_disk_id = Some(id);
 
     // Also refresh the current season if one is being viewed
-    let disk_selector_html =
-        templates::disk_titles::render_options(&state, &background_process_state)?;
+    let disk_selector_html = templates::disk_titles::render_options(&app_handle)?;
 
     Ok(disk_selector_html)
 }
@@ -30,11 +37,16 @@ pub fn selected_disk(
 #[tauri::command]
 pub fn eject_disk(
     state: State<'_, AppState>,
-    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<String, templates::Error> {
     match state.selected_disk() {
         Some(optical_disk) => {
             match optical_disk.read() {
+                Ok(disk) if disk.is_remote() => {
+                    return render_reelix_error(&ReelixError::Validation(
+                        "The disc is in a remote drive, eject it on that machine".to_string(),
+                    ))
+                }
                 Ok(disk) => {
                     // On Linux, use the device path; on other platforms use mount point
                     #[cfg(target_os = "linux")]
@@ -43,13 +55,388 @@ pub fn eject_disk(
                     #[cfg(not(target_os = "linux"))]
                     disk_manager::eject(&disk.mount_point);
                 }
-                Err(_) => return render_error("Failed to get lock on memory for optical disk"),
+                Err(_) => {
+                    return render_reelix_error(
+                        &"Failed to get lock on memory for optical disk".into(),
+                    )
+                }
             };
         }
-        None => return render_error("No Disk is Selected can't eject"),
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "No Disk is Selected can't eject".to_string(),
+            ))
+        }
+    };
+
+    templates::disk_titles::render_options(&app_handle)
+}
+
+/// Experimental: stream the disc with makemkvcon so it can be previewed in VLC before a long
+/// rip, shows the URL of the stream in a toast that stays until it is closed.
+#[tauri::command]
+pub async fn stream_disc(
+    disk_id: u32,
+    state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let id = DiskId::from(disk_id);
+    let Some(optical_disk) = state.find_optical_disk_by_id(&id) else {
+        return render_reelix_error(&ReelixError::NotFound(
+            "The disc is not in the drive anymore".to_string(),
+        ));
+    };
+    let disk = optical_disk.read_or_recover().clone();
+    if disk.is_remote() {
+        return render_reelix_error(&ReelixError::Validation(
+            "The disc is in a remote drive, stream it on that machine".to_string(),
+        ));
+    }
+    if disk.is_audio_cd() {
+        return render_reelix_error(&ReelixError::Validation(
+            "Audio CDs have no video to stream".to_string(),
+        ));
+    }
+    if background_process_state
+        .find_job(Some(id), &None, &[JobStatus::Processing])
+        .is_some()
+    {
+        return render_reelix_error(&ReelixError::Validation(
+            "The disc is busy, wait for the rip to finish".to_string(),
+        ));
+    }
+    match makemkvcon::stream(&app_handle, &disk).await {
+        Ok(url) => templates::toast::render_toast_append(
+            Toast::info(
+                format!("Streaming {}", disk.name),
+                format!("Open {url} in VLC"),
+            )
+            .with_auto_hide(0)
+            .with_action("Stop", format!("/stop_stream?diskId={disk_id}")),
+        ),
+        Err(message) => render_reelix_error(&message.into()),
+    }
+}
+
+/// Rips the first minutes of a title to a temp file and opens it in the default player, to
+/// check which title is the right episode before assigning it.
+#[tauri::command]
+pub async fn preview_title(
+    disk_id: u32,
+    title_id: TitleId,
+    state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let id = DiskId::from(disk_id);
+    let Some(optical_disk) = state.find_optical_disk_by_id(&id) else {
+        return render_reelix_error(&ReelixError::NotFound(
+            "The disc is not in the drive anymore".to_string(),
+        ));
+    };
+    let disk = optical_disk.read_or_recover().clone();
+    let Some(title) = disk.find_title_by_id(title_id) else {
+        return render_reelix_error(&ReelixError::NotFound(format!(
+            "Title {title_id} is not on the disc"
+        )));
+    };
+    if background_process_state
+        .find_job(Some(id), &None, &[JobStatus::Processing])
+        .is_some()
+    {
+        return render_reelix_error(&ReelixError::Validation(
+            "The disc is busy, wait for the rip to finish".to_string(),
+        ));
+    }
+    let dir = std::env::temp_dir()
+        .join("reelix-preview")
+        .join(format!("disc-{disk_id}-title-{title_id}"));
+    let path = match makemkvcon::preview_title(&app_handle, &disk, &title, &dir).await {
+        Ok(path) => path,
+        Err(message) => return render_reelix_error(&message.into()),
+    };
+    if let Err(e) = app_handle
+        .opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+    {
+        return render_reelix_error(&format!("Failed to open the preview: {e}").into());
+    }
+    templates::toast::render_toast_append(Toast::success(
+        "Preview ready",
+        format!(
+            "The first {} seconds of title {title_id} opened in your player",
+            makemkvcon::PREVIEW_SECONDS
+        ),
+    ))
+}
+
+/// Stops the stream of `stream_disc`.
+#[tauri::command]
+pub fn stop_stream(
+    disk_id: u32,
+    state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+) -> Result<String, templates::Error> {
+    let id = DiskId::from(disk_id);
+    let Some(optical_disk) = state.find_optical_disk_by_id(&id) else {
+        return render_reelix_error(&ReelixError::NotFound(
+            "The disc is not in the drive anymore".to_string(),
+        ));
+    };
+    // The process of the disc is the rip while one is running
+    if background_process_state
+        .find_job(Some(id), &None, &[JobStatus::Processing])
+        .is_some()
+    {
+        return render_reelix_error(&ReelixError::Validation(
+            "The disc is being ripped, not streamed".to_string(),
+        ));
+    }
+    optical_disk.read_or_recover().kill_process();
+    templates::toast::render_toast_append(Toast::success("Disc stream", "Stream stopped"))
+}
+
+/// Copy a data disc (photos, DVD-ROM extras) as it is into the archive folder instead of ripping it.
+#[tauri::command]
+pub fn archive_disc(
+    disk_id: u32,
+    state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let Some(optical_disk) = state.find_optical_disk_by_id(&DiskId::from(disk_id)) else {
+        return render_reelix_error(&ReelixError::NotFound(
+            "The disc is not in the drive anymore".to_string(),
+        ));
+    };
+    let disk = optical_disk.read_or_recover().clone();
+    if disk.is_remote() {
+        return render_reelix_error(&ReelixError::Validation(
+            "The disc is in a remote drive, archive it on that machine".to_string(),
+        ));
+    }
+    if disk.is_audio_cd() {
+        return render_reelix_error(&ReelixError::Validation(
+            "Audio CDs have no files to archive".to_string(),
+        ));
+    }
+    if !disk.mount_point.is_dir() {
+        return render_reelix_error(&ReelixError::Validation(
+            "The disc is not mounted, there is nothing to copy".to_string(),
+        ));
+    }
+
+    let job = background_process_state.new_job(
+        JobType::Archiving,
+        JobStatus::Pending,
+        Some(disk.clone()),
+    );
+    background_process_state.emit_jobs_changed(&app_handle);
+    archive::spawn(
+        app_handle.clone(),
+        job,
+        disk.mount_point.clone(),
+        disk.name.clone(),
+        state.archive_options(),
+    );
+
+    templates::disk_titles::render_options(&app_handle)
+}
+
+/// Stop archiving a disc, the copy made so far is removed.
+#[tauri::command]
+pub fn cancel_archive(
+    id: u64,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let Some(job) = background_process_state.find_job_by_id(id.into()) else {
+        return render_reelix_error(&ReelixError::NotFound(format!("Job {id} no longer exists")));
+    };
+    if !job.read_or_recover().is_cancellable() {
+        return render_reelix_error(&ReelixError::Validation(
+            "Only running archive jobs can be cancelled".to_string(),
+        ));
+    }
+    {
+        let mut job_guard = job.write_or_recover();
+        job_guard.update_status(JobStatus::Error);
+        job_guard.update_message(archive::CANCELLED_MESSAGE);
+    }
+    job.read_or_recover().emit_progress_change(&app_handle);
+    background_process_state.emit_jobs_changed(&app_handle);
+    Ok(String::new())
+}
+
+/// Read part of the selected disc to measure its drive, the result predicts rip times.
+#[tauri::command]
+pub async fn benchmark_drive(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let Some(optical_disk) = state.selected_disk() else {
+        return render_reelix_error(&ReelixError::Validation(
+            "Insert a disc to benchmark its drive".to_string(),
+        ));
+    };
+    let disk = optical_disk.read_or_recover().clone();
+    if disk.is_remote() {
+        return render_reelix_error(&ReelixError::Validation(
+            "The disc is in a remote drive, benchmark it on that machine".to_string(),
+        ));
+    }
+    if disk.is_audio_cd() || !disk.mount_point.is_dir() {
+        return render_reelix_error(&ReelixError::Validation(
+            "The drive can only be benchmarked with a mounted video disc".to_string(),
+        ));
+    }
+
+    let mount_point = disk.mount_point.clone();
+    let benchmark = match blocking::run(move || drive_benchmark::run(&mount_point)).await {
+        Ok(Ok(benchmark)) => benchmark,
+        Ok(Err(message)) | Err(message) => return render_reelix_error(&message.into()),
+    };
+    let drive_stats = app_handle.state::<DriveStatsState>();
+    if let Err(message) =
+        drive_stats.record_benchmark(&app_handle, &disk.drive_key(), benchmark.bytes_per_second())
+    {
+        return render_reelix_error(&message.into());
+    }
+    templates::drive_health::render_index(&drive_stats.all(), &makemkv_check::current(&app_handle))
+}
+
+/// The device to work on, the selected disc's drive unless one is given. An open tray
+/// has no disc the OS can see, so closing it needs the device from the caller.
+fn tray_device(state: &AppState, device: Option<String>) -> Result<String, String> {
+    if let Some(device) = device.filter(|device| !device.trim().is_empty()) {
+        return Ok(device);
+    }
+    match state.selected_disk() {
+        Some(optical_disk) => {
+            let disk = optical_disk.read_or_recover();
+            if disk.is_remote() {
+                return Err(
+                    "The disc is in a remote drive, use the tray on that machine".to_string(),
+                );
+            }
+            Ok(disk.dev.clone())
+        }
+        None => Err("No Disk is Selected".to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn close_tray(
+    device: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, templates::Error> {
+    let result = tray_device(&state, device).and_then(|device| disk_manager::close_tray(&device));
+    match result {
+        Ok(()) => templates::toast::render_toast_append(Toast::success("Disc tray", "Tray closed")),
+        Err(message) => render_reelix_error(&message.into()),
+    }
+}
+
+#[tauri::command]
+pub fn lock_tray(locked: bool, state: State<'_, AppState>) -> Result<String, templates::Error> {
+    let result =
+        tray_device(&state, None).and_then(|device| disk_manager::lock_tray(&device, locked));
+    let message = if locked {
+        "Tray locked"
+    } else {
+        "Tray unlocked"
     };
+    match result {
+        Ok(()) => templates::toast::render_toast_append(Toast::success("Disc tray", message)),
+        Err(message) => render_reelix_error(&message.into()),
+    }
+}
+
+/// Shows whether the tray is `open`, `closed` or `unknown`
+#[tauri::command]
+pub fn tray_state(
+    device: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, templates::Error> {
+    match tray_device(&state, device) {
+        Ok(device) => templates::toast::render_toast_append(Toast::info(
+            "Disc tray",
+            format!("The tray is {}", disk_manager::tray_state(&device).as_str()),
+        )),
+        Err(message) => render_reelix_error(&message.into()),
+    }
+}
+
+#[tauri::command]
+pub fn drive_health(app_handle: tauri::AppHandle) -> Result<String, templates::Error> {
+    templates::drive_health::render_index(
+        &app_handle.state::<DriveStatsState>().all(),
+        &makemkv_check::current(&app_handle),
+    )
+}
 
-    templates::disk_titles::render_options(&state, &background_process_state)
+/// The movies and TV shows folders of the main library and of every profile, each once.
+fn library_dirs(state: &AppState) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut movies_dirs = Vec::new();
+    let mut tv_shows_dirs = Vec::new();
+    let profiles = state.library_profiles();
+    let libraries = std::iter::once(state.library(None)).chain(
+        profiles
+            .iter()
+            .map(|profile| state.library(Some(&profile.name))),
+    );
+    for library in libraries {
+        if !movies_dirs.contains(&library.movies_dir) {
+            movies_dirs.push(library.movies_dir);
+        }
+        if !tv_shows_dirs.contains(&library.tv_shows_dir) {
+            tv_shows_dirs.push(library.tv_shows_dir);
+        }
+    }
+    (movies_dirs, tv_shows_dirs)
+}
+
+#[tauri::command]
+pub async fn audit_library(state: State<'_, AppState>) -> Result<String, templates::Error> {
+    let (movies_dirs, tv_shows_dirs) = library_dirs(&state);
+    match blocking::run(move || library_audit::audit(&movies_dirs, &tv_shows_dirs)).await {
+        Ok(audit) => templates::library_audit::render_index(&audit),
+        Err(message) => render_reelix_error(&message.into()),
+    }
+}
+
+#[tauri::command]
+pub async fn fix_library_issues(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let (movies_dirs, tv_shows_dirs) = library_dirs(&state);
+    // Audited again, the library may have changed since the page was shown
+    let result = blocking::run(move || {
+        let (fixed, errors) =
+            library_audit::fix(&library_audit::audit(&movies_dirs, &tv_shows_dirs).issues);
+        (
+            fixed,
+            errors,
+            library_audit::audit(&movies_dirs, &tv_shows_dirs),
+        )
+    })
+    .await;
+    let (fixed, errors, audit) = match result {
+        Ok(result) => result,
+        Err(message) => return render_reelix_error(&message.into()),
+    };
+    let toast = if errors.is_empty() {
+        Toast::success("Library fixed", format!("Fixed {fixed} issues"))
+    } else {
+        Toast::danger(
+            format!("Fixed {fixed} issues, {} failed", errors.len()),
+            errors.join("; "),
+        )
+    };
+    notifications::emit_toast(&app_handle, toast);
+    templates::library_audit::render_index(&audit)
 }
 
 #[cfg(test)]
@@ -1,26 +1,63 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-use crate::services::auto_complete;
+use crate::reelix_error::ReelixError;
+use crate::services::deep_link::DeepLink;
 use crate::services::plex::{
-    find_movie, find_season, find_tv, get_movie_certification, search_multi,
+    find_movie, find_person, find_season, find_tv, get_movie_certification, get_tv_certification,
+    search_multi,
+};
+use crate::services::{
+    auto_complete, blocking, ocr, owned_collection, rip_plan, search_suggest, tmdb_cache, ui_state,
 };
-use crate::state::background_process_state::BackgroundProcessState;
 use crate::state::AppState;
-use crate::templates::{self, render_error};
-use crate::the_movie_db;
+use crate::templates::{self, render_reelix_error};
+use log::debug;
+use std::path::PathBuf;
 use tauri::State;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_opener::OpenerExt;
 
 // This is the entry point, basically it decides what to first show the user
 #[tauri::command]
-pub fn index(
+pub async fn index(
     app_handle: tauri::AppHandle,
     app_state: State<'_, AppState>,
 ) -> Result<String, templates::Error> {
-    match search_multi(&app_state, "Martian") {
+    match search_multi(&app_handle, "Martian").await {
         Ok(resp) => resp,
         Err(e) => return templates::the_movie_db::render_index(&app_state, &e.message),
     };
-    templates::search::render_index(&app_handle)
+    // Reelix was started by a reelix:// link, that page is shown instead
+    let startup_link = app_handle
+        .deep_link()
+        .get_current()
+        .ok()
+        .flatten()
+        .and_then(|urls| urls.last().cloned());
+    if let Some(url) = startup_link {
+        return open_deep_link(url.to_string(), app_handle, app_state).await;
+    }
+    templates::search::render_index(&app_handle).await
+}
+
+/// Opens the movie, show or season page of a `reelix://` link, see `deep_link`.
+#[tauri::command]
+pub async fn open_deep_link(
+    url: String,
+    app_handle: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+) -> Result<String, templates::Error> {
+    match DeepLink::parse(&url) {
+        Some(DeepLink::Movie(id)) => movie(id, app_state, app_handle).await,
+        Some(DeepLink::Tv(id)) => tv(id, app_handle, app_state).await,
+        Some(DeepLink::Season {
+            tv_id,
+            season_number,
+        }) => season(tv_id, season_number, app_handle, app_state).await,
+        None => render_reelix_error(&ReelixError::NotFound(format!(
+            "Reelix has no page for {url}"
+        ))),
+    }
 }
 
 #[tauri::command]
@@ -29,80 +66,137 @@ pub fn open_url(url: &str, app_handle: tauri::AppHandle) -> Result<String, templ
 
     match response {
         Ok(_r) => Ok("".to_string()),
-        Err(e) => render_error(&format!("failed to open url: {e:?}")),
+        Err(e) => render_reelix_error(&format!("failed to open url: {e:?}").into()),
+    }
+}
+
+/// The UI state stored under `key` as JSON, `null` when it was never set. See `ui_state`.
+#[tauri::command]
+pub fn ui_state_get(key: String, app_handle: tauri::AppHandle) -> Result<String, templates::Error> {
+    if let Err(message) = ui_state::validate_key(&key) {
+        return render_reelix_error(&ReelixError::Validation(message));
+    }
+    match ui_state::get(&app_handle, &key) {
+        Ok(value) => Ok(value.to_string()),
+        Err(message) => render_reelix_error(&message.into()),
     }
 }
 
+/// Stores `value` under `key`, `null` deletes it.
 #[tauri::command]
-pub fn movie(
+pub fn ui_state_set(
+    key: String,
+    value: serde_json::Value,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    if let Err(message) =
+        ui_state::validate_key(&key).and_then(|_| ui_state::validate_value(&key, &value))
+    {
+        return render_reelix_error(&ReelixError::Validation(message));
+    }
+    match ui_state::set(&app_handle, &key, value) {
+        Ok(()) => Ok(String::new()),
+        Err(message) => render_reelix_error(&message.into()),
+    }
+}
+
+#[tauri::command]
+pub async fn movie(
     id: u32,
-    background_process_state: State<'_, BackgroundProcessState>,
     app_state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, templates::Error> {
-    let movie = match find_movie(&app_handle, id) {
+    let movie = match find_movie(&app_handle, id).await {
         Ok(resp) => resp,
         Err(e) => return templates::the_movie_db::render_index(&app_state, &e.message),
     };
 
-    let certification = match get_movie_certification(&app_handle, &id) {
+    let certification = match get_movie_certification(&app_handle, id).await {
         Ok(resp) => resp,
         Err(e) => return templates::the_movie_db::render_index(&app_state, &e.message),
     };
-    templates::movies::render_show(
-        &app_state,
-        &background_process_state,
-        &movie,
-        &certification,
-    )
+
+    let owned = owned_collection::load_or_empty(&app_handle).owns_movie(&movie);
+
+    // Checks the FTP server for an existing rip
+    blocking::run(move || {
+        templates::movies::render_show(&app_handle, &movie, &certification, owned)
+    })
+    .await
+    .unwrap_or_else(|e| render_reelix_error(&e.into()))
+}
+
+#[tauri::command]
+pub async fn tv(
+    id: u32,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, templates::Error> {
+    let tv = match find_tv(&app_handle, id).await {
+        Ok(resp) => resp,
+        Err(e) => return templates::the_movie_db::render_index(&state, &e.message),
+    };
+
+    // The show is still shown from the TMDB cache while offline, without its rating
+    let certification = get_tv_certification(&app_handle, id)
+        .await
+        .inspect_err(|e| debug!("No content rating for {id}: {}", e.message))
+        .ok()
+        .flatten();
+
+    templates::tvs::render_show(&tv, &certification)
 }
 
+/// An actor's or a director's movies and shows, the ones in the collection are marked
 #[tauri::command]
-pub fn tv(
+pub async fn person(
     id: u32,
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<String, templates::Error> {
-    let tv = match find_tv(&app_handle, id) {
+    let person = match find_person(&app_handle, id).await {
         Ok(resp) => resp,
         Err(e) => return templates::the_movie_db::render_index(&state, &e.message),
     };
 
-    templates::tvs::render_show(&tv)
+    templates::people::render_show(&person, &owned_collection::load_or_empty(&app_handle))
 }
 
 #[tauri::command]
-pub fn season(
+pub async fn season(
     tv_id: u32,
     season_number: u32,
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<String, templates::Error> {
-    let tv = match find_tv(&app_handle, tv_id) {
+    let tv = match find_tv(&app_handle, tv_id).await {
         Ok(resp) => resp,
         Err(e) => return templates::the_movie_db::render_index(&state, &e.message),
     };
 
-    let season = match find_season(&app_handle, tv_id, season_number) {
+    let season = match find_season(&app_handle, tv_id, season_number).await {
         Ok(resp) => resp,
         Err(e) => return templates::the_movie_db::render_index(&state, &e.message),
     };
 
-    templates::seasons::render_show(&app_handle, &tv, &season)
+    tmdb_cache::spawn_prefetch(&app_handle, &tv, &season);
+    rip_plan::select_season_drive(&app_handle, tv_id, season_number);
+
+    // Lists the season directory on the FTP server
+    blocking::run(move || templates::seasons::render_show(&app_handle, &tv, &season))
+        .await
+        .unwrap_or_else(|e| render_reelix_error(&e.into()))
 }
 
 #[tauri::command]
-pub fn search(
+pub async fn search(
     search: &str,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, templates::Error> {
     state.save_query(search);
 
-    let api_key = &state.lock_the_movie_db_key();
-    let language = "en-US";
-    let movie_db = the_movie_db::TheMovieDb::new(api_key, language);
-    let response = match movie_db.search_multi(search, 1) {
+    let response = match search_multi(&app_handle, search).await {
         Ok(resp) => resp,
         Err(e) => return templates::the_movie_db::render_index(&state, &e.message),
     };
@@ -110,6 +204,60 @@ pub fn search(
     templates::search::render_results(&app_handle, search, &response)
 }
 
+/// Search with the title read off a photo of the disc or case, for discs whose volume
+/// label is something useless like `DVD_VIDEO`. Without `image_path` the photo is picked
+/// with the system file picker, a cancelled picker changes nothing.
+#[tauri::command]
+pub async fn search_from_photo(
+    image_path: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let image_path = match image_path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let dialog_handle = app_handle.clone();
+            let picked = blocking::run(move || {
+                dialog_handle
+                    .dialog()
+                    .file()
+                    .add_filter("Photo", &["jpg", "jpeg", "png", "tif", "tiff", "bmp"])
+                    .blocking_pick_file()
+            })
+            .await;
+            match picked {
+                Ok(Some(file)) => match file.into_path() {
+                    Ok(path) => path,
+                    Err(e) => {
+                        return render_reelix_error(
+                            &format!("Failed to read the picked photo: {e}").into(),
+                        )
+                    }
+                },
+                Ok(None) => return Ok(String::new()),
+                Err(message) => return render_reelix_error(&message.into()),
+            }
+        }
+    };
+    let text = match ocr::read_text(&app_handle, &image_path).await {
+        Ok(text) => text,
+        Err(message) => return render_reelix_error(&message.into()),
+    };
+    let Some(search) = ocr::title_candidate(&text) else {
+        return render_reelix_error(&ReelixError::Validation(
+            "Couldn't read a title in the photo, try one closer to the title text".to_string(),
+        ));
+    };
+    state.save_query(&search);
+
+    let response = match search_multi(&app_handle, &search).await {
+        Ok(resp) => resp,
+        Err(e) => return templates::the_movie_db::render_index(&state, &e.message),
+    };
+
+    templates::search::render_results(&app_handle, &search, &response)
+}
+
 #[tauri::command]
 pub async fn suggestion(search: &str) -> Result<String, templates::Error> {
     use tokio::time::{timeout, Duration};
@@ -132,6 +280,19 @@ pub async fn suggestion(search: &str) -> Result<String, templates::Error> {
     templates::search::render_suggestion(search, &suggestion_opt).await
 }
 
+/// The titles under the search box, called on every key. A call that a newer one
+/// superseded returns nothing so the box keeps what the newer call shows.
+#[tauri::command]
+pub async fn search_suggest(
+    query: &str,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    match search_suggest::suggest(&app_handle, query).await {
+        Some(results) => templates::search::render_suggest(&results),
+        None => Ok(String::new()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
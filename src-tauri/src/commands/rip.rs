@@ -1,51 +1,91 @@
 use crate::models::optical_disk_info::DiskId;
+use crate::models::title_info::TitleId;
+use crate::reelix_error::ReelixError;
+use crate::services::assignment_patterns::{self, AssignmentPattern};
+use crate::services::disc_memory::{self, DiscContent};
 use crate::services::ftp_uploader;
+use crate::services::notifications::{
+    self, notify, notify_with_actions, NotificationActions, NotificationEvent, NotificationKind,
+};
 use crate::services::plex::find_tv;
-use crate::services::{self, disk_manager};
+use crate::services::rip_failure::{self, RipFailure};
+use crate::services::title_analysis::RuntimeConflict;
+use crate::services::{self, arr, blocking, disk_manager, file_move, job_notes, metrics, network};
 use crate::services::{
     makemkvcon,
     plex::{find_movie, find_season},
+    post_process, push, resource_monitor, rip_plan, trakt,
+    upload_review::{self, ReviewOutcome},
 };
 use crate::standard_error::StandardError;
 use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::drive_stats_state;
 use crate::state::job_state::{emit_progress, Job, JobStatus, JobType};
+use crate::state::locks::RwLockExt;
+use crate::state::resource_governor::Slot;
 use crate::state::title_video::{self, TitleVideo, Video};
 use crate::state::uploaded_state::UploadedState;
 use crate::state::{background_process_state, AppState};
 use crate::templates::toast::{Toast, ToastVariant};
 use crate::templates::{self};
+use crate::the_movie_db::{TvId, TvResponse};
 use log::{debug, error, warn};
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use tauri::{Emitter, Manager, State};
-use tauri_plugin_notification::NotificationExt;
-use templates::render_error;
+use tauri_plugin_opener::OpenerExt;
+use templates::render_reelix_error;
+
+/// The title of the errors makemkvcon itself returned while ripping
+const RIP_FAILURE_TITLE: &str = "Rip Failure";
+
+/// What an episode card sends when a title is picked for one of its parts, see
+/// episode_controller.js. `confirmed` skips the runtime check after the user was asked.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignEpisodeRequest {
+    pub mvdb_id: TvId,
+    pub season_number: u32,
+    pub episode_number: u32,
+    pub title_id: Option<TitleId>,
+    pub part: u16,
+    pub confirmed: Option<bool>,
+}
 
 #[tauri::command]
-pub fn assign_episode_to_title(
-    mvdb_id: u32,
-    season_number: u32,
-    episode_number: u32,
-    title_id: Option<u32>,
-    part: u16,
+pub async fn assign_episode_to_title(
+    request: AssignEpisodeRequest,
     background_process_state: State<'_, background_process_state::BackgroundProcessState>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, templates::Error> {
+    let AssignEpisodeRequest {
+        mvdb_id,
+        season_number,
+        episode_number,
+        title_id,
+        part,
+        confirmed,
+    } = request;
     let app_state = app_handle.state::<AppState>();
     let optical_disk = match app_state.selected_disk() {
         Some(disk) => disk,
-        None => return render_error("No current selected disk"),
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "No current selected disk".to_string(),
+            ))
+        }
     };
-    let tv = match find_tv(&app_handle, mvdb_id) {
+    let tv = match find_tv(&app_handle, mvdb_id.into()).await {
         Ok(tv) => tv,
-        Err(e) => return render_error(&e.message),
+        Err(e) => return render_reelix_error(&e.into()),
     };
 
-    let season = match find_season(&app_handle, mvdb_id, season_number) {
+    let season = match find_season(&app_handle, mvdb_id.into(), season_number).await {
         Ok(season) => season,
-        Err(e) => return render_error(&e.message),
+        Err(e) => return render_reelix_error(&e.into()),
     };
 
     let episode = match season
@@ -54,9 +94,13 @@ pub fn assign_episode_to_title(
         .find(|e| e.episode_number == episode_number)
     {
         Some(episode) => episode,
-        None => return templates::render_error("Could not find episode to assign"),
+        None => {
+            return templates::render_reelix_error(&ReelixError::NotFound(
+                "Could not find episode to assign".to_string(),
+            ))
+        }
     };
-    let disk_id = optical_disk.read().expect("failed to lock optical_disk").id;
+    let disk_id = optical_disk.read_or_recover().id;
     let job = match background_process_state.find_job(
         Some(disk_id),
         &Some(JobType::Ripping),
@@ -64,7 +108,7 @@ pub fn assign_episode_to_title(
     ) {
         Some(job) => job,
         None => {
-            let optical_disk_info = optical_disk.read().unwrap().clone();
+            let optical_disk_info = optical_disk.read_or_recover().clone();
             background_process_state.new_job(
                 JobType::Ripping,
                 JobStatus::Pending,
@@ -73,23 +117,31 @@ pub fn assign_episode_to_title(
         }
     };
     let title_video = job
-        .read()
-        .unwrap()
+        .read_or_recover()
         .find_tv_title_video(tv.id, season.id, episode.id, part);
     if let Some(title_id) = title_id {
-        let title = match optical_disk.read().unwrap().find_title_by_id(title_id) {
+        let title = match optical_disk.read_or_recover().find_title_by_id(title_id) {
             Some(title) => title,
             None => {
-                return templates::render_error(
-                    "Failed to find Title on Optical Disk to Assign to Episode",
-                );
+                return templates::render_reelix_error(&ReelixError::NotFound(
+                    "Failed to find Title on Optical Disk to Assign to Episode".to_string(),
+                ));
             }
         };
+        // The parts after the first of a split episode are short on purpose, they aren't checked
+        let conflict = (part == 1 && !confirmed.unwrap_or(false))
+            .then(|| {
+                RuntimeConflict::check(&title, episode.runtime.map(|_| episode.runtime_range()))
+            })
+            .flatten();
+        if let Some(conflict) = conflict {
+            debug!("Asking before assigning: {}", conflict.message());
+            return templates::seasons::render_runtime_conflict(episode, part, &conflict);
+        }
         match title_video {
             Some(title_vid) => {
                 title_vid
-                    .write()
-                    .unwrap()
+                    .write_or_recover()
                     .update_video(Video::Tv(Box::new(title_video::TvSeasonEpisode {
                         tv: tv.clone(),
                         season: season.clone(),
@@ -107,95 +159,135 @@ pub fn assign_episode_to_title(
                     part,
                 }));
 
-                let _ = job
-                    .write()
-                    .expect("Failed to lock job for write")
+                if let Err(e) = job
+                    .write_or_recover()
                     .add_title_video(title, tv_season_episode)
-                    .map_err(|e| {
-                        templates::render_error(&format!(
-                            "Failed to assign episode to title: {}",
-                            e.message
-                        ))
-                        .unwrap_err()
-                    });
+                {
+                    return render_reelix_error(&e.into());
+                }
             }
         };
     } else if let Some(title_video) = title_video {
-        let removed = job
-            .write()
-            .expect("Failed to lock job for write")
-            .remove_title_video(title_video.read().unwrap().title.as_ref().unwrap());
+        let Some(title) = title_video.read_or_recover().title.clone() else {
+            return render_reelix_error(&ReelixError::Validation(
+                "The assigned episode has no disc title to remove".to_string(),
+            ));
+        };
+        let removed = job.write_or_recover().remove_title_video(&title);
         match removed {
             Ok(_) => {
-                let no_titles = job.read().unwrap().title_videos.is_empty();
+                let no_titles = job.read_or_recover().title_videos.is_empty();
                 if no_titles {
                     // remove the job entirely if there are no more title videos, since a job with no title videos doesn't make sense and would just be confusing to show in the UI
-                    let job_id = job.read().expect("Failed to lock job for read").id;
+                    let job_id = job.read_or_recover().id;
                     background_process_state.delete_job(job_id);
                 }
             }
             Err(e) => {
-                return templates::render_error(&format!(
-                    "Failed to withdraw episode from title: {}",
-                    e.message
-                ));
+                return templates::render_reelix_error(
+                    &format!("Failed to withdraw episode from title: {}", e.message).into(),
+                );
             }
         }
     } else {
-        return templates::render_error("Unclear error: no title video found for episode to withdraw, and no title specified to assign");
+        return templates::render_reelix_error(&"Unclear error: no title video found for episode to withdraw, and no title specified to assign".into());
+    }
+
+    background_process_state.emit_jobs_changed(&app_handle);
+
+    blocking::run(move || templates::seasons::render_title_selected(&app_handle, &tv, season))
+        .await
+        .unwrap_or_else(|e| render_reelix_error(&e.into()))
+}
+
+/// Assigns the disc titles suggested by the previous disc of the season, see `assignment_patterns`.
+#[tauri::command]
+pub async fn apply_assignment_suggestions(
+    mvdb_id: TvId,
+    season_number: u32,
+    background_process_state: State<'_, background_process_state::BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let app_state = app_handle.state::<AppState>();
+    let optical_disk = match app_state.selected_disk() {
+        Some(disk) => disk,
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "No current selected disk".to_string(),
+            ))
+        }
+    };
+    let tv = match find_tv(&app_handle, mvdb_id.into()).await {
+        Ok(tv) => tv,
+        Err(e) => return render_reelix_error(&e.into()),
+    };
+    let season = match find_season(&app_handle, mvdb_id.into(), season_number).await {
+        Ok(season) => season,
+        Err(e) => return render_reelix_error(&e.into()),
+    };
+    let owned = {
+        let tv = tv.clone();
+        let season = season.clone();
+        let owned_handle = app_handle.clone();
+        blocking::run(move || {
+            ftp_uploader::owned_episode_numbers(&tv, &season, &owned_handle.state::<AppState>())
+        })
+        .await
+        .unwrap_or_default()
+    };
+    let disk = optical_disk.read_or_recover().clone();
+    let job = background_process_state.find_job(
+        Some(disk.id),
+        &Some(JobType::Ripping),
+        &[JobStatus::Pending],
+    );
+    let suggestions = assignment_patterns::suggestions(
+        &app_state.lock_assignment_patterns(),
+        tv.id,
+        &season,
+        &disk,
+        job.as_ref()
+            .map(|job| job.read_or_recover().clone())
+            .as_ref(),
+        &owned,
+    );
+    if suggestions.is_empty() {
+        return render_reelix_error(&ReelixError::NotFound(
+            "There are no suggested titles for this disc".to_string(),
+        ));
+    }
+    let job = job.unwrap_or_else(|| {
+        background_process_state.new_job(JobType::Ripping, JobStatus::Pending, Some(disk.clone()))
+    });
+    for (episode_number, title_id) in suggestions {
+        let (Some(episode), Some(title)) = (
+            season
+                .episodes
+                .iter()
+                .find(|episode| episode.episode_number == episode_number),
+            disk.find_title_by_id(title_id),
+        ) else {
+            continue;
+        };
+        let video = Video::Tv(Box::new(title_video::TvSeasonEpisode {
+            tv: tv.clone(),
+            season: season.clone(),
+            episode: episode.clone(),
+            part: 1,
+        }));
+        if let Err(e) = job.write_or_recover().add_title_video(title, video) {
+            return render_reelix_error(
+                &format!("Failed to assign episode to title: {}", e.message).into(),
+            );
+        }
     }
 
     background_process_state.emit_jobs_changed(&app_handle);
 
-    templates::seasons::render_title_selected(&app_handle, &tv, season)
-}
-
-// pub fn withdraw_episode_from_title(
-//     mvdb_id: u32,
-//     season_number: u32,
-//     episode_number: u32,
-//     title_id: u32,
-//     part: Option<u16>,
-//     app_state: State<'_, AppState>,
-//     app_handle: tauri::AppHandle,
-// ) -> Result<String, templates::Error> {
-//     let optical_disk = match app_state.selected_disk() {
-//         Some(d) => d,
-//         None => return render_error("No current selected disk"),
-//     };
-
-//     let season = match find_season(&app_handle, mvdb_id, season_number) {
-//         Ok(season) => season,
-//         Err(e) => return render_error(&e.message),
-//     };
-
-//     let tv = match find_tv(&app_handle, mvdb_id) {
-//         Ok(tv) => tv,
-//         Err(e) => return render_error(&e.message),
-//     };
-
-//     let job = find_or_create_job(&app_handle, &optical_disk.read().unwrap());
-//     let title_video = job.read().unwrap().find_tv_title_video(
-//         mvdb_id,
-//         season_number,
-//         episode_number,
-//         title_id,
-//         Some(part),
-//     );
-//     match title_video {
-//         Some(title_video) => {
-//             job.write()
-//                 .expect("Failed to lock job for write")
-//                 .title_videos
-//                 .retain(|tv| !Arc::ptr_eq(tv, &title_video));
-//         }
-//         None => {
-//             return render_error("Failed to find Episode to Withdraw from Title");
-//         }
-//     }
-
-//     templates::seasons::render_title_selected(&app_handle, &tv, season)
-// }
+    blocking::run(move || templates::seasons::render_title_selected(&app_handle, &tv, season))
+        .await
+        .unwrap_or_else(|e| render_reelix_error(&e.into()))
+}
 
 #[derive(Deserialize)]
 pub struct EpisodeSwap {
@@ -204,21 +296,20 @@ pub struct EpisodeSwap {
 }
 
 #[tauri::command]
-pub fn reorder_tv_episodes_on_ftp(
-    mvdb_id: u32,
+pub async fn reorder_tv_episodes_on_ftp(
+    mvdb_id: TvId,
     season_number: u32,
     swaps: Vec<EpisodeSwap>,
-    app_state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, templates::Error> {
-    let tv = match find_tv(&app_handle, mvdb_id) {
+    let tv = match find_tv(&app_handle, mvdb_id.into()).await {
         Ok(tv) => tv,
-        Err(e) => return render_error(&e.message),
+        Err(e) => return render_reelix_error(&e.into()),
     };
 
-    let season = match find_season(&app_handle, mvdb_id, season_number) {
+    let season = match find_season(&app_handle, mvdb_id.into(), season_number).await {
         Ok(season) => season,
-        Err(e) => return render_error(&e.message),
+        Err(e) => return render_reelix_error(&e.into()),
     };
 
     let filtered_swaps: Vec<(u32, u32)> = swaps
@@ -241,51 +332,73 @@ pub fn reorder_tv_episodes_on_ftp(
     let mut unique_targets = std::collections::HashSet::new();
     for (from, to) in &filtered_swaps {
         if !unique_sources.insert(*from) {
-            return render_error(&format!("Episode {from} is listed more than once"));
+            return render_reelix_error(&ReelixError::Validation(format!(
+                "Episode {from} is listed more than once"
+            )));
         }
         if !unique_targets.insert(*to) {
-            return render_error(&format!("Episode {to} is targeted more than once"));
+            return render_reelix_error(&ReelixError::Validation(format!(
+                "Episode {to} is targeted more than once"
+            )));
         }
     }
 
-    let renamed_count =
-        match ftp_uploader::reorder_tv_episode_files(&tv, &season, &filtered_swaps, &app_state) {
-            Ok(count) => count,
-            Err(message) => return render_error(&message),
-        };
+    blocking::run(move || {
+        let app_state = app_handle.state::<AppState>();
+        let renamed_count =
+            match ftp_uploader::reorder_tv_episode_files(&tv, &season, &filtered_swaps, &app_state)
+            {
+                Ok(count) => count,
+                Err(message) => return render_reelix_error(&ReelixError::Ftp(message)),
+            };
 
-    let toast = Toast::success(
-        "Episode files reordered",
-        format!("Renamed {renamed_count} file(s) on FTP."),
-    );
-    let toast_stream = templates::toast::render_toast_append(toast)?;
-    let season_stream = templates::seasons::render_show(&app_handle, &tv, &season)?;
+        let toast = Toast::success(
+            "Episode files reordered",
+            format!("Renamed {renamed_count} file(s) on FTP."),
+        );
+        let toast_stream = templates::toast::render_toast_append(toast)?;
+        let season_stream = templates::seasons::render_show(&app_handle, &tv, &season)?;
 
-    Ok(format!("{toast_stream}{season_stream}"))
+        Ok(format!("{toast_stream}{season_stream}"))
+    })
+    .await
+    .unwrap_or_else(|e| render_reelix_error(&e.into()))
 }
 
 #[tauri::command]
-pub fn rip_season(
+pub async fn rip_season(
+    library: Option<String>,
+    skip_upload: Option<bool>,
     app_handle: tauri::AppHandle,
     app_state: State<'_, AppState>,
 ) -> Result<String, templates::Error> {
+    let library = match library_profile_name(&app_state, library) {
+        Ok(library) => library,
+        Err(e) => return render_reelix_error(&e),
+    };
     let disk_id = app_state
         .selected_optical_disk_id
-        .read()
-        .unwrap()
+        .read_or_recover()
         .to_owned();
     let disk_id = match disk_id {
         Some(id) => id,
         None => {
             debug!("No optical disk is currently selected.");
-            return templates::render_error("No selected disk");
+            return templates::render_reelix_error(&ReelixError::NotFound(
+                "No selected disk".to_string(),
+            ));
         }
     };
 
     let optical_disk = match app_state.find_optical_disk_by_id(&disk_id) {
         Some(optical_disk) => optical_disk,
-        None => return render_error("Failed to find Optical Disk"),
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "Failed to find Optical Disk".to_string(),
+            ))
+        }
     };
+    let disc_name = optical_disk.read_or_recover().name.clone();
     let background_process_state = app_handle.state::<BackgroundProcessState>();
     let (job, is_new) = background_process_state.find_or_create_job(
         Some(disk_id),
@@ -298,61 +411,163 @@ pub fn rip_season(
         background_process_state.emit_jobs_changed(&app_handle);
     }
 
-    job.write()
-        .expect("Failed to get job writer")
-        .update_status(JobStatus::Processing);
+    job.read_or_recover().set_library(library);
+    job.read_or_recover()
+        .set_skip_upload(skip_upload.unwrap_or(false));
 
-    let season_update = {
-        let job_guard = job.read().expect("Failed to get job reader");
-        let tv_and_season = job_guard.title_videos.iter().find_map(|title_video| {
+    let tv_and_season = job
+        .read_or_recover()
+        .title_videos
+        .iter()
+        .find_map(|title_video| {
             let title_video_guard = title_video.read().ok()?;
             match &title_video_guard.video {
                 Video::Tv(tv_season_episode) => Some((
                     tv_season_episode.tv.clone(),
                     tv_season_episode.season.clone(),
                 )),
-                Video::Movie(_) => None,
+                Video::Movie(_) | Video::Personal(_) => None,
             }
         });
 
-        match tv_and_season {
-            Some((tv, season)) => templates::seasons::render_show(&app_handle, &tv, &season)?,
-            None => String::new(),
+    if let Some((tv, season)) = tv_and_season.clone() {
+        let season_number = season.season_number;
+        let owned_handle = app_handle.clone();
+        let owned = blocking::run(move || {
+            ftp_uploader::owned_episode_numbers(&tv, &season, &owned_handle.state::<AppState>())
+        })
+        .await
+        .unwrap_or_default();
+        let skipped = job
+            .write_or_recover()
+            .skip_owned_episodes(season_number, &owned);
+        if !skipped.is_empty() {
+            if job.read_or_recover().title_videos.is_empty() {
+                return render_reelix_error(&ReelixError::Validation(
+                    "Every assigned episode is already in the library".to_string(),
+                ));
+            }
+            job.write_or_recover().add_notice(format!(
+                "Skipped episodes already in the library: {}",
+                skipped.join(", ")
+            ));
         }
-    };
+    }
+    let problems = job.read_or_recover().season_assignment_problems();
+    if !problems.is_empty() {
+        return render_reelix_error(&ReelixError::Validation(format!(
+            "The assigned episodes don't match the disc titles: {}",
+            problems.join("; ")
+        )));
+    }
+    if let Some((tv, season)) = &tv_and_season {
+        let pattern = AssignmentPattern::learn(
+            &job.read_or_recover(),
+            tv.id,
+            season.season_number,
+            &disc_name,
+        );
+        if let Some(pattern) = pattern {
+            assignment_patterns::remember(&mut app_state.lock_assignment_patterns(), pattern);
+        }
+        disc_memory::remember(
+            &mut app_state.lock_disc_memories(),
+            &disc_name,
+            DiscContent::Tv {
+                tv_id: tv.id.into(),
+                name: tv.name.clone(),
+                season_number: season.season_number,
+            },
+        );
+        if let Err(e) = app_state.save(&app_handle) {
+            warn!("Failed to save the assignment patterns and disc memories: {e}");
+        }
+    }
+    job.write_or_recover().update_status(JobStatus::Processing);
+
+    spawn_rip(app_handle.clone(), job);
+    match tv_and_season {
+        Some((tv, season)) => {
+            blocking::run(move || templates::seasons::render_show(&app_handle, &tv, &season))
+                .await
+                .unwrap_or_else(|e| render_reelix_error(&e.into()))
+        }
+        None => Ok(String::new()),
+    }
+}
 
-    spawn_rip(app_handle, job);
-    Ok(season_update)
+/// What the rip button of a movie card sends, see rip_movie_controller.js
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RipMovieRequest {
+    pub disk_id: u32,
+    pub title_id: TitleId,
+    pub mvdb_id: u32,
+    pub part: Option<u16>,
+    pub edition: Option<String>,
+    pub library: Option<String>,
+    pub join_title_id: Option<TitleId>,
+    pub skip_upload: Option<bool>,
 }
 
 #[tauri::command]
-#[allow(clippy::too_many_arguments)]
-pub fn rip_movie(
-    disk_id: u32,
-    title_id: u32,
-    mvdb_id: u32,
-    part: Option<u16>,
-    edition: Option<String>,
+pub async fn rip_movie(
+    request: RipMovieRequest,
     app_state: State<'_, AppState>,
     background_process_state: State<'_, background_process_state::BackgroundProcessState>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, templates::Error> {
+    let RipMovieRequest {
+        disk_id,
+        title_id,
+        mvdb_id,
+        part,
+        edition,
+        library,
+        join_title_id,
+        skip_upload,
+    } = request;
+    let library = match library_profile_name(&app_state, library) {
+        Ok(library) => library,
+        Err(e) => return render_reelix_error(&e),
+    };
     let disk_id = DiskId::from(disk_id);
     let optical_disk = match app_state.find_optical_disk_by_id(&disk_id) {
         Some(optical_disk) => optical_disk,
-        None => return render_error("Failed to find Optical Disk"),
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "Failed to find Optical Disk".to_string(),
+            ))
+        }
     };
 
-    let title_info = match optical_disk.read().unwrap().find_title_by_id(title_id) {
+    let title_info = match optical_disk.read_or_recover().find_title_by_id(title_id) {
         Some(title) => title,
         None => {
-            return render_error("Failed to find Title on Optical Disk to Rip");
+            return render_reelix_error(&ReelixError::NotFound(
+                "Failed to find Title on Optical Disk to Rip".to_string(),
+            ));
         }
     };
+    // Some discs split the movie into two titles, the second is appended to the first
+    let joined_title = match join_title_id.filter(|join_title_id| *join_title_id != title_id) {
+        Some(join_title_id) => match optical_disk
+            .read_or_recover()
+            .find_title_by_id(join_title_id)
+        {
+            Some(title) => Some(title),
+            None => {
+                return render_reelix_error(&ReelixError::NotFound(format!(
+                    "Failed to find title {join_title_id} to join"
+                )))
+            }
+        },
+        None => None,
+    };
 
     let (job, is_new) = background_process_state.find_or_create_job(
         Some(disk_id),
-        &Some(optical_disk),
+        &Some(optical_disk.clone()),
         &JobType::Ripping,
         &JobStatus::Pending,
     );
@@ -361,9 +576,9 @@ pub fn rip_movie(
         background_process_state.emit_jobs_changed(&app_handle);
     }
 
-    let movie = match find_movie(&app_handle, mvdb_id) {
+    let movie = match find_movie(&app_handle, mvdb_id).await {
         Ok(movie) => movie,
-        Err(e) => return render_error(&e.message),
+        Err(e) => return render_reelix_error(&e.into()),
     };
 
     let movie_part_edition = crate::state::title_video::MoviePartEdition {
@@ -373,24 +588,164 @@ pub fn rip_movie(
     };
 
     match job
-        .write()
-        .expect("Failed to lock job")
+        .write_or_recover()
         .add_title_video(title_info, Video::Movie(Box::new(movie_part_edition)))
     {
         Ok(_) => {}
         Err(e) => {
-            return render_error(&e.message);
+            return render_reelix_error(&e.into());
         }
     };
-    job.read()
-        .expect("Failed to lock job for read")
-        .emit_progress_change(&app_handle);
+    if let Some(joined_title) = joined_title {
+        if let Some(title_video) = job.read_or_recover().title_videos.last() {
+            title_video
+                .write_or_recover()
+                .joined_titles
+                .push(joined_title);
+        }
+    }
+    job.read_or_recover().set_library(library);
+    job.read_or_recover()
+        .set_skip_upload(skip_upload.unwrap_or(false));
+    job.read_or_recover().emit_progress_change(&app_handle);
+    let disc_name = optical_disk.read_or_recover().name.clone();
+    disc_memory::remember(
+        &mut app_state.lock_disc_memories(),
+        &disc_name,
+        DiscContent::Movie,
+    );
+    if let Err(e) = app_state.save(&app_handle) {
+        warn!("Failed to save the disc memories: {e}");
+    }
     spawn_rip(app_handle, job);
     Ok("".to_string())
 }
 
+/// The form for ripping the selected disc as a home video with `rip_personal`.
+#[tauri::command]
+pub fn home_video(app_handle: tauri::AppHandle) -> Result<String, templates::Error> {
+    templates::home_video::render_index(&app_handle)
+}
+
+/// The home video form, see templates/home_video/index.html
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RipPersonalRequest {
+    pub disk_id: u32,
+    pub title_id: TitleId,
+    pub name: String,
+    pub date: Option<String>,
+    pub part: Option<u16>,
+    pub library: Option<String>,
+    pub skip_upload: Option<bool>,
+}
+
+/// Rips a home-burned disc under a name typed in by hand, nothing is looked up on TMDB.
+#[tauri::command]
+pub async fn rip_personal(
+    request: RipPersonalRequest,
+    app_state: State<'_, AppState>,
+    background_process_state: State<'_, background_process_state::BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let RipPersonalRequest {
+        disk_id,
+        title_id,
+        name,
+        date,
+        part,
+        library,
+        skip_upload,
+    } = request;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return render_reelix_error(&ReelixError::Validation(
+            "Home videos need a name".to_string(),
+        ));
+    }
+    let library = match library_profile_name(&app_state, library) {
+        Ok(library) => library,
+        Err(e) => return render_reelix_error(&e),
+    };
+    let disk_id = DiskId::from(disk_id);
+    let optical_disk = match app_state.find_optical_disk_by_id(&disk_id) {
+        Some(optical_disk) => optical_disk,
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "Failed to find Optical Disk".to_string(),
+            ))
+        }
+    };
+
+    let title_info = match optical_disk.read_or_recover().find_title_by_id(title_id) {
+        Some(title) => title,
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "Failed to find Title on Optical Disk to Rip".to_string(),
+            ));
+        }
+    };
+
+    let (job, is_new) = background_process_state.find_or_create_job(
+        Some(disk_id),
+        &Some(optical_disk),
+        &JobType::Ripping,
+        &JobStatus::Pending,
+    );
+
+    if is_new {
+        background_process_state.emit_jobs_changed(&app_handle);
+    }
+
+    let toast = Toast::success("Home video", format!("Ripping {name}"));
+    let personal = title_video::PersonalVideo {
+        name,
+        date: date
+            .map(|date| date.trim().to_string())
+            .filter(|date| !date.is_empty()),
+        part,
+    };
+
+    if let Err(e) = job
+        .write_or_recover()
+        .add_title_video(title_info, Video::Personal(Box::new(personal)))
+    {
+        return render_reelix_error(&e.into());
+    };
+    job.read_or_recover().set_library(library);
+    job.read_or_recover()
+        .set_skip_upload(skip_upload.unwrap_or(false));
+    job.read_or_recover().emit_progress_change(&app_handle);
+    spawn_rip(app_handle, job);
+    templates::toast::render_toast_append(toast)
+}
+
+/// The library profile picked for a rip, blank is the main library.
+fn library_profile_name(
+    app_state: &AppState,
+    library: Option<String>,
+) -> Result<Option<String>, ReelixError> {
+    let Some(library) = library
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+    else {
+        return Ok(None);
+    };
+    if app_state
+        .library_profiles()
+        .iter()
+        .any(|profile| profile.name == library)
+    {
+        Ok(Some(library))
+    } else {
+        Err(ReelixError::Validation(format!(
+            "There is no library profile named {library}"
+        )))
+    }
+}
+
 #[tauri::command]
-pub fn set_auto_rip(
+pub async fn set_auto_rip(
     disk_id: u32,
     mvdb_id: u32,
     enable: bool,
@@ -403,7 +758,11 @@ pub fn set_auto_rip(
     if enable {
         let optical_disk = match app_state.find_optical_disk_by_id(&disk_id) {
             Some(optical_disk) => optical_disk,
-            None => return render_error("Failed to find Optical Disk"),
+            None => {
+                return render_reelix_error(&ReelixError::NotFound(
+                    "Failed to find Optical Disk".to_string(),
+                ))
+            }
         };
 
         let (job, is_new) = background_process_state.find_or_create_job(
@@ -414,17 +773,17 @@ pub fn set_auto_rip(
         );
 
         if is_new {
-            debug!("Created new job for auto-rip: {}", job.read().unwrap().id);
+            debug!("Created new job for auto-rip: {}", job.read_or_recover().id);
         } else {
             debug!(
                 "Found existing job for auto-rip: {}",
-                job.read().unwrap().id
+                job.read_or_recover().id
             );
         }
 
-        let movie = match find_movie(&app_handle, mvdb_id) {
+        let movie = match find_movie(&app_handle, mvdb_id).await {
             Ok(movie) => movie,
-            Err(e) => return render_error(&e.message),
+            Err(e) => return render_reelix_error(&e.into()),
         };
 
         let movie_part_edition = crate::state::title_video::MoviePartEdition {
@@ -434,13 +793,12 @@ pub fn set_auto_rip(
         };
 
         match job
-            .write()
-            .expect("Failed to lock job")
+            .write_or_recover()
             .add_incomplete_video(Video::Movie(Box::new(movie_part_edition)))
         {
             Ok(_) => {}
             Err(e) => {
-                return render_error(&e.message);
+                return render_reelix_error(&e.into());
             }
         };
         background_process_state.emit_jobs_changed(&app_handle);
@@ -449,7 +807,7 @@ pub fn set_auto_rip(
         &Some(JobType::Ripping),
         &[JobStatus::Pending],
     ) {
-        let job_id = job.read().expect("Failed to lock job for read").id;
+        let job_id = job.read_or_recover().id;
         background_process_state.delete_job(job_id);
         debug!("Deleted job {job_id} for auto-rip disable");
         background_process_state.emit_jobs_changed(&app_handle);
@@ -460,60 +818,263 @@ pub fn set_auto_rip(
     templates::movies::render_cards(&app_handle)
 }
 
+#[tauri::command]
+pub fn rip_plan(app_state: State<'_, AppState>) -> Result<String, templates::Error> {
+    templates::rip_plan::render_index(&app_state)
+}
+
+#[tauri::command]
+pub async fn add_movie_to_rip_plan(
+    mvdb_id: u32,
+    app_state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let movie = match find_movie(&app_handle, mvdb_id).await {
+        Ok(movie) => movie,
+        Err(e) => return render_reelix_error(&e.into()),
+    };
+    add_to_rip_plan(
+        &app_state,
+        &app_handle,
+        rip_plan::PlanKind::Movie,
+        mvdb_id,
+        None,
+        movie.title_year(),
+    )
+}
+
+#[tauri::command]
+pub async fn add_season_to_rip_plan(
+    mvdb_id: TvId,
+    season_number: u32,
+    app_state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let tv = match find_tv(&app_handle, mvdb_id.into()).await {
+        Ok(tv) => tv,
+        Err(e) => return render_reelix_error(&e.into()),
+    };
+    add_to_rip_plan(
+        &app_state,
+        &app_handle,
+        rip_plan::PlanKind::Season,
+        mvdb_id.into(),
+        Some(season_number),
+        season_plan_title(&tv, season_number),
+    )
+}
+
+fn season_plan_title(tv: &TvResponse, season_number: u32) -> String {
+    if season_number == 0 {
+        format!("{} Specials", tv.title_year())
+    } else {
+        format!("{} Season {season_number}", tv.title_year())
+    }
+}
+
+fn add_to_rip_plan(
+    app_state: &AppState,
+    app_handle: &tauri::AppHandle,
+    kind: rip_plan::PlanKind,
+    mvdb_id: u32,
+    season_number: Option<u32>,
+    title: String,
+) -> Result<String, templates::Error> {
+    let added = app_state
+        .lock_rip_plan()
+        .add(kind, mvdb_id, season_number, title.clone());
+    if let Err(message) = added {
+        return render_reelix_error(&ReelixError::Validation(message));
+    }
+    if let Err(message) = app_state.save(app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    templates::toast::render_toast_append(Toast::success(
+        "Rip Plan",
+        format!("Added {title}, insert its disc whenever you are ready"),
+    ))
+}
+
+#[tauri::command]
+pub fn remove_rip_plan_item(
+    id: u64,
+    app_state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    if !app_state.lock_rip_plan().remove(id) {
+        return render_reelix_error(&ReelixError::NotFound(
+            "That item is not in the rip plan anymore".to_string(),
+        ));
+    }
+    save_rip_plan(&app_state, &app_handle)
+}
+
+#[tauri::command]
+pub fn complete_rip_plan_item(
+    id: u64,
+    app_state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    if !app_state
+        .lock_rip_plan()
+        .set_status(id, rip_plan::PlanStatus::Done)
+    {
+        return render_reelix_error(&ReelixError::NotFound(
+            "That item is not in the rip plan anymore".to_string(),
+        ));
+    }
+    save_rip_plan(&app_state, &app_handle)
+}
+
+/// Pins a planned season to a drive, `first_disc` goes back to pinning it to the drive of its
+/// next disc and neither accepts any drive.
+#[tauri::command]
+pub fn set_rip_plan_drive(
+    id: u64,
+    drive: Option<String>,
+    first_disc: Option<bool>,
+    app_state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let affinity = match (drive, first_disc) {
+        (_, Some(true)) => rip_plan::DriveAffinity::FirstDisc,
+        (Some(drive), _) if !drive.is_empty() => rip_plan::DriveAffinity::Drive(drive),
+        _ => rip_plan::DriveAffinity::Any,
+    };
+    if !app_state.lock_rip_plan().set_drive(id, affinity) {
+        return render_reelix_error(&ReelixError::NotFound(
+            "That item is not in the rip plan anymore".to_string(),
+        ));
+    }
+    save_rip_plan(&app_state, &app_handle)
+}
+
+#[tauri::command]
+pub fn clear_finished_rip_plan(
+    app_state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    app_state.lock_rip_plan().clear_done();
+    save_rip_plan(&app_state, &app_handle)
+}
+
+fn save_rip_plan(
+    app_state: &AppState,
+    app_handle: &tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    if let Err(message) = app_state.save(app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    templates::rip_plan::render_index(app_state)
+}
+
 fn emit_render_cards(app_handle: &tauri::AppHandle) {
-    let result =
-        templates::movies::render_cards(app_handle).expect("Failed to render movies/cards.html");
-    app_handle
-        .emit("disks-changed", result)
-        .expect("Failed to emit disks-changed");
+    match templates::movies::render_cards(app_handle) {
+        Ok(result) => {
+            if let Err(e) = app_handle.emit("disks-changed", result) {
+                error!("Failed to emit disks-changed: {e}");
+            }
+        }
+        Err(e) => error!("Failed to render movies/cards.html: {e}"),
+    }
 }
 
 fn notify_movie_success(
     app_handle: &tauri::AppHandle,
     movie: &crate::state::title_video::MoviePartEdition,
 ) {
-    app_handle
-        .notification()
-        .builder()
-        .title(format!("Finished Ripping {}", movie.movie.title))
-        .body(movie.movie.title_year())
-        .show()
-        .unwrap();
+    notify(
+        app_handle,
+        NotificationKind::Rip,
+        NotificationEvent::Finish,
+        format!("Finished Ripping {}", movie.movie.title),
+        movie.movie.title_year(),
+    );
+}
+
+fn notify_personal_success(
+    app_handle: &tauri::AppHandle,
+    personal: &crate::state::title_video::PersonalVideo,
+) {
+    notify(
+        app_handle,
+        NotificationKind::Rip,
+        NotificationEvent::Finish,
+        format!("Finished Ripping {}", personal.name),
+        personal.title(),
+    );
 }
 
 fn notify_failure(app_handle: &tauri::AppHandle, error: &StandardError) {
-    app_handle
-        .notification()
-        .builder()
-        .title(error.title.clone())
-        .body(error.message.clone())
-        .show()
-        .unwrap();
+    notify_with_actions(
+        app_handle,
+        NotificationKind::Rip,
+        NotificationEvent::Error,
+        error.title.clone(),
+        error.message.clone(),
+        Some(NotificationActions::RipFailed),
+    );
+}
+
+fn notify_rip_started(app_handle: &tauri::AppHandle, job: &Arc<RwLock<Job>>) {
+    let title = {
+        let job_guard = job.read_or_recover();
+        job_guard
+            .title
+            .clone()
+            .or_else(|| job_guard.disk.as_ref().map(|disk| disk.name.clone()))
+            .unwrap_or_default()
+    };
+    notify(
+        app_handle,
+        NotificationKind::Rip,
+        NotificationEvent::Start,
+        "Started Ripping",
+        title,
+    );
+}
+
+fn notify_upload_started(app_handle: &tauri::AppHandle, file_path: &Path) {
+    notify(
+        app_handle,
+        NotificationKind::Upload,
+        NotificationEvent::Start,
+        "Started Upload",
+        format!("File Path {}", file_path.to_string_lossy()),
+    );
 }
 
 fn notify_movie_upload_success(app_handle: &tauri::AppHandle, file_path: &Path) {
-    app_handle
-        .notification()
-        .builder()
-        .title("Finished Upload Movie".to_string())
-        .body(format!("File Path {}", file_path.to_string_lossy()))
-        .show()
-        .unwrap();
+    notify(
+        app_handle,
+        NotificationKind::Upload,
+        NotificationEvent::Finish,
+        "Finished Upload Movie",
+        format!("File Path {}", file_path.to_string_lossy()),
+    );
 }
 
-fn notify_movie_upload_failure(app_handle: &tauri::AppHandle, file_path: &Path, error: &str) {
+fn notify_movie_upload_failure(
+    app_handle: &tauri::AppHandle,
+    job: &Arc<RwLock<Job>>,
+    file_path: &Path,
+    error: &str,
+) {
     debug!(
         "failed to upload: {} {}",
         file_path.to_string_lossy(),
         error
     );
-    app_handle
-        .notification()
-        .builder()
-        .title("Failed to Upload")
-        .body(format!("{} {}", file_path.to_string_lossy(), error))
-        .show()
-        .unwrap();
+    notify_with_actions(
+        app_handle,
+        NotificationKind::Upload,
+        NotificationEvent::Error,
+        "Failed to Upload",
+        format!("{} {}", file_path.to_string_lossy(), error),
+        Some(NotificationActions::UploadFailed {
+            job_id: job.read_or_recover().id,
+        }),
+    );
 }
 
 /// Extract upload preparation data from a title_video
@@ -521,49 +1082,34 @@ fn extract_upload_info(
     app_handle: &tauri::AppHandle,
     title_video: &Arc<RwLock<TitleVideo>>,
     rip_job: &Arc<RwLock<Job>>,
-) -> Option<(
+) -> (
     UploadedState,
     PathBuf,
     crate::state::upload_state::UploadType,
-)> {
-    let uploaded_state = match app_handle.try_state::<UploadedState>() {
-        Some(state) => {
-            let state_ref = state.inner();
-            UploadedState {
-                queue: Arc::clone(&state_ref.queue),
-            }
-        }
-        None => {
-            error!("Failed to get UploadedState");
-            return None;
-        }
+) {
+    // Managed during setup before any command or disc can start a rip
+    let uploaded_state = UploadedState {
+        queue: Arc::clone(&app_handle.state::<UploadedState>().queue),
     };
 
     let multiple_parts = rip_job
-        .read()
-        .expect("Failed to get rip_job reader")
-        .has_multiple_parts(
-            &title_video
-                .read()
-                .expect("To get title_video read lock for multiple_parts check"),
-        );
+        .read_or_recover()
+        .has_multiple_parts(&title_video.read_or_recover());
 
     let path = title_video
-        .read()
-        .expect("Failed to get title_video reader")
-        .video_path(&app_handle.state::<AppState>(), multiple_parts);
+        .read_or_recover()
+        .local_video_path(&app_handle.state::<AppState>(), multiple_parts);
 
     let upload_type = {
-        let video_guard = title_video
-            .read()
-            .expect("Failed to get title_video reader");
+        let video_guard = title_video.read_or_recover();
         match &video_guard.video {
             Video::Movie(_) => crate::state::upload_state::UploadType::Movie,
             Video::Tv(_) => crate::state::upload_state::UploadType::TvShow,
+            Video::Personal(_) => crate::state::upload_state::UploadType::HomeVideo,
         }
     };
 
-    Some((uploaded_state, path, upload_type))
+    (uploaded_state, path, upload_type)
 }
 
 fn spawn_upload(
@@ -576,10 +1122,7 @@ fn spawn_upload(
     let title_video = title_video.clone();
     tauri::async_runtime::spawn(async move {
         let (uploaded_state, path, upload_type) =
-            match extract_upload_info(&app_handle, &title_video, &rip_job) {
-                Some(info) => info,
-                None => return,
-            };
+            extract_upload_info(&app_handle, &title_video, &rip_job);
 
         // Add to persistent upload queue before starting
         if let Err(e) =
@@ -589,57 +1132,576 @@ fn spawn_upload(
             return;
         }
 
+        // Every upload gets its own job, it may sit in the queue for a while
         let background_process_state = app_handle.state::<BackgroundProcessState>();
-        let (job, is_new) = background_process_state.find_or_create_job(
-            None,
-            &None,
-            &JobType::Uploading,
-            &JobStatus::Pending,
-        );
+        let job = background_process_state.new_job(JobType::Uploading, JobStatus::Pending, None);
+        {
+            let mut job_guard = job.write_or_recover();
+            job_guard.depend_on(rip_job.read_or_recover().id);
+            job_guard.note = rip_job.read_or_recover().note.clone();
+            job_guard.title_videos.push(title_video.clone());
+        }
+        background_process_state.group_by_season(&job, &title_video.read_or_recover());
+        background_process_state.emit_jobs_changed(&app_handle);
 
-        if is_new {
-            background_process_state.emit_jobs_changed(&app_handle);
+        run_upload(&app_handle, &job, &title_video, &path, &uploaded_state).await;
+    });
+}
+
+/// The upload step of the pipeline, waits for the rip it depends on and a free upload slot.
+async fn run_upload(
+    app_handle: &tauri::AppHandle,
+    job: &Arc<RwLock<Job>>,
+    title_video: &Arc<RwLock<TitleVideo>>,
+    path: &Path,
+    uploaded_state: &UploadedState,
+) {
+    let background_process_state = app_handle.state::<BackgroundProcessState>();
+    if let Err(e) = background_process_state
+        .wait_for_dependencies(app_handle, job)
+        .await
+    {
+        job.write_or_recover().update_status(JobStatus::Error);
+        job.write_or_recover().message = Some(e);
+        emit_progress(app_handle, job, true);
+        return;
+    }
+    if upload_review::wait_for_review(app_handle, job, title_video, path).await
+        == ReviewOutcome::Rejected
+    {
+        // The rip stays in the library, it is only kept from the server
+        if let Err(e) = uploaded_state.remove_upload(app_handle, &path.to_string_lossy()) {
+            error!("Failed to remove video from upload queue: {e}");
         }
+        emit_progress(app_handle, job, true);
+        return;
+    }
+    network::wait_until_online(app_handle, job).await;
+    let _upload_slot = background_process_state
+        .wait_for_slot(app_handle, job, Slot::Upload)
+        .await;
+    job.write_or_recover().update_status(JobStatus::Processing);
+    job.write_or_recover().subtitle = Some("Uploading Video".to_string());
+    job.read_or_recover().emit_progress_change(app_handle);
+    notify_upload_started(app_handle, path);
+
+    match services::ftp_uploader::upload(app_handle, job, title_video).await {
+        Ok(_m) => {
+            notify_movie_upload_success(app_handle, path);
+            arr::spawn_rescan(app_handle, &title_video.read_or_recover().video);
+            job.write_or_recover().update_status(JobStatus::Finished);
+            emit_progress(app_handle, job, true);
+
+            // Remove from upload queue on success
+            if let Err(e) = uploaded_state.remove_upload(app_handle, &path.to_string_lossy()) {
+                error!("Failed to remove video from upload queue: {e}");
+            }
 
-        job.write()
-            .expect("Failed to get job writer")
-            .title_videos
-            .push(title_video.clone());
-        job.write()
-            .expect("Failed to get job writer")
-            .update_status(JobStatus::Processing);
-        job.write().expect("Failed to get job writer").subtitle =
-            Some("Uploading Video".to_string());
-        job.read()
-            .expect("Failed to get job reader")
-            .emit_progress_change(&app_handle);
-
-        match services::ftp_uploader::upload(&app_handle, &job, &title_video).await {
-            Ok(_m) => {
-                notify_movie_upload_success(&app_handle, &path);
-                job.write()
-                    .expect("Failed to acquire write lock on job")
-                    .update_status(JobStatus::Finished);
-                emit_progress(&app_handle, &job, true);
-
-                // Remove from upload queue on success
-                if let Err(e) = uploaded_state.remove_upload(&app_handle, &path.to_string_lossy()) {
-                    error!("Failed to remove video from upload queue: {e}");
-                }
+            delete_file(path);
+        }
+        Err(e) => {
+            job.write_or_recover().update_status(JobStatus::Error);
+            job.write_or_recover().message = Some(e.clone());
+            emit_progress(app_handle, job, true);
+            notify_movie_upload_failure(app_handle, job, path, &e);
+            // Keep in upload queue on failure for retry on next boot
+        }
+    };
+}
+
+/// Lets an upload that waits for review start.
+#[tauri::command]
+pub fn approve_upload(
+    id: u64,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    review_upload(id, &background_process_state, &app_handle, |job| {
+        job.update_status(JobStatus::Pending);
+        job.subtitle = Some("Approved".to_string());
+    })
+}
+
+/// Drops an upload that waits for review, the rip stays in the local library.
+#[tauri::command]
+pub fn reject_upload(
+    id: u64,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    review_upload(id, &background_process_state, &app_handle, |job| {
+        job.update_status(JobStatus::Error);
+        job.update_message("Rejected in review, not uploaded");
+    })
+}
+
+fn review_upload(
+    id: u64,
+    background_process_state: &BackgroundProcessState,
+    app_handle: &tauri::AppHandle,
+    decide: impl FnOnce(&mut Job),
+) -> Result<String, templates::Error> {
+    let job = match background_process_state.find_job_by_id(id.into()) {
+        Some(job) => job,
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(format!(
+                "Job {id} no longer exists"
+            )))
+        }
+    };
+    if !job.read_or_recover().is_awaiting_review() {
+        return render_reelix_error(&ReelixError::Validation(
+            "This upload doesn't wait for a review".to_string(),
+        ));
+    }
+    decide(&mut job.write_or_recover());
+    emit_progress(app_handle, &job, true);
+    Ok(String::new())
+}
+
+/// Set the note of a job, e.g. where the disc is stored. A blank note deletes it.
+#[tauri::command]
+pub fn update_job_note(
+    id: u64,
+    note: String,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let job = match background_process_state.find_job_by_id(id.into()) {
+        Some(job) => job,
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(format!(
+                "Job {id} no longer exists"
+            )))
+        }
+    };
+    job.write_or_recover().note = job_notes::clean(&note);
+    if let Err(e) = job_notes::persist(&app_handle, &job.read_or_recover()) {
+        return render_reelix_error(&e.into());
+    }
+    emit_progress(&app_handle, &job, true);
+    Ok(String::new())
+}
+
+/// Run a failed upload again without re-ripping, the file is still waiting on disk.
+#[tauri::command]
+pub fn retry_job(
+    id: u64,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let job = match background_process_state.find_job_by_id(id.into()) {
+        Some(job) => job,
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(format!(
+                "Job {id} no longer exists"
+            )))
+        }
+    };
+    if !job.read_or_recover().is_retryable() {
+        return render_reelix_error(&ReelixError::Validation(
+            "Only failed uploads can be retried".to_string(),
+        ));
+    }
+    let title_video = match job.read_or_recover().title_videos.first() {
+        Some(title_video) => title_video.clone(),
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "Nothing to upload in this job".to_string(),
+            ))
+        }
+    };
+
+    // The rip knows whether the title was one part of a multi-part episode
+    let rip_job = job
+        .read_or_recover()
+        .depends_on
+        .iter()
+        .find_map(|parent_id| background_process_state.find_job_by_id(*parent_id))
+        .unwrap_or_else(|| job.clone());
+    let (uploaded_state, path, _upload_type) =
+        extract_upload_info(&app_handle, &title_video, &rip_job);
+    if !path.exists() {
+        return render_reelix_error(&ReelixError::NotFound(format!(
+            "{} no longer exists",
+            path.display()
+        )));
+    }
+
+    job.write_or_recover().reset_for_retry();
+    background_process_state.emit_jobs_changed(&app_handle);
+
+    tauri::async_runtime::spawn(async move {
+        run_upload(&app_handle, &job, &title_video, &path, &uploaded_state).await;
+    });
+    Ok(String::new())
+}
+
+/// Rip the titles a rip failed again, the titles it finished are kept. The disc has to be
+/// back in the drive it was ripped in.
+#[tauri::command]
+pub fn retry_failed_titles(
+    id: u64,
+    state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let job = match background_process_state.find_job_by_id(id.into()) {
+        Some(job) => job,
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(format!(
+                "Job {id} no longer exists"
+            )))
+        }
+    };
+    if !job.read_or_recover().has_failed_titles() {
+        return render_reelix_error(&ReelixError::Validation(
+            "Only rips with failed titles can be retried".to_string(),
+        ));
+    }
+    // A disc put back in gets a new id, it is found by its drive and name
+    let ripped_disk = job.read_or_recover().disk.clone();
+    let Some(disk) = ripped_disk.and_then(|ripped| {
+        state
+            .clone_optical_disks()
+            .into_iter()
+            .find(|disk| disk.dev == ripped.dev && disk.name == ripped.name)
+    }) else {
+        return render_reelix_error(&ReelixError::Validation(
+            "Put the disc back in the drive to rip the failed titles".to_string(),
+        ));
+    };
+
+    {
+        let mut job_guard = job.write_or_recover();
+        job_guard.reset_for_retry();
+        job_guard.disk = Some(disk);
+    }
+    background_process_state.emit_jobs_changed(&app_handle);
+    spawn_rip(app_handle, job);
+    Ok(String::new())
+}
+
+/// A rip with its title and the paths of its movie files.
+type RematchableJob = (Arc<RwLock<Job>>, String, Vec<String>);
+
+/// The rip and its movie files for the "Fix Match" page, when it has any to rematch.
+fn rematchable_job(
+    background_process_state: &BackgroundProcessState,
+    app_state: &AppState,
+    id: u64,
+) -> Result<RematchableJob, String> {
+    let job = background_process_state
+        .find_job_by_id(id.into())
+        .ok_or_else(|| format!("Job {id} no longer exists"))?;
+    let job_guard = job.read_or_recover();
+    let title_videos = job_guard.rematchable_title_videos();
+    if title_videos.is_empty() {
+        return Err("Only movies a rip finished can be matched again".to_string());
+    }
+    let files = title_videos
+        .iter()
+        .map(|title_video| {
+            let multiple_parts = job_guard.has_multiple_parts(&title_video.read_or_recover());
+            title_video
+                .read_or_recover()
+                .local_video_path(app_state, multiple_parts)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    let title = job_guard.title.clone().unwrap_or_default();
+    drop(job_guard);
+    Ok((job, title, files))
+}
+
+/// Pick another movie for a rip TMDB got wrong, see `services::rematch`.
+#[tauri::command]
+pub fn fix_match(
+    id: u64,
+    app_state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+) -> Result<String, templates::Error> {
+    match rematchable_job(&background_process_state, &app_state, id) {
+        Ok((_, title, files)) => templates::fix_match::render_index(id, &title, &files, "", &[]),
+        Err(message) => render_reelix_error(&ReelixError::Validation(message)),
+    }
+}
+
+/// The search form of the "Fix Match" page, form fields arrive as text.
+#[tauri::command]
+pub async fn fix_match_search(
+    job_id: String,
+    search: String,
+    app_state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let Ok(id) = job_id.parse::<u64>() else {
+        return render_reelix_error(&ReelixError::Validation(format!("Invalid job id {job_id}")));
+    };
+    let (_, title, files) = match rematchable_job(&background_process_state, &app_state, id) {
+        Ok(rematchable) => rematchable,
+        Err(message) => return render_reelix_error(&ReelixError::Validation(message)),
+    };
+    let response = match services::plex::search_multi(&app_handle, &search).await {
+        Ok(response) => response,
+        Err(e) => return render_reelix_error(&e.into()),
+    };
+    let movies: Vec<_> = response
+        .results
+        .iter()
+        .filter(|result| result.media_type == "movie")
+        .collect();
+    templates::fix_match::render_index(id, &title, &files, &search, &movies)
+}
+
+#[tauri::command]
+pub async fn apply_fix_match(
+    id: u64,
+    mvdb_id: u32,
+    app_state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let job = match rematchable_job(&background_process_state, &app_state, id) {
+        Ok((job, _, _)) => job,
+        Err(message) => return render_reelix_error(&ReelixError::Validation(message)),
+    };
+    let movie = match find_movie(&app_handle, mvdb_id).await {
+        Ok(movie) => movie,
+        Err(e) => return render_reelix_error(&e.into()),
+    };
 
-                delete_file(&path);
+    let rematch_handle = app_handle.clone();
+    let rematch_job = job.clone();
+    let rematched = match blocking::run(move || {
+        services::rematch::rematch_movie(&rematch_handle, &rematch_job, &movie)
+    })
+    .await
+    {
+        Ok(Ok(rematched)) => rematched,
+        Ok(Err(message)) | Err(message) => return render_reelix_error(&message.into()),
+    };
+
+    if let Some(first) = rematched.first() {
+        job.write_or_recover()
+            .update_title(&first.title_video.read_or_recover());
+    }
+    let mut reuploads = 0;
+    for rematched in rematched.iter().filter(|rematched| rematched.reupload) {
+        spawn_upload(&app_handle, &job, &rematched.title_video);
+        reuploads += 1;
+    }
+    background_process_state.emit_jobs_changed(&app_handle);
+
+    let title = job.read_or_recover().title.clone().unwrap_or_default();
+    let message = match reuploads {
+        0 => format!("Renamed {} files to {title}", rematched.len()),
+        _ => format!(
+            "Renamed {} files to {title}, {reuploads} are uploaded again",
+            rematched.len()
+        ),
+    };
+    notifications::emit_toast(&app_handle, Toast::success("Match Fixed", message));
+    templates::search::render_index(&app_handle).await
+}
+
+/// A button clicked on a notification, see `NotificationActions`.
+#[tauri::command]
+pub fn notification_action(
+    action_id: String,
+    job_id: Option<u64>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let path = match (action_id.as_str(), job_id) {
+        ("retry", Some(job_id)) => {
+            return retry_job(job_id, background_process_state, app_handle);
+        }
+        ("open-folder", Some(job_id)) => {
+            match job_video_folder(&app_handle, &background_process_state, job_id) {
+                Ok(folder) => folder,
+                Err(e) => return render_reelix_error(&e),
             }
+        }
+        ("view-log", _) => match app_handle.path().app_log_dir() {
+            Ok(dir) => dir,
             Err(e) => {
-                job.write()
-                    .expect("Failed to get job writer")
-                    .update_status(JobStatus::Error);
-                job.write().expect("Failed to get job writer").message = Some(e.clone());
-                emit_progress(&app_handle, &job, true);
-                notify_movie_upload_failure(&app_handle, &path, &e);
-                // Keep in upload queue on failure for retry on next boot
+                return render_reelix_error(&format!("Failed to find the log folder: {e}").into())
             }
+        },
+        _ => {
+            return render_reelix_error(&ReelixError::Validation(format!(
+                "Unknown notification action {action_id}"
+            )))
+        }
+    };
+    match app_handle
+        .opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+    {
+        Ok(()) => Ok(String::new()),
+        Err(e) => render_reelix_error(&format!("Failed to open {}: {e}", path.display()).into()),
+    }
+}
+
+/// The folder of the video a job uploads, only the job's own folder is ever opened.
+fn job_video_folder(
+    app_handle: &tauri::AppHandle,
+    background_process_state: &BackgroundProcessState,
+    job_id: u64,
+) -> Result<PathBuf, ReelixError> {
+    let job = background_process_state
+        .find_job_by_id(job_id.into())
+        .ok_or_else(|| ReelixError::NotFound(format!("Job {job_id} no longer exists")))?;
+    let title_video = job
+        .read_or_recover()
+        .title_videos
+        .first()
+        .cloned()
+        .ok_or_else(|| ReelixError::NotFound("This job has no video".to_string()))?;
+    let rip_job = job
+        .read_or_recover()
+        .depends_on
+        .iter()
+        .find_map(|parent_id| background_process_state.find_job_by_id(*parent_id))
+        .unwrap_or_else(|| job.clone());
+    let (_, path, _) = extract_upload_info(app_handle, &title_video, &rip_job);
+    match path.parent() {
+        Some(folder) if folder.is_dir() => Ok(folder.to_path_buf()),
+        _ => Err(ReelixError::NotFound(format!(
+            "The folder of {} no longer exists",
+            path.display()
+        ))),
+    }
+}
+
+/// Send a finished upload again, or put it back in the rip plan when the ripped file is gone.
+#[tauri::command]
+pub fn requeue_job(
+    id: u64,
+    app_state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let job = match background_process_state.find_job_by_id(id.into()) {
+        Some(job) => job,
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(format!(
+                "Job {id} no longer exists"
+            )))
+        }
+    };
+    if !job.read_or_recover().is_requeueable() {
+        return render_reelix_error(&ReelixError::Validation(
+            "Only finished uploads can be queued again".to_string(),
+        ));
+    }
+    let title_video = match job.read_or_recover().title_videos.first() {
+        Some(title_video) => title_video.clone(),
+        None => {
+            return render_reelix_error(&ReelixError::NotFound(
+                "Nothing to upload in this job".to_string(),
+            ))
+        }
+    };
+    let rip_job = job
+        .read_or_recover()
+        .depends_on
+        .iter()
+        .find_map(|parent_id| background_process_state.find_job_by_id(*parent_id))
+        .unwrap_or_else(|| job.clone());
+    let (uploaded_state, path, upload_type) =
+        extract_upload_info(&app_handle, &title_video, &rip_job);
+
+    if !path.exists() {
+        let video = title_video.read_or_recover().video.clone();
+        return match video {
+            Video::Movie(movie) => add_to_rip_plan(
+                &app_state,
+                &app_handle,
+                rip_plan::PlanKind::Movie,
+                movie.movie.id,
+                None,
+                movie.movie.title_year(),
+            ),
+            Video::Tv(tv_season_episode) => add_to_rip_plan(
+                &app_state,
+                &app_handle,
+                rip_plan::PlanKind::Season,
+                u32::from(tv_season_episode.tv.id),
+                Some(tv_season_episode.season.season_number),
+                season_plan_title(
+                    &tv_season_episode.tv,
+                    tv_season_episode.season.season_number,
+                ),
+            ),
+            Video::Personal(_) => render_reelix_error(&ReelixError::NotFound(format!(
+                "{} no longer exists, insert the disc to rip it again",
+                path.display()
+            ))),
         };
+    }
+
+    if let Err(message) =
+        uploaded_state.add_upload(&app_handle, path.to_string_lossy().to_string(), upload_type)
+    {
+        return render_reelix_error(&message.into());
+    }
+    job.write_or_recover().reset_for_retry();
+    background_process_state.emit_jobs_changed(&app_handle);
+
+    tauri::async_runtime::spawn(async move {
+        run_upload(&app_handle, &job, &title_video, &path, &uploaded_state).await;
     });
+    Ok(String::new())
+}
+
+/// Everything in the upload queue, the queue is otherwise only looked at on boot.
+#[tauri::command]
+pub fn pending_uploads(
+    app_state: State<'_, AppState>,
+    uploaded_state: State<'_, UploadedState>,
+) -> Result<String, templates::Error> {
+    templates::uploads::render_index(&app_state, &uploaded_state.get_pending())
+}
+
+/// Upload a queued video now instead of waiting for the next start.
+#[tauri::command]
+pub fn retry_pending_upload(
+    video_path: String,
+    uploaded_state: State<'_, UploadedState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    let Some(pending_upload) = uploaded_state.find(&video_path) else {
+        return render_reelix_error(&ReelixError::NotFound(format!(
+            "{video_path} is no longer in the upload queue"
+        )));
+    };
+    let uploaded_state = UploadedState::clone_arc(&uploaded_state);
+    tauri::async_runtime::spawn(async move {
+        services::upload_recovery::resume_upload(&app_handle, &uploaded_state, &pending_upload)
+            .await;
+    });
+    templates::toast::render_toast_append(Toast::success(
+        "Upload started".to_string(),
+        format!("{video_path} is uploading, it shows up in the jobs"),
+    ))
+}
+
+/// Drop a video from the upload queue, the file itself stays where it is.
+#[tauri::command]
+pub fn remove_pending_upload(
+    video_path: String,
+    app_state: State<'_, AppState>,
+    uploaded_state: State<'_, UploadedState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, templates::Error> {
+    if let Err(message) = uploaded_state.remove_upload(&app_handle, &video_path) {
+        return render_reelix_error(&message.into());
+    }
+    templates::uploads::render_index(&app_state, &uploaded_state.get_pending())
 }
 
 fn delete_file(file_path: &Path) {
@@ -653,100 +1715,190 @@ async fn rip_title(
     job: &Arc<RwLock<Job>>,
     title_video: &Arc<RwLock<TitleVideo>>,
 ) -> Result<PathBuf, StandardError> {
-    match makemkvcon::rip_title(app_handle, job, title_video).await {
-        Ok(_) => {
-            let app_state = app_handle.state::<AppState>();
-            let job_reader = job.read().expect("Failed to get job reader");
-            title_video
-                .read()
-                .expect("Failed to get title_video reader")
-                .rename_ripped_file(&app_state, &job_reader)
+    let started = Instant::now();
+    let expected_seconds = expected_rip_seconds(app_handle, &job.read_or_recover(), title_video);
+    job.write_or_recover().expected_seconds = expected_seconds;
+    job.write_or_recover().ripping_title = true;
+    let ripped = makemkvcon::rip_title(app_handle, job, title_video).await;
+    job.write_or_recover().ripping_title = false;
+    match ripped {
+        Ok(run_results) => {
+            join_ripped_titles(app_handle, job, title_video)
+                .await
+                .map_err(|e| StandardError {
+                    title: "Join Failure".into(),
+                    message: e,
+                })?;
+            let path = move_ripped_file(app_handle, job, title_video)
+                .await
                 .map_err(|e| StandardError {
                     title: "Rename Failure".into(),
                     message: e,
-                })
+                })?;
+            let job_reader = job.read_or_recover();
+            let bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let seconds = started.elapsed().as_secs();
+            let drive = job_drive_key(app_handle, &job_reader);
+            let media = title_video
+                .read_or_recover()
+                .title
+                .as_ref()
+                .and_then(drive_stats_state::media_type);
+            let read_errors = run_results.read_error_count();
+            metrics::record_drive_errors(read_errors);
+            drive_stats_state::record(app_handle, |stats| {
+                stats.record_rip(app_handle, &drive, media, bytes, seconds, read_errors)
+            });
+            Ok(path)
+        }
+        Err(e) => {
+            let drive = job_drive_key(app_handle, &job.read_or_recover());
+            metrics::record_drive_errors(1);
+            drive_stats_state::record(app_handle, |stats| stats.record_failure(app_handle, &drive));
+            Err(StandardError {
+                title: RIP_FAILURE_TITLE.into(),
+                message: e,
+            })
         }
-        Err(e) => Err(StandardError {
-            title: "Rip Failure".into(),
-            message: e,
-        }),
     }
 }
 
-// this never worked I will work on making this work later
-// async fn back_disk(
-//     app_handle: &tauri::AppHandle,
-//     job: &Arc<RwLock<Job>>,
-//     disk_id: &DiskId,
-//     rip_info: &RipInfo,
-// ) -> Result<(), StandardError> {
-//     match makemkvcon::back_disk(app_handle, job, disk_id, &rip_info.directory).await {
-//         Ok(_) => Ok(()),
-//         Err(e) => Err(StandardError {
-//             title: "Backup Failure".into(),
-//             message: e,
-//         }),
-//     }
-// }
+/// Appends the `joined_titles` to the ripped file, which then stands for the whole movie.
+async fn join_ripped_titles(
+    app_handle: &tauri::AppHandle,
+    job: &Arc<RwLock<Job>>,
+    title_video: &Arc<RwLock<TitleVideo>>,
+) -> Result<(), String> {
+    let (ripped_path, joined_paths) = {
+        let app_state = app_handle.state::<AppState>();
+        let title_video = title_video.read_or_recover();
+        (
+            title_video.partial_rip_path(&app_state),
+            title_video.joined_file_paths(&app_state),
+        )
+    };
+    let Some(ripped_path) = ripped_path.filter(|_| !joined_paths.is_empty()) else {
+        return Ok(());
+    };
+    let mut parts = vec![ripped_path.clone()];
+    parts.extend(joined_paths);
+    job.write_or_recover().subtitle = Some(format!("Joining {} titles", parts.len()));
+    emit_progress(app_handle, job, false);
+
+    let joined_path = ripped_path.with_extension("joined.mkv");
+    if let Err(e) = services::mkvtoolnix::append(app_handle, &parts, &joined_path).await {
+        let _ = fs::remove_file(&joined_path);
+        return Err(e);
+    }
+    for part in &parts {
+        fs::remove_file(part)
+            .map_err(|e| format!("Failed to remove {} after joining: {e}", part.display()))?;
+    }
+    fs::rename(&joined_path, &ripped_path).map_err(|e| {
+        format!(
+            "Failed to rename {} to {}: {e}",
+            joined_path.display(),
+            ripped_path.display()
+        )
+    })
+}
 
-fn notify_tv_success(app_handle: &tauri::AppHandle, title: &title_video::TvSeasonEpisode) {
+/// Rename the ripped file into place. A library on a network share is another device, the
+/// file is copied there instead and the copy shows up in the job.
+async fn move_ripped_file(
+    app_handle: &tauri::AppHandle,
+    job: &Arc<RwLock<Job>>,
+    title_video: &Arc<RwLock<TitleVideo>>,
+) -> Result<PathBuf, String> {
+    let (from_path, target_path) = {
+        let app_state = app_handle.state::<AppState>();
+        let job_reader = job.read_or_recover();
+        title_video
+            .read_or_recover()
+            .ripped_file_move(&app_state, &job_reader)?
+    };
+    let move_handle = app_handle.clone();
+    let move_job = job.clone();
+    let moved_to = blocking::run(move || {
+        file_move::move_file(&from_path, &target_path, |copied, total| {
+            let percent = (copied * 100).checked_div(total).unwrap_or(100);
+            move_job.write_or_recover().subtitle =
+                Some(format!("Copying to the library {percent}%"));
+            emit_progress(&move_handle, &move_job, false);
+        })
+    })
+    .await??;
+    title_video.write_or_recover().video_file = Some(moved_to.clone());
+    Ok(moved_to)
+}
+
+/// How long the title should take on this drive, from earlier rips of the same media type
+/// when there are any. `None` before the drive ripped or was benchmarked.
+fn expected_rip_seconds(
+    app_handle: &tauri::AppHandle,
+    job: &Job,
+    title_video: &Arc<RwLock<TitleVideo>>,
+) -> Option<u64> {
+    let title = title_video.read_or_recover().title.clone()?;
+    let bytes: u64 = title.bytes.as_deref()?.parse().ok()?;
     app_handle
-        .notification()
-        .builder()
-        .title(format!("Episode Created for {}", title.tv.name))
-        .body(title.title().to_string())
-        .show()
-        .unwrap();
-}
-
-// fn build_info(app_handle: &tauri::AppHandle, disk_id: &DiskId) -> JobInfo {
-//     let state = app_handle.state::<AppState>();
-//     let optical_disk = state.find_optical_disk_by_id(disk_id).unwrap();
-//     {
-//         let locked_disk = optical_disk.read().unwrap();
-//         match locked_disk.content.as_ref().unwrap() {
-//             DiskContent::Movie(movie) => {
-//                 let dir = create_movie_dir(movie);
-//                 let titles = locked_disk
-//                     .titles
-//                     .lock()
-//                     .unwrap()
-//                     .iter()
-//                     .filter(|t| t.rip)
-//                     .cloned()
-//                     .collect::<Vec<_>>();
-//                 JobInfo {
-//                     directory: dir,
-//                     titles,
-//                     content: DiskContent::Movie(movie.clone()),
-//                     disk: locked_disk.clone(),
-//                 }
-//             }
-//             DiskContent::Tv(season) => {
-//                 let dir = create_season_episode_dir(season);
-//                 let titles = locked_disk
-//                     .titles
-//                     .lock()
-//                     .unwrap()
-//                     .iter()
-//                     .filter(|t| t.rip)
-//                     .cloned()
-//                     .collect::<Vec<_>>();
-//                 RipInfo {
-//                     directory: dir,
-//                     titles,
-//                     content: DiskContent::Tv(season.clone()),
-//                     disk: locked_disk.clone(),
-//                 }
-//             }
-//         }
-//     }
-// }
+        .state::<drive_stats_state::DriveStatsState>()
+        .find(&job_drive_key(app_handle, job))?
+        .estimated_seconds(bytes, drive_stats_state::media_type(&title))
+}
+
+/// The job keeps a copy of the disk from when it was created, the drive name
+/// is only known once the scan finished so look at the live disk first.
+fn job_drive_key(app_handle: &tauri::AppHandle, job: &Job) -> String {
+    let Some(disk) = job.disk.as_ref() else {
+        return String::new();
+    };
+    match app_handle
+        .state::<AppState>()
+        .find_optical_disk_by_id(&disk.id)
+    {
+        Some(live_disk) => live_disk.read_or_recover().drive_key(),
+        None => disk.drive_key(),
+    }
+}
+
+/// Only makemkvcon's own failures say something about the drive or the disc. A join or a move
+/// that failed happened after the title was ripped, ripping it again wouldn't help.
+fn rip_failure(error: &StandardError) -> RipFailure {
+    if error.title == RIP_FAILURE_TITLE {
+        RipFailure::classify(&error.message)
+    } else {
+        RipFailure::Title
+    }
+}
+
+async fn rip_and_post_process_title(
+    app_handle: &tauri::AppHandle,
+    job: &Arc<RwLock<Job>>,
+    title_video: &Arc<RwLock<TitleVideo>>,
+) -> Result<PathBuf, StandardError> {
+    let path = rip_title(app_handle, job, title_video).await?;
+    post_process::process(app_handle, job, title_video, &path).await;
+    Ok(path)
+}
+
+fn notify_tv_success(app_handle: &tauri::AppHandle, title: &title_video::TvSeasonEpisode) {
+    notify(
+        app_handle,
+        NotificationKind::Rip,
+        NotificationEvent::Finish,
+        format!("Episode Created for {}", title.tv.name),
+        title.title(),
+    );
+}
 
 fn eject_disk(app_handle: &tauri::AppHandle, disk_id: &DiskId) {
     let state = app_handle.state::<AppState>();
     match state.find_optical_disk_by_id(disk_id) {
         Some(disk) => match disk.read() {
+            Ok(locked_disk) if locked_disk.is_remote() => {
+                debug!("Leaving {} in the remote drive", locked_disk.name)
+            }
             Ok(locked_disk) => disk_manager::eject(&locked_disk.mount_point),
             Err(_) => debug!("Failed to eject disk"),
         },
@@ -756,88 +1908,183 @@ fn eject_disk(app_handle: &tauri::AppHandle, disk_id: &DiskId) {
     }
 }
 
+/// Where the title starts on the disc, ripping the titles front to back keeps the drive from
+/// seeking back and forth. Titles without a segment map keep their order after the others.
+fn disc_position(title_video: &TitleVideo) -> (bool, Option<u32>) {
+    let segment = title_video
+        .title
+        .as_ref()
+        .and_then(|title| title.first_segment());
+    (segment.is_none(), segment)
+}
+
 async fn process_titles(app_handle: &tauri::AppHandle, job: Arc<RwLock<Job>>) -> bool {
     let mut any_success = false;
     let mut has_error = false;
-    let title_videos = {
-        let job_guard = job.read().expect("Failed to get job writer");
+    let mut title_videos = {
+        let job_guard = job.read_or_recover();
         job_guard.title_videos.clone()
     };
+    if !app_handle
+        .state::<AppState>()
+        .ripper_options()
+        .episode_order
+    {
+        title_videos.sort_by_key(|title| disc_position(&title.read_or_recover()));
+    }
     for title in title_videos.iter() {
+        if disc_removed(&job) {
+            has_error = true;
+            break;
+        }
+        let title_video_id = title.read_or_recover().id;
+        if job
+            .read_or_recover()
+            .finished_title_video_ids
+            .contains(&title_video_id)
+        {
+            // Ripped before the failed titles were retried
+            continue;
+        }
         // Set current title video ID for progress tracking
-        job.write()
-            .expect("Failed to get job writer")
-            .current_title_video_id = Some(title.read().unwrap().id);
-        job.write()
-            .expect("Failed to get job writer")
-            .update_title(&title.read().unwrap());
-        job.read()
-            .expect("Failed to get job reader")
-            .emit_progress_change(app_handle);
-        match rip_title(app_handle, &job, title).await {
+        job.write_or_recover().current_title_video_id = Some(title.read_or_recover().id);
+        job.write_or_recover()
+            .update_title(&title.read_or_recover());
+        job.read_or_recover().emit_progress_change(app_handle);
+        let mut attempt = 1;
+        let result = loop {
+            let result = rip_and_post_process_title(app_handle, &job, title).await;
+            match &result {
+                Err(error)
+                    if attempt < rip_failure::MAX_TITLE_ATTEMPTS
+                        && !disc_removed(&job)
+                        && rip_failure(error) == RipFailure::Transient =>
+                {
+                    warn!("Ripping the title again after: {}", error.message);
+                    job.write_or_recover()
+                        .add_notice(format!("Ripped a title again after: {}", error.message));
+                    attempt += 1;
+                }
+                _ => break result,
+            }
+        };
+        match result {
             Ok(_) => {
                 any_success = true;
-                match &title.read().unwrap().video {
-                    Video::Tv(season) => {
-                        notify_tv_success(app_handle, season);
-                        spawn_upload(app_handle, &job, title);
-                    }
+                // Lets the upload that depends on this job start for this title
+                job.write_or_recover()
+                    .finish_title_video(title.read_or_recover().id);
+                trakt::spawn_collect(app_handle, &title.read_or_recover().video);
+                match &title.read_or_recover().video {
+                    Video::Tv(season) => notify_tv_success(app_handle, season),
                     Video::Movie(movie) => {
                         notify_movie_success(app_handle, movie);
                         emit_render_cards(app_handle);
-                        spawn_upload(app_handle, &job, title);
                     }
+                    Video::Personal(personal) => notify_personal_success(app_handle, personal),
                 };
-                job.read()
-                    .expect("Failed to get job reader")
-                    .emit_progress_change(app_handle);
+                if title.read_or_recover().skip_upload {
+                    let mut job_guard = job.write_or_recover();
+                    let name = job_guard.title.clone().unwrap_or_default();
+                    job_guard.add_notice(format!("{name} kept local, not uploaded"));
+                } else {
+                    spawn_upload(app_handle, &job, title);
+                }
+                job.read_or_recover().emit_progress_change(app_handle);
             }
             Err(error) => {
                 has_error = true;
-                match &title.read().unwrap().video {
-                    Video::Tv(_) => {}
+                if disc_removed(&job) {
+                    // The job already says why, the makemkvcon error would only be "killed"
+                    break;
+                }
+                match &title.read_or_recover().video {
+                    Video::Tv(_) | Video::Personal(_) => {}
                     Video::Movie(_) => {
                         emit_render_cards(app_handle);
                     }
                 };
-                job.write().expect("Failed to get job writer").message =
-                    Some(error.message.clone());
-                job.write().expect("Failed to get job writer").subtitle = Some(error.title.clone());
-                job.read()
-                    .expect("Failed to get job reader")
-                    .emit_progress_change(app_handle);
+                {
+                    let mut job_guard = job.write_or_recover();
+                    let name = job_guard.title.clone().unwrap_or_default();
+                    job_guard.add_notice(format!("{name} failed: {}", error.message));
+                    job_guard.message = Some(error.message.clone());
+                    job_guard.subtitle = Some(error.title.clone());
+                }
+                job.read_or_recover().emit_progress_change(app_handle);
                 notify_failure(app_handle, &error);
+                if rip_failure(error) == RipFailure::Disc {
+                    // The other titles would fail the same way
+                    break;
+                }
             }
         };
     }
 
     // Mark job as finished/error only after ALL titles are processed
-    if has_error {
-        job.write()
-            .expect("Failed to get job writer")
-            .update_status(JobStatus::Error);
+    let removed = disc_removed(&job);
+    if removed {
+        // abort_jobs_for_removed_disk failed the job, it must not turn into Finished because
+        // the title that was on its way to the library made it
+        metrics::record_disc_rip(false);
+    } else if has_error {
+        job.write_or_recover().update_status(JobStatus::Error);
+        metrics::record_disc_rip(false);
+        report_failed_titles(app_handle, &job);
     } else if any_success {
-        job.write()
-            .expect("Failed to get job writer")
-            .update_status(JobStatus::Finished);
+        job.write_or_recover().update_status(JobStatus::Finished);
+        metrics::record_disc_rip(true);
     }
 
     // Final UI update
-    job.read()
-        .expect("Failed to get job reader")
-        .emit_progress_change(app_handle);
+    job.read_or_recover().emit_progress_change(app_handle);
     templates::disks::emit_disk_change(app_handle);
 
-    any_success
+    // There is no disc left to eject
+    any_success && !removed
+}
+
+/// Sums up a rip that got some of its titles done, the failed ones can be ripped again.
+/// A rip that got none done is told by the failure notifications already.
+fn report_failed_titles(app_handle: &tauri::AppHandle, job: &Arc<RwLock<Job>>) {
+    let (id, total, failed) = {
+        let job_guard = job.read_or_recover();
+        (
+            job_guard.id,
+            job_guard.title_videos.len(),
+            job_guard.failed_title_videos().len(),
+        )
+    };
+    if failed == 0 || failed == total {
+        return;
+    }
+    let summary = format!(
+        "Ripped {} of {total} titles, {failed} failed",
+        total - failed
+    );
+    job.write_or_recover().update_message(&summary);
+    notifications::emit_toast(
+        app_handle,
+        Toast::danger("Some Titles Failed", &summary)
+            .with_action("Retry Failed Titles", format!("retry_failed_titles/{id}")),
+    );
+}
+
+/// Set by the disk listener when the disc is pulled in the middle of a rip.
+fn disc_removed(job: &Arc<RwLock<Job>>) -> bool {
+    job.read_or_recover().disc_removed
 }
 
 pub fn spawn_rip(app_handle: tauri::AppHandle, job: Arc<RwLock<Job>>) {
     tauri::async_runtime::spawn(async move {
-        job.write()
-            .expect("Failed to get job writer")
-            .update_status(JobStatus::Processing);
+        let _rip_slot = app_handle
+            .state::<BackgroundProcessState>()
+            .wait_for_slot(&app_handle, &job, Slot::Rip)
+            .await;
+        job.write_or_recover().update_status(JobStatus::Processing);
+        let resources = resource_monitor::start();
         let has_tv_titles = {
-            let job_guard = job.read().expect("Failed to get job reader");
+            let job_guard = job.read_or_recover();
             job_guard.title_videos.iter().any(|title_video| {
                 title_video
                     .read()
@@ -848,68 +2095,70 @@ pub fn spawn_rip(app_handle: tauri::AppHandle, job: Arc<RwLock<Job>>) {
         if !has_tv_titles {
             templates::disks::emit_disk_change(&app_handle);
         }
-        job.read()
-            .expect("Failed to get job reader")
-            .emit_progress_change(&app_handle);
+        job.read_or_recover().emit_progress_change(&app_handle);
+        notify_rip_started(&app_handle, &job);
+        push::spawn_progress_pushes(&app_handle, &job);
+        let tray_options = app_handle.state::<AppState>().tray_options();
+        let device = local_device(&job);
+        if tray_options.lock_during_rip {
+            set_tray_lock(&device, true);
+        }
         let success = process_titles(&app_handle, job.clone()).await;
+        resources.finish(&app_handle, &job);
+        emit_progress(&app_handle, &job, true);
+        rip_plan::rip_finished(&app_handle, &job, success);
+        if tray_options.lock_during_rip {
+            set_tray_lock(&device, false);
+        }
         if success {
-            match &job.read().expect("Failed to get job reader").disk {
+            match &job.read_or_recover().disk {
                 Some(disk) => eject_disk(&app_handle, &disk.id),
                 None => warn!("No disk found in job after ripping nothing to eject"),
             };
+            if let (Some(device), Some(seconds)) = (device, tray_options.close_after_eject_seconds)
+            {
+                close_tray_after(device, seconds).await;
+            }
         }
     });
 }
 
-// fn after_process_titles(
-//     app_handle: &tauri::AppHandle,
-//     disk_id: &DiskId,
-//     job: &Arc<RwLock<Job>>,
-//     success: bool,
-// ) {
-//     if success {
-//         job.write()
-//             .expect("Failed to get job writer")
-//             .update_status(JobStatus::Finished);
-//         eject_disk(app_handle, disk_id);
-//     } else {
-//         job.write()
-//             .expect("Failed to get job writer")
-//             .update_status(JobStatus::Error);
-//     }
-// }
-
-// fn find_or_create_job(app_handle: &tauri::AppHandle, disk: &OpticalDiskInfo) -> Arc<RwLock<Job>> {
-//     let background_process_state = app_handle.state::<BackgroundProcessState>();
-
-//     let disk_id = disk.id;
-//     match background_process_state.find_job(
-//         Some(disk_id),
-//         &Some(JobType::Ripping),
-//         &[JobStatus::Pending],
-//     ) {
-//         Some(job) => job,
-//         None => {
-//             let optical_disk_info = disk.clone();
-//             let job = background_process_state.new_job(
-//                 JobType::Ripping,
-//                 JobStatus::Pending,
-//                 Some(optical_disk_info),
-//             );
-//             background_process_state.emit_jobs_changed(app_handle);
-//             job
-//         }
-//     }
-// }
+/// The drive of the job when it is on this machine, remote drives are left alone.
+fn local_device(job: &Arc<RwLock<Job>>) -> Option<String> {
+    job.read_or_recover()
+        .disk
+        .as_ref()
+        .filter(|disk| !disk.is_remote())
+        .map(|disk| disk.dev.clone())
+}
+
+fn set_tray_lock(device: &Option<String>, locked: bool) {
+    if let Some(device) = device {
+        if let Err(e) = disk_manager::lock_tray(device, locked) {
+            debug!("Failed to set tray lock of {device} to {locked}: {e}");
+        }
+    }
+}
+
+/// Gives a disc changer time to put the next disc in the open tray, the disk listener
+/// picks the disc up once the tray is closed.
+async fn close_tray_after(device: String, seconds: u64) {
+    tokio::time::sleep(std::time::Duration::from_secs(seconds)).await;
+    if let Err(e) = disk_manager::close_tray(&device) {
+        warn!("Failed to close the tray of {device}: {e}");
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use crate::state::title_video::TvSeasonEpisode;
-    use crate::the_movie_db::{SeasonEpisode, SeasonResponse, TvId, TvResponse};
+    use crate::the_movie_db::{
+        EpisodeId, SeasonEpisode, SeasonId, SeasonResponse, TvId, TvResponse,
+    };
 
     fn create_mock_tv_episode(id: u32, episode_number: u32) -> SeasonEpisode {
         SeasonEpisode {
-            id,
+            id: EpisodeId::from(id),
             episode_number,
             episode_type: "standard".to_string(),
             name: format!("Episode {episode_number}"),
@@ -918,7 +2167,7 @@ mod tests {
             production_code: None,
             runtime: Some(45),
             season_number: 1,
-            show_id: 100,
+            show_id: TvId::from(100),
             still_path: None,
             vote_average: 8.0,
             vote_count: 100,
@@ -930,7 +2179,7 @@ mod tests {
     fn create_mock_season() -> SeasonResponse {
         SeasonResponse {
             _id: "test_season".to_string(),
-            id: 1,
+            id: SeasonId::from(1),
             season_number: 1,
             name: "Season 1".to_string(),
             overview: "Test season".to_string(),
@@ -987,9 +2236,9 @@ mod tests {
         let season = create_mock_season();
 
         // Verify each episode has a unique ID
-        assert_eq!(season.episodes[0].id, 1);
-        assert_eq!(season.episodes[1].id, 2);
-        assert_eq!(season.episodes[2].id, 3);
+        assert_eq!(season.episodes[0].id, EpisodeId::from(1));
+        assert_eq!(season.episodes[1].id, EpisodeId::from(2));
+        assert_eq!(season.episodes[2].id, EpisodeId::from(3));
 
         // Verify IDs are different
         assert_ne!(season.episodes[0].id, season.episodes[1].id);
@@ -1019,7 +2268,7 @@ mod tests {
             part: 1,
         };
 
-        assert_eq!(tv_season_episode.episode.id, 1);
+        assert_eq!(tv_season_episode.episode.id, EpisodeId::from(1));
         assert_eq!(tv_season_episode.part, 1);
         assert_eq!(tv_season_episode.tv.id, TvId::from(100));
         assert_eq!(tv_season_episode.season.season_number, 1);
@@ -1077,9 +2326,9 @@ mod tests {
         };
 
         // Verify each episode has unique ID
-        assert_eq!(episode1.episode.id, 1);
-        assert_eq!(episode2.episode.id, 2);
-        assert_eq!(episode3.episode.id, 3);
+        assert_eq!(episode1.episode.id, EpisodeId::from(1));
+        assert_eq!(episode2.episode.id, EpisodeId::from(2));
+        assert_eq!(episode3.episode.id, EpisodeId::from(3));
 
         // Verify they're all different
         assert_ne!(episode1.episode.id, episode2.episode.id);
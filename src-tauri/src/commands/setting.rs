@@ -1,8 +1,31 @@
+use crate::reelix_error::ReelixError;
+use crate::services::archive::ArchiveOptions;
+use crate::services::arr::{ArrOptions, ArrServer};
+use crate::services::blocking;
+use crate::services::disk_manager::TrayOptions;
+use crate::services::ftp_uploader;
 use crate::services::ftp_validator;
+use crate::services::library::{self, LibraryProfile};
+use crate::services::library_dir;
+use crate::services::locale::{self, Locale};
+use crate::services::network::NetworkOptions;
+use crate::services::notifications::{self, DoNotDisturb, NotificationEvents};
+use crate::services::owned_collection;
 use crate::services::plex::search_multi;
-use crate::state::AppState;
-use crate::templates::{ftp_settings, render_error, search, Error};
+use crate::services::push::{PushOptions, PushService};
+use crate::services::remote_api::RemoteApiOptions;
+use crate::services::ripper_backend::{self, RipperKind, RipperOptions};
+use crate::services::trakt;
+use crate::services::upload_review::ReviewOptions;
+use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::locks::RwLockExt;
+use crate::state::resource_governor::ConcurrencyLimits;
+use crate::state::{AppState, FtpSettingsUpdate};
+use crate::templates::{ftp_settings, render_reelix_error, search, settings, Error};
+use serde::Deserialize;
+use std::path::PathBuf;
 use tauri::State;
+use tauri_plugin_dialog::DialogExt;
 
 #[tauri::command]
 pub fn ftp_settings(
@@ -13,26 +36,25 @@ pub fn ftp_settings(
     ftp_settings::render_show(&state)
 }
 
+/// Everything besides FTP and The Movie DB, those have pages of their own.
+#[tauri::command]
+pub fn settings(state: State<'_, AppState>) -> Result<String, Error> {
+    settings::render_index(&state)
+}
+
 #[tauri::command]
 pub fn update_ftp_settings(
-    ftp_host: String,
-    ftp_user: String,
-    ftp_pass: String,
-    ftp_movie_upload_path: String,
-    ftp_tv_upload_path: String,
+    settings: FtpSettingsUpdate,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, Error> {
-    state.update_ftp_settings(
-        Some(ftp_host),
-        Some(ftp_user),
-        Some(ftp_pass),
-        Some(ftp_movie_upload_path),
-        Some(ftp_tv_upload_path),
-    );
+    if let Err(message) = ftp_uploader::validate_file_mode(&settings.file_mode) {
+        return render_reelix_error(&ReelixError::Validation(message));
+    }
+    state.update_ftp_settings(settings);
 
     if let Err(message) = state.save(&app_handle) {
-        return render_error(&message);
+        return render_reelix_error(&message.into());
     }
 
     ftp_validator::trigger_ftp_check(&app_handle);
@@ -40,20 +62,711 @@ pub fn update_ftp_settings(
 }
 
 #[tauri::command]
-pub fn the_movie_db(
+pub fn update_season_poster_settings(
+    season_posters: bool,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    state.lock_ftp_config().season_posters = season_posters;
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    Ok("Season poster settings updated successfully".to_string())
+}
+
+#[tauri::command]
+pub fn update_naming_settings(
+    move_leading_article: bool,
+    ascii_only: bool,
+    append_quality_tag: bool,
+    tmdb_id_tag: bool,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    {
+        let mut naming_options = state.lock_naming_options();
+        naming_options.move_leading_article = move_leading_article;
+        naming_options.ascii_only = ascii_only;
+        naming_options.append_quality_tag = append_quality_tag;
+        naming_options.tmdb_id_tag = tmdb_id_tag;
+    }
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    Ok("Naming settings updated successfully".to_string())
+}
+
+/// The fields of the post-processing form
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostProcessSettingsRequest {
+    pub enabled: bool,
+    pub deinterlace: bool,
+    pub downmix_stereo: bool,
+    pub normalize_loudness: bool,
+    pub name_chapters: bool,
+    pub chapter_db_api_key: String,
+    pub preview_frames: bool,
+}
+
+#[tauri::command]
+pub fn update_post_process_settings(
+    request: PostProcessSettingsRequest,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let PostProcessSettingsRequest {
+        enabled,
+        deinterlace,
+        downmix_stereo,
+        normalize_loudness,
+        name_chapters,
+        chapter_db_api_key,
+        preview_frames,
+    } = request;
+    {
+        let mut post_process_options = state.lock_post_process_options();
+        post_process_options.enabled = enabled;
+        post_process_options.deinterlace = deinterlace;
+        post_process_options.downmix_stereo = downmix_stereo;
+        post_process_options.normalize_loudness = normalize_loudness;
+        post_process_options.name_chapters = name_chapters;
+        post_process_options.preview_frames = preview_frames;
+        post_process_options.chapter_db_api_key = optional(&chapter_db_api_key);
+    }
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    Ok("Post-process settings updated successfully".to_string())
+}
+
+/// The checkboxes of the notifications form, which events of rips and uploads notify
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSettingsRequest {
+    pub rip_start: bool,
+    pub rip_finish: bool,
+    pub rip_error: bool,
+    pub upload_start: bool,
+    pub upload_finish: bool,
+    pub upload_error: bool,
+}
+
+#[tauri::command]
+pub fn update_notification_settings(
+    request: NotificationSettingsRequest,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    {
+        let mut notification_preferences = state.lock_notification_preferences();
+        notification_preferences.rips = NotificationEvents {
+            on_start: request.rip_start,
+            on_finish: request.rip_finish,
+            on_error: request.rip_error,
+        };
+        notification_preferences.uploads = NotificationEvents {
+            on_start: request.upload_start,
+            on_finish: request.upload_finish,
+            on_error: request.upload_error,
+        };
+    }
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    Ok("Notification settings updated successfully".to_string())
+}
+
+#[tauri::command]
+pub fn update_push_settings(
+    service: String,
+    server: String,
+    topic: String,
+    token: String,
+    progress_interval_minutes: u64,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let push_options = PushOptions {
+        service: PushService::from_store_value(Some(service.trim())),
+        server: optional(&server),
+        topic: optional(&topic),
+        token: optional(&token),
+        progress_interval_minutes,
+    };
+    if push_options.service != PushService::Off && push_options.target().is_none() {
+        return render_reelix_error(&ReelixError::Validation(
+            "ntfy needs a topic, Gotify needs a server and an app token".to_string(),
+        ));
+    }
+    *state.lock_push_options() = push_options;
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    Ok("Push settings updated successfully".to_string())
+}
+
+/// Radarr and Sonarr rescan uploaded movies and shows, blank settings turn them off.
+#[tauri::command]
+pub fn update_arr_settings(
+    radarr_url: String,
+    radarr_api_key: String,
+    sonarr_url: String,
+    sonarr_api_key: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let arr_options = ArrOptions {
+        radarr: ArrServer {
+            url: optional(&radarr_url),
+            api_key: optional(&radarr_api_key),
+        },
+        sonarr: ArrServer {
+            url: optional(&sonarr_url),
+            api_key: optional(&sonarr_api_key),
+        },
+    };
+    for (name, server) in [
+        ("Radarr", &arr_options.radarr),
+        ("Sonarr", &arr_options.sonarr),
+    ] {
+        if server.url.is_some() != server.api_key.is_some() {
+            return render_reelix_error(&ReelixError::Validation(format!(
+                "{name} needs both a url and an API key"
+            )));
+        }
+        if server
+            .url
+            .as_deref()
+            .is_some_and(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            return render_reelix_error(&ReelixError::Validation(format!(
+                "The {name} url has to start with http:// or https://"
+            )));
+        }
+    }
+    *state.lock_arr_options() = arr_options;
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    Ok("Radarr and Sonarr settings updated successfully".to_string())
+}
+
+#[tauri::command]
+pub fn update_trakt_settings(
+    client_id: String,
+    client_secret: String,
+    sync_collection: bool,
+    highlight_watchlist: bool,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    {
+        let mut trakt_options = state.lock_trakt_options();
+        // Checked before anything changes, a rejected save keeps the user signed in
+        let mut updated = trakt_options.clone();
+        let client_id = optional(&client_id);
+        // Tokens belong to the app they were issued for
+        if client_id != updated.client_id {
+            updated.sign_out();
+        }
+        updated.client_id = client_id;
+        updated.client_secret = optional(&client_secret);
+        updated.sync_collection = sync_collection;
+        updated.highlight_watchlist = highlight_watchlist;
+        if (sync_collection || highlight_watchlist) && updated.api().is_none() {
+            return render_reelix_error(&ReelixError::Validation(
+                "Trakt needs the client id and secret of your Trakt app".to_string(),
+            ));
+        }
+        *trakt_options = updated;
+    }
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    Ok("Trakt settings updated successfully".to_string())
+}
+
+/// Starts signing in to Trakt, returns the code to enter on the Trakt website. Reelix keeps
+/// checking in the background until the code was entered.
+#[tauri::command]
+pub async fn authorize_trakt(app_handle: tauri::AppHandle) -> Result<String, Error> {
+    match trakt::start_device_flow(&app_handle).await {
+        Ok(code) => Ok(format!(
+            "Go to {} and enter {}",
+            code.verification_url, code.user_code
+        )),
+        Err(message) => render_reelix_error(&message.into()),
+    }
+}
+
+#[tauri::command]
+pub fn disconnect_trakt(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    state.lock_trakt_options().sign_out();
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    Ok("Signed out of Trakt".to_string())
+}
+
+/// How dates, times and sizes are written, `locale` is a tag like "en-GB", empty for en-US.
+#[tauri::command]
+pub fn update_locale_settings(
+    locale: String,
+    binary_sizes: bool,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let valid = locale
+        .trim()
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return render_reelix_error(&ReelixError::Validation(format!(
+            "{locale} is not a locale, use a tag like en-US or de-DE"
+        )));
+    }
+    locale::set_current(Locale::new(&locale, binary_sizes));
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    Ok("Locale settings updated successfully".to_string())
+}
+
+/// "Do not disturb until", `minutes` from now, 0 turns it off
+#[tauri::command]
+pub fn update_do_not_disturb(minutes: u64, app_handle: tauri::AppHandle) -> Result<String, Error> {
+    notifications::set_do_not_disturb(&app_handle, DoNotDisturb::for_minutes(minutes));
+    if minutes == 0 {
+        Ok("Do not disturb turned off".to_string())
+    } else {
+        let until = chrono::Local::now() + chrono::Duration::minutes(minutes as i64);
+        Ok(format!(
+            "Do not disturb until {}",
+            locale::current().format_time(until.time())
+        ))
+    }
+}
+
+#[tauri::command]
+pub fn update_concurrency_settings(
+    max_concurrent_rips: usize,
+    max_concurrent_post_process: usize,
+    max_concurrent_uploads: usize,
+    state: State<'_, AppState>,
+    background_process_state: State<'_, BackgroundProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    if max_concurrent_rips == 0 || max_concurrent_post_process == 0 || max_concurrent_uploads == 0 {
+        return render_reelix_error(&ReelixError::Validation(
+            "Concurrency limits have to be at least 1".to_string(),
+        ));
+    }
+    let limits = ConcurrencyLimits {
+        rips: max_concurrent_rips,
+        post_process: max_concurrent_post_process,
+        uploads: max_concurrent_uploads,
+    };
+    *state.lock_concurrency_limits() = limits.clone();
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    // Queued jobs pick up a raised limit right away
+    background_process_state.governor.set_limits(limits);
+    Ok("Concurrency settings updated successfully".to_string())
+}
+
+#[tauri::command]
+pub fn update_network_settings(
+    offline: bool,
+    tmdb_timeout_seconds: u64,
+    github_timeout_seconds: u64,
+    ftp_timeout_seconds: u64,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    if tmdb_timeout_seconds == 0 || github_timeout_seconds == 0 || ftp_timeout_seconds == 0 {
+        return render_reelix_error(&ReelixError::Validation(
+            "Timeouts have to be at least 1 second".to_string(),
+        ));
+    }
+    let was_offline = state.network_options().offline;
+    *state.lock_network_options() = NetworkOptions {
+        offline,
+        tmdb_timeout_seconds,
+        github_timeout_seconds,
+        ftp_timeout_seconds,
+    };
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+
+    // Queued uploads notice on their own, the FTP status is refreshed right away
+    if was_offline && !offline {
+        ftp_validator::trigger_ftp_check(&app_handle);
+    }
+    Ok("Network settings updated successfully".to_string())
+}
+
+#[tauri::command]
+pub fn update_review_settings(
+    enabled: bool,
+    auto_approve_hours: Option<u64>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    *state.lock_review_options() = ReviewOptions {
+        enabled,
+        auto_approve_hours: auto_approve_hours.filter(|hours| *hours > 0),
+    };
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    Ok("Review settings updated successfully".to_string())
+}
+
+/// The server only reads its options when the app starts.
+#[tauri::command]
+pub fn update_remote_api_settings(
+    enabled: bool,
+    bind_address: String,
+    port: u16,
+    token: String,
+    metrics: bool,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let bind_address = bind_address.trim();
+    if bind_address.parse::<std::net::IpAddr>().is_err() {
+        return render_reelix_error(&ReelixError::Validation(format!(
+            "{bind_address} is not an IP address"
+        )));
+    }
+    if port == 0 {
+        return render_reelix_error(&ReelixError::Validation(
+            "The port has to be between 1 and 65535".to_string(),
+        ));
+    }
+    let token = token.trim();
+    let options = RemoteApiOptions {
+        enabled,
+        bind_address: bind_address.to_string(),
+        port,
+        token: (!token.is_empty()).then(|| token.to_string()),
+        metrics,
+    };
+    if options.enabled && !options.can_serve() {
+        return render_reelix_error(&ReelixError::Validation(format!(
+            "Set a token to open the remote API on {bind_address}"
+        )));
+    }
+    *state.lock_remote_api_options() = options;
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    Ok("Remote API settings updated, restart Reelix to apply them".to_string())
+}
+
+/// Replaces all library profiles, a job that picked a removed profile goes to the main library.
+#[tauri::command]
+pub fn update_library_profiles(
+    profiles: Vec<LibraryProfile>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let profiles: Vec<LibraryProfile> = profiles.into_iter().map(LibraryProfile::cleaned).collect();
+    if let Err(message) = library::validate(&profiles) {
+        return render_reelix_error(&ReelixError::Validation(message));
+    }
+    *state.lock_library_profiles() = profiles;
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    Ok("Library profiles updated successfully".to_string())
+}
+
+/// The fields of the ripper form, blank text fields are left unset
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RipperSettingsRequest {
+    pub backend: String,
+    pub ssh_host: Option<String>,
+    pub ssh_port: Option<u16>,
+    pub remote_makemkvcon: Option<String>,
+    pub local_share_dir: Option<String>,
+    pub remote_share_dir: Option<String>,
+    pub local_makemkvcon: Option<String>,
+    pub local_env: Option<String>,
+    pub episode_order: Option<bool>,
+}
+
+/// Picks where makemkvcon runs, `backend` is `local` or `ssh`. The SSH settings are kept
+/// when switching back to local so they don't have to be typed in again.
+///
+/// `local_makemkvcon` runs a makemkvcon installed outside of Reelix instead of the bundled
+/// one, with the `KEY=VALUE` lines of `local_env` added to its environment. It is only
+/// saved once `makemkvcon --version` answers with a MakeMKV version.
+#[tauri::command]
+pub async fn update_ripper_settings(
+    request: RipperSettingsRequest,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let RipperSettingsRequest {
+        backend,
+        ssh_host,
+        ssh_port,
+        remote_makemkvcon,
+        local_share_dir,
+        remote_share_dir,
+        local_makemkvcon,
+        local_env,
+        episode_order,
+    } = request;
+    let local_env = match ripper_backend::parse_env(&local_env.unwrap_or_default()) {
+        Ok(env) => env,
+        Err(message) => return render_reelix_error(&ReelixError::Validation(message)),
+    };
+    let ripper_options = RipperOptions {
+        kind: RipperKind::from_store_value(Some(backend.as_str())),
+        ssh_host: ssh_host.as_deref().and_then(optional),
+        ssh_port,
+        remote_makemkvcon: remote_makemkvcon.as_deref().and_then(optional),
+        local_share_dir: local_share_dir
+            .as_deref()
+            .and_then(optional)
+            .map(PathBuf::from),
+        remote_share_dir: remote_share_dir.as_deref().and_then(optional),
+        local_makemkvcon: local_makemkvcon
+            .as_deref()
+            .and_then(optional)
+            .map(PathBuf::from),
+        local_env,
+        episode_order: episode_order.unwrap_or(false),
+    };
+    if let Err(message) = ripper_options.backend() {
+        return render_reelix_error(&ReelixError::Validation(message));
+    }
+    let mut message = "Ripper settings updated successfully".to_string();
+    if let Some(path) = ripper_options.local_makemkvcon.clone() {
+        let env = ripper_options.local_env.clone();
+        let probe = blocking::run(move || ripper_backend::probe_version(&path, &env))
+            .await
+            .and_then(|result| result);
+        match probe {
+            Ok(version) => message = format!("{message}, using MakeMKV {version}"),
+            Err(message) => return render_reelix_error(&ReelixError::Validation(message)),
+        }
+    }
+    *state.lock_ripper_options() = ripper_options;
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    Ok(message)
+}
+
+#[tauri::command]
+pub fn update_tray_settings(
+    lock_during_rip: bool,
+    close_after_eject_seconds: Option<u64>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    *state.lock_tray_options() = TrayOptions {
+        lock_during_rip,
+        close_after_eject_seconds,
+    };
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    Ok("Tray settings updated successfully".to_string())
+}
+
+#[tauri::command]
+pub fn update_archive_settings(
+    archive_dir: String,
+    zip: bool,
+    zip_store_only: bool,
+    zip_volume_gb: Option<u64>,
+    keep_copies: Option<usize>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let archive_dir = archive_dir.trim();
+    *state.lock_archive_options() = ArchiveOptions {
+        archive_dir: if archive_dir.is_empty() {
+            ArchiveOptions::default().archive_dir
+        } else {
+            PathBuf::from(archive_dir)
+        },
+        zip,
+        zip_store_only,
+        // 0 GB volumes mean one zip
+        zip_volume_gb: zip_volume_gb.filter(|gb| *gb > 0),
+        // The copy just made is always kept
+        keep_copies: keep_copies.map(|copies| copies.max(1)),
+    };
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    Ok("Archive settings updated successfully".to_string())
+}
+
+/// Save the local movies and TV shows folders, both are created when missing and have to be
+/// writable. A folder low on space is saved with a warning.
+#[tauri::command]
+pub fn update_library_dirs(
+    movies_dir: String,
+    tv_shows_dir: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let movies_dir = PathBuf::from(movies_dir.trim());
+    let tv_shows_dir = PathBuf::from(tv_shows_dir.trim());
+    let mut warnings = Vec::new();
+    for dir in [&movies_dir, &tv_shows_dir] {
+        match library_dir::prepare(dir) {
+            Ok(warning) => warnings.extend(warning),
+            Err(message) => return render_reelix_error(&ReelixError::Validation(message)),
+        }
+    }
+    *state.movies_dir.write_or_recover() = movies_dir;
+    *state.tv_shows_dir.write_or_recover() = tv_shows_dir;
+
+    if let Err(message) = state.save(&app_handle) {
+        return render_reelix_error(&message.into());
+    }
+    warnings.sort();
+    warnings.dedup();
+    let mut message = "Library folders updated successfully".to_string();
+    for warning in warnings {
+        message.push_str(". ");
+        message.push_str(&warning);
+    }
+    Ok(message)
+}
+
+/// Ask for a folder with the system folder picker, returns the checked path for the form or an
+/// empty string when the picker was cancelled. `kind` is `movies` or `tv_shows`.
+#[tauri::command]
+pub async fn pick_library_dir(
+    kind: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, Error> {
+    let current = match kind.as_str() {
+        "movies" => state.movies_dir.read_or_recover().clone(),
+        "tv_shows" => state.tv_shows_dir.read_or_recover().clone(),
+        _ => {
+            return render_reelix_error(&ReelixError::Validation(format!(
+                "Unknown library folder {kind}"
+            )))
+        }
+    };
+    let dialog_handle = app_handle.clone();
+    let picked = blocking::run(move || {
+        let mut dialog = dialog_handle.dialog().file();
+        if current.is_dir() {
+            dialog = dialog.set_directory(current);
+        }
+        dialog.blocking_pick_folder()
+    })
+    .await;
+    let path = match picked {
+        Ok(Some(folder)) => match folder.into_path() {
+            Ok(path) => path,
+            Err(e) => {
+                return render_reelix_error(
+                    &format!("Failed to read the picked folder: {e}").into(),
+                )
+            }
+        },
+        Ok(None) => return Ok(String::new()),
+        Err(message) => return render_reelix_error(&message.into()),
+    };
+    match library_dir::prepare(&path) {
+        Ok(_) => Ok(path.to_string_lossy().to_string()),
+        Err(message) => render_reelix_error(&ReelixError::Validation(message)),
+    }
+}
+
+/// Import a CSV or JSON list of the titles already owned, or a Plex library export, picked
+/// with the system file picker. Search results and movie pages show them as owned.
+#[tauri::command]
+pub async fn import_collection(app_handle: tauri::AppHandle) -> Result<String, Error> {
+    let dialog_handle = app_handle.clone();
+    let picked = blocking::run(move || {
+        dialog_handle
+            .dialog()
+            .file()
+            .add_filter("Collection list", &["csv", "json"])
+            .blocking_pick_file()
+    })
+    .await;
+    let path = match picked {
+        Ok(Some(file)) => match file.into_path() {
+            Ok(path) => path,
+            Err(e) => {
+                return render_reelix_error(&format!("Failed to read the picked file: {e}").into())
+            }
+        },
+        Ok(None) => return Ok(String::new()),
+        Err(message) => return render_reelix_error(&message.into()),
+    };
+    match owned_collection::import(&app_handle, &path) {
+        Ok(added) => Ok(format!("Imported {added} owned titles")),
+        Err(message) => render_reelix_error(&ReelixError::Validation(message)),
+    }
+}
+
+#[tauri::command]
+pub async fn the_movie_db(
     key: &str,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, Error> {
-    state
-        .update(&app_handle, "the_movie_db_key", Some(key.to_string()))
-        .unwrap();
-    let response = search_multi(&state, "Avengers");
+    if let Err(e) = state.update(&app_handle, "the_movie_db_key", Some(key.to_string())) {
+        return render_reelix_error(&e.into());
+    }
+    let response = search_multi(&app_handle, "Avengers").await;
     match response {
         Ok(resp) => resp,
-        Err(e) => return render_error(&e.message),
+        Err(e) => return render_reelix_error(&e.into()),
     };
-    search::render_index(&app_handle)
+    search::render_index(&app_handle).await
+}
+
+/// A trimmed text field, `None` when it was left blank.
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
 }
 
 #[cfg(test)]
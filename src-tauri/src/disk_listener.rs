@@ -1,17 +1,26 @@
+use crate::models::disc_protection::DiscProtection;
 use crate::models::optical_disk_info::{DiskId, OpticalDiskInfo};
-use crate::services::drive_info::opticals;
-use crate::services::makemkvcon;
+use crate::services::drive_info::{opticals, remote};
+use crate::services::{
+    disc_memory, disc_prescan, makemkv_check, makemkvcon, notifications, rip_plan,
+};
 use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::drive_stats_state;
 use crate::state::job_state::{Job, JobStatus, JobType};
+use crate::state::locks::{MutexExt, RwLockExt};
 use crate::state::title_video::Video;
 use crate::state::AppState;
 use crate::templates;
-use log::debug;
+use crate::templates::toast::Toast;
+use log::{debug, error};
 use std::sync::{Arc, RwLock};
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::broadcast;
 use tokio::time::{sleep, Duration};
 
+const DISC_REMOVED_MESSAGE: &str =
+    "Disc removed while it was being read, insert it again to start over";
+
 fn changes(
     current_opticals: &[OpticalDiskInfo],
     previous_opticals: &[OpticalDiskInfo],
@@ -29,11 +38,57 @@ fn changes(
     optics
 }
 
-pub async fn watch_for_changes(sender: broadcast::Sender<Vec<diff::Result<OpticalDiskInfo>>>) {
+/// Drives come from the OS, or from makemkvcon when it runs on another machine.
+/// `None` means the drives could not be looked at this time and nothing changed.
+async fn current_opticals(
+    app_handle: &AppHandle,
+    previous_opticals: &[OpticalDiskInfo],
+) -> Option<Vec<OpticalDiskInfo>> {
+    let ripper_options = app_handle.state::<AppState>().ripper_options();
+    match ripper_options.backend() {
+        Ok(backend) if backend.lists_drives() => {
+            if holds_remote_drive(app_handle, previous_opticals) {
+                debug!(
+                    "Skipping the drive poll of {} while it rips",
+                    backend.name()
+                );
+                return None;
+            }
+            remote::opticals(app_handle, &*backend).await
+        }
+        Ok(_) => Some(opticals()),
+        Err(e) => {
+            debug!("Can't list drives: {e}");
+            Some(Vec::new())
+        }
+    }
+}
+
+/// A second makemkvcon on the remote would fight the running one for the drive.
+fn holds_remote_drive(app_handle: &AppHandle, previous_opticals: &[OpticalDiskInfo]) -> bool {
+    let background_process_state = app_handle.state::<BackgroundProcessState>();
+    previous_opticals
+        .iter()
+        .filter(|disk| disk.is_remote())
+        .filter_map(|disk| find_optical_disk_id(app_handle, disk))
+        .any(|disk_id| {
+            background_process_state
+                .find_job(Some(disk_id), &None, &[JobStatus::Processing])
+                .is_some()
+        })
+}
+
+pub async fn watch_for_changes(
+    app_handle: AppHandle,
+    sender: broadcast::Sender<Vec<diff::Result<OpticalDiskInfo>>>,
+) {
     let mut previous_opticals = Vec::new();
     debug!("Stared watching for changes to optical Disks....");
     loop {
-        let current_opticals = opticals();
+        let Some(current_opticals) = current_opticals(&app_handle, &previous_opticals).await else {
+            sleep(Duration::from_secs(5)).await;
+            continue;
+        };
 
         if current_opticals != previous_opticals {
             let diff_result = changes(&current_opticals, &previous_opticals);
@@ -51,10 +106,7 @@ pub async fn watch_for_changes(sender: broadcast::Sender<Vec<diff::Result<Optica
 }
 
 fn emit_disk_titles_change(app_handle: &AppHandle) {
-    let app_state = app_handle.state::<AppState>();
-    let background_process_state = app_handle.state::<BackgroundProcessState>();
-
-    let result = templates::disk_titles::render_options(&app_state, &background_process_state)
+    let result = templates::disk_titles::render_options(app_handle)
         .expect("Failed to render disk_titles/options");
     app_handle
         .emit("disks-changed", result)
@@ -62,7 +114,7 @@ fn emit_disk_titles_change(app_handle: &AppHandle) {
 }
 
 fn unwrap_disk(disk: &Arc<RwLock<OpticalDiskInfo>>) -> OpticalDiskInfo {
-    disk.read().expect("Failed to lock").clone()
+    disk.read_or_recover().clone()
 }
 
 fn contains(
@@ -80,56 +132,82 @@ async fn load_titles(app_handle: &AppHandle, job: &Arc<RwLock<Job>>) {
         '_,
         crate::state::background_process_state::BackgroundProcessState,
     > = app_handle.state();
-    job.write()
-        .expect("failed to lock job for write")
-        .update_status(JobStatus::Processing);
-    job.read()
-        .expect("failed to lock job for read")
-        .emit_progress_change(app_handle);
+    let is_audio_cd = job
+        .read_or_recover()
+        .disk
+        .as_ref()
+        .is_some_and(|disk| disk.is_audio_cd());
+    if is_audio_cd {
+        // makemkvcon would only fail with "no titles", say what the disc actually is
+        job.write_or_recover().update_status(JobStatus::Error);
+        job.write_or_recover().update_message(
+            "This is an audio CD, Reelix rips DVDs and Blu-rays only. Use a CD ripper for music.",
+        );
+        job.read_or_recover().emit_progress_change(app_handle);
+        return;
+    }
+    job.write_or_recover().update_status(JobStatus::Processing);
+    job.read_or_recover().emit_progress_change(app_handle);
     let results = match makemkvcon::title_info(app_handle, job).await {
         Ok(run_result) => run_result,
         Err(message) => {
             debug!("failed to load titles: {message}");
-            job.write()
-                .expect("failed to lock job for write")
-                .update_status(JobStatus::Error);
-            job.write()
-                .expect("failed to lock job for write")
+            if job.read_or_recover().disc_removed {
+                // abort_jobs_for_removed_disk already failed the job
+                return;
+            }
+            if let Some(disk) = job.read_or_recover().disk.as_ref() {
+                let drive = disk.drive_key();
+                drive_stats_state::record(app_handle, |stats| {
+                    stats.record_failure(app_handle, &drive)
+                });
+            }
+            job.write_or_recover().update_status(JobStatus::Error);
+            job.write_or_recover()
                 .update_message(&format!("Failed to load titles: {message}"));
-            job.read()
-                .expect("failed to lock job for read")
-                .emit_progress_change(app_handle);
+            job.read_or_recover().emit_progress_change(app_handle);
             return;
         }
     };
 
     let disk_id = job
-        .read()
-        .expect("failed to lock job for read")
+        .read_or_recover()
         .disk
         .as_ref()
         .expect("There should of been a disk")
         .id;
 
-    match state.find_optical_disk_by_id(&disk_id) {
+    let loaded_disk = match state.find_optical_disk_by_id(&disk_id) {
         Some(disk) => {
-            let locked_disk = disk.write().expect("Failed to grab disk");
+            let mut locked_disk = disk.write_or_recover();
+            locked_disk.protection = Some(DiscProtection::from_messages(&results.messages));
+            let index = locked_disk.index as i32;
+            if let Some(drv) = results.drives.iter().find(|drv| drv.index == index) {
+                locked_disk.drive_name = Some(drv.drive_name.clone());
+            }
+            let drive = locked_disk.drive_key();
+            drive_stats_state::record(app_handle, |stats| {
+                stats.record_disc_read(app_handle, &drive, results.read_error_count())
+            });
             locked_disk
                 .titles
-                .lock()
-                .expect("failed to get titles")
+                .lock_or_recover()
                 .extend(results.title_infos);
+            Some(locked_disk.clone())
         }
-        None => debug!("Disk not found in state."),
-    }
-    job.write()
-        .expect("failed to lock job for write")
-        .update_status(JobStatus::Finished);
-    job.read()
-        .expect("failed to lock job for read")
-        .emit_progress_change(app_handle);
+        None => {
+            debug!("Disk not found in state.");
+            None
+        }
+    };
+    job.write_or_recover().update_status(JobStatus::Finished);
+    job.read_or_recover().emit_progress_change(app_handle);
     templates::disks::emit_disk_change(app_handle);
 
+    if let Some(disk) = &loaded_disk {
+        rip_plan::prepare_for_disc(app_handle, disk).await;
+    }
+
     if let Some(auto_rip_job) = background_process_state.find_job(
         Some(disk_id),
         &Some(JobType::Ripping),
@@ -146,7 +224,7 @@ fn auto_rip_if_ready(
     auto_rip_job: Arc<RwLock<Job>>,
 ) {
     debug!("auto_rip_if_ready: checking disk {disk_id}");
-    let job_ref = auto_rip_job.read().expect("Failed to lock job for read");
+    let job_ref = auto_rip_job.read_or_recover();
 
     if !job_ref.has_incomplete_titles() {
         debug!(
@@ -158,18 +236,28 @@ fn auto_rip_if_ready(
 
     drop(job_ref);
 
+    if let Some(check) = makemkv_check::current(app_handle).filter(|c| c.blocks_auto_rip()) {
+        debug!("auto_rip_if_ready: {}", check.label());
+        notifications::emit_toast(
+            app_handle,
+            Toast::warning("Auto-rip held back", check.label())
+                .with_action("Diagnostics", "/drive_health"),
+        );
+        return;
+    }
+
     if let Some(disk) = state.find_optical_disk_by_id(&disk_id) {
-        let disk_lock = disk.read().expect("Failed to lock disk for read");
-        let titles = disk_lock.titles.lock().expect("Failed to lock titles");
+        let disk_lock = disk.read_or_recover();
+        let titles = disk_lock.titles.lock_or_recover();
         debug!(
             "auto_rip_if_ready: found {count} titles for disk {disk_id}",
             count = titles.len()
         );
-        let job_write = auto_rip_job.write().expect("Failed to lock job for write");
+        let job_write = auto_rip_job.write_or_recover();
         let mut matched = 0usize;
 
         for title_video in &job_write.title_videos {
-            let mut tv = title_video.write().expect("Failed to lock title_video");
+            let mut tv = title_video.write_or_recover();
             if tv.title.is_none() {
                 if let Video::Movie(movie_edition) = &tv.video {
                     let runtime_range = movie_edition.runtime_range();
@@ -201,10 +289,7 @@ fn auto_rip_if_ready(
 fn add_optical_disk(app_handle: &AppHandle, disk: &OpticalDiskInfo) {
     let state: tauri::State<'_, AppState> = app_handle.state::<AppState>();
     let optical_disk = Arc::new(RwLock::new(disk.clone()));
-    let mut optical_disks = state
-        .optical_disks
-        .write()
-        .expect("Failed to grab optical disks");
+    let mut optical_disks = state.optical_disks.write_or_recover();
     if !contains(&optical_disks, &optical_disk) {
         optical_disks.push(optical_disk);
     }
@@ -212,14 +297,9 @@ fn add_optical_disk(app_handle: &AppHandle, disk: &OpticalDiskInfo) {
 
 fn remove_optical_disks(app_handle: &AppHandle, disk: &OpticalDiskInfo) {
     let state: tauri::State<'_, AppState> = app_handle.state::<AppState>();
-    let mut optical_disks = state
-        .optical_disks
-        .write()
-        .expect("Failed to grab optical disks");
+    let mut optical_disks = state.optical_disks.write_or_recover();
     optical_disks.retain(|optical_disk_info| {
-        let optical_disk = optical_disk_info
-            .read()
-            .expect("Failed to grab optical disk info");
+        let optical_disk = optical_disk_info.read_or_recover();
 
         if *optical_disk == *disk {
             optical_disk.kill_process();
@@ -230,12 +310,61 @@ fn remove_optical_disks(app_handle: &AppHandle, disk: &OpticalDiskInfo) {
     });
 }
 
+/// The watcher builds new `OpticalDiskInfo`s on every scan, so the id of a removed
+/// disk has to come from the copy kept in the app state.
+fn find_optical_disk_id(app_handle: &AppHandle, disk: &OpticalDiskInfo) -> Option<DiskId> {
+    let state: tauri::State<'_, AppState> = app_handle.state::<AppState>();
+    let optical_disks = state.optical_disks.read_or_recover();
+    optical_disks.iter().find_map(|optical_disk| {
+        let optical_disk = optical_disk.read_or_recover();
+        (*optical_disk == *disk).then_some(optical_disk.id)
+    })
+}
+
+/// The disc was pulled while a job was using it. By now makemkvcon has been killed by
+/// `remove_optical_disks`, fail the job with a message that says why and remove the half
+/// written title so it is never renamed or uploaded.
+fn abort_jobs_for_removed_disk(app_handle: &AppHandle, disk_id: DiskId) {
+    let app_state = app_handle.state::<AppState>();
+    let background_process_state = app_handle.state::<BackgroundProcessState>();
+    while let Some(job) =
+        background_process_state.find_job(Some(disk_id), &None, &[JobStatus::Processing])
+    {
+        let current_title_video = {
+            let mut job_guard = job.write_or_recover();
+            debug!("Disc removed while job {} was processing", job_guard.id);
+            job_guard.disc_removed = true;
+            job_guard.update_status(JobStatus::Error);
+            job_guard.update_message(DISC_REMOVED_MESSAGE);
+            job_guard.subtitle = Some("Disc Removed".to_string());
+            // A title that is being joined, moved or post-processed is complete already
+            let ripping_title = job_guard.ripping_title;
+            job_guard
+                .current_title_video_id
+                .filter(|_| ripping_title)
+                .and_then(|id| {
+                    job_guard
+                        .title_videos
+                        .iter()
+                        .find(|title_video| title_video.read_or_recover().id == id)
+                        .cloned()
+                })
+        };
+        if let Some(title_video) = current_title_video {
+            match title_video.read_or_recover().remove_partial_rip(&app_state) {
+                Ok(true) => debug!("Removed partial rip for job"),
+                Ok(false) => {}
+                Err(e) => error!("{e}"),
+            }
+        }
+        job.read_or_recover().emit_progress_change(app_handle);
+    }
+    background_process_state.emit_jobs_changed(app_handle);
+}
+
 pub fn set_default_selected_disk(app_handle: &AppHandle, disk_id: DiskId) {
     let state = app_handle.state::<AppState>();
-    let mut selected_optical_disk_id = state
-        .selected_optical_disk_id
-        .write()
-        .expect("failed to lock selected disk ID");
+    let mut selected_optical_disk_id = state.selected_optical_disk_id.write_or_recover();
     if selected_optical_disk_id.is_none() {
         debug!("changed default selected optical disk to {disk_id}");
         *selected_optical_disk_id = Some(disk_id);
@@ -244,10 +373,7 @@ pub fn set_default_selected_disk(app_handle: &AppHandle, disk_id: DiskId) {
 
 pub fn clear_selected_disk(app_handle: &AppHandle, disk_id: DiskId) {
     let state = app_handle.state::<AppState>();
-    let mut selected_optical_disk_id = state
-        .selected_optical_disk_id
-        .write()
-        .expect("failed to lock selected disk ID");
+    let mut selected_optical_disk_id = state.selected_optical_disk_id.write_or_recover();
 
     if selected_optical_disk_id.as_ref() == Some(&disk_id) {
         *selected_optical_disk_id = None;
@@ -268,8 +394,12 @@ pub async fn handle_changes(
                     match result {
                         diff::Result::Left(disk) => {
                             debug!("- {:?}", disk.name);
+                            let removed_disk_id = find_optical_disk_id(&app_handle, &disk);
                             clear_selected_disk(&app_handle, disk.id);
                             remove_optical_disks(&app_handle, &disk);
+                            if let Some(disk_id) = removed_disk_id {
+                                abort_jobs_for_removed_disk(&app_handle, disk_id);
+                            }
                             templates::disks::emit_disk_change(&app_handle);
                             emit_disk_titles_change(&app_handle);
                         }
@@ -291,12 +421,18 @@ pub async fn handle_changes(
                                     Some(disk.clone()),
                                 );
                                 background_process_state.emit_jobs_changed(&app_handle_clone);
-                                job.write().expect("failed to lock job for write").title =
+                                job.write_or_recover().title =
                                     Some(format!("Loading Titles for {}", disk.name));
-                                job.read()
-                                    .expect("failed to lock job for read")
+                                job.read_or_recover()
                                     .emit_progress_change(&app_handle_clone);
-                                load_titles(&app_handle_clone, &job).await;
+                                disc_memory::suggest_on_insert(&app_handle_clone, &disk);
+                                let (_, label_search) = tokio::join!(
+                                    load_titles(&app_handle_clone, &job),
+                                    disc_prescan::search_label(&app_handle_clone, &disk)
+                                );
+                                if let Some(label_search) = label_search {
+                                    disc_prescan::announce(&app_handle_clone, &disk, label_search);
+                                }
                                 emit_disk_titles_change(&app_handle_clone);
                                 templates::disks::emit_disk_change(&app_handle_clone);
                             });
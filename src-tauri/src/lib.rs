@@ -1,13 +1,20 @@
 use crate::models::optical_disk_info::OpticalDiskInfo;
 use crate::services::auto_complete;
 use crate::services::ftp_validator::spawn_ftp_validator;
+use crate::services::makemkv_check::spawn_makemkv_check;
+use crate::services::notifications::{self, DoNotDisturb, DoNotDisturbMenuItem};
+use crate::services::search_suggest::SearchSuggestState;
+use crate::services::tray_menu::{self, TrayMenu};
 use crate::services::version_checker::spawn_version_checker;
 use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::drive_stats_state::DriveStatsState;
+use crate::state::locks::RwLockExt;
 use crate::state::uploaded_state::UploadedState;
 use state::AppState;
-use tauri::menu::{Menu, MenuItem};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem};
 use tauri::tray::TrayIconBuilder;
-use tauri::{App, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::{App, AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_log::log::{debug, error, LevelFilter};
 use tauri_plugin_log::{Target, TargetKind};
 use tauri_plugin_opener::OpenerExt;
@@ -17,6 +24,7 @@ mod commands;
 mod disk_listener;
 mod models;
 mod progress_tracker;
+mod reelix_error;
 mod services;
 mod standard_error;
 mod state;
@@ -31,8 +39,9 @@ const ICON_BYTES: &[u8] = include_bytes!("../icons/menu-icon.png");
 
 fn spawn_disk_listener(app: &mut App) {
     let (sender, receiver) = broadcast::channel::<Vec<diff::Result<OpticalDiskInfo>>>(16);
+    let watcher_handle = app.handle().clone();
     tauri::async_runtime::spawn(async move {
-        disk_listener::watch_for_changes(sender).await;
+        disk_listener::watch_for_changes(watcher_handle, sender).await;
     });
 
     let app_handle = app.handle().clone();
@@ -47,38 +56,128 @@ fn setup_store(app: &mut App) {
     if let Err(e) = state.load_from_store(app_handle) {
         error!("Failed to load state from store: {e}");
     }
+    app_handle
+        .state::<BackgroundProcessState>()
+        .governor
+        .set_limits(state.concurrency_limits());
 }
 
+/// Managed before anything that can start a rip or an upload, every command can rely on it.
 fn setup_uploaded_state(app: &mut App) {
-    let uploaded_state = match UploadedState::new(app.handle()) {
-        Ok(state) => state,
-        Err(e) => {
-            eprintln!("Failed to initialize UploadedState: {e}");
-            UploadedState::new(app.handle()).unwrap()
-        }
-    };
-    app.manage(uploaded_state);
+    app.manage(UploadedState::load(app.handle()));
+}
+
+fn spawn_upload_recovery(app: &mut App) {
     let app_handle = app.handle().clone();
     tauri::async_runtime::spawn(async move {
         services::upload_recovery::resume_pending_uploads(app_handle).await;
     });
 }
-/// Custom filter that formats a datetime string into "YYYY"
-// pub fn to_year(value: &Value, _args: &HashMap<String, Value>) -> TeraResult<Value> {
-//     let date_str = value
-//         .as_str()
-//         .ok_or("format_date filter: expected a string")?;
-//     // Try parsing the string as an RFC3339 datetime.
-//     let formatted = if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
-//         dt.format("%Y").to_string()
-//     } else if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-//         // Fallback: if it's already just a date, use it.
-//         date.format("%Y").to_string()
-//     } else {
-//         return Err(format!("format_date filter: failed to parse date: {}", date_str).into());
-//     };
-//     to_value(formatted).map_err(Into::into)
-// }
+
+fn spawn_job_snapshots(app: &mut App) {
+    services::job_snapshots::recover(app.handle());
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        services::job_snapshots::watch(app_handle).await;
+    });
+}
+
+fn spawn_emit_coalescer(app: &mut App) {
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        BackgroundProcessState::flush_emits(app_handle).await;
+    });
+}
+
+fn spawn_remote_api(app: &mut App) {
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        services::remote_api::serve(app_handle).await;
+    });
+}
+
+fn spawn_trakt_watchlist(app: &mut App) {
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        services::trakt::refresh_watchlist(&app_handle).await;
+    });
+}
+
+fn spawn_do_not_disturb_watcher(app: &mut App) {
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        notifications::watch_do_not_disturb(app_handle).await;
+    });
+}
+
+fn setup_drive_stats_state(app: &mut App) {
+    app.manage(DriveStatsState::load(app.handle()));
+}
+
+/// Brings the window back from the tray, or from behind other windows.
+fn show_main_window(app_handle: &AppHandle) {
+    let Some(webview_window) = app_handle.get_webview_window("main") else {
+        debug!("No main window to show");
+        return;
+    };
+    if let Err(e) = webview_window
+        .show()
+        .and_then(|_| webview_window.unminimize())
+        .and_then(|_| webview_window.set_focus())
+    {
+        debug!("Failed to show window: {e}");
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SecondInstance {
+    args: Vec<String>,
+    cwd: String,
+}
+
+/// Reelix was started again. A second disk watcher would race this one for the drives, so
+/// the new process exits and its arguments are handed to this one as a `second-instance`
+/// event.
+fn on_second_instance(app_handle: &AppHandle, args: Vec<String>, cwd: String) {
+    debug!("Reelix was started again with {args:?} in {cwd}");
+    show_main_window(app_handle);
+    if let Err(e) = app_handle.emit("second-instance", SecondInstance { args, cwd }) {
+        error!("Failed to forward the arguments of the second instance: {e}");
+    }
+}
+
+/// `reelix://` links open their page in the main window. On macOS the system hands the link
+/// to the running app, on Windows and Linux a second Reelix is started with it and the single
+/// instance plugin forwards it here. A link Reelix was started with is shown by `index`.
+fn setup_deep_link(app: &mut App) {
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        error!("Failed to register the reelix:// links: {e}");
+    }
+
+    let app_handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        let Some(url) = event.urls().last().map(|url| url.to_string()) else {
+            return;
+        };
+        debug!("Opening {url}");
+        show_main_window(&app_handle);
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let app_state = app_handle.state::<AppState>();
+            let turbo = commands::general::open_deep_link(url, app_handle.clone(), app_state)
+                .await
+                .unwrap_or_else(|e| {
+                    error!("Failed to open the link: {}", e.message);
+                    String::new()
+                });
+            if let Err(e) = app_handle.emit("disks-changed", turbo) {
+                error!("Failed to show the linked page: {e}");
+            }
+        });
+    });
+}
+
 fn setup_tray_icon(app: &mut App) {
     let version_label = format!("Version {}", app.package_info().version);
     let version_i = MenuItem::with_id(app, "version", version_label, true, None::<&str>)
@@ -87,10 +186,22 @@ fn setup_tray_icon(app: &mut App) {
         .expect("failed to create quit item");
     let show_i = MenuItem::with_id(app, "show", "Show", true, None::<&str>)
         .expect("failed to create show item");
-    let menu = Menu::with_items(app, &[&show_i, &version_i, &quit_i])
+    let do_not_disturb_i = CheckMenuItem::with_id(
+        app,
+        "do_not_disturb",
+        "Do Not Disturb",
+        true,
+        notifications::do_not_disturb_active(app.handle()),
+        None::<&str>,
+    )
+    .expect("failed to create do not disturb item");
+    let menu = Menu::with_items(app, &[&show_i, &do_not_disturb_i, &version_i, &quit_i])
         .expect("Failed to define menu with items");
+    app.manage(DoNotDisturbMenuItem(do_not_disturb_i.clone()));
+    // Jobs and drives are added once they change, see `tray_menu::refresh`
+    app.manage(TrayMenu::new(show_i, do_not_disturb_i, version_i, quit_i));
     let tray_icon = tauri::image::Image::from_bytes(ICON_BYTES).expect("failure to load tray icon");
-    TrayIconBuilder::new()
+    TrayIconBuilder::with_id(tray_menu::TRAY_ID)
         .icon(tray_icon)
         .menu(&menu)
         .show_menu_on_left_click(true)
@@ -98,18 +209,15 @@ fn setup_tray_icon(app: &mut App) {
             "quit" => {
                 app.exit(0);
             }
-            "show" => {
-                let webview_window = app
-                    .get_webview_window("main")
-                    .expect("failed to find main window");
-                match webview_window.show() {
-                    Ok(_e) => {
-                        let _ = webview_window.set_focus();
-                    }
-                    Err(_e) => {
-                        debug!("Failed to show window");
-                    }
+            // A job opens the window with the job list
+            id if id == "show" || tray_menu::is_job_item(id) => show_main_window(app),
+            "do_not_disturb" => {
+                // The check mark has already been toggled by the click
+                let do_not_disturb = match app.try_state::<DoNotDisturbMenuItem>() {
+                    Some(item) if item.0.is_checked().unwrap_or(false) => DoNotDisturb::On,
+                    _ => DoNotDisturb::Off,
                 };
+                notifications::set_do_not_disturb(app.app_handle(), do_not_disturb);
             }
             "version" => {
                 let app_handle: &tauri::AppHandle = app.app_handle();
@@ -119,6 +227,7 @@ fn setup_tray_icon(app: &mut App) {
                     .map_err(|e| error!("Failed to open URL: {e}"))
                     .ok();
             }
+            id if tray_menu::eject(app.app_handle(), id) => {}
             _ => {
                 debug!("menu item {:?} not handled", event.id);
             }
@@ -173,6 +282,9 @@ fn setup_view_window(app: &mut App) {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let app = tauri::Builder::default()
+        // Registered first so a second launch exits before it sets anything else up
+        .plugin(tauri_plugin_single_instance::init(on_second_instance))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
@@ -191,14 +303,24 @@ pub fn run() {
         )
         .manage(AppState::new())
         .manage(BackgroundProcessState::new())
+        .manage(SearchSuggestState::default())
         .setup(|app| {
             setup_store(app);
+            setup_uploaded_state(app);
+            setup_drive_stats_state(app);
+            spawn_emit_coalescer(app);
             spawn_disk_listener(app);
             spawn_version_checker(app);
+            spawn_makemkv_check(app);
             spawn_ftp_validator(app.handle());
             setup_tray_icon(app);
+            spawn_do_not_disturb_watcher(app);
             setup_view_window(app);
-            setup_uploaded_state(app);
+            setup_deep_link(app);
+            spawn_upload_recovery(app);
+            spawn_job_snapshots(app);
+            spawn_remote_api(app);
+            spawn_trakt_watchlist(app);
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -215,15 +337,13 @@ pub fn run() {
     // Run the application with a run event callback to shutdown sidecar process
     app.run(|app_handle, event| {
         if let tauri::RunEvent::Exit = event {
+            services::job_snapshots::save_now(app_handle);
             let state = app_handle.state::<AppState>();
-            let disks = state
-                .optical_disks
-                .read()
-                .expect("Failed to get lock on optical_disks");
+            let disks = state.optical_disks.read_or_recover();
 
             // Iterate over the optical disks and kill the associated PID if it exists
             for disk in disks.iter() {
-                let locked_disk = disk.read().expect("failed to get lock on disk");
+                let locked_disk = disk.read_or_recover();
                 locked_disk.kill_process();
             }
         }
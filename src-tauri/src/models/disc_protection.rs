@@ -0,0 +1,246 @@
+use super::mkv::MSG;
+use serde::Serialize;
+use std::fmt;
+
+/// Messages makemkvcon prints when the keys it has can't decrypt the disc.
+/// Updating MakeMKV (or its key database) fixes these, the drive is fine.
+const NEEDS_KEYS_MESSAGES: [&str; 5] = [
+    "The volume key is unknown for this disc",
+    "uses newer version of AACS protection",
+    "current AACS host certificate is revoked",
+    "No SVQ files were found",
+    "No matching SVQ files were found",
+];
+
+/// Any other decryption problem, usually the drive or a damaged disc.
+const DECRYPTION_FAILED_MESSAGES: [&str; 5] = [
+    "AACS Auth Error",
+    "Decryption error",
+    "Failed to process BD+ code",
+    "AACS-protected discs that use SKB are not supported",
+    "Disc contains BD+ without AACS",
+];
+
+const UNENCRYPTED_MESSAGE: &str = "AACS directory not present, assuming unencrypted disc";
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub enum ProtectionScheme {
+    Css,
+    Aacs,
+    BdPlus,
+}
+
+impl fmt::Display for ProtectionScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtectionScheme::Css => write!(f, "CSS"),
+            ProtectionScheme::Aacs => write!(f, "AACS"),
+            ProtectionScheme::BdPlus => write!(f, "BD+"),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub enum DecryptionStatus {
+    /// No errors were reported while makemkvcon read the disc
+    Succeeded,
+    /// The disc isn't encrypted at all
+    NotEncrypted,
+    /// makemkvcon needs newer keys / program version for this disc
+    NeedsKeys(String),
+    /// Decryption failed for another reason, likely the drive or the disc
+    Failed(String),
+}
+
+/// Copy protection details gathered from makemkvcon messages while scanning a disc.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct DiscProtection {
+    pub schemes: Vec<ProtectionScheme>,
+    pub decryption: DecryptionStatus,
+    /// e.g. `v1.17.7 linux(x64-release)`
+    pub makemkv_version: Option<String>,
+    /// Highest AACS MKB version makemkvcon has keys for, e.g. `v77`
+    pub aacs_version: Option<String>,
+}
+
+impl DiscProtection {
+    pub fn from_messages(messages: &[MSG]) -> Self {
+        let texts: Vec<&str> = messages.iter().map(|m| m.message.as_str()).collect();
+        Self::from_texts(&texts)
+    }
+
+    fn from_texts(texts: &[&str]) -> Self {
+        let mut protection = DiscProtection {
+            schemes: Vec::new(),
+            decryption: DecryptionStatus::Succeeded,
+            makemkv_version: None,
+            aacs_version: None,
+        };
+
+        for text in texts {
+            if let Some(version) = parse_makemkv_version(text) {
+                protection.makemkv_version = Some(version);
+            }
+            if let Some(version) = parse_aacs_version(text) {
+                protection.aacs_version = Some(version);
+            }
+
+            if text.contains(UNENCRYPTED_MESSAGE) {
+                if protection.decryption == DecryptionStatus::Succeeded {
+                    protection.decryption = DecryptionStatus::NotEncrypted;
+                }
+                continue;
+            }
+            if text.contains("AACS") {
+                protection.add_scheme(ProtectionScheme::Aacs);
+            }
+            if text.contains("BD+") {
+                protection.add_scheme(ProtectionScheme::BdPlus);
+            }
+            if text.contains("CSS") {
+                protection.add_scheme(ProtectionScheme::Css);
+            }
+
+            // The first failure is the most useful one, later ones tend to be consequences
+            if matches!(
+                protection.decryption,
+                DecryptionStatus::NeedsKeys(_) | DecryptionStatus::Failed(_)
+            ) {
+                continue;
+            }
+            if NEEDS_KEYS_MESSAGES.iter().any(|m| text.contains(m)) {
+                protection.decryption = DecryptionStatus::NeedsKeys(text.to_string());
+            } else if DECRYPTION_FAILED_MESSAGES.iter().any(|m| text.contains(m)) {
+                protection.decryption = DecryptionStatus::Failed(text.to_string());
+            }
+        }
+        protection
+    }
+
+    fn add_scheme(&mut self, scheme: ProtectionScheme) {
+        if !self.schemes.contains(&scheme) {
+            self.schemes.push(scheme);
+        }
+    }
+
+    pub fn decrypted(&self) -> bool {
+        matches!(
+            self.decryption,
+            DecryptionStatus::Succeeded | DecryptionStatus::NotEncrypted
+        )
+    }
+
+    /// Short label for the disk selector, e.g. "AACS, BD+ • Needs new keys"
+    pub fn summary(&self) -> String {
+        let schemes = if self.schemes.is_empty() {
+            "No protection reported".to_string()
+        } else {
+            self.schemes
+                .iter()
+                .map(ProtectionScheme::to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let status = match self.decryption {
+            DecryptionStatus::Succeeded => "Decrypted",
+            DecryptionStatus::NotEncrypted => "Not encrypted",
+            DecryptionStatus::NeedsKeys(_) => "Needs new keys",
+            DecryptionStatus::Failed(_) => "Decryption failed",
+        };
+        format!("{schemes} • {status}")
+    }
+
+    /// Longer explanation including versions, used as a tooltip.
+    pub fn details(&self) -> String {
+        let mut details = Vec::new();
+        match &self.decryption {
+            DecryptionStatus::NeedsKeys(message) => details.push(format!(
+                "{message}. Update MakeMKV to get newer keys, the drive is not the problem."
+            )),
+            DecryptionStatus::Failed(message) => details.push(format!(
+                "{message}. This is usually the drive or a damaged disc."
+            )),
+            _ => {}
+        }
+        if let Some(version) = &self.makemkv_version {
+            details.push(format!("MakeMKV {version}"));
+        }
+        if let Some(version) = &self.aacs_version {
+            details.push(format!("AACS keys {version}"));
+        }
+        details.join(" • ")
+    }
+}
+
+/// "MakeMKV v1.17.7 linux(x64-release) started" -> "v1.17.7 linux(x64-release)"
+fn parse_makemkv_version(text: &str) -> Option<String> {
+    let version = text.strip_prefix("MakeMKV ")?.strip_suffix(" started")?;
+    version.starts_with('v').then(|| version.to_string())
+}
+
+/// "Highest AACS version is v77 , MKB saved as ..." -> "v77"
+fn parse_aacs_version(text: &str) -> Option<String> {
+    let rest = text.split("AACS version is ").nth(1)?;
+    rest.split_whitespace()
+        .next()
+        .map(|version| version.trim_end_matches(',').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_versions_and_schemes() {
+        let protection = DiscProtection::from_texts(&[
+            "MakeMKV v1.17.7 linux(x64-release) started",
+            "Highest AACS version is v77 , MKB saved as /tmp/MKB_v77_save.tgz",
+            "Processing BD+ code, please be patient - this may take up to few minutes",
+        ]);
+
+        assert_eq!(
+            protection.makemkv_version.as_deref(),
+            Some("v1.17.7 linux(x64-release)")
+        );
+        assert_eq!(protection.aacs_version.as_deref(), Some("v77"));
+        assert_eq!(
+            protection.schemes,
+            vec![ProtectionScheme::Aacs, ProtectionScheme::BdPlus]
+        );
+        assert!(protection.decrypted());
+        assert_eq!(protection.summary(), "AACS, BD+ • Decrypted");
+    }
+
+    #[test]
+    fn unknown_volume_key_needs_keys() {
+        let protection = DiscProtection::from_texts(&[
+            "The volume key is unknown for this disc - video can't be decrypted",
+            "Decryption error",
+        ]);
+
+        assert_eq!(
+            protection.decryption,
+            DecryptionStatus::NeedsKeys(
+                "The volume key is unknown for this disc - video can't be decrypted".to_string()
+            )
+        );
+        assert!(!protection.decrypted());
+    }
+
+    #[test]
+    fn auth_error_is_a_failure() {
+        let protection = DiscProtection::from_texts(&["AACS Auth Error"]);
+        assert_eq!(
+            protection.decryption,
+            DecryptionStatus::Failed("AACS Auth Error".to_string())
+        );
+        assert_eq!(protection.summary(), "AACS • Decryption failed");
+    }
+
+    #[test]
+    fn unencrypted_disc() {
+        let protection = DiscProtection::from_texts(&[UNENCRYPTED_MESSAGE]);
+        assert_eq!(protection.decryption, DecryptionStatus::NotEncrypted);
+        assert!(protection.schemes.is_empty());
+    }
+}
@@ -44,16 +44,18 @@ pub struct TCOUNT {
 /// Drive scan message (DRV)
 /// Represents information about an optical drive and the disc inserted.
 /// Reference: makemkvcon output, DRV:index,visible,enabled,flags,drive name,disc name
+/// The usage docs leave out the device path makemkvcon prints after the disc name, e.g.
+/// `DRV:0,2,999,1,"BD-RE HL-DT-ST BD-RE  WH16NS40 1.02 SIK9xxxxxx","THE_MARTIAN","/dev/sr0"`
 #[allow(dead_code)]
 #[allow(clippy::upper_case_acronyms)]
 pub struct DRV {
     pub index: i32,
     pub visible: i32,
-    pub unknown: i32,
     pub enabled: i32,
-    pub flags: String,
+    pub flags: i32,
     pub drive_name: String,
     pub disc_name: String,
+    pub device_path: String,
 }
 /// Progress bar values for current and total progress (PRGV)
 /// Represents the current, total, and maximum values for a progress bar.
@@ -1,3 +1,4 @@
+pub mod disc_protection;
 pub mod mkv;
 pub mod optical_disk_info;
 pub mod title_info;
@@ -1,4 +1,6 @@
-use super::title_info::TitleInfo;
+use super::disc_protection::DiscProtection;
+use super::title_info::{TitleId, TitleInfo};
+use crate::state::locks::MutexExt;
 use log::{debug, error};
 use serde::Serialize;
 use std::fmt;
@@ -7,6 +9,11 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 use sysinfo::{Pid, System};
 
+pub const REMOTE_KIND: &str = "Remote";
+
+/// What macOS mounts audio CDs as, the drive listings of the other platforms use the same name.
+pub const AUDIO_CD_FILE_SYSTEM: &str = "cddafs";
+
 #[derive(Serialize)]
 pub struct OpticalDiskInfo {
     pub id: DiskId,
@@ -22,11 +29,15 @@ pub struct OpticalDiskInfo {
     pub titles: Mutex<Vec<TitleInfo>>,
     pub pid: Mutex<Option<u32>>,
     pub index: u32,
+    /// Filled in once makemkvcon has scanned the disc
+    pub protection: Option<DiscProtection>,
+    /// Drive model, firmware and serial as makemkvcon reports them, filled in on scan
+    pub drive_name: Option<String>,
 }
 
 impl OpticalDiskInfo {
     pub fn set_pid(&self, pid: Option<u32>) {
-        *self.pid.lock().expect("failed to unlock pid") = pid;
+        *self.pid.lock_or_recover() = pid;
     }
 
     pub fn is_selected(&self, disk: &Option<OpticalDiskInfo>) -> bool {
@@ -36,12 +47,31 @@ impl OpticalDiskInfo {
         }
     }
 
+    /// Key the drive statistics are stored under. The device name is only a
+    /// fallback because it changes when drives are plugged in a different order.
+    pub fn drive_key(&self) -> String {
+        match &self.drive_name {
+            Some(drive_name) if !drive_name.is_empty() => drive_name.clone(),
+            _ => self.dev.clone(),
+        }
+    }
+
+    /// Drives found through a remote ripper, nothing on this machine can eject them.
+    pub fn is_remote(&self) -> bool {
+        self.kind == REMOTE_KIND
+    }
+
+    /// MakeMKV only reads video discs, an audio CD has no titles to load.
+    pub fn is_audio_cd(&self) -> bool {
+        self.file_system == AUDIO_CD_FILE_SYSTEM
+    }
+
     pub fn any_titles(&self) -> bool {
-        !self.titles.lock().unwrap().is_empty()
+        !self.titles.lock_or_recover().is_empty()
     }
 
     pub fn kill_process(&self) {
-        match *self.pid.lock().unwrap() {
+        match *self.pid.lock_or_recover() {
             Some(pid) => {
                 debug!("Killing process {pid:?}");
                 let mut system = System::new_all();
@@ -91,8 +121,8 @@ impl OpticalDiskInfo {
         titles
     }
 
-    pub fn find_title_by_id(&self, title_id: u32) -> Option<TitleInfo> {
-        let titles = self.titles.lock().unwrap();
+    pub fn find_title_by_id(&self, title_id: TitleId) -> Option<TitleInfo> {
+        let titles = self.titles.lock_or_recover();
         for title in titles.iter() {
             if title.id == title_id {
                 return Some(title.clone());
@@ -132,6 +162,8 @@ impl Clone for OpticalDiskInfo {
             titles: Mutex::new(cloned_titles),
             pid: Mutex::new(pid),
             index: self.index,
+            protection: self.protection.clone(),
+            drive_name: self.drive_name.clone(),
         }
     }
 }
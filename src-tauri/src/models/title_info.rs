@@ -1,8 +1,33 @@
-use serde::Serialize;
+use crate::services::locale;
+use serde::{Deserialize, Serialize};
+
+/// Number makemkvcon gives a title on the disc, not a TMDB id.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug,
+)]
+pub struct TitleId(u32);
+
+impl std::fmt::Display for TitleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u32> for TitleId {
+    fn from(id: u32) -> Self {
+        TitleId(id)
+    }
+}
+
+impl From<TitleId> for u32 {
+    fn from(id: TitleId) -> Self {
+        id.0
+    }
+}
 
 #[derive(Default, Serialize, Clone)]
 pub struct TitleInfo {
-    pub id: u32,
+    pub id: TitleId,
     pub name: Option<String>,
     pub chapter_count: Option<i32>,
     pub duration: Option<String>,
@@ -16,17 +41,17 @@ pub struct TitleInfo {
     pub lang: Option<String>,
     pub language: Option<String>,
     pub description: Option<String>,
+    pub video_size: Option<String>,
 }
 
 impl TitleInfo {
     pub fn new(id: u32) -> Self {
         Self {
-            id,
+            id: TitleId(id),
             ..Default::default()
         }
     }
 
-
     pub fn title_option_label(&self) -> String {
         let mut label = format!("Title {}", self.id);
         if let Some(description) = &self.description {
@@ -35,7 +60,7 @@ impl TitleInfo {
         if let Some(duration) = &self.duration {
             label.push_str(&format!(" • {duration}"));
         }
-        if let Some(size) = &self.size {
+        if let Some(size) = self.size_label() {
             label.push_str(&format!(" • {size}"));
         }
         if let Some(chapter_count) = self.chapter_count {
@@ -44,6 +69,14 @@ impl TitleInfo {
         label
     }
 
+    /// The size in the units of the locale, what makemkvcon reported when it gave no bytes.
+    pub fn size_label(&self) -> Option<String> {
+        match self.bytes.as_deref().and_then(|bytes| bytes.parse().ok()) {
+            Some(bytes) => Some(locale::current().format_size(bytes)),
+            None => self.size.clone(),
+        }
+    }
+
     pub fn has_chapters(&self) -> bool {
         self.chapter_count.unwrap_or(0) > 0
     }
@@ -66,6 +99,36 @@ impl TitleInfo {
         })
     }
 
+    /// Resolution label derived from the video stream size reported by makemkvcon.
+    ///
+    /// Width is preferred over height so letterboxed titles (1920x800) still report 1080p.
+    pub fn resolution(&self) -> Option<String> {
+        let (width, height) = self.video_size.as_ref()?.split_once('x')?;
+        let width = width.trim().parse::<u32>().ok()?;
+        let height = height.trim().parse::<u32>().ok()?;
+        let label = match (width, height) {
+            (w, _) if w >= 3800 => "2160p".to_string(),
+            (w, _) if w >= 1900 => "1080p".to_string(),
+            (w, _) if w >= 1270 => "720p".to_string(),
+            (_, h) if h >= 570 => "576p".to_string(),
+            (_, h) if h >= 470 => "480p".to_string(),
+            (_, h) => format!("{h}p"),
+        };
+        Some(label)
+    }
+
+    /// Quality tag in the format Radarr/Sonarr use, e.g. `Bluray-1080p` or `DVD`.
+    ///
+    /// makemkvcon doesn't tell us the disc type per title, so the source is guessed
+    /// from the resolution: anything below 720p can only come from a DVD.
+    pub fn quality_tag(&self) -> Option<String> {
+        let resolution = self.resolution()?;
+        match resolution.as_str() {
+            "2160p" | "1080p" | "720p" => Some(format!("Bluray-{resolution}")),
+            _ => Some("DVD".to_string()),
+        }
+    }
+
     pub fn within_range(&self, range: &Option<std::ops::Range<u64>>) -> bool {
         let range = match range {
             Some(r) => r,
@@ -78,6 +141,18 @@ impl TitleInfo {
         }
     }
 
+    /// The first segment the title plays, "00800" of "00800,00801" or 1 of "1-3,5". Segments
+    /// are numbered in the order they sit on the disc, close enough to where the title starts.
+    pub fn first_segment(&self) -> Option<u32> {
+        self.segment_map
+            .as_deref()?
+            .split([',', '-'])
+            .next()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
     pub fn set_field(&mut self, field: &str, value: String) {
         match field {
             "name" => self.name = Some(value),
@@ -93,6 +168,7 @@ impl TitleInfo {
             "lang" => self.lang = Some(value),
             "language" => self.language = Some(value),
             "description" => self.description = Some(value),
+            "video_size" => self.video_size = Some(value),
             _ => {}
         }
     }
@@ -111,12 +187,72 @@ mod tests {
         assert_eq!(title.title_option_label(), "Title 1 — Main Movie");
 
         title.duration = Some("01:30:00".to_string());
-        assert_eq!(title.title_option_label(), "Title 1 — Main Movie • 01:30:00");
+        assert_eq!(
+            title.title_option_label(),
+            "Title 1 — Main Movie • 01:30:00"
+        );
 
         title.size = Some("4.5 GB".to_string());
-        assert_eq!(title.title_option_label(), "Title 1 — Main Movie • 01:30:00 • 4.5 GB");
+        assert_eq!(
+            title.title_option_label(),
+            "Title 1 — Main Movie • 01:30:00 • 4.5 GB"
+        );
 
         title.chapter_count = Some(12);
-        assert_eq!(title.title_option_label(), "Title 1 — Main Movie • 01:30:00 • 4.5 GB • 12 ch");
+        assert_eq!(
+            title.title_option_label(),
+            "Title 1 — Main Movie • 01:30:00 • 4.5 GB • 12 ch"
+        );
+    }
+
+    #[test]
+    fn test_first_segment() {
+        let mut title = TitleInfo::new(1);
+        assert_eq!(title.first_segment(), None);
+
+        title.segment_map = Some("00800,00801".to_string());
+        assert_eq!(title.first_segment(), Some(800));
+
+        title.segment_map = Some("12-14,16".to_string());
+        assert_eq!(title.first_segment(), Some(12));
+
+        title.segment_map = Some("garbage".to_string());
+        assert_eq!(title.first_segment(), None);
+    }
+
+    #[test]
+    fn test_resolution() {
+        let mut title = TitleInfo::new(1);
+        assert_eq!(title.resolution(), None);
+
+        title.video_size = Some("1920x1080".to_string());
+        assert_eq!(title.resolution(), Some("1080p".to_string()));
+
+        title.video_size = Some("1920x800".to_string());
+        assert_eq!(title.resolution(), Some("1080p".to_string()));
+
+        title.video_size = Some("720x480".to_string());
+        assert_eq!(title.resolution(), Some("480p".to_string()));
+
+        title.video_size = Some("3840x2160".to_string());
+        assert_eq!(title.resolution(), Some("2160p".to_string()));
+
+        title.video_size = Some("garbage".to_string());
+        assert_eq!(title.resolution(), None);
+    }
+
+    #[test]
+    fn test_quality_tag() {
+        let mut title = TitleInfo::new(1);
+        assert_eq!(title.quality_tag(), None);
+
+        title.video_size = Some("1920x1080".to_string());
+        assert_eq!(title.quality_tag(), Some("Bluray-1080p".to_string()));
+
+        title.video_size = Some("3840x2160".to_string());
+        assert_eq!(title.quality_tag(), Some("Bluray-2160p".to_string()));
+
+        title.video_size = Some("720x576".to_string());
+        assert_eq!(title.quality_tag(), Some("DVD".to_string()));
     }
 }
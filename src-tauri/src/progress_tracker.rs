@@ -1,3 +1,4 @@
+use crate::state::locks::MutexExt;
 use log::debug;
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
@@ -10,70 +11,36 @@ use std::time::SystemTime;
 pub struct Progress {
     pub total: usize,
     pub progress: usize,
-    pub starting_position: usize,
 }
 
 impl Progress {
     pub fn new(total: Option<usize>) -> Self {
         let total = total.unwrap_or(100);
-        let starting_position = 0;
-        let progress = starting_position;
-        Progress {
-            total,
-            progress,
-            starting_position,
-        }
+        Progress { total, progress: 0 }
     }
 
     pub fn start(&mut self, at: Option<usize>) {
-        let pos = at.unwrap_or(self.progress);
-        self.starting_position = pos;
-        self.progress = pos;
+        self.progress = at.unwrap_or(self.progress);
     }
 
-    // Unused: warning: methods `finish` is never used
-    // pub fn finish(&mut self) {
-    //     self.progress = self.total;
-    // }
+    pub fn finish(&mut self) {
+        self.progress = self.total;
+    }
 
     pub fn finished(&self) -> bool {
         self.progress == self.total
     }
 
-    // Unused: warning: method `increment` is never used
-    // pub fn increment(&mut self) {
-    //     if self.progress == self.total {
-    //         debug!(
-    //             "WARNING: Your progress bar is currently at {} out of {}",
-    //             self.progress, self.total
-    //         );
-    //     } else {
-    //         self.progress += 1;
-    //     }
-    // }
-
-    // Unused: warning: method `decrement` is never used
-    // pub fn decrement(&mut self) {
-    //     if self.progress == 0 {
-    //         debug!(
-    //             "WARNING: Your progress bar is currently at {} out of {}",
-    //             self.progress, self.total
-    //         );
-    //     } else {
-    //         self.progress -= 1;
-    //     }
-    // }
-
-    // Unused: warning: method `reset` is never used
-    // pub fn reset(&mut self) {
-    //     self.start(Some(self.starting_position));
-    // }
-
+    /// Values past the total are capped, makemkvcon sometimes reports a little more than it
+    /// announced and a progress bar is no reason to fail a rip.
     pub fn set_progress(&mut self, new_progress: usize) {
         if new_progress > self.total {
-            panic!("You can't set the item's current value to be greater than the total.");
+            debug!(
+                "Progress {new_progress} is greater than the total {}, capping it",
+                self.total
+            );
         }
-        self.progress = new_progress;
+        self.progress = new_progress.min(self.total);
     }
 
     pub fn set_total(&mut self, new_total: usize) {
@@ -92,21 +59,6 @@ impl Progress {
         }
     }
 
-    // Unused: warning: method `percentage_completed_with_precision` is never used
-    // pub fn percentage_completed_with_precision(&self) -> String {
-    //     if self.total == 0 {
-    //         "100.00".to_string()
-    //     } else {
-    //         let percent =
-    //             (self.progress as f64 * 100.0 / self.total as f64 * 100.0).floor() / 100.0;
-    //         format!("{:5.2}", percent)
-    //     }
-    // }
-    /// Returns the “absolute” progress (progress minus starting position).
-    // Unused: warning: method `absolute` is never used
-    // pub fn absolute(&self) -> isize {
-    //     self.progress as isize - self.starting_position as isize
-    // }
     pub fn none(&self) -> bool {
         self.progress == 0
     }
@@ -151,16 +103,6 @@ impl Timer {
         }
     }
 
-    // Unused: warning: method `pause` is never used
-    // pub fn pause(&mut self) {
-    //     self.stop();
-    // }
-
-    // Unused: warning: method `resume` is never used
-    // pub fn resume(&mut self) {
-    //     self.start();
-    // }
-
     pub fn started(&self) -> bool {
         self.started_at.is_some()
     }
@@ -169,22 +111,10 @@ impl Timer {
         self.stopped_at.is_some()
     }
 
-    // Unused: warning: method `reset` is never used
-    // pub fn reset(&mut self) {
-    //     self.started_at = None;
-    //     self.stopped_at = None;
-    // }
-
     pub fn is_reset(&self) -> bool {
         self.started_at.is_none()
     }
 
-    // Unused: warning: method `restart` is never used
-    // pub fn restart(&mut self) {
-    //     self.reset();
-    //     self.start();
-    // }
-
     pub fn elapsed_seconds(&self) -> f64 {
         if let Some(started) = self.started_at {
             let end = self.stopped_at.unwrap_or_else(SystemTime::now);
@@ -198,11 +128,6 @@ impl Timer {
         }
     }
 
-    // Unused: warning: method `elapsed_whole_seconds` is never used
-    // pub fn elapsed_whole_seconds(&self) -> u64 {
-    //     self.elapsed_seconds().floor() as u64
-    // }
-
     pub fn divide_seconds(seconds: u64) -> (u64, u64, u64) {
         let hours = seconds / 3600;
         let minutes = (seconds % 3600) / 60;
@@ -215,13 +140,7 @@ impl Timer {
 // The trait now requires implementors to be Send + Sync.
 pub trait Projector: Send + Sync {
     fn start(&mut self, at: Option<f64>);
-    // Unused: warning: methods `decrement` is never used
-    // fn decrement(&mut self);
-    // Unused: warning: method `increment` is never used
-    // fn increment(&mut self);
     fn set_progress(&mut self, new_progress: f64);
-    // Unused: warning: method `reset` is never used
-    // fn reset(&mut self);
     fn get_progress(&self) -> f64;
     fn none(&self) -> bool;
 }
@@ -238,8 +157,6 @@ pub mod projectors {
     // --- SmoothedAverage ---
     impl SmoothedAverage {
         pub const DEFAULT_STRENGTH: f64 = 0.1;
-        // Unused: warning: associated constant `DEFAULT_BEGINNING_POSITION` is never used
-        // pub const DEFAULT_BEGINNING_POSITION: f64 = 0.0;
         // Adjust the strength to make the system update the weighted average
         // more often or less often. Larger numbers will keep the current
         // value closer to the current projection, while lower numbers will
@@ -255,6 +172,12 @@ pub mod projectors {
             projector
         }
 
+        /// The smoothed distance travelled since the start.
+        #[cfg(test)]
+        pub fn projection(&self) -> f64 {
+            self.projection
+        }
+
         fn absolute(&self) -> f64 {
             self.samples[1] - self.samples[0]
         }
@@ -272,28 +195,11 @@ pub mod projectors {
             self.samples[1] = initial;
         }
 
-        // Unused: warning: method `decrement` is never used
-        // fn decrement(&mut self) {
-        //     let new_value = self.get_progress() - 1.0;
-        //     self.set_progress(new_value);
-        // }
-
-        // Unused: warning: method `increment` is never used
-        // fn increment(&mut self) {
-        //     let new_value = self.get_progress() + 1.0;
-        //     self.set_progress(new_value);
-        // }
-
         fn set_progress(&mut self, new_progress: f64) {
             self.samples[1] = new_progress;
             self.projection = Self::calculate(self.projection, self.absolute(), self.strength);
         }
 
-        // Unused: warning: method `reset` is never used
-        // fn reset(&mut self) {
-        //     self.start(Some(self.samples[0]));
-        // }
-
         fn get_progress(&self) -> f64 {
             self.samples[1]
         }
@@ -318,7 +224,10 @@ pub mod projectors {
 // --- Components ---
 pub mod components {
     use super::{Progress, Projector, Timer};
+    use crate::state::locks::MutexExt;
+    use std::collections::VecDeque;
     use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
 
     pub struct Percentage {
         pub progress: Arc<Mutex<Progress>>,
@@ -330,75 +239,56 @@ pub mod components {
         }
 
         pub fn percentage(&self) -> f32 {
-            self.progress.lock().unwrap().percentage_completed()
+            self.progress.lock_or_recover().percentage_completed()
         }
+    }
 
-        // Unused: warning: method `justified_percentage` is never used
-        // pub fn justified_percentage(&self) -> String {
-        //     format!(
-        //         "{:>3}",
-        //         self.progress.lock().unwrap().percentage_completed()
-        //     )
-        // }
-
-        // Unused: warning: method `percentage_with_precision` is never used
-        // pub fn percentage_with_precision(&self) -> String {
-        //     self.progress
-        //         .lock()
-        //         .unwrap()
-        //         .percentage_completed_with_precision()
-        // }
-
-        // Unused: warning: method `justified_percentage_with_precision` is never used
-        // pub fn justified_percentage_with_precision(&self) -> String {
-        //     format!(
-        //         "{:>6}",
-        //         self.progress
-        //             .lock()
-        //             .unwrap()
-        //             .percentage_completed_with_precision()
-        //     )
-        // }
-    }
-
-    #[allow(dead_code)]
+    /// Progress per second over the last few seconds, e.g. the speed of an upload when the
+    /// progress is in bytes. Unlike the ETA it follows a slow or fast stretch right away.
     pub struct Rate {
-        pub rate_scale: Box<dyn Fn(f64) -> f64 + Send + Sync>,
-        pub timer: Arc<Mutex<Timer>>,
-        pub progress: Arc<Mutex<Progress>>,
+        samples: Mutex<VecDeque<(Instant, usize)>>,
     }
 
     impl Rate {
-        pub fn new(timer: Arc<Mutex<Timer>>, progress: Arc<Mutex<Progress>>) -> Self {
+        pub const WINDOW: Duration = Duration::from_secs(5);
+
+        pub fn new() -> Self {
             Rate {
-                rate_scale: Box::new(|x| x),
-                timer,
-                progress,
+                samples: Mutex::new(VecDeque::new()),
             }
         }
 
-        // Unused: warning: method `rate_of_change` is never used
-        // pub fn rate_of_change(&self, _format_string: Option<&str>) -> String {
-        //     let elapsed = self.timer.lock().unwrap().elapsed_seconds();
-        //     if elapsed <= 0.0 {
-        //         return "0".to_string();
-        //     }
-        //     let base_rate = self.progress.lock().unwrap().absolute() as f64 / elapsed;
-        //     let scaled_rate = (self.rate_scale)(base_rate);
-        //     format!("{}", scaled_rate)
-        // }
+        pub fn sample(&self, progress: usize) {
+            self.sample_at(Instant::now(), progress);
+        }
 
-        // Unused: warning: method `rate_of_change_with_precision` is never used
-        // pub fn rate_of_change_with_precision(&self) -> String {
-        //     self.rate_of_change(Some("%.2f"))
-        // }
-    }
+        pub fn sample_at(&self, at: Instant, progress: usize) {
+            let mut samples = self.samples.lock_or_recover();
+            samples.push_back((at, progress));
+            while samples.len() > 2
+                && samples
+                    .front()
+                    .is_some_and(|(sampled_at, _)| at.duration_since(*sampled_at) > Self::WINDOW)
+            {
+                samples.pop_front();
+            }
+        }
+
+        /// `None` until two samples are a moment apart.
+        pub fn per_second(&self) -> Option<f64> {
+            let samples = self.samples.lock_or_recover();
+            let (first_at, first) = samples.front()?;
+            let (last_at, last) = samples.back()?;
+            let seconds = last_at.duration_since(*first_at).as_secs_f64();
+            if seconds <= 0.0 {
+                return None;
+            }
+            Some(last.saturating_sub(*first) as f64 / seconds)
+        }
 
-    #[derive(Clone)]
-    #[allow(dead_code)]
-    pub enum OOBTimeFormat {
-        Unknown,
-        Friendly,
+        pub fn reset(&self) {
+            self.samples.lock_or_recover().clear();
+        }
     }
 
     pub struct TimeComponent {
@@ -408,13 +298,7 @@ pub mod components {
     }
 
     impl TimeComponent {
-        // const OOB_LIMIT_IN_HOURS: u64 = 99;
-        // const OOB_UNKNOWN_TIME_TEXT: &'static str = "??:??:??";
-        // const OOB_FRIENDLY_TIME_TEXT: &'static str = "> 4 Days";
         pub const NO_TIME_ELAPSED_TEXT: &'static str = "--:--:--";
-        // const ESTIMATED_LABEL: &'static str = " ETA";
-        // const ELAPSED_LABEL: &'static str = "Time";
-        // const WALL_CLOCK_FORMAT: &'static str = "%H:%M:%S";
 
         pub fn new(
             timer: Arc<Mutex<Timer>>,
@@ -436,114 +320,39 @@ pub mod components {
         ///
         /// How to use:
         /// - Call this method on a `TimeComponent` to get the ETA string.
-        /// - Optionally pass an `OOBTimeFormat` to control the output for very large/unknown times.
         ///
         /// Example:
         /// ```text
-        /// let eta = time_component.estimated(None); // e.g., "00:12:34"
-        /// let eta_friendly = time_component.estimated(Some(OOBTimeFormat::Friendly));
+        /// let eta = time_component.estimated(); // e.g., "00:12:34"
         /// ```
         ///
         /// Notes:
         /// - Returns "--:--:--" if ETA cannot be determined (e.g., not started, finished, or insufficient data).
-        /// - Caps output at 99 hours; uses OOB format for longer durations.
+        /// - Times past 99 hours are shown in full rather than capped.
         /// - Relies on progress, timer, and projector state for calculation.
-        pub fn estimated(&self, oob_format: Option<OOBTimeFormat>) -> String {
-            if let Some(estimated_secs) = self.estimated_seconds_remaining() {
-                let (hours, minutes, seconds) = Timer::divide_seconds(estimated_secs);
-                if hours > 99 {
-                    if let Some(oob) = oob_format {
-                        return match oob {
-                            OOBTimeFormat::Unknown => Self::NO_TIME_ELAPSED_TEXT.to_string(),
-                            OOBTimeFormat::Friendly => Self::NO_TIME_ELAPSED_TEXT.to_string(),
-                        };
-                    }
-                }
-                self.format_time(hours, minutes, seconds)
-            } else {
-                Self::NO_TIME_ELAPSED_TEXT.to_string()
-            }
+        pub fn estimated(&self) -> String {
+            let Some(estimated_secs) = self.estimated_seconds_remaining() else {
+                return Self::NO_TIME_ELAPSED_TEXT.to_string();
+            };
+            let (hours, minutes, seconds) = Timer::divide_seconds(estimated_secs);
+            Self::format_time(hours, minutes, seconds)
         }
 
-        fn format_time(&self, hours: u64, minutes: u64, seconds: u64) -> String {
+        fn format_time(hours: u64, minutes: u64, seconds: u64) -> String {
             format!("{hours:02}:{minutes:02}:{seconds:02}")
         }
 
-        // Unused: warning: method `estimated_with_label` is never used
-        // pub fn estimated_with_label(&self, oob_format: Option<OOBTimeFormat>) -> String {
-        //     format!("{}: {}", Self::ESTIMATED_LABEL, self.estimated(oob_format))
-        // }
-
-        // Unused: warning: method `elapsed` is never used
-        // pub fn elapsed(&self) -> String {
-        //     if !self.timer.lock().unwrap().started() {
-        //         return Self::NO_TIME_ELAPSED_TEXT.to_string();
-        //     }
-        //     let elapsed = self.timer.lock().unwrap().elapsed_whole_seconds();
-        //     let (hours, minutes, seconds) = Timer::divide_seconds(elapsed);
-        //     self.format_time(hours, minutes, seconds)
-        // }
-
-        // Unused: warning: method `elapsed_with_label` is never used
-        // pub fn elapsed_with_label(&self) -> String {
-        //     format!("{}: {}", Self::ELAPSED_LABEL, self.elapsed())
-        // }
-
-        // Unused: warning: method `estimated_with_no_oob` is never used
-        // pub fn estimated_with_no_oob(&self) -> String {
-        //     self.estimated_with_elapsed_fallback(None)
-        // }
-
-        // Unused: warning: method `estimated_with_unknown_oob` is never used
-        // pub fn estimated_with_unknown_oob(&self) -> String {
-        //     self.estimated_with_elapsed_fallback(Some(OOBTimeFormat::Unknown))
-        // }
-
-        // Unused: warning: method `estimated_with_friendly_oob` is never used
-        // pub fn estimated_with_friendly_oob(&self) -> String {
-        //     self.estimated_with_elapsed_fallback(Some(OOBTimeFormat::Friendly))
-        // }
-
-        // Unused: warning: method `estimated_with_elapsed_fallback` is never used
-        // fn estimated_with_elapsed_fallback(&self, oob_format: Option<OOBTimeFormat>) -> String {
-        //     if self.progress.lock().unwrap().finished() {
-        //         self.elapsed_with_label()
-        //     } else {
-        //         self.estimated_with_label(oob_format)
-        //     }
-        // }
-
-        // Unused: warning: method `estimated_wall_clock` is never used
-        // pub fn estimated_wall_clock(&self) -> String {
-        //     if self.progress.lock().unwrap().finished() {
-        //         if let Some(stopped) = self.timer.lock().unwrap().stopped_at {
-        //             let datetime: DateTime<Local> = stopped.into();
-        //             return datetime.format(Self::WALL_CLOCK_FORMAT).to_string();
-        //         }
-        //     }
-        //     if !self.timer.lock().unwrap().started() {
-        //         return Self::NO_TIME_ELAPSED_TEXT.to_string();
-        //     }
-        //     if let Some(estimated_secs) = self.estimated_seconds_remaining() {
-        //         let estimated_time =
-        //             SystemTime::now() + std::time::Duration::from_secs(estimated_secs);
-        //         let datetime: DateTime<Local> = estimated_time.into();
-        //         return datetime.format(Self::WALL_CLOCK_FORMAT).to_string();
-        //     }
-        //     Self::NO_TIME_ELAPSED_TEXT.to_string()
-        // }
-
-        fn estimated_seconds_remaining(&self) -> Option<u64> {
-            let progress = self.progress.lock().unwrap();
-            let projector_progress = self.projector.lock().unwrap().get_progress();
-            if self.projector.lock().unwrap().none()
+        pub fn estimated_seconds_remaining(&self) -> Option<u64> {
+            let progress = self.progress.lock_or_recover();
+            let projector_progress = self.projector.lock_or_recover().get_progress();
+            if self.projector.lock_or_recover().none()
                 || progress.none()
-                || self.timer.lock().unwrap().stopped()
-                || self.timer.lock().unwrap().is_reset()
+                || self.timer.lock_or_recover().stopped()
+                || self.timer.lock_or_recover().is_reset()
             {
                 return None;
             }
-            let elapsed = self.timer.lock().unwrap().elapsed_seconds();
+            let elapsed = self.timer.lock_or_recover().elapsed_seconds();
             if elapsed <= 0.0 || projector_progress == 0.0 {
                 return None;
             }
@@ -556,7 +365,6 @@ pub mod components {
 
 // --- Base ---
 /// The main ProgressTracker "Base" type.
-#[allow(dead_code)]
 pub struct Base {
     pub autostart: bool,
     pub autofinish: bool,
@@ -565,8 +373,8 @@ pub struct Base {
     pub projector: Arc<Mutex<Box<dyn Projector>>>,
     pub progress: Arc<Mutex<Progress>>,
     pub percentage_component: components::Percentage,
-    pub rate_component: components::Rate,
     pub time_component: components::TimeComponent,
+    pub rate_component: components::Rate,
 }
 
 impl Base {
@@ -586,7 +394,6 @@ impl Base {
 
         // Create components (they share the same progress, timer, and projector).
         let percentage_component = components::Percentage::new(Arc::clone(&progress));
-        let rate_component = components::Rate::new(Arc::clone(&timer), Arc::clone(&progress));
         let time_component = components::TimeComponent::new(
             Arc::clone(&timer),
             Arc::clone(&progress),
@@ -601,8 +408,8 @@ impl Base {
             projector,
             progress,
             percentage_component,
-            rate_component,
             time_component,
+            rate_component: components::Rate::new(),
         };
 
         if base.autostart {
@@ -615,87 +422,29 @@ impl Base {
     }
 
     pub fn start(&self, at: Option<usize>) {
-        self.timer.lock().unwrap().start();
-        self.progress.lock().unwrap().start(at);
-        let val = self.progress.lock().unwrap().progress as f64;
-        self.projector.lock().unwrap().start(Some(val));
-    }
-
-    // Unused: warning: method `finish` is never used
-    // pub fn finish(&mut self) {
-    //     if self.finished() {
-    //         return;
-    //     }
-    //     self.finished = true;
-    //     self.progress.lock().unwrap().finish();
-    //     self.timer.lock().unwrap().stop();
-    // }
-
-    // Unused: warning: method `pause` is never used
-    // pub fn pause(&self) {
-    //     if !self.paused() {
-    //         self.timer.lock().unwrap().pause();
-    //     }
-    // }
-
-    // Unused: warning: method `stop` is never used
-    // pub fn stop(&self) {
-    //     if !self.stopped() {
-    //         self.timer.lock().unwrap().stop();
-    //     }
-    // }
-
-    // Unused: warning: method `resume` is never used
-    // pub fn resume(&self) {
-    //     if self.stopped() {
-    //         self.timer.lock().unwrap().resume();
-    //     }
-    // }
-
-    // Unused: warning: method `reset` is never used
-    // pub fn reset(&mut self) {
-    //     self.finished = false;
-    //     self.progress.lock().unwrap().reset();
-    //     self.projector.lock().unwrap().reset();
-    //     self.timer.lock().unwrap().reset();
-    // }
-
-    // Unused: warning: method `stopped` is never used
-    // pub fn stopped(&self) -> bool {
-    //     self.timer.lock().unwrap().stopped() || self.finished()
-    // }
-
-    // Unused: warning: method `paused` is never used
-    // pub fn paused(&self) -> bool {
-    //     self.stopped()
-    // }
+        self.timer.lock_or_recover().start();
+        self.progress.lock_or_recover().start(at);
+        let progress = self.progress.lock_or_recover().progress;
+        self.projector
+            .lock_or_recover()
+            .start(Some(progress as f64));
+        self.rate_component.reset();
+        self.rate_component.sample(progress);
+    }
+
+    /// Jump to the total and stop the clock, e.g. when the work ended without a last update.
+    pub fn finish(&mut self) {
+        if self.finished() {
+            return;
+        }
+        self.finished = true;
+        self.progress.lock_or_recover().finish();
+        self.timer.lock_or_recover().stop();
+    }
 
     pub fn finished(&self) -> bool {
-        self.finished || (self.autofinish && self.progress.lock().unwrap().finished())
-    }
-
-    // Unused: warning: method `started` is never used
-    // pub fn started(&self) -> bool {
-    //     self.timer.lock().unwrap().started()
-    // }
-
-    // Unused: warning: method `decrement` is never used
-    // pub fn decrement(&self) {
-    //     self.progress.lock().unwrap().decrement();
-    //     self.projector.lock().unwrap().decrement();
-    //     if self.finished() {
-    //         self.timer.lock().unwrap().stop();
-    //     }
-    // }
-
-    // Unused: warning: method `increment` is never used
-    // pub fn increment(&self) {
-    //     self.progress.lock().unwrap().increment();
-    //     self.projector.lock().unwrap().increment();
-    //     if self.finished() {
-    //         self.timer.lock().unwrap().stop();
-    //     }
-    // }
+        self.finished || (self.autofinish && self.progress.lock_or_recover().finished())
+    }
 
     /// Set the current progress value for the tracker.
     ///
@@ -713,13 +462,14 @@ impl Base {
     /// tracker.set_progress(42); // Set progress to 42
     /// ```
     pub fn set_progress(&self, new_progress: usize) {
-        self.progress.lock().unwrap().set_progress(new_progress);
+        self.progress.lock_or_recover().set_progress(new_progress);
+        let progress = self.progress.lock_or_recover().progress;
         self.projector
-            .lock()
-            .unwrap()
-            .set_progress(new_progress as f64);
+            .lock_or_recover()
+            .set_progress(progress as f64);
+        self.rate_component.sample(progress);
         if self.finished() {
-            self.timer.lock().unwrap().stop();
+            self.timer.lock_or_recover().stop();
         }
     }
 
@@ -739,9 +489,9 @@ impl Base {
     /// tracker.set_total(100); // Set total to 100
     /// ```
     pub fn set_total(&self, new_total: usize) {
-        self.progress.lock().unwrap().set_total(new_total);
+        self.progress.lock_or_recover().set_total(new_total);
         if self.finished() {
-            self.timer.lock().unwrap().stop();
+            self.timer.lock_or_recover().stop();
         }
     }
 }
@@ -758,29 +508,143 @@ pub struct ProgressOptions {
     pub projector_at: Option<f64>,
 }
 
-// Example usage:
-// use progress_tracker::{Base, ProgressOptions};
-//
-// fn main() {
-//     let options = ProgressOptions {
-//         total: Some(200),
-//         autostart: true,
-//         autofinish: true,
-//         starting_at: Some(0),
-//         projector_type: Some("smoothed".to_string()),
-//         projector_strength: Some(0.1),
-//         projector_at: Some(0.0),
-//     };
-//
-//     let pb = Base::new(Some(options));
-//     pb.increment();
-//     pb.increment();
-//
-//     debug!(
-//         "Progress: {}/{}",
-//         pb.progress.lock().unwrap().progress,
-//         pb.progress.lock().unwrap().total
-//     );
-//     debug!("Percentage: {}", pb.percentage_component.percentage());
-//     debug!("Elapsed: {}", pb.time_component.elapsed_with_label());
-// }
+#[cfg(test)]
+mod tests {
+    use super::components::TimeComponent;
+    use super::projectors::SmoothedAverage;
+    use super::*;
+    use std::time::Duration;
+
+    fn tracker(total: usize) -> Base {
+        Base::new(Some(ProgressOptions {
+            total: Some(total),
+            autostart: true,
+            autofinish: true,
+            starting_at: Some(0),
+            projector_type: Some("smoothed".to_string()),
+            projector_strength: Some(0.1),
+            projector_at: Some(0.0),
+        }))
+    }
+
+    /// Pretends the tracker was started `seconds` ago.
+    fn started_ago(tracker: &Base, seconds: u64) {
+        tracker.timer.lock_or_recover().started_at =
+            Some(SystemTime::now() - Duration::from_secs(seconds));
+    }
+
+    #[test]
+    fn percentage_follows_the_progress() {
+        let tracker = tracker(200);
+        assert_eq!(tracker.percentage_component.percentage(), 0.0);
+        tracker.set_progress(50);
+        assert_eq!(tracker.percentage_component.percentage(), 25.0);
+
+        let mut empty = Progress::new(Some(0));
+        empty.set_progress(0);
+        assert_eq!(empty.percentage_completed(), 100.0);
+    }
+
+    #[test]
+    fn progress_past_the_total_is_capped() {
+        let tracker = tracker(100);
+        tracker.set_progress(120);
+        assert_eq!(tracker.progress.lock_or_recover().progress, 100);
+        assert!(tracker.finished());
+        assert!(tracker.timer.lock_or_recover().stopped());
+    }
+
+    #[test]
+    fn lowering_the_total_lowers_the_progress() {
+        let tracker = tracker(100);
+        tracker.set_progress(80);
+        tracker.set_total(50);
+        let progress = tracker.progress.lock_or_recover();
+        assert_eq!(progress.progress, 50);
+        assert_eq!(progress.total, 50);
+    }
+
+    #[test]
+    fn estimates_the_remaining_time_from_the_elapsed_time() {
+        let tracker = tracker(100);
+        assert_eq!(tracker.time_component.estimated_seconds_remaining(), None);
+        assert_eq!(
+            tracker.time_component.estimated(),
+            TimeComponent::NO_TIME_ELAPSED_TEXT
+        );
+
+        tracker.set_progress(25);
+        started_ago(&tracker, 600);
+        assert_eq!(
+            tracker.time_component.estimated_seconds_remaining(),
+            Some(1_800)
+        );
+        assert_eq!(tracker.time_component.estimated(), "00:30:00");
+    }
+
+    #[test]
+    fn no_estimate_once_finished() {
+        let mut tracker = tracker(100);
+        tracker.set_progress(10);
+        started_ago(&tracker, 60);
+        tracker.finish();
+        assert!(tracker.finished());
+        assert_eq!(tracker.progress.lock_or_recover().progress, 100);
+        assert_eq!(
+            tracker.time_component.estimated(),
+            TimeComponent::NO_TIME_ELAPSED_TEXT
+        );
+    }
+
+    #[test]
+    fn very_long_estimates_are_shown_in_full() {
+        let tracker = tracker(1_000);
+        tracker.set_progress(1);
+        // 999 times the elapsed hour is left
+        started_ago(&tracker, 3_600);
+
+        assert_eq!(tracker.time_component.estimated(), "999:00:00");
+    }
+
+    #[test]
+    fn projector_smooths_the_distance_travelled() {
+        let mut projector = SmoothedAverage::new(Some(0.5), Some(10.0));
+        assert!(projector.none());
+
+        projector.set_progress(20.0);
+        assert_eq!(projector.projection(), 5.0);
+        projector.set_progress(30.0);
+        assert_eq!(projector.projection(), 12.5);
+        assert_eq!(projector.get_progress(), 30.0);
+
+        projector.start(None);
+        assert!(projector.none());
+        assert_eq!(projector.get_progress(), 30.0);
+    }
+
+    #[test]
+    fn rate_follows_the_last_seconds() {
+        let rate = components::Rate::new();
+        let start = std::time::Instant::now();
+        assert_eq!(rate.per_second(), None);
+        rate.sample_at(start, 0);
+        assert_eq!(rate.per_second(), None);
+
+        rate.sample_at(start + Duration::from_secs(2), 2_000);
+        assert_eq!(rate.per_second(), Some(1_000.0));
+        // A slow stretch pushes the fast start out of the window
+        rate.sample_at(start + Duration::from_secs(10), 2_400);
+        rate.sample_at(start + Duration::from_secs(12), 2_800);
+        assert_eq!(rate.per_second(), Some(200.0));
+
+        rate.reset();
+        assert_eq!(rate.per_second(), None);
+    }
+
+    #[test]
+    fn divides_seconds_into_hours_minutes_and_seconds() {
+        assert_eq!(Timer::divide_seconds(0), (0, 0, 0));
+        assert_eq!(Timer::divide_seconds(3_661), (1, 1, 1));
+        assert_eq!(Timer::divide_seconds(360_000), (100, 0, 0));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::services::semantic_version::ParseError;
+use crate::standard_error::StandardError;
+use crate::the_movie_db;
+use serde::Serialize;
+
+/// What went wrong, sent along with command errors so the UI can react to it.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Something the user entered or picked can't be used
+    Validation,
+    NotFound,
+    /// The Movie DB could not be reached or refused the request
+    TheMovieDb,
+    Ftp,
+    /// Any other HTTP request, e.g. GitHub or a push service
+    Network,
+    Io,
+    Template,
+    /// Everything else, usually a rip or job that failed
+    Internal,
+}
+
+impl ErrorKind {
+    /// Toast title for errors of this kind
+    pub fn title(&self) -> &'static str {
+        match self {
+            ErrorKind::Validation => "Invalid Input",
+            ErrorKind::NotFound => "Not Found",
+            ErrorKind::TheMovieDb => "The Movie DB Error",
+            ErrorKind::Ftp => "FTP Error",
+            ErrorKind::Network => "Network Error",
+            ErrorKind::Io => "File Error",
+            ErrorKind::Template => "Display Error",
+            ErrorKind::Internal => "Error",
+        }
+    }
+}
+
+/// Crate wide error, everything else converts into it with `?`.
+#[derive(Debug, thiserror::Error)]
+pub enum ReelixError {
+    #[error("{0}")]
+    Validation(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("The Movie DB returned {code}: {message}")]
+    TheMovieDb { code: u16, message: String },
+    #[error("{0}")]
+    Ftp(String),
+    #[error("{context}: {message}")]
+    Network { context: String, message: String },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Template(String),
+    #[error("Invalid version: {0}")]
+    Version(#[from] ParseError),
+    #[error("{title}: {message}")]
+    Job { title: String, message: String },
+    #[error("{0}")]
+    Message(String),
+}
+
+impl ReelixError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReelixError::Validation(_) | ReelixError::Version(_) => ErrorKind::Validation,
+            ReelixError::NotFound(_) => ErrorKind::NotFound,
+            ReelixError::TheMovieDb { .. } => ErrorKind::TheMovieDb,
+            ReelixError::Ftp(_) => ErrorKind::Ftp,
+            ReelixError::Network { .. } => ErrorKind::Network,
+            ReelixError::Io(_) => ErrorKind::Io,
+            ReelixError::Template(_) => ErrorKind::Template,
+            ReelixError::Job { .. } | ReelixError::Message(_) => ErrorKind::Internal,
+        }
+    }
+
+    pub fn network(context: &str, error: impl std::fmt::Display) -> Self {
+        ReelixError::Network {
+            context: context.to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<String> for ReelixError {
+    fn from(message: String) -> Self {
+        ReelixError::Message(message)
+    }
+}
+
+impl From<&str> for ReelixError {
+    fn from(message: &str) -> Self {
+        ReelixError::Message(message.to_string())
+    }
+}
+
+impl From<StandardError> for ReelixError {
+    fn from(error: StandardError) -> Self {
+        ReelixError::Job {
+            title: error.title,
+            message: error.message,
+        }
+    }
+}
+
+impl From<the_movie_db::Error> for ReelixError {
+    fn from(error: the_movie_db::Error) -> Self {
+        if error.code == 404 {
+            return ReelixError::NotFound(error.message);
+        }
+        ReelixError::TheMovieDb {
+            code: error.code,
+            message: error.message,
+        }
+    }
+}
+
+impl From<suppaftp::FtpError> for ReelixError {
+    fn from(error: suppaftp::FtpError) -> Self {
+        ReelixError::Ftp(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_movie_db_not_found_is_its_own_kind() {
+        let error: ReelixError = the_movie_db::Error {
+            code: 404,
+            message: "The resource you requested could not be found.".to_string(),
+        }
+        .into();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+
+        let error: ReelixError = the_movie_db::Error {
+            code: 401,
+            message: "Invalid API key".to_string(),
+        }
+        .into();
+        assert_eq!(error.kind(), ErrorKind::TheMovieDb);
+        assert_eq!(
+            error.to_string(),
+            "The Movie DB returned 401: Invalid API key"
+        );
+    }
+
+    #[test]
+    fn standard_errors_keep_title_and_message() {
+        let error: ReelixError = StandardError {
+            title: "Rip Failed".to_string(),
+            message: "Drive not ready".to_string(),
+        }
+        .into();
+        assert_eq!(error.kind(), ErrorKind::Internal);
+        assert_eq!(error.to_string(), "Rip Failed: Drive not ready");
+    }
+
+    #[test]
+    fn kinds_serialize_for_the_ui() {
+        assert_eq!(
+            serde_json::to_string(&ErrorKind::TheMovieDb).unwrap(),
+            "\"the_movie_db\""
+        );
+    }
+}
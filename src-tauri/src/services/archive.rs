@@ -0,0 +1,541 @@
+//! Verbatim copies of data discs (photo CDs, DVD-ROM extras), nothing is ripped or renamed.
+use crate::progress_tracker::{self, ProgressOptions};
+use crate::services::notifications::{notify, NotificationEvent, NotificationKind};
+use crate::services::zip_directory::{self, ZipOptions};
+use crate::services::{blocking, resource_monitor};
+use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::job_state::{emit_progress, Job, JobStatus};
+use crate::state::locks::RwLockExt;
+use crate::state::resource_governor::Slot;
+use log::{debug, error, info};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use tauri::Manager;
+use walkdir::WalkDir;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Set on a job to stop archiving it, the partial copy or zip is removed.
+pub const CANCELLED_MESSAGE: &str = "Archiving was cancelled";
+
+/// The copies Reelix made in an archive folder, one name per line. Only these are ever
+/// removed, the archive folder may be shared with folders of the user's own.
+const ARCHIVED_COPIES_FILE: &str = ".reelix-archives";
+
+/// Where archive jobs copy discs to and whether the copy is zipped afterwards.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveOptions {
+    pub archive_dir: PathBuf,
+    /// Replaces the copied folder with `<disc>.zip`
+    pub zip: bool,
+    /// Zips without compressing, for discs that are mostly video
+    pub zip_store_only: bool,
+    /// Splits the zip into `<disc>.zip.001`, `.002`... of this many GB, for FAT32 drives and
+    /// servers that take no files over 4 GB
+    pub zip_volume_gb: Option<u64>,
+    /// Keeps the newest copies of a disc, older ones are removed after archiving it again
+    pub keep_copies: Option<usize>,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        ArchiveOptions {
+            archive_dir: dirs::home_dir()
+                .expect("failed to find home dir")
+                .join("Disc Archive"),
+            zip: false,
+            zip_store_only: false,
+            zip_volume_gb: None,
+            keep_copies: None,
+        }
+    }
+}
+
+/// A folder in `archive_dir` named after the disc that isn't taken yet, `PHOTOS (2)` for the second copy.
+pub fn destination(archive_dir: &Path, disc_name: &str) -> PathBuf {
+    let name = folder_name(disc_name);
+    let taken = |path: &Path| {
+        path.exists()
+            || with_suffix(path, ".zip").exists()
+            || with_suffix(path, ".zip.001").exists()
+    };
+    let mut destination = archive_dir.join(&name);
+    let mut copy = 2;
+    while taken(&destination) {
+        destination = archive_dir.join(format!("{name} ({copy})"));
+        copy += 1;
+    }
+    destination
+}
+
+/// Appended to the whole name, `with_extension` would replace the ".1" of "VOL.1".
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path.as_os_str());
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn folder_name(disc_name: &str) -> String {
+    let name = disc_name.trim().replace(['/', '\\', ':'], "-");
+    if name.is_empty() {
+        "Disc".to_string()
+    } else {
+        name
+    }
+}
+
+/// The copy a file in the archive folder belongs to, `PHOTOS (2)` for `PHOTOS (2).zip.003`.
+fn copy_name(file_name: &str) -> &str {
+    let name = match file_name.rsplit_once(".zip.") {
+        Some((name, volume)) if volume.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => file_name,
+    };
+    name.strip_suffix(".zip").unwrap_or(name)
+}
+
+fn archived_copies(archive_dir: &Path) -> Vec<String> {
+    fs::read_to_string(archive_dir.join(ARCHIVED_COPIES_FILE))
+        .map(|text| text.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn write_archived_copies(archive_dir: &Path, copies: &[String]) -> Result<(), String> {
+    let path = archive_dir.join(ARCHIVED_COPIES_FILE);
+    let mut text = copies.join("\n");
+    text.push('\n');
+    fs::write(&path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Remember `destination` as a copy made by Reelix, so `remove_old_copies` may rotate it.
+pub fn record_copy(destination: &Path) -> Result<(), String> {
+    let (Some(archive_dir), Some(copy)) = (destination.parent(), destination.file_name()) else {
+        return Err(format!("{} is not an archive copy", destination.display()));
+    };
+    let mut copies = archived_copies(archive_dir);
+    copies.push(copy.to_string_lossy().to_string());
+    write_archived_copies(archive_dir, &copies)
+}
+
+/// Remove all but the `keep` newest copies of a disc from `archive_dir`, a copy being its
+/// folder, zip or zip volumes. Only copies `record_copy` knows are touched, a folder of the
+/// same name that Reelix didn't make stays. Returns the copies removed.
+pub fn remove_old_copies(
+    archive_dir: &Path,
+    disc_name: &str,
+    keep: usize,
+) -> Result<Vec<String>, String> {
+    let name = folder_name(disc_name);
+    let archived = archived_copies(archive_dir);
+    let is_copy = |copy: &str| {
+        (copy == name
+            || copy
+                .strip_prefix(&format!("{name} ("))
+                .and_then(|rest| rest.strip_suffix(')'))
+                .is_some_and(|number| number.parse::<u32>().is_ok()))
+            && archived.iter().any(|archived| archived == copy)
+    };
+    let entries = fs::read_dir(archive_dir)
+        .map_err(|e| format!("Failed to read {}: {e}", archive_dir.display()))?;
+    let mut copies: HashMap<String, (SystemTime, Vec<PathBuf>)> = HashMap::new();
+    for entry in entries.filter_map(Result::ok) {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let copy = copy_name(&file_name);
+        if !is_copy(copy) {
+            continue;
+        }
+        let modified = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let (newest, paths) = copies
+            .entry(copy.to_string())
+            .or_insert((SystemTime::UNIX_EPOCH, Vec::new()));
+        *newest = (*newest).max(modified);
+        paths.push(entry.path());
+    }
+
+    let mut copies: Vec<(String, (SystemTime, Vec<PathBuf>))> = copies.into_iter().collect();
+    copies.sort_by(|(_, (a, _)), (_, (b, _))| b.cmp(a));
+    let mut removed = Vec::new();
+    for (copy, (_, paths)) in copies.into_iter().skip(keep) {
+        for path in paths {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            result.map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+        }
+        removed.push(copy);
+    }
+    if !removed.is_empty() {
+        let remaining: Vec<String> = archived
+            .into_iter()
+            .filter(|copy| !removed.contains(copy))
+            .collect();
+        write_archived_copies(archive_dir, &remaining)?;
+    }
+    Ok(removed)
+}
+
+/// Copy everything under `source` into `destination`, calling `on_progress` with the
+/// bytes copied so far and the total after every chunk, returning false cancels the copy.
+/// Returns the bytes copied.
+pub fn copy_disc(
+    source: &Path,
+    destination: &Path,
+    mut on_progress: impl FnMut(u64, u64) -> bool,
+) -> Result<u64, String> {
+    if !source.is_dir() {
+        return Err(format!("{} is not a mounted disc", source.display()));
+    }
+    let entries: Vec<walkdir::DirEntry> = WalkDir::new(source)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to read the disc: {e}"))?;
+    let total: u64 = entries
+        .iter()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    let mut copied = 0;
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    for entry in entries {
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(|e| format!("Failed to resolve {}: {e}", entry.path().display()))?;
+        let target = destination.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create {}: {e}", target.display()))?;
+            continue;
+        }
+        if !entry.file_type().is_file() {
+            debug!("Skipping {}, not a file", entry.path().display());
+            continue;
+        }
+        let mut reader = File::open(entry.path())
+            .map_err(|e| format!("Failed to open {}: {e}", entry.path().display()))?;
+        let mut writer = File::create(&target)
+            .map_err(|e| format!("Failed to create {}: {e}", target.display()))?;
+        loop {
+            let bytes_read = reader
+                .read(&mut buffer)
+                .map_err(|e| format!("Failed to read {}: {e}", entry.path().display()))?;
+            if bytes_read == 0 {
+                break;
+            }
+            writer
+                .write_all(&buffer[..bytes_read])
+                .map_err(|e| format!("Failed to write {}: {e}", target.display()))?;
+            copied += bytes_read as u64;
+            if !on_progress(copied, total) {
+                return Err(CANCELLED_MESSAGE.to_string());
+            }
+        }
+    }
+    Ok(copied)
+}
+
+/// Zip the copied folder next to it and remove the folder, returns the zip file or its first
+/// volume. `on_progress` works like the one of `copy_disc`.
+pub fn zip_copy(
+    destination: &Path,
+    options: ZipOptions,
+    on_progress: impl FnMut(u64, u64) -> bool,
+) -> Result<PathBuf, String> {
+    let zip_file = with_suffix(destination, ".zip");
+    let files = zip_directory::zip_dir(destination, &zip_file, options, on_progress).map_err(
+        |e| match e {
+            zip::result::ZipError::Io(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+                CANCELLED_MESSAGE.to_string()
+            }
+            e => format!("Failed to zip {}: {e}", destination.display()),
+        },
+    )?;
+    fs::remove_dir_all(destination)
+        .map_err(|e| format!("Failed to remove {}: {e}", destination.display()))?;
+    Ok(files.into_iter().next().unwrap_or(zip_file))
+}
+
+/// Copy the disc mounted at `source` into the archive folder, zipping it when set, as the given job.
+///
+/// Archiving reads the drive the whole time, so it takes a rip slot.
+pub fn spawn(
+    app_handle: tauri::AppHandle,
+    job: Arc<RwLock<Job>>,
+    source: PathBuf,
+    disc_name: String,
+    options: ArchiveOptions,
+) {
+    tauri::async_runtime::spawn(async move {
+        let _rip_slot = app_handle
+            .state::<BackgroundProcessState>()
+            .wait_for_slot(&app_handle, &job, Slot::Rip)
+            .await;
+        if job.read_or_recover().is_error() {
+            debug!("Archiving {disc_name} was cancelled before it started");
+            return;
+        }
+        {
+            let mut job_guard = job.write_or_recover();
+            job_guard.update_status(JobStatus::Processing);
+            job_guard.title = Some(format!("Archiving {disc_name}"));
+            job_guard.subtitle = Some("Copying".to_string());
+        }
+        job.read_or_recover().emit_progress_change(&app_handle);
+        let resources = resource_monitor::start();
+
+        let archive_dir = options.archive_dir.clone();
+        let keep_copies = options.keep_copies;
+        match archive(&app_handle, &job, source, &disc_name, options).await {
+            Ok(archived) => {
+                if let Some(keep) = keep_copies {
+                    let name = disc_name.clone();
+                    let removed =
+                        blocking::run(move || remove_old_copies(&archive_dir, &name, keep))
+                            .await
+                            .and_then(|removed| removed);
+                    match removed {
+                        Ok(removed) if removed.is_empty() => {}
+                        Ok(removed) => info!("Removed old archives {removed:?}"),
+                        Err(message) => error!("{message}"),
+                    }
+                }
+                job.write_or_recover().update_status(JobStatus::Finished);
+                job.write_or_recover().subtitle = Some(archived.display().to_string());
+                notify(
+                    &app_handle,
+                    NotificationKind::Rip,
+                    NotificationEvent::Finish,
+                    format!("Finished Archiving {disc_name}"),
+                    archived.display().to_string(),
+                );
+            }
+            Err(message) if message == CANCELLED_MESSAGE => {
+                debug!("Archiving {disc_name} was cancelled");
+                job.write_or_recover().update_status(JobStatus::Error);
+                job.write_or_recover().update_message(&message);
+            }
+            Err(message) => {
+                error!("Failed to archive {disc_name}: {message}");
+                job.write_or_recover().update_status(JobStatus::Error);
+                job.write_or_recover().update_message(&message);
+                notify(
+                    &app_handle,
+                    NotificationKind::Rip,
+                    NotificationEvent::Error,
+                    format!("Failed to Archive {disc_name}"),
+                    message,
+                );
+            }
+        }
+        resources.finish(&app_handle, &job);
+        job.read_or_recover().emit_progress_change(&app_handle);
+    });
+}
+
+async fn archive(
+    app_handle: &tauri::AppHandle,
+    job: &Arc<RwLock<Job>>,
+    source: PathBuf,
+    disc_name: &str,
+    options: ArchiveOptions,
+) -> Result<PathBuf, String> {
+    fs::create_dir_all(&options.archive_dir).map_err(|e| {
+        format!(
+            "Failed to create archive folder {}: {e}",
+            options.archive_dir.display()
+        )
+    })?;
+    let destination = destination(&options.archive_dir, disc_name);
+    debug!(
+        "Archiving {} to {}",
+        source.display(),
+        destination.display()
+    );
+
+    let copy_handle = app_handle.clone();
+    let copy_job = job.clone();
+    let copy_destination = destination.clone();
+    let copied = blocking::run(move || {
+        copy_disc(
+            &source,
+            &copy_destination,
+            progress_reporter(copy_handle, copy_job),
+        )
+    })
+    .await?;
+    if let Err(message) = copied {
+        // A half copied disc is no archive
+        let _ = fs::remove_dir_all(&destination);
+        return Err(message);
+    }
+
+    if !options.zip {
+        record_copy(&destination)?;
+        return Ok(destination);
+    }
+    job.write_or_recover().subtitle = Some("Zipping".to_string());
+    job.read_or_recover().emit_progress_change(app_handle);
+    let zip_options = ZipOptions {
+        store_only: options.zip_store_only,
+        volume_size: options.zip_volume_gb.map(|gb| gb * 1_000_000_000),
+        ..ZipOptions::default()
+    };
+    let zip_handle = app_handle.clone();
+    let zip_job = job.clone();
+    let zip_destination = destination.clone();
+    let zip_file = blocking::run(move || {
+        zip_copy(
+            &zip_destination,
+            zip_options,
+            progress_reporter(zip_handle, zip_job),
+        )
+    })
+    .await??;
+    record_copy(&destination)?;
+    Ok(zip_file)
+}
+
+/// Moves the job progress along with the bytes done, stops once the job is cancelled.
+fn progress_reporter(
+    app_handle: tauri::AppHandle,
+    job: Arc<RwLock<Job>>,
+) -> impl FnMut(u64, u64) -> bool {
+    let tracker = new_tracker();
+    move |done, total| {
+        if job.read_or_recover().is_error() {
+            return false;
+        }
+        let percent = (done * 100).checked_div(total).unwrap_or(100).min(100);
+        tracker.set_progress(percent as usize);
+        job.write_or_recover().update_progress(&tracker);
+        emit_progress(&app_handle, &job, false);
+        true
+    }
+}
+
+fn new_tracker() -> progress_tracker::Base {
+    let options = ProgressOptions {
+        total: Some(100),
+        autostart: true,
+        autofinish: true,
+        starting_at: Some(0),
+        projector_type: Some("smoothed".to_string()),
+        projector_strength: Some(0.1),
+        projector_at: Some(0.0),
+    };
+    progress_tracker::Base::new(Some(options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::test_dir;
+
+    #[test]
+    fn copies_the_disc_verbatim() {
+        let root = test_dir("archive-copy");
+        let disc = root.join("PHOTOS");
+        fs::create_dir_all(disc.join("DCIM")).unwrap();
+        fs::write(disc.join("DCIM/IMG_0001.JPG"), b"jpeg").unwrap();
+        fs::write(disc.join("README.TXT"), b"hello").unwrap();
+
+        let destination = destination(&root.join("archive"), "PHOTOS");
+        let mut progress = Vec::new();
+        let copied = copy_disc(&disc, &destination, |copied, total| {
+            progress.push((copied, total));
+            true
+        })
+        .unwrap();
+
+        assert_eq!(copied, 9);
+        assert_eq!(progress.last(), Some(&(9, 9)));
+        assert_eq!(
+            fs::read(destination.join("DCIM/IMG_0001.JPG")).unwrap(),
+            b"jpeg"
+        );
+        assert_eq!(fs::read(destination.join("README.TXT")).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn keeps_the_newest_copies_of_a_disc() {
+        let root = test_dir("archive-retention");
+        let old = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        for (copy, age) in [("PHOTOS (3)", 0), ("PHOTOS", 1), ("PHOTOS (2)", 2)] {
+            let modified = old - std::time::Duration::from_secs(age);
+            for file in [format!("{copy}.zip.001"), format!("{copy}.zip.002")] {
+                File::create(root.join(file))
+                    .unwrap()
+                    .set_modified(modified)
+                    .unwrap();
+            }
+            record_copy(&root.join(copy)).unwrap();
+        }
+        fs::create_dir_all(root.join("PHOTOS 2")).unwrap();
+
+        let removed = remove_old_copies(&root, "PHOTOS", 1).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert!(root.join("PHOTOS (3).zip.002").exists());
+        assert!(!root.join("PHOTOS.zip.001").exists());
+        assert!(!root.join("PHOTOS (2).zip.002").exists());
+        assert!(root.join("PHOTOS 2").exists());
+        assert_eq!(archived_copies(&root), vec!["PHOTOS (3)".to_string()]);
+    }
+
+    #[test]
+    fn leaves_folders_it_did_not_archive() {
+        let root = test_dir("archive-foreign");
+        fs::create_dir_all(root.join("Pictures")).unwrap();
+        fs::write(root.join("Pictures/family.jpg"), b"jpeg").unwrap();
+        fs::create_dir_all(root.join("Pictures (2)")).unwrap();
+        record_copy(&root.join("Pictures (2)")).unwrap();
+
+        let removed = remove_old_copies(&root, "Pictures", 0).unwrap();
+        assert_eq!(removed, vec!["Pictures (2)".to_string()]);
+        assert!(root.join("Pictures/family.jpg").exists());
+        assert!(!root.join("Pictures (2)").exists());
+    }
+
+    #[test]
+    fn never_reuses_an_archive_folder() {
+        let root = test_dir("archive-destination");
+        assert_eq!(destination(&root, "PHOTOS"), root.join("PHOTOS"));
+
+        fs::create_dir_all(root.join("PHOTOS")).unwrap();
+        fs::write(root.join("PHOTOS (2).zip"), b"").unwrap();
+        assert_eq!(destination(&root, "PHOTOS"), root.join("PHOTOS (3)"));
+        assert_eq!(destination(&root, " "), root.join("Disc"));
+    }
+
+    #[test]
+    fn keeps_dotted_disc_labels_whole() {
+        let root = test_dir("archive-dotted");
+        let disc = root.join("archive").join("VOL.1");
+        fs::create_dir_all(&disc).unwrap();
+        fs::write(disc.join("README.TXT"), b"hello").unwrap();
+
+        let zip_file = zip_copy(&disc, ZipOptions::default(), |_, _| true).unwrap();
+        assert_eq!(zip_file, root.join("archive").join("VOL.1.zip"));
+        assert_eq!(
+            destination(&root.join("archive"), "VOL.1"),
+            root.join("archive").join("VOL.1 (2)")
+        );
+
+        fs::create_dir_all(root.join("archive").join("VOL.1 (2)")).unwrap();
+        record_copy(&disc).unwrap();
+        record_copy(&root.join("archive").join("VOL.1 (2)")).unwrap();
+        let removed = remove_old_copies(&root.join("archive"), "VOL.1", 1).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert!(root.join("archive").join("VOL.1 (2)").exists());
+    }
+}
@@ -0,0 +1,233 @@
+//! Radarr and Sonarr: after a title is uploaded the *arr that manages it rescans the movie or
+//! series, so it knows about the file and doesn't grab a copy of its own.
+//!
+//! Titles are found by their TMDB id, titles the *arr doesn't manage are left alone.
+use crate::services::network;
+use crate::state::title_video::Video;
+use crate::state::AppState;
+use log::{debug, error, info};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::{Client, RequestBuilder};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// One Radarr or Sonarr server, off while the url or the API key is missing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArrServer {
+    /// e.g. http://nas:7878
+    pub url: Option<String>,
+    /// Settings > General > API Key
+    pub api_key: Option<String>,
+}
+
+impl ArrServer {
+    pub fn is_set_up(&self) -> bool {
+        self.url.is_some() && self.api_key.is_some()
+    }
+
+    fn request(
+        &self,
+        client: &Client,
+        builder: fn(&Client, String) -> RequestBuilder,
+        path: &str,
+    ) -> Option<RequestBuilder> {
+        let url = self.url.as_deref()?.trim_end_matches('/');
+        Some(
+            builder(client, format!("{url}/api/v3{path}"))
+                .header("X-Api-Key", self.api_key.as_deref()?)
+                .timeout(REQUEST_TIMEOUT),
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArrOptions {
+    /// Rescans movies
+    pub radarr: ArrServer,
+    /// Rescans TV shows
+    pub sonarr: ArrServer,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arr {
+    Radarr,
+    Sonarr,
+}
+
+impl Arr {
+    fn name(self) -> &'static str {
+        match self {
+            Arr::Radarr => "Radarr",
+            Arr::Sonarr => "Sonarr",
+        }
+    }
+
+    /// Where the *arr lists what it manages
+    fn list_path(self, tmdb_id: u32) -> String {
+        match self {
+            Arr::Radarr => format!("/movie?tmdbId={tmdb_id}"),
+            // Sonarr can't filter by TMDB id, the list is filtered here
+            Arr::Sonarr => "/series".to_string(),
+        }
+    }
+
+    fn rescan_command(self, id: u64) -> Value {
+        match self {
+            Arr::Radarr => json!({"name": "RescanMovie", "movieId": id}),
+            Arr::Sonarr => json!({"name": "RescanSeries", "seriesId": id}),
+        }
+    }
+}
+
+/// The *arr and TMDB id of an uploaded title, personal videos aren't managed by either.
+pub fn target(video: &Video) -> Option<(Arr, u32)> {
+    match video {
+        Video::Movie(movie) => Some((Arr::Radarr, movie.movie.id)),
+        Video::Tv(tv) => Some((Arr::Sonarr, tv.tv.id.into())),
+        Video::Personal(_) => None,
+    }
+}
+
+/// The *arr id of the movie or series with `tmdb_id` in a list response.
+pub fn find_id(items: &[Value], tmdb_id: u32) -> Option<u64> {
+    items
+        .iter()
+        .find(|item| item.get("tmdbId").and_then(Value::as_u64) == Some(tmdb_id.into()))?
+        .get("id")?
+        .as_u64()
+}
+
+/// Asks the *arr to rescan the title, `Ok(false)` when it doesn't manage it.
+pub async fn rescan(
+    client: &Client,
+    server: &ArrServer,
+    arr: Arr,
+    tmdb_id: u32,
+) -> Result<bool, String> {
+    let name = arr.name();
+    let response = server
+        .request(client, Client::get, &arr.list_path(tmdb_id))
+        .ok_or(format!("{name} needs a url and an API key"))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach {name}: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("{name} returned {}", response.status()));
+    }
+    let items: Vec<Value> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to read the {name} library: {e}"))?;
+    let Some(id) = find_id(&items, tmdb_id) else {
+        return Ok(false);
+    };
+
+    let response = server
+        .request(client, Client::post, "/command")
+        .ok_or(format!("{name} needs a url and an API key"))?
+        .header("Content-Type", "application/json")
+        .body(arr.rescan_command(id).to_string())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach {name}: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "{name} returned {} for the rescan",
+            response.status()
+        ));
+    }
+    Ok(true)
+}
+
+/// Rescans an uploaded title in the background, a failed rescan never fails the upload.
+pub fn spawn_rescan(app_handle: &AppHandle, video: &Video) {
+    let Some((arr, tmdb_id)) = target(video) else {
+        return;
+    };
+    let options = app_handle.state::<AppState>().arr_options();
+    let server = match arr {
+        Arr::Radarr => options.radarr,
+        Arr::Sonarr => options.sonarr,
+    };
+    if !server.is_set_up() || network::is_offline(app_handle) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        match rescan(&Client::new(), &server, arr, tmdb_id).await {
+            Ok(true) => info!("{} rescans TMDB {tmdb_id}", arr.name()),
+            Ok(false) => debug!("{} doesn't manage TMDB {tmdb_id}", arr.name()),
+            Err(e) => error!("{e}"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn server(mock: &MockServer) -> ArrServer {
+        ArrServer {
+            url: Some(format!("{}/", mock.uri())),
+            api_key: Some("key".to_string()),
+        }
+    }
+
+    #[test]
+    fn finds_the_id_by_tmdb_id() {
+        let items = vec![
+            json!({"id": 1, "title": "Alien", "tmdbId": 348}),
+            json!({"id": 2, "title": "The Matrix", "tmdbId": 603}),
+        ];
+        assert_eq!(find_id(&items, 603), Some(2));
+        assert_eq!(find_id(&items, 604), None);
+    }
+
+    #[tokio::test]
+    async fn rescans_the_movie_radarr_manages() {
+        let mock = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/movie"))
+            .and(query_param("tmdbId", "603"))
+            .and(header("X-Api-Key", "key"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"id": 7, "tmdbId": 603}])),
+            )
+            .mount(&mock)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v3/command"))
+            .and(body_json(json!({"name": "RescanMovie", "movieId": 7})))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock)
+            .await;
+
+        let result = rescan(&Client::new(), &server(&mock), Arr::Radarr, 603).await;
+        assert_eq!(result, Ok(true));
+    }
+
+    #[tokio::test]
+    async fn leaves_series_sonarr_doesnt_manage_alone() {
+        let mock = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/series"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"id": 3, "tmdbId": 2316}])),
+            )
+            .mount(&mock)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v3/command"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&mock)
+            .await;
+
+        let result = rescan(&Client::new(), &server(&mock), Arr::Sonarr, 1399).await;
+        assert_eq!(result, Ok(false));
+    }
+}
@@ -0,0 +1,220 @@
+//! Box sets put their episodes on every disc in the same title order, once disc 1 of a
+//! season is assigned the next discs can be assigned the same way.
+//!
+//! A pattern is remembered per show and season every time a season is ripped. It keeps the
+//! disc titles in episode order and the episodes they were, the next disc gets the same
+//! titles suggested for the episodes that follow.
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::models::title_info::{TitleId, TitleInfo};
+use crate::state::job_state::Job;
+use crate::state::locks::RwLockExt;
+use crate::state::title_video::Video;
+use crate::the_movie_db::{SeasonEpisode, SeasonResponse, TvId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AssignmentPattern {
+    pub tv_id: TvId,
+    pub season_number: u32,
+    /// Label of the disc the pattern was learned from
+    pub disc_name: String,
+    /// The disc titles of the first part of every episode, in episode order
+    pub title_ids: Vec<TitleId>,
+    pub first_episode: u32,
+    pub last_episode: u32,
+}
+
+impl AssignmentPattern {
+    /// The pattern of a season rip, `None` when the job has no episodes of that season.
+    pub fn learn(job: &Job, tv_id: TvId, season_number: u32, disc_name: &str) -> Option<Self> {
+        let mut titles_by_episode = BTreeMap::new();
+        for title_video in &job.title_videos {
+            let title_video = title_video.read_or_recover();
+            let (Video::Tv(tv_season_episode), Some(title)) =
+                (&title_video.video, &title_video.title)
+            else {
+                continue;
+            };
+            if tv_season_episode.tv.id != tv_id
+                || tv_season_episode.season.season_number != season_number
+                || tv_season_episode.part != 1
+            {
+                continue;
+            }
+            titles_by_episode.insert(tv_season_episode.episode.episode_number, title.id);
+        }
+        let first_episode = *titles_by_episode.keys().next()?;
+        let last_episode = *titles_by_episode.keys().last()?;
+        Some(AssignmentPattern {
+            tv_id,
+            season_number,
+            disc_name: disc_name.to_string(),
+            title_ids: titles_by_episode.into_values().collect(),
+            first_episode,
+            last_episode,
+        })
+    }
+
+    /// `(episode number, title id)` pairs for a disc of the same season.
+    ///
+    /// The disc the pattern came from gets its own episodes again, any other disc the
+    /// episodes after them. Titles the disc doesn't have and episodes in `skip`, already
+    /// assigned or in the library, are left out.
+    pub fn suggest(
+        &self,
+        disc_name: &str,
+        titles: &[TitleInfo],
+        episodes: &[SeasonEpisode],
+        skip: &HashSet<u32>,
+    ) -> Vec<(u32, TitleId)> {
+        let start = if disc_name == self.disc_name {
+            self.first_episode
+        } else {
+            self.last_episode + 1
+        };
+        let mut episode_numbers: Vec<u32> = episodes
+            .iter()
+            .map(|episode| episode.episode_number)
+            .filter(|number| *number >= start)
+            .collect();
+        episode_numbers.sort_unstable();
+        episode_numbers
+            .into_iter()
+            .zip(&self.title_ids)
+            .filter(|(number, title_id)| {
+                !skip.contains(number) && titles.iter().any(|title| title.id == **title_id)
+            })
+            .map(|(number, title_id)| (number, *title_id))
+            .collect()
+    }
+}
+
+/// Remembers `pattern`, replacing the one of the same season.
+pub fn remember(patterns: &mut Vec<AssignmentPattern>, pattern: AssignmentPattern) {
+    patterns.retain(|other| {
+        other.tv_id != pattern.tv_id || other.season_number != pattern.season_number
+    });
+    patterns.push(pattern);
+}
+
+pub fn find(
+    patterns: &[AssignmentPattern],
+    tv_id: TvId,
+    season_number: u32,
+) -> Option<&AssignmentPattern> {
+    patterns
+        .iter()
+        .find(|pattern| pattern.tv_id == tv_id && pattern.season_number == season_number)
+}
+
+/// The suggestions for `disk`, episodes in `owned` and whatever the job already assigned are
+/// left out.
+pub fn suggestions(
+    patterns: &[AssignmentPattern],
+    tv_id: TvId,
+    season: &SeasonResponse,
+    disk: &OpticalDiskInfo,
+    job: Option<&Job>,
+    owned: &HashSet<u32>,
+) -> Vec<(u32, TitleId)> {
+    let Some(pattern) = find(patterns, tv_id, season.season_number) else {
+        return Vec::new();
+    };
+    let mut skip = owned.clone();
+    let mut used_titles = HashSet::new();
+    for title_video in job.iter().flat_map(|job| &job.title_videos) {
+        let title_video = title_video.read_or_recover();
+        if let Some(title) = &title_video.title {
+            used_titles.insert(title.id);
+        }
+        if let Video::Tv(tv_season_episode) = &title_video.video {
+            skip.insert(tv_season_episode.episode.episode_number);
+        }
+    }
+    let titles: Vec<TitleInfo> = disk
+        .clone_titles()
+        .into_iter()
+        .filter(|title| !used_titles.contains(&title.id))
+        .collect();
+    pattern.suggest(&disk.name, &titles, &season.episodes, &skip)
+}
+
+/// The store only keeps strings, the patterns are kept as one JSON string.
+pub fn to_store_value(patterns: &[AssignmentPattern]) -> String {
+    serde_json::to_string(patterns).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn from_store_value(value: &str) -> Vec<AssignmentPattern> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::the_movie_db::EpisodeId;
+
+    fn episode(episode_number: u32) -> SeasonEpisode {
+        SeasonEpisode {
+            air_date: None,
+            episode_number,
+            episode_type: "standard".to_string(),
+            id: EpisodeId::from(1000 + episode_number),
+            name: format!("Episode {episode_number}"),
+            overview: String::new(),
+            production_code: None,
+            runtime: Some(45),
+            season_number: 1,
+            show_id: TvId::from(1),
+            still_path: None,
+            vote_average: 0.0,
+            vote_count: 0,
+            crew: vec![],
+            guest_stars: vec![],
+        }
+    }
+
+    fn disc_one() -> AssignmentPattern {
+        AssignmentPattern {
+            tv_id: TvId::from(1),
+            season_number: 1,
+            disc_name: "SHOW_S1_D1".to_string(),
+            title_ids: [2, 0, 1].map(TitleId::from).to_vec(),
+            first_episode: 1,
+            last_episode: 3,
+        }
+    }
+
+    #[test]
+    fn suggests_the_same_title_order_for_the_next_episodes() {
+        let titles: Vec<TitleInfo> = (0..4).map(TitleInfo::new).collect();
+        let episodes: Vec<SeasonEpisode> = (1..=8).map(episode).collect();
+
+        assert_eq!(
+            disc_one().suggest("SHOW_S1_D2", &titles, &episodes, &HashSet::new()),
+            vec![(4, 2.into()), (5, 0.into()), (6, 1.into())]
+        );
+        assert_eq!(
+            disc_one().suggest("SHOW_S1_D1", &titles, &episodes, &HashSet::from([2])),
+            vec![(1, 2.into()), (3, 1.into())]
+        );
+        assert!(disc_one()
+            .suggest("SHOW_S1_D2", &titles[..2], &episodes[..4], &HashSet::new())
+            .is_empty());
+    }
+
+    #[test]
+    fn remembers_one_pattern_per_season() {
+        let mut patterns = vec![disc_one()];
+        let mut disc_two = disc_one();
+        disc_two.disc_name = "SHOW_S1_D2".to_string();
+        disc_two.first_episode = 4;
+        disc_two.last_episode = 6;
+        remember(&mut patterns, disc_two.clone());
+
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(find(&patterns, TvId::from(1), 1), Some(&disc_two));
+        assert_eq!(find(&patterns, TvId::from(1), 2), None);
+        assert_eq!(from_store_value(&to_store_value(&patterns)), patterns);
+    }
+}
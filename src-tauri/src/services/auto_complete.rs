@@ -45,7 +45,7 @@ fn build_index(titles: &[String]) -> HashMap<String, Vec<usize>> {
                 prefix_len += 3;
             }
 
-            if token_len % 3 != 0 {
+            if !token_len.is_multiple_of(3) {
                 let full_token: String = chars.iter().collect();
                 index.entry(full_token).or_default().push(id);
             }
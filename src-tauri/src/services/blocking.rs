@@ -0,0 +1,13 @@
+/// Runs blocking network I/O (TMDB, FTP) on the blocking thread pool.
+///
+/// Async commands and background tasks await this instead of calling the blocking
+/// clients directly, so a slow server never stalls the async runtime.
+pub async fn run<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Blocking task failed: {e}"))
+}
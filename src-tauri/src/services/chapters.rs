@@ -0,0 +1,218 @@
+use log::debug;
+use regex::Regex;
+use std::time::Duration;
+use tauri_plugin_http::reqwest::Client;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+static CHAPTER_DB_SEARCH_URL: &str = "https://www.chapterdb.org/chapters/search";
+
+/// A chapter in the OGM "simple" format used by mkvextract and mkvpropedit.
+///
+/// ```text
+/// CHAPTER01=00:00:00.000
+/// CHAPTER01NAME=Chapter 01
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    pub time: String,
+    pub name: String,
+}
+
+pub fn parse_simple(content: &str) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for line in content.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let Some(key) = key.strip_prefix("CHAPTER") else {
+            continue;
+        };
+        if let Some(number) = key.strip_suffix("NAME") {
+            if let Some(chapter) = number
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| chapters.get_mut(n.wrapping_sub(1)))
+            {
+                chapter.name = value.to_string();
+            }
+        } else if key.parse::<usize>().is_ok() {
+            chapters.push(Chapter {
+                time: value.to_string(),
+                name: String::new(),
+            });
+        }
+    }
+    chapters
+}
+
+pub fn render_simple(chapters: &[Chapter]) -> String {
+    chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| {
+            let number = index + 1;
+            format!(
+                "CHAPTER{number:02}={}\nCHAPTER{number:02}NAME={}\n",
+                chapter.time, chapter.name
+            )
+        })
+        .collect()
+}
+
+/// True when makemkv only gave the chapters numbered names like "Chapter 01".
+pub fn has_generic_names(chapters: &[Chapter]) -> bool {
+    let generic = Regex::new(r"(?i)^chapter\s*\d+$").expect("invalid chapter regex");
+    chapters
+        .iter()
+        .all(|chapter| chapter.name.trim().is_empty() || generic.is_match(chapter.name.trim()))
+}
+
+/// Keep the times from the rip and take the names from the lookup.
+pub fn apply_names(chapters: &[Chapter], names: &[String]) -> Vec<Chapter> {
+    chapters
+        .iter()
+        .zip(names)
+        .map(|(chapter, name)| Chapter {
+            time: chapter.time.clone(),
+            name: name.clone(),
+        })
+        .collect()
+}
+
+/// Search ChapterDB for a chapter set with the same number of chapters as the rip.
+///
+/// Returns `Ok(None)` when nothing matched, the chapter count is the only thing we
+/// can compare because ChapterDB entries come from discs with different timings.
+pub async fn fetch_chapter_db_names(
+    api_key: &str,
+    title: &str,
+    chapter_count: usize,
+) -> Result<Option<Vec<String>>, String> {
+    let response = Client::new()
+        .get(CHAPTER_DB_SEARCH_URL)
+        .query(&[("title", title)])
+        .header("ApiKey", api_key)
+        .header("User-Agent", "Reelix")
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach ChapterDB: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!("ChapterDB returned {}", response.status()));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read ChapterDB response: {e}"))?;
+    let chapter_sets = parse_chapter_db_results(&body);
+    debug!(
+        "ChapterDB returned {} chapter sets for {title}",
+        chapter_sets.len()
+    );
+    Ok(chapter_sets
+        .into_iter()
+        .find(|names| names.len() == chapter_count && !names.iter().all(|n| n.is_empty())))
+}
+
+/// Chapter names of every `<chapterInfo>` in a ChapterDB search response.
+fn parse_chapter_db_results(xml: &str) -> Vec<Vec<String>> {
+    let chapter = Regex::new(r"<chapter\s[^>]*>").expect("invalid chapter regex");
+    let name = Regex::new(r#"\sname="([^"]*)""#).expect("invalid name regex");
+
+    xml.split("<chapterInfo")
+        .skip(1)
+        .map(|info| {
+            chapter
+                .find_iter(info)
+                .map(|element| {
+                    name.captures(element.as_str())
+                        .map(|captures| decode_entities(&captures[1]))
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<String>>()
+        })
+        .filter(|names| !names.is_empty())
+        .collect()
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE: &str = "CHAPTER01=00:00:00.000\nCHAPTER01NAME=Chapter 01\nCHAPTER02=00:05:12.345\nCHAPTER02NAME=Chapter 02\n";
+
+    #[test]
+    fn round_trips_simple_format() {
+        let chapters = parse_simple(SIMPLE);
+        assert_eq!(
+            chapters,
+            vec![
+                Chapter {
+                    time: "00:00:00.000".to_string(),
+                    name: "Chapter 01".to_string(),
+                },
+                Chapter {
+                    time: "00:05:12.345".to_string(),
+                    name: "Chapter 02".to_string(),
+                },
+            ]
+        );
+        assert_eq!(render_simple(&chapters), SIMPLE);
+    }
+
+    #[test]
+    fn detects_generic_names() {
+        let mut chapters = parse_simple(SIMPLE);
+        assert!(has_generic_names(&chapters));
+
+        chapters[1].name = "The Chase".to_string();
+        assert!(!has_generic_names(&chapters));
+    }
+
+    #[test]
+    fn applies_names_keeping_times() {
+        let chapters = parse_simple(SIMPLE);
+        let named = apply_names(&chapters, &["Opening".to_string(), "Heist".to_string()]);
+        assert_eq!(named[0].time, "00:00:00.000");
+        assert_eq!(named[1].name, "Heist");
+    }
+
+    #[test]
+    fn parses_chapter_db_results() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<results>
+  <chapterInfo xml:lang="eng" version="3">
+    <title>Alien</title>
+    <chapters>
+      <chapter time="00:00:00" name="Main Titles" />
+      <chapter name="Mother &amp; Crew" time="00:04:10" />
+    </chapters>
+  </chapterInfo>
+  <chapterInfo xml:lang="eng" version="3">
+    <title>Alien</title>
+    <chapters>
+      <chapter time="00:00:00" name="Opening" />
+    </chapters>
+  </chapterInfo>
+</results>"#;
+
+        assert_eq!(
+            parse_chapter_db_results(xml),
+            vec![
+                vec!["Main Titles".to_string(), "Mother & Crew".to_string()],
+                vec!["Opening".to_string()],
+            ]
+        );
+    }
+}
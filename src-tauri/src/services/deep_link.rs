@@ -0,0 +1,71 @@
+//! `reelix://` links open the app on a TMDB page, `reelix://movie/603`, `reelix://tv/1396`
+//! and `reelix://tv/1396/season/2`. The ids are TMDB's, the same ones themoviedb.org shows in
+//! its URLs.
+pub const SCHEME: &str = "reelix";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeepLink {
+    Movie(u32),
+    Tv(u32),
+    Season { tv_id: u32, season_number: u32 },
+}
+
+impl DeepLink {
+    /// The page a link points to, `None` for links Reelix has no page for.
+    ///
+    /// A trailing slash, a query or a fragment the browser added are ignored and the TMDB
+    /// slug is allowed after the id, `reelix://movie/603-the-matrix`.
+    pub fn parse(url: &str) -> Option<Self> {
+        let (scheme, rest) = url.split_once("://")?;
+        if !scheme.eq_ignore_ascii_case(SCHEME) {
+            return None;
+        }
+        let path = rest.split(['?', '#']).next().unwrap_or_default();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        match segments.as_slice() {
+            ["movie", id] => Some(DeepLink::Movie(tmdb_id(id)?)),
+            ["tv", id] => Some(DeepLink::Tv(tmdb_id(id)?)),
+            ["tv", id, "season", season_number] => Some(DeepLink::Season {
+                tv_id: tmdb_id(id)?,
+                season_number: season_number.parse().ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// `603` of `603-the-matrix`
+fn tmdb_id(segment: &str) -> Option<u32> {
+    segment.split('-').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_movie_tv_and_season_links() {
+        assert_eq!(
+            DeepLink::parse("reelix://movie/603"),
+            Some(DeepLink::Movie(603))
+        );
+        assert_eq!(
+            DeepLink::parse("Reelix://movie/603-the-matrix/?ref=wiki#top"),
+            Some(DeepLink::Movie(603))
+        );
+        assert_eq!(
+            DeepLink::parse("reelix://tv/1396"),
+            Some(DeepLink::Tv(1396))
+        );
+        assert_eq!(
+            DeepLink::parse("reelix://tv/1396/season/2"),
+            Some(DeepLink::Season {
+                tv_id: 1396,
+                season_number: 2
+            })
+        );
+        assert_eq!(DeepLink::parse("reelix://movie/matrix"), None);
+        assert_eq!(DeepLink::parse("reelix://person/6384"), None);
+        assert_eq!(DeepLink::parse("https://movie/603"), None);
+    }
+}
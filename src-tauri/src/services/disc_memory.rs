@@ -0,0 +1,154 @@
+//! What the discs of a label were ripped as, so the next disc of a box set starts in the
+//! right flow. A disc whose label starts like a season disc ripped before offers that show
+//! as soon as it is inserted.
+//!
+//! Labels are compared by their words without the disc numbering, `FRIENDS_S3_D2` is
+//! remembered as `FRIENDS S3` and `FRIENDS_S4_D1` still starts like it.
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::services::notifications;
+use crate::services::rip_plan;
+use crate::state::AppState;
+use crate::templates::toast::Toast;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+/// Oldest labels are forgotten first
+const MAX_MEMORIES: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum DiscContent {
+    Movie,
+    Tv {
+        tv_id: u32,
+        name: String,
+        season_number: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DiscMemory {
+    /// The label words, e.g. `FRIENDS S3`
+    pub label: String,
+    pub content: DiscContent,
+}
+
+/// The label words of a disc name, empty for labels like `DVD_VIDEO` that say nothing.
+pub fn label_key(disc_name: &str) -> String {
+    rip_plan::label_words(disc_name).join(" ")
+}
+
+/// Remember what a disc was ripped as, replacing what its label was before.
+pub fn remember(memories: &mut Vec<DiscMemory>, disc_name: &str, content: DiscContent) {
+    let label = label_key(disc_name);
+    if label.is_empty() {
+        return;
+    }
+    memories.retain(|memory| memory.label != label);
+    memories.push(DiscMemory { label, content });
+    if memories.len() > MAX_MEMORIES {
+        memories.drain(..memories.len() - MAX_MEMORIES);
+    }
+}
+
+/// The memory of the label the disc name shares the most leading words with, and whether
+/// it is the same label. The latest wins a tie.
+pub fn find<'a>(memories: &'a [DiscMemory], disc_name: &str) -> Option<(&'a DiscMemory, bool)> {
+    let words = rip_plan::label_words(disc_name);
+    memories
+        .iter()
+        .filter_map(|memory| {
+            let shared = memory
+                .label
+                .split(' ')
+                .zip(&words)
+                .take_while(|(remembered, word)| remembered == word)
+                .count();
+            let same = shared == words.len() && memory.label.split(' ').count() == shared;
+            (shared > 0).then_some((memory, shared, same))
+        })
+        // The last of the longest, memories are in the order they were learned
+        .max_by_key(|(_, shared, _)| *shared)
+        .map(|(memory, _, same)| (memory, same))
+}
+
+/// Offers the show a newly inserted disc most likely belongs to. Movies keep the default
+/// flow, they are found by searching anyway.
+pub fn suggest_on_insert(app_handle: &AppHandle, disk: &OpticalDiskInfo) {
+    let memories = app_handle.state::<AppState>().disc_memories();
+    let Some((memory, same_label)) = find(&memories, &disk.name) else {
+        return;
+    };
+    let DiscContent::Tv {
+        tv_id,
+        name,
+        season_number,
+    } = &memory.content
+    else {
+        debug!("{} was ripped as a movie before", disk.name);
+        return;
+    };
+    debug!("{} looks like {name} ({})", disk.name, memory.label);
+    let toast = if same_label {
+        Toast::info(
+            format!("Looks like {name}"),
+            format!("{} is probably a disc of season {season_number}", disk.name),
+        )
+        .with_action(
+            format!("Open Season {season_number}"),
+            format!("/season?tvId={tv_id}&seasonNumber={season_number}"),
+        )
+    } else {
+        Toast::info(
+            format!("Looks like {name}"),
+            format!("{} starts like the {name} discs", disk.name),
+        )
+        .with_action("Open Show", format!("/tv/{tv_id}"))
+    };
+    notifications::emit_toast(app_handle, toast.with_auto_hide(15000));
+}
+
+pub fn to_store_value(memories: &[DiscMemory]) -> String {
+    serde_json::to_string(memories).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn from_store_value(value: &str) -> Vec<DiscMemory> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn friends(season_number: u32) -> DiscContent {
+        DiscContent::Tv {
+            tv_id: 1668,
+            name: "Friends".to_string(),
+            season_number,
+        }
+    }
+
+    #[test]
+    fn finds_the_label_the_disc_starts_like() {
+        let mut memories = Vec::new();
+        remember(&mut memories, "FRIENDS_S3_D1", friends(3));
+        remember(&mut memories, "ALIEN_WS", DiscContent::Movie);
+        remember(&mut memories, "DVD_VIDEO", DiscContent::Movie);
+        assert_eq!(memories.len(), 2);
+
+        let (memory, same) = find(&memories, "FRIENDS_S3_D2").unwrap();
+        assert_eq!(memory.content, friends(3));
+        assert!(same);
+        let (memory, same) = find(&memories, "FRIENDS_S4_D1").unwrap();
+        assert_eq!(memory.label, "FRIENDS S3");
+        assert!(!same);
+        assert_eq!(find(&memories, "ALIENS_D1"), None);
+
+        remember(&mut memories, "FRIENDS_S3_DISC3", friends(4));
+        assert_eq!(memories.len(), 2);
+        assert_eq!(
+            find(&memories, "FRIENDS_S3_D1").unwrap().0.content,
+            friends(4)
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Works out what a new disc is while makemkvcon still reads its titles. The disc label is
+//! searched on TMDB at the same time, once the titles are in the label results and the
+//! title lengths pick the best match and a toast offers it. The search box is filled with
+//! the label, the search page shows the results when it is opened.
+//!
+//! Discs the rip plan or a disc memory already knows are left to those, see `rip_plan` and
+//! `disc_memory`.
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::services::plex::search_multi;
+use crate::services::title_analysis::TitleAnalysis;
+use crate::services::{disc_memory, notifications, rip_plan};
+use crate::state::locks::{MutexExt, RwLockExt};
+use crate::state::AppState;
+use crate::templates::toast::Toast;
+use crate::the_movie_db::{SearchResponse, SearchResult};
+use log::debug;
+use tauri::{AppHandle, Manager};
+
+/// What the disc label found on TMDB
+pub struct LabelSearch {
+    pub query: String,
+    pub response: SearchResponse,
+}
+
+/// Searches TMDB for the disc label, `None` for labels like `DVD_VIDEO` and for discs that
+/// are matched another way.
+pub async fn search_label(app_handle: &AppHandle, disk: &OpticalDiskInfo) -> Option<LabelSearch> {
+    let state = app_handle.state::<AppState>();
+    let words = rip_plan::label_words(&disk.name);
+    if words.is_empty()
+        || disk.is_audio_cd()
+        || state.lock_rip_plan().match_disc(&disk.name, None).is_some()
+        || disc_memory::find(&state.disc_memories(), &disk.name).is_some()
+    {
+        return None;
+    }
+    let query = words.join(" ").to_lowercase();
+    match search_multi(app_handle, &query).await {
+        Ok(response) => Some(LabelSearch { query, response }),
+        Err(e) => {
+            debug!("No pre-scan search for {}: {}", disk.name, e.message);
+            None
+        }
+    }
+}
+
+/// The first show when the disc has a run of episode length titles, the first movie
+/// otherwise. Either kind is better than nothing when the other isn't found.
+fn best_match(results: &[SearchResult], has_episodes: bool) -> Option<&SearchResult> {
+    let preferred = if has_episodes { "tv" } else { "movie" };
+    results
+        .iter()
+        .find(|result| result.media_type == preferred)
+        .or_else(|| {
+            results
+                .iter()
+                .find(|result| result.media_type == "movie" || result.media_type == "tv")
+        })
+}
+
+/// Offers the best match of the label search once the titles of the disc are loaded.
+pub fn announce(app_handle: &AppHandle, disk: &OpticalDiskInfo, search: LabelSearch) {
+    let state = app_handle.state::<AppState>();
+    let Some(loaded) = state.find_optical_disk_by_id(&disk.id) else {
+        return;
+    };
+    let titles = loaded.read_or_recover().titles.lock_or_recover().clone();
+    if titles.is_empty() {
+        return;
+    }
+    let analysis = TitleAnalysis::analyze(&titles);
+    let Some(result) = best_match(&search.response.results, analysis.has_episode_run()) else {
+        debug!("Pre-scan found nothing for {}", search.query);
+        return;
+    };
+    let title = match result.year() {
+        Some(year) => format!("{} ({year})", result.get_title()),
+        None => result.get_title(),
+    };
+    debug!("Pre-scan of {} matched {title}", disk.name);
+    state.save_query(&search.query);
+    notifications::emit_toast(
+        app_handle,
+        Toast::info("Disc ready", format!("{}, best match: {title}", disk.name))
+            .with_action("Open", format!("/{}/{}", result.media_type, result.id))
+            .with_auto_hide(15000),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_shows_for_discs_of_episodes() {
+        let response: SearchResponse = serde_json::from_str(
+            r#"{"page": 1, "total_pages": 1, "total_results": 3, "results": [
+                {"id": 1, "adult": false, "media_type": "person", "name": "Alien Person"},
+                {"id": 348, "adult": false, "media_type": "movie", "title": "Alien"},
+                {"id": 81, "adult": false, "media_type": "tv", "name": "Alien Nation"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(best_match(&response.results, false).unwrap().id, 348);
+        assert_eq!(best_match(&response.results, true).unwrap().id, 81);
+        assert!(best_match(&response.results[..1], true).is_none());
+    }
+}
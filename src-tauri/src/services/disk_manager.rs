@@ -7,6 +7,54 @@ use std::path::Path;
 #[cfg(target_os = "macos")]
 use std::thread;
 
+/// Tray handling around a rip, kept in the settings store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrayOptions {
+    /// Keeps the tray shut while makemkvcon reads the disc
+    pub lock_during_rip: bool,
+    /// Closes the tray this long after the eject, the time a disc changer needs to load the next disc
+    pub close_after_eject_seconds: Option<u64>,
+}
+
+impl Default for TrayOptions {
+    fn default() -> Self {
+        TrayOptions {
+            lock_during_rip: true,
+            close_after_eject_seconds: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrayState {
+    Open,
+    Closed,
+    /// The platform can't tell, e.g. slot loading drives
+    Unknown,
+}
+
+impl TrayState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrayState::Open => "open",
+            TrayState::Closed => "closed",
+            TrayState::Unknown => "unknown",
+        }
+    }
+
+    /// Maps the `CDROM_DRIVE_STATUS` result of the Linux cdrom driver.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn from_drive_status(status: i32) -> Self {
+        match status {
+            // CDS_TRAY_OPEN
+            2 => TrayState::Open,
+            // CDS_NO_DISC, CDS_DRIVE_NOT_READY, CDS_DISC_OK
+            1 | 3 | 4 => TrayState::Closed,
+            _ => TrayState::Unknown,
+        }
+    }
+}
+
 #[cfg(target_os = "macos")]
 pub fn eject(volume: &Path) {
     let ws = NSWorkspace::sharedWorkspace();
@@ -125,3 +173,139 @@ fn get_device_from_mount(mount_point: &Path) -> Option<String> {
 
     None
 }
+
+#[cfg(target_os = "linux")]
+fn run_eject(args: &[&str]) -> Result<(), String> {
+    use std::process::Command;
+
+    debug!("Executing command: eject {args:?}");
+    let output = Command::new("eject")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run eject command: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "eject {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn close_tray(device: &str) -> Result<(), String> {
+    if device.is_empty() {
+        return Err("Device path is empty, cannot close the tray".to_string());
+    }
+    run_eject(&["-t", device])
+}
+
+/// Locks the eject button, `eject` has to run again with `off` or the drive stays locked.
+#[cfg(target_os = "linux")]
+pub fn lock_tray(device: &str, locked: bool) -> Result<(), String> {
+    if device.is_empty() {
+        return Err("Device path is empty, cannot lock the tray".to_string());
+    }
+    run_eject(&["-i", if locked { "on" } else { "off" }, device])
+}
+
+#[cfg(target_os = "linux")]
+pub fn tray_state(device: &str) -> TrayState {
+    use std::fs::OpenOptions;
+    use std::os::raw::{c_int, c_ulong};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    const O_NONBLOCK: i32 = 0o4000;
+    const CDROM_DRIVE_STATUS: c_ulong = 0x5326;
+    const CDSL_CURRENT: c_int = c_int::MAX;
+
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    // Without O_NONBLOCK opening the device waits for a disc, or closes an open tray
+    match OpenOptions::new()
+        .read(true)
+        .custom_flags(O_NONBLOCK)
+        .open(device)
+    {
+        Ok(file) => {
+            // SAFETY: the fd stays open for the call and the request takes an int argument
+            let status = unsafe { ioctl(file.as_raw_fd(), CDROM_DRIVE_STATUS, CDSL_CURRENT) };
+            TrayState::from_drive_status(status)
+        }
+        Err(e) => {
+            debug!("Failed to open {device} for the tray state: {e}");
+            TrayState::Unknown
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn run_drutil(args: &[&str]) -> Result<(), String> {
+    use std::process::Command;
+
+    debug!("Executing command: drutil {args:?}");
+    let output = Command::new("drutil")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run drutil: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "drutil {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// drutil works on the first drive, the device is only used for the log.
+#[cfg(target_os = "macos")]
+pub fn close_tray(device: &str) -> Result<(), String> {
+    debug!("Closing the tray of {device}");
+    run_drutil(&["tray", "close"])
+}
+
+#[cfg(target_os = "macos")]
+pub fn lock_tray(_device: &str, _locked: bool) -> Result<(), String> {
+    Err("Locking the tray isn't supported on macOS".to_string())
+}
+
+#[cfg(target_os = "macos")]
+pub fn tray_state(_device: &str) -> TrayState {
+    TrayState::Unknown
+}
+
+#[cfg(target_os = "windows")]
+pub fn close_tray(device: &str) -> Result<(), String> {
+    Err(format!("Can't close the tray on windows yet {device}"))
+}
+
+#[cfg(target_os = "windows")]
+pub fn lock_tray(device: &str, _locked: bool) -> Result<(), String> {
+    Err(format!("Can't lock the tray on windows yet {device}"))
+}
+
+#[cfg(target_os = "windows")]
+pub fn tray_state(_device: &str) -> TrayState {
+    TrayState::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_cdrom_drive_status() {
+        assert_eq!(TrayState::from_drive_status(2), TrayState::Open);
+        assert_eq!(TrayState::from_drive_status(4), TrayState::Closed);
+        assert_eq!(TrayState::from_drive_status(1), TrayState::Closed);
+        assert_eq!(TrayState::from_drive_status(0), TrayState::Unknown);
+        assert_eq!(TrayState::from_drive_status(-1), TrayState::Unknown);
+    }
+}
@@ -0,0 +1,133 @@
+//! Reads a stretch of the inserted disc to see how fast the drive is, so drives can be
+//! compared and rips estimated before any were made with it.
+//!
+//! The sample comes from the middle of the largest video file on the disc, the start of a
+//! disc is often read faster than the rest. makemkvcon has no read-only mode, so the mounted
+//! file system is read directly, which needs the disc in a local drive.
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Enough to get past the drive spinning up, small enough to take well under a minute.
+pub const SAMPLE_BYTES: u64 = 256 * 1024 * 1024;
+
+const CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Where Blu-rays and DVDs keep their video, anything else is searched from the top.
+const VIDEO_DIRS: [&str; 2] = ["BDMV/STREAM", "VIDEO_TS"];
+
+/// Result of a benchmark run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Benchmark {
+    pub bytes: u64,
+    pub seconds: f64,
+}
+
+impl Benchmark {
+    pub fn bytes_per_second(&self) -> f64 {
+        if self.seconds <= 0.0 {
+            return 0.0;
+        }
+        self.bytes as f64 / self.seconds
+    }
+}
+
+/// Reads up to `SAMPLE_BYTES` from the disc mounted at `mount_point`.
+pub fn run(mount_point: &Path) -> Result<Benchmark, String> {
+    let file = sample_file(mount_point).ok_or_else(|| {
+        format!(
+            "There is no video file on the disc at {} to read",
+            mount_point.display()
+        )
+    })?;
+    measure(&file, SAMPLE_BYTES)
+}
+
+/// The largest file in the video folder of the disc.
+pub fn sample_file(mount_point: &Path) -> Option<PathBuf> {
+    VIDEO_DIRS
+        .iter()
+        .map(|dir| mount_point.join(dir))
+        .find(|dir| dir.is_dir())
+        .and_then(|dir| largest_file(&dir))
+        .or_else(|| largest_file(mount_point))
+}
+
+fn largest_file(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| (metadata.len(), entry.path()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, path)| path)
+}
+
+/// Reads `sample_bytes` from the middle of `path`, or the whole file when it is smaller.
+pub fn measure(path: &Path, sample_bytes: u64) -> Result<Benchmark, String> {
+    let mut file =
+        File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?
+        .len();
+    let start = len.saturating_sub(sample_bytes) / 2;
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+    let mut buffer = vec![0u8; CHUNK_BYTES];
+    let mut bytes = 0;
+    let started = Instant::now();
+    while bytes < sample_bytes {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        bytes += read as u64;
+    }
+    if bytes == 0 {
+        return Err(format!("{} is empty", path.display()));
+    }
+    Ok(Benchmark {
+        bytes,
+        seconds: started.elapsed().as_secs_f64(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::test_dir;
+
+    #[test]
+    fn samples_the_largest_stream_of_a_blu_ray() {
+        let dir = test_dir("drive-benchmark-bluray");
+        fs::create_dir_all(dir.join("BDMV/STREAM")).unwrap();
+        fs::write(dir.join("BDMV/STREAM/00001.m2ts"), vec![0u8; 10]).unwrap();
+        fs::write(dir.join("BDMV/STREAM/00002.m2ts"), vec![0u8; 20]).unwrap();
+        fs::write(dir.join("big_extra.bin"), vec![0u8; 30]).unwrap();
+
+        assert_eq!(sample_file(&dir), Some(dir.join("BDMV/STREAM/00002.m2ts")));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_at_most_the_sample() {
+        let dir = test_dir("drive-benchmark-measure");
+        let path = dir.join("video.vob");
+        fs::write(&path, vec![0u8; 3 * CHUNK_BYTES]).unwrap();
+
+        let benchmark = measure(&path, CHUNK_BYTES as u64).unwrap();
+        assert_eq!(benchmark.bytes, CHUNK_BYTES as u64);
+        assert_eq!(
+            measure(&path, 10 * CHUNK_BYTES as u64).unwrap().bytes,
+            3 * CHUNK_BYTES as u64
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -36,16 +36,89 @@ pub fn opticals() -> Vec<OpticalDiskInfo> {
                 mount_point,
                 titles: Mutex::new(Vec::new()),
                 pid: Mutex::new(None),
+                protection: None,
+                drive_name: None,
                 index: idx as u32,
             })
         });
 
+    opticals.extend(audio_cds(opticals.len()));
     opticals
 }
 
+/// Audio CDs have no file system to mount so sysinfo doesn't list them, udev knows
+/// the tracks of every disc in an `sr` drive.
+fn audio_cds(first_index: usize) -> Vec<OpticalDiskInfo> {
+    let Ok(entries) = std::fs::read_dir("/sys/block") else {
+        return Vec::new();
+    };
+    let mut devices: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with("sr"))
+        .collect();
+    devices.sort();
+    devices
+        .into_iter()
+        .filter(|name| {
+            std::fs::read_to_string(format!("/sys/block/{name}/dev"))
+                .and_then(|dev| std::fs::read_to_string(format!("/run/udev/data/b{}", dev.trim())))
+                .map(|udev_data| is_audio_only(&udev_data))
+                .unwrap_or(false)
+        })
+        .enumerate()
+        .map(|(idx, name)| OpticalDiskInfo {
+            id: optical_disk_info::DiskId::new(),
+            name: "Audio CD".to_string(),
+            available_space: 0,
+            total_space: 0,
+            file_system: optical_disk_info::AUDIO_CD_FILE_SYSTEM.to_string(),
+            is_removable: true,
+            is_read_only: true,
+            kind: "Audio CD".to_string(),
+            dev: format!("/dev/{name}"),
+            mount_point: std::path::PathBuf::new(),
+            titles: Mutex::new(Vec::new()),
+            pid: Mutex::new(None),
+            protection: None,
+            drive_name: None,
+            index: (first_index + idx) as u32,
+        })
+        .collect()
+}
+
+/// A disc with audio tracks and no data track, a mixed mode CD mounts like any data disc.
+fn is_audio_only(udev_data: &str) -> bool {
+    let track_count = |key: &str| -> u32 {
+        udev_data
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("E:{key}=")))
+            .and_then(|count| count.trim().parse().ok())
+            .unwrap_or(0)
+    };
+    track_count("ID_CDROM_MEDIA_TRACK_COUNT_AUDIO") > 0
+        && track_count("ID_CDROM_MEDIA_TRACK_COUNT_DATA") == 0
+}
+
 fn is_optical_disk(disk: &Disk) -> bool {
     let fs_bytes = disk.file_system();
     let fs_str = fs_bytes.to_str().unwrap_or("");
 
     disk.is_removable() && (fs_str.contains("udf") || fs_str.contains("iso9660"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_audio_only_discs() {
+        let audio = "E:ID_CDROM=1\nE:ID_CDROM_MEDIA=1\nE:ID_CDROM_MEDIA_TRACK_COUNT=12\nE:ID_CDROM_MEDIA_TRACK_COUNT_AUDIO=12\n";
+        let mixed = "E:ID_CDROM_MEDIA_TRACK_COUNT_AUDIO=11\nE:ID_CDROM_MEDIA_TRACK_COUNT_DATA=1\n";
+        let dvd = "E:ID_CDROM_MEDIA_DVD=1\nE:ID_CDROM_MEDIA_TRACK_COUNT_DATA=1\n";
+        assert!(is_audio_only(audio));
+        assert!(!is_audio_only(mixed));
+        assert!(!is_audio_only(dvd));
+        assert!(!is_audio_only(""));
+    }
+}
@@ -35,6 +35,8 @@ pub fn opticals() -> Vec<OpticalDiskInfo> {
                 mount_point,
                 titles: Mutex::new(Vec::new()),
                 pid: Mutex::new(None),
+                protection: None,
+                drive_name: None,
                 index: idx as u32,
             })
         });
@@ -45,5 +47,8 @@ fn is_optical_disk(disk: &Disk) -> bool {
     let fs_bytes = disk.file_system();
     let fs_str = fs_bytes.to_str().unwrap_or("");
 
-    disk.is_removable() && (fs_str.contains("udf") || fs_str.contains("iso9660"))
+    disk.is_removable()
+        && (fs_str.contains("udf")
+            || fs_str.contains("iso9660")
+            || fs_str == optical_disk_info::AUDIO_CD_FILE_SYSTEM)
 }
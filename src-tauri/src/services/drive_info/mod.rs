@@ -7,11 +7,19 @@ mod macos;
 #[cfg(target_os = "windows")]
 mod windows;
 
-#[cfg(target_os = "linux")]
+#[cfg(feature = "virtual-disc")]
+mod virtual_disc;
+
+pub mod remote;
+
+#[cfg(all(target_os = "linux", not(feature = "virtual-disc")))]
 pub use linux::opticals;
 
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", not(feature = "virtual-disc")))]
 pub use macos::opticals;
 
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", not(feature = "virtual-disc")))]
 pub use windows::opticals;
+
+#[cfg(feature = "virtual-disc")]
+pub use virtual_disc::opticals;
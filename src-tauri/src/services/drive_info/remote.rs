@@ -0,0 +1,107 @@
+//! Drives of a remote ripper, the local OS can't see them so makemkvcon is asked to list them.
+use crate::models::mkv::{MkvData, DRV};
+use crate::models::optical_disk_info;
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::services::makemkvcon_parser;
+use crate::services::ripper_backend::RipperBackend;
+use log::debug;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+// makemkvcon reports the drive as visible 2 when it has a disc in it
+const DISC_INSERTED: i32 = 2;
+
+/// `info disc:9999` makes makemkvcon print every drive and stop without scanning a disc.
+/// `None` when the remote could not be asked, which says nothing about its drives.
+pub async fn opticals(
+    app_handle: &AppHandle,
+    backend: &dyn RipperBackend,
+) -> Option<Vec<OpticalDiskInfo>> {
+    let command = match backend.command(app_handle, &["-r", "--cache=1", "info", "disc:9999"]) {
+        Ok(command) => command,
+        Err(e) => {
+            debug!("Failed to list the drives of {}: {e}", backend.name());
+            return None;
+        }
+    };
+    match command.output().await {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let drives: Vec<DRV> = makemkvcon_parser::parse_mkv_string(&stdout)
+                .into_iter()
+                .filter_map(|data| match data {
+                    MkvData::DRV(drv) => Some(drv),
+                    _ => None,
+                })
+                .collect();
+            // makemkvcon always prints every drive slot, an empty list means ssh or
+            // makemkvcon failed before it got that far
+            if drives.is_empty() {
+                debug!(
+                    "{} listed no drives: {}",
+                    backend.name(),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                return None;
+            }
+            Some(from_drives(drives))
+        }
+        Err(e) => {
+            debug!("Failed to list the drives of {}: {e}", backend.name());
+            None
+        }
+    }
+}
+
+fn from_drives(drives: Vec<DRV>) -> Vec<OpticalDiskInfo> {
+    drives
+        .into_iter()
+        .filter(|drv| drv.visible == DISC_INSERTED)
+        .map(|drv| OpticalDiskInfo {
+            id: optical_disk_info::DiskId::new(),
+            name: drv.disc_name,
+            // Nothing on this machine is mounted, the device path is all there is
+            mount_point: PathBuf::from(&drv.device_path),
+            available_space: 0,
+            total_space: 0,
+            file_system: String::new(),
+            is_removable: true,
+            is_read_only: true,
+            kind: optical_disk_info::REMOTE_KIND.to_string(),
+            dev: drv.device_path,
+            titles: Mutex::new(Vec::new()),
+            pid: Mutex::new(None),
+            index: drv.index as u32,
+            protection: None,
+            drive_name: Some(drv.drive_name).filter(|name| !name.is_empty()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drv(index: i32, visible: i32, disc_name: &str) -> DRV {
+        DRV {
+            index,
+            visible,
+            enabled: 999,
+            flags: 12,
+            drive_name: "BD-RE HL-DT-ST BD-RE WH16NS60".to_string(),
+            disc_name: disc_name.to_string(),
+            device_path: format!("/dev/sr{index}"),
+        }
+    }
+
+    #[test]
+    fn only_drives_with_a_disc() {
+        let opticals = from_drives(vec![drv(0, 2, "ALIEN"), drv(1, 0, ""), drv(2, 256, "")]);
+        assert_eq!(opticals.len(), 1);
+        assert_eq!(opticals[0].name, "ALIEN");
+        assert_eq!(opticals[0].index, 0);
+        assert_eq!(opticals[0].dev, "/dev/sr0");
+        assert!(opticals[0].is_remote());
+    }
+}
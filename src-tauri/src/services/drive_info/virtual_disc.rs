@@ -0,0 +1,79 @@
+//! Fake optical drives for the `virtual-disc` dev feature.
+//!
+//! Every label in `REELIX_VIRTUAL_DISCS` (comma separated, defaults to one disc)
+//! shows up as an inserted disc. The mount point is an empty directory in the temp
+//! dir, the mock makemkvcon script reads the label from it. The directories are
+//! created on the first scan only, delete one to simulate pulling the disc.
+use crate::models::optical_disk_info;
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use std::path::PathBuf;
+use std::sync::{Mutex, Once};
+
+const DEFAULT_DISCS: &str = "THE_MARTIAN";
+
+static INSERT_DISCS: Once = Once::new();
+
+pub fn opticals() -> Vec<OpticalDiskInfo> {
+    let labels = std::env::var("REELIX_VIRTUAL_DISCS").unwrap_or(DEFAULT_DISCS.to_string());
+    let labels = disc_labels(&labels);
+    INSERT_DISCS.call_once(|| {
+        for label in &labels {
+            let _ = std::fs::create_dir_all(mount_point(label));
+        }
+    });
+    labels
+        .into_iter()
+        .enumerate()
+        .filter(|(_, name)| mount_point(name).exists())
+        .map(|(idx, name)| {
+            let mount_point = mount_point(&name);
+            OpticalDiskInfo {
+                id: optical_disk_info::DiskId::new(),
+                available_space: 0,
+                total_space: 25_000_000_000,
+                file_system: "udf".to_string(),
+                is_removable: true,
+                is_read_only: true,
+                kind: "Virtual".to_string(),
+                dev: format!("virtual{idx}"),
+                mount_point,
+                titles: Mutex::new(Vec::new()),
+                pid: Mutex::new(None),
+                protection: None,
+                drive_name: None,
+                index: idx as u32,
+                name,
+            }
+        })
+        .collect()
+}
+
+/// "THE_MARTIAN, ,ALIEN" -> ["THE_MARTIAN", "ALIEN"]
+fn disc_labels(labels: &str) -> Vec<String> {
+    labels
+        .split(',')
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn mount_point(label: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join("reelix-virtual-discs")
+        .join(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_disc_labels() {
+        assert_eq!(
+            disc_labels("THE_MARTIAN, ,ALIEN"),
+            vec!["THE_MARTIAN".to_string(), "ALIEN".to_string()]
+        );
+        assert!(disc_labels("").is_empty());
+    }
+}
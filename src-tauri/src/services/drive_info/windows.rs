@@ -1,6 +1,7 @@
 use crate::models::optical_disk_info;
 use crate::models::optical_disk_info::OpticalDiskInfo;
 use serde::Deserialize;
+use std::path::Path;
 use std::sync::Mutex;
 use wmi::WMIConnection;
 
@@ -28,12 +29,18 @@ pub fn opticals() -> Vec<OpticalDiskInfo> {
         if let Some(dev) = drive.Drive {
             // Use the Caption if available, otherwise use the drive letter.
             let name = drive.VolumeName;
+            // Windows lists the tracks of an audio CD as Track01.cda, Track02.cda...
+            let file_system = if Path::new(&format!("{dev}\\Track01.cda")).exists() {
+                optical_disk_info::AUDIO_CD_FILE_SYSTEM.to_string()
+            } else {
+                String::new()
+            };
             opticals.push(OpticalDiskInfo {
                 id: optical_disk_info::DiskId::new(),
                 name,
                 available_space: 0,
                 total_space: 0,
-                file_system: String::new(),
+                file_system,
                 is_removable: true,
                 is_read_only: true,
                 kind: "Optical Disk".to_string(),
@@ -41,6 +48,8 @@ pub fn opticals() -> Vec<OpticalDiskInfo> {
                 mount_point: std::path::PathBuf::new(),
                 titles: Mutex::new(Vec::new()),
                 pid: Mutex::new(None),
+                protection: None,
+                drive_name: None,
                 index: idx as u32,
             });
         }
@@ -0,0 +1,397 @@
+use log::debug;
+use std::path::Path;
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+
+// ffmpeg is not bundled like makemkvcon, it has to be installed and available on the PATH.
+const FFMPEG: &str = "ffmpeg";
+
+// Installed next to ffmpeg by every distribution of it.
+const FFPROBE: &str = "ffprobe";
+
+// Enough frames for idet to pick up a telecine cadence without decoding the whole title.
+const IDET_FRAMES: &str = "2000";
+
+// EBU R128 broadcast targets, loud enough for TV speakers without clipping.
+const LOUDNORM_FILTER: &str = "loudnorm=I=-16:TP=-1.5:LRA=11";
+
+/// Frame counts from the ffmpeg `idet` filter "Multi frame detection" line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IdetReport {
+    pub tff: u64,
+    pub bff: u64,
+    pub progressive: u64,
+    pub undetermined: u64,
+}
+
+impl IdetReport {
+    pub fn interlaced_frames(&self) -> u64 {
+        self.tff + self.bff
+    }
+
+    /// Interlaced when more than 10% of the frames idet could decide on are interlaced.
+    /// Telecined content shows up as a mix of interlaced and progressive frames so a
+    /// majority check would miss it.
+    pub fn is_interlaced(&self) -> bool {
+        let decided = self.interlaced_frames() + self.progressive;
+        decided > 0 && self.interlaced_frames() * 10 > decided
+    }
+
+    pub fn summary(&self) -> String {
+        let decided = self.interlaced_frames() + self.progressive;
+        let percent = (self.interlaced_frames() * 100)
+            .checked_div(decided)
+            .unwrap_or(0);
+        format!(
+            "{percent}% interlaced (TFF: {}, BFF: {}, progressive: {}, undetermined: {})",
+            self.tff, self.bff, self.progressive, self.undetermined
+        )
+    }
+}
+
+/// Extra audio track added next to the original ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioTrackOptions {
+    /// Downmix to AAC 2.0
+    pub downmix_stereo: bool,
+    /// Run the track through `loudnorm`
+    pub normalize_loudness: bool,
+}
+
+impl AudioTrackOptions {
+    pub fn track_title(&self) -> &'static str {
+        match (self.downmix_stereo, self.normalize_loudness) {
+            (true, true) => "Stereo (Normalized)",
+            (true, false) => "Stereo",
+            (false, _) => "Normalized",
+        }
+    }
+}
+
+/// Run ffmpeg with the given arguments and return everything it wrote to stderr,
+/// which is where ffmpeg reports filter output and errors.
+async fn run(app_handle: &AppHandle, args: &[&str]) -> Result<String, String> {
+    let output = execute(app_handle, FFMPEG, args).await?;
+    Ok(String::from_utf8_lossy(&output.stderr).to_string())
+}
+
+async fn execute(
+    app_handle: &AppHandle,
+    program: &str,
+    args: &[&str],
+) -> Result<tauri_plugin_shell::process::Output, String> {
+    debug!("Executing command: {program} {args:?}");
+    let output = app_handle
+        .shell()
+        .command(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {program}, is it installed? {e}"))?;
+
+    if output.status.success() {
+        Ok(output)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last_line = stderr.lines().last().unwrap_or_default();
+        Err(format!(
+            "{program} exited with {:?}: {last_line}",
+            output.status.code()
+        ))
+    }
+}
+
+/// Number of audio streams in the file, used to address the track added by `add_audio_track`.
+pub async fn audio_stream_count(app_handle: &AppHandle, path: &Path) -> Result<usize, String> {
+    let input = path.to_string_lossy();
+    let output = execute(
+        app_handle,
+        FFPROBE,
+        &[
+            "-v",
+            "error",
+            "-select_streams",
+            "a",
+            "-show_entries",
+            "stream=index",
+            "-of",
+            "csv=p=0",
+            &input,
+        ],
+    )
+    .await?;
+    Ok(count_lines(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Decode the first frames of the video with the `idet` filter.
+pub async fn detect_interlacing(app_handle: &AppHandle, path: &Path) -> Result<IdetReport, String> {
+    let input = path.to_string_lossy();
+    let output = run(
+        app_handle,
+        &[
+            "-hide_banner",
+            "-nostats",
+            "-i",
+            &input,
+            "-map",
+            "0:v:0",
+            "-vf",
+            "idet",
+            "-frames:v",
+            IDET_FRAMES,
+            "-an",
+            "-sn",
+            "-f",
+            "null",
+            "-",
+        ],
+    )
+    .await?;
+
+    parse_idet(&output).ok_or_else(|| format!("No idet report in {FFMPEG} output"))
+}
+
+/// Re-encode the video stream with `bwdif`, only touching frames flagged as interlaced.
+/// Audio, subtitles and chapters are copied as is.
+pub async fn deinterlace(
+    app_handle: &AppHandle,
+    input: &Path,
+    output: &Path,
+) -> Result<(), String> {
+    let input = input.to_string_lossy();
+    let output = output.to_string_lossy();
+    run(
+        app_handle,
+        &[
+            "-hide_banner",
+            "-nostats",
+            "-y",
+            "-i",
+            &input,
+            "-map",
+            "0",
+            "-c",
+            "copy",
+            "-vf",
+            "bwdif=deint=interlaced",
+            "-c:v",
+            "libx264",
+            "-crf",
+            "18",
+            "-preset",
+            "slow",
+            &output,
+        ],
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Copy every stream and append an AAC version of the first audio track.
+///
+/// The original tracks stay untouched and remain the default, the new track is
+/// added last so players that can't handle the source codec have a fallback.
+pub async fn add_audio_track(
+    app_handle: &AppHandle,
+    input: &Path,
+    output: &Path,
+    options: &AudioTrackOptions,
+) -> Result<(), String> {
+    let new_track = audio_stream_count(app_handle, input).await?;
+    if new_track == 0 {
+        return Err("No audio track to convert".to_string());
+    }
+    let input = input.to_string_lossy();
+    let output = output.to_string_lossy();
+    let args = audio_track_args(&input, &output, new_track, options);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    run(app_handle, &args).await.map(|_| ())
+}
+
+fn audio_track_args(
+    input: &str,
+    output: &str,
+    new_track: usize,
+    options: &AudioTrackOptions,
+) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "-hide_banner",
+        "-nostats",
+        "-y",
+        "-i",
+        input,
+        "-map",
+        "0",
+        "-map",
+        "0:a:0",
+        "-c",
+        "copy",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    args.push(format!("-c:a:{new_track}"));
+    args.push("aac".to_string());
+    args.push(format!("-b:a:{new_track}"));
+    args.push("192k".to_string());
+    if options.downmix_stereo {
+        args.push(format!("-ac:a:{new_track}"));
+        args.push("2".to_string());
+    }
+    if options.normalize_loudness {
+        args.push(format!("-filter:a:{new_track}"));
+        args.push(LOUDNORM_FILTER.to_string());
+    }
+    args.push(format!("-metadata:s:a:{new_track}"));
+    args.push(format!("title={}", options.track_title()));
+    args.push(format!("-disposition:a:{new_track}"));
+    args.push("0".to_string());
+    args.push(output.to_string());
+    args
+}
+
+fn count_lines(output: &str) -> usize {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count()
+}
+
+/// Save a single frame at `seconds` as a small JPEG.
+pub async fn extract_frame(
+    app_handle: &AppHandle,
+    input: &Path,
+    seconds: u64,
+    output: &Path,
+) -> Result<(), String> {
+    let seconds = seconds.to_string();
+    let input = input.to_string_lossy();
+    let output = output.to_string_lossy();
+    run(
+        app_handle,
+        &[
+            "-hide_banner",
+            "-nostats",
+            "-y",
+            "-ss",
+            &seconds,
+            "-i",
+            &input,
+            "-frames:v",
+            "1",
+            "-vf",
+            "scale=320:-2",
+            "-q:v",
+            "5",
+            &output,
+        ],
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Parse the last "Multi frame detection" line printed by `idet`.
+///
+/// Example:
+/// `[Parsed_idet_0 @ 0x0] Multi frame detection: TFF:  1520 BFF:     0 Progressive:   470 Undetermined:    10`
+fn parse_idet(output: &str) -> Option<IdetReport> {
+    let line = output
+        .lines()
+        .rev()
+        .find(|line| line.contains("Multi frame detection:"))?;
+    let counts = line.split("Multi frame detection:").nth(1)?;
+
+    let mut report = IdetReport::default();
+    let mut tokens = counts.split_whitespace();
+    while let Some(label) = tokens.next() {
+        let value = tokens.next()?.parse::<u64>().ok()?;
+        match label {
+            "TFF:" => report.tff = value,
+            "BFF:" => report.bff = value,
+            "Progressive:" => report.progressive = value,
+            "Undetermined:" => report.undetermined = value,
+            _ => {}
+        }
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_multi_frame_detection() {
+        let output = "\
+[Parsed_idet_0 @ 0x600] Repeated Fields: Neither:  1999 Top:     1 Bottom:     0
+[Parsed_idet_0 @ 0x600] Single frame detection: TFF:   900 BFF:     0 Progressive:   800 Undetermined:   300
+[Parsed_idet_0 @ 0x600] Multi frame detection: TFF:  1520 BFF:     0 Progressive:   470 Undetermined:    10";
+
+        let report = parse_idet(output).unwrap();
+        assert_eq!(
+            report,
+            IdetReport {
+                tff: 1520,
+                bff: 0,
+                progressive: 470,
+                undetermined: 10,
+            }
+        );
+        assert!(report.is_interlaced());
+    }
+
+    #[test]
+    fn progressive_source_is_not_interlaced() {
+        let report = IdetReport {
+            tff: 3,
+            bff: 0,
+            progressive: 1990,
+            undetermined: 7,
+        };
+        assert!(!report.is_interlaced());
+        assert_eq!(
+            report.summary(),
+            "0% interlaced (TFF: 3, BFF: 0, progressive: 1990, undetermined: 7)"
+        );
+    }
+
+    #[test]
+    fn audio_track_args_address_the_new_track() {
+        let options = AudioTrackOptions {
+            downmix_stereo: true,
+            normalize_loudness: true,
+        };
+        let args = audio_track_args("in.mkv", "out.mkv", 2, &options);
+        let args = args.join(" ");
+        assert_eq!(
+            args,
+            "-hide_banner -nostats -y -i in.mkv -map 0 -map 0:a:0 -c copy \
+-c:a:2 aac -b:a:2 192k -ac:a:2 2 -filter:a:2 loudnorm=I=-16:TP=-1.5:LRA=11 \
+-metadata:s:a:2 title=Stereo (Normalized) -disposition:a:2 0 out.mkv"
+        );
+    }
+
+    #[test]
+    fn audio_track_args_without_downmix_keep_channels() {
+        let options = AudioTrackOptions {
+            downmix_stereo: false,
+            normalize_loudness: true,
+        };
+        let args = audio_track_args("in.mkv", "out.mkv", 1, &options);
+        assert!(!args.contains(&"-ac:a:1".to_string()));
+        assert!(args.contains(&"title=Normalized".to_string()));
+    }
+
+    #[test]
+    fn counts_ffprobe_streams() {
+        assert_eq!(count_lines("1\n2\n\n"), 2);
+        assert_eq!(count_lines(""), 0);
+    }
+
+    #[test]
+    fn missing_report_is_none() {
+        assert_eq!(parse_idet("Output #0, null, to 'pipe:':"), None);
+        assert!(!IdetReport::default().is_interlaced());
+    }
+}
@@ -0,0 +1,292 @@
+//! Moving ripped files into the library, which can be on a network mount (SMB, NFS) where
+//! a plain rename doesn't work.
+use crate::services::long_path::extend;
+use crate::services::naming::nfc;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Move `from` to `to`, renaming when both are on the same device and copying then deleting
+/// when they are not. `on_progress` gets the bytes copied and the total, it is only called
+/// for a copy.
+///
+/// An existing file at `to` is never replaced, the video gets the next free version suffix
+/// instead (`Alien (1979) (1).mkv`). Returns where the file ended up.
+pub fn move_file(
+    from: &Path,
+    to: &Path,
+    mut on_progress: impl FnMut(u64, u64),
+) -> Result<PathBuf, String> {
+    let to = available_path(to);
+    match fs::rename(extend(from), extend(&to)) {
+        Ok(()) => Ok(to),
+        Err(e) if is_cross_device(&e) => {
+            copy_file(from, &to, &mut on_progress)?;
+            fs::remove_file(extend(from)).map_err(|e| {
+                format!(
+                    "Copied to {} but failed to remove {}: {e}",
+                    to.display(),
+                    from.display()
+                )
+            })?;
+            Ok(to)
+        }
+        Err(e) => Err(explain(&e, from, &to)),
+    }
+}
+
+/// `path` when nothing is there yet, otherwise the first of `name (1).ext`, `name (2).ext`, ...
+/// that is free.
+pub fn available_path(path: &Path) -> PathBuf {
+    if !taken(path) {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|version| path.with_file_name(format!("{stem} ({version}){extension}")))
+        .find(|candidate| !taken(candidate))
+        .expect("ran out of version numbers")
+}
+
+/// A `.part` file is a copy still running, its name is as good as taken. So is a name that
+/// only differs in case, it is the same file on APFS and NTFS and a duplicate anywhere else.
+fn taken(path: &Path) -> bool {
+    extend(path).exists()
+        || extend(&part_path(path)).exists()
+        || existing_case(path) != path
+        || existing_case(&part_path(path)) != part_path(path)
+}
+
+/// `path` with each part spelled the way it already is on disk when only the case or the
+/// Unicode form differs, `Movies/alien (1979)` becomes `Movies/Alien (1979)` when that folder
+/// exists. Videos then go into the folder that is there instead of a second one next to it.
+/// The parts that don't exist yet are kept as they are.
+pub fn existing_case(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    let mut components = path.components();
+    for component in components.by_ref() {
+        let candidate = resolved.join(component);
+        if resolved.as_os_str().is_empty() || extend(&candidate).exists() {
+            resolved = candidate;
+            continue;
+        }
+        let name = nfc(&component.as_os_str().to_string_lossy()).to_lowercase();
+        let existing = fs::read_dir(extend(&resolved)).ok().and_then(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.file_name())
+                .find(|entry| nfc(&entry.to_string_lossy()).to_lowercase() == name)
+        });
+        match existing {
+            Some(existing) => resolved.push(existing),
+            None => {
+                resolved = candidate;
+                break;
+            }
+        }
+    }
+    resolved.extend(components);
+    resolved
+}
+
+/// Copies into `<to>.part` first so a copy that dies halfway never looks like a finished video.
+fn copy_file(from: &Path, to: &Path, on_progress: &mut impl FnMut(u64, u64)) -> Result<(), String> {
+    let part = part_path(to);
+    let result = (|| {
+        let mut reader = File::open(extend(from))?;
+        let total = reader.metadata()?.len();
+        let mut writer = File::create(extend(&part))?;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut copied = 0;
+        loop {
+            let bytes_read = reader.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..bytes_read])?;
+            copied += bytes_read as u64;
+            on_progress(copied, total);
+        }
+        writer.sync_all()?;
+        fs::rename(extend(&part), extend(to))
+    })();
+    result.map_err(|e| {
+        let _ = fs::remove_file(extend(&part));
+        explain(&e, from, to)
+    })
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+#[cfg(unix)]
+fn is_cross_device(error: &io::Error) -> bool {
+    // EXDEV
+    error.raw_os_error() == Some(18)
+}
+
+#[cfg(windows)]
+fn is_cross_device(error: &io::Error) -> bool {
+    // ERROR_NOT_SAME_DEVICE
+    error.raw_os_error() == Some(17)
+}
+
+/// A share that dropped off shows up as one of a handful of errors, all of them mean the same to the user.
+#[cfg(target_os = "linux")]
+fn is_unavailable(error: &io::Error) -> bool {
+    // EIO, ENOTCONN, EHOSTDOWN, ESTALE
+    matches!(error.raw_os_error(), Some(5 | 107 | 112 | 116))
+}
+
+#[cfg(target_os = "macos")]
+fn is_unavailable(error: &io::Error) -> bool {
+    // EIO, ENOTCONN, EHOSTDOWN, ESTALE
+    matches!(error.raw_os_error(), Some(5 | 57 | 64 | 70))
+}
+
+#[cfg(windows)]
+fn is_unavailable(error: &io::Error) -> bool {
+    // ERROR_BAD_NETPATH, ERROR_NETNAME_DELETED, ERROR_BAD_NET_NAME
+    matches!(error.raw_os_error(), Some(53 | 64 | 67))
+}
+
+/// Create `dir` and its missing parents, a missing share is explained like in `move_file`.
+pub fn create_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(extend(dir)).map_err(|error| {
+        if is_unavailable(&error) || error.kind() == io::ErrorKind::NotFound {
+            return not_reachable(dir);
+        }
+        format!("Failed to create {}: {error}", dir.display())
+    })
+}
+
+fn explain(error: &io::Error, from: &Path, to: &Path) -> String {
+    let dir = to.parent().unwrap_or(to);
+    if is_unavailable(error) || (error.kind() == io::ErrorKind::NotFound && !dir.exists()) {
+        return not_reachable(dir);
+    }
+    format!(
+        "Failed to move {} to {}: {error}",
+        from.display(),
+        to.display()
+    )
+}
+
+fn not_reachable(dir: &Path) -> String {
+    format!(
+        "{} is not reachable, check that the network share is mounted and try again",
+        dir.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::test_dir;
+
+    #[test]
+    fn copies_with_progress() {
+        let dir = test_dir("file-move-copy");
+        let from = dir.join("title_t00.mkv");
+        let to = dir.join("Alien (1979).mkv");
+        fs::write(&from, b"matroska").unwrap();
+
+        let mut progress = Vec::new();
+        copy_file(&from, &to, &mut |copied, total| {
+            progress.push((copied, total))
+        })
+        .unwrap();
+
+        assert_eq!(fs::read(&to).unwrap(), b"matroska");
+        assert_eq!(progress.last(), Some(&(8, 8)));
+        assert!(!part_path(&to).exists());
+    }
+
+    #[test]
+    fn never_replaces_an_existing_video() {
+        let dir = test_dir("file-move-collision");
+        let to = dir.join("Alien (1979).mkv");
+        fs::write(&to, b"first").unwrap();
+        fs::write(dir.join("Alien (1979) (1).mkv.part"), b"").unwrap();
+
+        let from = dir.join("title_t00.mkv");
+        fs::write(&from, b"second").unwrap();
+        let moved = move_file(&from, &to, |_, _| {}).unwrap();
+
+        assert_eq!(moved, dir.join("Alien (1979) (2).mkv"));
+        assert_eq!(fs::read(&to).unwrap(), b"first");
+        assert_eq!(fs::read(&moved).unwrap(), b"second");
+    }
+
+    #[test]
+    fn moves_into_paths_longer_than_max_path() {
+        let dir = test_dir("file-move-long-path");
+        let show = "The Marvelous Adventures of an Extraordinarily Long Named Show (2019)";
+        let season_dir = dir.join("TV Shows").join(show).join("Season 01");
+        fs::create_dir_all(extend(&season_dir)).unwrap();
+        let to = season_dir.join(format!(
+            "{show} - S01E01 - The One Where The Episode Title Is Also Quite Unreasonably Long.mkv"
+        ));
+        assert!(to.to_string_lossy().len() > 260);
+
+        let from = dir.join("title_t00.mkv");
+        fs::write(&from, b"matroska").unwrap();
+        let moved = move_file(&from, &to, |_, _| {}).unwrap();
+
+        assert_eq!(moved, to);
+        assert_eq!(fs::read(extend(&moved)).unwrap(), b"matroska");
+    }
+
+    #[test]
+    fn merges_into_a_folder_that_only_differs_in_case() {
+        let dir = test_dir("file-move-case");
+        fs::create_dir_all(dir.join("Movies/Alien (1979)")).unwrap();
+        fs::write(dir.join("Movies/Alien (1979)/Alien (1979).mkv"), b"first").unwrap();
+
+        let extras = existing_case(&dir.join("movies/alien (1979)/Extras"));
+        assert!(extras.parent().unwrap().is_dir());
+        // Case-insensitive volumes find the folder as it was asked for
+        if cfg!(target_os = "linux") {
+            assert_eq!(extras, dir.join("Movies/Alien (1979)/Extras"));
+        }
+        assert_eq!(
+            available_path(&dir.join("Movies/Alien (1979)/alien (1979).mkv")),
+            dir.join("Movies/Alien (1979)/alien (1979) (1).mkv")
+        );
+    }
+
+    #[test]
+    fn missing_share_is_explained() {
+        let dir = test_dir("file-move-missing");
+        let from = dir.join("title_t00.mkv");
+        fs::write(&from, b"matroska").unwrap();
+
+        let error = move_file(&from, &dir.join("gone/Alien (1979).mkv"), |_, _| {}).unwrap_err();
+        assert!(error.contains("is not reachable"), "{error}");
+        assert!(from.exists());
+    }
+
+    #[test]
+    fn failed_folder_is_explained() {
+        let dir = test_dir("file-move-create");
+        let movies = dir.join("Movies");
+        fs::write(&movies, b"not a folder").unwrap();
+
+        let error = create_dir(&movies.join("Alien (1979)")).unwrap_err();
+        assert!(error.starts_with("Failed to create"), "{error}");
+        create_dir(&dir.join("TV Shows/Season 01")).unwrap();
+        assert!(dir.join("TV Shows/Season 01").is_dir());
+    }
+}
@@ -1,21 +1,31 @@
 use crate::progress_tracker::{self, ProgressOptions};
+use crate::services::blocking;
+use crate::services::locale;
+use crate::services::metrics;
+use crate::services::naming::{self, transliterate_ascii, NamingOptions};
+use crate::services::path_template;
 use crate::state::job_state::{emit_progress, Job};
-use crate::state::title_video::TitleVideo;
+use crate::state::locks::RwLockExt;
+use crate::state::title_video::{TitleVideo, Video};
 use crate::state::AppState;
 use crate::the_movie_db::{SeasonResponse, TvResponse};
 use log::{debug, error};
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
-use std::io::{BufReader, Read};
+use std::io::{BufReader, Cursor, Read};
+use std::net::ToSocketAddrs;
 use std::path::{Component, Path, PathBuf};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use suppaftp::types::FileType;
 use suppaftp::FtpError as SuppaFtpError;
 use suppaftp::FtpStream;
 use tauri::{AppHandle, Manager, State};
+use tauri_plugin_http::reqwest::blocking::Client;
 
 const CHUNK_SIZE: usize = 8192; // 8KB chunk size for streaming upload
+const TMDB_IMAGE_URL: &str = "https://image.tmdb.org/t/p/original";
 
 struct FileInfo {
     file_size: u64,
@@ -98,15 +108,18 @@ pub fn tv_ripped_episode_numbers(
     season: &SeasonResponse,
     state: &State<'_, AppState>,
 ) -> HashSet<u32> {
-    let tv_upload_path = match state.lock_ftp_tv_upload_path().clone() {
+    let naming = state.naming_options();
+    let season_dir = match path_template::tv_upload_season_dir(
+        &state.lock_ftp_config(),
+        &naming,
+        tv,
+        season.season_number,
+        None,
+    ) {
         Some(value) => value,
         None => return HashSet::new(),
     };
 
-    let season_dir = tv_upload_path
-        .join(tv.title_year())
-        .join(format!("Season {:02}", season.season_number));
-
     let mut ftp = match connect_to_ftp(state) {
         Ok(ftp) => ftp,
         Err(_) => return HashSet::new(),
@@ -120,7 +133,7 @@ pub fn tv_ripped_episode_numbers(
                 let file_name = entry.rsplit('/').next().unwrap_or(&entry).trim();
                 if let Some(episode_number) = parse_episode_number_from_tv_filename(
                     file_name,
-                    &tv.title_year(),
+                    &naming.title_year(&tv.name, tv.year()),
                     season.season_number,
                 ) {
                     ripped_episode_numbers.insert(episode_number);
@@ -137,6 +150,46 @@ pub fn tv_ripped_episode_numbers(
     ripped_episode_numbers
 }
 
+/// Episodes of the season already in the local TV shows folder.
+pub fn local_ripped_episode_numbers(
+    tv: &TvResponse,
+    season: &SeasonResponse,
+    state: &State<'_, AppState>,
+) -> HashSet<u32> {
+    let naming = state.naming_options();
+    let title_year = naming.title_year(&tv.name, tv.year());
+    let season_dir = state
+        .tv_shows_dir
+        .read_or_recover()
+        .join(naming.show_folder(&tv.name, tv.year(), tv.id.into()))
+        .join(format!("Season {:02}", season.season_number));
+    let Ok(entries) = std::fs::read_dir(&season_dir) else {
+        return HashSet::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            parse_episode_number_from_tv_filename(
+                &entry.file_name().to_string_lossy(),
+                &title_year,
+                season.season_number,
+            )
+        })
+        .collect()
+}
+
+/// Episodes of the season that are already ripped, locally or on the FTP server. They are
+/// left out of the assignment and the rip.
+pub fn owned_episode_numbers(
+    tv: &TvResponse,
+    season: &SeasonResponse,
+    state: &State<'_, AppState>,
+) -> HashSet<u32> {
+    let mut owned = local_ripped_episode_numbers(tv, season, state);
+    owned.extend(tv_ripped_episode_numbers(tv, season, state));
+    owned
+}
+
 fn parse_episode_number_from_tv_filename(
     file_name: &str,
     tv_title_year: &str,
@@ -151,12 +204,16 @@ fn parse_episode_info_from_tv_filename(
     tv_title_year: &str,
     season_number: u32,
 ) -> Option<(u32, Option<u16>)> {
-    let lower_name = file_name.to_lowercase();
+    let lower_name = naming::nfc(file_name).to_lowercase();
     if !lower_name.ends_with(".mkv") {
         return None;
     }
 
-    let prefix = format!("{} - s{:02}e", tv_title_year.to_lowercase(), season_number);
+    let prefix = format!(
+        "{} - s{:02}e",
+        naming::nfc(tv_title_year).to_lowercase(),
+        season_number
+    );
     if !lower_name.starts_with(&prefix) {
         return None;
     }
@@ -189,17 +246,43 @@ fn parse_part_suffix(lower_name: &str) -> Option<u16> {
     part_digits.parse::<u16>().ok()
 }
 
+/// Quality tag in a filename built with `append_quality_tag`.
+///
+/// "Show (2023) - S01E01 - Pilot [Bluray-1080p]-pt1.mkv" -> "Bluray-1080p"
+fn parse_quality_tag(file_name: &str) -> Option<&str> {
+    let (stem, _) = file_name.rsplit_once('.')?;
+    let stem = match stem.rfind("-pt") {
+        Some(index) if stem[index + 3..].chars().all(|ch| ch.is_ascii_digit()) => &stem[..index],
+        _ => stem,
+    };
+    let stem = stem.strip_suffix(']')?;
+    let (_, tag) = stem.rsplit_once(" [")?;
+    if tag.is_empty() {
+        return None;
+    }
+    Some(tag)
+}
+
 fn build_tv_episode_filename(
     tv: &TvResponse,
     season: &SeasonResponse,
     episode: &crate::the_movie_db::SeasonEpisode,
     part: Option<u16>,
+    quality_tag: Option<&str>,
     extension: &str,
+    naming: &NamingOptions,
 ) -> String {
-    let episode_title = episode.name.replace('/', "-");
+    let mut episode_title = naming::nfc(&episode.name).replace('/', "-");
+    if naming.ascii_only {
+        episode_title = transliterate_ascii(&episode_title);
+    }
+    // Keep the tag of the file being renamed, the title it was ripped from is not known here.
+    let quality = quality_tag
+        .map(|tag| format!(" [{tag}]"))
+        .unwrap_or_default();
     let mut file_name = format!(
-        "{} - S{:02}E{:02} - {}.{extension}",
-        tv.title_year(),
+        "{} - S{:02}E{:02} - {}{quality}.{extension}",
+        naming.title_year(&tv.name, tv.year()),
         season.season_number,
         episode.episode_number,
         episode_title
@@ -223,17 +306,37 @@ pub fn reorder_tv_episode_files(
         return Ok(0);
     }
 
-    let tv_upload_path = match state.lock_ftp_tv_upload_path().clone() {
+    let naming = state.naming_options();
+    let season_dir = match path_template::tv_upload_season_dir(
+        &state.lock_ftp_config(),
+        &naming,
+        tv,
+        season.season_number,
+        None,
+    ) {
         Some(value) => value,
         None => return Err("FTP TV upload path is not configured".to_string()),
     };
 
-    let season_dir = tv_upload_path
-        .join(tv.title_year())
-        .join(format!("Season {:02}", season.season_number));
-
     let mut ftp =
         connect_to_ftp(state).map_err(|e| format!("Failed to connect to FTP server: {e:?}"))?;
+    let moved = reorder_tv_episode_files_in(&mut ftp, tv, season, swaps, &season_dir, &naming)?;
+
+    ftp.quit()
+        .map_err(|e| format!("Failed to close FTP connection: {e:?}"))?;
+
+    Ok(moved)
+}
+
+/// Does the renames of `reorder_tv_episode_files` on an open connection.
+fn reorder_tv_episode_files_in(
+    ftp: &mut FtpStream,
+    tv: &TvResponse,
+    season: &SeasonResponse,
+    swaps: &[(u32, u32)],
+    season_dir: &Path,
+    naming: &NamingOptions,
+) -> Result<usize, String> {
     ftp.transfer_type(FileType::Binary)
         .map_err(|e| format!("Failed to set FTP binary mode: {e:?}"))?;
 
@@ -244,6 +347,7 @@ pub fn reorder_tv_episode_files(
         .nlst(None)
         .map_err(|e| format!("Failed to list season directory on FTP: {e:?}"))?;
 
+    let tv_title_year = naming.title_year(&tv.name, tv.year());
     let mut episode_files: HashMap<u32, Vec<String>> = HashMap::new();
     let mut existing_files: HashSet<String> = HashSet::new();
 
@@ -255,7 +359,7 @@ pub fn reorder_tv_episode_files(
             .trim()
             .to_string();
         if let Some((episode_number, _)) =
-            parse_episode_info_from_tv_filename(&file_name, &tv.title_year(), season.season_number)
+            parse_episode_info_from_tv_filename(&file_name, &tv_title_year, season.season_number)
         {
             episode_files
                 .entry(episode_number)
@@ -303,8 +407,15 @@ pub fn reorder_tv_episode_files(
                 .and_then(|ext| ext.to_str())
                 .unwrap_or("mkv");
 
-            let target_file =
-                build_tv_episode_filename(tv, season, target_episode, part, extension);
+            let target_file = build_tv_episode_filename(
+                tv,
+                season,
+                target_episode,
+                part,
+                parse_quality_tag(source_file),
+                extension,
+                naming,
+            );
 
             if !target_files.insert(target_file.clone()) {
                 return Err(format!(
@@ -346,12 +457,42 @@ pub fn reorder_tv_episode_files(
             .map_err(|e| format!("Failed to rename {temp_name} to {target_name}: {e:?}"))?;
     }
 
-    ftp.quit()
-        .map_err(|e| format!("Failed to close FTP connection: {e:?}"))?;
-
     Ok(move_ops.len())
 }
 
+/// Moves a video that is on the server already to `to`, creating the folders it needs. The
+/// folder it leaves is removed when nothing else is in it.
+pub fn rename_remote_file(
+    state: &State<'_, AppState>,
+    from: &Path,
+    to: &Path,
+) -> Result<(), String> {
+    let mut ftp_stream =
+        connect_to_ftp(state).map_err(|e| format!("Failed to connect to FTP server: {e:?}"))?;
+    if let Some(dir) = to.parent() {
+        ensure_remote_dir_recursive(&mut ftp_stream, dir)?;
+    }
+    ftp_stream
+        .rename(from.to_string_lossy(), to.to_string_lossy())
+        .map_err(|e| {
+            format!(
+                "Failed to rename {} to {} on FTP: {e}",
+                from.display(),
+                to.display()
+            )
+        })?;
+    if let Some(dir) = from.parent().filter(|dir| Some(*dir) != to.parent()) {
+        // Servers refuse to remove a folder that still has files, those are kept
+        if let Err(e) = ftp_stream.rmdir(dir.to_string_lossy()) {
+            debug!("Kept {} on FTP: {e}", dir.display());
+        }
+    }
+    ftp_stream
+        .quit()
+        .map_err(|e| format!("Failed to close FTP connection: {e:?}"))?;
+    Ok(())
+}
+
 /// Connects, authenticates, and Changes current directory to MOVIE_UPLOAD_PATH
 pub fn connect_to_ftp(state: &State<'_, AppState>) -> Result<FtpStream, SuppaFtpError> {
     let ftp_host = match state.lock_ftp_host().clone() {
@@ -388,7 +529,23 @@ pub fn connect_to_ftp(state: &State<'_, AppState>) -> Result<FtpStream, SuppaFtp
     };
 
     debug!("Connecting to FTP server at: {ftp_addr}");
-    let mut ftp_stream = FtpStream::connect(&ftp_addr)?;
+    let socket_addr = ftp_addr
+        .to_socket_addrs()
+        .map_err(SuppaFtpError::ConnectionError)?
+        .next()
+        .ok_or_else(|| {
+            SuppaFtpError::ConnectionError(std::io::Error::other(format!(
+                "no address found for {ftp_addr}"
+            )))
+        })?;
+    // Used for connecting and for every command waiting on the server
+    let timeout = state.network_options().ftp_timeout();
+    let mut ftp_stream = FtpStream::connect_timeout(socket_addr, timeout)?;
+    let tcp_stream = ftp_stream.get_ref();
+    tcp_stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| tcp_stream.set_write_timeout(Some(timeout)))
+        .map_err(SuppaFtpError::ConnectionError)?;
     ftp_stream.login(ftp_user, ftp_pass)?;
     Ok(ftp_stream)
 }
@@ -424,12 +581,13 @@ fn create_upload_dir(
     _job: &Arc<RwLock<Job>>,
     title_video: &Arc<RwLock<TitleVideo>>,
 ) -> Result<PathBuf, String> {
-    let title_video_guard = title_video.read().unwrap();
+    let title_video_guard = title_video.read_or_recover();
 
     // Determine content type for better error messages
     let content_type = match &title_video_guard.video {
         crate::state::title_video::Video::Movie(_) => "movie",
         crate::state::title_video::Video::Tv(_) => "TV show",
+        crate::state::title_video::Video::Personal(_) => "home video",
     };
 
     let upload_dir = title_video_guard
@@ -538,21 +696,18 @@ fn start_upload(
 ) -> Result<(), String> {
     let state = app_handle.state::<AppState>();
     let multiple_parts = job
-        .read()
-        .expect("Failed to acquire read lock on job")
-        .has_multiple_parts(&title_video.read().unwrap());
+        .read_or_recover()
+        .has_multiple_parts(&title_video.read_or_recover());
     let upload_file_path = title_video
-        .read()
-        .unwrap()
+        .read_or_recover()
         .upload_file_path(&state, multiple_parts);
     if upload_file_path.is_none() {
         return Err("Failed to get upload file path".to_string());
     }
     let upload_file_path = upload_file_path.unwrap();
     let local_file_path = title_video
-        .read()
-        .unwrap()
-        .video_path(&state, multiple_parts);
+        .read_or_recover()
+        .local_video_path(&state, multiple_parts);
     debug!(
         "Start uploading {} to {:?}",
         upload_file_path.display(),
@@ -565,16 +720,166 @@ fn start_upload(
     ftp_stream
         .transfer_type(FileType::Binary)
         .expect("failed to set binary mode");
-    let tracker = new_tracker();
-    job.write()
-        .expect("Failed to acquire write lock on job")
-        .update_title(&title_video.read().unwrap().clone());
-    job.write()
-        .expect("Failed to acquire write lock on job")
-        .subtitle = Some(format!("Uploading {filename}"));
-    job.read()
-        .expect("Failed to acquire read lock on job")
-        .emit_progress_change(app_handle);
+    let mut tracker = new_tracker(file_info.file_size);
+    job.write_or_recover()
+        .update_title(&title_video.read_or_recover().clone());
+    job.write_or_recover().subtitle = Some(format!("Uploading {filename}"));
+    job.read_or_recover().emit_progress_change(app_handle);
+    let bytes_sent =
+        upload_then_rename(ftp_stream, &mut file_info.reader, &filename, |bytes_sent| {
+            tracker.set_progress(bytes_sent as usize);
+            let speed = tracker.rate_component.per_second();
+            let mut job_guard = job.write_or_recover();
+            job_guard.update_progress(&tracker);
+            job_guard.subtitle = Some(upload_subtitle(&filename, speed));
+            drop(job_guard);
+            emit_progress(app_handle, job, false);
+        })
+        .inspect_err(|_| metrics::record_upload(None))?;
+    metrics::record_upload(Some(bytes_sent));
+    // The last chunk doesn't always get a callback, the job shouldn't stop short of 100%
+    tracker.finish();
+    job.write_or_recover().update_progress(&tracker);
+
+    let file_mode = title_video
+        .read_or_recover()
+        .library(&state)
+        .ftp_config
+        .file_mode;
+    if let Some(file_mode) = file_mode {
+        // The video is on the server, wrong permissions are for the user to fix there
+        if let Err(e) = chmod(ftp_stream, &filename, &file_mode) {
+            error!("{e}");
+            job.write_or_recover().add_notice(e);
+        }
+    }
+    Ok(())
+}
+
+/// Plex picks up season artwork named after the season inside the season folder.
+fn season_poster_filename(season: &SeasonResponse) -> String {
+    format!("Season{:02}.jpg", season.season_number)
+}
+
+/// Blocks, the uploads already run on a blocking thread.
+fn download_poster(poster_path: &str, timeout: Duration) -> Result<Vec<u8>, String> {
+    let url = format!("{TMDB_IMAGE_URL}{poster_path}");
+    Client::new()
+        .get(&url)
+        .timeout(timeout)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.bytes())
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("Failed to download the season poster {url}: {e}"))
+}
+
+/// Uploads the TMDB poster of the episode's season into the current remote directory,
+/// unless the season folder already has one there.
+fn upload_season_poster(
+    app_handle: &AppHandle,
+    ftp_stream: &mut FtpStream,
+    title_video: &Arc<RwLock<TitleVideo>>,
+) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+    let (season, season_posters) = {
+        let title_video = title_video.read_or_recover();
+        let Video::Tv(tv) = &title_video.video else {
+            return Ok(());
+        };
+        (
+            tv.season.clone(),
+            title_video.library(&state).ftp_config.season_posters,
+        )
+    };
+    let Some(poster_path) = season.poster_path.as_deref().filter(|_| season_posters) else {
+        return Ok(());
+    };
+    let filename = season_poster_filename(&season);
+    if ftp_stream.size(&filename).is_ok() {
+        debug!("{filename} is already on the server");
+        return Ok(());
+    }
+
+    let timeout = state.network_options().tmdb_timeout();
+    let poster = download_poster(poster_path, timeout)?;
+    upload_then_rename(ftp_stream, &mut Cursor::new(poster), &filename, |_| {})?;
+    debug!("Uploaded {filename}");
+    Ok(())
+}
+
+/// A file mode for SITE CHMOD, 3 or 4 octal digits like "664". Empty means no CHMOD.
+pub fn validate_file_mode(file_mode: &str) -> Result<(), String> {
+    let file_mode = file_mode.trim();
+    if file_mode.is_empty()
+        || ((3..=4).contains(&file_mode.len())
+            && file_mode.chars().all(|c| ('0'..='7').contains(&c)))
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "{file_mode} is not a file mode, use octal digits like 664"
+        ))
+    }
+}
+
+/// Sets the permissions of `filename` in the current remote directory.
+fn chmod(ftp_stream: &mut FtpStream, filename: &str, file_mode: &str) -> Result<(), String> {
+    ftp_stream
+        .site(format!("CHMOD {file_mode} {filename}"))
+        .map(|_| ())
+        .map_err(|e| format!("Failed to set the permissions of {filename} to {file_mode}: {e}"))
+}
+
+/// Added to the name of a file while it uploads, Plex skips files it doesn't know.
+const PART_EXTENSION: &str = ".part";
+
+fn part_filename(filename: &str) -> String {
+    format!("{filename}{PART_EXTENSION}")
+}
+
+/// Uploads `reader` as `filename.part` and renames it to `filename` once it is complete, so
+/// the media server never scans half a video. A file already called `filename` is replaced.
+///
+/// Returns the number of bytes sent.
+fn upload_then_rename<R: Read>(
+    ftp_stream: &mut FtpStream,
+    reader: &mut R,
+    filename: &str,
+    on_progress: impl FnMut(u64),
+) -> Result<u64, String> {
+    let part_filename = part_filename(filename);
+    let bytes_sent = match stream_file(ftp_stream, reader, &part_filename, on_progress) {
+        Ok(bytes_sent) => bytes_sent,
+        Err(e) => {
+            // The next attempt starts over, a connection that broke can't delete it either
+            if let Err(rm_error) = ftp_stream.rm(&part_filename) {
+                debug!("Failed to remove {part_filename}: {rm_error}");
+            }
+            return Err(e);
+        }
+    };
+    if ftp_stream.size(filename).is_ok() {
+        ftp_stream
+            .rm(filename)
+            .map_err(|e| format!("Failed to replace {filename}: {e}"))?;
+    }
+    ftp_stream
+        .rename(part_filename.as_str(), filename)
+        .map_err(|e| format!("Failed to rename {part_filename} to {filename}: {e}"))?;
+    Ok(bytes_sent)
+}
+
+/// Upload `reader` as `filename` into the current remote directory in chunks,
+/// calling `on_progress` with the bytes sent so far after every chunk.
+///
+/// Returns the number of bytes sent.
+fn stream_file<R: Read>(
+    ftp_stream: &mut FtpStream,
+    reader: &mut R,
+    filename: &str,
+    mut on_progress: impl FnMut(u64),
+) -> Result<u64, String> {
     // Start uploading stream by creating a data stream object
     let mut data_stream = ftp_stream
         .put_with_stream(filename)
@@ -587,8 +892,7 @@ fn start_upload(
     let mut buffer = [0u8; CHUNK_SIZE];
     let mut total_bytes_sent: u64 = 0;
     loop {
-        let bytes_read = file_info
-            .reader
+        let bytes_read = reader
             .read(&mut buffer)
             .map_err(|e| format!("failed to read file info {e}"))?;
         if bytes_read == 0 {
@@ -600,24 +904,31 @@ fn start_upload(
             .map_err(|e| format!("failed to upload file {e}"))?;
         total_bytes_sent += bytes_read as u64;
 
-        let percent = (total_bytes_sent as f64 / file_info.file_size as f64) * 100.0;
-        tracker.set_progress(percent as usize);
-
-        job.write()
-            .expect("Failed to acquire write lock on job")
-            .update_progress(&tracker);
-        emit_progress(app_handle, job, false);
+        on_progress(total_bytes_sent);
     }
 
     // Finalize upload
     ftp_stream
         .finalize_put_stream(data_stream)
-        .map_err(|e| format!("failed to finalize stream: {e}"))
+        .map_err(|e| format!("failed to finalize stream: {e}"))?;
+    Ok(total_bytes_sent)
+}
+
+/// e.g. "Uploading Alien (1979).mkv at 11.2 MB/s"
+fn upload_subtitle(filename: &str, bytes_per_second: Option<f64>) -> String {
+    match bytes_per_second {
+        Some(speed) => format!(
+            "Uploading {filename} at {}",
+            locale::current().format_speed(speed)
+        ),
+        None => format!("Uploading {filename}"),
+    }
 }
 
-fn new_tracker() -> progress_tracker::Base {
+/// Tracks the upload in bytes, so the percentage, ETA and speed follow every chunk.
+fn new_tracker(file_size: u64) -> progress_tracker::Base {
     let options = ProgressOptions {
-        total: Some(100),
+        total: Some(file_size as usize),
         autostart: true,
         autofinish: true,
         starting_at: Some(0),
@@ -667,20 +978,32 @@ pub async fn upload(
     job: &Arc<RwLock<Job>>,
     title_video: &Arc<RwLock<TitleVideo>>,
 ) -> Result<(), String> {
-    let state = app_handle.state::<AppState>();
-    let mut ftp_stream =
-        connect_to_ftp(&state).map_err(|e| format!("Failed to login and change directory {e}"))?;
-
-    create_upload_dir(&state, &mut ftp_stream, job, title_video)?;
-
-    start_upload(app_handle, &mut ftp_stream, job, title_video)?;
+    let app_handle = app_handle.clone();
+    let job = job.clone();
+    let title_video = title_video.clone();
+    blocking::run(move || {
+        let state = app_handle.state::<AppState>();
+        let mut ftp_stream = connect_to_ftp(&state)
+            .map_err(|e| format!("Failed to login and change directory {e}"))?;
+
+        create_upload_dir(&state, &mut ftp_stream, &job, &title_video)?;
+
+        start_upload(&app_handle, &mut ftp_stream, &job, &title_video)?;
+
+        // The episode is on the server, a missing poster is for the user to add there
+        if let Err(e) = upload_season_poster(&app_handle, &mut ftp_stream, &title_video) {
+            error!("{e}");
+            job.write_or_recover().add_notice(e);
+        }
 
-    ftp_stream
-        .quit()
-        .map_err(|e| format!("Failed to close or quit connection: {e}"))?;
+        ftp_stream
+            .quit()
+            .map_err(|e| format!("Failed to close or quit connection: {e}"))?;
 
-    debug!("Upload complete.");
-    Ok(())
+        debug!("Upload complete.");
+        Ok(())
+    })
+    .await?
 }
 
 pub fn cwd(ftp_stream: &mut FtpStream, path: &Path) -> Result<(), String> {
@@ -729,7 +1052,7 @@ pub fn list_directories(ftp_stream: &mut FtpStream, path: &str) -> Result<Vec<St
 mod tests {
     use super::{
         parse_episode_info_from_tv_filename, parse_episode_number_from_tv_filename,
-        parse_part_suffix,
+        parse_part_suffix, parse_quality_tag,
     };
 
     #[test]
@@ -754,6 +1077,23 @@ mod tests {
         assert_eq!(result, Some(3));
     }
 
+    #[test]
+    fn parses_specials_filename() {
+        let special = parse_episode_number_from_tv_filename(
+            "Example Show (2023) - S00E02 - Behind the Scenes.mkv",
+            "Example Show (2023)",
+            0,
+        );
+        let regular_season = parse_episode_number_from_tv_filename(
+            "Example Show (2023) - S00E02 - Behind the Scenes.mkv",
+            "Example Show (2023)",
+            1,
+        );
+
+        assert_eq!(special, Some(2));
+        assert_eq!(regular_season, None);
+    }
+
     #[test]
     fn ignores_other_show_and_non_mkv_files() {
         let wrong_show = parse_episode_number_from_tv_filename(
@@ -810,4 +1150,382 @@ mod tests {
 
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn parses_quality_tag() {
+        assert_eq!(
+            parse_quality_tag("Example Show (2023) - S01E01 - Pilot [Bluray-1080p]-pt1.mkv"),
+            Some("Bluray-1080p")
+        );
+        assert_eq!(
+            parse_quality_tag("Example Show (2023) - S01E01 - Pilot [DVD].mkv"),
+            Some("DVD")
+        );
+        assert_eq!(
+            parse_quality_tag("Example Show (2023) - S01E01 - Pilot.mkv"),
+            None
+        );
+    }
+}
+
+/// End to end tests against an in-process FTP server backed by a temp directory.
+#[cfg(test)]
+mod ftp_server_tests {
+    use super::*;
+    use crate::services::test_support::test_dir;
+    use crate::the_movie_db::{EpisodeId, SeasonEpisode, SeasonId, TvId};
+    use std::fs;
+    use std::io::Cursor;
+    use std::net::TcpListener;
+    use std::time::Duration;
+    use unftp_sbe_fs::ServerExt;
+
+    struct TestFtpServer {
+        addr: String,
+        root: PathBuf,
+    }
+
+    impl TestFtpServer {
+        /// Anonymous logins are accepted, the server is dropped with the test runtime.
+        async fn start() -> Self {
+            let port = TcpListener::bind("127.0.0.1:0")
+                .and_then(|listener| listener.local_addr())
+                .expect("failed to find a free port")
+                .port();
+            let addr = format!("127.0.0.1:{port}");
+            let root = test_dir(&format!("ftp-uploader-{port}"));
+
+            let server = libunftp::Server::with_fs(root.clone())
+                .build()
+                .expect("failed to build ftp server");
+            let listen_addr = addr.clone();
+            tokio::spawn(async move {
+                let _ = server.listen(listen_addr).await;
+            });
+
+            for _ in 0..50 {
+                if std::net::TcpStream::connect(&addr).is_ok() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            TestFtpServer { addr, root }
+        }
+
+        fn connect(&self) -> FtpStream {
+            let mut ftp_stream = FtpStream::connect(&self.addr).expect("failed to connect");
+            ftp_stream
+                .login("anonymous", "reelix")
+                .expect("failed to login");
+            ftp_stream
+        }
+    }
+
+    impl Drop for TestFtpServer {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    /// The FTP client blocks, keep it off the runtime thread serving the server.
+    async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+        tokio::task::spawn_blocking(f)
+            .await
+            .expect("blocking task panicked")
+    }
+
+    fn episode(episode_number: u32) -> SeasonEpisode {
+        SeasonEpisode {
+            id: EpisodeId::from(episode_number),
+            episode_number,
+            episode_type: "standard".to_string(),
+            name: format!("Episode {episode_number}"),
+            overview: String::new(),
+            air_date: None,
+            production_code: None,
+            runtime: Some(45),
+            season_number: 1,
+            show_id: TvId::from(100),
+            still_path: None,
+            vote_average: 0.0,
+            vote_count: 0,
+            crew: vec![],
+            guest_stars: vec![],
+        }
+    }
+
+    fn season() -> SeasonResponse {
+        SeasonResponse {
+            _id: "season".to_string(),
+            id: SeasonId::from(1),
+            season_number: 1,
+            name: "Season 1".to_string(),
+            overview: String::new(),
+            poster_path: None,
+            air_date: None,
+            episodes: vec![episode(1), episode(2)],
+            vote_average: 0.0,
+        }
+    }
+
+    fn tv() -> TvResponse {
+        TvResponse {
+            adult: false,
+            backdrop_path: None,
+            created_by: vec![],
+            episode_run_time: vec![45],
+            first_air_date: Some("2020-01-01".to_string()),
+            genres: vec![],
+            homepage: None,
+            id: TvId::from(100),
+            in_production: false,
+            languages: vec![],
+            last_air_date: None,
+            last_episode_to_air: None,
+            name: "Test Show".to_string(),
+            networks: vec![],
+            next_episode_to_air: None,
+            number_of_episodes: 2,
+            number_of_seasons: 1,
+            origin_country: vec![],
+            original_language: "en".to_string(),
+            original_name: "Test Show".to_string(),
+            overview: String::new(),
+            popularity: 0.0,
+            poster_path: None,
+            production_companies: vec![],
+            production_countries: vec![],
+            seasons: vec![],
+            spoken_languages: vec![],
+            status: "Ended".to_string(),
+            tagline: String::new(),
+            type_: "Scripted".to_string(),
+            vote_average: 0.0,
+            vote_count: 0,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn creates_nested_upload_dirs_once() {
+        let server = TestFtpServer::start().await;
+        let mut ftp_stream = server.connect();
+
+        let result = blocking(move || {
+            let dir = Path::new("/Movies/Alien (1979)");
+            ensure_remote_dir_recursive(&mut ftp_stream, dir)?;
+            // Running it again must not try to create the directories twice
+            ensure_remote_dir_recursive(&mut ftp_stream, dir)?;
+            let dirs = list_directories(&mut ftp_stream, "/Movies")?;
+            let _ = ftp_stream.quit();
+            Ok::<_, String>(dirs)
+        })
+        .await;
+
+        assert_eq!(result, Ok(vec!["Alien (1979)".to_string()]));
+        assert!(server.root.join("Movies/Alien (1979)").is_dir());
+    }
+
+    #[test]
+    fn file_modes_are_octal() {
+        assert!(validate_file_mode("664").is_ok());
+        assert!(validate_file_mode("0775").is_ok());
+        assert!(validate_file_mode("").is_ok());
+        assert!(validate_file_mode("rw-r--r--").is_err());
+        assert!(validate_file_mode("968").is_err());
+        assert!(validate_file_mode("64").is_err());
+    }
+
+    #[test]
+    fn season_posters_are_named_after_the_season() {
+        assert_eq!(season_poster_filename(&season()), "Season01.jpg");
+        let specials = SeasonResponse {
+            season_number: 0,
+            ..season()
+        };
+        assert_eq!(season_poster_filename(&specials), "Season00.jpg");
+    }
+
+    #[test]
+    fn shows_the_upload_speed_once_known() {
+        assert_eq!(
+            upload_subtitle("Alien (1979).mkv", Some(11_240_000.0)),
+            "Uploading Alien (1979).mkv at 11.2 MB/s"
+        );
+        assert_eq!(
+            upload_subtitle("Alien (1979).mkv", None),
+            "Uploading Alien (1979).mkv"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn streams_file_with_progress() {
+        let server = TestFtpServer::start().await;
+        let mut ftp_stream = server.connect();
+        // A few chunks plus a partial one
+        let content: Vec<u8> = (0..CHUNK_SIZE * 3 + 17).map(|i| (i % 251) as u8).collect();
+        let expected = content.clone();
+
+        let (sent, progress) = blocking(move || {
+            let mut progress = Vec::new();
+            ftp_stream.transfer_type(FileType::Binary).unwrap();
+            let sent = stream_file(
+                &mut ftp_stream,
+                &mut Cursor::new(&content),
+                "Alien (1979).mkv",
+                |bytes_sent| progress.push(bytes_sent),
+            );
+            let _ = ftp_stream.quit();
+            (sent, progress)
+        })
+        .await;
+
+        assert_eq!(sent, Ok(expected.len() as u64));
+        assert_eq!(progress.len(), 4);
+        assert_eq!(progress.first().copied(), Some(CHUNK_SIZE as u64));
+        assert_eq!(progress.last().copied(), Some(expected.len() as u64));
+        assert_eq!(
+            fs::read(server.root.join("Alien (1979).mkv")).unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn uploads_under_a_part_name_then_renames() {
+        let server = TestFtpServer::start().await;
+        fs::write(server.root.join("Alien (1979).mkv"), b"old rip").unwrap();
+        let mut ftp_stream = server.connect();
+        let content: Vec<u8> = (0..CHUNK_SIZE + 5).map(|i| (i % 251) as u8).collect();
+        let expected = content.clone();
+
+        let sent = blocking(move || {
+            ftp_stream.transfer_type(FileType::Binary).unwrap();
+            let sent = upload_then_rename(
+                &mut ftp_stream,
+                &mut Cursor::new(&content),
+                "Alien (1979).mkv",
+                |_| {},
+            );
+            let _ = ftp_stream.quit();
+            sent
+        })
+        .await;
+
+        assert_eq!(sent, Ok(expected.len() as u64));
+        assert!(!server.root.join("Alien (1979).mkv.part").exists());
+        assert_eq!(
+            fs::read(server.root.join("Alien (1979).mkv")).unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reports_missing_directories() {
+        let server = TestFtpServer::start().await;
+        let mut ftp_stream = server.connect();
+
+        let (cwd_result, list_result) = blocking(move || {
+            let cwd_result = cwd(&mut ftp_stream, Path::new("/TV Shows"));
+            let list_result = list_directories(&mut ftp_stream, "/TV Shows");
+            let _ = ftp_stream.quit();
+            (cwd_result, list_result)
+        })
+        .await;
+
+        assert!(cwd_result
+            .unwrap_err()
+            .starts_with("failed to CWD to /TV Shows"));
+        assert_eq!(
+            list_result,
+            Err("Cannot access directory: /TV Shows".to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn swaps_episode_files() {
+        let server = TestFtpServer::start().await;
+        let season_dir = server.root.join("TV Shows/Test Show (2020)/Season 01");
+        fs::create_dir_all(&season_dir).unwrap();
+        fs::write(
+            season_dir.join("Test Show (2020) - S01E01 - Episode 1.mkv"),
+            "first",
+        )
+        .unwrap();
+        fs::write(
+            season_dir.join("Test Show (2020) - S01E02 - Episode 2 [Bluray-1080p].mkv"),
+            "second",
+        )
+        .unwrap();
+        let mut ftp_stream = server.connect();
+
+        let moved = blocking(move || {
+            let moved = reorder_tv_episode_files_in(
+                &mut ftp_stream,
+                &tv(),
+                &season(),
+                &[(1, 2), (2, 1)],
+                Path::new("/TV Shows/Test Show (2020)/Season 01"),
+                &NamingOptions::default(),
+            );
+            let _ = ftp_stream.quit();
+            moved
+        })
+        .await;
+
+        assert_eq!(moved, Ok(2));
+        assert_eq!(
+            fs::read_to_string(season_dir.join("Test Show (2020) - S01E02 - Episode 2.mkv"))
+                .unwrap(),
+            "first"
+        );
+        assert_eq!(
+            fs::read_to_string(
+                season_dir.join("Test Show (2020) - S01E01 - Episode 1 [Bluray-1080p].mkv")
+            )
+            .unwrap(),
+            "second"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn refuses_to_overwrite_other_episodes() {
+        let server = TestFtpServer::start().await;
+        let season_dir = server.root.join("Season 01");
+        fs::create_dir_all(&season_dir).unwrap();
+        fs::write(
+            season_dir.join("Test Show (2020) - S01E01 - Episode 1.mkv"),
+            "1",
+        )
+        .unwrap();
+        fs::write(
+            season_dir.join("Test Show (2020) - S01E02 - Episode 2.mkv"),
+            "2",
+        )
+        .unwrap();
+        let mut ftp_stream = server.connect();
+
+        let moved = blocking(move || {
+            reorder_tv_episode_files_in(
+                &mut ftp_stream,
+                &tv(),
+                &season(),
+                &[(1, 2)],
+                Path::new("/Season 01"),
+                &NamingOptions::default(),
+            )
+        })
+        .await;
+
+        assert_eq!(
+            moved,
+            Err(
+                "Destination file already exists: Test Show (2020) - S01E02 - Episode 2.mkv"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            fs::read_to_string(season_dir.join("Test Show (2020) - S01E02 - Episode 2.mkv"))
+                .unwrap(),
+            "2"
+        );
+    }
 }
@@ -1,7 +1,8 @@
-use crate::services::ftp_uploader;
+use crate::services::{blocking, ftp_uploader, network, notifications};
+use crate::state::locks::MutexExt;
 use crate::state::{AppState, FtpConfig};
 use crate::templates::{ftp_status, toast};
-use log::debug;
+use log::{debug, error};
 use std::collections::HashSet;
 use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
@@ -339,9 +340,7 @@ fn run_ftp_check_with_statuses(
     let ftp_config = app_state.ftp_config.clone();
 
     let config_snapshot = {
-        let config = ftp_config
-            .lock()
-            .expect("failed to lock ftp_config to clone config snapshot");
+        let config = ftp_config.lock_or_recover();
         config.clone()
     };
 
@@ -351,9 +350,7 @@ fn run_ftp_check_with_statuses(
 
     // Publish an immediate "Checking" state so the UI reflects active validation.
     {
-        let mut config = ftp_config
-            .lock()
-            .expect("failed to lock ftp_config to set checker checking state");
+        let mut config = ftp_config.lock_or_recover();
         config.checker = checker.clone();
     }
     if let Ok(turbo) = ftp_status::render_update(app_handle) {
@@ -371,9 +368,7 @@ fn run_ftp_check_with_statuses(
     }
 
     {
-        let mut config = ftp_config
-            .lock()
-            .expect("failed to lock ftp_config to update checker");
+        let mut config = ftp_config.lock_or_recover();
         config.checker = checker;
     }
 
@@ -391,15 +386,33 @@ async fn start_periodic_ftp_check(app_handle: &AppHandle) {
 
     loop {
         interval.tick().await;
-        let (previous_status, new_status) = run_ftp_check_with_statuses(app_handle);
-        emit_toast(app_handle, previous_status, new_status);
+        if network::is_offline(app_handle) {
+            continue;
+        }
+        if let Some((previous_status, new_status)) = run_ftp_check(app_handle).await {
+            emit_toast(app_handle, previous_status, new_status);
+        }
+    }
+}
+
+/// The check talks to the FTP server, it runs on the blocking pool.
+async fn run_ftp_check(
+    app_handle: &AppHandle,
+) -> Option<(FtpConnectionStatus, FtpConnectionStatus)> {
+    let app_handle = app_handle.clone();
+    match blocking::run(move || run_ftp_check_with_statuses(&app_handle)).await {
+        Ok(statuses) => Some(statuses),
+        Err(e) => {
+            error!("FTP check failed: {e}");
+            None
+        }
     }
 }
 
 pub fn trigger_ftp_check(app_handle: &AppHandle) {
     let app_handle = app_handle.clone();
     tauri::async_runtime::spawn(async move {
-        let _ = run_ftp_check_with_statuses(&app_handle);
+        let _ = run_ftp_check(&app_handle).await;
     });
 }
 
@@ -423,9 +436,7 @@ fn emit_toast(
             _ => return,
         };
 
-        if let Ok(turbo) = toast::render_toast_append(toast_msg) {
-            let _ = app_handle.emit("disks-changed", turbo);
-        }
+        notifications::emit_toast(app_handle, toast_msg);
     }
 }
 
@@ -450,6 +461,7 @@ mod tests {
             movie_upload_path: Some(PathBuf::from("/Media/Movies")),
             tv_upload_path: Some(PathBuf::from("/Media/TV Shows")),
             checker: FtpChecker::default(),
+            ..FtpConfig::new()
         }
     }
 
@@ -1,8 +1,9 @@
+use crate::reelix_error::ReelixError;
 use crate::services::semantic_version::SemanticVersion;
-use crate::standard_error::StandardError;
 use log::error;
 use regex::Regex;
 use serde::Deserialize;
+use std::time::Duration;
 use tauri_plugin_http::reqwest::Client;
 
 #[derive(Debug, Deserialize)]
@@ -10,8 +11,11 @@ struct GitHubRelease {
     tag_name: String,
 }
 
-pub async fn fetch_latest_release_version() -> SemanticVersion {
-    let client = Client::new();
+pub async fn fetch_latest_release_version(timeout: Duration) -> SemanticVersion {
+    let client = Client::builder()
+        .timeout(timeout)
+        .build()
+        .unwrap_or_else(|_| Client::new());
     match check_for_update_with_client(
         &client,
         "https://api.github.com/repos/brand-it/reelix/releases/latest",
@@ -29,25 +33,22 @@ pub async fn fetch_latest_release_version() -> SemanticVersion {
 async fn check_for_update_with_client(
     client: &Client,
     api_url: &str,
-) -> Result<SemanticVersion, StandardError> {
+) -> Result<SemanticVersion, ReelixError> {
     let response = client
         .get(api_url)
         .header("User-Agent", "Reelix")
         .send()
         .await
-        .map_err(|e| StandardError::new("Failed to fetch latest release".into(), e.to_string()))?;
+        .map_err(|e| ReelixError::network("Failed to fetch latest release", e))?;
 
     if !response.status().is_success() {
-        return Err(StandardError::new(
-            "GitHub API Error".into(),
-            response.status().to_string(),
-        ));
+        return Err(ReelixError::network("GitHub API Error", response.status()));
     }
 
     let release: GitHubRelease = response
         .json()
         .await
-        .map_err(|e| StandardError::new("Failed to parse GitHub response".into(), e.to_string()))?;
+        .map_err(|e| ReelixError::network("Failed to parse GitHub response", e))?;
 
     Ok(extract_version(&release.tag_name))
 }
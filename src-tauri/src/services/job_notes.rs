@@ -0,0 +1,117 @@
+//! Free-text notes on jobs, e.g. "shelf B, box 3", to find the physical disc again when a
+//! title has to be ripped a second time.
+//!
+//! Jobs only live in memory, the notes are kept in "job_notes.json" by the title and disc
+//! name of their job so they are still there after a restart.
+use crate::services::unix_time;
+use crate::state::job_state::Job;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "job_notes.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JobNote {
+    pub title: Option<String>,
+    pub disc: Option<String>,
+    pub note: String,
+    /// Seconds since the Unix epoch
+    pub noted_at: u64,
+}
+
+impl JobNote {
+    pub fn of(job: &Job, note: &str) -> Self {
+        JobNote {
+            title: job.title.clone(),
+            disc: job.disk.as_ref().map(|disk| disk.name.clone()),
+            note: note.to_string(),
+            noted_at: unix_time::now(),
+        }
+    }
+
+    fn same_job(&self, other: &JobNote) -> bool {
+        self.title == other.title && self.disc == other.disc
+    }
+}
+
+/// Blank notes are `None`.
+pub fn clean(note: &str) -> Option<String> {
+    let note = note.trim();
+    (!note.is_empty()).then(|| note.to_string())
+}
+
+/// Replaces the note of the same job, a blank note deletes it.
+pub fn upsert(notes: &mut Vec<JobNote>, note: JobNote) {
+    notes.retain(|existing| !existing.same_job(&note));
+    if !note.note.trim().is_empty() {
+        notes.push(note);
+    }
+}
+
+pub fn load(app_handle: &AppHandle) -> Result<Vec<JobNote>, String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to load {STORE_FILE} store: {e}"))?;
+    let notes = match store.get("notes") {
+        Some(value) => serde_json::from_value(value.clone()).unwrap_or_default(),
+        None => Vec::new(),
+    };
+    store.close_resource();
+    Ok(notes)
+}
+
+fn save(app_handle: &AppHandle, notes: &[JobNote]) -> Result<(), String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to open {STORE_FILE} store: {e}"))?;
+    store.set("notes", json!(notes));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save {STORE_FILE} store: {e}"))?;
+    store.close_resource();
+    Ok(())
+}
+
+/// Writes the note of `job` to the store.
+pub fn persist(app_handle: &AppHandle, job: &Job) -> Result<(), String> {
+    let mut notes = load(app_handle)?;
+    upsert(
+        &mut notes,
+        JobNote::of(job, job.note.as_deref().unwrap_or_default()),
+    );
+    save(app_handle, &notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(title: &str, note: &str) -> JobNote {
+        JobNote {
+            title: Some(title.to_string()),
+            disc: Some("ALIEN".to_string()),
+            note: note.to_string(),
+            noted_at: 0,
+        }
+    }
+
+    #[test]
+    fn replaces_the_note_of_the_same_job() {
+        let mut notes = vec![note("Alien (1979)", "shelf A")];
+        upsert(&mut notes, note("Aliens (1986)", "shelf A"));
+        upsert(&mut notes, note("Alien (1979)", "shelf B, box 3"));
+        assert_eq!(
+            notes,
+            vec![
+                note("Aliens (1986)", "shelf A"),
+                note("Alien (1979)", "shelf B, box 3")
+            ]
+        );
+
+        upsert(&mut notes, note("Aliens (1986)", " "));
+        assert_eq!(notes, vec![note("Alien (1979)", "shelf B, box 3")]);
+        assert_eq!(clean("  "), None);
+    }
+}
@@ -0,0 +1,254 @@
+//! Jobs only live in memory, a crash forgets what was running and leaves half-written rips
+//! in the library folders.
+//!
+//! Every few seconds the running jobs are written to "job_snapshots.json". On the next start
+//! they show up in the job history as interrupted and the titles they were ripping are
+//! deleted, makemkvcon can't continue a partial file.
+use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::job_state::{Job, JobStatus, JobType};
+use crate::state::locks::RwLockExt;
+use crate::state::title_video;
+use crate::state::AppState;
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "job_snapshots.json";
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JobSnapshot {
+    pub job_id: u64,
+    pub job_type: JobType,
+    pub title: Option<String>,
+    /// 1 based position of the title being worked on
+    pub title_position: Option<usize>,
+    pub total_titles: usize,
+    pub percent: f32,
+    /// What makemkvcon was writing when the snapshot was taken
+    pub partial_file: Option<PathBuf>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl JobSnapshot {
+    pub fn of(job: &Job, app_state: &AppState) -> Self {
+        let partial_file = if job.job_type == JobType::Ripping {
+            job.current_title_video()
+                .and_then(|title_video| title_video.read_or_recover().partial_rip_path(app_state))
+        } else {
+            None
+        };
+        JobSnapshot {
+            job_id: job.id.into(),
+            job_type: job.job_type.clone(),
+            title: job.title.clone(),
+            title_position: job.current_title_position(),
+            total_titles: job.total_titles_count(),
+            percent: job.progress.percent,
+            partial_file,
+            note: job.note.clone(),
+        }
+    }
+
+    /// e.g. "Reelix stopped at 42% of title 2 of 5"
+    pub fn interrupted_message(&self) -> String {
+        match self.title_position {
+            Some(position) if self.total_titles > 1 => format!(
+                "Reelix stopped at {:.0}% of title {position} of {}",
+                self.percent, self.total_titles
+            ),
+            _ => format!("Reelix stopped at {:.0}%", self.percent),
+        }
+    }
+}
+
+/// Snapshots of the jobs that are running right now.
+pub fn snapshots(app_handle: &AppHandle) -> Vec<JobSnapshot> {
+    let app_state = app_handle.state::<AppState>();
+    let background_process_state = app_handle.state::<BackgroundProcessState>();
+    let jobs = background_process_state.jobs.read_or_recover().clone();
+    jobs.iter()
+        .filter_map(|job| {
+            let job = job.read_or_recover();
+            // A season job only mirrors its children, they have their own snapshots
+            (job.status == JobStatus::Processing && job.job_type != JobType::Season)
+                .then(|| JobSnapshot::of(&job, &app_state))
+        })
+        .collect()
+}
+
+/// Writes the snapshots every few seconds, only when something changed.
+pub async fn watch(app_handle: AppHandle) {
+    let mut last_saved = Vec::new();
+    loop {
+        tokio::time::sleep(SNAPSHOT_INTERVAL).await;
+        let current = snapshots(&app_handle);
+        if current == last_saved {
+            continue;
+        }
+        match save(&app_handle, &current) {
+            Ok(()) => last_saved = current,
+            Err(e) => error!("Failed to save job snapshots: {e}"),
+        }
+    }
+}
+
+/// Saves what is running right now, called on exit so finished jobs aren't reported as
+/// interrupted on the next start.
+pub fn save_now(app_handle: &AppHandle) {
+    if let Err(e) = save(app_handle, &snapshots(app_handle)) {
+        error!("Failed to save job snapshots: {e}");
+    }
+}
+
+/// Puts the jobs of the last run that never finished into the job history and deletes
+/// their partial rips.
+pub fn recover(app_handle: &AppHandle) {
+    let interrupted = match load(app_handle) {
+        Ok(interrupted) => interrupted,
+        Err(e) => {
+            error!("Failed to read the job snapshots of the last run: {e}");
+            return;
+        }
+    };
+    if interrupted.is_empty() {
+        return;
+    }
+    info!(
+        "{} jobs were interrupted in the last run",
+        interrupted.len()
+    );
+
+    let background_process_state = app_handle.state::<BackgroundProcessState>();
+    for snapshot in &interrupted {
+        let mut job = Job::new(snapshot.job_type.clone(), None, JobStatus::Error);
+        job.title = snapshot.title.clone();
+        job.note = snapshot.note.clone();
+        job.message = Some(snapshot.interrupted_message());
+        if let Some(partial_file) = &snapshot.partial_file {
+            match title_video::remove_partial_file(partial_file) {
+                Ok(true) => job.notices.push(format!(
+                    "Deleted the partial rip {}, rip the disc again",
+                    partial_file.display()
+                )),
+                Ok(false) => {}
+                Err(e) => error!("{e}"),
+            }
+        }
+        debug!("Job {} was interrupted: {:?}", snapshot.job_id, snapshot);
+        background_process_state.add_job(job);
+    }
+    background_process_state.emit_jobs_changed(app_handle);
+
+    // The interrupted jobs are in the history now, a second start shouldn't add them again
+    if let Err(e) = save(app_handle, &[]) {
+        error!("Failed to clear the job snapshots: {e}");
+    }
+}
+
+fn load(app_handle: &AppHandle) -> Result<Vec<JobSnapshot>, String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to load {STORE_FILE} store: {e}"))?;
+    let snapshots = match store.get("jobs") {
+        Some(value) => serde_json::from_value(value.clone()).unwrap_or_default(),
+        None => Vec::new(),
+    };
+    store.close_resource();
+    Ok(snapshots)
+}
+
+fn save(app_handle: &AppHandle, snapshots: &[JobSnapshot]) -> Result<(), String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to open {STORE_FILE} store: {e}"))?;
+    store.set("jobs", json!(snapshots));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save {STORE_FILE} store: {e}"))?;
+    store.close_resource();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::title_info::TitleInfo;
+    use crate::services::test_support::test_dir;
+    use crate::state::title_video::{PersonalVideo, TitleVideo, TitleVideoId, Video};
+    use std::sync::{Arc, RwLock};
+
+    fn snapshot(title_position: Option<usize>, total_titles: usize) -> JobSnapshot {
+        JobSnapshot {
+            job_id: 7,
+            job_type: JobType::Ripping,
+            title: Some("Alien (1979)".to_string()),
+            title_position,
+            total_titles,
+            percent: 42.4,
+            partial_file: Some(PathBuf::from("/rips/Movies/Alien (1979)/title_t00.mkv")),
+            note: None,
+        }
+    }
+
+    #[test]
+    fn says_where_the_job_stopped() {
+        assert_eq!(
+            snapshot(Some(2), 5).interrupted_message(),
+            "Reelix stopped at 42% of title 2 of 5"
+        );
+        assert_eq!(
+            snapshot(Some(1), 1).interrupted_message(),
+            "Reelix stopped at 42%"
+        );
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_json() {
+        let snapshots = vec![snapshot(Some(2), 5)];
+        let value = json!(snapshots);
+        let restored: Vec<JobSnapshot> = serde_json::from_value(value).unwrap();
+        assert_eq!(restored, snapshots);
+    }
+
+    #[test]
+    fn snapshots_leave_the_library_folders_alone() {
+        let dir = test_dir("job-snapshots-unwritable");
+        // A file where the library is expected, nothing can be created below it
+        std::fs::write(dir.join("Home Videos"), b"not a folder").unwrap();
+        let app_state = AppState::new();
+        *app_state.movies_dir.write().unwrap() = dir.join("Movies");
+
+        let mut title = TitleInfo::new(0);
+        title.filename = Some("title_t00.mkv".to_string());
+        let title_video = TitleVideo {
+            id: TitleVideoId::new(),
+            title: Some(title),
+            video: Video::Personal(Box::new(PersonalVideo {
+                name: "Birthday".to_string(),
+                date: None,
+                part: None,
+            })),
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
+        };
+        let current_id = title_video.id;
+        let mut job = Job::new(JobType::Ripping, None, JobStatus::Processing)
+            .with_title_videos(vec![Arc::new(RwLock::new(title_video))]);
+        job.current_title_video_id = Some(current_id);
+
+        let snapshot = JobSnapshot::of(&job, &app_state);
+        assert_eq!(
+            snapshot.partial_file,
+            Some(dir.join("Home Videos/Birthday/title_t00.mkv"))
+        );
+        assert!(!dir.join("Movies").exists());
+    }
+}
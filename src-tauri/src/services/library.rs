@@ -0,0 +1,284 @@
+use crate::services::ftp_uploader;
+use crate::services::naming::NamingOptions;
+use crate::state::FtpConfig;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A named library, e.g. "Kids", picked per rip job so its videos land in their own Plex library.
+///
+/// Every field left empty falls back to the main library settings, a profile that only
+/// sets the FTP paths still rips into the main movies and TV shows folders.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LibraryProfile {
+    pub name: String,
+    #[serde(default)]
+    pub movies_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub tv_shows_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub movie_upload_path: Option<PathBuf>,
+    #[serde(default)]
+    pub tv_upload_path: Option<PathBuf>,
+    #[serde(default)]
+    pub movie_path_template: Option<String>,
+    #[serde(default)]
+    pub tv_path_template: Option<String>,
+    /// Replaces the main FTP file mode, e.g. when this library's Plex runs as another user
+    #[serde(default)]
+    pub file_mode: Option<String>,
+    /// Replaces the main setting for uploading season posters next to the episodes
+    #[serde(default)]
+    pub season_posters: Option<bool>,
+    /// Audio languages in order of preference, e.g. `["jpn", "eng"]`
+    #[serde(default)]
+    pub audio_languages: Vec<String>,
+    /// Subtitle languages in order of preference, empty leaves the subtitles as ripped
+    #[serde(default)]
+    pub subtitle_languages: Vec<String>,
+    /// The makemkvcon read cache in MB, sized from the memory of this machine when not set
+    #[serde(default)]
+    pub rip_cache_mb: Option<u32>,
+    /// Replaces the main post-process setting for adding a stereo downmix
+    #[serde(default)]
+    pub downmix_stereo: Option<bool>,
+    /// Replaces the main post-process setting for loudness normalizing the added track
+    #[serde(default)]
+    pub normalize_loudness: Option<bool>,
+}
+
+impl LibraryProfile {
+    /// Trims the name and turns blank fields into `None` so they fall back to the main library.
+    pub fn cleaned(self) -> Self {
+        fn path(value: Option<PathBuf>) -> Option<PathBuf> {
+            value.filter(|path| !path.as_os_str().to_string_lossy().trim().is_empty())
+        }
+        fn text(value: Option<String>) -> Option<String> {
+            value
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        }
+        fn languages(value: Vec<String>) -> Vec<String> {
+            value
+                .into_iter()
+                .map(|language| language.trim().to_lowercase())
+                .filter(|language| !language.is_empty())
+                .collect()
+        }
+        LibraryProfile {
+            name: self.name.trim().to_string(),
+            movies_dir: path(self.movies_dir),
+            tv_shows_dir: path(self.tv_shows_dir),
+            movie_upload_path: path(self.movie_upload_path),
+            tv_upload_path: path(self.tv_upload_path),
+            movie_path_template: text(self.movie_path_template),
+            tv_path_template: text(self.tv_path_template),
+            file_mode: text(self.file_mode),
+            season_posters: self.season_posters,
+            audio_languages: languages(self.audio_languages),
+            subtitle_languages: languages(self.subtitle_languages),
+            rip_cache_mb: self.rip_cache_mb.filter(|mb| *mb > 0),
+            downmix_stereo: self.downmix_stereo,
+            normalize_loudness: self.normalize_loudness,
+        }
+    }
+
+    /// The main library with this profile's settings on top.
+    pub fn apply(&self, mut library: Library) -> Library {
+        if let Some(dir) = &self.movies_dir {
+            library.movies_dir = dir.clone();
+        }
+        if let Some(dir) = &self.tv_shows_dir {
+            library.tv_shows_dir = dir.clone();
+        }
+        if let Some(path) = &self.movie_upload_path {
+            library.ftp_config.movie_upload_path = Some(path.clone());
+        }
+        if let Some(path) = &self.tv_upload_path {
+            library.ftp_config.tv_upload_path = Some(path.clone());
+        }
+        if let Some(template) = &self.movie_path_template {
+            library.ftp_config.movie_path_template = Some(template.clone());
+        }
+        if let Some(template) = &self.tv_path_template {
+            library.ftp_config.tv_path_template = Some(template.clone());
+        }
+        if let Some(file_mode) = &self.file_mode {
+            library.ftp_config.file_mode = Some(file_mode.clone());
+        }
+        if let Some(season_posters) = self.season_posters {
+            library.ftp_config.season_posters = season_posters;
+        }
+        library.audio_languages = self.audio_languages.clone();
+        library.subtitle_languages = self.subtitle_languages.clone();
+        library.rip_cache_mb = self.rip_cache_mb;
+        library.downmix_stereo = self.downmix_stereo;
+        library.normalize_loudness = self.normalize_loudness;
+        library
+    }
+
+    /// True when `path` is inside one of this profile's own local folders.
+    pub fn contains(&self, path: &Path) -> bool {
+        [&self.movies_dir, &self.tv_shows_dir]
+            .into_iter()
+            .flatten()
+            .any(|dir| path.starts_with(dir))
+    }
+}
+
+/// Everything needed to work out where a video goes, resolved from the settings and a profile.
+#[derive(Clone)]
+pub struct Library {
+    pub movies_dir: PathBuf,
+    pub tv_shows_dir: PathBuf,
+    pub ftp_config: FtpConfig,
+    pub naming: NamingOptions,
+    /// Picks the default audio track after the rip, the main library keeps what the disc marks
+    pub audio_languages: Vec<String>,
+    pub subtitle_languages: Vec<String>,
+    /// See `makemkv_cache`, the main library sizes the cache from the memory
+    pub rip_cache_mb: Option<u32>,
+    /// Post-process settings of the profile, `None` keeps the main ones
+    pub downmix_stereo: Option<bool>,
+    pub normalize_loudness: Option<bool>,
+}
+
+/// Home videos go in their own Plex library next to the movies, e.g. `/rips/Home Videos`.
+const HOME_VIDEOS_DIR: &str = "Home Videos";
+
+impl Library {
+    pub fn home_videos_dir(&self) -> PathBuf {
+        sibling(&self.movies_dir, HOME_VIDEOS_DIR)
+    }
+
+    pub fn home_videos_upload_path(&self) -> Option<PathBuf> {
+        self.ftp_config
+            .movie_upload_path
+            .as_deref()
+            .map(|path| sibling(path, HOME_VIDEOS_DIR))
+    }
+}
+
+fn sibling(dir: &Path, name: &str) -> PathBuf {
+    dir.parent().unwrap_or(dir).join(name)
+}
+
+/// Profile names have to be unique and not blank, they are how a job refers to its profile.
+pub fn validate(profiles: &[LibraryProfile]) -> Result<(), String> {
+    for (index, profile) in profiles.iter().enumerate() {
+        if profile.name.is_empty() {
+            return Err("Library profiles need a name".to_string());
+        }
+        if profiles[..index]
+            .iter()
+            .any(|other| other.name.eq_ignore_ascii_case(&profile.name))
+        {
+            return Err(format!(
+                "There is more than one library profile named {}",
+                profile.name
+            ));
+        }
+        if let Some(file_mode) = &profile.file_mode {
+            ftp_uploader::validate_file_mode(file_mode)
+                .map_err(|e| format!("Library profile {}: {e}", profile.name))?;
+        }
+    }
+    Ok(())
+}
+
+/// The store only keeps strings, the profiles are kept as one JSON string.
+pub fn to_store_value(profiles: &[LibraryProfile]) -> String {
+    serde_json::to_string(profiles).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn from_store_value(value: &str) -> Vec<LibraryProfile> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn main_library() -> Library {
+        let mut ftp_config = FtpConfig::new();
+        ftp_config.movie_upload_path = Some(PathBuf::from("/Media/Movies"));
+        ftp_config.tv_upload_path = Some(PathBuf::from("/Media/TV Shows"));
+        Library {
+            movies_dir: PathBuf::from("/rips/Movies"),
+            tv_shows_dir: PathBuf::from("/rips/TV Shows"),
+            ftp_config,
+            naming: NamingOptions::default(),
+            audio_languages: Vec::new(),
+            subtitle_languages: Vec::new(),
+            rip_cache_mb: None,
+            downmix_stereo: None,
+            normalize_loudness: None,
+        }
+    }
+
+    #[test]
+    fn profile_overrides_only_what_it_sets() {
+        let kids = LibraryProfile {
+            name: "Kids".to_string(),
+            movies_dir: Some(PathBuf::from("/rips/Kids Movies")),
+            movie_upload_path: Some(PathBuf::from("/Media/Kids Movies")),
+            season_posters: Some(false),
+            downmix_stereo: Some(true),
+            ..LibraryProfile::default()
+        };
+
+        let library = kids.apply(main_library());
+        assert_eq!(library.movies_dir, PathBuf::from("/rips/Kids Movies"));
+        assert_eq!(library.tv_shows_dir, PathBuf::from("/rips/TV Shows"));
+        assert_eq!(
+            library.ftp_config.movie_upload_path,
+            Some(PathBuf::from("/Media/Kids Movies"))
+        );
+        assert_eq!(
+            library.ftp_config.tv_upload_path,
+            Some(PathBuf::from("/Media/TV Shows"))
+        );
+        assert!(!library.ftp_config.season_posters);
+        assert!(main_library().ftp_config.season_posters);
+        assert_eq!(library.downmix_stereo, Some(true));
+        assert_eq!(library.normalize_loudness, None);
+    }
+
+    #[test]
+    fn blank_fields_fall_back_to_the_main_library() {
+        let profile = LibraryProfile {
+            name: " Kids ".to_string(),
+            tv_shows_dir: Some(PathBuf::from("")),
+            tv_path_template: Some("  ".to_string()),
+            audio_languages: vec![" ENG ".to_string(), "".to_string()],
+            ..LibraryProfile::default()
+        }
+        .cleaned();
+
+        assert_eq!(profile.name, "Kids");
+        assert_eq!(profile.tv_shows_dir, None);
+        assert_eq!(profile.tv_path_template, None);
+        assert_eq!(profile.audio_languages, vec!["eng".to_string()]);
+    }
+
+    #[test]
+    fn names_must_be_unique() {
+        let profile = |name: &str| LibraryProfile {
+            name: name.to_string(),
+            ..LibraryProfile::default()
+        };
+        assert!(validate(&[profile("Kids"), profile("Anime")]).is_ok());
+        assert!(validate(&[profile("Kids"), profile("kids")]).is_err());
+        assert!(validate(&[profile("")]).is_err());
+    }
+
+    #[test]
+    fn store_value_round_trips() {
+        let profiles = vec![LibraryProfile {
+            name: "Kids".to_string(),
+            tv_upload_path: Some(PathBuf::from("/Media/Kids TV")),
+            ..LibraryProfile::default()
+        }];
+        assert_eq!(from_store_value(&to_store_value(&profiles)), profiles);
+        assert!(from_store_value("not json").is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+//! Looks through the local library for what Plex trips over: folders and files that are not
+//! named the way Reelix names them, empty video files left by a failed rip and season folders
+//! without a show or without episodes.
+//!
+//! Only what can be fixed without guessing is fixed, zero-byte files are removed, empty season
+//! folders too and a lone movie file takes its folder's name. The rest is listed to be renamed
+//! by hand.
+use crate::services::naming::nfc;
+use log::debug;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VIDEO_EXTENSIONS: &[&str] = &["mkv", "mp4", "m4v"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IssueKind {
+    /// A video file with nothing in it
+    ZeroByte,
+    /// A movie folder that is not `Title (Year)`
+    MovieFolderName,
+    /// A movie file that doesn't start with its folder name
+    MovieFileName,
+    /// An episode file without `Show - S01E01`
+    EpisodeFileName,
+    /// A `Season 01` folder that is not inside a show folder
+    OrphanSeason,
+    /// A season folder without a single episode
+    EmptySeason,
+}
+
+impl IssueKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IssueKind::ZeroByte => "Empty file",
+            IssueKind::MovieFolderName => "Movie folder name",
+            IssueKind::MovieFileName => "Movie file name",
+            IssueKind::EpisodeFileName => "Episode file name",
+            IssueKind::OrphanSeason => "Season without show",
+            IssueKind::EmptySeason => "Empty season",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub path: PathBuf,
+    pub detail: String,
+    /// Where a rename fix moves the file to
+    pub rename_to: Option<PathBuf>,
+}
+
+impl Issue {
+    fn new(kind: IssueKind, path: &Path, detail: impl Into<String>) -> Self {
+        Issue {
+            kind,
+            path: path.to_path_buf(),
+            detail: detail.into(),
+            rename_to: None,
+        }
+    }
+
+    pub fn is_fixable(&self) -> bool {
+        matches!(self.kind, IssueKind::ZeroByte | IssueKind::EmptySeason)
+            || self.rename_to.is_some()
+    }
+
+    pub fn path_label(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+}
+
+/// What `audit` found, in the order the folders were walked.
+#[derive(Debug, Default)]
+pub struct LibraryAudit {
+    pub issues: Vec<Issue>,
+}
+
+impl LibraryAudit {
+    pub fn fixable_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.is_fixable())
+            .count()
+    }
+}
+
+/// Audits the movies and TV shows folders, folders that don't exist are skipped.
+pub fn audit(movies_dirs: &[PathBuf], tv_shows_dirs: &[PathBuf]) -> LibraryAudit {
+    let mut issues = Vec::new();
+    for dir in movies_dirs {
+        audit_movies(dir, &mut issues);
+    }
+    for dir in tv_shows_dirs {
+        audit_tv_shows(dir, &mut issues);
+    }
+    LibraryAudit { issues }
+}
+
+/// Fixes the fixable issues, returns how many were fixed and the errors of the rest.
+pub fn fix(issues: &[Issue]) -> (usize, Vec<String>) {
+    let mut fixed = 0;
+    let mut errors = Vec::new();
+    for issue in issues.iter().filter(|issue| issue.is_fixable()) {
+        let result = match (&issue.kind, &issue.rename_to) {
+            (IssueKind::ZeroByte, _) => fs::remove_file(&issue.path),
+            (IssueKind::EmptySeason, _) => fs::remove_dir_all(&issue.path),
+            (_, Some(rename_to)) if rename_to.exists() => {
+                errors.push(format!("{} already exists", rename_to.display()));
+                continue;
+            }
+            (_, Some(rename_to)) => fs::rename(&issue.path, rename_to),
+            _ => continue,
+        };
+        match result {
+            Ok(()) => {
+                debug!("Fixed {:?} {}", issue.kind, issue.path.display());
+                fixed += 1;
+            }
+            Err(e) => errors.push(format!("{}: {e}", issue.path.display())),
+        }
+    }
+    (fixed, errors)
+}
+
+fn sub_dirs(dir: &Path) -> Vec<PathBuf> {
+    entries(dir)
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| !file_name(path).starts_with('.'))
+        .collect();
+    entries.sort();
+    entries
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Names compare the way `file_move::existing_case` matches folders
+fn compare_key(name: &str) -> String {
+    nfc(name).to_lowercase()
+}
+
+fn is_video(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+fn is_empty_file(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| metadata.len() == 0)
+}
+
+/// `Show (2016) {tmdb-66732}` names its episodes `Show (2016) - S01E01`
+fn without_tmdb_tag(folder: &str) -> &str {
+    match folder.rfind(" {tmdb-") {
+        Some(index) if folder.ends_with('}') => &folder[..index],
+        _ => folder,
+    }
+}
+
+fn audit_movies(movies_dir: &Path, issues: &mut Vec<Issue>) {
+    let title_year = Regex::new(r"^.+ \(\d{4}\)$").expect("invalid title year regex");
+    for folder in sub_dirs(movies_dir) {
+        let folder_name = file_name(&folder);
+        if !title_year.is_match(&folder_name) {
+            issues.push(Issue::new(
+                IssueKind::MovieFolderName,
+                &folder,
+                "Plex matches movies by a \"Title (Year)\" folder",
+            ));
+        }
+
+        let videos: Vec<PathBuf> = entries(&folder)
+            .into_iter()
+            .filter(|path| is_video(path))
+            .collect();
+        for video in &videos {
+            if is_empty_file(video) {
+                issues.push(Issue::new(
+                    IssueKind::ZeroByte,
+                    video,
+                    "Nothing was ripped into it",
+                ));
+                continue;
+            }
+            if compare_key(&file_name(video)).starts_with(&compare_key(&folder_name)) {
+                continue;
+            }
+            let mut issue = Issue::new(
+                IssueKind::MovieFileName,
+                video,
+                format!("Should start with \"{folder_name}\""),
+            );
+            // A lone movie can only be the movie, more files may be parts or extras
+            if videos.len() == 1 {
+                let extension = video.extension().unwrap_or_default().to_string_lossy();
+                issue.rename_to = Some(folder.join(format!("{folder_name}.{extension}")));
+            }
+            issues.push(issue);
+        }
+    }
+}
+
+fn audit_tv_shows(tv_shows_dir: &Path, issues: &mut Vec<Issue>) {
+    let season_folder = Regex::new(r"^Season \d{2,}$").expect("invalid season regex");
+    let episode = Regex::new(r"^(.+) - [Ss]\d{2,}[Ee]\d{2,}").expect("invalid episode regex");
+    for show in sub_dirs(tv_shows_dir) {
+        let show_name = file_name(&show);
+        if season_folder.is_match(&show_name) {
+            issues.push(Issue::new(
+                IssueKind::OrphanSeason,
+                &show,
+                "Belongs in a show folder",
+            ));
+            continue;
+        }
+        let episode_show = compare_key(without_tmdb_tag(&show_name));
+        for season in sub_dirs(&show) {
+            if !season_folder.is_match(&file_name(&season)) {
+                continue;
+            }
+            let videos: Vec<PathBuf> = entries(&season)
+                .into_iter()
+                .filter(|path| is_video(path))
+                .collect();
+            if videos.iter().all(|video| is_empty_file(video)) {
+                issues.push(Issue::new(
+                    IssueKind::EmptySeason,
+                    &season,
+                    "Has no episodes",
+                ));
+                continue;
+            }
+            for video in videos {
+                if is_empty_file(&video) {
+                    issues.push(Issue::new(
+                        IssueKind::ZeroByte,
+                        &video,
+                        "Nothing was ripped into it",
+                    ));
+                    continue;
+                }
+                let video_name = file_name(&video);
+                let named_after_show = episode
+                    .captures(&video_name)
+                    .is_some_and(|captures| compare_key(&captures[1]) == episode_show);
+                if !named_after_show {
+                    issues.push(Issue::new(
+                        IssueKind::EpisodeFileName,
+                        &video,
+                        format!(
+                            "Should be \"{} - S01E01 - Episode.mkv\"",
+                            without_tmdb_tag(&show_name)
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::test_dir;
+
+    #[test]
+    fn finds_and_fixes_library_issues() {
+        let root = test_dir("library-audit");
+        let movies = root.join("Movies");
+        let tv_shows = root.join("TV Shows");
+        for dir in [
+            "Movies/Alien (1979)",
+            "Movies/Dune (2021)",
+            "Movies/Heat",
+            "TV Shows/Friends (1994)/Season 01",
+            "TV Shows/Friends (1994)/Season 02",
+            "TV Shows/Season 03",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for (file, contents) in [
+            ("Movies/Alien (1979)/Alien (1979).mkv", "video"),
+            ("Movies/Dune (2021)/title_t00.mkv", "video"),
+            ("Movies/Heat/Heat.mkv", ""),
+            (
+                "TV Shows/Friends (1994)/Season 01/Friends (1994) - S01E01 - Pilot.mkv",
+                "video",
+            ),
+            ("TV Shows/Friends (1994)/Season 01/title_t01.mkv", "video"),
+            ("TV Shows/Friends (1994)/Season 02/Season02.jpg", "poster"),
+        ] {
+            fs::write(root.join(file), contents).unwrap();
+        }
+
+        let audit = audit(
+            std::slice::from_ref(&movies),
+            std::slice::from_ref(&tv_shows),
+        );
+        let kinds: Vec<(IssueKind, String)> = audit
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, file_name(&issue.path)))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (IssueKind::MovieFileName, "title_t00.mkv".to_string()),
+                (IssueKind::MovieFolderName, "Heat".to_string()),
+                (IssueKind::ZeroByte, "Heat.mkv".to_string()),
+                (IssueKind::EpisodeFileName, "title_t01.mkv".to_string()),
+                (IssueKind::EmptySeason, "Season 02".to_string()),
+                (IssueKind::OrphanSeason, "Season 03".to_string()),
+            ]
+        );
+        assert_eq!(audit.fixable_count(), 3);
+
+        let (fixed, errors) = fix(&audit.issues);
+        assert_eq!((fixed, errors), (3, Vec::new()));
+        assert!(movies.join("Dune (2021)/Dune (2021).mkv").is_file());
+        assert!(!movies.join("Heat/Heat.mkv").exists());
+        assert!(!tv_shows.join("Friends (1994)/Season 02").exists());
+    }
+}
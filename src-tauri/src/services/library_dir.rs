@@ -0,0 +1,116 @@
+//! Checks for the local movies and TV shows folders, done when they are saved so a bad
+//! folder is reported in the settings instead of halfway through a rip.
+use crate::services::{locale, long_path};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use sysinfo::Disks;
+
+/// Roughly one Blu-ray rip, less than this is worth a warning.
+const LOW_FREE_SPACE: u64 = 50 * 1024 * 1024 * 1024;
+
+const WRITE_TEST_FILE: &str = ".reelix-write-test";
+
+/// Make `dir` ready to rip into, creating it when missing. Returns a warning when the
+/// folder works but is low on space.
+pub fn prepare(dir: &Path) -> Result<Option<String>, String> {
+    if dir.as_os_str().to_string_lossy().trim().is_empty() {
+        return Err("Pick a folder".to_string());
+    }
+    if !dir.is_absolute() {
+        return Err(format!(
+            "{} is not a full path, pick the folder instead of typing it",
+            dir.display()
+        ));
+    }
+    if dir.exists() && !dir.is_dir() {
+        return Err(format!("{} is a file, not a folder", dir.display()));
+    }
+    fs::create_dir_all(long_path::extend(dir)).map_err(|e| explain(&e, "create", dir))?;
+
+    let test_file = dir.join(WRITE_TEST_FILE);
+    fs::write(&test_file, b"").map_err(|e| explain(&e, "write to", dir))?;
+    let _ = fs::remove_file(&test_file);
+
+    Ok(free_space(dir)
+        .filter(|free| *free < LOW_FREE_SPACE)
+        .map(|free| {
+            format!(
+                "Only {} free in {}, a Blu-ray needs up to 50 GB",
+                locale::current().format_size(free),
+                dir.display()
+            )
+        }))
+}
+
+/// What went wrong in words that say how to fix it.
+fn explain(error: &io::Error, action: &str, dir: &Path) -> String {
+    match error.kind() {
+        io::ErrorKind::PermissionDenied => {
+            let hint = if cfg!(target_os = "macos") {
+                ", allow Reelix under System Settings > Privacy & Security > Files and Folders"
+            } else {
+                ""
+            };
+            format!(
+                "Reelix is not allowed to {action} {}, pick a folder you own or change its permissions{hint}",
+                dir.display()
+            )
+        }
+        _ if read_only(error) => format!(
+            "{} is on a read-only drive, pick a folder on another drive",
+            dir.display()
+        ),
+        _ => format!("Failed to {action} {}: {error}", dir.display()),
+    }
+}
+
+#[cfg(unix)]
+fn read_only(error: &io::Error) -> bool {
+    // EROFS
+    error.raw_os_error() == Some(30)
+}
+
+#[cfg(not(unix))]
+fn read_only(_error: &io::Error) -> bool {
+    false
+}
+
+/// Free space of the drive `dir` is on, `None` when no mounted drive contains it.
+fn free_space(dir: &Path) -> Option<u64> {
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| PathBuf::from(dir));
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::test_dir;
+
+    #[test]
+    fn creates_missing_folders() {
+        let dir = test_dir("library-dir-missing").join("Movies");
+        assert!(prepare(&dir).is_ok());
+        assert!(dir.is_dir());
+        assert!(!dir.join(WRITE_TEST_FILE).exists());
+    }
+
+    #[test]
+    fn rejects_files_and_relative_paths() {
+        let root = test_dir("library-dir-file");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("Movies"), b"").unwrap();
+        assert!(prepare(&root.join("Movies"))
+            .unwrap_err()
+            .contains("is a file"));
+        assert!(prepare(Path::new("Movies"))
+            .unwrap_err()
+            .contains("not a full path"));
+        assert!(prepare(Path::new("")).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! How dates, times, runtimes and sizes are written, following the locale setting.
+//!
+//! The models format themselves and have no app state at hand, so the locale is kept in a
+//! static next to the store setting. It is set when the store loads and when the setting
+//! changes, en-US with GB until then.
+use chrono::{NaiveDate, NaiveTime};
+use std::sync::RwLock;
+
+static CURRENT: RwLock<Locale> = RwLock::new(Locale {
+    tag: String::new(),
+    binary_sizes: false,
+});
+
+/// Languages that write 4,5 GB instead of 4.5 GB
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &[
+    "cs", "da", "de", "es", "fi", "fr", "it", "nb", "nl", "pl", "pt", "ru", "sv", "tr",
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Locale {
+    /// BCP 47 tag like "en-US" or "de-DE", empty is en-US
+    pub tag: String,
+    /// GiB (1024³ bytes) instead of GB (1000³ bytes)
+    pub binary_sizes: bool,
+}
+
+impl Locale {
+    pub fn new(tag: &str, binary_sizes: bool) -> Self {
+        Locale {
+            tag: tag.trim().replace('_', "-"),
+            binary_sizes,
+        }
+    }
+
+    fn language(&self) -> String {
+        match self.tag.split('-').next() {
+            Some(language) if !language.is_empty() => language.to_lowercase(),
+            _ => "en".to_string(),
+        }
+    }
+
+    fn region(&self) -> Option<String> {
+        self.tag
+            .split('-')
+            .nth(1)
+            .map(|region| region.to_uppercase())
+            .or_else(|| self.tag.is_empty().then(|| "US".to_string()))
+    }
+
+    /// The country whose ratings are shown, "DE" for de-DE, the US without a region
+    pub fn country(&self) -> String {
+        self.region().unwrap_or_else(|| "US".to_string())
+    }
+
+    fn is_us(&self) -> bool {
+        self.language() == "en" && matches!(self.region().as_deref(), Some("US") | None)
+    }
+
+    /// Only the US and a few English speaking places read a 12 hour clock.
+    pub fn uses_24_hour_clock(&self) -> bool {
+        !(self.is_us()
+            || matches!(
+                (self.language().as_str(), self.region().as_deref()),
+                ("en", Some("CA" | "AU" | "NZ" | "PH" | "IN"))
+            ))
+    }
+
+    /// Month names are only written out in English, the rest get numeric dates.
+    fn date_pattern(&self) -> &'static str {
+        match self.language().as_str() {
+            _ if self.is_us() => "%B %-d, %Y",
+            "en" => "%-d %B %Y",
+            "de" | "cs" | "da" | "fi" | "nb" | "pl" | "ru" | "tr" => "%-d.%-m.%Y",
+            "ja" | "ko" | "zh" | "sv" | "lt" | "hu" => "%Y-%m-%d",
+            "nl" => "%-d-%-m-%Y",
+            _ => "%d/%m/%Y",
+        }
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(self.date_pattern()).to_string()
+    }
+
+    /// "14:30" or "2:30 PM"
+    pub fn format_time(&self, time: NaiveTime) -> String {
+        if self.uses_24_hour_clock() {
+            time.format("%H:%M").to_string()
+        } else {
+            time.format("%-I:%M %p").to_string()
+        }
+    }
+
+    /// "1h 52m", "45m"
+    pub fn format_runtime(&self, seconds: u64) -> String {
+        let minutes = seconds / 60;
+        match (minutes / 60, minutes % 60) {
+            (0, minutes) => format!("{minutes}m"),
+            (hours, 0) => format!("{hours}h"),
+            (hours, minutes) => format!("{hours}h {minutes}m"),
+        }
+    }
+
+    /// One decimal with the separator of the language.
+    fn format_decimal(&self, value: f64) -> String {
+        let formatted = format!("{value:.1}");
+        if DECIMAL_COMMA_LANGUAGES.contains(&self.language().as_str()) {
+            formatted.replace('.', ",")
+        } else {
+            formatted
+        }
+    }
+
+    /// "4.5 GB" or "4.2 GiB"
+    pub fn format_size(&self, bytes: u64) -> String {
+        let (base, units) = if self.binary_sizes {
+            (1024.0, ["B", "KiB", "MiB", "GiB", "TiB"])
+        } else {
+            (1000.0, ["B", "KB", "MB", "GB", "TB"])
+        };
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= base && unit < units.len() - 1 {
+            value /= base;
+            unit += 1;
+        }
+        if unit == 0 {
+            return format!("{bytes} B");
+        }
+        format!("{} {}", self.format_decimal(value), units[unit])
+    }
+
+    /// "11.2 MB/s" or "10.7 MiB/s"
+    pub fn format_speed(&self, bytes_per_second: f64) -> String {
+        let (mega, unit) = if self.binary_sizes {
+            (1024.0 * 1024.0, "MiB/s")
+        } else {
+            (1_000_000.0, "MB/s")
+        };
+        format!("{} {unit}", self.format_decimal(bytes_per_second / mega))
+    }
+}
+
+pub fn current() -> Locale {
+    match CURRENT.read() {
+        Ok(locale) => locale.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+pub fn set_current(locale: Locale) {
+    match CURRENT.write() {
+        Ok(mut current) => *current = locale,
+        Err(poisoned) => *poisoned.into_inner() = locale,
+    }
+}
+
+/// Changes part of the current locale, e.g. when one store key is loaded.
+pub fn update(change: impl FnOnce(&mut Locale)) {
+    let mut locale = current();
+    change(&mut locale);
+    set_current(locale);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_dates_and_times_like_the_locale() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 7).unwrap();
+        let time = NaiveTime::from_hms_opt(14, 30, 0).unwrap();
+        let us = Locale::default();
+        assert_eq!(us.format_date(date), "March 7, 2024");
+        assert_eq!(us.format_time(time), "2:30 PM");
+
+        let uk = Locale::new("en_GB", false);
+        assert_eq!(uk.format_date(date), "7 March 2024");
+        assert_eq!(uk.format_time(time), "14:30");
+
+        let german = Locale::new("de-DE", false);
+        assert_eq!(german.format_date(date), "7.3.2024");
+        assert_eq!(Locale::new("ja", false).format_date(date), "2024-03-07");
+    }
+
+    #[test]
+    fn writes_sizes_in_decimal_or_binary_units() {
+        let bytes = 12 * 1024 * 1024 * 1024;
+        assert_eq!(Locale::default().format_size(bytes), "12.9 GB");
+        assert_eq!(Locale::new("en-US", true).format_size(bytes), "12.0 GiB");
+        assert_eq!(
+            Locale::new("de-DE", false).format_size(4_500_000_000),
+            "4,5 GB"
+        );
+        assert_eq!(Locale::default().format_size(512), "512 B");
+        assert_eq!(Locale::default().format_speed(11_200_000.0), "11.2 MB/s");
+    }
+
+    #[test]
+    fn writes_runtimes_in_hours_and_minutes() {
+        let locale = Locale::default();
+        assert_eq!(locale.format_runtime(6720), "1h 52m");
+        assert_eq!(locale.format_runtime(7200), "2h");
+        assert_eq!(locale.format_runtime(2700), "45m");
+    }
+}
@@ -0,0 +1,94 @@
+//! Windows fails to create, open or rename a path longer than MAX_PATH (260 characters)
+//! unless the path is verbatim, `\\?\C:\TV Shows\...`. A long show name and an episode title
+//! get there quickly, so the library is written through `extend`. Other platforms have no such
+//! limit and keep their paths.
+//!
+//! Only the file system calls get the verbatim path, what is shown or compared against the
+//! library folders stays the plain one.
+use std::path::{Path, PathBuf};
+
+/// Longest path Windows takes without the verbatim prefix, less the 12 characters it keeps
+/// free for an 8.3 file name in a directory
+const MAX_PLAIN_PATH: usize = 260 - 12;
+const VERBATIM_PREFIX: &str = r"\\?\";
+
+/// `path` as the file system calls should get it, verbatim when it is too long for Windows.
+pub fn extend(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        let path_str = path.to_string_lossy();
+        if path_str.chars().count() >= MAX_PLAIN_PATH {
+            if let Some(verbatim) = verbatim(&path_str) {
+                return PathBuf::from(verbatim);
+            }
+        }
+    }
+    path.to_path_buf()
+}
+
+/// `\\?\C:\Movies\...` for `C:\Movies\...` and `\\?\UNC\nas\share\...` for `\\nas\share\...`.
+///
+/// Windows takes verbatim paths as they are, so `/` becomes `\` and `.` and `..` are resolved
+/// here. Relative paths can't be verbatim, `None` for those.
+pub fn verbatim(path: &str) -> Option<String> {
+    if path.starts_with(VERBATIM_PREFIX) {
+        return Some(path.to_string());
+    }
+    let path = path.replace('/', "\\");
+    let (prefix, rest) = if let Some(share) = path.strip_prefix(r"\\") {
+        (format!(r"{VERBATIM_PREFIX}UNC\"), share.to_string())
+    } else {
+        let mut chars = path.chars();
+        let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+        if chars.next() != Some(':') || chars.next() != Some('\\') {
+            return None;
+        }
+        (
+            format!("{VERBATIM_PREFIX}{drive}:\\"),
+            path[3..].to_string(),
+        )
+    };
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in rest.split('\\') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    Some(format!("{prefix}{}", components.join("\\")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn long_episode_path(root: &str) -> String {
+        let show = "The Marvelous Adventures of an Extraordinarily Long Named Show (2019)";
+        let episode =
+            "The One Where The Episode Title Is Also Quite Unreasonably Long For Any File System";
+        format!(r"{root}\TV Shows\{show}\Season 01\{show} - S01E01 - {episode}.mkv")
+    }
+
+    #[test]
+    fn makes_long_windows_paths_verbatim() {
+        let path = long_episode_path(r"C:\Users\me\..\me\.\Videos");
+        assert!(path.len() > 260);
+        let local = verbatim(&path).unwrap();
+        assert!(local.starts_with(r"\\?\C:\Users\me\Videos\TV Shows\The Marvelous"));
+        assert!(!local.contains(r"\.\") && !local.contains(".."));
+
+        let share = verbatim(&long_episode_path("//nas/media")).unwrap();
+        assert!(share.starts_with(r"\\?\UNC\nas\media\TV Shows\"));
+        assert_eq!(verbatim(&share).as_deref(), Some(share.as_str()));
+        assert_eq!(verbatim(r"TV Shows\Show"), None);
+    }
+
+    #[test]
+    fn leaves_short_paths_alone() {
+        let path = Path::new(r"C:\Movies\Alien (1979)\Alien (1979).mkv");
+        assert_eq!(extend(path), path);
+    }
+}
@@ -0,0 +1,60 @@
+//! The read cache makemkvcon gets for a rip, `--cache=<MB>`. A big cache rides out slow
+//! spots of the disc but a machine with little memory swaps under it, the cache is sized
+//! from the memory of this machine unless the library profile of the rip sets one.
+use crate::services::ripper_backend::RipperKind;
+use std::sync::OnceLock;
+use sysinfo::System;
+
+/// What makemkvcon got before the cache was sized, kept when the memory isn't known
+const DEFAULT_CACHE_MB: u32 = 1024;
+const MIN_CACHE_MB: u32 = 128;
+const MAX_CACHE_MB: u32 = 2048;
+/// The share of the memory the cache may take
+const MEMORY_SHARE: u64 = 16;
+
+/// A sixteenth of the memory in steps of `MIN_CACHE_MB`, 1024 MB on a 16 GB machine.
+fn cache_for_memory(total_bytes: u64) -> u32 {
+    if total_bytes == 0 {
+        return DEFAULT_CACHE_MB;
+    }
+    let mb = total_bytes / MEMORY_SHARE / (1024 * 1024);
+    let mb = mb - mb % u64::from(MIN_CACHE_MB);
+    mb.clamp(u64::from(MIN_CACHE_MB), u64::from(MAX_CACHE_MB)) as u32
+}
+
+/// The cache for this machine, the memory is read once.
+fn local_cache_mb() -> u32 {
+    static CACHE_MB: OnceLock<u32> = OnceLock::new();
+    *CACHE_MB.get_or_init(|| {
+        let mut system = System::new();
+        system.refresh_memory();
+        cache_for_memory(system.total_memory())
+    })
+}
+
+/// The cache for a rip, the profile's when it sets one. The memory of a remote host isn't
+/// known here, it keeps `DEFAULT_CACHE_MB`.
+pub fn cache_mb(kind: &RipperKind, profile_cache_mb: Option<u32>) -> u32 {
+    match (profile_cache_mb, kind) {
+        (Some(mb), _) => mb,
+        (None, RipperKind::Local) => local_cache_mb(),
+        (None, RipperKind::Ssh) => DEFAULT_CACHE_MB,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_the_cache_from_memory() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        assert_eq!(cache_for_memory(2 * GB), 128);
+        assert_eq!(cache_for_memory(6 * GB), 384);
+        assert_eq!(cache_for_memory(16 * GB), 1024);
+        assert_eq!(cache_for_memory(128 * GB), 2048);
+        assert_eq!(cache_for_memory(0), DEFAULT_CACHE_MB);
+        assert_eq!(cache_mb(&RipperKind::Local, Some(512)), 512);
+        assert_eq!(cache_mb(&RipperKind::Ssh, None), DEFAULT_CACHE_MB);
+    }
+}
@@ -0,0 +1,124 @@
+//! MakeMKV ships the keys for new discs with its releases, an old makemkvcon still starts but
+//! fails on discs pressed after its release. The version is checked once at startup, an
+//! outdated makemkvcon gets a warning and discs are not ripped on their own until it is
+//! updated, manual rips are still allowed.
+use crate::services::semantic_version::SemanticVersion;
+use crate::services::{notifications, ripper_backend};
+use crate::state::locks::MutexExt;
+use crate::state::AppState;
+use crate::templates::toast::Toast;
+use log::{debug, warn};
+use std::sync::Mutex;
+use tauri::{App, AppHandle, Manager};
+
+/// The oldest MakeMKV known to read the discs sold today, raised by hand with new releases
+pub const MIN_VERSION: &str = "1.17.7";
+
+#[derive(Clone, Debug)]
+pub enum MakemkvCheck {
+    Supported(SemanticVersion),
+    Outdated(SemanticVersion),
+    /// makemkvcon didn't start or didn't say its version
+    Unknown(String),
+}
+
+impl MakemkvCheck {
+    pub fn new(version: Result<SemanticVersion, String>) -> Self {
+        let min_version = SemanticVersion::parse(MIN_VERSION).expect("invalid MIN_VERSION");
+        match version {
+            Ok(version) if version < min_version => MakemkvCheck::Outdated(version),
+            Ok(version) => MakemkvCheck::Supported(version),
+            Err(message) => MakemkvCheck::Unknown(message),
+        }
+    }
+
+    /// Outdated is the only state that holds auto-rip back, a makemkvcon whose version is
+    /// unknown may still rip
+    pub fn blocks_auto_rip(&self) -> bool {
+        matches!(self, MakemkvCheck::Outdated(_))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            MakemkvCheck::Supported(version) => format!("MakeMKV {version}"),
+            MakemkvCheck::Outdated(version) => {
+                format!("MakeMKV {version} is older than {MIN_VERSION}, update it to rip new discs")
+            }
+            MakemkvCheck::Unknown(message) => format!("MakeMKV version unknown: {message}"),
+        }
+    }
+}
+
+/// The last check, `None` until the startup check finished.
+#[derive(Default)]
+pub struct MakemkvCheckState(Mutex<Option<MakemkvCheck>>);
+
+impl MakemkvCheckState {
+    pub fn get(&self) -> Option<MakemkvCheck> {
+        self.0.lock_or_recover().clone()
+    }
+
+    fn set(&self, check: MakemkvCheck) {
+        *self.0.lock_or_recover() = Some(check);
+    }
+}
+
+/// Asks the makemkvcon the ripper settings point at for its version.
+pub async fn probe(app_handle: &AppHandle) -> Result<SemanticVersion, String> {
+    let backend = app_handle.state::<AppState>().ripper_options().backend()?;
+    let output = backend
+        .command(app_handle, &["--version"])?
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {e}", backend.name()))?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    ripper_backend::parse_version(&text)
+        .ok_or_else(|| format!("{} didn't report a MakeMKV version", backend.name()))
+}
+
+pub fn spawn_makemkv_check(app: &mut App) {
+    app.manage(MakemkvCheckState::default());
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let check = MakemkvCheck::new(probe(&app_handle).await);
+        debug!("{}", check.label());
+        if let MakemkvCheck::Outdated(_) = check {
+            warn!("{}", check.label());
+            notifications::emit_toast(
+                &app_handle,
+                Toast::warning("MakeMKV is outdated", check.label())
+                    .with_action("Diagnostics", "/drive_health"),
+            );
+        }
+        app_handle.state::<MakemkvCheckState>().set(check);
+    });
+}
+
+/// The last check, `None` while it runs.
+pub fn current(app_handle: &AppHandle) -> Option<MakemkvCheck> {
+    app_handle
+        .try_state::<MakemkvCheckState>()
+        .and_then(|state| state.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gates_auto_rip_on_outdated_versions() {
+        let check = |version: &str| MakemkvCheck::new(Ok(SemanticVersion::parse(version).unwrap()));
+        assert!(check("1.17.6").blocks_auto_rip());
+        assert!(!check(MIN_VERSION).blocks_auto_rip());
+        assert!(!check("1.18.1").blocks_auto_rip());
+        assert!(!MakemkvCheck::new(Err("not found".to_string())).blocks_auto_rip());
+        assert_eq!(
+            check("1.16.0").label(),
+            "MakeMKV 1.16.0 is older than 1.17.7, update it to rip new discs"
+        );
+    }
+}
@@ -1,27 +1,32 @@
 use crate::models::mkv::PRGV;
-use crate::models::optical_disk_info::DiskId;
-use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::models::optical_disk_info::{DiskId, OpticalDiskInfo};
+use crate::models::title_info::TitleInfo;
 use crate::models::{mkv, title_info};
 use crate::progress_tracker::{self, ProgressOptions};
-use crate::services::makemkvcon_parser;
+use crate::services::ripper_backend::{RipperBackend, RipperKind};
+use crate::services::{makemkv_cache, makemkvcon_parser};
 use crate::state::job_state::emit_progress;
 use crate::state::job_state::Job;
+use crate::state::locks::{MutexExt, RwLockExt};
 use crate::state::title_video::TitleVideo;
 use crate::state::AppState;
 use crate::templates;
 use log::debug;
-use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tauri::async_runtime::Receiver;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_shell::process::CommandEvent;
-use tauri_plugin_shell::ShellExt;
 
-#[cfg(all(target_os = "windows", target_pointer_width = "64"))]
-const MAKEMKVCON: &str = "makemkvcon64";
+/// makemkvcon's own default port for the streaming server
+pub const STREAM_PORT: u16 = 51000;
+/// A disc makemkvcon can't open stops the server right away, after this it counts as started
+const STREAM_STARTUP: Duration = Duration::from_secs(5);
 
-#[cfg(not(all(target_os = "windows", target_pointer_width = "64")))]
-const MAKEMKVCON: &str = "makemkvcon";
+/// How much of a title `preview_title` rips
+pub const PREVIEW_SECONDS: u64 = 90;
 
 pub struct RunResults {
     pub title_infos: Vec<title_info::TitleInfo>,
@@ -45,6 +50,14 @@ impl RunResults {
     fn err_summary(&self) -> Option<&mkv::MSG> {
         self.messages.iter().find(|message| message.code == 5003)
     }
+
+    /// MSG:2003 "Error '...' occurred while reading '...' at offset '...'"
+    pub fn read_error_count(&self) -> u64 {
+        self.messages
+            .iter()
+            .filter(|message| message.code == 2003)
+            .count() as u64
+    }
 }
 // makemkvcon [options] Command Parameters
 // https://www.makemkv.com/developers/usage.txt
@@ -248,6 +261,9 @@ fn convert_to_run_result(
             mkv::MkvData::TINFO(tinfo) => {
                 set_title_info_field(&tinfo, run_results);
             }
+            mkv::MkvData::SINFO(sinfo) => {
+                set_title_stream_field(&sinfo, run_results);
+            }
             mkv::MkvData::DRV(drv) => {
                 run_results.drives.push(drv);
             }
@@ -259,7 +275,7 @@ fn convert_to_run_result(
             mkv::MkvData::PRGT(prgt) => {
                 create_tracker(tracker);
                 update_job_progress(job, tracker);
-                job.write().unwrap().subtitle = Some(prgt.name.clone());
+                job.write_or_recover().subtitle = Some(prgt.name.clone());
                 emit_progress(app_handle, job, true);
             }
             mkv::MkvData::PRGC(_prgc) => {
@@ -270,7 +286,7 @@ fn convert_to_run_result(
             mkv::MkvData::MSG(msg) => {
                 run_results.messages.push(msg.clone());
                 update_job_progress(job, tracker);
-                job.write().unwrap().message = Some(msg.message.clone());
+                job.write_or_recover().message = Some(msg.message.clone());
                 emit_progress(app_handle, job, true);
             }
             _ => {}
@@ -295,7 +311,7 @@ fn set_title_info_field(tinfo: &mkv::TINFO, run_results: &mut RunResults) {
     let title_info: &mut title_info::TitleInfo = match run_results
         .title_infos
         .iter_mut()
-        .find(|t| t.id == tinfo.id)
+        .find(|t| t.id == tinfo.id.into())
     {
         Some(title) => title,
         None => {
@@ -308,6 +324,35 @@ fn set_title_info_field(tinfo: &mkv::TINFO, run_results: &mut RunResults) {
     title_info.set_field(&tinfo.type_code, tinfo.value.clone())
 }
 
+// SINFO:title_id,stream_id,attribute_id,code,value
+// The parser keeps "code,value" together in `value`, `code` holds the attribute id.
+// Only the first video size (attribute 19) is kept, which is the main video stream.
+fn set_title_stream_field(sinfo: &mkv::SINFO, run_results: &mut RunResults) {
+    if sinfo.code != "19" {
+        return;
+    }
+    let value = match sinfo.value.split_once(',') {
+        Some((_, value)) => value,
+        None => sinfo.value.as_str(),
+    };
+    let title_info: &mut title_info::TitleInfo = match run_results
+        .title_infos
+        .iter_mut()
+        .find(|t| t.id == sinfo.id.into())
+    {
+        Some(title) => title,
+        None => {
+            run_results
+                .title_infos
+                .push(title_info::TitleInfo::new(sinfo.id));
+            run_results.title_infos.last_mut().unwrap()
+        }
+    };
+    if title_info.video_size.is_none() {
+        title_info.set_field("video_size", value.to_string());
+    }
+}
+
 fn update_tracker(tracker: &mut Option<progress_tracker::Base>, prgv: PRGV) {
     if tracker.is_none() {
         let options = ProgressOptions {
@@ -329,32 +374,18 @@ fn update_tracker(tracker: &mut Option<progress_tracker::Base>, prgv: PRGV) {
     }
 }
 
-fn spawn<I: IntoIterator<Item = S> + std::fmt::Debug + std::marker::Copy, S: AsRef<OsStr>>(
+fn spawn(
     app_handle: &AppHandle,
     job: &Arc<RwLock<Job>>,
-    args: I,
-) -> Receiver<CommandEvent> {
-    let mut sidecar_command = app_handle
-        .shell()
-        .sidecar(MAKEMKVCON)
-        .expect("failed to get makemkvcon");
-
-    // On Linux, set LD_LIBRARY_PATH to include the resource directory
-    // where the shared libraries are located
-    #[cfg(target_os = "linux")]
-    {
-        if let Ok(resource_path) = app_handle.path().resource_dir() {
-            sidecar_command = sidecar_command.env("LD_LIBRARY_PATH", resource_path);
-        }
-    }
-
-    let (receiver, child) = sidecar_command
-        .args(args)
+    backend: &dyn RipperBackend,
+    args: &[&str],
+) -> Result<Receiver<CommandEvent>, String> {
+    let (receiver, child) = backend
+        .command(app_handle, args)?
         .spawn()
-        .expect("Failed to spawn sidecar for rip_title");
+        .map_err(|e| format!("Failed to start {}: {e}", backend.name()))?;
     let disk_id = job
-        .read()
-        .expect("failed to lock job for read")
+        .read_or_recover()
         .disk
         .as_ref()
         .expect("There should of been a disk")
@@ -362,14 +393,12 @@ fn spawn<I: IntoIterator<Item = S> + std::fmt::Debug + std::marker::Copy, S: AsR
     let state = app_handle.state::<AppState>();
     match state.find_optical_disk_by_id(&disk_id) {
         Some(disk) => {
-            disk.write()
-                .expect("Failed to acquire lock on disk from disk_arc in spawn command")
-                .set_pid(Some(child.pid()));
+            disk.write_or_recover().set_pid(Some(child.pid()));
         }
         None => debug!("failed to assign the sidecar to disk {disk_id}"),
     }
-    debug!("Executing command: makemkvcon {args:?}");
-    receiver
+    debug!("Executing command: {} {args:?}", backend.name());
+    Ok(receiver)
 }
 
 fn disk_index_args(app_handle: &AppHandle, disk_id: &DiskId) -> String {
@@ -377,7 +406,7 @@ fn disk_index_args(app_handle: &AppHandle, disk_id: &DiskId) -> String {
 
     match state.find_optical_disk_by_id(disk_id) {
         Some(disk) => {
-            let locked_disk = disk.read().expect("Failed to grab disk");
+            let locked_disk = disk.read_or_recover();
             format!("disc:{}", locked_disk.index)
         }
         None => "".to_string(),
@@ -424,52 +453,228 @@ pub async fn rip_title(
     title_video: &Arc<RwLock<TitleVideo>>,
 ) -> Result<RunResults, String> {
     let disk = job
-        .read()
-        .unwrap()
+        .read_or_recover()
         .disk
         .clone()
         .expect("There should of been a disk");
-    let args = disk_args(&disk);
-    let tmp_dir = title_video
-        .read()
-        .unwrap()
-        .create_video_dir(&app_handle.state::<AppState>());
+    let state = app_handle.state::<AppState>();
+    let ripper_options = state.ripper_options();
+    let backend = ripper_options.backend()?;
+    let args = backend.disc_arg(&disk);
+    let tmp_dir = title_video.read_or_recover().create_video_dir(&state)?;
+    let cache_mb = makemkv_cache::cache_mb(
+        &ripper_options.kind,
+        title_video.read_or_recover().library(&state).rip_cache_mb,
+    );
+    let cache_arg = format!("--cache={cache_mb}");
+    let output_dir = backend.output_dir(&tmp_dir)?;
+    // A movie split in two rips both titles, they are joined afterwards
+    let title_ids: Vec<String> = {
+        let title_video = title_video.read_or_recover();
+        title_video
+            .title
+            .iter()
+            .chain(&title_video.joined_titles)
+            .map(|title| title.id.to_string())
+            .collect()
+    };
+
+    let mut results: Option<RunResults> = None;
+    for (index, title_id) in title_ids.iter().enumerate() {
+        job.write_or_recover().joined_part =
+            (title_ids.len() > 1).then_some((index, title_ids.len()));
+        let args = [
+            "mkv",
+            &args,
+            title_id,
+            &output_dir,
+            "--progress=-same",
+            "--robot",
+            "--minlength=45",
+            &cache_arg,
+            "--noscan",
+        ];
+
+        let receiver = spawn(app_handle, job, &*backend, &args)?;
+        templates::disks::emit_disk_change(app_handle);
+
+        let response = run(job, receiver, app_handle.clone()).await;
+        let run_results = match response {
+            Ok(run_results) => match run_results.err_summary() {
+                Some(err_summary) => Err(err_summary.message.clone()),
+                None => Ok(run_results),
+            },
+            Err(e) => Err(e),
+        };
+        let run_results = run_results.inspect_err(|_| job.write_or_recover().joined_part = None)?;
+        match results.as_mut() {
+            Some(results) => results.messages.extend(run_results.messages),
+            None => results = Some(run_results),
+        }
+    }
+    job.write_or_recover().joined_part = None;
+    results.ok_or_else(|| "Title information is missing for this video".to_string())
+}
+
+/// Where VLC or a browser finds the titles of a streamed disc.
+pub fn stream_url(port: u16) -> String {
+    format!("http://127.0.0.1:{port}/")
+}
+
+/// Starts the makemkvcon streaming server for a disc and returns its URL, so the disc can be
+/// previewed in a player before it is ripped. The server runs until it is stopped with
+/// `kill_process` on the disc, ejecting the disc stops it as well.
+pub async fn stream(app_handle: &AppHandle, disk: &OpticalDiskInfo) -> Result<String, String> {
+    let ripper_options = app_handle.state::<AppState>().ripper_options();
+    if ripper_options.kind != RipperKind::Local {
+        return Err("Streaming only works with the makemkvcon on this machine".to_string());
+    }
+    let backend = ripper_options.backend()?;
+    let disc_arg = backend.disc_arg(disk);
+    let port_arg = format!("--bindport={STREAM_PORT}");
+    let args = [
+        "stream",
+        "-r",
+        "--upnp=1",
+        "--cache=128",
+        "--bindip=127.0.0.1",
+        &port_arg,
+        &disc_arg,
+    ];
+    let (mut receiver, child) = backend
+        .command(app_handle, &args)?
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {e}", backend.name()))?;
+    let pid = child.pid();
+    debug!("Executing command: {} {args:?}", backend.name());
+    set_disk_pid(app_handle, &disk.id, None, Some(pid));
+
+    let stopped = tokio::time::timeout(STREAM_STARTUP, async {
+        loop {
+            match receiver.recv().await {
+                Some(CommandEvent::Stdout(line_bytes)) => {
+                    debug!("Stream: {}", String::from_utf8_lossy(&line_bytes));
+                }
+                Some(CommandEvent::Terminated(payload)) => {
+                    return format!("makemkvcon stopped streaming: {payload:?}");
+                }
+                Some(CommandEvent::Error(error)) => return format!("makemkvcon error: {error}"),
+                Some(other) => debug!("Stream: {other:?}"),
+                None => return "makemkvcon stopped streaming".to_string(),
+            }
+        }
+    })
+    .await;
+    if let Ok(message) = stopped {
+        set_disk_pid(app_handle, &disk.id, Some(pid), None);
+        return Err(message);
+    }
+
+    let app_handle = app_handle.clone();
+    let disk_id = disk.id;
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            debug!("Stream: {event:?}");
+        }
+        set_disk_pid(&app_handle, &disk_id, Some(pid), None);
+    });
+    Ok(stream_url(STREAM_PORT))
+}
+
+/// Share of a title's progress that covers its first `PREVIEW_SECONDS`, the whole title when
+/// makemkvcon didn't report a duration.
+fn preview_share(title: &TitleInfo) -> f64 {
+    match title.duration_seconds() {
+        Some(seconds) if seconds > PREVIEW_SECONDS => PREVIEW_SECONDS as f64 / seconds as f64,
+        _ => 1.0,
+    }
+}
+
+/// Rips the first `PREVIEW_SECONDS` of a title into `dir` and returns the MKV, to check which
+/// title is the right episode before assigning it. makemkvcon has no way to rip part of a
+/// title, it is stopped once its progress passed the preview and the cut off MKV still plays.
+pub async fn preview_title(
+    app_handle: &AppHandle,
+    disk: &OpticalDiskInfo,
+    title: &TitleInfo,
+    dir: &Path,
+) -> Result<PathBuf, String> {
+    let backend = app_handle.state::<AppState>().ripper_options().backend()?;
+    if dir.exists() {
+        fs::remove_dir_all(dir).map_err(|e| format!("Failed to clear {}: {e}", dir.display()))?;
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let disc_arg = backend.disc_arg(disk);
+    let title_arg = title.id.to_string();
+    let output_dir = backend.output_dir(dir)?;
     let args = [
         "mkv",
-        &args,
-        &title_video.read().unwrap().title.as_ref().unwrap().id.to_string(),
-        &tmp_dir.to_string_lossy(),
+        &disc_arg,
+        &title_arg,
+        &output_dir,
         "--progress=-same",
         "--robot",
-        "--minlength=45",
-        "--cache=1024",
+        "--cache=128",
         "--noscan",
     ];
+    let (mut receiver, child) = backend
+        .command(app_handle, &args)?
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {e}", backend.name()))?;
+    let pid = child.pid();
+    debug!("Executing command: {} {args:?}", backend.name());
+    set_disk_pid(app_handle, &disk.id, None, Some(pid));
 
-    let receiver = spawn(app_handle, job, args);
-    templates::disks::emit_disk_change(app_handle);
-
-    let response = run(job, receiver, app_handle.clone()).await;
-    match response {
-        Ok(run_results) => {
-            if let Some(err_summary) = run_results.err_summary() {
-                Err(err_summary.message.clone())
-            } else {
-                Ok(run_results)
+    let share = preview_share(title);
+    let mut child = Some(child);
+    while let Some(event) = receiver.recv().await {
+        match event {
+            CommandEvent::Stdout(line_bytes) => {
+                let line = String::from_utf8_lossy(&line_bytes);
+                let passed =
+                    makemkvcon_parser::parse_mkv_string(&line)
+                        .iter()
+                        .any(|data| match data {
+                            mkv::MkvData::PRGV(prgv) if prgv.pmax > 0 => {
+                                prgv.total as f64 / prgv.pmax as f64 >= share
+                            }
+                            _ => false,
+                        });
+                if passed {
+                    if let Some(child) = child.take() {
+                        debug!("Preview of title {} is long enough", title.id);
+                        child
+                            .kill()
+                            .map_err(|e| format!("Failed to stop {}: {e}", backend.name()))?;
+                    }
+                }
             }
+            CommandEvent::Terminated(payload) => {
+                debug!("Terminated: {payload:?}");
+                break;
+            }
+            other => debug!("Preview: {other:?}"),
         }
-        Err(e) => Err(e),
     }
-}
+    set_disk_pid(app_handle, &disk.id, Some(pid), None);
 
-#[cfg(target_os = "windows")]
-fn disk_args(disk: &OpticalDiskInfo) -> String {
-    format!("dev:{}", disk.dev)
+    fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .find(|path| path.extension().is_some_and(|extension| extension == "mkv"))
+        .ok_or_else(|| format!("makemkvcon didn't rip a preview of title {}", title.id))
 }
 
-#[cfg(not(target_os = "windows"))]
-fn disk_args(disk: &OpticalDiskInfo) -> String {
-    format!("file:{}", disk.mount_point.to_string_lossy())
+/// Sets the process of the disc, only when it still is `expected` if that is given.
+fn set_disk_pid(app_handle: &AppHandle, disk_id: &DiskId, expected: Option<u32>, pid: Option<u32>) {
+    let state = app_handle.state::<AppState>();
+    let Some(disk) = state.find_optical_disk_by_id(disk_id) else {
+        return;
+    };
+    let disk = disk.read_or_recover();
+    if expected.is_none() || *disk.pid.lock_or_recover() == expected {
+        disk.set_pid(pid);
+    }
 }
 
 pub async fn title_info(
@@ -477,18 +682,19 @@ pub async fn title_info(
     job: &Arc<RwLock<Job>>,
 ) -> Result<RunResults, String> {
     let disk_id = job
-        .read()
-        .expect("failed to lock job for read")
+        .read_or_recover()
         .disk
         .as_ref()
         .expect("There should of been a disk")
         .id;
+    let backend = app_handle.state::<AppState>().ripper_options().backend()?;
     let args = disk_index_args(app_handle, &disk_id);
     let receiver = spawn(
         app_handle,
         job,
-        ["-r", "--minlength=45", "--cache=128", "info", &args],
-    );
+        &*backend,
+        &["-r", "--minlength=45", "--cache=128", "info", &args],
+    )?;
     templates::disks::emit_disk_change(app_handle);
     let app_handle_clone = app_handle.clone();
 
@@ -497,7 +703,7 @@ pub async fn title_info(
 
 fn update_job_progress(job: &Arc<RwLock<Job>>, tracker: &Option<progress_tracker::Base>) {
     if let Some(ref tracker) = tracker {
-        let mut job_guard = job.write().expect("failed to lock job for write");
+        let mut job_guard = job.write_or_recover();
         job_guard.update_progress(tracker);
     }
 }
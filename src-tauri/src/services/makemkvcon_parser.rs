@@ -61,11 +61,11 @@ fn define_type<I: IntoIterator<Item = String>>(type_str: &str, fields: I) -> Mkv
             MkvData::DRV(DRV {
                 index: cast_to_i32(iter.next().unwrap()),
                 visible: cast_to_i32(iter.next().unwrap()),
-                unknown: cast_to_i32(iter.next().unwrap()),
                 enabled: cast_to_i32(iter.next().unwrap()),
-                flags: iter.next().unwrap(),
+                flags: cast_to_i32(iter.next().unwrap()),
                 drive_name: iter.next().unwrap(),
                 disc_name: iter.next().unwrap(),
+                device_path: iter.next().unwrap_or_default(),
             })
         }
         "PRGV" => {
@@ -153,3 +153,23 @@ pub fn parse_mkv_string(stdout_str: &str) -> Vec<MkvData> {
 
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_drive_name_before_disc_name() {
+        let line =
+            r#"DRV:0,2,999,1,"BD-RE HL-DT-ST BD-RE  WH16NS40 1.02 SIK9","THE_MARTIAN","/dev/sr0""#;
+        match parse_mkv_string(line).into_iter().next() {
+            Some(MkvData::DRV(drv)) => {
+                assert_eq!(drv.index, 0);
+                assert_eq!(drv.drive_name, "BD-RE HL-DT-ST BD-RE  WH16NS40 1.02 SIK9");
+                assert_eq!(drv.disc_name, "THE_MARTIAN");
+                assert_eq!(drv.device_path, "/dev/sr0");
+            }
+            _ => panic!("expected a DRV line"),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Counters for the `/metrics` endpoint of the remote API, in the Prometheus text format.
+//!
+//! The counters start at 0 with every start of the app, Prometheus handles the resets. The
+//! gauges are read from the app state when the endpoint is scraped.
+use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::job_state::JobStatus;
+use crate::state::uploaded_state::UploadedState;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::{AppHandle, Manager};
+
+static DISCS_RIPPED: AtomicU64 = AtomicU64::new(0);
+static DISC_RIPS_FAILED: AtomicU64 = AtomicU64::new(0);
+static UPLOADS: AtomicU64 = AtomicU64::new(0);
+static UPLOADS_FAILED: AtomicU64 = AtomicU64::new(0);
+static BYTES_UPLOADED: AtomicU64 = AtomicU64::new(0);
+static DRIVE_ERRORS: AtomicU64 = AtomicU64::new(0);
+static TMDB_REQUESTS: AtomicU64 = AtomicU64::new(0);
+static TMDB_REQUEST_ERRORS: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_disc_rip(success: bool) {
+    let counter = if success {
+        &DISCS_RIPPED
+    } else {
+        &DISC_RIPS_FAILED
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_upload(bytes: Option<u64>) {
+    match bytes {
+        Some(bytes) => {
+            UPLOADS.fetch_add(1, Ordering::Relaxed);
+            BYTES_UPLOADED.fetch_add(bytes, Ordering::Relaxed);
+        }
+        None => {
+            UPLOADS_FAILED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Read errors makemkvcon reported or rips the drive couldn't finish
+pub fn record_drive_errors(count: u64) {
+    DRIVE_ERRORS.fetch_add(count, Ordering::Relaxed);
+}
+
+pub fn record_tmdb_request(success: bool) {
+    TMDB_REQUESTS.fetch_add(1, Ordering::Relaxed);
+    if !success {
+        TMDB_REQUEST_ERRORS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Values that come from the app state instead of a counter
+#[derive(Debug, Default, PartialEq)]
+pub struct Gauges {
+    pub pending_jobs: usize,
+    pub processing_jobs: usize,
+    pub queued_uploads: usize,
+}
+
+impl Gauges {
+    pub fn of(app_handle: &AppHandle) -> Self {
+        let jobs = app_handle
+            .state::<BackgroundProcessState>()
+            .clone_all_jobs();
+        let count = |status: JobStatus| jobs.iter().filter(|job| job.status == status).count();
+        Gauges {
+            pending_jobs: count(JobStatus::Pending),
+            processing_jobs: count(JobStatus::Processing),
+            queued_uploads: app_handle.state::<UploadedState>().get_pending().len(),
+        }
+    }
+}
+
+pub fn render(gauges: &Gauges) -> String {
+    let mut text = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+        let _ = write!(
+            text,
+            "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"
+        );
+    };
+    let counter = |counter: &AtomicU64| counter.load(Ordering::Relaxed).to_string();
+
+    metric(
+        "reelix_discs_ripped_total",
+        "counter",
+        "Discs ripped without errors.",
+        counter(&DISCS_RIPPED),
+    );
+    metric(
+        "reelix_disc_rips_failed_total",
+        "counter",
+        "Disc rips with at least one failed title.",
+        counter(&DISC_RIPS_FAILED),
+    );
+    metric(
+        "reelix_uploads_total",
+        "counter",
+        "Videos uploaded to the FTP server.",
+        counter(&UPLOADS),
+    );
+    metric(
+        "reelix_uploads_failed_total",
+        "counter",
+        "Uploads that failed.",
+        counter(&UPLOADS_FAILED),
+    );
+    metric(
+        "reelix_uploaded_bytes_total",
+        "counter",
+        "Bytes uploaded to the FTP server.",
+        counter(&BYTES_UPLOADED),
+    );
+    metric(
+        "reelix_drive_errors_total",
+        "counter",
+        "Read errors and failed rips of the optical drives.",
+        counter(&DRIVE_ERRORS),
+    );
+    metric(
+        "reelix_tmdb_requests_total",
+        "counter",
+        "Requests made to The Movie Database.",
+        counter(&TMDB_REQUESTS),
+    );
+    metric(
+        "reelix_tmdb_request_errors_total",
+        "counter",
+        "Requests to The Movie Database that failed.",
+        counter(&TMDB_REQUEST_ERRORS),
+    );
+    metric(
+        "reelix_pending_jobs",
+        "gauge",
+        "Jobs waiting to start.",
+        gauges.pending_jobs.to_string(),
+    );
+    metric(
+        "reelix_active_jobs",
+        "gauge",
+        "Jobs running right now.",
+        gauges.processing_jobs.to_string(),
+    );
+    metric(
+        "reelix_queued_uploads",
+        "gauge",
+        "Videos waiting to be uploaded.",
+        gauges.queued_uploads.to_string(),
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_the_prometheus_text_format() {
+        let gauges = Gauges {
+            pending_jobs: 1,
+            processing_jobs: 2,
+            queued_uploads: 3,
+        };
+        let text = render(&gauges);
+
+        assert!(text.contains(
+            "# HELP reelix_active_jobs Jobs running right now.\n\
+             # TYPE reelix_active_jobs gauge\n\
+             reelix_active_jobs 2\n"
+        ));
+        assert!(text.contains("# TYPE reelix_uploaded_bytes_total counter\n"));
+        assert!(text.contains("reelix_queued_uploads 3\n"));
+    }
+
+    #[test]
+    fn counts_uploaded_bytes() {
+        let before = BYTES_UPLOADED.load(Ordering::Relaxed);
+        record_upload(Some(1024));
+        record_upload(None);
+        assert!(BYTES_UPLOADED.load(Ordering::Relaxed) >= before + 1024);
+        assert!(UPLOADS_FAILED.load(Ordering::Relaxed) >= 1);
+    }
+}
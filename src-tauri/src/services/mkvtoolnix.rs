@@ -0,0 +1,112 @@
+use crate::services::track_languages::{self, Track};
+use log::debug;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+
+// MKVToolNix is not bundled, mkvextract, mkvmerge and mkvpropedit have to be on the PATH.
+const MKVEXTRACT: &str = "mkvextract";
+const MKVMERGE: &str = "mkvmerge";
+const MKVPROPEDIT: &str = "mkvpropedit";
+
+async fn execute(app_handle: &AppHandle, program: &str, args: &[&str]) -> Result<(), String> {
+    debug!("Executing command: {program} {args:?}");
+    let output = app_handle
+        .shell()
+        .command(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {program}, is MKVToolNix installed? {e}"))?;
+
+    // mkvtoolnix exits with 1 for warnings, only 2 is an actual failure
+    match output.status.code() {
+        Some(0) | Some(1) => Ok(()),
+        code => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let last_line = stdout.lines().last().unwrap_or_default();
+            Err(format!("{program} exited with {code:?}: {last_line}"))
+        }
+    }
+}
+
+/// Write the chapters of `path` to `chapters_file` in the OGM "simple" format.
+pub async fn extract_chapters(
+    app_handle: &AppHandle,
+    path: &Path,
+    chapters_file: &Path,
+) -> Result<(), String> {
+    let input = path.to_string_lossy();
+    let output = chapters_file.to_string_lossy();
+    execute(
+        app_handle,
+        MKVEXTRACT,
+        &[&input, "chapters", "--simple", &output],
+    )
+    .await
+}
+
+/// Replace the chapters of `path` in place, no remux needed.
+pub async fn replace_chapters(
+    app_handle: &AppHandle,
+    path: &Path,
+    chapters_file: &Path,
+) -> Result<(), String> {
+    let input = path.to_string_lossy();
+    let chapters = chapters_file.to_string_lossy();
+    execute(app_handle, MKVPROPEDIT, &[&input, "--chapters", &chapters]).await
+}
+
+/// Write `parts` one after the other into `output`, the way `mkvmerge -o output a + b` does.
+/// The parts need the same tracks, which titles of one movie on one disc have.
+pub async fn append(
+    app_handle: &AppHandle,
+    parts: &[PathBuf],
+    output: &Path,
+) -> Result<(), String> {
+    let output = output.to_string_lossy();
+    let mut args = vec!["-o".to_string(), output.to_string()];
+    for (index, part) in parts.iter().enumerate() {
+        if index > 0 {
+            args.push("+".to_string());
+        }
+        args.push(part.to_string_lossy().to_string());
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    execute(app_handle, MKVMERGE, &args).await
+}
+
+/// The tracks of `path` as `mkvmerge -J` reports them.
+pub async fn identify(app_handle: &AppHandle, path: &Path) -> Result<Vec<Track>, String> {
+    let input = path.to_string_lossy();
+    debug!("Executing command: {MKVMERGE} -J {input}");
+    let output = app_handle
+        .shell()
+        .command(MKVMERGE)
+        .args(["-J", &*input])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {MKVMERGE}, is MKVToolNix installed? {e}"))?;
+    if !output.status.success() {
+        return Err(format!("{MKVMERGE} exited with {:?}", output.status.code()));
+    }
+    track_languages::parse_identification(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Set the default flag of the given `(track number, default)` pairs in place.
+pub async fn set_default_tracks(
+    app_handle: &AppHandle,
+    path: &Path,
+    flags: &[(u64, bool)],
+) -> Result<(), String> {
+    let input = path.to_string_lossy().to_string();
+    let mut args = vec![input];
+    for (number, default) in flags {
+        args.push("--edit".to_string());
+        args.push(format!("track:@{number}"));
+        args.push("--set".to_string());
+        args.push(format!("flag-default={}", u8::from(*default)));
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    execute(app_handle, MKVPROPEDIT, &args).await
+}
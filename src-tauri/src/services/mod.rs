@@ -1,14 +1,62 @@
+pub mod archive;
+pub mod arr;
+pub mod assignment_patterns;
 pub mod auto_complete;
+pub mod blocking;
+pub mod chapters;
 pub mod converter;
+pub mod deep_link;
+pub mod disc_memory;
+pub mod disc_prescan;
 pub mod disk_manager;
+pub mod drive_benchmark;
 pub mod drive_info;
+pub mod ffmpeg;
+pub mod file_move;
 pub mod ftp_uploader;
 pub mod ftp_validator;
 pub mod github_api;
+pub mod job_notes;
+pub mod job_snapshots;
+pub mod library;
+pub mod library_audit;
+pub mod library_dir;
+pub mod locale;
+pub mod long_path;
+pub mod makemkv_cache;
+pub mod makemkv_check;
 pub mod makemkvcon;
 pub mod makemkvcon_parser;
+pub mod metrics;
+pub mod mkvtoolnix;
+pub mod naming;
+pub mod network;
+pub mod notifications;
+pub mod ocr;
+pub mod owned_collection;
+pub mod path_template;
 pub mod plex;
+pub mod post_process;
+pub mod preview;
+pub mod push;
+pub mod rematch;
+pub mod remote_api;
+pub mod resource_monitor;
+pub mod rip_failure;
+pub mod rip_plan;
+pub mod ripper_backend;
+pub mod search_suggest;
 pub mod semantic_version;
+#[cfg(test)]
+pub mod test_support;
+pub mod title_analysis;
+pub mod tmdb_cache;
+pub mod track_languages;
+pub mod trakt;
+pub mod tray_menu;
+pub mod ui_state;
+pub mod unix_time;
 pub mod upload_recovery;
+pub mod upload_review;
 pub mod version_checker;
 pub mod zip_directory;
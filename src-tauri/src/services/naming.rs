@@ -0,0 +1,300 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Articles moved to the end of a title when `move_leading_article` is enabled.
+const ARTICLES: [&str; 3] = ["The", "An", "A"];
+
+/// Optional transformations applied to titles before they are used in
+/// folder and file names.
+///
+/// Both options are off by default so the Plex recommended naming
+/// (`The Matrix (1999)`) is kept unless the user opts in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NamingOptions {
+    /// `The Matrix` becomes `Matrix, The`
+    pub move_leading_article: bool,
+    /// `Amélie` becomes `Amelie`, characters without an ASCII equivalent are dropped
+    pub ascii_only: bool,
+    /// `Alien (1979).mkv` becomes `Alien (1979) [Bluray-1080p].mkv`
+    pub append_quality_tag: bool,
+    /// `The Office (2005)/` becomes `The Office (2005) {tmdb-2316}/`, keeps shows with the
+    /// same name and year apart. Only the show folder gets it, Plex reads it from there.
+    pub tmdb_id_tag: bool,
+}
+
+impl NamingOptions {
+    pub fn apply(&self, title: &str) -> String {
+        let mut title = nfc(title.trim());
+        if self.ascii_only {
+            title = transliterate_ascii(&title);
+        }
+        if self.move_leading_article {
+            title = move_leading_article(&title);
+        }
+        title
+    }
+
+    /// Suffix added to filenames when `append_quality_tag` is enabled and the quality is known.
+    pub fn quality_suffix(&self, quality_tag: Option<&str>) -> String {
+        match quality_tag {
+            Some(tag) if self.append_quality_tag && !tag.is_empty() => format!(" [{tag}]"),
+            _ => String::new(),
+        }
+    }
+
+    /// Same format as `MovieResponse::title_year` with the options applied to the title.
+    pub fn title_year(&self, title: &str, year: Option<u32>) -> String {
+        match year {
+            Some(year) => format!("{} ({year})", self.apply(title)),
+            None => self.apply(title),
+        }
+    }
+
+    /// Folder of a TV show, `title_year` with the TMDB id tag when `tmdb_id_tag` is enabled.
+    pub fn show_folder(&self, title: &str, year: Option<u32>, tmdb_id: u32) -> String {
+        let title_year = self.title_year(title, year);
+        if self.tmdb_id_tag {
+            format!("{title_year} {{tmdb-{tmdb_id}}}")
+        } else {
+            title_year
+        }
+    }
+}
+
+/// `é` as one character. macOS hands out names with the accent as a character of its own
+/// (NFD), TMDB and most servers use the composed form (NFC), the two never compare equal.
+/// Generated names are NFC and names read back are made NFC before they are compared.
+pub fn nfc(name: &str) -> String {
+    name.nfc().collect()
+}
+
+/// The TMDB id of a `show_folder` name with the tag, e.g. `The Office (2005) {tmdb-2316}`.
+pub fn parse_tmdb_id_tag(folder: &str) -> Option<u32> {
+    let start = folder.rfind("{tmdb-")? + "{tmdb-".len();
+    let end = start + folder[start..].find('}')?;
+    folder[start..end].parse().ok()
+}
+
+/// Moves a leading English article to the end of the title.
+///
+/// Examples:
+/// - "The Matrix" -> "Matrix, The"
+/// - "A Quiet Place" -> "Quiet Place, A"
+/// - "Theodore Rex" -> "Theodore Rex" (article must be a whole word)
+/// - "The" -> "The" (nothing to move it behind)
+pub fn move_leading_article(title: &str) -> String {
+    for article in ARTICLES {
+        let Some(prefix) = title.get(..article.len()) else {
+            continue;
+        };
+        if !prefix.eq_ignore_ascii_case(article) {
+            continue;
+        }
+        let rest = &title[article.len()..];
+        if !rest.starts_with(' ') {
+            continue;
+        }
+        let rest = rest.trim();
+        if rest.is_empty() {
+            continue;
+        }
+        return format!("{rest}, {prefix}");
+    }
+    title.to_string()
+}
+
+/// Replaces accented latin characters with their closest ASCII equivalent.
+/// Anything else outside of ASCII is removed and the remaining whitespace collapsed.
+pub fn transliterate_ascii(title: &str) -> String {
+    let mut result = String::with_capacity(title.len());
+    for c in title.chars() {
+        if c.is_ascii() {
+            result.push(c);
+        } else if let Some(replacement) = ascii_equivalent(c) {
+            result.push_str(replacement);
+        } else if c.is_whitespace() {
+            result.push(' ');
+        }
+    }
+    result.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn ascii_equivalent(c: char) -> Option<&'static str> {
+    let replacement = match c {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'ð' | 'ď' | 'đ' => "d",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Þ' => "Th",
+        'þ' => "th",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ÿ' | 'Ŷ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        '‘' | '’' | '‚' | '′' => "'",
+        '“' | '”' | '„' | '″' => "\"",
+        '–' | '—' | '‐' | '‑' => "-",
+        '…' => "...",
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_composed() {
+        let decomposed = "Ame\u{301}lie";
+        assert_ne!(decomposed, "Amélie");
+        assert_eq!(nfc(decomposed), "Amélie");
+        assert_eq!(
+            NamingOptions::default().title_year(decomposed, Some(2001)),
+            "Amélie (2001)"
+        );
+    }
+
+    #[test]
+    fn default_options_keep_title() {
+        let options = NamingOptions::default();
+        assert_eq!(options.apply("The Matrix"), "The Matrix");
+        assert_eq!(options.title_year("Amélie", Some(2001)), "Amélie (2001)");
+    }
+
+    #[test]
+    fn moves_leading_articles() {
+        assert_eq!(move_leading_article("The Matrix"), "Matrix, The");
+        assert_eq!(move_leading_article("A Quiet Place"), "Quiet Place, A");
+        assert_eq!(
+            move_leading_article("An American Tail"),
+            "American Tail, An"
+        );
+        assert_eq!(move_leading_article("the thing"), "thing, the");
+    }
+
+    #[test]
+    fn article_must_be_a_whole_word() {
+        assert_eq!(move_leading_article("Theodore Rex"), "Theodore Rex");
+        assert_eq!(move_leading_article("Annie Hall"), "Annie Hall");
+        assert_eq!(move_leading_article("Alien"), "Alien");
+    }
+
+    #[test]
+    fn article_alone_is_unchanged() {
+        assert_eq!(move_leading_article("The"), "The");
+        assert_eq!(move_leading_article("A "), "A ");
+        assert_eq!(move_leading_article(""), "");
+    }
+
+    #[test]
+    fn only_moves_the_first_article() {
+        assert_eq!(
+            move_leading_article("The Good, the Bad and the Ugly"),
+            "Good, the Bad and the Ugly, The"
+        );
+    }
+
+    #[test]
+    fn transliterates_accents() {
+        assert_eq!(transliterate_ascii("Amélie"), "Amelie");
+        assert_eq!(
+            transliterate_ascii("Léon: The Professional"),
+            "Leon: The Professional"
+        );
+        assert_eq!(transliterate_ascii("Die Brücke"), "Die Brucke");
+        assert_eq!(transliterate_ascii("Æon Flux"), "AEon Flux");
+        assert_eq!(transliterate_ascii("Straße"), "Strasse");
+    }
+
+    #[test]
+    fn drops_characters_without_ascii_equivalent() {
+        assert_eq!(
+            transliterate_ascii("千と千尋の神隠し Spirited Away"),
+            "Spirited Away"
+        );
+        assert_eq!(transliterate_ascii("WALL·E"), "WALLE");
+    }
+
+    #[test]
+    fn applies_both_options_with_year() {
+        let options = NamingOptions {
+            move_leading_article: true,
+            ascii_only: true,
+            append_quality_tag: false,
+            tmdb_id_tag: false,
+        };
+        assert_eq!(
+            options.title_year("The Légend", Some(1985)),
+            "Legend, The (1985)"
+        );
+        assert_eq!(options.title_year("The Matrix", None), "Matrix, The");
+    }
+
+    #[test]
+    fn quality_suffix_only_when_enabled() {
+        let mut options = NamingOptions::default();
+        assert_eq!(options.quality_suffix(Some("Bluray-1080p")), "");
+
+        options.append_quality_tag = true;
+        assert_eq!(
+            options.quality_suffix(Some("Bluray-1080p")),
+            " [Bluray-1080p]"
+        );
+        assert_eq!(options.quality_suffix(None), "");
+    }
+
+    #[test]
+    fn show_folder_tags_the_tmdb_id_when_enabled() {
+        let mut options = NamingOptions::default();
+        assert_eq!(
+            options.show_folder("The Office", Some(2005), 2316),
+            "The Office (2005)"
+        );
+        options.tmdb_id_tag = true;
+        assert_eq!(
+            options.show_folder("The Office", Some(2005), 2316),
+            "The Office (2005) {tmdb-2316}"
+        );
+        assert_eq!(
+            parse_tmdb_id_tag("The Office (2005) {tmdb-2316}"),
+            Some(2316)
+        );
+        assert_eq!(parse_tmdb_id_tag("The Office (2005)"), None);
+        assert_eq!(parse_tmdb_id_tag("The Office {tmdb-}"), None);
+    }
+}
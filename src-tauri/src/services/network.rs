@@ -0,0 +1,88 @@
+use crate::state::job_state::{emit_progress, Job};
+use crate::state::locks::RwLockExt;
+use crate::state::AppState;
+use log::info;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often a queued upload checks whether offline mode was turned off
+const ONLINE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Timeouts of the network calls and offline mode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkOptions {
+    /// No metadata lookups, uploads wait until it is turned off again
+    pub offline: bool,
+    pub tmdb_timeout_seconds: u64,
+    pub github_timeout_seconds: u64,
+    pub ftp_timeout_seconds: u64,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        NetworkOptions {
+            offline: false,
+            tmdb_timeout_seconds: 15,
+            github_timeout_seconds: 15,
+            ftp_timeout_seconds: 30,
+        }
+    }
+}
+
+impl NetworkOptions {
+    pub fn tmdb_timeout(&self) -> Duration {
+        timeout(self.tmdb_timeout_seconds)
+    }
+
+    pub fn github_timeout(&self) -> Duration {
+        timeout(self.github_timeout_seconds)
+    }
+
+    pub fn ftp_timeout(&self) -> Duration {
+        timeout(self.ftp_timeout_seconds)
+    }
+}
+
+/// A timeout of 0 would fail every request, it is treated as 1 second.
+fn timeout(seconds: u64) -> Duration {
+    Duration::from_secs(seconds.max(1))
+}
+
+pub fn is_offline(app_handle: &AppHandle) -> bool {
+    app_handle.state::<AppState>().network_options().offline
+}
+
+/// Returns once offline mode is turned off.
+pub async fn wait_for_online(app_handle: &AppHandle) {
+    while is_offline(app_handle) {
+        tokio::time::sleep(ONLINE_CHECK_INTERVAL).await;
+    }
+}
+
+/// Keeps `job` Pending while offline mode is on, it is how uploads are queued.
+pub async fn wait_until_online(app_handle: &AppHandle, job: &Arc<RwLock<Job>>) {
+    if !is_offline(app_handle) {
+        return;
+    }
+    let job_id = job.read_or_recover().id;
+    info!("Offline mode is on, job {job_id} waits for it to end");
+    job.write_or_recover().subtitle = Some("Queued until offline mode is off".to_string());
+    emit_progress(app_handle, job, true);
+    wait_for_online(app_handle).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_timeouts_still_wait_a_second() {
+        let options = NetworkOptions {
+            tmdb_timeout_seconds: 0,
+            ..NetworkOptions::default()
+        };
+        assert_eq!(options.tmdb_timeout(), Duration::from_secs(1));
+        assert_eq!(options.ftp_timeout(), Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,378 @@
+use crate::services::push::{self, PushPriority};
+use crate::services::unix_time;
+use crate::state::job_state::JobId;
+use crate::state::AppState;
+use crate::templates::toast::{self, Toast, ToastVariant};
+use log::{debug, error, info};
+use std::time::Duration;
+use tauri::menu::CheckMenuItem;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_notification::NotificationExt;
+
+/// How often an expired "do not disturb until" is noticed without anything being shown
+const DO_NOT_DISTURB_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What a desktop notification is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationKind {
+    Rip,
+    Upload,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationEvent {
+    Start,
+    Finish,
+    Error,
+}
+
+/// The buttons of a notification, so a failed job can be dealt with without going back to
+/// the app.
+///
+/// The frontend registers one action type per variant and sends the clicked button to
+/// `notification_action`. Only the mobile notification centers show them, the desktop
+/// notification has no buttons.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotificationActions {
+    /// View log
+    RipFailed,
+    /// Retry, Open folder, View log
+    UploadFailed { job_id: JobId },
+}
+
+impl NotificationActions {
+    /// Same ids as in notification_actions.js
+    pub fn action_type_id(&self) -> &'static str {
+        match self {
+            NotificationActions::RipFailed => "rip-failed",
+            NotificationActions::UploadFailed { .. } => "upload-failed",
+        }
+    }
+}
+
+/// Which events of one kind of job show a desktop notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotificationEvents {
+    pub on_start: bool,
+    pub on_finish: bool,
+    pub on_error: bool,
+}
+
+impl Default for NotificationEvents {
+    /// Same as before there were preferences, finished and failed jobs only.
+    fn default() -> Self {
+        NotificationEvents {
+            on_start: false,
+            on_finish: true,
+            on_error: true,
+        }
+    }
+}
+
+impl NotificationEvents {
+    pub fn allows(&self, event: NotificationEvent) -> bool {
+        match event {
+            NotificationEvent::Start => self.on_start,
+            NotificationEvent::Finish => self.on_finish,
+            NotificationEvent::Error => self.on_error,
+        }
+    }
+}
+
+/// Notification preferences, rips and uploads are set separately.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NotificationPreferences {
+    pub rips: NotificationEvents,
+    pub uploads: NotificationEvents,
+}
+
+impl NotificationPreferences {
+    pub fn allows(&self, kind: NotificationKind, event: NotificationEvent) -> bool {
+        match kind {
+            NotificationKind::Rip => self.rips.allows(event),
+            NotificationKind::Upload => self.uploads.allows(event),
+        }
+    }
+}
+
+/// Holds back notifications and toasts, e.g. while screen sharing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum DoNotDisturb {
+    #[default]
+    Off,
+    /// Until turned off again from the tray
+    On,
+    /// Until this unix timestamp in seconds
+    Until(u64),
+}
+
+impl DoNotDisturb {
+    /// `minutes` from now, 0 turns it off
+    pub fn for_minutes(minutes: u64) -> Self {
+        if minutes == 0 {
+            return DoNotDisturb::Off;
+        }
+        DoNotDisturb::Until(unix_time::now() + minutes * 60)
+    }
+
+    pub fn is_active_at(&self, now: u64) -> bool {
+        match self {
+            DoNotDisturb::Off => false,
+            DoNotDisturb::On => true,
+            DoNotDisturb::Until(until) => now < *until,
+        }
+    }
+
+    /// "off", "on" or the timestamp, the store only keeps strings
+    pub fn to_store_value(&self) -> String {
+        match self {
+            DoNotDisturb::Off => "off".to_string(),
+            DoNotDisturb::On => "on".to_string(),
+            DoNotDisturb::Until(until) => until.to_string(),
+        }
+    }
+
+    pub fn from_store_value(value: Option<&str>) -> Self {
+        match value {
+            Some("on") => DoNotDisturb::On,
+            Some(value) => value
+                .parse()
+                .map(DoNotDisturb::Until)
+                .unwrap_or(DoNotDisturb::Off),
+            None => DoNotDisturb::Off,
+        }
+    }
+}
+
+/// Something held back by do not disturb, shown once it is over.
+#[derive(Clone, Debug)]
+pub enum HeldMessage {
+    Notification {
+        title: String,
+        body: String,
+        actions: Option<NotificationActions>,
+    },
+    Toast(Toast),
+}
+
+/// The tray's do not disturb item, kept so the check mark follows changes made elsewhere.
+pub struct DoNotDisturbMenuItem(pub CheckMenuItem<Wry>);
+
+/// True while do not disturb is on, an expired one is turned off and the held messages are shown.
+pub fn do_not_disturb_active(app_handle: &AppHandle) -> bool {
+    let do_not_disturb = app_handle.state::<AppState>().do_not_disturb();
+    if do_not_disturb.is_active_at(unix_time::now()) {
+        return true;
+    }
+    if do_not_disturb != DoNotDisturb::Off {
+        set_do_not_disturb(app_handle, DoNotDisturb::Off);
+    }
+    false
+}
+
+/// Change do not disturb, persist it and show whatever was held back when it ends.
+pub fn set_do_not_disturb(app_handle: &AppHandle, do_not_disturb: DoNotDisturb) {
+    let state = app_handle.state::<AppState>();
+    let active = do_not_disturb.is_active_at(unix_time::now());
+    info!("Do not disturb is now {do_not_disturb:?}");
+    *state.lock_do_not_disturb() = do_not_disturb;
+    if let Err(e) = state.save(app_handle) {
+        error!("Failed to save do not disturb: {e}");
+    }
+    if let Some(menu_item) = app_handle.try_state::<DoNotDisturbMenuItem>() {
+        if let Err(e) = menu_item.0.set_checked(active) {
+            debug!("Failed to update do not disturb tray item: {e}");
+        }
+    }
+    if !active {
+        show_held_messages(app_handle);
+    }
+}
+
+fn show_held_messages(app_handle: &AppHandle) {
+    let held = std::mem::take(&mut *app_handle.state::<AppState>().lock_held_messages());
+    if held.is_empty() {
+        return;
+    }
+    info!(
+        "Showing {} messages held back by do not disturb",
+        held.len()
+    );
+    for message in held {
+        match message {
+            HeldMessage::Notification {
+                title,
+                body,
+                actions,
+            } => show_notification(app_handle, title, body, actions),
+            HeldMessage::Toast(toast) => show_toast(app_handle, toast),
+        }
+    }
+}
+
+/// Show a desktop notification unless the preferences turned this one off.
+///
+/// It is also pushed to ntfy/Gotify when configured. Do not disturb only covers
+/// the desktop, errors are held back and everything else is dropped.
+pub fn notify(
+    app_handle: &AppHandle,
+    kind: NotificationKind,
+    event: NotificationEvent,
+    title: impl Into<String>,
+    body: impl Into<String>,
+) {
+    notify_with_actions(app_handle, kind, event, title, body, None);
+}
+
+/// `notify` with buttons where the notification center has them, see `NotificationActions`.
+pub fn notify_with_actions(
+    app_handle: &AppHandle,
+    kind: NotificationKind,
+    event: NotificationEvent,
+    title: impl Into<String>,
+    body: impl Into<String>,
+    actions: Option<NotificationActions>,
+) {
+    let preferences = app_handle.state::<AppState>().notification_preferences();
+    let title = title.into();
+    if !preferences.allows(kind, event) {
+        debug!("Notification turned off for {kind:?} {event:?}: {title}");
+        return;
+    }
+    let body = body.into();
+    let priority = match event {
+        NotificationEvent::Error => PushPriority::High,
+        NotificationEvent::Start | NotificationEvent::Finish => PushPriority::Default,
+    };
+    push::spawn_push(app_handle, title.clone(), body.clone(), priority);
+
+    if do_not_disturb_active(app_handle) {
+        if event == NotificationEvent::Error {
+            hold(
+                app_handle,
+                HeldMessage::Notification {
+                    title,
+                    body,
+                    actions,
+                },
+            );
+        }
+        return;
+    }
+    show_notification(app_handle, title, body, actions);
+}
+
+/// Toasts from background work go through here so do not disturb can hold them back.
+///
+/// Toasts answering something the user just clicked are returned directly instead.
+pub fn emit_toast(app_handle: &AppHandle, toast: Toast) {
+    if do_not_disturb_active(app_handle) {
+        if matches!(toast.variant, ToastVariant::Danger) {
+            hold(app_handle, HeldMessage::Toast(toast));
+        }
+        return;
+    }
+    show_toast(app_handle, toast);
+}
+
+fn hold(app_handle: &AppHandle, message: HeldMessage) {
+    debug!("Holding back {message:?} until do not disturb is over");
+    app_handle
+        .state::<AppState>()
+        .lock_held_messages()
+        .push(message);
+}
+
+fn show_notification(
+    app_handle: &AppHandle,
+    title: String,
+    body: String,
+    actions: Option<NotificationActions>,
+) {
+    let mut builder = app_handle.notification().builder().title(title).body(body);
+    if let Some(actions) = &actions {
+        builder = builder.action_type_id(actions.action_type_id());
+    }
+    if let Some(NotificationActions::UploadFailed { job_id }) = actions {
+        builder = builder.extra("job_id", u64::from(job_id));
+    }
+    if let Err(e) = builder.show() {
+        error!("Failed to show notification: {e}");
+    }
+}
+
+fn show_toast(app_handle: &AppHandle, toast: Toast) {
+    if let Ok(turbo) = toast::render_toast_append(toast) {
+        let _ = app_handle.emit("disks-changed", turbo);
+    }
+}
+
+/// Ends an expired "do not disturb until" even when nothing tries to notify.
+pub async fn watch_do_not_disturb(app_handle: AppHandle) {
+    let mut interval = tokio::time::interval(DO_NOT_DISTURB_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        do_not_disturb_active(&app_handle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_finished_and_failed_jobs() {
+        let preferences = NotificationPreferences::default();
+        for kind in [NotificationKind::Rip, NotificationKind::Upload] {
+            assert!(!preferences.allows(kind, NotificationEvent::Start));
+            assert!(preferences.allows(kind, NotificationEvent::Finish));
+            assert!(preferences.allows(kind, NotificationEvent::Error));
+        }
+    }
+
+    #[test]
+    fn rips_and_uploads_are_separate() {
+        let preferences = NotificationPreferences {
+            rips: NotificationEvents {
+                on_start: true,
+                on_finish: true,
+                on_error: true,
+            },
+            uploads: NotificationEvents {
+                on_start: false,
+                on_finish: false,
+                on_error: true,
+            },
+        };
+        assert!(preferences.allows(NotificationKind::Rip, NotificationEvent::Start));
+        assert!(!preferences.allows(NotificationKind::Upload, NotificationEvent::Start));
+        assert!(!preferences.allows(NotificationKind::Upload, NotificationEvent::Finish));
+        assert!(preferences.allows(NotificationKind::Upload, NotificationEvent::Error));
+    }
+
+    #[test]
+    fn do_not_disturb_until_expires() {
+        assert!(!DoNotDisturb::Off.is_active_at(100));
+        assert!(DoNotDisturb::On.is_active_at(u64::MAX));
+        assert!(DoNotDisturb::Until(200).is_active_at(100));
+        assert!(!DoNotDisturb::Until(200).is_active_at(200));
+        assert_eq!(DoNotDisturb::for_minutes(0), DoNotDisturb::Off);
+    }
+
+    #[test]
+    fn do_not_disturb_store_value_round_trips() {
+        for do_not_disturb in [
+            DoNotDisturb::Off,
+            DoNotDisturb::On,
+            DoNotDisturb::Until(1_700_000_000),
+        ] {
+            let value = do_not_disturb.to_store_value();
+            assert_eq!(DoNotDisturb::from_store_value(Some(&value)), do_not_disturb);
+        }
+        assert_eq!(DoNotDisturb::from_store_value(None), DoNotDisturb::Off);
+        assert_eq!(
+            DoNotDisturb::from_store_value(Some("later")),
+            DoNotDisturb::Off
+        );
+    }
+}
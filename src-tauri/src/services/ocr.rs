@@ -0,0 +1,113 @@
+use log::debug;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+
+// Tesseract is not bundled, it has to be installed and available on the PATH.
+const TESSERACT: &str = "tesseract";
+
+// Automatic page segmentation, a case cover is a mix of big title text and small print.
+const PAGE_SEGMENTATION: &str = "3";
+
+/// Words on every case that are never part of the title.
+const PACKAGING_WORDS: [&str; 14] = [
+    "DVD",
+    "BLU-RAY",
+    "BLURAY",
+    "4K",
+    "ULTRA",
+    "HD",
+    "WIDESCREEN",
+    "FULLSCREEN",
+    "EDITION",
+    "RATED",
+    "DIGITAL",
+    "DISC",
+    "SPECIAL",
+    "FEATURES",
+];
+
+/// The text tesseract reads in the photo at `image`.
+pub async fn read_text(app_handle: &AppHandle, image: &Path) -> Result<String, String> {
+    let input = input_path(image);
+    let input = input.to_string_lossy();
+    debug!("Executing command: {TESSERACT} {input} stdout --psm {PAGE_SEGMENTATION}");
+    let output = app_handle
+        .shell()
+        .command(TESSERACT)
+        .args([&*input, "stdout", "--psm", PAGE_SEGMENTATION])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {TESSERACT}, is Tesseract OCR installed? {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last_line = stderr.lines().last().unwrap_or_default();
+        return Err(format!(
+            "{TESSERACT} exited with {:?}: {last_line}",
+            output.status.code()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Tesseract reads a file name starting with "-" as an option and has no "--" to stop
+/// that, a relative path gets "./" in front instead.
+fn input_path(image: &Path) -> PathBuf {
+    if image.is_absolute() {
+        image.to_path_buf()
+    } else {
+        Path::new(".").join(image)
+    }
+}
+
+/// The line most likely to be the title, the one with the most letters once the packaging
+/// words are gone. OCR of small print reads as short noisy lines, a title rarely does.
+pub fn title_candidate(text: &str) -> Option<String> {
+    text.lines()
+        .map(clean_line)
+        .filter(|line| line.chars().filter(|c| c.is_alphabetic()).count() >= 3)
+        .max_by_key(|line| {
+            let letters = line.chars().filter(|c| c.is_alphabetic()).count();
+            let noise = line
+                .chars()
+                .filter(|c| !c.is_alphanumeric() && !c.is_whitespace() && !"'&:-".contains(*c))
+                .count();
+            letters.saturating_sub(noise * 3)
+        })
+}
+
+fn clean_line(line: &str) -> String {
+    line.split_whitespace()
+        .filter(|word| {
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+            !word.is_empty()
+                && !PACKAGING_WORDS
+                    .iter()
+                    .any(|packaging| packaging.eq_ignore_ascii_case(word))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_title_out_of_the_cover_text() {
+        let text = "WIDESCREEN EDITION\n\nTHE IRON GIANT\n\n© 1999 Warner Bros. | PG\n,.;' ~\n";
+        assert_eq!(title_candidate(text), Some("THE IRON GIANT".to_string()));
+    }
+
+    #[test]
+    fn relative_images_never_look_like_options() {
+        assert_eq!(input_path(Path::new("-psm.jpg")), Path::new("./-psm.jpg"));
+        let absolute = std::env::temp_dir().join("-psm.jpg");
+        assert_eq!(input_path(&absolute), absolute);
+    }
+
+    #[test]
+    fn nothing_readable_is_none() {
+        assert_eq!(title_candidate("DVD\n~~ ..\nBLU-RAY DISC"), None);
+    }
+}
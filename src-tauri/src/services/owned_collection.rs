@@ -0,0 +1,343 @@
+//! Titles the user owns that Reelix didn't rip, imported from a CSV or JSON list or a Plex
+//! library export, so search results and movie pages can show them as owned.
+//!
+//! The titles are kept in "owned_collection.json". A title matches by its TMDB id when the
+//! list has one, by title and year otherwise.
+use crate::the_movie_db::{MovieResponse, SearchResult};
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "owned_collection.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OwnedTitle {
+    /// "movie" or "tv", `None` matches both
+    pub media_type: Option<String>,
+    pub tmdb_id: Option<u32>,
+    pub title: String,
+    pub year: Option<u32>,
+}
+
+impl OwnedTitle {
+    fn matches(&self, media_type: &str, tmdb_id: u32, title: &str, year: Option<u32>) -> bool {
+        if self
+            .media_type
+            .as_deref()
+            .is_some_and(|kind| kind != media_type)
+        {
+            return false;
+        }
+        if let Some(id) = self.tmdb_id {
+            return id == tmdb_id;
+        }
+        let same_year = match (self.year, year) {
+            (Some(owned), Some(year)) => owned == year,
+            _ => true,
+        };
+        same_year && normalize(&self.title) == normalize(title)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct OwnedCollection {
+    pub titles: Vec<OwnedTitle>,
+}
+
+impl OwnedCollection {
+    pub fn owns(&self, media_type: &str, tmdb_id: u32, title: &str, year: Option<u32>) -> bool {
+        self.titles
+            .iter()
+            .any(|owned| owned.matches(media_type, tmdb_id, title, year))
+    }
+
+    pub fn owns_result(&self, result: &SearchResult) -> bool {
+        self.owns(
+            &result.media_type,
+            result.id,
+            &result.get_title(),
+            result.year(),
+        )
+    }
+
+    pub fn owns_movie(&self, movie: &MovieResponse) -> bool {
+        self.owns("movie", movie.id, &movie.title, movie.year())
+    }
+
+    /// Adds the titles that aren't in the collection yet, returns how many were new.
+    pub fn merge(&mut self, titles: Vec<OwnedTitle>) -> usize {
+        let before = self.titles.len();
+        for title in titles {
+            if !self.titles.contains(&title) {
+                self.titles.push(title);
+            }
+        }
+        self.titles.len() - before
+    }
+}
+
+/// Lowercase letters and digits only, "Alien³" and "alien 3" still differ but
+/// "Spider-Man: Homecoming" and "spider man homecoming" don't.
+fn normalize(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn media_type(value: &str) -> Option<String> {
+    match value.trim().to_lowercase().as_str() {
+        "movie" | "movies" | "film" => Some("movie".to_string()),
+        "tv" | "show" | "series" | "tv show" => Some("tv".to_string()),
+        _ => None,
+    }
+}
+
+/// Reads a JSON or CSV collection list, the format is told apart by its first character.
+pub fn parse(text: &str) -> Result<Vec<OwnedTitle>, String> {
+    let text = text.trim_start_matches('\u{feff}').trim();
+    if text.starts_with('[') || text.starts_with('{') {
+        parse_json(text)
+    } else {
+        parse_csv(text)
+    }
+}
+
+/// A list of `{"title", "year", "tmdb_id", "type"}` objects, or a Plex library export
+/// with `MediaContainer.Metadata`.
+fn parse_json(text: &str) -> Result<Vec<OwnedTitle>, String> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|e| format!("Failed to read the JSON list: {e}"))?;
+    let items = match &value {
+        Value::Array(items) => items,
+        _ => value
+            .pointer("/MediaContainer/Metadata")
+            .or_else(|| value.get("titles"))
+            .and_then(Value::as_array)
+            .ok_or("The JSON list has to be an array or a Plex library export")?,
+    };
+    let text_field = |item: &Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| item.get(*key))
+            .and_then(|value| match value {
+                Value::String(text) => Some(text.clone()),
+                Value::Number(number) => Some(number.to_string()),
+                _ => None,
+            })
+    };
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            let title = text_field(item, &["title", "name", "Title", "Name"])?;
+            // Plex keeps the TMDB id in `Guid: [{"id": "tmdb://603"}]`
+            let plex_tmdb_id = item
+                .get("Guid")
+                .and_then(Value::as_array)
+                .and_then(|guids| {
+                    guids.iter().find_map(|guid| {
+                        guid.get("id")?
+                            .as_str()?
+                            .strip_prefix("tmdb://")
+                            .map(str::to_string)
+                    })
+                });
+            Some(OwnedTitle {
+                media_type: text_field(item, &["type", "media_type", "Type"])
+                    .and_then(|kind| media_type(&kind)),
+                tmdb_id: text_field(item, &["tmdb_id", "tmdbId", "tmdb"])
+                    .or(plex_tmdb_id)
+                    .and_then(|id| id.trim().parse().ok()),
+                title,
+                year: text_field(item, &["year", "Year"]).and_then(|year| year.trim().parse().ok()),
+            })
+        })
+        .collect())
+}
+
+/// Splits a CSV line, quoted cells may have commas and `""` for a quote.
+fn csv_cells(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/// A CSV with a header row, it needs a `title` or `name` column, `year`, `tmdb_id` and
+/// `type` are used when they are there.
+fn parse_csv(text: &str) -> Result<Vec<OwnedTitle>, String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = csv_cells(lines.next().ok_or("The CSV list is empty")?)
+        .iter()
+        .map(|cell| cell.to_lowercase().replace([' ', '-'], "_"))
+        .collect();
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|cell| names.contains(&cell.as_str()))
+    };
+    let title_column =
+        column(&["title", "name"]).ok_or("The CSV list needs a title or name column")?;
+    let year_column = column(&["year", "release_year"]);
+    let tmdb_column = column(&["tmdb_id", "tmdb", "tmdbid"]);
+    let type_column = column(&["type", "media_type", "kind"]);
+
+    Ok(lines
+        .filter_map(|line| {
+            let cells = csv_cells(line);
+            let cell = |index: Option<usize>| {
+                index
+                    .and_then(|index| cells.get(index))
+                    .filter(|cell| !cell.is_empty())
+            };
+            let title = cell(Some(title_column))?.clone();
+            Some(OwnedTitle {
+                media_type: cell(type_column).and_then(|kind| media_type(kind)),
+                tmdb_id: cell(tmdb_column).and_then(|id| id.parse().ok()),
+                title,
+                year: cell(year_column).and_then(|year| year.parse().ok()),
+            })
+        })
+        .collect())
+}
+
+pub fn load(app_handle: &AppHandle) -> Result<OwnedCollection, String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to load {STORE_FILE} store: {e}"))?;
+    let titles = match store.get("titles") {
+        Some(value) => serde_json::from_value(value.clone()).unwrap_or_default(),
+        None => Vec::new(),
+    };
+    store.close_resource();
+    Ok(OwnedCollection { titles })
+}
+
+/// The collection for rendering, empty when the store can't be read.
+pub fn load_or_empty(app_handle: &AppHandle) -> OwnedCollection {
+    load(app_handle).unwrap_or_else(|e| {
+        error!("{e}");
+        OwnedCollection::default()
+    })
+}
+
+fn save(app_handle: &AppHandle, collection: &OwnedCollection) -> Result<(), String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to open {STORE_FILE} store: {e}"))?;
+    store.set("titles", json!(collection.titles));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save {STORE_FILE} store: {e}"))?;
+    store.close_resource();
+    Ok(())
+}
+
+/// Adds the titles of the list at `path` to the collection, returns how many were new.
+pub fn import(app_handle: &AppHandle, path: &Path) -> Result<usize, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let titles = parse(&text)?;
+    let mut collection = load(app_handle)?;
+    let added = collection.merge(titles);
+    save(app_handle, &collection)?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn title(title: &str, year: Option<u32>) -> OwnedTitle {
+        OwnedTitle {
+            title: title.to_string(),
+            year,
+            ..OwnedTitle::default()
+        }
+    }
+
+    #[test]
+    fn reads_csv_with_quotes() {
+        let titles = parse(
+            "Title,Year,Type,TMDB ID\n\
+             \"Crouching Tiger, Hidden Dragon\",2000,movie,146\n\
+             The Office,2005,TV Show,\n\
+             ,1999,movie,\n",
+        )
+        .unwrap();
+        assert_eq!(
+            titles,
+            vec![
+                OwnedTitle {
+                    media_type: Some("movie".to_string()),
+                    tmdb_id: Some(146),
+                    title: "Crouching Tiger, Hidden Dragon".to_string(),
+                    year: Some(2000),
+                },
+                OwnedTitle {
+                    media_type: Some("tv".to_string()),
+                    tmdb_id: None,
+                    title: "The Office".to_string(),
+                    year: Some(2005),
+                },
+            ]
+        );
+        assert!(parse("Year\n2000").is_err());
+    }
+
+    #[test]
+    fn reads_json_lists_and_plex_exports() {
+        assert_eq!(
+            parse(r#"[{"title": "Alien", "year": 1979}]"#).unwrap(),
+            vec![title("Alien", Some(1979))]
+        );
+        let plex = r#"{"MediaContainer": {"Metadata": [
+            {"type": "movie", "title": "The Matrix", "year": 1999,
+             "Guid": [{"id": "imdb://tt0133093"}, {"id": "tmdb://603"}]}
+        ]}}"#;
+        assert_eq!(
+            parse(plex).unwrap(),
+            vec![OwnedTitle {
+                media_type: Some("movie".to_string()),
+                tmdb_id: Some(603),
+                title: "The Matrix".to_string(),
+                year: Some(1999),
+            }]
+        );
+    }
+
+    #[test]
+    fn matches_by_tmdb_id_or_title_and_year() {
+        let collection = OwnedCollection {
+            titles: vec![
+                title("Spider-Man: Homecoming", Some(2017)),
+                OwnedTitle {
+                    media_type: Some("movie".to_string()),
+                    tmdb_id: Some(603),
+                    ..title("Matrix", None)
+                },
+            ],
+        };
+        assert!(collection.owns("movie", 1, "spider man homecoming", Some(2017)));
+        assert!(!collection.owns("movie", 1, "Spider-Man: Homecoming", Some(2002)));
+        assert!(collection.owns("movie", 603, "The Matrix", Some(1999)));
+        assert!(!collection.owns("tv", 603, "The Matrix", Some(1999)));
+        assert!(!collection.owns("movie", 604, "Matrix", None));
+    }
+}
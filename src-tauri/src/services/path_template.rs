@@ -0,0 +1,349 @@
+use crate::models::title_info::TitleInfo;
+use crate::services::naming::NamingOptions;
+use crate::state::FtpConfig;
+use crate::the_movie_db::{MovieResponse, TvResponse};
+use std::path::PathBuf;
+
+/// Remote layout used for movies when no template is configured.
+/// Matches the local Plex layout: `Movie Name (Year)/`
+pub const DEFAULT_MOVIE_TEMPLATE: &str = "{title_year}";
+
+/// Remote layout used for TV shows when no template is configured.
+/// Matches the local Plex layout: `Show Name (Year)/Season 01/`
+pub const DEFAULT_TV_TEMPLATE: &str = "{show_folder}/Season {season}";
+
+/// Values that can be referenced from a remote path template.
+///
+/// The title has already been passed through `NamingOptions`.
+///
+/// Supported tokens:
+/// - `{title}`        the movie title or show name
+/// - `{year}`         release year (movies) or first air year (TV)
+/// - `{title_year}`   `Title (Year)`, same as the local folder name
+/// - `{first_letter}` first letter of the title, upper cased (`#` for digits)
+/// - `{resolution}`   resolution of the ripped title, e.g. `1080p`
+/// - `{quality}`      source and resolution of the ripped title, e.g. `Bluray-1080p`
+/// - `{season}`       zero padded season number, e.g. `01`
+/// - `{tmdb_id}`      the TMDB id, e.g. `2316`
+/// - `{show_folder}`  `{title_year}` with the `{tmdb-2316}` tag when that naming option is on
+///
+/// Unknown tokens are left untouched so typos are easy to spot on the server.
+pub struct PathTemplateVars {
+    pub title: String,
+    pub year: Option<u32>,
+    pub resolution: Option<String>,
+    pub quality: Option<String>,
+    pub season: Option<u32>,
+    pub tmdb_id: Option<u32>,
+    /// Adds the `{tmdb-ID}` tag to `{show_folder}`
+    pub tmdb_id_tag: bool,
+}
+
+impl PathTemplateVars {
+    pub fn movie(movie: &MovieResponse, naming: &NamingOptions, title: Option<&TitleInfo>) -> Self {
+        Self {
+            title: naming.apply(&movie.title),
+            year: movie.year(),
+            resolution: title.and_then(TitleInfo::resolution),
+            quality: title.and_then(TitleInfo::quality_tag),
+            season: None,
+            tmdb_id: Some(movie.id),
+            tmdb_id_tag: false,
+        }
+    }
+
+    pub fn tv(
+        tv: &TvResponse,
+        season_number: u32,
+        naming: &NamingOptions,
+        title: Option<&TitleInfo>,
+    ) -> Self {
+        Self {
+            title: naming.apply(&tv.name),
+            year: tv.year(),
+            resolution: title.and_then(TitleInfo::resolution),
+            quality: title.and_then(TitleInfo::quality_tag),
+            season: Some(season_number),
+            tmdb_id: Some(tv.id.into()),
+            tmdb_id_tag: naming.tmdb_id_tag,
+        }
+    }
+
+    fn title_year(&self) -> String {
+        match self.year {
+            Some(year) => format!("{} ({year})", self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    fn lookup(&self, token: &str) -> Option<String> {
+        match token {
+            "title" => Some(self.title.clone()),
+            "year" => Some(self.year.map(|y| y.to_string()).unwrap_or_default()),
+            "title_year" => Some(self.title_year()),
+            "show_folder" => Some(match self.tmdb_id {
+                Some(id) if self.tmdb_id_tag => format!("{} {{tmdb-{id}}}", self.title_year()),
+                _ => self.title_year(),
+            }),
+            "tmdb_id" => Some(self.tmdb_id.map(|id| id.to_string()).unwrap_or_default()),
+            "first_letter" => Some(first_letter(&self.title)),
+            "resolution" => Some(self.resolution.clone().unwrap_or_default()),
+            "quality" => Some(self.quality.clone().unwrap_or_default()),
+            "season" => Some(self.season.map(|s| format!("{s:02}")).unwrap_or_default()),
+            _ => None,
+        }
+    }
+}
+
+/// Evaluate a remote path template into a relative directory.
+///
+/// Values are sanitized so they can never introduce extra directories, empty
+/// brackets left by missing values are removed, and empty segments are dropped.
+///
+/// Example:
+/// - `{first_letter}/{title} ({year})` for "Alien" (1979) -> `A/Alien (1979)`
+/// - `{title} [{resolution}]` without resolution -> `Alien`
+pub fn render(template: &str, vars: &PathTemplateVars) -> PathBuf {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) => {
+                let token = &after[..end];
+                match vars.lookup(token) {
+                    Some(value) => rendered.push_str(&sanitize_value(&value)),
+                    None => {
+                        rendered.push('{');
+                        rendered.push_str(token);
+                        rendered.push('}');
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    rendered
+        .split(['/', '\\'])
+        .map(clean_segment)
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Remote directory for a movie upload, `None` when FTP movie path is not configured.
+pub fn movie_upload_dir(
+    ftp_config: &FtpConfig,
+    naming: &NamingOptions,
+    movie: &MovieResponse,
+    title: Option<&TitleInfo>,
+) -> Option<PathBuf> {
+    let template = ftp_config
+        .movie_path_template
+        .as_deref()
+        .unwrap_or(DEFAULT_MOVIE_TEMPLATE);
+    ftp_config.movie_upload_path.as_ref().map(|dir| {
+        dir.join(render(
+            template,
+            &PathTemplateVars::movie(movie, naming, title),
+        ))
+    })
+}
+
+/// Path of the main movie file relative to the FTP movie upload path.
+///
+/// Used to check if a movie was already uploaded before a title is picked, `title` is the
+/// best guess at the one that will be. Without it `{resolution}` and the quality tag are not
+/// known and drop out of the path.
+pub fn movie_relative_file_path(
+    ftp_config: &FtpConfig,
+    naming: &NamingOptions,
+    movie: &MovieResponse,
+    title: Option<&TitleInfo>,
+) -> String {
+    let template = ftp_config
+        .movie_path_template
+        .as_deref()
+        .unwrap_or(DEFAULT_MOVIE_TEMPLATE);
+    render(template, &PathTemplateVars::movie(movie, naming, title))
+        .join(format!(
+            "{}{}.mkv",
+            naming.title_year(&movie.title, movie.year()),
+            naming.quality_suffix(title.and_then(TitleInfo::quality_tag).as_deref())
+        ))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Remote season directory for a TV upload, `None` when FTP TV path is not configured.
+pub fn tv_upload_season_dir(
+    ftp_config: &FtpConfig,
+    naming: &NamingOptions,
+    tv: &TvResponse,
+    season_number: u32,
+    title: Option<&TitleInfo>,
+) -> Option<PathBuf> {
+    let template = ftp_config
+        .tv_path_template
+        .as_deref()
+        .unwrap_or(DEFAULT_TV_TEMPLATE);
+    ftp_config.tv_upload_path.as_ref().map(|dir| {
+        dir.join(render(
+            template,
+            &PathTemplateVars::tv(tv, season_number, naming, title),
+        ))
+    })
+}
+
+fn first_letter(title: &str) -> String {
+    match title.chars().find(|c| c.is_alphanumeric()) {
+        Some(c) if c.is_numeric() => "#".to_string(),
+        Some(c) => c.to_uppercase().collect(),
+        None => "#".to_string(),
+    }
+}
+
+fn sanitize_value(value: &str) -> String {
+    value.replace(['/', '\\'], "-")
+}
+
+fn clean_segment(segment: &str) -> String {
+    let mut cleaned = segment.to_string();
+    for empty in ["()", "[]", "{}"] {
+        cleaned = cleaned.replace(empty, "");
+    }
+    cleaned.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(title: &str, year: Option<u32>, resolution: Option<&str>) -> PathTemplateVars {
+        PathTemplateVars {
+            title: title.to_string(),
+            year,
+            resolution: resolution.map(str::to_string),
+            quality: None,
+            season: None,
+            tmdb_id: None,
+            tmdb_id_tag: false,
+        }
+    }
+
+    #[test]
+    fn renders_default_movie_template() {
+        let path = render(DEFAULT_MOVIE_TEMPLATE, &vars("Alien", Some(1979), None));
+        assert_eq!(path, PathBuf::from("Alien (1979)"));
+    }
+
+    #[test]
+    fn movie_file_path_has_the_quality_tag_of_the_title() {
+        let movie = MovieResponse {
+            title: "Alien".to_string(),
+            release_date: Some("1979-05-25".to_string()),
+            ..MovieResponse::default()
+        };
+        let title = TitleInfo {
+            video_size: Some("1920x1080".to_string()),
+            ..TitleInfo::default()
+        };
+        let naming = NamingOptions {
+            append_quality_tag: true,
+            ..NamingOptions::default()
+        };
+        let ftp_config = FtpConfig::new();
+        assert_eq!(
+            movie_relative_file_path(&ftp_config, &naming, &movie, Some(&title)),
+            PathBuf::from("Alien (1979)")
+                .join("Alien (1979) [Bluray-1080p].mkv")
+                .to_string_lossy()
+        );
+        assert_eq!(
+            movie_relative_file_path(&ftp_config, &naming, &movie, None),
+            PathBuf::from("Alien (1979)")
+                .join("Alien (1979).mkv")
+                .to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn renders_first_letter_and_resolution() {
+        let path = render(
+            "{first_letter}/{title} ({year}) [{resolution}]",
+            &vars("the thing", Some(1982), Some("1080p")),
+        );
+        assert_eq!(path, PathBuf::from("T/the thing (1982) [1080p]"));
+    }
+
+    #[test]
+    fn drops_empty_brackets_and_segments() {
+        let path = render(
+            "{resolution}/{title} ({year}) [{resolution}]",
+            &vars("Alien", None, None),
+        );
+        assert_eq!(path, PathBuf::from("Alien"));
+    }
+
+    #[test]
+    fn sanitizes_slashes_in_values() {
+        let path = render("{title}", &vars("AC/DC Live", None, None));
+        assert_eq!(path, PathBuf::from("AC-DC Live"));
+    }
+
+    #[test]
+    fn first_letter_groups_digits() {
+        let path = render("{first_letter}", &vars("12 Monkeys", None, None));
+        assert_eq!(path, PathBuf::from("#"));
+    }
+
+    #[test]
+    fn leaves_unknown_tokens() {
+        let path = render("{title}/{nope}", &vars("Alien", None, None));
+        assert_eq!(path, PathBuf::from("Alien/{nope}"));
+    }
+
+    #[test]
+    fn renders_quality() {
+        let mut vars = vars("Alien", Some(1979), Some("1080p"));
+        vars.quality = Some("Bluray-1080p".to_string());
+        let path = render("{title_year} [{quality}]", &vars);
+        assert_eq!(path, PathBuf::from("Alien (1979) [Bluray-1080p]"));
+    }
+
+    #[test]
+    fn renders_default_tv_template() {
+        let mut vars = vars("Example Show", Some(2023), None);
+        vars.season = Some(2);
+        let path = render(DEFAULT_TV_TEMPLATE, &vars);
+        assert_eq!(path, PathBuf::from("Example Show (2023)/Season 02"));
+    }
+
+    #[test]
+    fn tags_the_show_folder_with_the_tmdb_id() {
+        let mut vars = vars("The Office", Some(2005), None);
+        vars.season = Some(1);
+        vars.tmdb_id = Some(2316);
+        assert_eq!(
+            render(DEFAULT_TV_TEMPLATE, &vars),
+            PathBuf::from("The Office (2005)/Season 01")
+        );
+
+        vars.tmdb_id_tag = true;
+        assert_eq!(
+            render(DEFAULT_TV_TEMPLATE, &vars),
+            PathBuf::from("The Office (2005) {tmdb-2316}/Season 01")
+        );
+        assert_eq!(
+            render("{title} [{tmdb_id}]", &vars),
+            PathBuf::from("The Office [2316]")
+        );
+    }
+}
@@ -1,70 +1,121 @@
+use crate::services::{blocking, locale, tmdb_cache};
 use crate::state::AppState;
 use crate::the_movie_db;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use tauri::{AppHandle, Manager};
 
-pub fn search_multi(
-    app_state: &tauri::State<'_, AppState>,
+const LANGUAGE: &str = "en-US";
+
+/// `TheMovieDb` uses a blocking client, the request runs on the blocking pool.
+async fn request<T, F>(app_handle: &AppHandle, request: F) -> Result<T, the_movie_db::Error>
+where
+    F: FnOnce(&the_movie_db::TheMovieDb) -> Result<T, the_movie_db::Error> + Send + 'static,
+    T: Send + 'static,
+{
+    let state = app_handle.state::<AppState>();
+    let network_options = state.network_options();
+    if network_options.offline {
+        return Err(the_movie_db::Error {
+            code: 503,
+            message: "Offline mode is on, The Movie DB lookups are turned off".to_string(),
+        });
+    }
+    let api_key = state.lock_the_movie_db_key().to_string();
+    let timeout = network_options.tmdb_timeout();
+    blocking::run(move || request(&the_movie_db::TheMovieDb::new(&api_key, LANGUAGE, timeout)))
+        .await
+        .unwrap_or_else(|message| Err(the_movie_db::Error { code: 500, message }))
+}
+
+/// Keeps a copy of what TMDB answered, the copy is used when TMDB can't be reached.
+fn cached<T: Serialize + DeserializeOwned>(
+    app_handle: &AppHandle,
+    key: &str,
+    result: Result<T, the_movie_db::Error>,
+) -> Result<T, the_movie_db::Error> {
+    match result {
+        Ok(value) => {
+            tmdb_cache::store(app_handle, key, &value);
+            Ok(value)
+        }
+        Err(e) => match tmdb_cache::load(app_handle, key) {
+            Some(value) => {
+                warn!("{}, using the cached {key}", e.message);
+                Ok(value)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+pub async fn search_multi(
+    app_handle: &AppHandle,
     query: &str,
 ) -> Result<the_movie_db::SearchResponse, the_movie_db::Error> {
-    let api_key = &app_state.lock_the_movie_db_key().to_string();
-    let language = "en-US";
-    let movie_db = the_movie_db::TheMovieDb::new(api_key, language);
-
-    movie_db.search_multi(query, 1)
+    let query = query.to_string();
+    request(app_handle, move |movie_db| movie_db.search_multi(&query, 1)).await
 }
 
-pub fn find_movie(
+pub async fn find_movie(
     app_handle: &AppHandle,
     id: u32,
 ) -> Result<the_movie_db::MovieResponse, the_movie_db::Error> {
-    let state: tauri::State<AppState> = app_handle.state::<AppState>();
-    let api_key = &state.lock_the_movie_db_key().to_string();
+    request(app_handle, move |movie_db| movie_db.movie(id)).await
+}
 
-    let language = "en-US";
-    let movie_db = the_movie_db::TheMovieDb::new(api_key, language);
-    movie_db.movie(id)
+pub async fn find_person(
+    app_handle: &AppHandle,
+    id: u32,
+) -> Result<the_movie_db::PersonResponse, the_movie_db::Error> {
+    request(app_handle, move |movie_db| movie_db.person(id)).await
 }
 
-pub fn find_tv(
+pub async fn find_tv(
     app_handle: &AppHandle,
     id: u32,
 ) -> Result<the_movie_db::TvResponse, the_movie_db::Error> {
-    let state: tauri::State<AppState> = app_handle.state::<AppState>();
-    let api_key = &state.lock_the_movie_db_key().to_string();
-
-    let language = "en-US";
-    let movie_db = the_movie_db::TheMovieDb::new(api_key, language);
-    movie_db.tv(id)
+    let result = request(app_handle, move |movie_db| movie_db.tv(id)).await;
+    cached(app_handle, &tmdb_cache::tv_key(id), result)
 }
 
-pub fn find_season(
+pub async fn find_season(
     app_handle: &AppHandle,
     tv_id: u32,
     season_number: u32,
 ) -> Result<the_movie_db::SeasonResponse, the_movie_db::Error> {
-    let state: tauri::State<AppState> = app_handle.state::<AppState>();
-    let api_key = &state.lock_the_movie_db_key().to_string();
-
-    let language = "en-US";
-    let movie_db = the_movie_db::TheMovieDb::new(api_key, language);
-    movie_db.season(tv_id, season_number)
+    let result = request(app_handle, move |movie_db| {
+        movie_db.season(tv_id, season_number)
+    })
+    .await;
+    cached(
+        app_handle,
+        &tmdb_cache::season_key(tv_id, season_number),
+        result,
+    )
 }
 
-pub fn get_movie_certification(
+pub async fn get_movie_certification(
     app_handle: &AppHandle,
-    movie_id: &u32,
+    movie_id: u32,
 ) -> Result<Option<String>, the_movie_db::Error> {
-    let state: tauri::State<AppState> = app_handle.state::<AppState>();
-    let api_key = &state.lock_the_movie_db_key().to_string();
+    let release_dates = request(app_handle, move |movie_db| {
+        movie_db.movie_release_dates(&movie_id)
+    })
+    .await?;
 
-    let language = "en-US";
-    let movie_db = the_movie_db::TheMovieDb::new(api_key, language);
-    let release_dates = movie_db.movie_release_dates(movie_id)?;
+    Ok(release_dates.certification(&locale::current().country()))
+}
+
+pub async fn get_tv_certification(
+    app_handle: &AppHandle,
+    tv_id: u32,
+) -> Result<Option<String>, the_movie_db::Error> {
+    let content_ratings = request(app_handle, move |movie_db| {
+        movie_db.tv_content_ratings(tv_id)
+    })
+    .await?;
 
-    Ok(release_dates
-        .results
-        .iter()
-        .find(|entry| entry.iso_3166_1 == "US")
-        .and_then(|us| us.release_dates.first())
-        .map(|rd| rd.certification.trim().to_string()))
+    Ok(content_ratings.certification(&locale::current().country()))
 }
@@ -0,0 +1,373 @@
+use crate::models::title_info::TitleInfo;
+use crate::services::library::Library;
+use crate::services::track_languages::{self, AUDIO, SUBTITLES};
+use crate::services::{chapters, ffmpeg, long_path, mkvtoolnix, network, preview};
+use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::job_state::{emit_progress, Job, TitlePreview};
+use crate::state::locks::RwLockExt;
+use crate::state::resource_governor::Slot;
+use crate::state::title_video::{TitleVideo, Video};
+use crate::state::AppState;
+use log::{debug, error};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager};
+
+/// Optional ffmpeg stage that runs on a title after it has been ripped and renamed.
+///
+/// Everything is off by default, ffmpeg is not bundled with Reelix.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PostProcessOptions {
+    /// Run the post-process stage at all
+    pub enabled: bool,
+    /// Deinterlace DVD titles that ffmpeg detects as interlaced or telecined
+    pub deinterlace: bool,
+    /// Add an AAC 2.0 downmix of the main audio track
+    pub downmix_stereo: bool,
+    /// Loudness normalize the added audio track
+    pub normalize_loudness: bool,
+    /// Replace generic "Chapter 01" names of movies with names from ChapterDB
+    pub name_chapters: bool,
+    /// ChapterDB only answers requests with an API key
+    pub chapter_db_api_key: Option<String>,
+    /// Grab a few frames of every title for the job view
+    pub preview_frames: bool,
+}
+
+impl PostProcessOptions {
+    /// `None` when no extra audio track was asked for.
+    pub fn audio_track(&self) -> Option<ffmpeg::AudioTrackOptions> {
+        if !self.downmix_stereo && !self.normalize_loudness {
+            return None;
+        }
+        Some(ffmpeg::AudioTrackOptions {
+            downmix_stereo: self.downmix_stereo,
+            normalize_loudness: self.normalize_loudness,
+        })
+    }
+}
+
+/// Run the enabled post-process steps on a ripped file.
+///
+/// Failures never fail the rip, the untouched file is still uploaded and
+/// the problem is added to the job notices instead.
+///
+/// The default tracks of a library profile with language preferences are set even
+/// when the ffmpeg stage is off, it only needs mkvpropedit. A profile can also turn the
+/// stereo downmix and loudness normalizing on or off for its videos.
+pub async fn process(
+    app_handle: &AppHandle,
+    job: &Arc<RwLock<Job>>,
+    title_video: &Arc<RwLock<TitleVideo>>,
+    path: &Path,
+) {
+    let state = app_handle.state::<AppState>();
+    let library = state.library(title_video.read_or_recover().library.as_deref());
+    if !library.audio_languages.is_empty() || !library.subtitle_languages.is_empty() {
+        if let Err(e) = languages_step(app_handle, job, path, &library).await {
+            error!("Setting default tracks failed for {}: {e}", path.display());
+            add_notice(job, format!("Default tracks not set: {e}"));
+        }
+    }
+
+    let mut options = state.post_process_options();
+    if let Some(downmix_stereo) = library.downmix_stereo {
+        options.downmix_stereo = downmix_stereo;
+    }
+    if let Some(normalize_loudness) = library.normalize_loudness {
+        options.normalize_loudness = normalize_loudness;
+    }
+    if !options.enabled {
+        return;
+    }
+    // ffmpeg is CPU heavy, only run as many of these as the governor allows
+    let _post_process_slot = app_handle
+        .state::<BackgroundProcessState>()
+        .wait_for_slot(app_handle, job, Slot::PostProcess)
+        .await;
+
+    let (title, movie_title, video_title) = {
+        let title_video = title_video.read_or_recover();
+        let (movie_title, video_title) = match &title_video.video {
+            Video::Movie(movie) => (Some(movie.movie.title.clone()), movie.movie.title_year()),
+            Video::Tv(tv) => (None, tv.title()),
+            Video::Personal(personal) => (None, personal.title()),
+        };
+        (title_video.title.clone(), movie_title, video_title)
+    };
+    if is_dvd_source(title.as_ref()) {
+        if let Err(e) = interlace_step(app_handle, job, path, &options).await {
+            error!("Interlace check failed for {}: {e}", path.display());
+            add_notice(job, format!("Interlace check failed: {e}"));
+        }
+    }
+    if let Some(audio_track) = options.audio_track() {
+        if let Err(e) = audio_step(app_handle, job, path, &audio_track).await {
+            error!("Audio post-process failed for {}: {e}", path.display());
+            add_notice(job, format!("Audio track not added: {e}"));
+        }
+    }
+    // ChapterDB is built from movie discs, TV episodes keep the makemkv names
+    if let (true, Some(movie_title)) = (options.name_chapters, movie_title) {
+        if let Err(e) = chapters_step(app_handle, job, path, &movie_title, &options).await {
+            error!("Chapter naming failed for {}: {e}", path.display());
+            add_notice(job, format!("Chapters not named: {e}"));
+        }
+    }
+    // Last so the frames show the file as it will be uploaded
+    if options.preview_frames {
+        let duration = title.as_ref().and_then(TitleInfo::duration_seconds);
+        if let Err(e) = preview_step(app_handle, job, path, video_title, duration).await {
+            error!("Preview frames failed for {}: {e}", path.display());
+            add_notice(job, format!("No preview frames: {e}"));
+        }
+    }
+    emit_progress(app_handle, job, true);
+}
+
+/// The resolution is the only hint makemkvcon gives us about the source type per title.
+fn is_dvd_source(title: Option<&TitleInfo>) -> bool {
+    title.and_then(TitleInfo::quality_tag).as_deref() == Some("DVD")
+}
+
+async fn interlace_step(
+    app_handle: &AppHandle,
+    job: &Arc<RwLock<Job>>,
+    path: &Path,
+    options: &PostProcessOptions,
+) -> Result<(), String> {
+    set_subtitle(app_handle, job, "Checking for interlacing");
+    let report = ffmpeg::detect_interlacing(app_handle, path).await?;
+    debug!("idet report for {}: {report:?}", path.display());
+
+    if !report.is_interlaced() {
+        add_notice(job, format!("Progressive: {}", report.summary()));
+        return Ok(());
+    }
+
+    if !options.deinterlace {
+        add_notice(
+            job,
+            format!(
+                "Interlaced: {}. Enable deinterlacing to remove combing.",
+                report.summary()
+            ),
+        );
+        return Ok(());
+    }
+
+    set_subtitle(app_handle, job, "Deinterlacing");
+    let deinterlaced = temp_path(path);
+    if let Err(e) = ffmpeg::deinterlace(app_handle, path, &deinterlaced).await {
+        let _ = fs::remove_file(&deinterlaced);
+        return Err(e);
+    }
+    replace_file(&deinterlaced, path)?;
+    add_notice(job, format!("Deinterlaced: {}", report.summary()));
+    Ok(())
+}
+
+async fn languages_step(
+    app_handle: &AppHandle,
+    job: &Arc<RwLock<Job>>,
+    path: &Path,
+    library: &Library,
+) -> Result<(), String> {
+    set_subtitle(app_handle, job, "Picking audio and subtitle tracks");
+    let tracks = mkvtoolnix::identify(app_handle, path).await?;
+    let audio = track_languages::default_flags(&tracks, AUDIO, &library.audio_languages);
+    let subtitles = track_languages::default_flags(&tracks, SUBTITLES, &library.subtitle_languages);
+    if audio.is_empty() && subtitles.is_empty() {
+        add_notice(
+            job,
+            "None of the preferred languages are on this title".to_string(),
+        );
+        return Ok(());
+    }
+
+    let flags: Vec<(u64, bool)> = audio.iter().chain(subtitles.iter()).copied().collect();
+    mkvtoolnix::set_default_tracks(app_handle, path, &flags).await?;
+    add_notice(
+        job,
+        format!(
+            "Default tracks: audio {}, subtitles {}",
+            default_track_summary(&audio),
+            default_track_summary(&subtitles)
+        ),
+    );
+    Ok(())
+}
+
+fn default_track_summary(flags: &[(u64, bool)]) -> String {
+    flags
+        .iter()
+        .find(|(_, default)| *default)
+        .map(|(number, _)| format!("track {number}"))
+        .unwrap_or_else(|| "as ripped".to_string())
+}
+
+async fn audio_step(
+    app_handle: &AppHandle,
+    job: &Arc<RwLock<Job>>,
+    path: &Path,
+    audio_track: &ffmpeg::AudioTrackOptions,
+) -> Result<(), String> {
+    set_subtitle(app_handle, job, "Adding audio track");
+    let processed = temp_path(path);
+    if let Err(e) = ffmpeg::add_audio_track(app_handle, path, &processed, audio_track).await {
+        let _ = fs::remove_file(&processed);
+        return Err(e);
+    }
+    replace_file(&processed, path)?;
+    add_notice(
+        job,
+        format!("Added {} audio track", audio_track.track_title()),
+    );
+    Ok(())
+}
+
+async fn chapters_step(
+    app_handle: &AppHandle,
+    job: &Arc<RwLock<Job>>,
+    path: &Path,
+    movie_title: &str,
+    options: &PostProcessOptions,
+) -> Result<(), String> {
+    let api_key = options
+        .chapter_db_api_key
+        .as_deref()
+        .ok_or("ChapterDB API key is not configured")?;
+
+    set_subtitle(app_handle, job, "Naming chapters");
+    let chapters_file = path.with_extension("chapters.txt");
+    let result = name_chapters(app_handle, path, &chapters_file, movie_title, api_key).await;
+    let _ = fs::remove_file(&chapters_file);
+
+    if let Some(notice) = result? {
+        add_notice(job, notice);
+    }
+    Ok(())
+}
+
+/// Returns a notice for the job summary when there was something worth reporting.
+async fn name_chapters(
+    app_handle: &AppHandle,
+    path: &Path,
+    chapters_file: &Path,
+    movie_title: &str,
+    api_key: &str,
+) -> Result<Option<String>, String> {
+    mkvtoolnix::extract_chapters(app_handle, path, chapters_file).await?;
+    let content = fs::read_to_string(chapters_file)
+        .map_err(|e| format!("Failed to read extracted chapters: {e}"))?;
+    let ripped = chapters::parse_simple(&content);
+    if ripped.is_empty() || !chapters::has_generic_names(&ripped) {
+        return Ok(None);
+    }
+
+    if network::is_offline(app_handle) {
+        return Ok(Some(
+            "Offline mode is on, chapter names were not looked up".to_string(),
+        ));
+    }
+    let names = match chapters::fetch_chapter_db_names(api_key, movie_title, ripped.len()).await? {
+        Some(names) => names,
+        None => {
+            return Ok(Some(format!(
+                "No ChapterDB entry with {} chapters",
+                ripped.len()
+            )))
+        }
+    };
+
+    let named = chapters::apply_names(&ripped, &names);
+    fs::write(chapters_file, chapters::render_simple(&named))
+        .map_err(|e| format!("Failed to write chapters: {e}"))?;
+    mkvtoolnix::replace_chapters(app_handle, path, chapters_file).await?;
+    Ok(Some(format!(
+        "Named {} chapters from ChapterDB",
+        named.len()
+    )))
+}
+
+async fn preview_step(
+    app_handle: &AppHandle,
+    job: &Arc<RwLock<Job>>,
+    path: &Path,
+    video_title: String,
+    duration: Option<u64>,
+) -> Result<(), String> {
+    let duration = duration.ok_or("Title duration is unknown")?;
+    set_subtitle(app_handle, job, "Grabbing preview frames");
+    let images = preview::extract(app_handle, path, duration).await?;
+    job.write_or_recover().previews.push(TitlePreview {
+        title: video_title,
+        images,
+    });
+    Ok(())
+}
+
+fn replace_file(processed: &Path, path: &Path) -> Result<(), String> {
+    fs::rename(long_path::extend(processed), long_path::extend(path))
+        .map_err(|e| format!("Failed to replace {}: {e}", path.display()))
+}
+
+/// `Movie (2001).mkv` -> `Movie (2001).post.mkv` in the same directory so the final rename is atomic.
+fn temp_path(path: &Path) -> PathBuf {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("mkv");
+    path.with_extension(format!("post.{extension}"))
+}
+
+fn set_subtitle(app_handle: &AppHandle, job: &Arc<RwLock<Job>>, subtitle: &str) {
+    job.write_or_recover().subtitle = Some(subtitle.to_string());
+    emit_progress(app_handle, job, true);
+}
+
+fn add_notice(job: &Arc<RwLock<Job>>, notice: String) {
+    job.write_or_recover().add_notice(notice);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_path_keeps_extension() {
+        assert_eq!(
+            temp_path(Path::new("/movies/Alien (1979)/Alien (1979).mkv")),
+            PathBuf::from("/movies/Alien (1979)/Alien (1979).post.mkv")
+        );
+    }
+
+    #[test]
+    fn audio_track_only_when_requested() {
+        let mut options = PostProcessOptions::default();
+        assert_eq!(options.audio_track(), None);
+
+        options.normalize_loudness = true;
+        assert_eq!(
+            options.audio_track(),
+            Some(ffmpeg::AudioTrackOptions {
+                downmix_stereo: false,
+                normalize_loudness: true,
+            })
+        );
+    }
+
+    #[test]
+    fn only_standard_definition_is_dvd() {
+        let mut title = TitleInfo::new(1);
+        assert!(!is_dvd_source(Some(&title)));
+        assert!(!is_dvd_source(None));
+
+        title.video_size = Some("720x480".to_string());
+        assert!(is_dvd_source(Some(&title)));
+
+        title.video_size = Some("1920x1080".to_string());
+        assert!(!is_dvd_source(Some(&title)));
+    }
+}
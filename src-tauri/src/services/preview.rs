@@ -0,0 +1,56 @@
+use crate::services::ffmpeg;
+use base64::Engine;
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Number of frames grabbed from each ripped title.
+pub const FRAME_COUNT: u64 = 4;
+
+/// Grab `FRAME_COUNT` frames spread evenly through the title and return them as
+/// `data:` URIs so the job view can show them without access to the file system.
+pub async fn extract(
+    app_handle: &AppHandle,
+    path: &Path,
+    duration_seconds: u64,
+) -> Result<Vec<String>, String> {
+    let mut images = Vec::new();
+    for (index, seconds) in frame_times(duration_seconds).into_iter().enumerate() {
+        let frame = path.with_extension(format!("preview{index}.jpg"));
+        let result = ffmpeg::extract_frame(app_handle, path, seconds, &frame).await;
+        let bytes = result.and_then(|_| {
+            fs::read(&frame).map_err(|e| format!("Failed to read preview frame: {e}"))
+        });
+        let _ = fs::remove_file(&frame);
+        images.push(data_uri(&bytes?));
+    }
+    Ok(images)
+}
+
+/// Skips the very start and end so studio logos and credits are not picked.
+fn frame_times(duration_seconds: u64) -> Vec<u64> {
+    (1..=FRAME_COUNT)
+        .map(|n| duration_seconds * n / (FRAME_COUNT + 1))
+        .collect()
+}
+
+fn data_uri(jpeg: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(jpeg);
+    format!("data:image/jpeg;base64,{encoded}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_frames_through_the_title() {
+        assert_eq!(frame_times(100), vec![20, 40, 60, 80]);
+        assert_eq!(frame_times(0), vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn builds_jpeg_data_uri() {
+        assert_eq!(data_uri(b"foo"), "data:image/jpeg;base64,Zm9v");
+    }
+}
@@ -0,0 +1,346 @@
+use crate::services::network;
+use crate::state::job_state::Job;
+use crate::state::locks::RwLockExt;
+use crate::state::AppState;
+use log::{debug, error};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::Client;
+
+const NTFY_DEFAULT_SERVER: &str = "https://ntfy.sh";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Phone push services notifications are also sent to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PushService {
+    #[default]
+    Off,
+    Ntfy,
+    Gotify,
+}
+
+impl PushService {
+    pub fn from_store_value(value: Option<&str>) -> Self {
+        match value {
+            Some("ntfy") => PushService::Ntfy,
+            Some("gotify") => PushService::Gotify,
+            _ => PushService::Off,
+        }
+    }
+
+    pub fn to_store_value(self) -> &'static str {
+        match self {
+            PushService::Off => "off",
+            PushService::Ntfy => "ntfy",
+            PushService::Gotify => "gotify",
+        }
+    }
+}
+
+/// Where pushes go, everything is off by default.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PushOptions {
+    pub service: PushService,
+    /// ntfy defaults to https://ntfy.sh, Gotify needs its own server
+    pub server: Option<String>,
+    /// ntfy topic, not used by Gotify
+    pub topic: Option<String>,
+    /// ntfy access token or Gotify application token
+    pub token: Option<String>,
+    /// Minutes between progress pushes of a running rip, 0 only pushes notifications
+    pub progress_interval_minutes: u64,
+}
+
+impl PushOptions {
+    /// `None` while pushes are off or the service is missing a setting.
+    pub fn target(&self) -> Option<PushTarget> {
+        match self.service {
+            PushService::Off => None,
+            PushService::Ntfy => {
+                let server = self.server.as_deref().unwrap_or(NTFY_DEFAULT_SERVER);
+                let topic = self.topic.as_deref()?;
+                Some(PushTarget {
+                    url: format!("{}/{topic}", server.trim_end_matches('/')),
+                    service: PushService::Ntfy,
+                    token: self.token.clone(),
+                })
+            }
+            PushService::Gotify => {
+                let server = self.server.as_deref()?;
+                Some(PushTarget {
+                    url: format!("{}/message", server.trim_end_matches('/')),
+                    service: PushService::Gotify,
+                    token: Some(self.token.clone()?),
+                })
+            }
+        }
+    }
+
+    pub fn progress_interval(&self) -> Option<Duration> {
+        if self.progress_interval_minutes == 0 {
+            return None;
+        }
+        Some(Duration::from_secs(self.progress_interval_minutes * 60))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PushTarget {
+    pub url: String,
+    pub service: PushService,
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushPriority {
+    Low,
+    Default,
+    High,
+}
+
+impl PushPriority {
+    /// ntfy goes from 1 to 5
+    fn ntfy(self) -> &'static str {
+        match self {
+            PushPriority::Low => "2",
+            PushPriority::Default => "3",
+            PushPriority::High => "4",
+        }
+    }
+
+    /// Gotify goes from 0 to 10
+    fn gotify(self) -> u8 {
+        match self {
+            PushPriority::Low => 2,
+            PushPriority::Default => 5,
+            PushPriority::High => 8,
+        }
+    }
+}
+
+pub async fn send(
+    client: &Client,
+    target: &PushTarget,
+    title: &str,
+    body: &str,
+    priority: PushPriority,
+) -> Result<(), String> {
+    let request = match target.service {
+        PushService::Ntfy => {
+            let request = client
+                .post(&target.url)
+                .header("Title", title)
+                .header("Priority", priority.ntfy())
+                .body(body.to_string());
+            match &target.token {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            }
+        }
+        PushService::Gotify => client
+            .post(&target.url)
+            .header("X-Gotify-Key", target.token.as_deref().unwrap_or_default())
+            .header("Content-Type", "application/json")
+            .body(
+                serde_json::json!({
+                    "title": title,
+                    "message": body,
+                    "priority": priority.gotify(),
+                })
+                .to_string(),
+            ),
+        PushService::Off => return Ok(()),
+    };
+
+    let response = request
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach {}: {e}", target.url))?;
+    if !response.status().is_success() {
+        return Err(format!("{} returned {}", target.url, response.status()));
+    }
+    Ok(())
+}
+
+/// Push in the background, a phone that can't be reached never holds up a rip.
+pub fn spawn_push(app_handle: &AppHandle, title: String, body: String, priority: PushPriority) {
+    if network::is_offline(app_handle) {
+        return;
+    }
+    let Some(target) = app_handle.state::<AppState>().push_options().target() else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = send(&Client::new(), &target, &title, &body, priority).await {
+            error!("Push notification failed: {e}");
+        }
+    });
+}
+
+/// e.g. "45.2% • 12 min left"
+fn progress_body(job: &Job) -> String {
+    format!(
+        "{} • {}",
+        job.overall_progress_formatted_percentage(),
+        job.progress.eta
+    )
+}
+
+/// Push the progress of `job` every `progress_interval_minutes` until it completes.
+pub fn spawn_progress_pushes(app_handle: &AppHandle, job: &Arc<RwLock<Job>>) {
+    let Some(interval) = app_handle
+        .state::<AppState>()
+        .push_options()
+        .progress_interval()
+    else {
+        return;
+    };
+    let app_handle = app_handle.clone();
+    let job = job.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let (title, body) = {
+                let job = job.read_or_recover();
+                if job.is_completed() {
+                    debug!("Job {} completed, no more progress pushes", job.id);
+                    return;
+                }
+                let title = job
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| job.job_type.to_string());
+                (format!("Ripping {title}"), progress_body(&job))
+            };
+            spawn_push(&app_handle, title, body, PushPriority::Low);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn ntfy_defaults_to_ntfy_sh() {
+        let options = PushOptions {
+            service: PushService::Ntfy,
+            topic: Some("reelix".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.target().map(|target| target.url),
+            Some("https://ntfy.sh/reelix".to_string())
+        );
+    }
+
+    #[test]
+    fn incomplete_settings_have_no_target() {
+        let mut options = PushOptions {
+            service: PushService::Ntfy,
+            ..Default::default()
+        };
+        assert_eq!(options.target(), None);
+
+        options.service = PushService::Gotify;
+        options.server = Some("https://gotify.example.com/".to_string());
+        assert_eq!(options.target(), None);
+
+        options.token = Some("app-token".to_string());
+        assert_eq!(
+            options.target().map(|target| target.url),
+            Some("https://gotify.example.com/message".to_string())
+        );
+    }
+
+    #[test]
+    fn progress_interval_off_by_default() {
+        let mut options = PushOptions::default();
+        assert_eq!(options.progress_interval(), None);
+        options.progress_interval_minutes = 5;
+        assert_eq!(options.progress_interval(), Some(Duration::from_secs(300)));
+    }
+
+    #[tokio::test]
+    async fn sends_ntfy_messages_with_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/reelix"))
+            .and(header("Title", "Finished Ripping Alien"))
+            .and(header("Priority", "3"))
+            .and(body_string("Alien (1979)"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let target = PushOptions {
+            service: PushService::Ntfy,
+            server: Some(server.uri()),
+            topic: Some("reelix".to_string()),
+            ..Default::default()
+        }
+        .target()
+        .unwrap();
+        let result = send(
+            &Client::new(),
+            &target,
+            "Finished Ripping Alien",
+            "Alien (1979)",
+            PushPriority::Default,
+        )
+        .await;
+        assert_eq!(result, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn sends_gotify_messages_with_the_app_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/message"))
+            .and(header("X-Gotify-Key", "app-token"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let target = PushOptions {
+            service: PushService::Gotify,
+            server: Some(server.uri()),
+            token: Some("app-token".to_string()),
+            ..Default::default()
+        }
+        .target()
+        .unwrap();
+        let result = send(
+            &Client::new(),
+            &target,
+            "Failed",
+            "Boom",
+            PushPriority::High,
+        )
+        .await;
+        assert_eq!(result, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn reports_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let target = PushTarget {
+            url: format!("{}/reelix", server.uri()),
+            service: PushService::Ntfy,
+            token: Some("wrong".to_string()),
+        };
+        let result = send(&Client::new(), &target, "Title", "Body", PushPriority::Low).await;
+        assert!(result.unwrap_err().contains("401"));
+    }
+}
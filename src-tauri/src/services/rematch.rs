@@ -0,0 +1,104 @@
+//! Fixing a rip that was matched to the wrong movie. The files are renamed after the right
+//! movie in the library and on the FTP server, a file the server doesn't have under its old
+//! name is uploaded again.
+use crate::services::ftp_uploader;
+use crate::services::{file_move, long_path};
+use crate::state::job_state::Job;
+use crate::state::locks::RwLockExt;
+use crate::state::title_video::{TitleVideo, Video};
+use crate::state::uploaded_state::UploadedState;
+use crate::state::AppState;
+use crate::the_movie_db::MovieResponse;
+use log::{debug, warn};
+use std::fs;
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager};
+
+/// A video that was renamed after its new movie
+pub struct Rematched {
+    pub title_video: Arc<RwLock<TitleVideo>>,
+    /// The server didn't get the renamed file, it has to be uploaded again
+    pub reupload: bool,
+}
+
+/// Renames the movies the rip finished after `movie`, parts and editions are kept.
+///
+/// Stops at the first file that can't be moved, the files moved before it keep their new
+/// name.
+pub fn rematch_movie(
+    app_handle: &AppHandle,
+    job: &Arc<RwLock<Job>>,
+    movie: &MovieResponse,
+) -> Result<Vec<Rematched>, String> {
+    let state = app_handle.state::<AppState>();
+    let uploaded_state = app_handle.state::<UploadedState>();
+    let uploads_to_ftp = state.lock_ftp_host().is_some();
+    let mut rematched = Vec::new();
+
+    let title_videos = job.read_or_recover().rematchable_title_videos();
+    for title_video in title_videos {
+        let multiple_parts = job
+            .read_or_recover()
+            .has_multiple_parts(&title_video.read_or_recover());
+        let (old_path, old_upload_path) = {
+            let title_video = title_video.read_or_recover();
+            (
+                title_video.local_video_path(&state, multiple_parts),
+                title_video.upload_file_path(&state, multiple_parts),
+            )
+        };
+        {
+            let mut title_video = title_video.write_or_recover();
+            if let Video::Movie(ref mut movie_part_edition) = title_video.video {
+                movie_part_edition.movie = movie.clone();
+            }
+            title_video.video_file = None;
+        }
+        let (new_path, new_upload_path) = {
+            let title_video = title_video.read_or_recover();
+            (
+                title_video.video_path(&state, multiple_parts),
+                title_video.upload_file_path(&state, multiple_parts),
+            )
+        };
+
+        if new_path == old_path {
+            continue;
+        }
+        if let Some(dir) = new_path.parent() {
+            fs::create_dir_all(long_path::extend(dir))
+                .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        let moved_to = file_move::move_file(&old_path, &new_path, |_, _| {})?;
+        debug!("Rematched {} to {}", old_path.display(), moved_to.display());
+        title_video.write_or_recover().video_file = Some(moved_to.clone());
+        if let Some(dir) = old_path
+            .parent()
+            .filter(|dir| Some(*dir) != moved_to.parent())
+        {
+            // Only an empty folder is removed, extras the user added stay
+            let _ = fs::remove_dir(long_path::extend(dir));
+        }
+
+        // A queued upload would look for the old file, it uploads the new one instead
+        let old_path_str = old_path.to_string_lossy().to_string();
+        let mut reupload = uploaded_state.find(&old_path_str).is_some();
+        if reupload {
+            uploaded_state.remove_upload(app_handle, &old_path_str)?;
+        } else if let (true, Some(from), Some(to)) =
+            (uploads_to_ftp, old_upload_path, new_upload_path)
+        {
+            if from != to {
+                if let Err(e) = ftp_uploader::rename_remote_file(&state, &from, &to) {
+                    warn!("{e}, uploading {} again", moved_to.display());
+                    reupload = true;
+                }
+            }
+        }
+        rematched.push(Rematched {
+            title_video,
+            reupload,
+        });
+    }
+    Ok(rematched)
+}
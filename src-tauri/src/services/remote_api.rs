@@ -0,0 +1,447 @@
+//! A small HTTP server for dashboards on other machines (Homepage, Grafana through a bridge)
+//! that want to follow the jobs without polling the app.
+//!
+//! `GET /api/jobs/events` is a server-sent event stream. It starts with every job, after that
+//! a `job` event only carries the fields of a job that changed and a `removed` event the id of
+//! a job that was deleted.
+//!
+//! `GET /metrics` has counters and gauges in the Prometheus text format when `metrics` is on,
+//! see `metrics`.
+//!
+//! The server is off by default. When a token is set every request needs it, as an
+//! `Authorization: Bearer` header or a `token` query parameter, `EventSource` can't set headers.
+//! Without a token the server only starts on a loopback address, and browsers on other origins
+//! are not allowed to read the stream.
+use crate::services::metrics;
+use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::job_state::{Job, JobType};
+use crate::state::AppState;
+use log::{debug, error, info};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const EVENT_INTERVAL: Duration = Duration::from_secs(1);
+/// Proxies close streams that stay quiet, a comment is sent when nothing changed for a while
+const KEEP_ALIVE_TICKS: u32 = 15;
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+/// A client that hasn't sent its headers by then is dropped
+const READ_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteApiOptions {
+    pub enabled: bool,
+    /// `127.0.0.1` keeps the API on this machine, `0.0.0.0` opens it to the network
+    pub bind_address: String,
+    pub port: u16,
+    pub token: Option<String>,
+    /// Serve `/metrics` for Prometheus
+    pub metrics: bool,
+}
+
+impl Default for RemoteApiOptions {
+    fn default() -> Self {
+        RemoteApiOptions {
+            enabled: false,
+            bind_address: "127.0.0.1".to_string(),
+            port: 8673,
+            token: None,
+            metrics: false,
+        }
+    }
+}
+
+impl RemoteApiOptions {
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.bind_address, self.port)
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        self.bind_address.eq_ignore_ascii_case("localhost")
+            || self
+                .bind_address
+                .parse::<IpAddr>()
+                .is_ok_and(|address| address.is_loopback())
+    }
+
+    /// Without a token anyone who can reach the port could follow the jobs
+    pub fn can_serve(&self) -> bool {
+        self.token.is_some() || self.is_loopback()
+    }
+}
+
+/// What a dashboard gets to see of a job
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct JobSummary {
+    pub id: u64,
+    pub job_type: JobType,
+    pub status: String,
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub message: Option<String>,
+    pub percent: f32,
+    pub overall_percent: f64,
+    pub eta: String,
+}
+
+impl JobSummary {
+    pub fn of(job: &Job) -> Self {
+        JobSummary {
+            id: job.id.into(),
+            job_type: job.job_type.clone(),
+            status: job.status.to_string(),
+            title: job.title.clone(),
+            subtitle: job.subtitle.clone(),
+            message: job.message.clone(),
+            percent: job.progress.percent,
+            overall_percent: job.overall_progress_percent(),
+            eta: job.progress.eta.clone(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub token: Option<String>,
+}
+
+/// Parses the request line and the headers, the API has no request bodies.
+pub fn parse_request(head: &str) -> Option<Request> {
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut token = url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "token")
+        .map(|(_, value)| value.into_owned());
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if name.trim().eq_ignore_ascii_case("authorization") {
+            if let Some(bearer) = value.trim().strip_prefix("Bearer ") {
+                token = Some(bearer.trim().to_string());
+            }
+        }
+    }
+    Some(Request {
+        method,
+        path: path.to_string(),
+        token,
+    })
+}
+
+pub fn authorized(options: &RemoteApiOptions, request: &Request) -> bool {
+    match &options.token {
+        Some(token) => request
+            .token
+            .as_deref()
+            .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes())),
+        None => true,
+    }
+}
+
+/// Looks at every byte whatever the first difference, so the time taken says nothing about
+/// how much of a guessed token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b)
+        .fold(0, |difference, (a, b)| difference | (a ^ b))
+        == 0
+}
+
+/// The fields of `current` that are different from `previous`, always with the id.
+/// `None` when nothing changed.
+pub fn delta(previous: Option<&Value>, current: &Value) -> Option<Value> {
+    let Some(previous) = previous else {
+        return Some(current.clone());
+    };
+    let (Value::Object(previous), Value::Object(current)) = (previous, current) else {
+        return (previous != current).then(|| current.clone());
+    };
+    let mut changed: Map<String, Value> = current
+        .iter()
+        .filter(|(key, value)| previous.get(*key) != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if changed.is_empty() {
+        return None;
+    }
+    if let Some(id) = current.get("id") {
+        changed.insert("id".to_string(), id.clone());
+    }
+    Some(Value::Object(changed))
+}
+
+/// The events that take a dashboard from `previous` to `current`, both keyed by job id.
+pub fn job_events(previous: &BTreeMap<u64, Value>, current: &BTreeMap<u64, Value>) -> String {
+    let mut events = String::new();
+    for (id, job) in current {
+        if let Some(changed) = delta(previous.get(id), job) {
+            events.push_str(&format!("event: job\ndata: {changed}\n\n"));
+        }
+    }
+    for id in previous.keys().filter(|id| !current.contains_key(id)) {
+        events.push_str(&format!("event: removed\ndata: {{\"id\":{id}}}\n\n"));
+    }
+    events
+}
+
+fn job_values(app_handle: &AppHandle) -> BTreeMap<u64, Value> {
+    app_handle
+        .state::<BackgroundProcessState>()
+        .clone_all_jobs()
+        .iter()
+        .map(|job| {
+            let summary = JobSummary::of(job);
+            (
+                summary.id,
+                serde_json::to_value(&summary).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+/// Runs the API for as long as the app runs, changed options apply on the next start.
+pub async fn serve(app_handle: AppHandle) {
+    let options = app_handle.state::<AppState>().remote_api_options();
+    if !options.enabled {
+        return;
+    }
+    if !options.can_serve() {
+        error!(
+            "Not starting the remote API on {}, set a token to open it beyond this machine",
+            options.address()
+        );
+        return;
+    }
+    let listener = match TcpListener::bind(options.address()).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!(
+                "Failed to start the remote API on {}: {e}",
+                options.address()
+            );
+            return;
+        }
+    };
+    info!("Remote API listening on {}", options.address());
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                error!("Remote API failed to accept a connection: {e}");
+                continue;
+            }
+        };
+        debug!("Remote API connection from {peer}");
+        let app_handle = app_handle.clone();
+        let options = options.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle(app_handle, &options, stream).await {
+                debug!("Remote API connection from {peer} ended: {e}");
+            }
+        });
+    }
+}
+
+async fn handle(
+    app_handle: AppHandle,
+    options: &RemoteApiOptions,
+    mut stream: TcpStream,
+) -> Result<(), String> {
+    let head = read_head(&mut stream).await?;
+    let Some(request) = parse_request(&head) else {
+        return respond(&mut stream, "400 Bad Request", "text/plain", "Bad request").await;
+    };
+    if !authorized(options, &request) {
+        return respond(
+            &mut stream,
+            "401 Unauthorized",
+            "text/plain",
+            "Unauthorized",
+        )
+        .await;
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/api/jobs/events") => stream_job_events(&app_handle, options, &mut stream).await,
+        ("GET", "/metrics") if options.metrics => {
+            let text = metrics::render(&metrics::Gauges::of(&app_handle));
+            respond(&mut stream, "200 OK", "text/plain; version=0.0.4", &text).await
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "Not found").await,
+    }
+}
+
+async fn read_head(stream: &mut TcpStream) -> Result<String, String> {
+    tokio::time::timeout(READ_HEAD_TIMEOUT, read_head_bytes(stream))
+        .await
+        .map_err(|_| "Timed out reading the request headers".to_string())?
+}
+
+async fn read_head_bytes(stream: &mut TcpStream) -> Result<String, String> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_BYTES {
+            return Err("Request headers are too large".to_string());
+        }
+        let read = stream.read(&mut buffer).await.map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> Result<(), String> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    write(stream, &response).await
+}
+
+async fn write(stream: &mut TcpStream, text: &str) -> Result<(), String> {
+    stream
+        .write_all(text.as_bytes())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Only with a token can a dashboard on another origin read the stream, without one any page
+/// open in the browser could.
+fn event_stream_head(options: &RemoteApiOptions) -> String {
+    let cors = if options.token.is_some() {
+        "Access-Control-Allow-Origin: *\r\n"
+    } else {
+        ""
+    };
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n{cors}\r\n"
+    )
+}
+
+async fn stream_job_events(
+    app_handle: &AppHandle,
+    options: &RemoteApiOptions,
+    stream: &mut TcpStream,
+) -> Result<(), String> {
+    write(stream, &event_stream_head(options)).await?;
+
+    let mut sent = BTreeMap::new();
+    let mut quiet_ticks = 0;
+    loop {
+        let current = job_values(app_handle);
+        let events = job_events(&sent, &current);
+        if !events.is_empty() {
+            write(stream, &events).await?;
+            quiet_ticks = 0;
+        } else {
+            quiet_ticks += 1;
+            if quiet_ticks >= KEEP_ALIVE_TICKS {
+                write(stream, ": keep-alive\n\n").await?;
+                quiet_ticks = 0;
+            }
+        }
+        sent = current;
+        tokio::time::sleep(EVENT_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_the_token_from_the_header_or_the_query() {
+        let request =
+            parse_request("GET /api/jobs/events?token=abc HTTP/1.1\r\nHost: reelix\r\n\r\n")
+                .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/api/jobs/events");
+        assert_eq!(request.token.as_deref(), Some("abc"));
+
+        let request =
+            parse_request("GET /api/jobs/events HTTP/1.1\r\nauthorization: Bearer xyz\r\n\r\n")
+                .unwrap();
+        assert_eq!(request.token.as_deref(), Some("xyz"));
+
+        let options = RemoteApiOptions {
+            token: Some("abc".to_string()),
+            ..RemoteApiOptions::default()
+        };
+        assert!(!authorized(&options, &request));
+        assert!(authorized(&RemoteApiOptions::default(), &request));
+        assert_eq!(parse_request(""), None);
+    }
+
+    #[test]
+    fn decodes_the_query_token() {
+        let request = parse_request("GET /api/jobs?token=a%2Bb%3D%3D HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(request.token.as_deref(), Some("a+b=="));
+
+        let options = RemoteApiOptions {
+            token: Some("a+b==".to_string()),
+            ..RemoteApiOptions::default()
+        };
+        assert!(authorized(&options, &request));
+        assert!(!constant_time_eq(b"a+b==", b"a+b="));
+        assert!(!constant_time_eq(b"a+b==", b"a+b=A"));
+    }
+
+    #[test]
+    fn needs_a_token_off_loopback() {
+        let mut options = RemoteApiOptions::default();
+        assert!(options.can_serve());
+        assert!(!event_stream_head(&options).contains("Access-Control-Allow-Origin"));
+
+        options.bind_address = "0.0.0.0".to_string();
+        assert!(!options.can_serve());
+        options.bind_address = "::1".to_string();
+        assert!(options.can_serve());
+        options.bind_address = "192.168.1.20".to_string();
+        options.token = Some("abc".to_string());
+        assert!(options.can_serve());
+        assert!(event_stream_head(&options).contains("Access-Control-Allow-Origin: *"));
+    }
+
+    #[test]
+    fn sends_only_what_changed() {
+        let previous = BTreeMap::from([
+            (1, json!({"id": 1, "percent": 10.0, "eta": "00:10:00"})),
+            (2, json!({"id": 2, "percent": 50.0, "eta": "00:01:00"})),
+        ]);
+        let current = BTreeMap::from([
+            (1, json!({"id": 1, "percent": 20.0, "eta": "00:10:00"})),
+            (3, json!({"id": 3, "percent": 0.0})),
+        ]);
+
+        assert_eq!(
+            job_events(&previous, &current),
+            "event: job\ndata: {\"id\":1,\"percent\":20.0}\n\n\
+             event: job\ndata: {\"id\":3,\"percent\":0.0}\n\n\
+             event: removed\ndata: {\"id\":2}\n\n"
+        );
+        assert_eq!(job_events(&current, &current), "");
+    }
+}
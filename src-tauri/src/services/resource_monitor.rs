@@ -0,0 +1,268 @@
+//! CPU, memory and disk load of the machine while a rip runs, to tell why a rip or its
+//! post-processing was slow on one machine and not on another.
+//!
+//! The whole machine is sampled every `SAMPLE_INTERVAL`, not only makemkvcon and ffmpeg, a
+//! backup running next to the rip is what slowed it down. The average and peak are added to
+//! the job notices and kept in "resource_stats.json" for the last `KEPT_JOBS` jobs.
+use crate::services::{locale, unix_time};
+use crate::state::job_state::Job;
+use crate::state::locks::{MutexExt, RwLockExt};
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use sysinfo::{Disks, System};
+use tauri::async_runtime::JoinHandle;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+const STORE_FILE: &str = "resource_stats.json";
+const KEPT_JOBS: usize = 100;
+
+/// The average and the highest sample of one value
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Reading {
+    pub average: f64,
+    pub peak: f64,
+}
+
+#[derive(Default)]
+struct Series {
+    total: f64,
+    peak: f64,
+    count: u64,
+}
+
+impl Series {
+    fn add(&mut self, value: f64) {
+        self.total += value;
+        self.peak = self.peak.max(value);
+        self.count += 1;
+    }
+
+    fn reading(&self) -> Reading {
+        Reading {
+            average: self.total / self.count.max(1) as f64,
+            peak: self.peak,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Samples {
+    cpu_percent: Series,
+    memory_bytes: Series,
+    disk_bytes_per_second: Series,
+}
+
+impl Samples {
+    fn add(&mut self, cpu_percent: f64, memory_bytes: f64, disk_bytes_per_second: f64) {
+        self.cpu_percent.add(cpu_percent);
+        self.memory_bytes.add(memory_bytes);
+        self.disk_bytes_per_second.add(disk_bytes_per_second);
+    }
+
+    fn usage(&self) -> Option<ResourceUsage> {
+        (self.cpu_percent.count > 0).then(|| ResourceUsage {
+            cpu_percent: self.cpu_percent.reading(),
+            memory_bytes: self.memory_bytes.reading(),
+            disk_bytes_per_second: self.disk_bytes_per_second.reading(),
+        })
+    }
+}
+
+/// The load of the machine over one job
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResourceUsage {
+    pub cpu_percent: Reading,
+    /// Memory in use on the whole machine
+    pub memory_bytes: Reading,
+    /// Read and written together over all disks
+    pub disk_bytes_per_second: Reading,
+}
+
+impl ResourceUsage {
+    /// e.g. "CPU 42% (peak 97%), memory 5.1 GB (peak 6.0 GB), disk 21.3 MB/s (peak 38.0 MB/s)"
+    pub fn summary(&self) -> String {
+        let locale = locale::current();
+        let size = |bytes: f64| locale.format_size(bytes as u64);
+        format!(
+            "CPU {:.0}% (peak {:.0}%), memory {} (peak {}), disk {} (peak {})",
+            self.cpu_percent.average,
+            self.cpu_percent.peak,
+            size(self.memory_bytes.average),
+            size(self.memory_bytes.peak),
+            locale.format_speed(self.disk_bytes_per_second.average),
+            locale.format_speed(self.disk_bytes_per_second.peak),
+        )
+    }
+}
+
+/// One job in "resource_stats.json"
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResourceRecord {
+    pub job_type: String,
+    pub title: Option<String>,
+    /// Seconds since the Unix epoch
+    pub finished_at: u64,
+    pub seconds: u64,
+    pub usage: ResourceUsage,
+}
+
+/// Bytes read and written since the disks were listed
+fn disk_io_bytes(disks: &Disks) -> u64 {
+    disks
+        .list()
+        .iter()
+        .map(|disk| {
+            let usage = disk.usage();
+            usage.total_read_bytes + usage.total_written_bytes
+        })
+        .sum()
+}
+
+/// Samples the machine until `finish` is called or it is dropped.
+pub struct Monitor {
+    samples: Arc<Mutex<Samples>>,
+    task: JoinHandle<()>,
+    started: Instant,
+}
+
+pub fn start() -> Monitor {
+    let samples = Arc::new(Mutex::new(Samples::default()));
+    let task = tauri::async_runtime::spawn({
+        let samples = samples.clone();
+        async move {
+            let mut system = System::new();
+            let mut disks = Disks::new_with_refreshed_list();
+            // CPU usage is measured between two refreshes
+            system.refresh_cpu_usage();
+            let mut last_io = disk_io_bytes(&disks);
+            loop {
+                tokio::time::sleep(SAMPLE_INTERVAL).await;
+                system.refresh_cpu_usage();
+                system.refresh_memory();
+                disks.refresh(true);
+                let io = disk_io_bytes(&disks);
+                let disk_rate = io.saturating_sub(last_io) as f64 / SAMPLE_INTERVAL.as_secs_f64();
+                last_io = io;
+                samples.lock_or_recover().add(
+                    f64::from(system.global_cpu_usage()),
+                    system.used_memory() as f64,
+                    disk_rate,
+                );
+            }
+        }
+    });
+    Monitor {
+        samples,
+        task,
+        started: Instant::now(),
+    }
+}
+
+impl Monitor {
+    /// Stops sampling, adds the usage to the job notices and keeps it in the store. Jobs
+    /// shorter than one sample have nothing to show.
+    pub fn finish(self, app_handle: &AppHandle, job: &Arc<RwLock<Job>>) {
+        let Some(usage) = self.samples.lock_or_recover().usage() else {
+            return;
+        };
+        let record = {
+            let mut job = job.write_or_recover();
+            job.add_notice(format!("Resources: {}", usage.summary()));
+            ResourceRecord {
+                job_type: job.job_type.to_string(),
+                title: job.title.clone(),
+                finished_at: unix_time::now(),
+                seconds: self.started.elapsed().as_secs(),
+                usage,
+            }
+        };
+        debug!(
+            "Resources of {:?}: {}",
+            record.title,
+            record.usage.summary()
+        );
+        if let Err(e) = persist(app_handle, record) {
+            error!("Failed to keep the resource usage: {e}");
+        }
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn load(app_handle: &AppHandle) -> Result<Vec<ResourceRecord>, String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to load {STORE_FILE} store: {e}"))?;
+    let records = match store.get("jobs") {
+        Some(value) => serde_json::from_value(value.clone()).unwrap_or_default(),
+        None => Vec::new(),
+    };
+    store.close_resource();
+    Ok(records)
+}
+
+fn push(records: &mut Vec<ResourceRecord>, record: ResourceRecord) {
+    records.push(record);
+    let excess = records.len().saturating_sub(KEPT_JOBS);
+    records.drain(..excess);
+}
+
+fn persist(app_handle: &AppHandle, record: ResourceRecord) -> Result<(), String> {
+    let mut records = load(app_handle)?;
+    push(&mut records, record);
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to open {STORE_FILE} store: {e}"))?;
+    store.set("jobs", json!(records));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save {STORE_FILE} store: {e}"))?;
+    store.close_resource();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_samples_and_keeps_the_last_jobs() {
+        let mut samples = Samples::default();
+        assert!(samples.usage().is_none());
+        samples.add(20.0, 4e9, 10e6);
+        samples.add(80.0, 6e9, 30e6);
+        let usage = samples.usage().unwrap();
+        assert_eq!(
+            usage.cpu_percent,
+            Reading {
+                average: 50.0,
+                peak: 80.0
+            }
+        );
+        assert_eq!(usage.memory_bytes.peak, 6e9);
+        assert_eq!(usage.disk_bytes_per_second.average, 20e6);
+
+        let mut records = Vec::new();
+        for seconds in 0..=KEPT_JOBS as u64 {
+            let record = ResourceRecord {
+                job_type: "Ripping".to_string(),
+                title: None,
+                finished_at: 0,
+                seconds,
+                usage: usage.clone(),
+            };
+            push(&mut records, record);
+        }
+        assert_eq!(records.len(), KEPT_JOBS);
+        assert_eq!(records[0].seconds, 1);
+    }
+}
@@ -0,0 +1,94 @@
+//! What a failed title means for the rest of the disc. A drive that was busy for a moment gets
+//! the title ripped again, a title makemkvcon can't save is skipped and the next title ripped,
+//! a disc makemkvcon can't read at all stops the rip since every other title would fail too.
+//!
+//! makemkvcon only reports text, the kinds are told apart by what its messages say.
+
+/// Transient failures are ripped this often before the title is given up
+pub const MAX_TITLE_ATTEMPTS: usize = 2;
+
+/// The drive or the disc can't be read, no title of the disc will rip
+const DISC_PATTERNS: &[&str] = &[
+    "failed to open disc",
+    "no disc",
+    "medium not present",
+    "drive not ready",
+    "can't open drive",
+    "evaluation period",
+    "registration key",
+    "not a mounted disc",
+    // makemkvcon.rs when the binary can't be run, e.g. "Failed to start local makemkvcon: ..."
+    "failed to start local makemkvcon",
+    "failed to start makemkvcon",
+    "failed to get makemkvcon",
+];
+
+/// The disc is damaged where the title is, reading it again gives the same error
+const TITLE_PATTERNS: &[&str] = &["medium error", "uncorrectable"];
+
+/// The drive was busy or slow to answer, the same title usually rips on the next try
+const TRANSIENT_PATTERNS: &[&str] = &[
+    "timeout",
+    "timed out",
+    "device or resource busy",
+    "resource temporarily unavailable",
+    "scsi error",
+    "i/o error",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RipFailure {
+    /// Rip the title again
+    Transient,
+    /// Skip to the next title
+    Title,
+    /// Stop ripping the disc
+    Disc,
+}
+
+impl RipFailure {
+    pub fn classify(message: &str) -> Self {
+        let message = message.to_lowercase();
+        let matches = |patterns: &[&str]| patterns.iter().any(|pattern| message.contains(pattern));
+        if matches(DISC_PATTERNS) {
+            RipFailure::Disc
+        } else if matches(TITLE_PATTERNS) {
+            RipFailure::Title
+        } else if matches(TRANSIENT_PATTERNS) {
+            RipFailure::Transient
+        } else {
+            RipFailure::Title
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_disc_title_and_transient_failures_apart() {
+        assert_eq!(
+            RipFailure::classify("Failed to open disc"),
+            RipFailure::Disc
+        );
+        assert_eq!(
+            RipFailure::classify("Failed to start local makemkvcon: No such file or directory"),
+            RipFailure::Disc
+        );
+        assert_eq!(
+            RipFailure::classify(
+                "Error 'Scsi error - MEDIUM ERROR:L-EC UNCORRECTABLE ERROR' occurred while reading"
+            ),
+            RipFailure::Title
+        );
+        assert_eq!(
+            RipFailure::classify("Error 'Scsi error - UNIT ATTENTION' occurred while reading"),
+            RipFailure::Transient
+        );
+        assert_eq!(
+            RipFailure::classify("Failed to save title 3 to file /tmp/title_t03.mkv"),
+            RipFailure::Title
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! A rip plan is the list of movies and seasons for a stack of discs, worked off one disc at a time.
+//!
+//! Every inserted disc is matched to the plan by its volume label. Movies are queued for
+//! auto-rip like `set_auto_rip` does and checked off once the rip finished, seasons only get
+//! the disc noted because their episodes still have to be assigned by hand.
+//!
+//! With more than one drive a season keeps to the drive its first disc went in, see
+//! `DriveAffinity`, so two stacks can be worked off side by side.
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::services::plex::find_movie;
+use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::job_state::{Job, JobStatus, JobType};
+use crate::state::locks::RwLockExt;
+use crate::state::title_video::{MoviePartEdition, Video};
+use crate::state::AppState;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager};
+
+/// Label words that say what kind of disc it is, never which movie.
+const LABEL_NOISE: [&str; 12] = [
+    "DISC", "DISK", "DVD", "BD", "BLURAY", "VIDEO", "WS", "FS", "NTSC", "PAL", "SE", "THE",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum PlanKind {
+    Movie,
+    Season,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PlanStatus {
+    #[default]
+    Pending,
+    /// A disc was matched to it and is being ripped
+    Queued,
+    Done,
+}
+
+/// Which drive the discs of a planned season go in, drives are told apart by `drive_key`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DriveAffinity {
+    /// Pinned to the drive of the first disc matched to it
+    #[default]
+    FirstDisc,
+    Any,
+    Drive(String),
+}
+
+impl DriveAffinity {
+    /// Every drive is accepted until the item is pinned, `None` is a drive not known yet
+    pub fn accepts(&self, drive: Option<&str>) -> bool {
+        match (self, drive) {
+            (DriveAffinity::Drive(pinned), Some(drive)) => pinned == drive,
+            _ => true,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            DriveAffinity::FirstDisc => "Drive of the first disc".to_string(),
+            DriveAffinity::Any => "Any drive".to_string(),
+            DriveAffinity::Drive(drive) => drive.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PlanItem {
+    pub id: u64,
+    pub kind: PlanKind,
+    pub mvdb_id: u32,
+    #[serde(default)]
+    pub season_number: Option<u32>,
+    /// `Alien (1979)` or `Firefly (2002) Season 1`
+    pub title: String,
+    #[serde(default)]
+    pub status: PlanStatus,
+    /// Label of the disc last matched to it
+    #[serde(default)]
+    pub disc_name: Option<String>,
+    /// Seasons only, movies are ripped from whichever drive has them
+    #[serde(default)]
+    pub drive: DriveAffinity,
+}
+
+impl PlanItem {
+    pub fn is_done(&self) -> bool {
+        self.status == PlanStatus::Done
+    }
+
+    pub fn is_season(&self) -> bool {
+        self.kind == PlanKind::Season
+    }
+
+    pub fn status_label(&self) -> &'static str {
+        match self.status {
+            PlanStatus::Pending => "Waiting for disc",
+            PlanStatus::Queued => "Ripping",
+            PlanStatus::Done => "Done",
+        }
+    }
+
+    /// The title without the year and season, the words a disc label could have.
+    fn name_words(&self) -> Vec<String> {
+        let name = self.title.split(" (").next().unwrap_or(&self.title);
+        words(name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RipPlan {
+    pub items: Vec<PlanItem>,
+}
+
+impl RipPlan {
+    pub fn add(
+        &mut self,
+        kind: PlanKind,
+        mvdb_id: u32,
+        season_number: Option<u32>,
+        title: String,
+    ) -> Result<u64, String> {
+        let planned = self.items.iter().any(|item| {
+            item.kind == kind && item.mvdb_id == mvdb_id && item.season_number == season_number
+        });
+        if planned {
+            return Err(format!("{title} is already in the rip plan"));
+        }
+        let id = self.items.iter().map(|item| item.id).max().unwrap_or(0) + 1;
+        self.items.push(PlanItem {
+            id,
+            kind,
+            mvdb_id,
+            season_number,
+            title,
+            status: PlanStatus::Pending,
+            disc_name: None,
+            drive: DriveAffinity::default(),
+        });
+        Ok(id)
+    }
+
+    pub fn remove(&mut self, id: u64) -> bool {
+        let count = self.items.len();
+        self.items.retain(|item| item.id != id);
+        self.items.len() != count
+    }
+
+    pub fn set_status(&mut self, id: u64, status: PlanStatus) -> bool {
+        match self.items.iter_mut().find(|item| item.id == id) {
+            Some(item) => {
+                item.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear_done(&mut self) {
+        self.items.retain(|item| !item.is_done());
+    }
+
+    /// The pending item whose title matches the label. Labels like `DVD_VIDEO` say nothing,
+    /// those take the next pending item, the stack is expected to be in plan order. Seasons
+    /// pinned to another drive are skipped.
+    pub fn match_disc(&self, disc_name: &str, drive: Option<&str>) -> Option<&PlanItem> {
+        let mut pending = self
+            .items
+            .iter()
+            .filter(|item| item.status == PlanStatus::Pending)
+            .filter(|item| !item.is_season() || item.drive.accepts(drive));
+        let label = label_words(disc_name);
+        if label.is_empty() {
+            return pending.next();
+        }
+        pending.find(|item| {
+            let name = item.name_words();
+            !name.is_empty()
+                && (label.iter().all(|word| name.contains(word))
+                    || name
+                        .iter()
+                        .filter(|word| !LABEL_NOISE.contains(&word.as_str()))
+                        .all(|word| label.contains(word)))
+        })
+    }
+
+    pub fn set_drive(&mut self, id: u64, drive: DriveAffinity) -> bool {
+        match self.items.iter_mut().find(|item| item.id == id) {
+            Some(item) => {
+                item.drive = drive;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The drive the discs of a planned season go in, `None` when any drive will do
+    pub fn season_drive(&self, mvdb_id: u32, season_number: u32) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|item| {
+                item.kind == PlanKind::Season
+                    && item.mvdb_id == mvdb_id
+                    && item.season_number == Some(season_number)
+            })
+            .and_then(|item| match &item.drive {
+                DriveAffinity::Drive(drive) => Some(drive.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Check off the movie after its rip, `success == false` puts it back for the next disc.
+    pub fn movie_ripped(&mut self, mvdb_id: u32, success: bool) -> bool {
+        let Some(item) = self.items.iter_mut().find(|item| {
+            item.kind == PlanKind::Movie && item.mvdb_id == mvdb_id && !item.is_done()
+        }) else {
+            return false;
+        };
+        item.status = if success {
+            PlanStatus::Done
+        } else {
+            PlanStatus::Pending
+        };
+        true
+    }
+
+    pub fn done_count(&self) -> usize {
+        self.items.iter().filter(|item| item.is_done()).count()
+    }
+
+    pub fn percentage(&self) -> usize {
+        (self.done_count() * 100)
+            .checked_div(self.items.len())
+            .unwrap_or(0)
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_uppercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The label words that could be part of a title, `ALIEN_D1_WS` is just `ALIEN`.
+pub fn label_words(disc_name: &str) -> Vec<String> {
+    words(disc_name)
+        .into_iter()
+        .filter(|word| !LABEL_NOISE.contains(&word.as_str()))
+        .filter(|word| !is_disc_number(word))
+        .collect()
+}
+
+/// `D1`, `DISC2`, `S1D3`, numbering on multi-disc sets
+fn is_disc_number(word: &str) -> bool {
+    let word = word.trim_start_matches(|c: char| c == 'S' || c.is_ascii_digit());
+    let digits = word
+        .strip_prefix("DISC")
+        .or_else(|| word.strip_prefix('D'))
+        .unwrap_or(word);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The store only keeps strings, the plan is kept as one JSON string.
+pub fn to_store_value(plan: &RipPlan) -> String {
+    serde_json::to_string(plan).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn from_store_value(value: &str) -> RipPlan {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+/// Match a freshly inserted disc to the plan. Called once its titles are loaded, when the
+/// drive name is known, and before auto-rip looks for a waiting job.
+pub async fn prepare_for_disc(app_handle: &AppHandle, disk: &OpticalDiskInfo) {
+    let state = app_handle.state::<AppState>();
+    let drive = disk.drive_key();
+    let Some(item) = state
+        .lock_rip_plan()
+        .match_disc(&disk.name, Some(&drive))
+        .cloned()
+    else {
+        return;
+    };
+    debug!("Matched disc {} to rip plan item {}", disk.name, item.title);
+
+    if item.kind == PlanKind::Movie {
+        if let Err(message) = queue_movie(app_handle, disk, item.mvdb_id).await {
+            warn!(
+                "Failed to queue {} for {}: {message}",
+                item.title, disk.name
+            );
+            return;
+        }
+    }
+    {
+        let mut plan = state.lock_rip_plan();
+        if let Some(planned) = plan.items.iter_mut().find(|planned| planned.id == item.id) {
+            planned.disc_name = Some(disk.name.clone());
+            if planned.kind == PlanKind::Movie {
+                planned.status = PlanStatus::Queued;
+            } else if planned.drive == DriveAffinity::FirstDisc {
+                debug!("Pinned {} to {drive}", planned.title);
+                planned.drive = DriveAffinity::Drive(drive);
+            }
+        }
+    }
+    if let Err(e) = state.save(app_handle) {
+        warn!("Failed to save the rip plan: {e}");
+    }
+}
+
+/// Selects the disc in the drive a planned season is pinned to, so the season page opens on
+/// that season's disc when both drives hold one.
+pub fn select_season_drive(app_handle: &AppHandle, mvdb_id: u32, season_number: u32) {
+    let state = app_handle.state::<AppState>();
+    let Some(drive) = state
+        .lock_rip_plan()
+        .season_drive(mvdb_id, season_number)
+        .map(str::to_string)
+    else {
+        return;
+    };
+    let Some(disk) = state
+        .clone_optical_disks()
+        .into_iter()
+        .find(|disk| disk.drive_key() == drive)
+    else {
+        return;
+    };
+    debug!("Selected the disc in {drive} for season {season_number} of {mvdb_id}");
+    *state.selected_optical_disk_id.write_or_recover() = Some(disk.id);
+}
+
+async fn queue_movie(
+    app_handle: &AppHandle,
+    disk: &OpticalDiskInfo,
+    mvdb_id: u32,
+) -> Result<(), String> {
+    let movie = find_movie(app_handle, mvdb_id)
+        .await
+        .map_err(|e| e.message)?;
+    let optical_disk = app_handle
+        .state::<AppState>()
+        .find_optical_disk_by_id(&disk.id);
+    let background_process_state = app_handle.state::<BackgroundProcessState>();
+    let (job, _) = background_process_state.find_or_create_job(
+        Some(disk.id),
+        &optical_disk,
+        &JobType::Ripping,
+        &JobStatus::Pending,
+    );
+    job.write_or_recover()
+        .add_incomplete_video(Video::Movie(Box::new(MoviePartEdition {
+            movie,
+            part: None,
+            edition: None,
+        })))
+        .map_err(|e| e.message)?;
+    background_process_state.emit_jobs_changed(app_handle);
+    Ok(())
+}
+
+/// Check off the planned movies of a finished rip job.
+pub fn rip_finished(app_handle: &AppHandle, job: &Arc<RwLock<Job>>, success: bool) {
+    let mvdb_ids: Vec<u32> = job
+        .read_or_recover()
+        .title_videos
+        .iter()
+        .filter_map(|title_video| match &title_video.read_or_recover().video {
+            Video::Movie(movie) => Some(movie.movie.id),
+            _ => None,
+        })
+        .collect();
+    let state = app_handle.state::<AppState>();
+    let changed = {
+        let mut plan = state.lock_rip_plan();
+        // Every movie of the job is marked, not just the first one that changed
+        let mut changed = false;
+        for id in mvdb_ids {
+            changed |= plan.movie_ripped(id, success);
+        }
+        changed
+    };
+    if changed {
+        if let Err(e) = state.save(app_handle) {
+            warn!("Failed to save the rip plan: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> RipPlan {
+        let mut plan = RipPlan::default();
+        plan.add(PlanKind::Movie, 348, None, "Alien (1979)".to_string())
+            .unwrap();
+        plan.add(PlanKind::Movie, 679, None, "Aliens (1986)".to_string())
+            .unwrap();
+        plan.add(
+            PlanKind::Season,
+            1437,
+            Some(1),
+            "Firefly (2002) Season 1".to_string(),
+        )
+        .unwrap();
+        plan
+    }
+
+    #[test]
+    fn matches_discs_by_label() {
+        let plan = plan();
+        assert_eq!(plan.match_disc("ALIENS_D1_WS", None).unwrap().mvdb_id, 679);
+        assert_eq!(plan.match_disc("ALIEN", None).unwrap().mvdb_id, 348);
+        assert_eq!(plan.match_disc("FIREFLY_S1D2", None).unwrap().mvdb_id, 1437);
+        assert!(plan.match_disc("THE_MATRIX", None).is_none());
+    }
+
+    #[test]
+    fn junk_labels_take_the_next_pending_item() {
+        let mut plan = plan();
+        plan.set_status(1, PlanStatus::Queued);
+        assert_eq!(plan.match_disc("DVD_VIDEO", None).unwrap().mvdb_id, 679);
+    }
+
+    #[test]
+    fn checks_off_ripped_movies() {
+        let mut plan = plan();
+        assert!(plan
+            .add(PlanKind::Movie, 348, None, "Alien (1979)".to_string())
+            .is_err());
+        assert!(plan.movie_ripped(348, true));
+        assert_eq!(plan.done_count(), 1);
+        assert_eq!(plan.percentage(), 33);
+        assert!(!plan.movie_ripped(348, true));
+
+        plan.clear_done();
+        assert_eq!(plan.items.len(), 2);
+        assert_eq!(from_store_value(&to_store_value(&plan)), plan);
+    }
+
+    #[test]
+    fn pinned_seasons_only_take_discs_of_their_drive() {
+        let mut plan = plan();
+        plan.set_drive(3, DriveAffinity::Drive("BD-RE WH16NS60".to_string()));
+        assert!(plan
+            .match_disc("FIREFLY_S1D2", Some("DVD-RW GH24NSD1"))
+            .is_none());
+        assert_eq!(
+            plan.match_disc("FIREFLY_S1D2", Some("BD-RE WH16NS60"))
+                .unwrap()
+                .mvdb_id,
+            1437
+        );
+        assert_eq!(plan.season_drive(1437, 1), Some("BD-RE WH16NS60"));
+        plan.set_drive(3, DriveAffinity::Any);
+        assert_eq!(plan.season_drive(1437, 1), None);
+    }
+}
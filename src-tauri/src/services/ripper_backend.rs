@@ -0,0 +1,449 @@
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::services::semantic_version::SemanticVersion;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_shell::process::Command;
+use tauri_plugin_shell::ShellExt;
+
+#[cfg(target_os = "linux")]
+use tauri::Manager;
+
+#[cfg(all(target_os = "windows", target_pointer_width = "64"))]
+#[cfg_attr(feature = "virtual-disc", allow(dead_code))]
+const MAKEMKVCON: &str = "makemkvcon64";
+
+#[cfg(not(all(target_os = "windows", target_pointer_width = "64")))]
+#[cfg_attr(feature = "virtual-disc", allow(dead_code))]
+const MAKEMKVCON: &str = "makemkvcon";
+
+#[cfg(feature = "virtual-disc")]
+const MOCK_MAKEMKVCON: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/mock/makemkvcon.sh");
+
+const DEFAULT_REMOTE_MAKEMKVCON: &str = "makemkvcon";
+
+/// Where makemkvcon runs, everything else (metadata, uploads) always stays local.
+pub trait RipperBackend: Send + Sync {
+    /// Shown in logs and errors
+    fn name(&self) -> String;
+
+    /// makemkvcon with `args`, ready to be spawned
+    fn command(&self, app_handle: &AppHandle, args: &[&str]) -> Result<Command, String>;
+
+    /// The makemkvcon source argument for a disc, `disc:N` or `file:...`
+    fn disc_arg(&self, disk: &OpticalDiskInfo) -> String;
+
+    /// The output directory argument for a local directory
+    fn output_dir(&self, dir: &Path) -> Result<String, String>;
+
+    /// True when the drives have to be asked from makemkvcon, the local OS can't see them
+    fn lists_drives(&self) -> bool {
+        false
+    }
+}
+
+/// The bundled makemkvcon sidecar on this machine.
+pub struct LocalSidecar;
+
+impl RipperBackend for LocalSidecar {
+    fn name(&self) -> String {
+        "local makemkvcon".to_string()
+    }
+
+    fn command(&self, app_handle: &AppHandle, args: &[&str]) -> Result<Command, String> {
+        #[cfg(not(feature = "virtual-disc"))]
+        let command = app_handle
+            .shell()
+            .sidecar(MAKEMKVCON)
+            .map_err(|e| format!("Failed to get makemkvcon: {e}"))?;
+
+        // The mock prints canned robot output, it is run with sh so it works without
+        // the executable bit and without the real sidecar being present
+        #[cfg(feature = "virtual-disc")]
+        let command = app_handle.shell().command("sh").args([MOCK_MAKEMKVCON]);
+
+        // On Linux, set LD_LIBRARY_PATH to include the resource directory
+        // where the shared libraries are located
+        #[cfg(target_os = "linux")]
+        let command = match app_handle.path().resource_dir() {
+            Ok(resource_path) => command.env("LD_LIBRARY_PATH", resource_path),
+            Err(_) => command,
+        };
+        Ok(command.args(args))
+    }
+
+    #[cfg(target_os = "windows")]
+    fn disc_arg(&self, disk: &OpticalDiskInfo) -> String {
+        format!("dev:{}", disk.dev)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn disc_arg(&self, disk: &OpticalDiskInfo) -> String {
+        format!("file:{}", disk.mount_point.to_string_lossy())
+    }
+
+    fn output_dir(&self, dir: &Path) -> Result<String, String> {
+        Ok(dir.to_string_lossy().to_string())
+    }
+}
+
+/// makemkvcon installed outside of Reelix, e.g. the MakeMKV package of a Linux distribution,
+/// run instead of the bundled sidecar. `env` is added to its environment, a system install
+/// usually needs `LD_LIBRARY_PATH` pointed at its libraries.
+pub struct LocalBinary {
+    pub path: PathBuf,
+    pub env: Vec<(String, String)>,
+}
+
+impl RipperBackend for LocalBinary {
+    fn name(&self) -> String {
+        format!("makemkvcon at {}", self.path.display())
+    }
+
+    fn command(&self, app_handle: &AppHandle, args: &[&str]) -> Result<Command, String> {
+        Ok(app_handle
+            .shell()
+            .command(&self.path)
+            .envs(self.env.clone())
+            .args(args))
+    }
+
+    fn disc_arg(&self, disk: &OpticalDiskInfo) -> String {
+        LocalSidecar.disc_arg(disk)
+    }
+
+    fn output_dir(&self, dir: &Path) -> Result<String, String> {
+        LocalSidecar.output_dir(dir)
+    }
+}
+
+/// Runs `makemkvcon --version` to check that `path` is a makemkvcon that starts with `env`,
+/// returns the MakeMKV version it reports.
+pub fn probe_version(path: &Path, env: &[(String, String)]) -> Result<SemanticVersion, String> {
+    let output = std::process::Command::new(path)
+        .arg("--version")
+        .envs(env.iter().cloned())
+        .output()
+        .map_err(|e| format!("Failed to run {}: {e}", path.display()))?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_version(&text).ok_or_else(|| {
+        format!(
+            "{} doesn't look like makemkvcon, it printed: {}",
+            path.display(),
+            text.lines().next().unwrap_or_default()
+        )
+    })
+}
+
+/// The version of `MakeMKV v1.17.7 linux(x64-release) started`
+pub fn parse_version(output: &str) -> Option<SemanticVersion> {
+    let version = Regex::new(r"MakeMKV v(\d+\.\d+\.\d+)").expect("invalid version regex");
+    let captures = version.captures(output)?;
+    SemanticVersion::parse(&captures[1]).ok()
+}
+
+/// The environment variables of `KEY=VALUE` lines, blank lines are skipped.
+pub fn parse_env(text: &str) -> Result<Vec<(String, String)>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(format!("{line} is not KEY=VALUE")),
+        })
+        .collect()
+}
+
+/// `parse_env` reads this back
+pub fn env_text(env: &[(String, String)]) -> String {
+    env.iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// makemkvcon on another machine, run over SSH so a laptop can drive a headless box with the drives.
+///
+/// The remote host writes into a folder that is shared with this machine (NFS, SMB), so
+/// the ripped files show up locally for post-processing and the upload. SSH has to log in
+/// without a password prompt, e.g. with a key in the agent. Cancelling a rip kills the local
+/// ssh client, the remote makemkvcon stops once it can't write its progress anymore.
+///
+/// MakeMKV's own `stream` server only serves video to players, it can't rip, which is why
+/// this goes through SSH.
+pub struct Ssh {
+    /// `user@host` or an alias from `~/.ssh/config`
+    pub host: String,
+    pub port: Option<u16>,
+    pub makemkvcon: String,
+    /// The shared folder as this machine mounts it
+    pub local_share_dir: PathBuf,
+    /// The same folder as the remote host sees it
+    pub remote_share_dir: String,
+}
+
+impl RipperBackend for Ssh {
+    fn name(&self) -> String {
+        format!("makemkvcon on {}", self.host)
+    }
+
+    fn command(&self, app_handle: &AppHandle, args: &[&str]) -> Result<Command, String> {
+        Ok(app_handle.shell().command("ssh").args(self.ssh_args(args)))
+    }
+
+    /// The remote drives are only known by their makemkvcon index.
+    fn disc_arg(&self, disk: &OpticalDiskInfo) -> String {
+        format!("disc:{}", disk.index)
+    }
+
+    fn output_dir(&self, dir: &Path) -> Result<String, String> {
+        let relative = dir.strip_prefix(&self.local_share_dir).map_err(|_| {
+            format!(
+                "{} is not inside the folder shared with {} ({}), point the movies and TV shows folders into it",
+                dir.display(),
+                self.host,
+                self.local_share_dir.display()
+            )
+        })?;
+        let mut remote = self.remote_share_dir.trim_end_matches('/').to_string();
+        for component in relative.components() {
+            remote.push('/');
+            remote.push_str(&component.as_os_str().to_string_lossy());
+        }
+        Ok(remote)
+    }
+
+    fn lists_drives(&self) -> bool {
+        true
+    }
+}
+
+impl Ssh {
+    /// ssh joins everything after the host into one remote shell command, so every
+    /// makemkvcon argument is quoted on its own.
+    fn ssh_args(&self, args: &[&str]) -> Vec<String> {
+        let mut ssh_args = vec!["-o".to_string(), "BatchMode=yes".to_string()];
+        if let Some(port) = self.port {
+            ssh_args.push("-p".to_string());
+            ssh_args.push(port.to_string());
+        }
+        ssh_args.push(self.host.clone());
+        ssh_args.push("--".to_string());
+        let remote_command: Vec<String> = std::iter::once(self.makemkvcon.as_str())
+            .chain(args.iter().copied())
+            .map(shell_quote)
+            .collect();
+        ssh_args.push(remote_command.join(" "));
+        ssh_args
+    }
+}
+
+/// Single quotes anything a POSIX shell would treat specially.
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@,+%".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RipperKind {
+    #[default]
+    Local,
+    Ssh,
+}
+
+impl RipperKind {
+    pub fn to_store_value(&self) -> &'static str {
+        match self {
+            RipperKind::Local => "local",
+            RipperKind::Ssh => "ssh",
+        }
+    }
+
+    pub fn from_store_value(value: Option<&str>) -> Self {
+        match value {
+            Some("ssh") => RipperKind::Ssh,
+            _ => RipperKind::Local,
+        }
+    }
+}
+
+/// Which makemkvcon rips, the bundled one unless a remote host is set up.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RipperOptions {
+    pub kind: RipperKind,
+    pub ssh_host: Option<String>,
+    pub ssh_port: Option<u16>,
+    /// makemkvcon on the remote host, `makemkvcon` when not set
+    pub remote_makemkvcon: Option<String>,
+    pub local_share_dir: Option<PathBuf>,
+    pub remote_share_dir: Option<String>,
+    /// A makemkvcon on this machine to run instead of the bundled one
+    pub local_makemkvcon: Option<PathBuf>,
+    /// Added to the environment of `local_makemkvcon`
+    pub local_env: Vec<(String, String)>,
+    /// Rip the titles of a disc in episode order instead of the order they sit on the disc,
+    /// slower on discs that make the drive seek but the progress reads episode by episode
+    pub episode_order: bool,
+}
+
+impl RipperOptions {
+    pub fn backend(&self) -> Result<Box<dyn RipperBackend>, String> {
+        match self.kind {
+            RipperKind::Local => match &self.local_makemkvcon {
+                Some(path) => Ok(Box::new(LocalBinary {
+                    path: path.clone(),
+                    env: self.local_env.clone(),
+                })),
+                None => Ok(Box::new(LocalSidecar)),
+            },
+            RipperKind::Ssh => {
+                let (Some(host), Some(local_share_dir), Some(remote_share_dir)) = (
+                    self.ssh_host.clone(),
+                    self.local_share_dir.clone(),
+                    self.remote_share_dir.clone(),
+                ) else {
+                    return Err(
+                        "Remote ripping needs the SSH host and the shared folder on both machines"
+                            .to_string(),
+                    );
+                };
+                // The host sits before `--`, a leading dash would be read as an ssh option
+                if host.starts_with('-') || host.chars().any(char::is_whitespace) {
+                    return Err(format!("{host} is not a valid SSH host"));
+                }
+                Ok(Box::new(Ssh {
+                    host,
+                    port: self.ssh_port,
+                    makemkvcon: self
+                        .remote_makemkvcon
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_REMOTE_MAKEMKVCON.to_string()),
+                    local_share_dir,
+                    remote_share_dir,
+                }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ssh() -> Ssh {
+        Ssh {
+            host: "rip@basement".to_string(),
+            port: Some(2222),
+            makemkvcon: "/usr/bin/makemkvcon".to_string(),
+            local_share_dir: PathBuf::from("/Volumes/rips"),
+            remote_share_dir: "/srv/rips/".to_string(),
+        }
+    }
+
+    #[test]
+    fn quotes_every_remote_argument() {
+        assert_eq!(
+            ssh().ssh_args(&["mkv", "disc:0", "1", "/srv/rips/Movies/Alien (1979)"]),
+            vec![
+                "-o",
+                "BatchMode=yes",
+                "-p",
+                "2222",
+                "rip@basement",
+                "--",
+                "/usr/bin/makemkvcon mkv disc:0 1 '/srv/rips/Movies/Alien (1979)'",
+            ]
+        );
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn maps_local_folders_into_the_remote_share() {
+        assert_eq!(
+            ssh()
+                .output_dir(Path::new("/Volumes/rips/Movies/Alien (1979)"))
+                .unwrap(),
+            "/srv/rips/Movies/Alien (1979)"
+        );
+        assert!(ssh().output_dir(Path::new("/Users/me/Movies")).is_err());
+    }
+
+    #[test]
+    fn reads_the_external_makemkvcon_version_and_env() {
+        let output = "MakeMKV v1.17.7 linux(x64-release) started\nUse: makemkvcon [switches] Command [Parameters]";
+        assert_eq!(parse_version(output).unwrap().to_string(), "1.17.7");
+        assert!(parse_version("sh: makemkvcon: not found").is_none());
+
+        let env = parse_env("LD_LIBRARY_PATH=/usr/lib/makemkv\n\n HOME = /home/rip ").unwrap();
+        assert_eq!(
+            env,
+            [
+                (
+                    "LD_LIBRARY_PATH".to_string(),
+                    "/usr/lib/makemkv".to_string()
+                ),
+                ("HOME".to_string(), "/home/rip".to_string()),
+            ]
+        );
+        assert_eq!(parse_env(&env_text(&env)).unwrap(), env);
+        assert!(parse_env("=value").is_err());
+        assert!(parse_env("LD_LIBRARY_PATH").is_err());
+
+        let options = RipperOptions {
+            local_makemkvcon: Some(PathBuf::from("/usr/bin/makemkvcon")),
+            ..RipperOptions::default()
+        };
+        assert_eq!(
+            options.backend().unwrap().name(),
+            "makemkvcon at /usr/bin/makemkvcon"
+        );
+    }
+
+    #[test]
+    fn ssh_needs_host_and_share() {
+        let mut options = RipperOptions {
+            kind: RipperKind::Ssh,
+            ssh_host: Some("basement".to_string()),
+            ..RipperOptions::default()
+        };
+        assert!(options.backend().is_err());
+
+        options.local_share_dir = Some(PathBuf::from("/Volumes/rips"));
+        options.remote_share_dir = Some("/srv/rips".to_string());
+        assert_eq!(options.backend().unwrap().name(), "makemkvcon on basement");
+    }
+
+    #[test]
+    fn rejects_ssh_hosts_that_read_as_options() {
+        let mut options = RipperOptions {
+            kind: RipperKind::Ssh,
+            local_share_dir: Some(PathBuf::from("/Volumes/rips")),
+            remote_share_dir: Some("/srv/rips".to_string()),
+            ..RipperOptions::default()
+        };
+        for host in [
+            "-oProxyCommand=touch /tmp/owned",
+            "rip@basement extra",
+            "base\tment",
+        ] {
+            options.ssh_host = Some(host.to_string());
+            assert!(options.backend().is_err(), "{host} was accepted");
+        }
+        options.ssh_host = Some("rip@basement-1".to_string());
+        assert!(options.backend().is_ok());
+    }
+}
@@ -0,0 +1,130 @@
+//! Titles offered under the search box while typing. The box calls in on every key, a lookup
+//! only goes to TMDB once typing paused for `DEBOUNCE` and only the newest one is
+//! shown, TMDB answers that arrive after a newer key are dropped. Queries that were looked
+//! up before are answered from memory, backspacing over a title doesn't ask TMDB again.
+use crate::services::plex::search_multi;
+use crate::state::locks::MutexExt;
+use crate::the_movie_db::SearchResult;
+use log::debug;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const DEBOUNCE: Duration = Duration::from_millis(250);
+/// One and two letters match too much to be worth a lookup
+const MIN_QUERY_CHARS: usize = 3;
+const MAX_SUGGESTIONS: usize = 6;
+const CACHED_QUERIES: usize = 100;
+
+#[derive(Default)]
+pub struct SearchSuggestState {
+    latest: AtomicU64,
+    cache: Mutex<SuggestionCache>,
+}
+
+impl SearchSuggestState {
+    /// A ticket for a new lookup, every older ticket stops being current
+    fn next_ticket(&self) -> u64 {
+        self.latest.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    fn is_current(&self, ticket: u64) -> bool {
+        self.latest.load(Ordering::SeqCst) == ticket
+    }
+}
+
+/// The last `CACHED_QUERIES` lookups, the oldest is forgotten first
+#[derive(Default)]
+struct SuggestionCache {
+    results: HashMap<String, Arc<Vec<SearchResult>>>,
+    order: VecDeque<String>,
+}
+
+impl SuggestionCache {
+    fn get(&self, query: &str) -> Option<Arc<Vec<SearchResult>>> {
+        self.results.get(query).cloned()
+    }
+
+    fn insert(&mut self, query: String, results: Arc<Vec<SearchResult>>) {
+        if self.results.insert(query.clone(), results).is_none() {
+            self.order.push_back(query);
+        }
+        while self.order.len() > CACHED_QUERIES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.results.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// "the matrix" for "  The  Matrix "
+fn normalize(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Collections have no page to open
+fn suggestions(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    results
+        .into_iter()
+        .filter(|result| result.media_type != "collection")
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// The titles for `query`, `None` when a newer call superseded this one and the box should
+/// be left to that call.
+pub async fn suggest(app_handle: &AppHandle, query: &str) -> Option<Arc<Vec<SearchResult>>> {
+    let state = app_handle.state::<SearchSuggestState>();
+    let query = normalize(query);
+    let ticket = state.next_ticket();
+    if query.chars().count() < MIN_QUERY_CHARS {
+        return Some(Arc::default());
+    }
+    if let Some(results) = state.cache.lock_or_recover().get(&query) {
+        return Some(results);
+    }
+
+    tokio::time::sleep(DEBOUNCE).await;
+    if !state.is_current(ticket) {
+        return None;
+    }
+    let results = match search_multi(app_handle, &query).await {
+        Ok(response) => Arc::new(suggestions(response.results)),
+        Err(e) => {
+            debug!("No suggestions for {query}: {}", e.message);
+            return state.is_current(ticket).then(Arc::default);
+        }
+    };
+    state.cache.lock_or_recover().insert(query, results.clone());
+    state.is_current(ticket).then_some(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supersedes_older_lookups_and_forgets_old_queries() {
+        let state = SearchSuggestState::default();
+        let first = state.next_ticket();
+        let second = state.next_ticket();
+        assert!(!state.is_current(first));
+        assert!(state.is_current(second));
+
+        assert_eq!(normalize("  The  Matrix "), "the matrix");
+
+        let mut cache = SuggestionCache::default();
+        for number in 0..=CACHED_QUERIES {
+            cache.insert(number.to_string(), Arc::default());
+        }
+        assert!(cache.get("0").is_none());
+        assert!(cache.get(&CACHED_QUERIES.to_string()).is_some());
+        assert_eq!(cache.results.len(), CACHED_QUERIES);
+    }
+}
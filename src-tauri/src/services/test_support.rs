@@ -0,0 +1,14 @@
+//! Helpers shared by the tests that need the file system.
+use std::fs;
+use std::path::PathBuf;
+
+/// An empty folder for one test. It sits in a folder of this test run, two runs on the same
+/// machine don't clear each other's files. `name` has to be unique across the crate's tests.
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("reelix-test-{}", std::process::id()))
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,215 @@
+//! Hints about what a disc title holds, worked out from the lengths and chapters of all the
+//! titles on the disc: credits-only titles, titles as long as another one (an angle or a
+//! cut of the same video) and runs of titles that look like the episodes of the disc.
+use crate::models::title_info::{TitleId, TitleInfo};
+use crate::services::locale;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Titles this short with at most this many chapters are likely credits or a studio logo
+const CREDITS_MAX_SECONDS: u64 = 10 * 60;
+const CREDITS_MAX_CHAPTERS: i32 = 2;
+/// Titles within this many seconds of each other count as the same length
+const SAME_LENGTH_SECONDS: u64 = 1;
+/// Episode lengths, a half hour sitcom to an hour long drama
+const EPISODE_SECONDS: std::ops::RangeInclusive<u64> = 18 * 60..=75 * 60;
+/// Episodes of a disc are within a tenth of each other's length
+const EPISODE_TOLERANCE: f64 = 0.1;
+/// Fewer titles of similar length are just as likely extras
+const EPISODE_RUN_MIN: usize = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TitleHint {
+    CreditsOnly,
+    /// As long as title `title_id`
+    SameLength {
+        title_id: TitleId,
+    },
+    /// One of `count` titles of episode length
+    EpisodeRun {
+        count: usize,
+    },
+}
+
+impl TitleHint {
+    pub fn label(&self) -> String {
+        match self {
+            TitleHint::CreditsOnly => "Credits?".to_string(),
+            TitleHint::SameLength { title_id } => format!("Same length as {title_id}"),
+            TitleHint::EpisodeRun { .. } => "Episode?".to_string(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            TitleHint::CreditsOnly => {
+                "Short with few chapters, likely credits or a studio logo".to_string()
+            }
+            TitleHint::SameLength { title_id } => format!(
+                "As long as title {title_id}, probably another angle or cut of the same video"
+            ),
+            TitleHint::EpisodeRun { count } => {
+                format!("One of {count} titles of similar length, likely an episode")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TitleAnalysis {
+    hints: HashMap<TitleId, Vec<TitleHint>>,
+}
+
+impl TitleAnalysis {
+    pub fn analyze(titles: &[TitleInfo]) -> Self {
+        let lengths: Vec<(TitleId, u64)> = titles
+            .iter()
+            .filter_map(|title| Some((title.id, title.duration_seconds()?)))
+            .collect();
+        let mut hints: HashMap<TitleId, Vec<TitleHint>> = HashMap::new();
+        for title in titles {
+            let Some(seconds) = title.duration_seconds() else {
+                continue;
+            };
+            let title_hints = hints.entry(title.id).or_default();
+            let credits_only = seconds <= CREDITS_MAX_SECONDS
+                && title.chapter_count.unwrap_or(0) <= CREDITS_MAX_CHAPTERS;
+            if credits_only {
+                title_hints.push(TitleHint::CreditsOnly);
+                continue;
+            }
+            if let Some(&(title_id, _)) = lengths.iter().find(|(other_id, other_seconds)| {
+                *other_id != title.id && seconds.abs_diff(*other_seconds) <= SAME_LENGTH_SECONDS
+            }) {
+                title_hints.push(TitleHint::SameLength { title_id });
+            }
+            if EPISODE_SECONDS.contains(&seconds) {
+                let tolerance = (seconds as f64 * EPISODE_TOLERANCE) as u64;
+                let count = lengths
+                    .iter()
+                    .filter(|(_, other_seconds)| seconds.abs_diff(*other_seconds) <= tolerance)
+                    .count();
+                if count >= EPISODE_RUN_MIN {
+                    title_hints.push(TitleHint::EpisodeRun { count });
+                }
+            }
+        }
+        hints.retain(|_, title_hints| !title_hints.is_empty());
+        TitleAnalysis { hints }
+    }
+
+    pub fn hints(&self, title: &TitleInfo) -> &[TitleHint] {
+        self.hints
+            .get(&title.id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Whether the disc has a run of episode length titles, a disc of a show
+    pub fn has_episode_run(&self) -> bool {
+        self.hints
+            .values()
+            .flatten()
+            .any(|hint| matches!(hint, TitleHint::EpisodeRun { .. }))
+    }
+
+    /// The hints for a title select option, e.g. " • Episode?", empty without hints.
+    pub fn option_suffix(&self, title: &TitleInfo) -> String {
+        self.hints(title)
+            .iter()
+            .map(|hint| format!(" • {}", hint.label()))
+            .collect()
+    }
+}
+
+/// A title assigned to an episode it is far longer or shorter than, likely the wrong title.
+/// The assignment is only made once the user confirmed it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeConflict {
+    pub title_id: TitleId,
+    pub title_seconds: u64,
+    /// `SeasonEpisode::runtime_range`
+    pub expected: Range<u64>,
+}
+
+impl RuntimeConflict {
+    /// `None` when the title fits or when either length is unknown
+    pub fn check(title: &TitleInfo, expected: Option<Range<u64>>) -> Option<Self> {
+        let expected = expected?;
+        let title_seconds = title.duration_seconds()?;
+        (!expected.contains(&title_seconds)).then_some(RuntimeConflict {
+            title_id: title.id,
+            title_seconds,
+            expected,
+        })
+    }
+
+    pub fn message(&self) -> String {
+        let locale = locale::current();
+        format!(
+            "Title {} runs {}, the episode should run {} to {}",
+            self.title_id,
+            locale.format_runtime(self.title_seconds),
+            locale.format_runtime(self.expected.start),
+            locale.format_runtime(self.expected.end),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn title(id: u32, duration: &str, chapters: i32) -> TitleInfo {
+        TitleInfo {
+            duration: Some(duration.to_string()),
+            chapter_count: Some(chapters),
+            ..TitleInfo::new(id)
+        }
+    }
+
+    #[test]
+    fn spots_credits_angles_and_episodes() {
+        let titles = [
+            title(0, "1:58:03", 28),
+            title(1, "1:58:04", 28),
+            title(2, "0:02:10", 1),
+            title(3, "0:42:10", 6),
+            title(4, "0:44:01", 6),
+            title(5, "0:41:37", 5),
+            title(6, "0:12:00", 1),
+        ];
+        let analysis = TitleAnalysis::analyze(&titles);
+        assert_eq!(
+            analysis.hints(&titles[0]),
+            [TitleHint::SameLength {
+                title_id: TitleId::from(1)
+            }]
+        );
+        assert_eq!(analysis.hints(&titles[2]), [TitleHint::CreditsOnly]);
+        assert_eq!(
+            analysis.hints(&titles[4]),
+            [TitleHint::EpisodeRun { count: 3 }]
+        );
+        assert_eq!(analysis.option_suffix(&titles[3]), " • Episode?");
+        assert!(analysis.has_episode_run());
+        assert!(analysis.hints(&titles[6]).is_empty());
+        assert!(analysis.hints(&TitleInfo::new(9)).is_empty());
+    }
+
+    #[test]
+    fn flags_titles_outside_the_episode_runtime() {
+        let expected = Some(35 * 60..55 * 60);
+        assert_eq!(
+            RuntimeConflict::check(&title(3, "0:42:10", 6), expected.clone()),
+            None
+        );
+        let conflict = RuntimeConflict::check(&title(0, "1:32:00", 28), expected.clone()).unwrap();
+        assert_eq!(
+            conflict.message(),
+            "Title 0 runs 1h 32m, the episode should run 35m to 55m"
+        );
+        assert_eq!(RuntimeConflict::check(&TitleInfo::new(9), expected), None);
+        assert_eq!(RuntimeConflict::check(&title(0, "1:32:00", 28), None), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! A copy of the TMDB shows and seasons that were looked up, with the season posters and the
+//! episode stills of the season pages that were opened. Ripping rigs tend to sit where the
+//! network drops, with the copy a disc can still be assigned to its episodes when TMDB can't
+//! be reached in the middle of a session.
+//!
+//! Lookups fall back to the copy when TMDB fails, images are shown from the copy once they
+//! were downloaded.
+use crate::state::AppState;
+use crate::the_movie_db::{SeasonResponse, TvResponse};
+use base64::Engine;
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::Client;
+
+const TMDB_IMAGE_URL: &str = "https://image.tmdb.org/t/p";
+pub const POSTER_SIZE: &str = "w200";
+pub const STILL_SIZE: &str = "w227_and_h127_bestv2";
+
+pub fn tv_key(tv_id: u32) -> String {
+    format!("tv-{tv_id}")
+}
+
+pub fn season_key(tv_id: u32, season_number: u32) -> String {
+    format!("season-{tv_id}-{season_number}")
+}
+
+fn cache_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    match app_handle.path().app_cache_dir() {
+        Ok(dir) => Some(dir.join("tmdb")),
+        Err(e) => {
+            debug!("No cache directory for TMDB: {e}");
+            None
+        }
+    }
+}
+
+/// Keeps `value` under `key`, a copy that can't be written is only logged.
+pub fn store<T: Serialize>(app_handle: &AppHandle, key: &str, value: &T) {
+    let Some(dir) = cache_dir(app_handle) else {
+        return;
+    };
+    let result = serde_json::to_vec(value)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            fs::write(dir.join(format!("{key}.json")), json).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        debug!("Failed to cache {key}: {e}");
+    }
+}
+
+pub fn load<T: DeserializeOwned>(app_handle: &AppHandle, key: &str) -> Option<T> {
+    let file = cache_dir(app_handle)?.join(format!("{key}.json"));
+    let json = fs::read(file).ok()?;
+    serde_json::from_slice(&json)
+        .inspect_err(|e| debug!("Failed to read the cached {key}: {e}"))
+        .ok()
+}
+
+/// `images/w200-abc.jpg` for the `/abc.jpg` poster
+fn image_file(dir: &Path, size: &str, image_path: &str) -> PathBuf {
+    dir.join("images")
+        .join(format!("{size}-{}", image_path.trim_start_matches('/')))
+}
+
+fn data_uri(image_path: &str, bytes: &[u8]) -> String {
+    let mime = match Path::new(image_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        _ => "image/jpeg",
+    };
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    format!("data:{mime};base64,{encoded}")
+}
+
+/// What an `<img>` shows for a TMDB image, the cached copy inlined when there is one.
+pub fn image_src(app_handle: &AppHandle, size: &str, image_path: &str) -> String {
+    cache_dir(app_handle)
+        .and_then(|dir| fs::read(image_file(&dir, size, image_path)).ok())
+        .map(|bytes| data_uri(image_path, &bytes))
+        .unwrap_or_else(|| format!("{TMDB_IMAGE_URL}/{size}{image_path}"))
+}
+
+/// Downloads the posters and the episode stills of the season that are not cached yet. Runs
+/// in the background, the page is shown with TMDB's images meanwhile.
+pub fn spawn_prefetch(app_handle: &AppHandle, tv: &TvResponse, season: &SeasonResponse) {
+    let network_options = app_handle.state::<AppState>().network_options();
+    if network_options.offline {
+        return;
+    }
+    let Some(dir) = cache_dir(app_handle) else {
+        return;
+    };
+    let images: Vec<(&'static str, String)> = [&season.poster_path, &tv.poster_path]
+        .into_iter()
+        .flatten()
+        .map(|path| (POSTER_SIZE, path.clone()))
+        .chain(
+            season
+                .episodes
+                .iter()
+                .filter_map(|episode| episode.still_path.clone())
+                .map(|path| (STILL_SIZE, path)),
+        )
+        .filter(|(size, path)| !image_file(&dir, size, path).exists())
+        .collect();
+    if images.is_empty() {
+        return;
+    }
+
+    let timeout = network_options.tmdb_timeout();
+    tauri::async_runtime::spawn(async move {
+        let client = Client::new();
+        let mut cached = 0;
+        for (size, image_path) in &images {
+            match download(&client, size, image_path, timeout).await {
+                Ok(bytes) => {
+                    let file = image_file(&dir, size, image_path);
+                    let written = fs::create_dir_all(dir.join("images"))
+                        .and_then(|_| fs::write(&file, bytes));
+                    match written {
+                        Ok(()) => cached += 1,
+                        Err(e) => debug!("Failed to cache {}: {e}", file.display()),
+                    }
+                }
+                Err(e) => {
+                    // The network is gone, the rest would wait for the timeout one by one
+                    debug!("{e}, prefetching stopped");
+                    break;
+                }
+            }
+        }
+        debug!("Cached {cached} of {} TMDB images", images.len());
+    });
+}
+
+async fn download(
+    client: &Client,
+    size: &str,
+    image_path: &str,
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let url = format!("{TMDB_IMAGE_URL}/{size}{image_path}");
+    let response = client
+        .get(&url)
+        .timeout(timeout)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to download {url}: {e}"))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download {url}: {e}"))?;
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_cached_images_and_inlines_them() {
+        let dir = Path::new("cache");
+        assert_eq!(
+            image_file(dir, STILL_SIZE, "/abc.jpg"),
+            dir.join("images").join("w227_and_h127_bestv2-abc.jpg")
+        );
+        assert_eq!(data_uri("/abc.jpg", b"jpg"), "data:image/jpeg;base64,anBn");
+        assert_eq!(data_uri("/abc.PNG", b"png"), "data:image/png;base64,cG5n");
+        assert_eq!(season_key(1396, 2), "season-1396-2");
+    }
+}
@@ -0,0 +1,127 @@
+use serde::Deserialize;
+
+/// Track kinds as `mkvmerge -J` reports them.
+pub const AUDIO: &str = "audio";
+pub const SUBTITLES: &str = "subtitles";
+
+#[derive(Deserialize)]
+struct Identification {
+    #[serde(default)]
+    tracks: Vec<Track>,
+}
+
+/// One track of `mkvmerge -J`, only what picking the default track needs.
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Track {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub properties: TrackProperties,
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrackProperties {
+    /// Track number as used by `mkvpropedit --edit track:@N`
+    pub number: u64,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub language_ietf: Option<String>,
+    #[serde(default)]
+    pub forced_track: bool,
+}
+
+impl Track {
+    /// `eng`, `en` and `en-US` all match an English track.
+    fn has_language(&self, language: &str) -> bool {
+        let language = language.to_lowercase();
+        let ietf = self.properties.language_ietf.as_deref().unwrap_or_default();
+        let primary = ietf.split('-').next().unwrap_or_default();
+        [
+            self.properties.language.as_deref().unwrap_or_default(),
+            ietf,
+            primary,
+        ]
+        .iter()
+        .any(|code| !code.is_empty() && code.eq_ignore_ascii_case(&language))
+    }
+}
+
+pub fn parse_identification(json: &str) -> Result<Vec<Track>, String> {
+    serde_json::from_str::<Identification>(json)
+        .map(|identification| identification.tracks)
+        .map_err(|e| format!("Failed to parse mkvmerge output: {e}"))
+}
+
+/// `(track number, default)` for every track of `kind`, the first preferred language on the
+/// disc becomes the default. Empty when none of the languages is on the disc.
+///
+/// Forced subtitles only cover foreign dialog, a full subtitle track is picked before them.
+pub fn default_flags(tracks: &[Track], kind: &str, languages: &[String]) -> Vec<(u64, bool)> {
+    let tracks: Vec<&Track> = tracks.iter().filter(|track| track.kind == kind).collect();
+    let chosen = languages.iter().find_map(|language| {
+        let matching: Vec<&&Track> = tracks
+            .iter()
+            .filter(|track| track.has_language(language))
+            .collect();
+        matching
+            .iter()
+            .find(|track| !track.properties.forced_track)
+            .or(matching.first())
+            .map(|track| track.properties.number)
+    });
+    match chosen {
+        Some(number) => tracks
+            .iter()
+            .map(|track| (track.properties.number, track.properties.number == number))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTIFICATION: &str = r#"{
+        "tracks": [
+            {"id": 0, "type": "video", "properties": {"number": 1, "language": "und"}},
+            {"id": 1, "type": "audio", "properties": {"number": 2, "language": "eng", "language_ietf": "en"}},
+            {"id": 2, "type": "audio", "properties": {"number": 3, "language": "jpn", "language_ietf": "ja"}},
+            {"id": 3, "type": "subtitles", "properties": {"number": 4, "language": "eng", "forced_track": true}},
+            {"id": 4, "type": "subtitles", "properties": {"number": 5, "language": "eng", "language_ietf": "en-US"}}
+        ]
+    }"#;
+
+    fn languages(codes: &[&str]) -> Vec<String> {
+        codes.iter().map(|code| code.to_string()).collect()
+    }
+
+    #[test]
+    fn first_preferred_language_on_the_disc_wins() {
+        let tracks = parse_identification(IDENTIFICATION).unwrap();
+        assert_eq!(
+            default_flags(&tracks, AUDIO, &languages(&["fra", "jpn", "eng"])),
+            vec![(2, false), (3, true)]
+        );
+        assert_eq!(
+            default_flags(&tracks, AUDIO, &languages(&["en"])),
+            vec![(2, true), (3, false)]
+        );
+    }
+
+    #[test]
+    fn full_subtitles_before_forced_ones() {
+        let tracks = parse_identification(IDENTIFICATION).unwrap();
+        assert_eq!(
+            default_flags(&tracks, SUBTITLES, &languages(&["en"])),
+            vec![(4, false), (5, true)]
+        );
+    }
+
+    #[test]
+    fn nothing_changes_without_a_match() {
+        let tracks = parse_identification(IDENTIFICATION).unwrap();
+        assert!(default_flags(&tracks, AUDIO, &languages(&["deu"])).is_empty());
+        assert!(default_flags(&tracks, SUBTITLES, &[]).is_empty());
+    }
+}
@@ -0,0 +1,553 @@
+//! Trakt.tv: every title that rips adds the movie or episode to the Trakt collection, and the
+//! Trakt watchlist is marked in the search results so the discs waiting to be ripped stand out.
+//!
+//! Reelix signs in with the OAuth device flow, the user enters a code on trakt.tv/activate
+//! while Reelix polls for the token. Both need a Trakt API app, its client id and secret go
+//! into the settings.
+use crate::services::{network, unix_time};
+use crate::state::title_video::Video;
+use crate::state::AppState;
+use log::{debug, error, info};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::{Client, RequestBuilder, StatusCode};
+
+const TRAKT_API_URL: &str = "https://api.trakt.tv";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Trakt's redirect uri for apps without one
+const DEVICE_REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
+
+/// TMDB ids of the Trakt watchlist, refreshed on start and after signing in
+static WATCHLIST: Mutex<Vec<WatchlistItem>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraktOptions {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub access_token: Option<String>,
+    pub refresh_token: Option<String>,
+    /// Seconds since the Unix epoch
+    pub expires_at: Option<u64>,
+    /// Add ripped titles to the Trakt collection
+    pub sync_collection: bool,
+    /// Mark titles on the Trakt watchlist in the search results
+    pub highlight_watchlist: bool,
+}
+
+impl TraktOptions {
+    pub fn api(&self) -> Option<TraktApi> {
+        Some(TraktApi {
+            base_url: TRAKT_API_URL.to_string(),
+            client_id: self.client_id.clone()?,
+            client_secret: self.client_secret.clone()?,
+        })
+    }
+
+    pub fn is_signed_in(&self) -> bool {
+        self.access_token.is_some()
+    }
+
+    /// True a day before the token runs out, so a rip never has to wait for a refresh.
+    pub fn needs_refresh(&self, now: u64) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| now + 24 * 60 * 60 >= expires_at)
+    }
+
+    fn sign_in(&mut self, token: Token, now: u64) {
+        self.access_token = Some(token.access_token);
+        self.refresh_token = Some(token.refresh_token);
+        self.expires_at = Some(now + token.expires_in);
+    }
+
+    pub fn sign_out(&mut self) {
+        self.access_token = None;
+        self.refresh_token = None;
+        self.expires_at = None;
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_url: String,
+    /// Seconds until the code runs out
+    pub expires_in: u64,
+    /// Seconds between polls
+    pub interval: u64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: u64,
+}
+
+/// Where the device flow stands after a poll.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DevicePoll {
+    Authorized(Token),
+    Pending,
+    /// Polled too fast, wait longer
+    SlowDown,
+    /// Expired, denied or already used, start over
+    Failed(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchlistItem {
+    /// "movie" or "tv" like TMDB
+    pub media_type: String,
+    pub tmdb_id: u32,
+}
+
+/// What to add to the collection for a ripped title.
+pub fn collection_body(video: &Video) -> Option<Value> {
+    match video {
+        Video::Movie(movie) => Some(json!({
+            "movies": [{"ids": {"tmdb": movie.movie.id}}]
+        })),
+        Video::Tv(tv) => Some(json!({
+            "shows": [{
+                "ids": {"tmdb": tv.tv.id},
+                "seasons": [{
+                    "number": tv.season.season_number,
+                    "episodes": [{"number": tv.episode.episode_number}]
+                }]
+            }]
+        })),
+        Video::Personal(_) => None,
+    }
+}
+
+/// The TMDB ids of a `/sync/watchlist` response, entries without one are skipped.
+pub fn parse_watchlist(items: &[Value]) -> Vec<WatchlistItem> {
+    items
+        .iter()
+        .filter_map(|item| {
+            let (key, media_type) = match item.get("type")?.as_str()? {
+                "movie" => ("movie", "movie"),
+                "show" => ("show", "tv"),
+                _ => return None,
+            };
+            Some(WatchlistItem {
+                media_type: media_type.to_string(),
+                tmdb_id: item.pointer(&format!("/{key}/ids/tmdb"))?.as_u64()? as u32,
+            })
+        })
+        .collect()
+}
+
+pub fn on_watchlist(media_type: &str, tmdb_id: u32) -> bool {
+    WATCHLIST
+        .lock()
+        .map(|watchlist| {
+            watchlist
+                .iter()
+                .any(|item| item.media_type == media_type && item.tmdb_id == tmdb_id)
+        })
+        .unwrap_or(false)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraktApi {
+    pub base_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl TraktApi {
+    fn post(&self, client: &Client, path: &str, body: Value) -> RequestBuilder {
+        client
+            .post(format!("{}{path}", self.base_url))
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .timeout(REQUEST_TIMEOUT)
+    }
+
+    /// Headers every call of the API itself needs, the OAuth endpoints don't.
+    fn authorized(&self, request: RequestBuilder, access_token: &str) -> RequestBuilder {
+        request
+            .header("trakt-api-version", "2")
+            .header("trakt-api-key", &self.client_id)
+            .bearer_auth(access_token)
+    }
+
+    pub async fn device_code(&self, client: &Client) -> Result<DeviceCode, String> {
+        let response = self
+            .post(
+                client,
+                "/oauth/device/code",
+                json!({"client_id": self.client_id}),
+            )
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Trakt: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!("Trakt returned {}", response.status()));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to read the Trakt device code: {e}"))
+    }
+
+    pub async fn poll_device_token(
+        &self,
+        client: &Client,
+        device_code: &str,
+    ) -> Result<DevicePoll, String> {
+        let response = self
+            .post(
+                client,
+                "/oauth/device/token",
+                json!({
+                    "code": device_code,
+                    "client_id": self.client_id,
+                    "client_secret": self.client_secret,
+                }),
+            )
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Trakt: {e}"))?;
+        Ok(match response.status() {
+            StatusCode::OK => DevicePoll::Authorized(
+                response
+                    .json()
+                    .await
+                    .map_err(|e| format!("Failed to read the Trakt token: {e}"))?,
+            ),
+            StatusCode::BAD_REQUEST => DevicePoll::Pending,
+            StatusCode::TOO_MANY_REQUESTS => DevicePoll::SlowDown,
+            StatusCode::GONE => DevicePoll::Failed("The Trakt code expired".to_string()),
+            StatusCode::IM_A_TEAPOT => {
+                DevicePoll::Failed("Signing in to Trakt was denied".to_string())
+            }
+            status => DevicePoll::Failed(format!("Trakt returned {status}")),
+        })
+    }
+
+    pub async fn refresh_token(
+        &self,
+        client: &Client,
+        refresh_token: &str,
+    ) -> Result<Token, String> {
+        let response = self
+            .post(
+                client,
+                "/oauth/token",
+                json!({
+                    "refresh_token": refresh_token,
+                    "client_id": self.client_id,
+                    "client_secret": self.client_secret,
+                    "redirect_uri": DEVICE_REDIRECT_URI,
+                    "grant_type": "refresh_token",
+                }),
+            )
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Trakt: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Trakt returned {} for the token refresh",
+                response.status()
+            ));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to read the Trakt token: {e}"))
+    }
+
+    pub async fn add_to_collection(
+        &self,
+        client: &Client,
+        access_token: &str,
+        body: Value,
+    ) -> Result<(), String> {
+        let response = self
+            .authorized(self.post(client, "/sync/collection", body), access_token)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Trakt: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Trakt returned {} for the collection",
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn watchlist(
+        &self,
+        client: &Client,
+        access_token: &str,
+    ) -> Result<Vec<WatchlistItem>, String> {
+        let request = client
+            .get(format!("{}/sync/watchlist", self.base_url))
+            .timeout(REQUEST_TIMEOUT);
+        let response = self
+            .authorized(request, access_token)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Trakt: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Trakt returned {} for the watchlist",
+                response.status()
+            ));
+        }
+        let items: Vec<Value> = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to read the Trakt watchlist: {e}"))?;
+        Ok(parse_watchlist(&items))
+    }
+}
+
+/// The API and a token that is good for a while, refreshed and saved when it is about to
+/// run out. `None` when Trakt isn't set up or signed in.
+async fn signed_in_api(app_handle: &AppHandle, client: &Client) -> Option<(TraktApi, String)> {
+    let state = app_handle.state::<AppState>();
+    let options = state.trakt_options();
+    let api = options.api()?;
+    if !options.needs_refresh(unix_time::now()) {
+        return Some((api, options.access_token?));
+    }
+    match api
+        .refresh_token(client, options.refresh_token.as_deref()?)
+        .await
+    {
+        Ok(token) => {
+            let access_token = token.access_token.clone();
+            state.lock_trakt_options().sign_in(token, unix_time::now());
+            if let Err(e) = state.save(app_handle) {
+                error!("Failed to save the Trakt token: {e}");
+            }
+            Some((api, access_token))
+        }
+        Err(e) => {
+            error!("{e}");
+            // An expired token can't be used anymore, the user has to sign in again
+            options
+                .expires_at
+                .is_some_and(|expires_at| expires_at > unix_time::now())
+                .then_some((api, options.access_token?))
+        }
+    }
+}
+
+/// Starts signing in, returns the code for the user and polls for the token in the
+/// background until it comes or the code runs out.
+pub async fn start_device_flow(app_handle: &AppHandle) -> Result<DeviceCode, String> {
+    let api = app_handle
+        .state::<AppState>()
+        .trakt_options()
+        .api()
+        .ok_or("Add the client id and secret of your Trakt app first")?;
+    let client = Client::new();
+    let code = api.device_code(&client).await?;
+
+    let app_handle = app_handle.clone();
+    let device_code = code.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = Duration::from_secs(device_code.interval.max(1));
+        let deadline = unix_time::now() + device_code.expires_in;
+        while unix_time::now() < deadline {
+            tokio::time::sleep(interval).await;
+            match api
+                .poll_device_token(&client, &device_code.device_code)
+                .await
+            {
+                Ok(DevicePoll::Authorized(token)) => {
+                    let state = app_handle.state::<AppState>();
+                    state.lock_trakt_options().sign_in(token, unix_time::now());
+                    if let Err(e) = state.save(&app_handle) {
+                        error!("Failed to save the Trakt token: {e}");
+                    }
+                    info!("Signed in to Trakt");
+                    refresh_watchlist(&app_handle).await;
+                    return;
+                }
+                Ok(DevicePoll::Pending) => {}
+                Ok(DevicePoll::SlowDown) => interval += Duration::from_secs(1),
+                Ok(DevicePoll::Failed(message)) => {
+                    error!("{message}");
+                    return;
+                }
+                Err(e) => debug!("{e}"),
+            }
+        }
+        debug!("The Trakt device code expired");
+    });
+    Ok(code)
+}
+
+/// Loads the watchlist again, when it is highlighted.
+pub async fn refresh_watchlist(app_handle: &AppHandle) {
+    if !app_handle
+        .state::<AppState>()
+        .trakt_options()
+        .highlight_watchlist
+        || network::is_offline(app_handle)
+    {
+        return;
+    }
+    let client = Client::new();
+    let Some((api, access_token)) = signed_in_api(app_handle, &client).await else {
+        return;
+    };
+    match api.watchlist(&client, &access_token).await {
+        Ok(items) => {
+            debug!("{} titles on the Trakt watchlist", items.len());
+            if let Ok(mut watchlist) = WATCHLIST.lock() {
+                *watchlist = items;
+            }
+        }
+        Err(e) => error!("{e}"),
+    }
+}
+
+/// Adds a ripped title to the Trakt collection in the background, Trakt being down never
+/// holds up a rip.
+pub fn spawn_collect(app_handle: &AppHandle, video: &Video) {
+    if !app_handle
+        .state::<AppState>()
+        .trakt_options()
+        .sync_collection
+        || network::is_offline(app_handle)
+    {
+        return;
+    }
+    let Some(body) = collection_body(video) else {
+        return;
+    };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let client = Client::new();
+        let Some((api, access_token)) = signed_in_api(&app_handle, &client).await else {
+            return;
+        };
+        if let Err(e) = api.add_to_collection(&client, &access_token, body).await {
+            error!("{e}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn api(server: &MockServer) -> TraktApi {
+        TraktApi {
+            base_url: server.uri(),
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+        }
+    }
+
+    #[test]
+    fn refreshes_a_day_early() {
+        let options = TraktOptions {
+            expires_at: Some(100_000),
+            ..TraktOptions::default()
+        };
+        assert!(!options.needs_refresh(0));
+        assert!(options.needs_refresh(100_000 - 60));
+        assert!(!TraktOptions::default().needs_refresh(100_000));
+    }
+
+    #[test]
+    fn reads_the_tmdb_ids_of_the_watchlist() {
+        let items: Vec<Value> = serde_json::from_str(
+            r#"[
+                {"type": "movie", "movie": {"title": "Alien", "ids": {"trakt": 1, "tmdb": 348}}},
+                {"type": "show", "show": {"title": "The Office", "ids": {"tmdb": 2316}}},
+                {"type": "season", "season": {"ids": {"tmdb": 1}}},
+                {"type": "movie", "movie": {"ids": {"tmdb": null}}}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parse_watchlist(&items),
+            vec![
+                WatchlistItem {
+                    media_type: "movie".to_string(),
+                    tmdb_id: 348,
+                },
+                WatchlistItem {
+                    media_type: "tv".to_string(),
+                    tmdb_id: 2316,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn polls_the_device_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/device/token"))
+            .and(body_json(json!({
+                "code": "device",
+                "client_id": "client",
+                "client_secret": "secret",
+            })))
+            .respond_with(ResponseTemplate::new(400))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/device/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "access",
+                "refresh_token": "refresh",
+                "expires_in": 86400,
+            })))
+            .mount(&server)
+            .await;
+
+        let api = api(&server);
+        let client = Client::new();
+        assert_eq!(
+            api.poll_device_token(&client, "device").await,
+            Ok(DevicePoll::Pending)
+        );
+        assert_eq!(
+            api.poll_device_token(&client, "device").await,
+            Ok(DevicePoll::Authorized(Token {
+                access_token: "access".to_string(),
+                refresh_token: "refresh".to_string(),
+                expires_in: 86400,
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn adds_to_the_collection_with_the_api_headers() {
+        let server = MockServer::start().await;
+        let body = json!({"movies": [{"ids": {"tmdb": 348}}]});
+        Mock::given(method("POST"))
+            .and(path("/sync/collection"))
+            .and(header("trakt-api-version", "2"))
+            .and(header("trakt-api-key", "client"))
+            .and(header("Authorization", "Bearer access"))
+            .and(body_json(body.clone()))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = api(&server)
+            .add_to_collection(&Client::new(), "access", body)
+            .await;
+        assert_eq!(result, Ok(()));
+    }
+}
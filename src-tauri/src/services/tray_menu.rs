@@ -0,0 +1,242 @@
+//! The tray menu lists the running jobs, "Ripping: Dune (2021) — 42%", and has an Eject
+//! submenu with the drives that have a disc. It is rebuilt when jobs or discs change, while
+//! only the progress of the jobs moves their items are renamed in place so an open menu stays
+//! open.
+use crate::models::optical_disk_info::{DiskId, OpticalDiskInfo};
+use crate::services::disk_manager;
+use crate::state::background_process_state::BackgroundProcessState;
+use crate::state::job_state::{Job, JobType};
+use crate::state::locks::{MutexExt, RwLockExt};
+use crate::state::AppState;
+use log::{debug, error};
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Wry};
+
+pub const TRAY_ID: &str = "main";
+const JOB_PREFIX: &str = "job:";
+const EJECT_PREFIX: &str = "eject:";
+
+/// One item the menu shows for a job or a drive
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub id: String,
+    pub label: String,
+}
+
+/// The menu items of the jobs, kept to rename them when the progress changes
+type JobItems = Vec<(Entry, MenuItem<Wry>)>;
+
+/// The items that are always in the menu and what it shows right now.
+pub struct TrayMenu {
+    pub show: MenuItem<Wry>,
+    pub do_not_disturb: CheckMenuItem<Wry>,
+    pub version: MenuItem<Wry>,
+    pub quit: MenuItem<Wry>,
+    shown: Mutex<Shown>,
+}
+
+#[derive(Default)]
+struct Shown {
+    jobs: JobItems,
+    drives: Vec<Entry>,
+}
+
+impl TrayMenu {
+    pub fn new(
+        show: MenuItem<Wry>,
+        do_not_disturb: CheckMenuItem<Wry>,
+        version: MenuItem<Wry>,
+        quit: MenuItem<Wry>,
+    ) -> Self {
+        TrayMenu {
+            show,
+            do_not_disturb,
+            version,
+            quit,
+            shown: Mutex::new(Shown::default()),
+        }
+    }
+
+    /// The menu with the job and drive items, and the job items to rename later.
+    fn build(
+        &self,
+        app_handle: &AppHandle,
+        jobs: &[Entry],
+        drives: &[Entry],
+    ) -> tauri::Result<(Menu<Wry>, JobItems)> {
+        let menu = Menu::new(app_handle)?;
+        let mut job_items = Vec::new();
+        for entry in jobs {
+            let item = MenuItem::with_id(
+                app_handle,
+                entry.id.as_str(),
+                &entry.label,
+                true,
+                None::<&str>,
+            )?;
+            menu.append(&item)?;
+            job_items.push((entry.clone(), item));
+        }
+        if !jobs.is_empty() {
+            menu.append(&PredefinedMenuItem::separator(app_handle)?)?;
+        }
+        if !drives.is_empty() {
+            let eject = Submenu::with_id(app_handle, "eject", "Eject", true)?;
+            for entry in drives {
+                eject.append(&MenuItem::with_id(
+                    app_handle,
+                    entry.id.as_str(),
+                    &entry.label,
+                    true,
+                    None::<&str>,
+                )?)?;
+            }
+            menu.append(&eject)?;
+        }
+        menu.append_items(&[&self.show, &self.do_not_disturb, &self.version, &self.quit])?;
+        Ok((menu, job_items))
+    }
+}
+
+/// The jobs that are running, season jobs only sum up their episodes so they are left out.
+pub fn job_entries(jobs: &[Job]) -> Vec<Entry> {
+    jobs.iter()
+        .filter(|job| job.is_processing() && job.job_type != JobType::Season)
+        .map(|job| {
+            let verb = job.job_type.to_string();
+            let title = job
+                .title
+                .clone()
+                .or_else(|| job.disk.as_ref().map(|disk| disk.name.clone()))
+                .unwrap_or_default();
+            let title = if title.starts_with(&verb) {
+                title
+            } else {
+                format!("{verb}: {title}")
+            };
+            Entry {
+                id: format!("{JOB_PREFIX}{}", job.id),
+                label: format!("{title} — {:.0}%", job.progress.percent),
+            }
+        })
+        .collect()
+}
+
+/// The drives on this machine that have a disc, remote drives are ejected on their machine.
+pub fn drive_entries(disks: &[OpticalDiskInfo]) -> Vec<Entry> {
+    disks
+        .iter()
+        .filter(|disk| !disk.is_remote())
+        .map(|disk| Entry {
+            id: format!("{EJECT_PREFIX}{}", disk.id),
+            label: format!("{} ({})", disk.name, disk.dev),
+        })
+        .collect()
+}
+
+/// Brings the menu up to date with the jobs and discs.
+pub fn refresh(app_handle: &AppHandle) {
+    let Some(tray_menu) = app_handle.try_state::<TrayMenu>() else {
+        return;
+    };
+    let jobs = job_entries(
+        &app_handle
+            .state::<BackgroundProcessState>()
+            .clone_all_jobs(),
+    );
+    let drives = drive_entries(&app_handle.state::<AppState>().clone_optical_disks());
+
+    let mut shown = tray_menu.shown.lock_or_recover();
+    let same_items = shown.drives == drives
+        && shown.jobs.len() == jobs.len()
+        && shown
+            .jobs
+            .iter()
+            .zip(&jobs)
+            .all(|((entry, _), job)| entry.id == job.id);
+    if same_items {
+        for ((entry, item), job) in shown.jobs.iter_mut().zip(jobs) {
+            if entry.label != job.label {
+                if let Err(e) = item.set_text(&job.label) {
+                    debug!("Failed to rename tray item {}: {e}", entry.id);
+                }
+                *entry = job;
+            }
+        }
+        return;
+    }
+
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match tray_menu.build(app_handle, &jobs, &drives) {
+        Ok((menu, job_items)) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                error!("Failed to update the tray menu: {e}");
+                return;
+            }
+            *shown = Shown {
+                jobs: job_items,
+                drives,
+            };
+        }
+        Err(e) => error!("Failed to build the tray menu: {e}"),
+    }
+}
+
+pub fn is_job_item(id: &str) -> bool {
+    id.starts_with(JOB_PREFIX)
+}
+
+/// Ejects the drive of an Eject item, returns false for other items.
+pub fn eject(app_handle: &AppHandle, id: &str) -> bool {
+    let Some(disk_id) = id
+        .strip_prefix(EJECT_PREFIX)
+        .and_then(|disk_id| disk_id.parse::<u64>().ok())
+    else {
+        return false;
+    };
+    let Some(optical_disk) = app_handle
+        .state::<AppState>()
+        .find_optical_disk_by_id(&DiskId::from(disk_id))
+    else {
+        debug!("The disc of tray item {id} is gone");
+        return true;
+    };
+    let disk = optical_disk.read_or_recover().clone();
+    // Ejecting waits for the drive, the menu is handled on the main thread
+    tauri::async_runtime::spawn_blocking(move || {
+        #[cfg(target_os = "linux")]
+        disk_manager::eject_by_device(&disk.dev);
+
+        #[cfg(not(target_os = "linux"))]
+        disk_manager::eject(&disk.mount_point);
+    });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::job_state::JobStatus;
+
+    #[test]
+    fn lists_the_running_jobs_with_their_progress() {
+        let mut ripping = Job::new(JobType::Ripping, None, JobStatus::Processing);
+        ripping.title = Some("Dune (2021)".to_string());
+        ripping.progress.percent = 42.4;
+        let mut archiving = Job::new(JobType::Archiving, None, JobStatus::Processing);
+        archiving.title = Some("Archiving PHOTOS".to_string());
+        let pending = Job::new(JobType::Uploading, None, JobStatus::Pending);
+
+        let entries = job_entries(&[ripping.clone(), archiving, pending]);
+        let labels: Vec<&str> = entries.iter().map(|entry| entry.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["Ripping: Dune (2021) — 42%", "Archiving PHOTOS — 0%"]
+        );
+        assert_eq!(entries[0].id, format!("job:{}", ripping.id));
+        assert!(is_job_item(&entries[0].id));
+    }
+}
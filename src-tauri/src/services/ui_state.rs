@@ -0,0 +1,80 @@
+//! Layout the frontend wants to keep between starts, collapsed panels, the last library
+//! profile, sort orders. The frontend stays stateless, it reads and writes the values by key.
+//!
+//! The values are kept as JSON in "ui_state.json", one store entry per key.
+use serde_json::Value;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "ui_state.json";
+const MAX_KEY_LENGTH: usize = 64;
+const MAX_VALUE_LENGTH: usize = 16 * 1024;
+
+/// Keys are short and lowercase, e.g. "jobs.completed.collapsed" or "search.sort".
+pub fn validate_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(format!(
+            "UI state keys are 1 to {MAX_KEY_LENGTH} characters long"
+        ));
+    }
+    let valid = key
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        return Err(format!(
+            "The UI state key {key} may only have lowercase letters, digits, '_', '-' and '.'"
+        ));
+    }
+    Ok(())
+}
+
+/// Layout state is small, anything bigger is most likely a frontend bug filling the store.
+pub fn validate_value(key: &str, value: &Value) -> Result<(), String> {
+    if value.to_string().len() > MAX_VALUE_LENGTH {
+        return Err(format!(
+            "The UI state of {key} is over {MAX_VALUE_LENGTH} bytes"
+        ));
+    }
+    Ok(())
+}
+
+/// The value of `key`, `Value::Null` when it was never set.
+pub fn get(app_handle: &AppHandle, key: &str) -> Result<Value, String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to load {STORE_FILE} store: {e}"))?;
+    let value = store.get(key).unwrap_or(Value::Null);
+    store.close_resource();
+    Ok(value)
+}
+
+/// Sets `key` to `value`, `Value::Null` deletes it.
+pub fn set(app_handle: &AppHandle, key: &str, value: Value) -> Result<(), String> {
+    let store = app_handle
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to open {STORE_FILE} store: {e}"))?;
+    if value.is_null() {
+        store.delete(key);
+    } else {
+        store.set(key, value);
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save {STORE_FILE} store: {e}"))?;
+    store.close_resource();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_short_and_lowercase() {
+        assert_eq!(validate_key("jobs.completed.collapsed"), Ok(()));
+        assert_eq!(validate_key("library_profile"), Ok(()));
+        assert!(validate_key("").is_err());
+        assert!(validate_key("Search Sort").is_err());
+        assert!(validate_key(&"a".repeat(MAX_KEY_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,10 @@
+//! Timestamps that are stored or compared across restarts, an `Instant` doesn't survive one.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch, 0 if the clock is set before it.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -1,38 +1,48 @@
+use crate::models::title_info::TitleId;
 use crate::services;
+use crate::services::arr;
+use crate::services::naming;
+use crate::services::network;
+use crate::services::notifications::{
+    notify, notify_with_actions, NotificationActions, NotificationEvent, NotificationKind,
+};
+use crate::services::upload_review::{self, ReviewOutcome};
 use crate::state::background_process_state::BackgroundProcessState;
-use crate::state::job_state::{emit_progress, JobStatus, JobType};
+use crate::state::job_state::{emit_progress, JobId, JobStatus, JobType};
+use crate::state::locks::RwLockExt;
+use crate::state::resource_governor::Slot;
 use crate::state::title_video::{self, TitleVideo};
 use crate::state::upload_state::{PendingUpload, UploadType};
 use crate::state::uploaded_state::UploadedState;
 use crate::state::AppState;
 use crate::the_movie_db;
 use log::{error, info, warn};
+use std::fs;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
 use tauri::{AppHandle, Manager};
-use tauri_plugin_notification::NotificationExt;
+use tokio::sync::Semaphore;
 
-/// Resume uploads on boot - processes pending uploads sequentially
+/// TMDB lookups that run at once while the queue is rebuilt, the uploads themselves still
+/// wait for an upload slot.
+const RECONSTRUCT_CONCURRENCY: usize = 4;
+
+/// Resume uploads on boot - smallest files first so the queue clears faster
 /// This function runs asynchronously and does not block the boot process
 pub async fn resume_pending_uploads(app_handle: AppHandle) {
     info!("Starting upload recovery process");
 
-    // Get the UploadedState
-    let uploaded_state = match app_handle.try_state::<UploadedState>() {
-        Some(state) => {
-            let state_ref = state.inner();
-            Arc::new(UploadedState {
-                queue: Arc::clone(&state_ref.queue),
-            })
-        }
-        None => {
-            error!("Failed to get UploadedState for recovery");
-            return;
-        }
+    let Some(uploaded_state) = app_handle.try_state::<UploadedState>() else {
+        error!(
+            "The upload queue is not set up, pending uploads stay in uploads.json and resume \
+             on the next start"
+        );
+        return;
     };
+    let uploaded_state = UploadedState::clone_arc(uploaded_state.inner());
 
     // Get pending uploads
-    let pending_uploads = uploaded_state.get_pending();
+    let mut pending_uploads = uploaded_state.get_pending();
 
     if pending_uploads.is_empty() {
         info!("No pending uploads to resume");
@@ -40,26 +50,34 @@ pub async fn resume_pending_uploads(app_handle: AppHandle) {
     }
 
     info!("Found {} pending uploads to resume", pending_uploads.len());
+    if network::is_offline(&app_handle) {
+        // Reconstructing the metadata needs TMDB
+        info!("Offline mode is on, pending uploads resume once it is off");
+        network::wait_for_online(&app_handle).await;
+    }
+    sort_smallest_first(&mut pending_uploads);
+
+    // The metadata of every upload is looked up in the background while the ones before
+    // it upload, in the same order
+    let permits = Arc::new(Semaphore::new(RECONSTRUCT_CONCURRENCY));
+    let prepared: Vec<_> = pending_uploads
+        .into_iter()
+        .map(|pending_upload| {
+            let app_handle = app_handle.clone();
+            let uploaded_state = uploaded_state.clone();
+            let permits = permits.clone();
+            tauri::async_runtime::spawn(async move {
+                let _permit = permits.acquire_owned().await.ok()?;
+                let title_video =
+                    prepare_upload(&app_handle, &uploaded_state, &pending_upload).await?;
+                Some((pending_upload, title_video))
+            })
+        })
+        .collect();
 
-    // Process each upload sequentially (one at a time)
-    for pending_upload in pending_uploads {
-        // Check if file still exists before attempting upload
-        let path = Path::new(&pending_upload.video_path);
-        if !path.exists() {
-            warn!("Skipping non-existent file: {}", pending_upload.video_path);
-            // Remove from queue
-            if let Err(e) = uploaded_state.remove_upload(&app_handle, &pending_upload.video_path) {
-                error!("Failed to remove non-existent file from queue: {e}");
-            }
-            continue;
-        }
-
-        info!("Processing upload: {}", pending_upload.video_path);
-
-        // Try to reconstruct TitleVideo with TMDB metadata (blocking TMDB calls offloaded)
-        match reconstruct_title_video_with_tmdb(&pending_upload, &app_handle).await {
-            Ok(title_video) => {
-                // Upload the video using the standard upload function
+    for handle in prepared {
+        match handle.await {
+            Ok(Some((pending_upload, title_video))) => {
                 upload_video(
                     &app_handle,
                     &pending_upload.video_path,
@@ -68,18 +86,79 @@ pub async fn resume_pending_uploads(app_handle: AppHandle) {
                 )
                 .await;
             }
-            Err(e) => {
-                error!(
-                    "Failed to reconstruct video metadata for {}: {}",
-                    pending_upload.video_path, e
-                );
-            }
+            Ok(None) => {}
+            Err(e) => error!("Upload recovery task failed: {e}"),
         }
     }
 
     info!("Upload recovery process completed");
 }
 
+/// Upload one queued video now, used by the retry button of the upload queue.
+pub async fn resume_upload(
+    app_handle: &AppHandle,
+    uploaded_state: &Arc<UploadedState>,
+    pending_upload: &PendingUpload,
+) {
+    if let Some(title_video) = prepare_upload(app_handle, uploaded_state, pending_upload).await {
+        upload_video(
+            app_handle,
+            &pending_upload.video_path,
+            &title_video,
+            uploaded_state,
+        )
+        .await;
+    }
+}
+
+/// Smaller files upload sooner, files that can't be read go first and drop out of the queue.
+fn sort_smallest_first(pending_uploads: &mut [PendingUpload]) {
+    pending_uploads.sort_by_cached_key(|pending_upload| {
+        fs::metadata(&pending_upload.video_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0)
+    });
+}
+
+/// The TitleVideo of a queued video, `None` when the file is gone or TMDB doesn't know it.
+async fn prepare_upload(
+    app_handle: &AppHandle,
+    uploaded_state: &Arc<UploadedState>,
+    pending_upload: &PendingUpload,
+) -> Option<Arc<RwLock<TitleVideo>>> {
+    // Check if file still exists before attempting upload
+    let path = Path::new(&pending_upload.video_path);
+    if !path.exists() {
+        warn!("Skipping non-existent file: {}", pending_upload.video_path);
+        // Remove from queue
+        if let Err(e) = uploaded_state.remove_upload(app_handle, &pending_upload.video_path) {
+            error!("Failed to remove non-existent file from queue: {e}");
+        }
+        return None;
+    }
+
+    info!("Processing upload: {}", pending_upload.video_path);
+
+    // Try to reconstruct TitleVideo with TMDB metadata (blocking TMDB calls offloaded)
+    match reconstruct_title_video_with_tmdb(pending_upload, app_handle).await {
+        Ok(title_video) => Some(title_video),
+        Err(e) => {
+            error!(
+                "Failed to reconstruct video metadata for {}: {}",
+                pending_upload.video_path, e
+            );
+            if let Err(e) = uploaded_state.record_failure(
+                app_handle,
+                &pending_upload.video_path,
+                &format!("Could not look up the video on TMDB: {e}"),
+            ) {
+                error!("Failed to record the failed upload: {e}");
+            }
+            None
+        }
+    }
+}
+
 /// Reconstruct a TitleVideo from a pending upload using TMDB API
 async fn reconstruct_title_video_with_tmdb(
     pending_upload: &PendingUpload,
@@ -104,6 +183,10 @@ fn reconstruct_title_video_with_tmdb_blocking(
     match pending_upload.upload_type {
         UploadType::Movie => reconstruct_movie_with_tmdb_blocking(path, app_handle),
         UploadType::TvShow => reconstruct_tv_with_tmdb_blocking(path, app_handle),
+        UploadType::HomeVideo => {
+            let library = app_handle.state::<AppState>().library_name_for_path(path);
+            reconstruct_home_video(path, library)
+        }
     }
 }
 
@@ -131,7 +214,8 @@ fn reconstruct_movie_with_tmdb_blocking(
         return Err("TMDB API key not configured".to_string());
     }
 
-    let movie_db = the_movie_db::TheMovieDb::new(&api_key, "en-US");
+    let timeout = state.network_options().tmdb_timeout();
+    let movie_db = the_movie_db::TheMovieDb::new(&api_key, "en-US", timeout);
 
     // Search for the movie using dedicated search_movie endpoint with year filter
     let search_results = movie_db
@@ -161,7 +245,7 @@ fn reconstruct_movie_with_tmdb_blocking(
 
     // Create TitleInfo with the original filename
     let title_info = crate::models::title_info::TitleInfo {
-        id: 0,
+        id: TitleId::default(),
         name: None,
         chapter_count: None,
         duration: None,
@@ -175,6 +259,7 @@ fn reconstruct_movie_with_tmdb_blocking(
         lang: None,
         language: None,
         description: None,
+        video_size: None,
     };
 
     let video = title_video::Video::Movie(Box::new(movie));
@@ -182,6 +267,10 @@ fn reconstruct_movie_with_tmdb_blocking(
         id: title_video::TitleVideoId::new(),
         title: Some(title_info),
         video,
+        library: state.library_name_for_path(path),
+        video_file: None,
+        joined_titles: Vec::new(),
+        skip_upload: false,
     };
 
     info!("Successfully reconstructed metadata for {title} using TMDB");
@@ -210,21 +299,33 @@ fn reconstruct_tv_with_tmdb_blocking(
         return Err("TMDB API key not configured".to_string());
     }
 
-    let movie_db = the_movie_db::TheMovieDb::new(&api_key, "en-US");
-
-    // Search for the TV show using dedicated search_tv endpoint with year filter
-    let search_results = movie_db
-        .search_tv(&show_name, Some(year), 1)
-        .map_err(|e| format!("TMDB TV search failed: {}", e.message))?;
+    let timeout = state.network_options().tmdb_timeout();
+    let movie_db = the_movie_db::TheMovieDb::new(&api_key, "en-US", timeout);
 
-    // Get the first result (should be the best match)
-    let tv_result = search_results
-        .results
-        .first()
-        .ok_or_else(|| format!("No TMDB TV show found for {show_name} ({year})"))?;
+    // A tagged show folder says which show it is, shows sharing a name and year can't mix up
+    let tagged_id = path
+        .parent()
+        .and_then(Path::parent)
+        .and_then(Path::file_name)
+        .and_then(|name| naming::parse_tmdb_id_tag(&name.to_string_lossy()));
+    let tv_id = match tagged_id {
+        Some(tv_id) => tv_id,
+        None => {
+            // Search for the TV show using dedicated search_tv endpoint with year filter
+            let search_results = movie_db
+                .search_tv(&show_name, Some(year), 1)
+                .map_err(|e| format!("TMDB TV search failed: {}", e.message))?;
+
+            // Get the first result (should be the best match)
+            search_results
+                .results
+                .first()
+                .ok_or_else(|| format!("No TMDB TV show found for {show_name} ({year})"))?
+                .id
+        }
+    };
 
     // Get full TV show details
-    let tv_id = tv_result.id;
     let tv_response = movie_db
         .tv(tv_id)
         .map_err(|e| format!("Failed to get TV show details: {}", e.message))?;
@@ -272,7 +373,7 @@ fn reconstruct_tv_with_tmdb_blocking(
 
     // Create TitleInfo with the original filename
     let title_info = crate::models::title_info::TitleInfo {
-        id: 0,
+        id: TitleId::default(),
         name: None,
         chapter_count: None,
         duration: None,
@@ -286,6 +387,7 @@ fn reconstruct_tv_with_tmdb_blocking(
         lang: None,
         language: None,
         description: None,
+        video_size: None,
     };
 
     let video = title_video::Video::Tv(Box::new(tv_show));
@@ -293,6 +395,10 @@ fn reconstruct_tv_with_tmdb_blocking(
         id: title_video::TitleVideoId::new(),
         title: Some(title_info),
         video,
+        library: state.library_name_for_path(path),
+        video_file: None,
+        joined_titles: Vec::new(),
+        skip_upload: false,
     };
 
     info!(
@@ -425,6 +531,7 @@ async fn reconstruct_title_video(
             // Expected format: /path/to/TV Shows/ShowName/Season XX/ShowName - SXXEXX - Episode.ext
             reconstruct_tv_video(path)
         }
+        UploadType::HomeVideo => reconstruct_home_video(path, None),
     }
 }
 
@@ -443,22 +550,10 @@ fn reconstruct_movie_video(path: &Path) -> Result<Arc<RwLock<TitleVideo>>, Strin
     // We need to create a minimal MovieResponse for upload
     // Since we're only reconstructing for upload, we don't need full metadata
     let movie_response = the_movie_db::MovieResponse {
-        adult: false,
-        backdrop_path: None,
-        genres: vec![],
-        homepage: String::new(),
-        id: 0,
-        imdb_id: String::new(),
-        origin_country: vec![],
-        original_language: String::new(),
         original_title: title.clone(),
-        overview: String::new(),
-        popularity: 0.0,
-        poster_path: None,
         release_date: Some(format!("{year}-01-01")),
-        revenue: 0,
-        runtime: 0,
         title: title.clone(),
+        ..Default::default()
     };
 
     let movie = title_video::MoviePartEdition {
@@ -469,7 +564,7 @@ fn reconstruct_movie_video(path: &Path) -> Result<Arc<RwLock<TitleVideo>>, Strin
 
     // Create a minimal TitleInfo for the title
     let title_info = crate::models::title_info::TitleInfo {
-        id: 0,
+        id: TitleId::default(),
         name: None,
         chapter_count: None,
         duration: None,
@@ -483,6 +578,7 @@ fn reconstruct_movie_video(path: &Path) -> Result<Arc<RwLock<TitleVideo>>, Strin
         lang: None,
         language: None,
         description: None,
+        video_size: None,
     };
 
     let video = title_video::Video::Movie(Box::new(movie));
@@ -490,8 +586,53 @@ fn reconstruct_movie_video(path: &Path) -> Result<Arc<RwLock<TitleVideo>>, Strin
         id: title_video::TitleVideoId::new(),
         title: Some(title_info),
         video,
+        library: None,
+        video_file: None,
+        joined_titles: Vec::new(),
+        skip_upload: false,
+    };
+
+    Ok(Arc::new(RwLock::new(title_video)))
+}
+
+/// Home videos have nothing to look up, the folder is "Name (Year)" and the filename
+/// only adds the part.
+fn reconstruct_home_video(
+    path: &Path,
+    library: Option<String>,
+) -> Result<Arc<RwLock<TitleVideo>>, String> {
+    let filename = path
+        .file_name()
+        .ok_or_else(|| "No filename found".to_string())?
+        .to_string_lossy()
+        .to_string();
+    let folder = path
+        .parent()
+        .and_then(Path::file_name)
+        .ok_or_else(|| format!("No home video folder found for {filename}"))?
+        .to_string_lossy()
+        .to_string();
+    let (name, date) = match parse_movie_filename(&folder) {
+        Ok((name, year)) => (name, Some(year)),
+        Err(_) => (folder, None),
     };
+    let (_, part) = parse_edition_and_part(&filename);
 
+    let mut title_info = crate::models::title_info::TitleInfo::new(0);
+    title_info.filename = Some(filename);
+    let title_video = title_video::TitleVideo {
+        id: title_video::TitleVideoId::new(),
+        title: Some(title_info),
+        video: title_video::Video::Personal(Box::new(title_video::PersonalVideo {
+            name,
+            date,
+            part,
+        })),
+        library,
+        video_file: None,
+        joined_titles: Vec::new(),
+        skip_upload: false,
+    };
     Ok(Arc::new(RwLock::new(title_video)))
 }
 
@@ -531,68 +672,22 @@ fn reconstruct_tv_video(path: &Path) -> Result<Arc<RwLock<TitleVideo>>, String>
 
     // Create minimal TV show structures for upload
     let tv_response = the_movie_db::TvResponse {
-        adult: false,
-        backdrop_path: None,
-        created_by: vec![],
-        episode_run_time: vec![],
-        first_air_date: None,
-        genres: vec![],
-        homepage: None,
-        id: the_movie_db::TvId::from(0),
-        in_production: false,
-        languages: vec![],
-        last_air_date: None,
-        last_episode_to_air: None,
         name: show_name.clone(),
-        networks: vec![],
-        next_episode_to_air: None,
-        number_of_episodes: 0,
-        number_of_seasons: 0,
-        origin_country: vec![],
-        original_language: String::new(),
         original_name: show_name.clone(),
-        overview: String::new(),
-        popularity: 0.0,
-        poster_path: None,
-        production_companies: vec![],
-        production_countries: vec![],
-        seasons: vec![],
-        spoken_languages: vec![],
-        status: String::new(),
-        tagline: String::new(),
-        type_: String::new(),
-        vote_average: 0.0,
-        vote_count: 0,
+        ..Default::default()
     };
 
     let season_response = the_movie_db::SeasonResponse {
-        _id: String::new(),
-        air_date: None,
-        episodes: vec![],
         name: format!("Season {season}"),
-        overview: String::new(),
-        id: 0,
-        poster_path: None,
         season_number: season,
-        vote_average: 0.0,
+        ..Default::default()
     };
 
     let episode_obj = the_movie_db::SeasonEpisode {
-        air_date: None,
         episode_number: episode,
-        episode_type: String::new(),
-        id: 0,
         name: format!("Episode {episode}"),
-        overview: String::new(),
-        production_code: None,
-        runtime: None,
         season_number: season,
-        show_id: 0,
-        still_path: None,
-        vote_average: 0.0,
-        vote_count: 0,
-        crew: vec![],
-        guest_stars: vec![],
+        ..Default::default()
     };
 
     let tv_show = title_video::TvSeasonEpisode {
@@ -604,7 +699,7 @@ fn reconstruct_tv_video(path: &Path) -> Result<Arc<RwLock<TitleVideo>>, String>
 
     // Create a minimal TitleInfo for the title
     let title_info = crate::models::title_info::TitleInfo {
-        id: 0,
+        id: TitleId::default(),
         name: None,
         chapter_count: None,
         duration: None,
@@ -618,6 +713,7 @@ fn reconstruct_tv_video(path: &Path) -> Result<Arc<RwLock<TitleVideo>>, String>
         lang: None,
         language: None,
         description: None,
+        video_size: None,
     };
 
     let video = title_video::Video::Tv(Box::new(tv_show));
@@ -625,6 +721,10 @@ fn reconstruct_tv_video(path: &Path) -> Result<Arc<RwLock<TitleVideo>>, String>
         id: title_video::TitleVideoId::new(),
         title: Some(title_info),
         video,
+        library: None,
+        video_file: None,
+        joined_titles: Vec::new(),
+        skip_upload: false,
     };
 
     Ok(Arc::new(RwLock::new(title_video)))
@@ -677,39 +777,45 @@ async fn upload_video(
 ) {
     let background_process_state = app_handle.state::<BackgroundProcessState>();
 
-    let (job, is_new) = background_process_state.find_or_create_job(
-        None,
-        &None,
-        &JobType::Uploading,
-        &JobStatus::Pending,
-    );
-
-    job.write()
-        .expect("Failed to get job writer")
+    let job = background_process_state.new_job(JobType::Uploading, JobStatus::Pending, None);
+    job.write_or_recover()
         .title_videos
         .push(title_video.clone());
-    job.write()
-        .expect("Failed to get job writer")
-        .update_status(JobStatus::Processing);
-    job.write().expect("Failed to get job writer").subtitle =
-        Some(format!("Resuming upload: {video_path}"));
-
-    if is_new {
-        background_process_state.emit_jobs_changed(app_handle);
+    background_process_state.group_by_season(&job, &title_video.read_or_recover());
+    background_process_state.emit_jobs_changed(app_handle);
+    if upload_review::wait_for_review(app_handle, &job, title_video, Path::new(video_path)).await
+        == ReviewOutcome::Rejected
+    {
+        if let Err(e) = uploaded_state.remove_upload(app_handle, video_path) {
+            error!("Failed to remove video from upload queue: {e}");
+        }
+        emit_progress(app_handle, &job, true);
+        return;
     }
-    job.read()
-        .expect("Failed to get job reader")
-        .emit_progress_change(app_handle);
+    network::wait_until_online(app_handle, &job).await;
+    let _upload_slot = background_process_state
+        .wait_for_slot(app_handle, &job, Slot::Upload)
+        .await;
+    job.write_or_recover().update_status(JobStatus::Processing);
+    job.write_or_recover().subtitle = Some(format!("Resuming upload: {video_path}"));
+
+    job.read_or_recover().emit_progress_change(app_handle);
+    notify(
+        app_handle,
+        NotificationKind::Upload,
+        NotificationEvent::Start,
+        "Resuming Upload",
+        video_path,
+    );
 
     // Use the standard ftp_uploader::upload function
     match services::ftp_uploader::upload(app_handle, &job, title_video).await {
         Ok(_) => {
             info!("Successfully uploaded: {video_path}");
             notify_upload_success(app_handle, video_path);
+            arr::spawn_rescan(app_handle, &title_video.read_or_recover().video);
 
-            job.write()
-                .expect("Failed to acquire write lock on job")
-                .update_status(JobStatus::Finished);
+            job.write_or_recover().update_status(JobStatus::Finished);
             emit_progress(app_handle, &job, true);
 
             // Remove from upload queue on success
@@ -723,14 +829,16 @@ async fn upload_video(
         Err(e) => {
             error!("Failed to upload {video_path}: {e}");
 
-            job.write()
-                .expect("Failed to get job writer")
-                .update_status(JobStatus::Error);
-            job.write().expect("Failed to get job writer").message = Some(e.clone());
+            job.write_or_recover().update_status(JobStatus::Error);
+            job.write_or_recover().message = Some(e.clone());
             emit_progress(app_handle, &job, true);
 
-            notify_upload_failure(app_handle, video_path, &e);
+            let job_id = job.read_or_recover().id;
+            notify_upload_failure(app_handle, job_id, video_path, &e);
             // Keep in upload queue for retry on next boot
+            if let Err(e) = uploaded_state.record_failure(app_handle, video_path, &e) {
+                error!("Failed to record the failed upload: {e}");
+            }
         }
     }
 }
@@ -740,27 +848,26 @@ fn notify_upload_success(app_handle: &AppHandle, file_path: &str) {
         .file_name()
         .unwrap_or_default()
         .to_string_lossy();
-    app_handle
-        .notification()
-        .builder()
-        .title("Upload Resumed Successfully")
-        .body(format!("Uploaded: {filename}"))
-        .show()
-        .unwrap();
+    notify(
+        app_handle,
+        NotificationKind::Upload,
+        NotificationEvent::Finish,
+        "Upload Resumed Successfully",
+        format!("Uploaded: {filename}"),
+    );
 }
 
-fn notify_upload_failure(app_handle: &AppHandle, file_path: &str, error: &str) {
-    let filename = Path::new(file_path)
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy();
-    app_handle
-        .notification()
-        .builder()
-        .title("Failed to Resume Upload")
-        .body(format!("{filename}: {error}"))
-        .show()
-        .unwrap();
+fn notify_upload_failure(app_handle: &AppHandle, job_id: JobId, file_path: &str, error: &str) {
+    let path = Path::new(file_path);
+    let filename = path.file_name().unwrap_or_default().to_string_lossy();
+    notify_with_actions(
+        app_handle,
+        NotificationKind::Upload,
+        NotificationEvent::Error,
+        "Failed to Resume Upload",
+        format!("{filename}: {error}"),
+        Some(NotificationActions::UploadFailed { job_id }),
+    );
 }
 
 fn delete_file(file_path: &str) {
@@ -772,6 +879,37 @@ fn delete_file(file_path: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::test_support::test_dir;
+
+    #[test]
+    fn resumes_the_smallest_files_first() {
+        let dir = test_dir("upload-recovery-order");
+        let pending = |name: &str, size: usize| {
+            let path = dir.join(name);
+            fs::write(&path, vec![0u8; size]).unwrap();
+            PendingUpload {
+                video_path: path.to_string_lossy().to_string(),
+                upload_type: UploadType::Movie,
+                attempts: 0,
+                last_error: None,
+            }
+        };
+        let mut pending_uploads = vec![pending("big.mkv", 30), pending("small.mkv", 10)];
+        pending_uploads.push(PendingUpload {
+            video_path: dir.join("gone.mkv").to_string_lossy().to_string(),
+            upload_type: UploadType::Movie,
+            attempts: 0,
+            last_error: None,
+        });
+
+        sort_smallest_first(&mut pending_uploads);
+        let names: Vec<&str> = pending_uploads
+            .iter()
+            .map(|pending_upload| pending_upload.video_path.rsplit('/').next().unwrap())
+            .collect();
+        assert_eq!(names, vec!["gone.mkv", "small.mkv", "big.mkv"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
     #[test]
     fn test_parse_movie_filename() {
@@ -791,6 +929,24 @@ mod tests {
         assert_eq!(year, "2008");
     }
 
+    #[test]
+    fn test_reconstruct_home_video() {
+        let title_video = reconstruct_home_video(
+            Path::new("/rips/Home Videos/Smith Wedding (2004)/Smith Wedding (2004)-pt2.mkv"),
+            None,
+        )
+        .unwrap();
+        let title_video = title_video.read().unwrap();
+        match &title_video.video {
+            title_video::Video::Personal(personal) => {
+                assert_eq!(personal.name, "Smith Wedding");
+                assert_eq!(personal.year(), Some(2004));
+                assert_eq!(personal.part, Some(2));
+            }
+            _ => panic!("Expected a home video"),
+        }
+    }
+
     #[test]
     fn test_parse_tv_filename() {
         let result = parse_tv_filename("Game of Thrones - S01E01 - Winter is Coming");
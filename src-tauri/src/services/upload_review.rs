@@ -0,0 +1,145 @@
+//! Holds finished rips back from the upload until they are looked at, a bad rip would
+//! replace the good copy on the server otherwise. The upload job waits in
+//! `JobStatus::AwaitingReview` with a few frames of the rip until it is approved or rejected
+//! in the job view, or approved on its own after `auto_approve_hours`.
+use crate::services::{notifications, preview};
+use crate::state::job_state::{emit_progress, Job, JobStatus, TitlePreview};
+use crate::state::locks::RwLockExt;
+use crate::state::title_video::TitleVideo;
+use crate::state::AppState;
+use crate::templates::toast::Toast;
+use log::{debug, info};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const REVIEW_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReviewOptions {
+    /// Uploads wait for an approval
+    pub enabled: bool,
+    /// Uploads that weren't reviewed start after this many hours, never when not set
+    pub auto_approve_hours: Option<u64>,
+}
+
+impl ReviewOptions {
+    fn auto_approve_after(&self) -> Option<Duration> {
+        self.auto_approve_hours
+            .map(|hours| Duration::from_secs(hours * 60 * 60))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReviewOutcome {
+    Approved,
+    Rejected,
+}
+
+/// What the review of `status` came to, `None` while it is still open.
+fn outcome(
+    status: &JobStatus,
+    waited: Duration,
+    auto_approve_after: Option<Duration>,
+) -> Option<ReviewOutcome> {
+    match status {
+        JobStatus::AwaitingReview if auto_approve_after.is_some_and(|after| waited >= after) => {
+            Some(ReviewOutcome::Approved)
+        }
+        JobStatus::AwaitingReview => None,
+        JobStatus::Error => Some(ReviewOutcome::Rejected),
+        JobStatus::Pending | JobStatus::Processing | JobStatus::Finished => {
+            Some(ReviewOutcome::Approved)
+        }
+    }
+}
+
+/// Keeps the upload `job` in review until it is approved or rejected, right away approved
+/// when reviews are off.
+pub async fn wait_for_review(
+    app_handle: &AppHandle,
+    job: &Arc<RwLock<Job>>,
+    title_video: &Arc<RwLock<TitleVideo>>,
+    path: &Path,
+) -> ReviewOutcome {
+    let options = app_handle.state::<AppState>().review_options();
+    if !options.enabled {
+        return ReviewOutcome::Approved;
+    }
+    let file_name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let duration = title_video
+        .read_or_recover()
+        .title
+        .as_ref()
+        .and_then(|title| title.duration_seconds());
+    if let Some(duration) = duration {
+        match preview::extract(app_handle, path, duration).await {
+            Ok(images) => job.write_or_recover().previews.push(TitlePreview {
+                title: file_name.clone(),
+                images,
+            }),
+            Err(e) => debug!("No review frames for {file_name}: {e}"),
+        }
+    }
+    {
+        let mut job = job.write_or_recover();
+        job.update_status(JobStatus::AwaitingReview);
+        job.subtitle = Some(match options.auto_approve_hours {
+            Some(hours) => format!("Waiting for review, uploads on its own in {hours}h"),
+            None => "Waiting for review".to_string(),
+        });
+    }
+    emit_progress(app_handle, job, true);
+    notifications::emit_toast(
+        app_handle,
+        Toast::info(
+            "Ready for review",
+            format!("{file_name} uploads once it is approved"),
+        ),
+    );
+
+    let started = Instant::now();
+    loop {
+        let status = job.read_or_recover().status.clone();
+        if let Some(outcome) = outcome(&status, started.elapsed(), options.auto_approve_after()) {
+            if status == JobStatus::AwaitingReview {
+                info!("Approved the upload of {file_name} after waiting for review");
+                let mut job = job.write_or_recover();
+                job.update_status(JobStatus::Pending);
+                job.add_notice("Approved on its own, nobody reviewed it in time".to_string());
+            }
+            return outcome;
+        }
+        tokio::time::sleep(REVIEW_CHECK_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_a_decision_or_the_auto_approval() {
+        let hour = Some(Duration::from_secs(60 * 60));
+        let waiting = JobStatus::AwaitingReview;
+        assert_eq!(outcome(&waiting, Duration::from_secs(60), hour), None);
+        assert_eq!(outcome(&waiting, Duration::from_secs(99_999), None), None);
+        assert_eq!(
+            outcome(&waiting, Duration::from_secs(60 * 60), hour),
+            Some(ReviewOutcome::Approved)
+        );
+        assert_eq!(
+            outcome(&JobStatus::Pending, Duration::ZERO, None),
+            Some(ReviewOutcome::Approved)
+        );
+        assert_eq!(
+            outcome(&JobStatus::Error, Duration::ZERO, None),
+            Some(ReviewOutcome::Rejected)
+        );
+    }
+}
@@ -1,5 +1,5 @@
+use crate::reelix_error::ReelixError;
 use crate::services::semantic_version::SemanticVersion;
-use crate::standard_error::StandardError;
 use log::debug;
 use tauri::App;
 use tauri::AppHandle;
@@ -55,7 +55,7 @@ pub fn spawn_version_checker(app: &App) {
     });
 }
 
-pub async fn check_on_boot(app_handle: &AppHandle) -> Result<VersionState, StandardError> {
+pub async fn check_on_boot(app_handle: &AppHandle) -> Result<VersionState, ReelixError> {
     let current_version =
         match SemanticVersion::parse(&app_handle.package_info().version.to_string()) {
             Ok(v) => v,
@@ -63,11 +63,13 @@ pub async fn check_on_boot(app_handle: &AppHandle) -> Result<VersionState, Stand
         };
     let app_state = app_handle.state::<crate::state::AppState>();
     let version_state = app_state.get_version_state(app_handle);
-    if version_state.has_update {
+    let network_options = app_state.network_options();
+    if version_state.has_update || network_options.offline {
         return Ok(version_state);
     }
 
-    let latest_version = github_api::fetch_latest_release_version().await;
+    let latest_version =
+        github_api::fetch_latest_release_version(network_options.github_timeout()).await;
 
     Ok(VersionState::new(current_version, latest_version))
 }
@@ -1,50 +1,324 @@
-// use log::debug;
-// use std::fs::File;
-// use std::io::{Read, Write};
-// use std::path::Path;
-// use walkdir::WalkDir;
-// use zip::{result::ZipError, write::SimpleFileOptions, CompressionMethod, ZipWriter};
-
-// pub fn zip_dir(src_dir: &Path, dst_file: &Path, method: CompressionMethod) -> Result<(), ZipError> {
-//     if !Path::new(src_dir).is_dir() {
-//         return Err(ZipError::FileNotFound);
-//     }
-
-//     let path = Path::new(&dst_file);
-//     let file = File::create(path).unwrap();
-
-//     let walkdir = WalkDir::new(src_dir);
-//     let dir_entries = walkdir.into_iter().filter_map(|e| e.ok());
-
-//     let mut zip = ZipWriter::new(file);
-//     let options = SimpleFileOptions::default()
-//         .compression_method(method)
-//         .unix_permissions(0o755);
-
-//     let prefix = Path::new(src_dir);
-//     let mut buffer = Vec::new();
-//     for entry in dir_entries {
-//         let path = entry.path();
-//         let name = path.strip_prefix(prefix).unwrap();
-//         let path_as_string = name.to_str().map(str::to_owned).unwrap();
-
-//         // Write file or directory explicitly
-//         // Some unzip tools unzip files with directory paths correctly, some do not!
-//         if path.is_file() {
-//             debug!("adding file {path:?} as {name:?} ...");
-//             zip.start_file(path_as_string, options)?;
-//             let mut f = File::open(path)?;
-
-//             f.read_to_end(&mut buffer)?;
-//             zip.write_all(&buffer)?;
-//             buffer.clear();
-//         } else if !name.as_os_str().is_empty() {
-//             // Only if not root! Avoids path spec / warning
-//             // and map name conversion failed error on unzip
-//             debug!("adding dir {path_as_string:?} as {name:?} ...");
-//             zip.add_directory(path_as_string, options)?;
-//         }
-//     }
-//     zip.finish()?;
-//     Ok(())
-// }
+use log::debug;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use zip::{result::ZipError, write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+/// Files this big need ZIP64, the classic format stops at 4 GiB
+const ZIP64_THRESHOLD: u64 = u32::MAX as u64;
+/// Video and images are compressed already, deflating them only costs time
+const COMPRESSED_EXTENSIONS: &[&str] = &[
+    "mkv", "mp4", "m4v", "avi", "mov", "m2ts", "mts", "vob", "evo", "jpg", "jpeg", "png", "gif",
+    "heic", "mp3", "m4a", "aac", "ogg", "flac", "zip", "7z", "rar", "gz",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZipOptions {
+    pub method: CompressionMethod,
+    /// Stores everything, for folders that are mostly video
+    pub store_only: bool,
+    /// ZIP64 for every file, files over 4 GiB get it anyway
+    pub zip64: bool,
+    /// Splits the zip into volumes of this many bytes, see `VolumeWriter`
+    pub volume_size: Option<u64>,
+}
+
+impl Default for ZipOptions {
+    fn default() -> Self {
+        ZipOptions {
+            method: CompressionMethod::Deflated,
+            store_only: false,
+            zip64: false,
+            volume_size: None,
+        }
+    }
+}
+
+impl ZipOptions {
+    /// Already compressed files are stored whatever the method.
+    fn method_for(&self, path: &Path) -> CompressionMethod {
+        let compressed = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                COMPRESSED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+            });
+        if self.store_only || compressed {
+            CompressionMethod::Stored
+        } else {
+            self.method
+        }
+    }
+}
+
+/// One stream written into `<name>.001`, `<name>.002`... of `volume_size` bytes each, the way
+/// 7-Zip splits archives, so no file is bigger than FAT32 allows. The volumes join back with
+/// `cat` or 7-Zip.
+pub struct VolumeWriter {
+    base: PathBuf,
+    volume_size: u64,
+    volumes: Vec<File>,
+    position: u64,
+    len: u64,
+}
+
+impl VolumeWriter {
+    pub fn new(base: &Path, volume_size: u64) -> Self {
+        VolumeWriter {
+            base: base.to_path_buf(),
+            volume_size: volume_size.max(1),
+            volumes: Vec::new(),
+            position: 0,
+            len: 0,
+        }
+    }
+
+    /// `<name>.001` for the first volume
+    pub fn volume_path(base: &Path, index: usize) -> PathBuf {
+        let mut path = OsString::from(base.as_os_str());
+        path.push(format!(".{:03}", index + 1));
+        PathBuf::from(path)
+    }
+
+    /// The volumes written so far, first to last
+    pub fn paths(&self) -> Vec<PathBuf> {
+        (0..self.volumes.len())
+            .map(|index| Self::volume_path(&self.base, index))
+            .collect()
+    }
+
+    fn volume(&mut self, index: usize) -> io::Result<&mut File> {
+        while self.volumes.len() <= index {
+            let path = Self::volume_path(&self.base, self.volumes.len());
+            let file = File::options()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)?;
+            self.volumes.push(file);
+        }
+        Ok(&mut self.volumes[index])
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let index = (self.position / self.volume_size) as usize;
+        let offset = self.position % self.volume_size;
+        let room = (self.volume_size - offset).min(buf.len() as u64) as usize;
+        let file = self.volume(index)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let written = file.write(&buf[..room])?;
+        self.position += written as u64;
+        self.len = self.len.max(self.position);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.volumes.iter_mut().try_for_each(|file| file.flush())
+    }
+}
+
+impl Seek for VolumeWriter {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        let position = match position {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position = position.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before the first volume")
+        })?;
+        Ok(self.position)
+    }
+}
+
+/// Zip everything under `src_dir` into `dst_file`, streaming each file in chunks.
+///
+/// `on_progress` gets the bytes zipped so far and the total after every chunk, returning false
+/// cancels the zip. A cancelled or failed zip is removed. Returns the files written, the zip
+/// or its volumes.
+pub fn zip_dir(
+    src_dir: &Path,
+    dst_file: &Path,
+    options: ZipOptions,
+    mut on_progress: impl FnMut(u64, u64) -> bool,
+) -> Result<Vec<PathBuf>, ZipError> {
+    if !Path::new(src_dir).is_dir() {
+        return Err(ZipError::FileNotFound);
+    }
+
+    match options.volume_size {
+        Some(volume_size) => {
+            let writer = VolumeWriter::new(dst_file, volume_size);
+            match write_zip(src_dir, writer, options, &mut on_progress) {
+                Ok(writer) => Ok(writer.paths()),
+                Err(e) => {
+                    // The volumes that were started are numbered from 001 on
+                    let mut index = 0;
+                    while fs::remove_file(VolumeWriter::volume_path(dst_file, index)).is_ok() {
+                        index += 1;
+                    }
+                    Err(e)
+                }
+            }
+        }
+        None => {
+            let result = write_zip(src_dir, File::create(dst_file)?, options, &mut on_progress);
+            if let Err(e) = result {
+                let _ = fs::remove_file(dst_file);
+                return Err(e);
+            }
+            Ok(vec![dst_file.to_path_buf()])
+        }
+    }
+}
+
+fn write_zip<W: Write + Seek>(
+    src_dir: &Path,
+    writer: W,
+    options: ZipOptions,
+    on_progress: &mut impl FnMut(u64, u64) -> bool,
+) -> Result<W, ZipError> {
+    let entries: Vec<walkdir::DirEntry> = WalkDir::new(src_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .collect();
+    let total: u64 = entries
+        .iter()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    let mut zip = ZipWriter::new(writer);
+    let directory_options = SimpleFileOptions::default().unix_permissions(0o755);
+
+    let mut zipped = 0;
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    for entry in entries {
+        let path = entry.path();
+        let Ok(name) = path.strip_prefix(src_dir) else {
+            continue;
+        };
+        let path_as_string = name.to_string_lossy().replace('\\', "/");
+
+        // Write file or directory explicitly
+        // Some unzip tools unzip files with directory paths correctly, some do not!
+        if path.is_file() {
+            debug!("adding file {path:?} as {name:?} ...");
+            let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            let file_options = directory_options
+                .compression_method(options.method_for(path))
+                .large_file(options.zip64 || size >= ZIP64_THRESHOLD);
+            zip.start_file(path_as_string, file_options)?;
+            let mut f = File::open(path)?;
+            loop {
+                let bytes_read = f.read(&mut buffer)?;
+                if bytes_read == 0 {
+                    break;
+                }
+                zip.write_all(&buffer[..bytes_read])?;
+                zipped += bytes_read as u64;
+                if !on_progress(zipped, total) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Interrupted,
+                        "Zipping was cancelled",
+                    )
+                    .into());
+                }
+            }
+        } else if !name.as_os_str().is_empty() {
+            // Only if not root! Avoids path spec / warning
+            // and map name conversion failed error on unzip
+            debug!("adding dir {path_as_string:?} as {name:?} ...");
+            zip.add_directory(path_as_string, directory_options)?;
+        }
+    }
+    debug!("zipped {zipped} bytes");
+    zip.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::test_dir;
+    use std::path::PathBuf;
+
+    fn sample_folder(name: &str) -> PathBuf {
+        let dir = test_dir(&format!("zip-directory-{name}")).join("DISC");
+        fs::create_dir_all(dir.join("VIDEO")).unwrap();
+        fs::write(dir.join("README.TXT"), b"hello").unwrap();
+        fs::write(dir.join("VIDEO/CLIP.MKV"), b"matroska").unwrap();
+        dir
+    }
+
+    #[test]
+    fn zips_the_folder_and_stores_video() {
+        let dir = sample_folder("zip");
+        let zip_file = dir.with_extension("zip");
+        let mut progress = Vec::new();
+        let files = zip_dir(&dir, &zip_file, ZipOptions::default(), |zipped, total| {
+            progress.push((zipped, total));
+            true
+        })
+        .unwrap();
+
+        assert_eq!(files, std::slice::from_ref(&zip_file));
+        assert_eq!(progress.last(), Some(&(13, 13)));
+        let mut archive = zip::ZipArchive::new(File::open(&zip_file).unwrap()).unwrap();
+        assert_eq!(
+            archive.by_name("VIDEO/CLIP.MKV").unwrap().compression(),
+            CompressionMethod::Stored
+        );
+        assert_eq!(
+            archive.by_name("README.TXT").unwrap().compression(),
+            CompressionMethod::Deflated
+        );
+    }
+
+    #[test]
+    fn splits_the_zip_into_volumes() {
+        let dir = sample_folder("volumes");
+        let zip_file = dir.with_extension("zip");
+        let options = ZipOptions {
+            volume_size: Some(100),
+            ..ZipOptions::default()
+        };
+        let volumes = zip_dir(&dir, &zip_file, options, |_, _| true).unwrap();
+
+        assert!(volumes.len() > 1);
+        assert_eq!(volumes[0], dir.with_extension("zip.001"));
+        let mut joined = Vec::new();
+        for volume in &volumes {
+            let mut bytes = fs::read(volume).unwrap();
+            assert!(bytes.len() <= 100);
+            joined.append(&mut bytes);
+        }
+        let mut archive = zip::ZipArchive::new(io::Cursor::new(joined)).unwrap();
+        let mut readme = String::new();
+        archive
+            .by_name("README.TXT")
+            .unwrap()
+            .read_to_string(&mut readme)
+            .unwrap();
+        assert_eq!(readme, "hello");
+    }
+
+    #[test]
+    fn removes_a_cancelled_zip() {
+        let dir = sample_folder("cancel");
+        let zip_file = dir.with_extension("zip");
+        let result = zip_dir(&dir, &zip_file, ZipOptions::default(), |_, _| false);
+
+        assert!(matches!(result, Err(ZipError::Io(e)) if e.kind() == io::ErrorKind::Interrupted));
+        assert!(!zip_file.exists());
+    }
+}
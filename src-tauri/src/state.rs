@@ -1,12 +1,38 @@
 use crate::models::optical_disk_info::{DiskId, OpticalDiskInfo};
+use crate::services::archive::ArchiveOptions;
+use crate::services::arr::ArrOptions;
+use crate::services::assignment_patterns::{self, AssignmentPattern};
+use crate::services::disc_memory::{self, DiscMemory};
+use crate::services::disk_manager::TrayOptions;
 use crate::services::ftp_validator;
+use crate::services::library::{self, Library, LibraryProfile};
+use crate::services::library_dir;
+use crate::services::locale::{self, Locale};
+use crate::services::naming::NamingOptions;
+use crate::services::network::NetworkOptions;
+use crate::services::notifications::{DoNotDisturb, HeldMessage, NotificationPreferences};
+use crate::services::post_process::PostProcessOptions;
+use crate::services::push::{PushOptions, PushService};
+use crate::services::remote_api::RemoteApiOptions;
+use crate::services::rip_plan::{self, RipPlan};
+use crate::services::ripper_backend::{self, RipperKind, RipperOptions};
+use crate::services::trakt::TraktOptions;
+use crate::services::upload_review::ReviewOptions;
+use crate::state::locks::{MutexExt, RwLockExt};
+use crate::state::resource_governor::ConcurrencyLimits;
 use log::debug;
-use std::path::PathBuf;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 use tauri_plugin_store::StoreExt;
 
 pub mod background_process_state;
+pub mod drive_stats_state;
+pub mod emit_coalescer;
 pub mod job_state;
+pub mod locks;
+pub mod resource_governor;
+pub mod store_migrations;
 pub mod title_video;
 pub mod upload_state;
 pub mod uploaded_state;
@@ -16,6 +42,12 @@ pub struct FtpConfig {
     pub host: Option<String>,
     pub movie_upload_path: Option<PathBuf>,
     pub tv_upload_path: Option<PathBuf>,
+    pub movie_path_template: Option<String>,
+    pub tv_path_template: Option<String>,
+    /// Octal mode set with SITE CHMOD after an upload, e.g. "664" so Plex can read the file
+    pub file_mode: Option<String>,
+    /// Upload the TMDB season poster as `Season02.jpg` when the season folder has none
+    pub season_posters: bool,
     pub pass: Option<String>,
     pub user: Option<String>,
     pub checker: ftp_validator::FtpChecker,
@@ -29,6 +61,10 @@ impl FtpConfig {
             pass: None,
             movie_upload_path: None,
             tv_upload_path: None,
+            movie_path_template: None,
+            tv_path_template: None,
+            file_mode: None,
+            season_posters: true,
             checker: ftp_validator::FtpChecker::new(),
         }
     }
@@ -38,6 +74,20 @@ impl FtpConfig {
     }
 }
 
+/// The fields of the FTP settings form, an empty field clears the setting.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FtpSettingsUpdate {
+    pub host: String,
+    pub user: String,
+    pub pass: String,
+    pub movie_upload_path: String,
+    pub tv_upload_path: String,
+    pub movie_path_template: String,
+    pub tv_path_template: String,
+    pub file_mode: String,
+}
+
 impl PartialEq for FtpConfig {
     fn eq(&self, other: &Self) -> bool {
         self.host == other.host
@@ -45,6 +95,10 @@ impl PartialEq for FtpConfig {
             && self.pass == other.pass
             && self.movie_upload_path == other.movie_upload_path
             && self.tv_upload_path == other.tv_upload_path
+            && self.movie_path_template == other.movie_path_template
+            && self.tv_path_template == other.tv_path_template
+            && self.file_mode == other.file_mode
+            && self.season_posters == other.season_posters
     }
 }
 
@@ -141,6 +195,28 @@ pub struct AppState {
     pub tv_shows_dir: Arc<RwLock<PathBuf>>,
     pub current_video: Arc<Mutex<Option<title_video::Video>>>,
     pub latest_version: Arc<Mutex<Option<String>>>,
+    pub naming_options: Arc<Mutex<NamingOptions>>,
+    pub post_process_options: Arc<Mutex<PostProcessOptions>>,
+    pub concurrency_limits: Arc<Mutex<ConcurrencyLimits>>,
+    pub notification_preferences: Arc<Mutex<NotificationPreferences>>,
+    pub do_not_disturb: Arc<Mutex<DoNotDisturb>>,
+    pub push_options: Arc<Mutex<PushOptions>>,
+    pub network_options: Arc<Mutex<NetworkOptions>>,
+    pub review_options: Arc<Mutex<ReviewOptions>>,
+    pub remote_api_options: Arc<Mutex<RemoteApiOptions>>,
+    pub library_profiles: Arc<Mutex<Vec<LibraryProfile>>>,
+    pub ripper_options: Arc<Mutex<RipperOptions>>,
+    pub tray_options: Arc<Mutex<TrayOptions>>,
+    pub trakt_options: Arc<Mutex<TraktOptions>>,
+    pub archive_options: Arc<Mutex<ArchiveOptions>>,
+    pub arr_options: Arc<Mutex<ArrOptions>>,
+    pub rip_plan: Arc<Mutex<RipPlan>>,
+    /// Title order of the last ripped disc of every season, see `assignment_patterns`
+    pub assignment_patterns: Arc<Mutex<Vec<AssignmentPattern>>>,
+    /// What the discs of a label were ripped as, see `disc_memory`
+    pub disc_memories: Arc<Mutex<Vec<DiscMemory>>>,
+    /// Errors that came in during do not disturb, not persisted
+    pub held_messages: Arc<Mutex<Vec<HeldMessage>>>,
 }
 
 impl AppState {
@@ -148,24 +224,49 @@ impl AppState {
 
     pub fn new() -> Self {
         Self {
+            archive_options: Arc::new(Mutex::new(ArchiveOptions::default())),
+            arr_options: Arc::new(Mutex::new(ArrOptions::default())),
+            assignment_patterns: Arc::new(Mutex::new(Vec::new())),
+            concurrency_limits: Arc::new(Mutex::new(ConcurrencyLimits::default())),
             current_video: Arc::new(Mutex::new(None)),
+            disc_memories: Arc::new(Mutex::new(Vec::new())),
+            do_not_disturb: Arc::new(Mutex::new(DoNotDisturb::default())),
             ftp_config: Arc::new(Mutex::new(FtpConfig::new())),
+            held_messages: Arc::new(Mutex::new(Vec::new())),
             latest_version: Arc::new(Mutex::new(None)),
+            library_profiles: Arc::new(Mutex::new(Vec::new())),
             movies_dir: Arc::new(RwLock::new(Self::default_movies_dir())),
+            naming_options: Arc::new(Mutex::new(NamingOptions::default())),
+            notification_preferences: Arc::new(Mutex::new(NotificationPreferences::default())),
             optical_disks: Arc::new(RwLock::new(Vec::<Arc<RwLock<OpticalDiskInfo>>>::new())),
+            post_process_options: Arc::new(Mutex::new(PostProcessOptions::default())),
+            push_options: Arc::new(Mutex::new(PushOptions::default())),
+            network_options: Arc::new(Mutex::new(NetworkOptions::default())),
             query: Arc::new(Mutex::new(String::new())),
+            remote_api_options: Arc::new(Mutex::new(RemoteApiOptions::default())),
+            review_options: Arc::new(Mutex::new(ReviewOptions::default())),
+            rip_plan: Arc::new(Mutex::new(RipPlan::default())),
+            ripper_options: Arc::new(Mutex::new(RipperOptions::default())),
             selected_optical_disk_id: Arc::new(RwLock::new(None)),
             the_movie_db_key: Arc::new(Mutex::new(String::new())),
+            trakt_options: Arc::new(Mutex::new(TraktOptions::default())),
+            tray_options: Arc::new(Mutex::new(TrayOptions::default())),
             tv_shows_dir: Arc::new(RwLock::new(Self::default_tv_shows_dir())),
         }
     }
 
     /// Load state from the persistent store file
     pub fn load_from_store(&self, app_handle: &tauri::AppHandle) -> Result<(), String> {
-        let store = app_handle
+        let mut store = app_handle
             .store(Self::STORE_NAME)
             .map_err(|e| format!("Failed to load store: {e}"))?;
 
+        if store_migrations::migrate(&mut store) > 0 {
+            store
+                .save()
+                .map_err(|e| format!("Failed to save migrated store: {e}"))?;
+        }
+
         for key in store.keys() {
             if let Some(value) = store.get(&key) {
                 if let Some(value_str) = value.as_str() {
@@ -197,6 +298,21 @@ impl AppState {
                             let mut ftp_config = self.lock_ftp_config();
                             ftp_config.tv_upload_path = cleaned.map(PathBuf::from);
                         }
+                        "ftp_movie_path_template" => {
+                            let mut ftp_config = self.lock_ftp_config();
+                            ftp_config.movie_path_template = cleaned;
+                        }
+                        "ftp_tv_path_template" => {
+                            let mut ftp_config = self.lock_ftp_config();
+                            ftp_config.tv_path_template = cleaned;
+                        }
+                        "ftp_file_mode" => {
+                            self.lock_ftp_config().file_mode = cleaned;
+                        }
+                        "ftp_season_posters" => {
+                            self.lock_ftp_config().season_posters =
+                                cleaned.as_deref() != Some("false");
+                        }
                         "the_movie_db_key" => {
                             if let Some(val) = cleaned {
                                 let mut the_movie_db_key = self.lock_the_movie_db_key();
@@ -207,8 +323,7 @@ impl AppState {
                             if let Some(val) = cleaned {
                                 let path = PathBuf::from(&val);
                                 if path.exists() {
-                                    let mut movies_dir =
-                                        self.movies_dir.write().expect("failed to lock movies_dir");
+                                    let mut movies_dir = self.movies_dir.write_or_recover();
                                     *movies_dir = path;
                                 } else {
                                     debug!("Skipping movies_dir load: path does not exist: {val}");
@@ -219,10 +334,7 @@ impl AppState {
                             if let Some(val) = cleaned {
                                 let path = PathBuf::from(&val);
                                 if path.exists() {
-                                    let mut tv_shows_dir = self
-                                        .tv_shows_dir
-                                        .write()
-                                        .expect("failed to lock tv_shows_dir");
+                                    let mut tv_shows_dir = self.tv_shows_dir.write_or_recover();
                                     *tv_shows_dir = path;
                                 } else {
                                     debug!(
@@ -232,9 +344,278 @@ impl AppState {
                             }
                         }
                         "latest_version" => {
-                            let mut lv = self.latest_version.lock().unwrap();
+                            let mut lv = self.latest_version.lock_or_recover();
                             *lv = cleaned;
                         }
+                        "naming_move_leading_article" => {
+                            let mut naming_options = self.lock_naming_options();
+                            naming_options.move_leading_article =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "naming_ascii_only" => {
+                            let mut naming_options = self.lock_naming_options();
+                            naming_options.ascii_only = cleaned.as_deref() == Some("true");
+                        }
+                        "naming_append_quality_tag" => {
+                            let mut naming_options = self.lock_naming_options();
+                            naming_options.append_quality_tag = cleaned.as_deref() == Some("true");
+                        }
+                        "naming_tmdb_id_tag" => {
+                            let mut naming_options = self.lock_naming_options();
+                            naming_options.tmdb_id_tag = cleaned.as_deref() == Some("true");
+                        }
+                        "post_process_enabled" => {
+                            let mut post_process_options = self.lock_post_process_options();
+                            post_process_options.enabled = cleaned.as_deref() == Some("true");
+                        }
+                        "post_process_deinterlace" => {
+                            let mut post_process_options = self.lock_post_process_options();
+                            post_process_options.deinterlace = cleaned.as_deref() == Some("true");
+                        }
+                        "post_process_downmix_stereo" => {
+                            let mut post_process_options = self.lock_post_process_options();
+                            post_process_options.downmix_stereo =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "post_process_normalize_loudness" => {
+                            let mut post_process_options = self.lock_post_process_options();
+                            post_process_options.normalize_loudness =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "post_process_name_chapters" => {
+                            let mut post_process_options = self.lock_post_process_options();
+                            post_process_options.name_chapters = cleaned.as_deref() == Some("true");
+                        }
+                        "chapter_db_api_key" => {
+                            let mut post_process_options = self.lock_post_process_options();
+                            post_process_options.chapter_db_api_key = cleaned;
+                        }
+                        "post_process_preview_frames" => {
+                            let mut post_process_options = self.lock_post_process_options();
+                            post_process_options.preview_frames =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "max_concurrent_rips" => {
+                            if let Some(limit) = parse_limit(&cleaned) {
+                                self.lock_concurrency_limits().rips = limit;
+                            }
+                        }
+                        "max_concurrent_post_process" => {
+                            if let Some(limit) = parse_limit(&cleaned) {
+                                self.lock_concurrency_limits().post_process = limit;
+                            }
+                        }
+                        "max_concurrent_uploads" => {
+                            if let Some(limit) = parse_limit(&cleaned) {
+                                self.lock_concurrency_limits().uploads = limit;
+                            }
+                        }
+                        "notify_rip_start" => {
+                            self.lock_notification_preferences().rips.on_start =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "notify_rip_finish" => {
+                            self.lock_notification_preferences().rips.on_finish =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "notify_rip_error" => {
+                            self.lock_notification_preferences().rips.on_error =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "notify_upload_start" => {
+                            self.lock_notification_preferences().uploads.on_start =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "notify_upload_finish" => {
+                            self.lock_notification_preferences().uploads.on_finish =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "notify_upload_error" => {
+                            self.lock_notification_preferences().uploads.on_error =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "do_not_disturb" => {
+                            *self.lock_do_not_disturb() =
+                                DoNotDisturb::from_store_value(cleaned.as_deref());
+                        }
+                        "push_service" => {
+                            self.lock_push_options().service =
+                                PushService::from_store_value(cleaned.as_deref());
+                        }
+                        "push_server" => self.lock_push_options().server = cleaned,
+                        "push_topic" => self.lock_push_options().topic = cleaned,
+                        "push_token" => self.lock_push_options().token = cleaned,
+                        "push_progress_interval" => {
+                            self.lock_push_options().progress_interval_minutes = cleaned
+                                .and_then(|minutes| minutes.parse().ok())
+                                .unwrap_or_default();
+                        }
+                        "locale" => {
+                            locale::update(|locale| {
+                                *locale = Locale::new(
+                                    cleaned.as_deref().unwrap_or_default(),
+                                    locale.binary_sizes,
+                                )
+                            });
+                        }
+                        "binary_sizes" => {
+                            locale::update(|locale| {
+                                locale.binary_sizes = cleaned.as_deref() == Some("true")
+                            });
+                        }
+                        "radarr_url" => self.lock_arr_options().radarr.url = cleaned,
+                        "radarr_api_key" => self.lock_arr_options().radarr.api_key = cleaned,
+                        "sonarr_url" => self.lock_arr_options().sonarr.url = cleaned,
+                        "sonarr_api_key" => self.lock_arr_options().sonarr.api_key = cleaned,
+                        "trakt_client_id" => self.lock_trakt_options().client_id = cleaned,
+                        "trakt_client_secret" => {
+                            self.lock_trakt_options().client_secret = cleaned;
+                        }
+                        "trakt_access_token" => self.lock_trakt_options().access_token = cleaned,
+                        "trakt_refresh_token" => {
+                            self.lock_trakt_options().refresh_token = cleaned;
+                        }
+                        "trakt_expires_at" => {
+                            self.lock_trakt_options().expires_at =
+                                cleaned.and_then(|seconds| seconds.parse().ok());
+                        }
+                        "trakt_sync_collection" => {
+                            self.lock_trakt_options().sync_collection =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "trakt_highlight_watchlist" => {
+                            self.lock_trakt_options().highlight_watchlist =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "library_profiles" => {
+                            *self.lock_library_profiles() = cleaned
+                                .map(|value| library::from_store_value(&value))
+                                .unwrap_or_default();
+                        }
+                        "rip_plan" => {
+                            *self.lock_rip_plan() = cleaned
+                                .map(|value| rip_plan::from_store_value(&value))
+                                .unwrap_or_default();
+                        }
+                        "assignment_patterns" => {
+                            *self.lock_assignment_patterns() = cleaned
+                                .map(|value| assignment_patterns::from_store_value(&value))
+                                .unwrap_or_default();
+                        }
+                        "disc_memories" => {
+                            *self.lock_disc_memories() = cleaned
+                                .map(|value| disc_memory::from_store_value(&value))
+                                .unwrap_or_default();
+                        }
+                        "offline_mode" => {
+                            self.lock_network_options().offline =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "tmdb_timeout_seconds" => {
+                            if let Some(seconds) = parse_seconds(&cleaned) {
+                                self.lock_network_options().tmdb_timeout_seconds = seconds;
+                            }
+                        }
+                        "github_timeout_seconds" => {
+                            if let Some(seconds) = parse_seconds(&cleaned) {
+                                self.lock_network_options().github_timeout_seconds = seconds;
+                            }
+                        }
+                        "ftp_timeout_seconds" => {
+                            if let Some(seconds) = parse_seconds(&cleaned) {
+                                self.lock_network_options().ftp_timeout_seconds = seconds;
+                            }
+                        }
+                        "review_before_upload" => {
+                            self.lock_review_options().enabled = cleaned.as_deref() == Some("true");
+                        }
+                        "review_auto_approve_hours" => {
+                            self.lock_review_options().auto_approve_hours = cleaned
+                                .and_then(|hours| hours.parse().ok())
+                                .filter(|hours| *hours > 0);
+                        }
+                        "remote_api_enabled" => {
+                            self.lock_remote_api_options().enabled =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "remote_api_bind_address" => {
+                            if let Some(bind_address) = cleaned {
+                                self.lock_remote_api_options().bind_address = bind_address;
+                            }
+                        }
+                        "remote_api_port" => {
+                            if let Some(port) = cleaned.and_then(|port| port.parse().ok()) {
+                                self.lock_remote_api_options().port = port;
+                            }
+                        }
+                        "remote_api_token" => self.lock_remote_api_options().token = cleaned,
+                        "remote_api_metrics" => {
+                            self.lock_remote_api_options().metrics =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "ripper_backend" => {
+                            self.lock_ripper_options().kind =
+                                RipperKind::from_store_value(cleaned.as_deref());
+                        }
+                        "ripper_ssh_host" => {
+                            self.lock_ripper_options().ssh_host = cleaned;
+                        }
+                        "ripper_ssh_port" => {
+                            self.lock_ripper_options().ssh_port =
+                                cleaned.and_then(|port| port.parse().ok());
+                        }
+                        "ripper_remote_makemkvcon" => {
+                            self.lock_ripper_options().remote_makemkvcon = cleaned;
+                        }
+                        "ripper_local_share_dir" => {
+                            self.lock_ripper_options().local_share_dir = cleaned.map(PathBuf::from);
+                        }
+                        "ripper_remote_share_dir" => {
+                            self.lock_ripper_options().remote_share_dir = cleaned;
+                        }
+                        "ripper_local_makemkvcon" => {
+                            self.lock_ripper_options().local_makemkvcon =
+                                cleaned.map(PathBuf::from);
+                        }
+                        "ripper_local_env" => {
+                            self.lock_ripper_options().local_env = cleaned
+                                .and_then(|text| ripper_backend::parse_env(&text).ok())
+                                .unwrap_or_default();
+                        }
+                        "ripper_episode_order" => {
+                            self.lock_ripper_options().episode_order =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "lock_tray_during_rip" => {
+                            self.lock_tray_options().lock_during_rip =
+                                cleaned.as_deref() != Some("false");
+                        }
+                        "close_tray_after_eject_seconds" => {
+                            self.lock_tray_options().close_after_eject_seconds =
+                                cleaned.and_then(|seconds| seconds.parse().ok());
+                        }
+                        "archive_dir" => {
+                            if let Some(dir) = cleaned {
+                                self.lock_archive_options().archive_dir = PathBuf::from(dir);
+                            }
+                        }
+                        "archive_zip" => {
+                            self.lock_archive_options().zip = cleaned.as_deref() == Some("true");
+                        }
+                        "archive_zip_store_only" => {
+                            self.lock_archive_options().zip_store_only =
+                                cleaned.as_deref() == Some("true");
+                        }
+                        "archive_zip_volume_gb" => {
+                            self.lock_archive_options().zip_volume_gb =
+                                cleaned.and_then(|gb| gb.parse().ok());
+                        }
+                        "archive_keep_copies" => {
+                            // Keeping no copy would delete the archive just made
+                            self.lock_archive_options().keep_copies = cleaned
+                                .and_then(|copies| copies.parse().ok())
+                                .filter(|copies| *copies > 0);
+                        }
                         _ => debug!("Unknown key in store: {key}"),
                     }
                     debug!("Loaded key from store: {key}");
@@ -285,6 +666,25 @@ impl AppState {
         } else {
             store.delete("ftp_tv_upload_path");
         }
+        if let Some(ref template) = ftp_config.movie_path_template {
+            store.set("ftp_movie_path_template", serde_json::json!(template));
+        } else {
+            store.delete("ftp_movie_path_template");
+        }
+        if let Some(ref template) = ftp_config.tv_path_template {
+            store.set("ftp_tv_path_template", serde_json::json!(template));
+        } else {
+            store.delete("ftp_tv_path_template");
+        }
+        if let Some(ref file_mode) = ftp_config.file_mode {
+            store.set("ftp_file_mode", serde_json::json!(file_mode));
+        } else {
+            store.delete("ftp_file_mode");
+        }
+        store.set(
+            "ftp_season_posters",
+            serde_json::json!(ftp_config.season_posters.to_string()),
+        );
 
         // Save The Movie DB key
         let tmdb_key = self.lock_the_movie_db_key();
@@ -293,26 +693,355 @@ impl AppState {
         }
 
         // Save directory paths
-        let movies_dir = self
-            .movies_dir
-            .read()
-            .expect("failed to lock movies_dir for read");
+        let movies_dir = self.movies_dir.read_or_recover();
         if let Some(path_str) = movies_dir.to_str() {
             store.set("movies_dir", serde_json::json!(path_str));
         }
-        let tv_shows_dir = self
-            .tv_shows_dir
-            .read()
-            .expect("failed to lock tv_shows_dir for read");
+        let tv_shows_dir = self.tv_shows_dir.read_or_recover();
         if let Some(path_str) = tv_shows_dir.to_str() {
             store.set("tv_shows_dir", serde_json::json!(path_str));
         }
 
+        // Save naming options
+        let naming_options = self.naming_options();
+        store.set(
+            "naming_move_leading_article",
+            serde_json::json!(naming_options.move_leading_article.to_string()),
+        );
+        store.set(
+            "naming_ascii_only",
+            serde_json::json!(naming_options.ascii_only.to_string()),
+        );
+        store.set(
+            "naming_append_quality_tag",
+            serde_json::json!(naming_options.append_quality_tag.to_string()),
+        );
+        store.set(
+            "naming_tmdb_id_tag",
+            serde_json::json!(naming_options.tmdb_id_tag.to_string()),
+        );
+
+        // Save post-process options
+        let post_process_options = self.post_process_options();
+        store.set(
+            "post_process_enabled",
+            serde_json::json!(post_process_options.enabled.to_string()),
+        );
+        store.set(
+            "post_process_deinterlace",
+            serde_json::json!(post_process_options.deinterlace.to_string()),
+        );
+        store.set(
+            "post_process_downmix_stereo",
+            serde_json::json!(post_process_options.downmix_stereo.to_string()),
+        );
+        store.set(
+            "post_process_normalize_loudness",
+            serde_json::json!(post_process_options.normalize_loudness.to_string()),
+        );
+        store.set(
+            "post_process_name_chapters",
+            serde_json::json!(post_process_options.name_chapters.to_string()),
+        );
+        store.set(
+            "post_process_preview_frames",
+            serde_json::json!(post_process_options.preview_frames.to_string()),
+        );
+        if let Some(ref api_key) = post_process_options.chapter_db_api_key {
+            store.set("chapter_db_api_key", serde_json::json!(api_key));
+        } else {
+            store.delete("chapter_db_api_key");
+        }
+
+        // Save concurrency limits
+        let concurrency_limits = self.concurrency_limits();
+        store.set(
+            "max_concurrent_rips",
+            serde_json::json!(concurrency_limits.rips.to_string()),
+        );
+        store.set(
+            "max_concurrent_post_process",
+            serde_json::json!(concurrency_limits.post_process.to_string()),
+        );
+        store.set(
+            "max_concurrent_uploads",
+            serde_json::json!(concurrency_limits.uploads.to_string()),
+        );
+
+        // Save notification preferences
+        let notification_preferences = self.notification_preferences();
+        store.set(
+            "notify_rip_start",
+            serde_json::json!(notification_preferences.rips.on_start.to_string()),
+        );
+        store.set(
+            "notify_rip_finish",
+            serde_json::json!(notification_preferences.rips.on_finish.to_string()),
+        );
+        store.set(
+            "notify_rip_error",
+            serde_json::json!(notification_preferences.rips.on_error.to_string()),
+        );
+        store.set(
+            "notify_upload_start",
+            serde_json::json!(notification_preferences.uploads.on_start.to_string()),
+        );
+        store.set(
+            "notify_upload_finish",
+            serde_json::json!(notification_preferences.uploads.on_finish.to_string()),
+        );
+        store.set(
+            "notify_upload_error",
+            serde_json::json!(notification_preferences.uploads.on_error.to_string()),
+        );
+
+        store.set(
+            "do_not_disturb",
+            serde_json::json!(self.do_not_disturb().to_store_value()),
+        );
+
+        // Save push options
+        let push_options = self.push_options();
+        store.set(
+            "push_service",
+            serde_json::json!(push_options.service.to_store_value()),
+        );
+        for (key, value) in [
+            ("push_server", &push_options.server),
+            ("push_topic", &push_options.topic),
+            ("push_token", &push_options.token),
+        ] {
+            if let Some(value) = value {
+                store.set(key, serde_json::json!(value));
+            } else {
+                store.delete(key);
+            }
+        }
+        store.set(
+            "push_progress_interval",
+            serde_json::json!(push_options.progress_interval_minutes.to_string()),
+        );
+
+        // Save the locale
+        let current_locale = locale::current();
+        store.set("locale", serde_json::json!(current_locale.tag));
+        store.set(
+            "binary_sizes",
+            serde_json::json!(current_locale.binary_sizes.to_string()),
+        );
+
+        // Save Radarr and Sonarr options
+        let arr_options = self.arr_options();
+        for (key, value) in [
+            ("radarr_url", &arr_options.radarr.url),
+            ("radarr_api_key", &arr_options.radarr.api_key),
+            ("sonarr_url", &arr_options.sonarr.url),
+            ("sonarr_api_key", &arr_options.sonarr.api_key),
+        ] {
+            if let Some(value) = value {
+                store.set(key, serde_json::json!(value));
+            } else {
+                store.delete(key);
+            }
+        }
+
+        // Save Trakt options
+        let trakt_options = self.trakt_options();
+        for (key, value) in [
+            ("trakt_client_id", trakt_options.client_id),
+            ("trakt_client_secret", trakt_options.client_secret),
+            ("trakt_access_token", trakt_options.access_token),
+            ("trakt_refresh_token", trakt_options.refresh_token),
+            (
+                "trakt_expires_at",
+                trakt_options.expires_at.map(|seconds| seconds.to_string()),
+            ),
+        ] {
+            if let Some(value) = value {
+                store.set(key, serde_json::json!(value));
+            } else {
+                store.delete(key);
+            }
+        }
+        store.set(
+            "trakt_sync_collection",
+            serde_json::json!(trakt_options.sync_collection.to_string()),
+        );
+        store.set(
+            "trakt_highlight_watchlist",
+            serde_json::json!(trakt_options.highlight_watchlist.to_string()),
+        );
+
+        // Save library profiles
+        store.set(
+            "library_profiles",
+            serde_json::json!(library::to_store_value(&self.library_profiles())),
+        );
+
+        // Save rip plan
+        store.set(
+            "rip_plan",
+            serde_json::json!(rip_plan::to_store_value(&self.rip_plan())),
+        );
+
+        // Save assignment patterns
+        store.set(
+            "assignment_patterns",
+            serde_json::json!(assignment_patterns::to_store_value(
+                &self.assignment_patterns()
+            )),
+        );
+        store.set(
+            "disc_memories",
+            serde_json::json!(disc_memory::to_store_value(&self.disc_memories())),
+        );
+
+        // Save network options
+        let network_options = self.network_options();
+        store.set(
+            "offline_mode",
+            serde_json::json!(network_options.offline.to_string()),
+        );
+        store.set(
+            "tmdb_timeout_seconds",
+            serde_json::json!(network_options.tmdb_timeout_seconds.to_string()),
+        );
+        store.set(
+            "github_timeout_seconds",
+            serde_json::json!(network_options.github_timeout_seconds.to_string()),
+        );
+        store.set(
+            "ftp_timeout_seconds",
+            serde_json::json!(network_options.ftp_timeout_seconds.to_string()),
+        );
+
+        // Save upload review options
+        let review_options = self.review_options();
+        store.set(
+            "review_before_upload",
+            serde_json::json!(review_options.enabled.to_string()),
+        );
+        match review_options.auto_approve_hours {
+            Some(hours) => store.set(
+                "review_auto_approve_hours",
+                serde_json::json!(hours.to_string()),
+            ),
+            None => {
+                store.delete("review_auto_approve_hours");
+            }
+        }
+
+        // Save remote API options
+        let remote_api_options = self.remote_api_options();
+        store.set(
+            "remote_api_enabled",
+            serde_json::json!(remote_api_options.enabled.to_string()),
+        );
+        store.set(
+            "remote_api_bind_address",
+            serde_json::json!(remote_api_options.bind_address),
+        );
+        store.set(
+            "remote_api_port",
+            serde_json::json!(remote_api_options.port.to_string()),
+        );
+        store.set(
+            "remote_api_metrics",
+            serde_json::json!(remote_api_options.metrics.to_string()),
+        );
+        if let Some(token) = &remote_api_options.token {
+            store.set("remote_api_token", serde_json::json!(token));
+        } else {
+            store.delete("remote_api_token");
+        }
+
+        // Save ripper backend
+        let ripper_options = self.ripper_options();
+        store.set(
+            "ripper_backend",
+            serde_json::json!(ripper_options.kind.to_store_value()),
+        );
+        let ripper_values = [
+            ("ripper_ssh_host", ripper_options.ssh_host),
+            (
+                "ripper_ssh_port",
+                ripper_options.ssh_port.map(|port| port.to_string()),
+            ),
+            ("ripper_remote_makemkvcon", ripper_options.remote_makemkvcon),
+            (
+                "ripper_local_share_dir",
+                ripper_options
+                    .local_share_dir
+                    .map(|dir| dir.to_string_lossy().to_string()),
+            ),
+            ("ripper_remote_share_dir", ripper_options.remote_share_dir),
+            (
+                "ripper_local_makemkvcon",
+                ripper_options
+                    .local_makemkvcon
+                    .map(|path| path.to_string_lossy().to_string()),
+            ),
+            (
+                "ripper_local_env",
+                Some(ripper_backend::env_text(&ripper_options.local_env))
+                    .filter(|text| !text.is_empty()),
+            ),
+            (
+                "ripper_episode_order",
+                ripper_options.episode_order.then(|| "true".to_string()),
+            ),
+        ];
+        for (key, value) in ripper_values {
+            match value {
+                Some(value) => store.set(key, serde_json::json!(value)),
+                None => {
+                    store.delete(key);
+                }
+            }
+        }
+
+        // Save tray options
+        let tray_options = self.tray_options();
+        store.set(
+            "lock_tray_during_rip",
+            serde_json::json!(tray_options.lock_during_rip.to_string()),
+        );
+        if let Some(seconds) = tray_options.close_after_eject_seconds {
+            store.set(
+                "close_tray_after_eject_seconds",
+                serde_json::json!(seconds.to_string()),
+            );
+        } else {
+            store.delete("close_tray_after_eject_seconds");
+        }
+
+        // Save archive options
+        let archive_options = self.archive_options();
+        store.set(
+            "archive_dir",
+            serde_json::json!(archive_options.archive_dir.to_string_lossy()),
+        );
+        store.set(
+            "archive_zip",
+            serde_json::json!(archive_options.zip.to_string()),
+        );
+        store.set(
+            "archive_zip_store_only",
+            serde_json::json!(archive_options.zip_store_only.to_string()),
+        );
+        if let Some(gb) = archive_options.zip_volume_gb {
+            store.set("archive_zip_volume_gb", serde_json::json!(gb.to_string()));
+        } else {
+            store.delete("archive_zip_volume_gb");
+        }
+        if let Some(copies) = archive_options.keep_copies {
+            store.set("archive_keep_copies", serde_json::json!(copies.to_string()));
+        } else {
+            store.delete("archive_keep_copies");
+        }
+
         // Save version info
-        let latest_version_guard = self
-            .latest_version
-            .lock()
-            .expect("failed to lock latest_version");
+        let latest_version_guard = self.latest_version.lock_or_recover();
         if let Some(version) = latest_version_guard.as_ref() {
             store.set("latest_version", serde_json::json!(version));
         }
@@ -325,15 +1054,12 @@ impl AppState {
     }
 
     pub fn save_current_video(&self, video: Option<title_video::Video>) {
-        let mut guard = self
-            .current_video
-            .lock()
-            .expect("failed to lock current_video");
+        let mut guard = self.current_video.lock_or_recover();
         *guard = video;
     }
 
     pub fn save_query(&self, search: &str) {
-        let mut query = self.query.lock().unwrap();
+        let mut query = self.query.lock_or_recover();
         *query = search.to_string();
     }
 
@@ -350,13 +1076,206 @@ impl AppState {
     }
 
     pub fn lock_the_movie_db_key(&self) -> MutexGuard<'_, String> {
-        self.the_movie_db_key
-            .lock()
-            .expect("failed to lock the_movie_db_key")
+        self.the_movie_db_key.lock_or_recover()
+    }
+
+    pub fn lock_naming_options(&self) -> MutexGuard<'_, NamingOptions> {
+        self.naming_options.lock_or_recover()
+    }
+
+    /// Copy of the naming options so callers don't hold the lock while building paths
+    pub fn naming_options(&self) -> NamingOptions {
+        self.lock_naming_options().clone()
+    }
+
+    pub fn lock_post_process_options(&self) -> MutexGuard<'_, PostProcessOptions> {
+        self.post_process_options.lock_or_recover()
+    }
+
+    /// Copy of the post-process options so the lock isn't held while ffmpeg runs
+    pub fn post_process_options(&self) -> PostProcessOptions {
+        self.lock_post_process_options().clone()
+    }
+
+    pub fn lock_concurrency_limits(&self) -> MutexGuard<'_, ConcurrencyLimits> {
+        self.concurrency_limits.lock_or_recover()
+    }
+
+    pub fn concurrency_limits(&self) -> ConcurrencyLimits {
+        self.lock_concurrency_limits().clone()
+    }
+
+    pub fn lock_notification_preferences(&self) -> MutexGuard<'_, NotificationPreferences> {
+        self.notification_preferences.lock_or_recover()
+    }
+
+    pub fn notification_preferences(&self) -> NotificationPreferences {
+        self.lock_notification_preferences().clone()
+    }
+
+    pub fn lock_do_not_disturb(&self) -> MutexGuard<'_, DoNotDisturb> {
+        self.do_not_disturb.lock_or_recover()
+    }
+
+    pub fn do_not_disturb(&self) -> DoNotDisturb {
+        self.lock_do_not_disturb().clone()
+    }
+
+    pub fn lock_network_options(&self) -> MutexGuard<'_, NetworkOptions> {
+        self.network_options.lock_or_recover()
+    }
+
+    pub fn network_options(&self) -> NetworkOptions {
+        self.lock_network_options().clone()
+    }
+
+    pub fn lock_review_options(&self) -> MutexGuard<'_, ReviewOptions> {
+        self.review_options.lock_or_recover()
+    }
+
+    pub fn review_options(&self) -> ReviewOptions {
+        self.lock_review_options().clone()
+    }
+
+    pub fn lock_remote_api_options(&self) -> MutexGuard<'_, RemoteApiOptions> {
+        self.remote_api_options.lock_or_recover()
+    }
+
+    pub fn remote_api_options(&self) -> RemoteApiOptions {
+        self.lock_remote_api_options().clone()
+    }
+
+    pub fn lock_ripper_options(&self) -> MutexGuard<'_, RipperOptions> {
+        self.ripper_options.lock_or_recover()
+    }
+
+    pub fn ripper_options(&self) -> RipperOptions {
+        self.lock_ripper_options().clone()
+    }
+
+    pub fn lock_tray_options(&self) -> MutexGuard<'_, TrayOptions> {
+        self.tray_options.lock_or_recover()
+    }
+
+    pub fn tray_options(&self) -> TrayOptions {
+        self.lock_tray_options().clone()
+    }
+
+    pub fn lock_archive_options(&self) -> MutexGuard<'_, ArchiveOptions> {
+        self.archive_options.lock_or_recover()
+    }
+
+    pub fn archive_options(&self) -> ArchiveOptions {
+        self.lock_archive_options().clone()
+    }
+
+    pub fn lock_arr_options(&self) -> MutexGuard<'_, ArrOptions> {
+        self.arr_options.lock_or_recover()
+    }
+
+    pub fn arr_options(&self) -> ArrOptions {
+        self.lock_arr_options().clone()
+    }
+
+    pub fn lock_push_options(&self) -> MutexGuard<'_, PushOptions> {
+        self.push_options.lock_or_recover()
+    }
+
+    pub fn push_options(&self) -> PushOptions {
+        self.lock_push_options().clone()
+    }
+
+    pub fn lock_trakt_options(&self) -> MutexGuard<'_, TraktOptions> {
+        self.trakt_options.lock_or_recover()
+    }
+
+    pub fn trakt_options(&self) -> TraktOptions {
+        self.lock_trakt_options().clone()
+    }
+
+    pub fn lock_library_profiles(&self) -> MutexGuard<'_, Vec<LibraryProfile>> {
+        self.library_profiles.lock_or_recover()
+    }
+
+    pub fn library_profiles(&self) -> Vec<LibraryProfile> {
+        self.lock_library_profiles().clone()
+    }
+
+    pub fn lock_rip_plan(&self) -> MutexGuard<'_, RipPlan> {
+        self.rip_plan.lock_or_recover()
+    }
+
+    pub fn rip_plan(&self) -> RipPlan {
+        self.lock_rip_plan().clone()
+    }
+
+    pub fn lock_assignment_patterns(&self) -> MutexGuard<'_, Vec<AssignmentPattern>> {
+        self.assignment_patterns.lock_or_recover()
+    }
+
+    pub fn assignment_patterns(&self) -> Vec<AssignmentPattern> {
+        self.lock_assignment_patterns().clone()
+    }
+
+    pub fn lock_disc_memories(&self) -> MutexGuard<'_, Vec<DiscMemory>> {
+        self.disc_memories.lock_or_recover()
+    }
+
+    pub fn disc_memories(&self) -> Vec<DiscMemory> {
+        self.lock_disc_memories().clone()
+    }
+
+    /// Names of the library profiles, offered next to the rip buttons
+    pub fn library_profile_names(&self) -> Vec<String> {
+        self.lock_library_profiles()
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect()
+    }
+
+    /// Where videos of the named profile go, `None` or a profile that was removed is the main library.
+    pub fn library(&self, profile_name: Option<&str>) -> Library {
+        let library = Library {
+            movies_dir: self.movies_dir.read_or_recover().clone(),
+            tv_shows_dir: self.tv_shows_dir.read_or_recover().clone(),
+            ftp_config: self.lock_ftp_config().clone(),
+            naming: self.naming_options(),
+            audio_languages: Vec::new(),
+            subtitle_languages: Vec::new(),
+            rip_cache_mb: None,
+            downmix_stereo: None,
+            normalize_loudness: None,
+        };
+        let Some(profile_name) = profile_name else {
+            return library;
+        };
+        match self
+            .lock_library_profiles()
+            .iter()
+            .find(|profile| profile.name == profile_name)
+        {
+            Some(profile) => profile.apply(library),
+            None => {
+                debug!("Library profile {profile_name} no longer exists, using the main library");
+                library
+            }
+        }
+    }
+
+    /// Name of the profile whose local folders contain `path`, `None` for the main library.
+    pub fn library_name_for_path(&self, path: &Path) -> Option<String> {
+        self.lock_library_profiles()
+            .iter()
+            .find(|profile| profile.contains(path))
+            .map(|profile| profile.name.clone())
+    }
+
+    pub fn lock_held_messages(&self) -> MutexGuard<'_, Vec<HeldMessage>> {
+        self.held_messages.lock_or_recover()
     }
 
     pub fn lock_ftp_config(&self) -> MutexGuard<'_, FtpConfig> {
-        self.ftp_config.lock().expect("failed to lock ftp_config")
+        self.ftp_config.lock_or_recover()
     }
 
     pub fn lock_ftp_host(&self) -> FtpHostGuard<'_> {
@@ -379,31 +1298,25 @@ impl AppState {
         FtpTvUploadPathGuard(self.lock_ftp_config())
     }
 
-    pub fn update_ftp_settings(
-        &self,
-        ftp_host: Option<String>,
-        ftp_user: Option<String>,
-        ftp_pass: Option<String>,
-        ftp_movie_upload_path: Option<String>,
-        ftp_tv_upload_path: Option<String>,
-    ) {
-        let clean = |value: Option<String>| {
-            value.and_then(|s| {
-                let trimmed = s.trim();
-                if trimmed.is_empty() {
-                    None
-                } else {
-                    Some(trimmed.to_string())
-                }
-            })
+    pub fn update_ftp_settings(&self, update: FtpSettingsUpdate) {
+        let clean = |value: String| {
+            let trimmed = value.trim();
+            if trimmed.is_empty() {
+                None
+            } else {
+                Some(trimmed.to_string())
+            }
         };
 
         let mut ftp_config = self.lock_ftp_config();
-        ftp_config.host = clean(ftp_host);
-        ftp_config.user = clean(ftp_user);
-        ftp_config.pass = clean(ftp_pass);
-        ftp_config.movie_upload_path = clean(ftp_movie_upload_path).map(PathBuf::from);
-        ftp_config.tv_upload_path = clean(ftp_tv_upload_path).map(PathBuf::from);
+        ftp_config.host = clean(update.host);
+        ftp_config.user = clean(update.user);
+        ftp_config.pass = clean(update.pass);
+        ftp_config.movie_upload_path = clean(update.movie_upload_path).map(PathBuf::from);
+        ftp_config.tv_upload_path = clean(update.tv_upload_path).map(PathBuf::from);
+        ftp_config.movie_path_template = clean(update.movie_path_template);
+        ftp_config.tv_path_template = clean(update.tv_path_template);
+        ftp_config.file_mode = clean(update.file_mode);
     }
 
     pub fn update(
@@ -442,6 +1355,20 @@ impl AppState {
                 let mut ftp_tv_upload_path = self.lock_ftp_tv_upload_path();
                 *ftp_tv_upload_path = cleaned.map(PathBuf::from);
             }
+            "ftp_movie_path_template" => {
+                let mut ftp_config = self.lock_ftp_config();
+                ftp_config.movie_path_template = cleaned;
+            }
+            "ftp_tv_path_template" => {
+                let mut ftp_config = self.lock_ftp_config();
+                ftp_config.tv_path_template = cleaned;
+            }
+            "ftp_file_mode" => {
+                self.lock_ftp_config().file_mode = cleaned;
+            }
+            "ftp_season_posters" => {
+                self.lock_ftp_config().season_posters = cleaned.as_deref() != Some("false");
+            }
             "the_movie_db_key" => {
                 if let Some(val) = cleaned {
                     let mut the_movie_db_key = self.lock_the_movie_db_key();
@@ -450,34 +1377,247 @@ impl AppState {
             }
             "movies_dir" => {
                 if let Some(val) = cleaned {
-                    let mut movies_dir = self
-                        .movies_dir
-                        .write()
-                        .expect("failed to lock movies_dir for write");
-                    // validate path exists
-                    if !movies_dir.exists() {
-                        return Err(format!("movies_dir path does not exist: {val}"));
-                    }
-                    *movies_dir = PathBuf::from(val);
+                    let path = PathBuf::from(val);
+                    library_dir::prepare(&path)?;
+                    *self.movies_dir.write_or_recover() = path;
                 };
             }
             "tv_shows_dir" => {
                 if let Some(val) = cleaned {
-                    let mut tv_shows_dir = self
-                        .tv_shows_dir
-                        .write()
-                        .expect("failed to lock tv_shows_dir for write");
-                    // validate path exists
-                    if !tv_shows_dir.exists() {
-                        return Err(format!("tv_shows_dir path does not exist: {val}"));
-                    }
-                    *tv_shows_dir = PathBuf::from(val);
+                    let path = PathBuf::from(val);
+                    library_dir::prepare(&path)?;
+                    *self.tv_shows_dir.write_or_recover() = path;
                 };
             }
             "latest_version" => {
-                let mut lv = self.latest_version.lock().unwrap();
+                let mut lv = self.latest_version.lock_or_recover();
                 *lv = cleaned;
             }
+            "naming_move_leading_article" => {
+                let mut naming_options = self.lock_naming_options();
+                naming_options.move_leading_article = cleaned.as_deref() == Some("true");
+            }
+            "naming_ascii_only" => {
+                let mut naming_options = self.lock_naming_options();
+                naming_options.ascii_only = cleaned.as_deref() == Some("true");
+            }
+            "naming_append_quality_tag" => {
+                let mut naming_options = self.lock_naming_options();
+                naming_options.append_quality_tag = cleaned.as_deref() == Some("true");
+            }
+            "naming_tmdb_id_tag" => {
+                let mut naming_options = self.lock_naming_options();
+                naming_options.tmdb_id_tag = cleaned.as_deref() == Some("true");
+            }
+            "post_process_enabled" => {
+                let mut post_process_options = self.lock_post_process_options();
+                post_process_options.enabled = cleaned.as_deref() == Some("true");
+            }
+            "post_process_deinterlace" => {
+                let mut post_process_options = self.lock_post_process_options();
+                post_process_options.deinterlace = cleaned.as_deref() == Some("true");
+            }
+            "post_process_downmix_stereo" => {
+                let mut post_process_options = self.lock_post_process_options();
+                post_process_options.downmix_stereo = cleaned.as_deref() == Some("true");
+            }
+            "post_process_normalize_loudness" => {
+                let mut post_process_options = self.lock_post_process_options();
+                post_process_options.normalize_loudness = cleaned.as_deref() == Some("true");
+            }
+            "post_process_name_chapters" => {
+                let mut post_process_options = self.lock_post_process_options();
+                post_process_options.name_chapters = cleaned.as_deref() == Some("true");
+            }
+            "chapter_db_api_key" => {
+                let mut post_process_options = self.lock_post_process_options();
+                post_process_options.chapter_db_api_key = cleaned;
+            }
+            "post_process_preview_frames" => {
+                let mut post_process_options = self.lock_post_process_options();
+                post_process_options.preview_frames = cleaned.as_deref() == Some("true");
+            }
+            "max_concurrent_rips" => {
+                let limit =
+                    parse_limit(&cleaned).ok_or_else(|| format!("invalid limit for {key}"))?;
+                self.lock_concurrency_limits().rips = limit;
+            }
+            "max_concurrent_post_process" => {
+                let limit =
+                    parse_limit(&cleaned).ok_or_else(|| format!("invalid limit for {key}"))?;
+                self.lock_concurrency_limits().post_process = limit;
+            }
+            "max_concurrent_uploads" => {
+                let limit =
+                    parse_limit(&cleaned).ok_or_else(|| format!("invalid limit for {key}"))?;
+                self.lock_concurrency_limits().uploads = limit;
+            }
+            "notify_rip_start" => {
+                self.lock_notification_preferences().rips.on_start =
+                    cleaned.as_deref() == Some("true");
+            }
+            "notify_rip_finish" => {
+                self.lock_notification_preferences().rips.on_finish =
+                    cleaned.as_deref() == Some("true");
+            }
+            "notify_rip_error" => {
+                self.lock_notification_preferences().rips.on_error =
+                    cleaned.as_deref() == Some("true");
+            }
+            "notify_upload_start" => {
+                self.lock_notification_preferences().uploads.on_start =
+                    cleaned.as_deref() == Some("true");
+            }
+            "notify_upload_finish" => {
+                self.lock_notification_preferences().uploads.on_finish =
+                    cleaned.as_deref() == Some("true");
+            }
+            "notify_upload_error" => {
+                self.lock_notification_preferences().uploads.on_error =
+                    cleaned.as_deref() == Some("true");
+            }
+            "do_not_disturb" => {
+                *self.lock_do_not_disturb() = DoNotDisturb::from_store_value(cleaned.as_deref());
+            }
+            "push_service" => {
+                self.lock_push_options().service =
+                    PushService::from_store_value(cleaned.as_deref());
+            }
+            "push_server" => self.lock_push_options().server = cleaned,
+            "push_topic" => self.lock_push_options().topic = cleaned,
+            "push_token" => self.lock_push_options().token = cleaned,
+            "push_progress_interval" => {
+                self.lock_push_options().progress_interval_minutes = cleaned
+                    .and_then(|minutes| minutes.parse().ok())
+                    .unwrap_or_default();
+            }
+            "locale" => locale::update(|locale| {
+                *locale = Locale::new(cleaned.as_deref().unwrap_or_default(), locale.binary_sizes)
+            }),
+            "binary_sizes" => {
+                locale::update(|locale| locale.binary_sizes = cleaned.as_deref() == Some("true"));
+            }
+            "radarr_url" => self.lock_arr_options().radarr.url = cleaned,
+            "radarr_api_key" => self.lock_arr_options().radarr.api_key = cleaned,
+            "sonarr_url" => self.lock_arr_options().sonarr.url = cleaned,
+            "sonarr_api_key" => self.lock_arr_options().sonarr.api_key = cleaned,
+            "trakt_client_id" => self.lock_trakt_options().client_id = cleaned,
+            "trakt_client_secret" => self.lock_trakt_options().client_secret = cleaned,
+            "trakt_sync_collection" => {
+                self.lock_trakt_options().sync_collection = cleaned.as_deref() == Some("true");
+            }
+            "trakt_highlight_watchlist" => {
+                self.lock_trakt_options().highlight_watchlist = cleaned.as_deref() == Some("true");
+            }
+            "library_profiles" => {
+                let profiles = cleaned
+                    .map(|value| library::from_store_value(&value))
+                    .unwrap_or_default();
+                library::validate(&profiles)?;
+                *self.lock_library_profiles() = profiles;
+            }
+            "offline_mode" => {
+                self.lock_network_options().offline = cleaned.as_deref() == Some("true");
+            }
+            "tmdb_timeout_seconds" => {
+                if let Some(seconds) = parse_seconds(&cleaned) {
+                    self.lock_network_options().tmdb_timeout_seconds = seconds;
+                }
+            }
+            "github_timeout_seconds" => {
+                if let Some(seconds) = parse_seconds(&cleaned) {
+                    self.lock_network_options().github_timeout_seconds = seconds;
+                }
+            }
+            "ftp_timeout_seconds" => {
+                if let Some(seconds) = parse_seconds(&cleaned) {
+                    self.lock_network_options().ftp_timeout_seconds = seconds;
+                }
+            }
+            "review_before_upload" => {
+                self.lock_review_options().enabled = cleaned.as_deref() == Some("true");
+            }
+            "review_auto_approve_hours" => {
+                self.lock_review_options().auto_approve_hours = cleaned
+                    .and_then(|hours| hours.parse().ok())
+                    .filter(|hours| *hours > 0);
+            }
+            "remote_api_enabled" => {
+                self.lock_remote_api_options().enabled = cleaned.as_deref() == Some("true");
+            }
+            "remote_api_bind_address" => {
+                self.lock_remote_api_options().bind_address =
+                    cleaned.ok_or_else(|| format!("{key} can't be empty"))?;
+            }
+            "remote_api_port" => {
+                self.lock_remote_api_options().port = cleaned
+                    .and_then(|port| port.parse().ok())
+                    .ok_or_else(|| format!("invalid port for {key}"))?;
+            }
+            "remote_api_token" => self.lock_remote_api_options().token = cleaned,
+            "ripper_backend" => {
+                self.lock_ripper_options().kind = RipperKind::from_store_value(cleaned.as_deref());
+            }
+            "ripper_ssh_host" => {
+                self.lock_ripper_options().ssh_host = cleaned;
+            }
+            "ripper_ssh_port" => {
+                let port = cleaned
+                    .map(|port| {
+                        port.parse::<u16>()
+                            .map_err(|_| format!("{port} is not a valid SSH port"))
+                    })
+                    .transpose()?;
+                self.lock_ripper_options().ssh_port = port;
+            }
+            "ripper_remote_makemkvcon" => {
+                self.lock_ripper_options().remote_makemkvcon = cleaned;
+            }
+            "ripper_local_share_dir" => {
+                self.lock_ripper_options().local_share_dir = cleaned.map(PathBuf::from);
+            }
+            "ripper_remote_share_dir" => {
+                self.lock_ripper_options().remote_share_dir = cleaned;
+            }
+            "ripper_local_makemkvcon" => {
+                self.lock_ripper_options().local_makemkvcon = cleaned.map(PathBuf::from);
+            }
+            "ripper_local_env" => {
+                self.lock_ripper_options().local_env = cleaned
+                    .map(|text| ripper_backend::parse_env(&text))
+                    .transpose()?
+                    .unwrap_or_default();
+            }
+            "ripper_episode_order" => {
+                self.lock_ripper_options().episode_order = cleaned.as_deref() == Some("true");
+            }
+            "lock_tray_during_rip" => {
+                self.lock_tray_options().lock_during_rip = cleaned.as_deref() != Some("false");
+            }
+            "close_tray_after_eject_seconds" => {
+                self.lock_tray_options().close_after_eject_seconds =
+                    cleaned.and_then(|seconds| seconds.parse().ok());
+            }
+            "archive_dir" => {
+                self.lock_archive_options().archive_dir = match cleaned {
+                    Some(dir) => PathBuf::from(dir),
+                    None => ArchiveOptions::default().archive_dir,
+                };
+            }
+            "archive_zip" => {
+                self.lock_archive_options().zip = cleaned.as_deref() == Some("true");
+            }
+            "archive_zip_store_only" => {
+                self.lock_archive_options().zip_store_only = cleaned.as_deref() == Some("true");
+            }
+            "archive_zip_volume_gb" => {
+                self.lock_archive_options().zip_volume_gb = cleaned.and_then(|gb| gb.parse().ok());
+            }
+            "archive_keep_copies" => {
+                self.lock_archive_options().keep_copies = cleaned
+                    .and_then(|copies| copies.parse().ok())
+                    .filter(|copies| *copies > 0);
+            }
             _ => return Err(format!("can't update {key}")),
         }
 
@@ -487,18 +1627,15 @@ impl AppState {
     }
 
     pub fn clone_optical_disks(&self) -> Vec<OpticalDiskInfo> {
-        let guard = self.optical_disks.read().unwrap();
+        let guard = self.optical_disks.read_or_recover();
         guard
             .iter()
-            .map(|disk_arc| disk_arc.read().unwrap().to_owned())
+            .map(|disk_arc| disk_arc.read_or_recover().to_owned())
             .collect()
     }
 
     pub fn selected_disk(&self) -> Option<Arc<RwLock<OpticalDiskInfo>>> {
-        let disk_id = self
-            .selected_optical_disk_id
-            .read()
-            .expect("failed to lock selected_optical_disk_id in find_selected_disk");
+        let disk_id = self.selected_optical_disk_id.read_or_recover();
         match disk_id.as_ref() {
             Some(disk_id) => self.find_optical_disk_by_id(disk_id),
             None => None,
@@ -509,14 +1646,9 @@ impl AppState {
         &self,
         disk_id: &DiskId,
     ) -> Option<Arc<RwLock<OpticalDiskInfo>>> {
-        let disks = self
-            .optical_disks
-            .read()
-            .expect("Failed to acquire lock on optical_disks in find_optical_disk_by_id command");
+        let disks = self.optical_disks.read_or_recover();
         for disk in disks.iter() {
-            let disk_guard = disk
-                .read()
-                .expect("Failed to acquire lock on disk in find_optical_disk_by_id command");
+            let disk_guard = disk.read_or_recover();
             if &disk_guard.id == disk_id {
                 return Some(Arc::clone(disk));
             }
@@ -534,8 +1666,7 @@ impl AppState {
                 .unwrap_or_else(|_| crate::services::semantic_version::SemanticVersion::none());
         let latest_version = self
             .latest_version
-            .lock()
-            .expect("failed to lock latest_version")
+            .lock_or_recover()
             .clone()
             .and_then(|v| crate::services::semantic_version::SemanticVersion::parse(&v).ok())
             .unwrap_or_else(crate::services::semantic_version::SemanticVersion::none);
@@ -576,11 +1707,41 @@ impl AppState {
                 let mut ftp_tv_upload_path = self.lock_ftp_tv_upload_path();
                 *ftp_tv_upload_path = cleaned.map(PathBuf::from);
             }
+            "ftp_movie_path_template" => {
+                let mut ftp_config = self.lock_ftp_config();
+                ftp_config.movie_path_template = cleaned;
+            }
+            "ftp_tv_path_template" => {
+                let mut ftp_config = self.lock_ftp_config();
+                ftp_config.tv_path_template = cleaned;
+            }
+            "ftp_file_mode" => {
+                self.lock_ftp_config().file_mode = cleaned;
+            }
+            "ftp_season_posters" => {
+                self.lock_ftp_config().season_posters = cleaned.as_deref() != Some("false");
+            }
             _ => {}
         }
     }
 }
 
+/// Concurrency limits are stored as strings like every other setting, "0" and junk are ignored.
+fn parse_limit(value: &Option<String>) -> Option<usize> {
+    value
+        .as_deref()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|limit| *limit > 0)
+}
+
+/// Same as `parse_limit` for timeouts in seconds.
+fn parse_seconds(value: &Option<String>) -> Option<u64> {
+    value
+        .as_deref()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|seconds| *seconds > 0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -827,6 +1988,34 @@ mod tests {
         assert_eq!(*state.lock_ftp_tv_upload_path(), None);
     }
 
+    #[test]
+    fn test_update_ftp_path_template_fields() {
+        let state = AppState::new();
+
+        state.test_update_ftp_setting(
+            "ftp_movie_path_template",
+            Some("  {first_letter}/{title} ({year})  ".to_string()),
+        );
+        state.test_update_ftp_setting(
+            "ftp_tv_path_template",
+            Some("{title}/S{season}".to_string()),
+        );
+        {
+            let ftp_config = state.lock_ftp_config();
+            assert_eq!(
+                ftp_config.movie_path_template,
+                Some("{first_letter}/{title} ({year})".to_string())
+            );
+            assert_eq!(
+                ftp_config.tv_path_template,
+                Some("{title}/S{season}".to_string())
+            );
+        }
+
+        state.test_update_ftp_setting("ftp_movie_path_template", Some("".to_string()));
+        assert_eq!(state.lock_ftp_config().movie_path_template, None);
+    }
+
     #[test]
     fn test_update_the_movie_db_key_field() {
         let state = AppState::new();
@@ -946,4 +2135,12 @@ mod tests {
             Some(PathBuf::from("/Media/TV Shows"))
         );
     }
+
+    #[test]
+    fn test_parse_limit() {
+        assert_eq!(parse_limit(&Some("2".to_string())), Some(2));
+        assert_eq!(parse_limit(&Some("0".to_string())), None);
+        assert_eq!(parse_limit(&Some("two".to_string())), None);
+        assert_eq!(parse_limit(&None), None);
+    }
 }
@@ -1,25 +1,63 @@
 use crate::models::optical_disk_info::{DiskId, OpticalDiskInfo};
-use crate::state::job_state::{Job, JobStatus, JobType};
+use crate::state::emit_coalescer::EmitCoalescer;
+use crate::state::job_state::{emit_progress, Job, JobId, JobStatus, JobType};
+use crate::state::locks::RwLockExt;
+use crate::state::resource_governor::{ConcurrencyLimits, ResourceGovernor, Slot, SlotPermit};
+use crate::state::title_video::{TitleVideo, Video};
+use log::error;
 use std::sync::{Arc, RwLock};
-use tauri::Emitter;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// How often a job waiting on its parents checks them again
+const DEPENDENCY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Where a job stands with the jobs it depends on.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Dependencies {
+    /// Every parent is done with this job's titles
+    Ready,
+    /// This parent is still working
+    Waiting(JobId),
+    /// This parent failed before it got to this job's titles
+    Failed(JobId),
+}
 
 pub struct BackgroundProcessState {
     pub jobs: RwLock<Vec<Arc<RwLock<Job>>>>,
+    pub governor: Arc<ResourceGovernor>,
+    pub emits: EmitCoalescer,
 }
 
 impl BackgroundProcessState {
     pub fn new() -> Self {
         Self {
             jobs: RwLock::new(Vec::new()),
+            governor: Arc::new(ResourceGovernor::new(ConcurrencyLimits::default())),
+            emits: EmitCoalescer::new(),
+        }
+    }
+
+    /// Keeps `job` Pending until the governor has a free `slot` for it.
+    ///
+    /// The permit has to be held for as long as the work runs.
+    pub async fn wait_for_slot(
+        &self,
+        app_handle: &tauri::AppHandle,
+        job: &Arc<RwLock<Job>>,
+        slot: Slot,
+    ) -> SlotPermit {
+        if let Some(permit) = self.governor.try_acquire(slot) {
+            return permit;
         }
+        job.write_or_recover().subtitle = Some(format!("Waiting for a free {slot} slot"));
+        emit_progress(app_handle, job, true);
+        self.governor.acquire(slot).await
     }
 
     pub fn add_job(&self, job: Job) -> Arc<RwLock<Job>> {
         let job = Arc::new(RwLock::new(job));
-        self.jobs
-            .write()
-            .expect("lock jobs for write")
-            .push(job.clone());
+        self.jobs.write_or_recover().push(job.clone());
         job
     }
 
@@ -32,12 +70,121 @@ impl BackgroundProcessState {
         self.add_job(Job::new(job_type, disk, job_state))
     }
 
+    /// The active season job called `title`, a new one when the season has none yet.
+    pub fn season_job(&self, title: &str) -> Arc<RwLock<Job>> {
+        let existing = self
+            .jobs
+            .read_or_recover()
+            .iter()
+            .find(|job| {
+                let job = job.read_or_recover();
+                job.job_type == JobType::Season
+                    && !job.is_completed()
+                    && job.title.as_deref() == Some(title)
+            })
+            .cloned();
+        existing.unwrap_or_else(|| {
+            let mut job = Job::new(JobType::Season, None, JobStatus::Pending);
+            job.title = Some(title.to_string());
+            self.add_job(job)
+        })
+    }
+
+    /// Lists `job` under the season job of its title video when that is an episode.
+    pub fn group_by_season(&self, job: &Arc<RwLock<Job>>, title_video: &TitleVideo) {
+        let Video::Tv(tv) = &title_video.video else {
+            return;
+        };
+        let parent = self.season_job(&tv.season_title());
+        let parent_id = parent.read_or_recover().id;
+        job.write_or_recover().parent_id = Some(parent_id);
+        self.refresh_parent(parent_id);
+    }
+
+    pub fn children(&self, parent_id: JobId) -> Vec<Job> {
+        self.jobs
+            .read_or_recover()
+            .iter()
+            .map(|job| job.read_or_recover())
+            .filter(|job| job.parent_id == Some(parent_id))
+            .map(|job| job.clone())
+            .collect()
+    }
+
+    /// Aggregates the season job `parent_id` again from its children.
+    pub fn refresh_parent(&self, parent_id: JobId) -> Option<Arc<RwLock<Job>>> {
+        let parent = self.find_job_by_id(parent_id)?;
+        let children = self.children(parent_id);
+        parent.write_or_recover().aggregate(&children);
+        Some(parent)
+    }
+
+    pub fn find_job_by_id(&self, job_id: JobId) -> Option<Arc<RwLock<Job>>> {
+        self.jobs
+            .read_or_recover()
+            .iter()
+            .find(|job| job.read_or_recover().id == job_id)
+            .cloned()
+    }
+
+    /// Checks the parents of `job`. A parent that was deleted no longer holds anything up.
+    pub fn dependencies(&self, job: &Job) -> Dependencies {
+        let title_video_ids: Vec<_> = job
+            .title_videos
+            .iter()
+            .filter_map(|title_video| title_video.read().ok().map(|guard| guard.id))
+            .collect();
+
+        for parent_id in &job.depends_on {
+            let Some(parent) = self.find_job_by_id(*parent_id) else {
+                continue;
+            };
+            let parent = parent.read_or_recover();
+            if parent.has_finished_title_videos(&title_video_ids) {
+                continue;
+            }
+            if parent.is_error() {
+                return Dependencies::Failed(*parent_id);
+            }
+            return Dependencies::Waiting(*parent_id);
+        }
+        Dependencies::Ready
+    }
+
+    /// Keeps `job` Pending until all of its parents are done with its titles.
+    ///
+    /// Errors when a parent failed, there is nothing for this job to work on then.
+    pub async fn wait_for_dependencies(
+        &self,
+        app_handle: &tauri::AppHandle,
+        job: &Arc<RwLock<Job>>,
+    ) -> Result<(), String> {
+        let mut waiting_on = None;
+        loop {
+            let dependencies = self.dependencies(&job.read_or_recover());
+            match dependencies {
+                Dependencies::Ready => return Ok(()),
+                Dependencies::Failed(parent_id) => {
+                    return Err(format!("Job {parent_id} this job depends on failed"))
+                }
+                Dependencies::Waiting(parent_id) => {
+                    if waiting_on != Some(parent_id) {
+                        waiting_on = Some(parent_id);
+                        job.write_or_recover().subtitle =
+                            Some(format!("Waiting for job {parent_id}"));
+                        emit_progress(app_handle, job, true);
+                    }
+                    tokio::time::sleep(DEPENDENCY_POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
     pub fn clone_all_jobs(&self) -> Vec<Job> {
         self.jobs
-            .read()
-            .expect("lock jobs for read")
+            .read_or_recover()
             .iter()
-            .map(|job| job.read().expect("lock job for read").clone())
+            .map(|job| job.read_or_recover().clone())
             .collect()
     }
 
@@ -48,6 +195,41 @@ impl BackgroundProcessState {
         app_handle
             .emit("disks-changed", result)
             .expect("Failed to emit jobs-changed");
+        crate::services::tray_menu::refresh(app_handle);
+    }
+
+    /// The progress of the changed jobs rendered into one payload, `None` when nothing
+    /// changed.
+    pub fn render_changed_jobs(&self) -> Option<String> {
+        let payload: String = self
+            .emits
+            .take()
+            .into_iter()
+            .filter_map(|job_id| self.find_job_by_id(job_id))
+            .filter_map(|job| {
+                let job = job.read_or_recover();
+                crate::templates::jobs::render_job_progress(&job)
+                    .map_err(|e| error!("Failed to render job {}: {e:?}", job.id))
+                    .ok()
+            })
+            .collect();
+        (!payload.is_empty()).then_some(payload)
+    }
+
+    /// Emits the progress of every job that changed, at most `MAX_EMITS_PER_SECOND` times
+    /// a second however many jobs are running.
+    pub async fn flush_emits(app_handle: tauri::AppHandle) {
+        loop {
+            tokio::time::sleep(EmitCoalescer::interval()).await;
+            let state = app_handle.state::<BackgroundProcessState>();
+            let Some(payload) = state.render_changed_jobs() else {
+                continue;
+            };
+            if let Err(e) = app_handle.emit("disks-changed", payload) {
+                error!("Failed to emit job progress: {e}");
+            }
+            crate::services::tray_menu::refresh(&app_handle);
+        }
     }
 
     /// Finds the first job matching the specified criteria.
@@ -98,9 +280,9 @@ impl BackgroundProcessState {
         job_type: &Option<JobType>,
         job_states: &[JobStatus],
     ) -> Option<Arc<RwLock<Job>>> {
-        let jobs = self.jobs.read().expect("lock jobs for read");
+        let jobs = self.jobs.read_or_recover();
         jobs.iter().find_map(|job| {
-            let job_guard = job.read().expect("lock job for read");
+            let job_guard = job.read_or_recover();
 
             // Check if job type matches (None means match any type)
             let job_type_matches = match job_type {
@@ -141,13 +323,17 @@ impl BackgroundProcessState {
         job_type: &JobType,
         job_state: &JobStatus,
     ) -> (Arc<RwLock<Job>>, bool) {
-        if let Some(job) = self.find_job(disk_id, &Some(job_type.clone()), &[job_state.clone()]) {
+        if let Some(job) = self.find_job(
+            disk_id,
+            &Some(job_type.clone()),
+            std::slice::from_ref(job_state),
+        ) {
             (job, false)
         } else {
             let job = match optical_disk {
                 None => self.new_job(job_type.clone(), job_state.clone(), None),
                 Some(optical_disk) => {
-                    let optical_disk_info = optical_disk.read().unwrap().clone();
+                    let optical_disk_info = optical_disk.read_or_recover().clone();
                     self.new_job(job_type.clone(), job_state.clone(), Some(optical_disk_info))
                 }
             };
@@ -155,11 +341,12 @@ impl BackgroundProcessState {
         }
     }
 
+    /// Deletes the job, the children of a season job go with it.
     pub fn delete_job(&self, job_id: crate::state::job_state::JobId) {
-        let mut jobs = self.jobs.write().expect("lock jobs for write");
+        let mut jobs = self.jobs.write_or_recover();
         jobs.retain(|job| {
-            let job_guard = job.read().expect("lock job for read");
-            job_guard.id != job_id
+            let job_guard = job.read_or_recover();
+            job_guard.id != job_id && job_guard.parent_id != Some(job_id)
         });
     }
 }
@@ -167,9 +354,115 @@ impl BackgroundProcessState {
 pub fn copy_job_state(job: &Option<Arc<RwLock<Job>>>) -> Option<Job> {
     match job {
         Some(j) => {
-            let job_guard = j.read().expect("failed to lock job for read");
+            let job_guard = j.read_or_recover();
             Some(job_guard.clone())
         }
         None => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependent_job(
+        state: &BackgroundProcessState,
+        parent: &Arc<RwLock<Job>>,
+    ) -> Arc<RwLock<Job>> {
+        let job = state.new_job(JobType::Uploading, JobStatus::Pending, None);
+        job.write().unwrap().depend_on(parent.read().unwrap().id);
+        job
+    }
+
+    fn dependencies_of(state: &BackgroundProcessState, job: &Arc<RwLock<Job>>) -> Dependencies {
+        state.dependencies(&job.read().unwrap())
+    }
+
+    #[test]
+    fn waits_for_a_running_parent() {
+        let state = BackgroundProcessState::new();
+        let rip = state.new_job(JobType::Ripping, JobStatus::Processing, None);
+        let upload = dependent_job(&state, &rip);
+        let rip_id = rip.read().unwrap().id;
+
+        assert_eq!(
+            dependencies_of(&state, &upload),
+            Dependencies::Waiting(rip_id)
+        );
+
+        rip.write().unwrap().update_status(JobStatus::Finished);
+        assert_eq!(dependencies_of(&state, &upload), Dependencies::Ready);
+    }
+
+    #[test]
+    fn failed_parent_fails_the_dependency() {
+        let state = BackgroundProcessState::new();
+        let rip = state.new_job(JobType::Ripping, JobStatus::Processing, None);
+        let upload = dependent_job(&state, &rip);
+        rip.write().unwrap().update_status(JobStatus::Error);
+        let rip_id = rip.read().unwrap().id;
+
+        assert_eq!(
+            dependencies_of(&state, &upload),
+            Dependencies::Failed(rip_id)
+        );
+    }
+
+    #[test]
+    fn deleted_parent_no_longer_blocks() {
+        let state = BackgroundProcessState::new();
+        let rip = state.new_job(JobType::Ripping, JobStatus::Error, None);
+        let upload = dependent_job(&state, &rip);
+
+        state.delete_job(rip.read().unwrap().id);
+        assert_eq!(dependencies_of(&state, &upload), Dependencies::Ready);
+    }
+
+    #[test]
+    fn groups_episodes_under_one_season_job() {
+        let state = BackgroundProcessState::new();
+        let season = state.season_job("Show (2020) - Season 1");
+        assert!(Arc::ptr_eq(
+            &season,
+            &state.season_job("Show (2020) - Season 1")
+        ));
+        let season_id = season.read().unwrap().id;
+        let upload = state.new_job(JobType::Uploading, JobStatus::Processing, None);
+        upload.write().unwrap().parent_id = Some(season_id);
+
+        assert_eq!(state.children(season_id).len(), 1);
+        let season = state.refresh_parent(season_id).unwrap();
+        assert!(season.read().unwrap().is_processing());
+
+        state.delete_job(season_id);
+        assert!(state.clone_all_jobs().is_empty());
+    }
+
+    #[test]
+    fn renders_only_the_changed_jobs_that_still_exist() {
+        let state = BackgroundProcessState::new();
+        assert_eq!(state.render_changed_jobs(), None);
+
+        let job = state.new_job(JobType::Uploading, JobStatus::Processing, None);
+        let job_id = job.read().unwrap().id;
+        state.emits.queue(job_id);
+        state.emits.queue(JobId::from(u64::MAX));
+        let payload = state.render_changed_jobs().expect("a payload");
+        assert_eq!(
+            payload,
+            crate::templates::jobs::render_job_progress(&job.read().unwrap()).unwrap()
+        );
+        assert_eq!(state.render_changed_jobs(), None);
+    }
+
+    #[test]
+    fn finds_jobs_by_id() {
+        let state = BackgroundProcessState::new();
+        let job = state.new_job(JobType::Uploading, JobStatus::Pending, None);
+        let job_id = job.read().unwrap().id;
+
+        let found = state.find_job_by_id(job_id).expect("job to be found");
+        assert!(Arc::ptr_eq(&found, &job));
+        assert!(state.find_job_by_id(JobId::from(u64::MAX)).is_none());
+    }
+}
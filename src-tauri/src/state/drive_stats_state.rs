@@ -0,0 +1,359 @@
+use crate::models::title_info::TitleInfo;
+use crate::services::locale;
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "drive_stats.json";
+
+/// More read errors per disc than this and the drive is flagged on the health view.
+const UNHEALTHY_ERRORS_PER_DISC: f64 = 1.0;
+
+/// DVDs and Blu-rays read at very different speeds in the same drive.
+pub fn media_type(title: &TitleInfo) -> Option<&'static str> {
+    match title.quality_tag()? {
+        tag if tag.starts_with("Bluray") => Some("Blu-ray"),
+        _ => Some("DVD"),
+    }
+}
+
+/// Bytes read and how long it took, per media type.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Throughput {
+    pub bytes_read: u64,
+    pub seconds_reading: u64,
+}
+
+impl Throughput {
+    pub fn speed(&self) -> Option<f64> {
+        if self.seconds_reading == 0 {
+            return None;
+        }
+        Some(self.bytes_read as f64 / self.seconds_reading as f64)
+    }
+}
+
+/// Read statistics for a single optical drive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DriveStats {
+    /// Drive model/serial, see `OpticalDiskInfo::drive_key`
+    pub drive: String,
+    pub discs_read: u64,
+    pub read_errors: u64,
+    pub failed_rips: u64,
+    pub bytes_read: u64,
+    pub seconds_reading: u64,
+    /// Bytes per second of the last benchmark, see `services::drive_benchmark`
+    #[serde(default)]
+    pub benchmark_speed: Option<f64>,
+    /// The rips above split up by `media_type`
+    #[serde(default)]
+    pub media: BTreeMap<String, Throughput>,
+}
+
+impl DriveStats {
+    pub fn new(drive: &str) -> Self {
+        DriveStats {
+            drive: drive.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Bytes per second over every rip, `None` until something was ripped.
+    pub fn average_speed(&self) -> Option<f64> {
+        if self.seconds_reading == 0 {
+            return None;
+        }
+        Some(self.bytes_read as f64 / self.seconds_reading as f64)
+    }
+
+    /// e.g. "12.4 MB/s"
+    pub fn average_speed_label(&self) -> String {
+        match self.average_speed() {
+            Some(speed) => locale::current().format_speed(speed),
+            None => "-".to_string(),
+        }
+    }
+
+    /// e.g. "18.2 MB/s", "-" when the drive was never benchmarked
+    pub fn benchmark_speed_label(&self) -> String {
+        match self.benchmark_speed {
+            Some(speed) => locale::current().format_speed(speed),
+            None => "-".to_string(),
+        }
+    }
+
+    /// e.g. `["Blu-ray 21.3 MB/s", "DVD 7.9 MB/s"]`
+    pub fn media_speed_labels(&self) -> Vec<String> {
+        self.media
+            .iter()
+            .filter_map(|(media, throughput)| {
+                let speed = throughput.speed()?;
+                Some(format!("{media} {}", locale::current().format_speed(speed)))
+            })
+            .collect()
+    }
+
+    /// How long ripping `bytes` should take. Rips of the same media type are trusted over
+    /// all rips, and those over a benchmark.
+    pub fn estimated_seconds(&self, bytes: u64, media: Option<&str>) -> Option<u64> {
+        let speed = media
+            .and_then(|media| self.media.get(media))
+            .and_then(Throughput::speed)
+            .or_else(|| self.average_speed())
+            .or(self.benchmark_speed)?;
+        if speed <= 0.0 {
+            return None;
+        }
+        Some((bytes as f64 / speed).round() as u64)
+    }
+
+    pub fn errors_per_disc(&self) -> f64 {
+        if self.discs_read == 0 {
+            return self.read_errors as f64;
+        }
+        self.read_errors as f64 / self.discs_read as f64
+    }
+
+    pub fn is_unhealthy(&self) -> bool {
+        self.errors_per_disc() > UNHEALTHY_ERRORS_PER_DISC
+            || (self.failed_rips > 0 && self.failed_rips * 2 >= self.discs_read)
+    }
+}
+
+/// Per-drive statistics kept in memory and persisted to "drive_stats.json"
+pub struct DriveStatsState {
+    stats: Arc<RwLock<HashMap<String, DriveStats>>>,
+}
+
+impl DriveStatsState {
+    /// Create a new DriveStatsState and load the statistics from store
+    pub fn new(app_handle: &AppHandle) -> Result<Self, String> {
+        let store = app_handle
+            .store(STORE_FILE)
+            .map_err(|e| format!("Failed to load {STORE_FILE} store: {e}"))?;
+
+        let drives: Vec<DriveStats> = if let Some(value) = store.get("drives") {
+            serde_json::from_value(value.clone()).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        store.close_resource();
+
+        debug!("Loaded statistics for {} drives from store", drives.len());
+        let stats = drives
+            .into_iter()
+            .map(|drive_stats| (drive_stats.drive.clone(), drive_stats))
+            .collect();
+        Ok(DriveStatsState {
+            stats: Arc::new(RwLock::new(stats)),
+        })
+    }
+
+    /// Load the statistics, or start without any so the app always has them
+    pub fn load(app_handle: &AppHandle) -> Self {
+        match Self::new(app_handle) {
+            Ok(state) => state,
+            Err(e) => {
+                error!("{e}. Drive statistics start over and are saved again after the next rip");
+                DriveStatsState {
+                    stats: Arc::new(RwLock::new(HashMap::new())),
+                }
+            }
+        }
+    }
+
+    /// A disc was scanned, `read_errors` is how many read errors makemkvcon reported doing it
+    pub fn record_disc_read(
+        &self,
+        app_handle: &AppHandle,
+        drive: &str,
+        read_errors: u64,
+    ) -> Result<(), String> {
+        self.update(app_handle, drive, |stats| {
+            stats.discs_read += 1;
+            stats.read_errors += read_errors;
+        })
+    }
+
+    /// A title finished ripping, used for the average read speed
+    pub fn record_rip(
+        &self,
+        app_handle: &AppHandle,
+        drive: &str,
+        media: Option<&str>,
+        bytes: u64,
+        seconds: u64,
+        read_errors: u64,
+    ) -> Result<(), String> {
+        self.update(app_handle, drive, |stats| {
+            stats.bytes_read += bytes;
+            stats.seconds_reading += seconds;
+            stats.read_errors += read_errors;
+            if let Some(media) = media {
+                let throughput = stats.media.entry(media.to_string()).or_default();
+                throughput.bytes_read += bytes;
+                throughput.seconds_reading += seconds;
+            }
+        })
+    }
+
+    /// The drive was benchmarked, the last result replaces the one before
+    pub fn record_benchmark(
+        &self,
+        app_handle: &AppHandle,
+        drive: &str,
+        bytes_per_second: f64,
+    ) -> Result<(), String> {
+        self.update(app_handle, drive, |stats| {
+            stats.benchmark_speed = Some(bytes_per_second);
+        })
+    }
+
+    /// makemkvcon gave up on a title or a scan
+    pub fn record_failure(&self, app_handle: &AppHandle, drive: &str) -> Result<(), String> {
+        self.update(app_handle, drive, |stats| {
+            stats.failed_rips += 1;
+            stats.read_errors += 1;
+        })
+    }
+
+    /// All drives sorted by name
+    pub fn all(&self) -> Vec<DriveStats> {
+        let mut drives: Vec<DriveStats> = self
+            .stats
+            .read()
+            .map(|guard| guard.values().cloned().collect())
+            .unwrap_or_default();
+        drives.sort_by(|a, b| a.drive.cmp(&b.drive));
+        drives
+    }
+
+    pub fn find(&self, drive: &str) -> Option<DriveStats> {
+        self.stats
+            .read()
+            .ok()
+            .and_then(|guard| guard.get(drive).cloned())
+    }
+
+    fn update(
+        &self,
+        app_handle: &AppHandle,
+        drive: &str,
+        change: impl FnOnce(&mut DriveStats),
+    ) -> Result<(), String> {
+        {
+            let mut guard = self
+                .stats
+                .write()
+                .map_err(|_| "Failed to acquire write lock on drive stats".to_string())?;
+            let stats = guard
+                .entry(drive.to_string())
+                .or_insert_with(|| DriveStats::new(drive));
+            change(stats);
+        }
+        self.persist_to_store(app_handle)
+    }
+
+    fn persist_to_store(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let store = app_handle
+            .store(STORE_FILE)
+            .map_err(|e| format!("Failed to open {STORE_FILE} store: {e}"))?;
+
+        store.set("drives", json!(self.all()));
+        store
+            .save()
+            .map_err(|e| format!("Failed to save {STORE_FILE} store: {e}"))?;
+
+        store.close_resource();
+        Ok(())
+    }
+}
+
+/// Statistics are best effort, a failure to record them is logged and never fails a rip.
+pub fn record(app_handle: &AppHandle, change: impl FnOnce(&DriveStatsState) -> Result<(), String>) {
+    if let Err(e) = change(&app_handle.state::<DriveStatsState>()) {
+        error!("Failed to record drive stats: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_speed_needs_a_rip() {
+        let mut stats = DriveStats::new("BD-RE HL-DT-ST BD-RE WH16NS40 1.02 SIK9xxxxxx");
+        assert_eq!(stats.average_speed(), None);
+        assert_eq!(stats.average_speed_label(), "-");
+
+        stats.bytes_read = 25_000_000_000;
+        stats.seconds_reading = 2_000;
+        assert_eq!(stats.average_speed(), Some(12_500_000.0));
+        assert_eq!(stats.average_speed_label(), "12.5 MB/s");
+    }
+
+    #[test]
+    fn estimates_rip_time_from_rips_before_the_benchmark() {
+        let mut stats = DriveStats::new("drive");
+        assert_eq!(stats.estimated_seconds(1_000_000_000, None), None);
+
+        stats.benchmark_speed = Some(20_000_000.0);
+        assert_eq!(stats.estimated_seconds(1_000_000_000, None), Some(50));
+        assert_eq!(stats.benchmark_speed_label(), "20.0 MB/s");
+
+        stats.bytes_read = 10_000_000_000;
+        stats.seconds_reading = 1_000;
+        assert_eq!(stats.estimated_seconds(1_000_000_000, None), Some(100));
+    }
+
+    #[test]
+    fn estimates_from_rips_of_the_same_media_type() {
+        let mut stats = DriveStats::new("drive");
+        stats.bytes_read = 10_000_000_000;
+        stats.seconds_reading = 1_000;
+        stats.media.insert(
+            "DVD".to_string(),
+            Throughput {
+                bytes_read: 4_000_000_000,
+                seconds_reading: 800,
+            },
+        );
+
+        assert_eq!(
+            stats.estimated_seconds(1_000_000_000, Some("DVD")),
+            Some(200)
+        );
+        assert_eq!(
+            stats.estimated_seconds(1_000_000_000, Some("Blu-ray")),
+            Some(100)
+        );
+        assert_eq!(stats.media_speed_labels(), vec!["DVD 5.0 MB/s".to_string()]);
+    }
+
+    #[test]
+    fn flags_drives_with_many_errors() {
+        let mut stats = DriveStats::new("drive");
+        stats.discs_read = 10;
+        stats.read_errors = 3;
+        assert!(!stats.is_unhealthy());
+
+        stats.read_errors = 25;
+        assert!(stats.is_unhealthy());
+    }
+
+    #[test]
+    fn flags_drives_that_fail_half_the_rips() {
+        let mut stats = DriveStats::new("drive");
+        stats.discs_read = 4;
+        stats.failed_rips = 1;
+        assert!(!stats.is_unhealthy());
+
+        stats.failed_rips = 2;
+        assert!(stats.is_unhealthy());
+    }
+}
@@ -0,0 +1,59 @@
+//! Every running job reports its progress several times a second, with a few jobs at once
+//! the webview spends its time swapping job items in and out.
+//!
+//! Progress updates only mark the job as changed here, a flush renders all changed jobs into
+//! one payload and emits it at most `MAX_EMITS_PER_SECOND` times a second.
+use crate::state::job_state::JobId;
+use crate::state::locks::MutexExt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub const MAX_EMITS_PER_SECOND: u32 = 2;
+
+pub struct EmitCoalescer {
+    /// Jobs changed since the last flush, in the order they first changed
+    changed: Mutex<Vec<JobId>>,
+}
+
+impl EmitCoalescer {
+    pub fn new() -> Self {
+        Self {
+            changed: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn interval() -> Duration {
+        Duration::from_secs(1) / MAX_EMITS_PER_SECOND
+    }
+
+    pub fn queue(&self, job_id: JobId) {
+        let mut changed = self.changed.lock_or_recover();
+        if !changed.contains(&job_id) {
+            changed.push(job_id);
+        }
+    }
+
+    /// The jobs that changed, the next flush starts empty.
+    pub fn take(&self) -> Vec<JobId> {
+        std::mem::take(&mut *self.changed.lock_or_recover())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queues_every_job_once_until_taken() {
+        let coalescer = EmitCoalescer::new();
+        let first = JobId::from(1);
+        let second = JobId::from(2);
+        coalescer.queue(first);
+        coalescer.queue(second);
+        coalescer.queue(first);
+
+        assert_eq!(coalescer.take(), vec![first, second]);
+        assert!(coalescer.take().is_empty());
+        assert_eq!(EmitCoalescer::interval(), Duration::from_millis(500));
+    }
+}
@@ -1,21 +1,21 @@
-use crate::models::title_info::TitleInfo;
+use crate::models::title_info::{TitleId, TitleInfo};
 use crate::standard_error::StandardError;
+use crate::state::locks::RwLockExt;
 use crate::state::title_video::{TitleVideo, Video};
-use crate::the_movie_db::TvId;
+use crate::the_movie_db::{EpisodeId, SeasonId, TvId};
 use crate::{
     models::optical_disk_info::OpticalDiskInfo,
     progress_tracker::{self, components::TimeComponent},
 };
 use log::debug;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
-use std::time::SystemTime;
 use std::{
+    collections::{BTreeMap, HashSet},
     fmt,
     sync::atomic::{AtomicU64, Ordering},
 };
-use tauri::{AppHandle, Emitter};
-use tokio::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
 
 #[derive(Serialize, Clone)]
 pub struct Job {
@@ -29,7 +29,29 @@ pub struct Job {
     pub disk: Option<OpticalDiskInfo>,
     pub title_videos: Vec<Arc<RwLock<TitleVideo>>>,
     pub current_title_video_id: Option<crate::state::title_video::TitleVideoId>,
-    pub last_emit: SystemTime,
+    /// Findings worth keeping after the job finishes, e.g. post-process results
+    pub notices: Vec<String>,
+    pub previews: Vec<TitlePreview>,
+    /// Jobs that have to finish before this one may start, e.g. the rip an upload is for
+    pub depends_on: Vec<JobId>,
+    /// Titles this job is done with, a dependent job only waits for the titles it shares
+    pub finished_title_video_ids: Vec<crate::state::title_video::TitleVideoId>,
+    /// Rip time of the current title predicted from the drive's speed, the ETA until
+    /// makemkvcon reported enough progress for its own
+    pub expected_seconds: Option<u64>,
+    /// The season job this job is listed under, see `JobType::Season`
+    pub parent_id: Option<JobId>,
+    /// Set by the user, e.g. where the disc is stored, see `job_notes`
+    pub note: Option<String>,
+    /// `(index, count)` of the title being ripped when the video joins several, each title's
+    /// progress is a share of the whole
+    pub joined_part: Option<(usize, usize)>,
+    /// Set by the disk listener when the disc was pulled while the job was using it, the
+    /// job stays failed even when the titles that were done already finish
+    pub disc_removed: bool,
+    /// makemkvcon is writing the current title, only then does a pulled disc leave a
+    /// partial file behind
+    pub ripping_title: bool,
 }
 
 impl Job {
@@ -48,7 +70,16 @@ impl Job {
             disk,
             title_videos: Vec::new(),
             current_title_video_id: None,
-            last_emit: SystemTime::now(),
+            notices: Vec::new(),
+            previews: Vec::new(),
+            depends_on: Vec::new(),
+            finished_title_video_ids: Vec::new(),
+            expected_seconds: None,
+            parent_id: None,
+            note: None,
+            joined_part: None,
+            disc_removed: false,
+            ripping_title: false,
         }
     }
 
@@ -71,15 +102,15 @@ impl Job {
     fn select_tv_title_video_parts(
         &self,
         tv_id: TvId,
-        season_number: u32,
-        episode_number: u32,
+        season_id: SeasonId,
+        episode_id: EpisodeId,
     ) -> Vec<Arc<RwLock<TitleVideo>>> {
         let parts = self.title_videos.iter().filter(|tv| {
             if let Ok(guard) = tv.read() {
                 if let Video::Tv(tv_ep) = &guard.video {
                     return tv_ep.tv.id == tv_id
-                        && tv_ep.season.id == season_number
-                        && tv_ep.episode.id == episode_number;
+                        && tv_ep.season.id == season_id
+                        && tv_ep.episode.id == episode_id;
                 }
             }
             false
@@ -93,7 +124,7 @@ impl Job {
     /// This lets us keep `part` metadata (e.g. `part=1`) on the assigned video
     /// while deciding at rip time whether `-pt1` is actually needed in filenames.
     pub fn has_multiple_parts(&self, title_video: &TitleVideo) -> bool {
-        let (tv_id, season_number, episode_number) = match &title_video.video {
+        let (tv_id, season_id, episode_id) = match &title_video.video {
             Video::Tv(tv_season_episode) => (
                 tv_season_episode.tv.id,
                 tv_season_episode.season.id,
@@ -101,7 +132,7 @@ impl Job {
             ),
             _ => return false,
         };
-        self.select_tv_title_video_parts(tv_id, season_number, episode_number)
+        self.select_tv_title_video_parts(tv_id, season_id, episode_id)
             .len()
             > 1
     }
@@ -110,7 +141,7 @@ impl Job {
     ///
     /// How to use:
     /// ```text
-    /// let maybe_title_video = job.find_tv_title_video(tv_id, season_number, episode_number, title_id, Some(part));
+    /// let maybe_title_video = job.find_tv_title_video(tv.id, season.id, episode.id, part);
     /// if let Some(title_video) = maybe_title_video {
     ///     // Do something with the matching TitleVideo
     /// }
@@ -120,19 +151,19 @@ impl Job {
     pub fn find_tv_title_video(
         &self,
         tv_id: TvId,
-        season_number: u32,
-        episode_number: u32,
+        season_id: SeasonId,
+        episode_id: EpisodeId,
         part: u16,
     ) -> Option<Arc<RwLock<TitleVideo>>> {
         self.title_videos
             .iter()
             .find(|title_video| {
-                let title_video = title_video.read().unwrap();
+                let title_video = title_video.read_or_recover();
 
                 if let Video::Tv(tv_season_episode) = &title_video.video {
                     tv_season_episode.tv.id == tv_id
-                        && tv_season_episode.season.id == season_number
-                        && tv_season_episode.episode.id == episode_number
+                        && tv_season_episode.season.id == season_id
+                        && tv_season_episode.episode.id == episode_id
                         && tv_season_episode.part == part
                 } else {
                     false
@@ -143,14 +174,14 @@ impl Job {
 
     pub fn matching_title(&self, title: &TitleInfo) -> bool {
         self.title_videos.iter().any(|title_video| {
-            let title_video = title_video.read().unwrap();
+            let title_video = title_video.read_or_recover();
             title_video.title.as_ref().map(|t| t.id) == Some(title.id)
         })
     }
 
     pub fn has_incomplete_titles(&self) -> bool {
         self.title_videos.iter().any(|title_video| {
-            let title_video = title_video.read().unwrap();
+            let title_video = title_video.read_or_recover();
             title_video.title.is_none()
         })
     }
@@ -165,6 +196,10 @@ impl Job {
             id: crate::state::title_video::TitleVideoId::new(),
             title: Some(title),
             video,
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
         };
         self.update_title(&title_video);
         self.title_videos.push(Arc::new(RwLock::new(title_video)));
@@ -177,11 +212,39 @@ impl Job {
             id: crate::state::title_video::TitleVideoId::new(),
             title: None,
             video,
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
         };
         self.title_videos.push(Arc::new(RwLock::new(title_video)));
         Ok(self)
     }
 
+    /// Send the titles of this job to a library profile, `None` is the main library.
+    ///
+    /// Titles that are already done keep their library, their files are already there.
+    pub fn set_library(&self, library: Option<String>) {
+        for title_video in &self.title_videos {
+            let mut title_video = title_video.write_or_recover();
+            if !self.finished_title_video_ids.contains(&title_video.id) {
+                title_video.library = library.clone();
+            }
+        }
+    }
+
+    /// Keep the titles of this job out of the upload queue, e.g. to try out encodes.
+    ///
+    /// Titles that are already done keep their flag, their uploads are already queued.
+    pub fn set_skip_upload(&self, skip_upload: bool) {
+        for title_video in &self.title_videos {
+            let mut title_video = title_video.write_or_recover();
+            if !self.finished_title_video_ids.contains(&title_video.id) {
+                title_video.skip_upload = skip_upload;
+            }
+        }
+    }
+
     // Removes the title video matching the given title from the job.
     // If the job is currently processing, returns an error instead of modifying the job.
     // If the removed title video was the only one in the job, resets the job status to Pending.
@@ -192,13 +255,92 @@ impl Job {
     pub fn remove_title_video(&mut self, title: &TitleInfo) -> Result<(), StandardError> {
         self.validate_title_video_modifiable("remove")?;
         self.title_videos
-            .retain(|tv| tv.read().unwrap().title.as_ref().map(|t| t.id) != Some(title.id));
+            .retain(|tv| tv.read_or_recover().title.as_ref().map(|t| t.id) != Some(title.id));
         if self.title_videos.is_empty() {
             self.status = JobStatus::Pending;
         }
         Ok(())
     }
 
+    /// Drop the assigned episodes of `season_number` that are already in the library,
+    /// returns one label per dropped title for the job notice.
+    pub fn skip_owned_episodes(&mut self, season_number: u32, owned: &HashSet<u32>) -> Vec<String> {
+        let mut skipped = Vec::new();
+        self.title_videos.retain(|title_video| {
+            let title_video = title_video.read_or_recover();
+            match &title_video.video {
+                Video::Tv(tv_season_episode)
+                    if tv_season_episode.season.season_number == season_number
+                        && owned.contains(&tv_season_episode.episode.episode_number) =>
+                {
+                    skipped.push(format!(
+                        "S{:02}E{:02} {}",
+                        season_number,
+                        tv_season_episode.episode.episode_number,
+                        tv_season_episode.episode.name
+                    ));
+                    false
+                }
+                _ => true,
+            }
+        });
+        skipped
+    }
+
+    /// Everything about the assigned episodes that doesn't line up with the disc titles, an
+    /// episode without a title, a title picked for more than one episode or a missing part.
+    /// Empty when the season can be ripped as assigned.
+    pub fn season_assignment_problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut episodes_by_title: BTreeMap<TitleId, Vec<String>> = BTreeMap::new();
+        let mut parts_by_episode: BTreeMap<(u32, u32), (String, Vec<u16>)> = BTreeMap::new();
+        for title_video in &self.title_videos {
+            let title_video = title_video.read_or_recover();
+            let Video::Tv(tv_season_episode) = &title_video.video else {
+                continue;
+            };
+            let episode = &tv_season_episode.episode;
+            let label = format!(
+                "S{:02}E{:02} {}",
+                tv_season_episode.season.season_number, episode.episode_number, episode.name
+            );
+            match &title_video.title {
+                Some(title) => episodes_by_title
+                    .entry(title.id)
+                    .or_default()
+                    .push(label.clone()),
+                None => problems.push(format!("{label} has no disc title")),
+            }
+            parts_by_episode
+                .entry((
+                    tv_season_episode.season.season_number,
+                    episode.episode_number,
+                ))
+                .or_insert_with(|| (label, Vec::new()))
+                .1
+                .push(tv_season_episode.part);
+        }
+        for (title_id, episodes) in &episodes_by_title {
+            if episodes.len() > 1 {
+                problems.push(format!(
+                    "Title {title_id} is assigned to {}",
+                    episodes.join(" and ")
+                ));
+            }
+        }
+        for (label, parts) in parts_by_episode.values() {
+            let last = parts.iter().copied().max().unwrap_or(1);
+            let missing: Vec<String> = (1..=last)
+                .filter(|part| !parts.contains(part))
+                .map(|part| part.to_string())
+                .collect();
+            if !missing.is_empty() {
+                problems.push(format!("{label} is missing part {}", missing.join(", ")));
+            }
+        }
+        problems
+    }
+
     // pub fn clear_title_videos(&mut self) -> Result<(), StandardError> {
     //     self.validate_title_video_modifiable("clear")?;
     //     self.title_videos.clear();
@@ -226,10 +368,16 @@ impl Job {
     /// - The tracker should be updated externally as the operation proceeds.
     pub fn update_progress(&mut self, tracker: &progress_tracker::Base) {
         let percent = tracker.percentage_component.percentage();
-        self.progress = JobProgress {
-            eta: tracker.time_component.estimated(None),
-            percent,
+        let eta = match self.expected_seconds {
+            Some(expected_seconds) => calibrated_eta(
+                expected_seconds,
+                tracker.time_component.estimated_seconds_remaining(),
+                percent,
+            ),
+            None => tracker.time_component.estimated(),
         };
+        let percent = combined_percent(percent, self.joined_part);
+        self.progress = JobProgress { eta, percent };
     }
 
     /// Emits a progress change event for THIS JOB ONLY to the frontend UI.
@@ -256,18 +404,131 @@ impl Job {
             .expect("Failed to emit job-changed");
     }
 
-    pub fn rate_limited_emit_progress_change(&mut self, app_handle: &tauri::AppHandle) {
-        let now = SystemTime::now();
-        if let Ok(duration) = now.duration_since(self.last_emit) {
-            if duration >= Duration::from_secs(1) {
-                self.emit_progress_change(app_handle);
-                self.last_emit = now;
+    pub fn update_message(&mut self, message: &str) {
+        self.message = Some(message.to_string());
+    }
+
+    pub fn add_notice(&mut self, notice: String) {
+        self.notices.push(notice);
+    }
+
+    pub fn depend_on(&mut self, job_id: JobId) {
+        if !self.depends_on.contains(&job_id) {
+            self.depends_on.push(job_id);
+        }
+    }
+
+    /// Takes the status and progress of a season job from its children, the progress is the
+    /// average of their `overall_progress_percent`.
+    pub fn aggregate(&mut self, children: &[Job]) {
+        if children.is_empty() {
+            return;
+        }
+        let done = children.iter().filter(|child| child.is_completed()).count();
+        let status = if done == children.len() {
+            if children.iter().any(Job::is_error) {
+                JobStatus::Error
+            } else {
+                JobStatus::Finished
             }
+        } else if done > 0 || children.iter().any(Job::is_processing) {
+            JobStatus::Processing
+        } else {
+            JobStatus::Pending
+        };
+        self.update_status(status);
+        if !self.is_completed() {
+            let total: f64 = children.iter().map(Job::overall_progress_percent).sum();
+            self.progress.percent = (total / children.len() as f64) as f32;
         }
+        self.subtitle = Some(format!("{done} of {} episodes done", children.len()));
     }
 
-    pub fn update_message(&mut self, message: &str) {
-        self.message = Some(message.to_string());
+    pub fn finish_title_video(&mut self, title_video_id: crate::state::title_video::TitleVideoId) {
+        self.finished_title_video_ids.push(title_video_id);
+    }
+
+    /// True when this job is done with every title in `title_video_ids`.
+    ///
+    /// A finished job is done with all of them, without titles to share only finishing counts.
+    pub fn has_finished_title_videos(
+        &self,
+        title_video_ids: &[crate::state::title_video::TitleVideoId],
+    ) -> bool {
+        if self.is_finished() {
+            return true;
+        }
+        !title_video_ids.is_empty()
+            && title_video_ids
+                .iter()
+                .all(|id| self.finished_title_video_ids.contains(id))
+    }
+
+    /// Only the upload step can be run again on its own, the ripped file is still on disk.
+    pub fn is_retryable(&self) -> bool {
+        self.is_error() && self.job_type == JobType::Uploading
+    }
+
+    /// Archiving can be stopped until it is done, see `archive::CANCELLED_MESSAGE`.
+    pub fn is_cancellable(&self) -> bool {
+        !self.is_completed() && self.job_type == JobType::Archiving
+    }
+
+    /// A finished upload can be sent again, e.g. after the server lost it.
+    pub fn is_requeueable(&self) -> bool {
+        self.is_finished() && self.job_type == JobType::Uploading
+    }
+
+    /// The titles a rip didn't get done, the ones that failed or weren't reached.
+    pub fn failed_title_videos(&self) -> Vec<Arc<RwLock<TitleVideo>>> {
+        self.title_videos
+            .iter()
+            .filter(|title_video| {
+                !self
+                    .finished_title_video_ids
+                    .contains(&title_video.read_or_recover().id)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// A rip that failed some of its titles can rip just those again.
+    pub fn has_failed_titles(&self) -> bool {
+        self.is_error()
+            && self.job_type == JobType::Ripping
+            && !self.failed_title_videos().is_empty()
+    }
+
+    /// The movies a rip finished, what "Fix Match" renames when TMDB got the wrong movie.
+    pub fn rematchable_title_videos(&self) -> Vec<Arc<RwLock<TitleVideo>>> {
+        if self.job_type != JobType::Ripping || !self.is_completed() {
+            return Vec::new();
+        }
+        self.title_videos
+            .iter()
+            .filter(|title_video| {
+                let title_video = title_video.read_or_recover();
+                matches!(title_video.video, Video::Movie(_))
+                    && self.finished_title_video_ids.contains(&title_video.id)
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_rematchable(&self) -> bool {
+        !self.rematchable_title_videos().is_empty()
+    }
+
+    /// Back to Pending so the scheduler can pick it up again
+    pub fn reset_for_retry(&mut self) {
+        self.status = JobStatus::Pending;
+        self.disc_removed = false;
+        self.message = None;
+        self.subtitle = None;
+        self.progress = JobProgress {
+            eta: TimeComponent::NO_TIME_ELAPSED_TEXT.to_string(),
+            percent: 0.0,
+        };
     }
 
     // Replace the job's title with the title from the given TitleVideo, if it has one.
@@ -275,6 +536,7 @@ impl Job {
         let title = match title_video.video {
             Video::Movie(ref movie) => Some(movie.movie.title_year()),
             Video::Tv(ref tv) => Some(tv.title()),
+            Video::Personal(ref personal) => Some(personal.title()),
         };
         self.title = title;
         self
@@ -334,6 +596,10 @@ impl Job {
         self.status == JobStatus::Processing
     }
 
+    pub fn is_awaiting_review(&self) -> bool {
+        self.status == JobStatus::AwaitingReview
+    }
+
     pub fn is_finished(&self) -> bool {
         self.status == JobStatus::Finished
     }
@@ -363,6 +629,14 @@ impl Job {
             .map(|index| index + 1)
     }
 
+    pub fn current_title_video(&self) -> Option<Arc<RwLock<TitleVideo>>> {
+        let current_id = self.current_title_video_id?;
+        self.title_videos
+            .iter()
+            .find(|title_video| title_video.read_or_recover().id == current_id)
+            .cloned()
+    }
+
     pub fn completed_titles_count(&self) -> usize {
         let total = self.total_titles_count();
         if total == 0 {
@@ -383,6 +657,10 @@ impl Job {
     }
 
     pub fn overall_progress_percent(&self) -> f64 {
+        if self.job_type == JobType::Season {
+            // Already combined from the children by `aggregate`
+            return self.progress.percent as f64;
+        }
         let total = self.total_titles_count();
         if total == 0 {
             return 0.0;
@@ -424,6 +702,22 @@ impl Job {
     }
 }
 
+/// Frames grabbed from a ripped title so the user can check the right title was picked.
+#[derive(Serialize, Clone)]
+pub struct TitlePreview {
+    pub title: String,
+    /// `data:` URIs, ready for an `<img src>`
+    pub images: Vec<String>,
+}
+
+/// The progress of the whole video while one of its joined titles rips.
+fn combined_percent(percent: f32, joined_part: Option<(usize, usize)>) -> f32 {
+    match joined_part {
+        Some((index, count)) if count > 1 => (index as f32 * 100.0 + percent) / count as f32,
+        _ => percent,
+    }
+}
+
 #[derive(Serialize, Clone)]
 pub struct JobProgress {
     pub percent: f32,
@@ -446,6 +740,8 @@ pub enum JobStatus {
     #[default]
     Pending,
     Processing,
+    /// A finished rip waits for an approval before it uploads, see `upload_review`
+    AwaitingReview,
     Finished,
     Error,
 }
@@ -455,17 +751,21 @@ impl fmt::Display for JobStatus {
         match self {
             JobStatus::Pending => write!(f, "Pending"),
             JobStatus::Processing => write!(f, "Processing"),
+            JobStatus::AwaitingReview => write!(f, "Awaiting review"),
             JobStatus::Finished => write!(f, "Finished"),
             JobStatus::Error => write!(f, "Error"),
         }
     }
 }
 
-#[derive(Serialize, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum JobType {
     Loading,
     Ripping,
     Uploading,
+    Archiving,
+    /// Groups the upload jobs of a season's episodes into one row
+    Season,
 }
 
 impl fmt::Display for JobType {
@@ -474,13 +774,15 @@ impl fmt::Display for JobType {
             JobType::Loading => write!(f, "Loading"),
             JobType::Ripping => write!(f, "Ripping"),
             JobType::Uploading => write!(f, "Uploading"),
+            JobType::Archiving => write!(f, "Archiving"),
+            JobType::Season => write!(f, "Season"),
         }
     }
 }
 
 static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
 
-#[derive(Serialize, Clone, PartialEq, Eq, Copy, PartialOrd, Ord)]
+#[derive(Serialize, Clone, PartialEq, Eq, Copy, PartialOrd, Ord, Debug)]
 pub struct JobId(u64);
 
 impl JobId {
@@ -489,21 +791,60 @@ impl JobId {
     }
 }
 
+impl From<u64> for JobId {
+    fn from(id: u64) -> Self {
+        JobId(id)
+    }
+}
+
+impl From<JobId> for u64 {
+    fn from(id: JobId) -> Self {
+        id.0
+    }
+}
+
 impl fmt::Display for JobId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+/// Progress after which makemkvcon's own ETA is trusted on its own.
+const CALIBRATION_PERCENT: f32 = 10.0;
+
+/// What is left of `expected_seconds` at `percent`, in the tracker's `HH:MM:SS` format.
+///
+/// The first minutes of a rip make the measured ETA jump around, it is blended with the
+/// historical one and takes over more the further the rip gets.
+fn calibrated_eta(expected_seconds: u64, measured_seconds: Option<u64>, percent: f32) -> String {
+    let percent = percent.clamp(0.0, 100.0);
+    let historical = expected_seconds as f64 * (1.0 - f64::from(percent) / 100.0);
+    let left = match measured_seconds {
+        Some(measured) => {
+            let weight = f64::from((percent / CALIBRATION_PERCENT).min(1.0));
+            measured as f64 * weight + historical * (1.0 - weight)
+        }
+        None => historical,
+    };
+    let (hours, minutes, seconds) = progress_tracker::Timer::divide_seconds(left.round() as u64);
+    format!("{hours:02}:{minutes:02}:{seconds:02}")
+}
+
+/// Emits the job right away when `now`, otherwise with the next batch of changed jobs.
+/// The season job the job belongs to follows along.
 pub fn emit_progress(app_handle: &AppHandle, job: &Arc<RwLock<Job>>, now: bool) {
-    if now {
-        job.write()
-            .expect("failed to lock job for write")
-            .emit_progress_change(app_handle);
-    } else {
-        job.write()
-            .expect("failed to lock job for write")
-            .rate_limited_emit_progress_change(app_handle);
+    let background_process_state =
+        app_handle.state::<crate::state::background_process_state::BackgroundProcessState>();
+    let parent_id = job.read_or_recover().parent_id;
+    let parent = parent_id.and_then(|parent_id| background_process_state.refresh_parent(parent_id));
+    for job in [Some(job), parent.as_ref()].into_iter().flatten() {
+        if now {
+            job.read_or_recover().emit_progress_change(app_handle);
+        } else {
+            background_process_state
+                .emits
+                .queue(job.read_or_recover().id);
+        }
     }
 }
 
@@ -555,13 +896,13 @@ mod tests {
             air_date: Some("2020-01-01".to_string()),
             episode_number,
             episode_type: "standard".to_string(),
-            id: episode_number,
+            id: EpisodeId::from(episode_number),
             name: format!("Episode {episode_number}"),
             overview: "Test episode".to_string(),
             production_code: None,
             runtime: Some(45),
             season_number,
-            show_id,
+            show_id: TvId::from(show_id),
             still_path: None,
             vote_average: 7.0,
             vote_count: 10,
@@ -581,7 +922,7 @@ mod tests {
             episodes,
             name: format!("Season {season_number}"),
             overview: "Test season".to_string(),
-            id: season_id,
+            id: SeasonId::from(season_id),
             poster_path: None,
             season_number,
             vote_average: 8.0,
@@ -608,6 +949,10 @@ mod tests {
                 tv,
                 part,
             })),
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
         }))
     }
 
@@ -637,6 +982,10 @@ mod tests {
                 part: None,
                 edition: None,
             })),
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
         }))
     }
 
@@ -652,7 +1001,8 @@ mod tests {
             different_episode,
         ]);
 
-        let parts = job.select_tv_title_video_parts(TvId::from(100), 1, 1);
+        let parts =
+            job.select_tv_title_video_parts(TvId::from(100), SeasonId::from(1), EpisodeId::from(1));
 
         assert_eq!(parts.len(), 2);
         assert!(parts.iter().any(|p| Arc::ptr_eq(p, &match_part_1)));
@@ -675,7 +1025,8 @@ mod tests {
             movie,
         ]);
 
-        let parts = job.select_tv_title_video_parts(TvId::from(100), 1, 1);
+        let parts =
+            job.select_tv_title_video_parts(TvId::from(100), SeasonId::from(1), EpisodeId::from(1));
 
         assert_eq!(parts.len(), 1);
         assert!(Arc::ptr_eq(&parts[0], &matching_tv));
@@ -685,11 +1036,42 @@ mod tests {
     fn select_tv_title_video_parts_returns_empty_when_no_matches_exist() {
         let job = Job::new(JobType::Ripping, None, JobStatus::Pending).with_title_videos(vec![]);
 
-        let parts = job.select_tv_title_video_parts(TvId::from(100), 1, 1);
+        let parts =
+            job.select_tv_title_video_parts(TvId::from(100), SeasonId::from(1), EpisodeId::from(1));
 
         assert!(parts.is_empty());
     }
 
+    #[test]
+    fn set_library_leaves_finished_titles_alone() {
+        let finished = create_tv_title_video(100, 1, 1, 1, 1);
+        let pending = create_tv_title_video(100, 1, 1, 2, 1);
+
+        let mut job = Job::new(JobType::Ripping, None, JobStatus::Pending)
+            .with_title_videos(vec![finished.clone(), pending.clone()]);
+        job.finished_title_video_ids
+            .push(finished.read().unwrap().id);
+        job.set_library(Some("Kids".to_string()));
+
+        assert_eq!(finished.read().unwrap().library, None);
+        assert_eq!(pending.read().unwrap().library.as_deref(), Some("Kids"));
+    }
+
+    #[test]
+    fn set_skip_upload_leaves_finished_titles_alone() {
+        let finished = create_tv_title_video(100, 1, 1, 1, 1);
+        let pending = create_tv_title_video(100, 1, 1, 2, 1);
+
+        let mut job = Job::new(JobType::Ripping, None, JobStatus::Pending)
+            .with_title_videos(vec![finished.clone(), pending.clone()]);
+        job.finished_title_video_ids
+            .push(finished.read().unwrap().id);
+        job.set_skip_upload(true);
+
+        assert!(!finished.read().unwrap().skip_upload);
+        assert!(pending.read().unwrap().skip_upload);
+    }
+
     #[test]
     fn has_multiple_parts_returns_true_when_episode_has_multiple_parts() {
         let part1 = create_tv_title_video(100, 1, 1, 1, 1);
@@ -712,4 +1094,179 @@ mod tests {
         assert!(!job.has_multiple_parts(&single.read().unwrap()));
         assert!(!job.has_multiple_parts(&different_episode.read().unwrap()));
     }
+
+    #[test]
+    fn has_finished_title_videos_per_title() {
+        let first = create_movie_title_video(1);
+        let second = create_movie_title_video(2);
+        let first_id = first.read().unwrap().id;
+        let second_id = second.read().unwrap().id;
+        let mut job = Job::new(JobType::Ripping, None, JobStatus::Processing)
+            .with_title_videos(vec![first, second]);
+
+        assert!(!job.has_finished_title_videos(&[first_id]));
+        job.finish_title_video(first_id);
+        assert!(job.has_finished_title_videos(&[first_id]));
+        assert!(!job.has_finished_title_videos(&[first_id, second_id]));
+        assert!(!job.has_finished_title_videos(&[]));
+
+        job.update_status(JobStatus::Finished);
+        assert!(job.has_finished_title_videos(&[first_id, second_id]));
+        assert!(job.has_finished_title_videos(&[]));
+    }
+
+    #[test]
+    fn only_failed_uploads_are_retryable() {
+        let mut upload = Job::new(JobType::Uploading, None, JobStatus::Error);
+        upload.message = Some("Connection refused".to_string());
+        assert!(upload.is_retryable());
+
+        upload.reset_for_retry();
+        assert!(upload.is_pending());
+        assert_eq!(upload.message, None);
+        assert!(!upload.is_retryable());
+
+        let rip = Job::new(JobType::Ripping, None, JobStatus::Error);
+        assert!(!rip.is_retryable());
+    }
+
+    #[test]
+    fn calibrated_eta_hands_over_to_the_measured_one() {
+        assert_eq!(calibrated_eta(3_600, None, 0.0), "01:00:00");
+        assert_eq!(calibrated_eta(3_600, None, 25.0), "00:45:00");
+        assert_eq!(calibrated_eta(3_600, None, 120.0), "00:00:00");
+        // Halfway through the calibration both count the same
+        assert_eq!(calibrated_eta(4_000, Some(600), 5.0), "00:36:40");
+        assert_eq!(calibrated_eta(3_600, Some(600), 50.0), "00:10:00");
+    }
+
+    #[test]
+    fn only_finished_uploads_are_requeueable() {
+        let upload = Job::new(JobType::Uploading, None, JobStatus::Finished);
+        assert!(upload.is_requeueable());
+        assert!(!upload.is_retryable());
+
+        let failed = Job::new(JobType::Uploading, None, JobStatus::Error);
+        assert!(!failed.is_requeueable());
+
+        let rip = Job::new(JobType::Ripping, None, JobStatus::Finished);
+        assert!(!rip.is_requeueable());
+    }
+
+    #[test]
+    fn skips_episodes_already_in_the_library() {
+        let mut job = Job::new(JobType::Ripping, None, JobStatus::Pending).with_title_videos(vec![
+            create_tv_title_video(1, 10, 1, 1, 1),
+            create_tv_title_video(1, 10, 1, 2, 1),
+            create_tv_title_video(1, 20, 2, 1, 1),
+            create_movie_title_video(5),
+        ]);
+
+        let skipped = job.skip_owned_episodes(1, &HashSet::from([1]));
+
+        assert_eq!(skipped, vec!["S01E01 Episode 1".to_string()]);
+        assert_eq!(job.title_videos.len(), 3);
+    }
+
+    #[test]
+    fn reports_episodes_that_do_not_line_up_with_disc_titles() {
+        let with_title = |video: Arc<RwLock<TitleVideo>>, title_id: u32| {
+            video.write().unwrap().title = Some(TitleInfo::new(title_id));
+            video
+        };
+        let lined_up =
+            Job::new(JobType::Ripping, None, JobStatus::Pending).with_title_videos(vec![
+                with_title(create_tv_title_video(1, 10, 1, 1, 1), 0),
+                with_title(create_tv_title_video(1, 10, 1, 2, 1), 1),
+                with_title(create_tv_title_video(1, 10, 1, 2, 2), 2),
+            ]);
+        assert!(lined_up.season_assignment_problems().is_empty());
+
+        let job = Job::new(JobType::Ripping, None, JobStatus::Pending).with_title_videos(vec![
+            with_title(create_tv_title_video(1, 10, 1, 1, 1), 0),
+            with_title(create_tv_title_video(1, 10, 1, 2, 1), 0),
+            create_tv_title_video(1, 10, 1, 3, 1),
+            with_title(create_tv_title_video(1, 10, 1, 4, 2), 3),
+        ]);
+        assert_eq!(
+            job.season_assignment_problems(),
+            vec![
+                "S01E03 Episode 3 has no disc title".to_string(),
+                "Title 0 is assigned to S01E01 Episode 1 and S01E02 Episode 2".to_string(),
+                "S01E04 Episode 4 is missing part 1".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_titles_are_the_ones_not_finished() {
+        let title_videos = vec![
+            create_tv_title_video(1, 10, 1, 1, 1),
+            create_tv_title_video(1, 10, 1, 2, 1),
+            create_tv_title_video(1, 10, 1, 3, 1),
+        ];
+        let mut job = Job::new(JobType::Ripping, None, JobStatus::Processing)
+            .with_title_videos(title_videos.clone());
+        job.finish_title_video(title_videos[0].read_or_recover().id);
+        job.finish_title_video(title_videos[2].read_or_recover().id);
+        assert!(!job.has_failed_titles());
+
+        job.update_status(JobStatus::Error);
+        assert!(job.has_failed_titles());
+        let failed = job.failed_title_videos();
+        assert_eq!(failed.len(), 1);
+        assert!(Arc::ptr_eq(&failed[0], &title_videos[1]));
+    }
+
+    #[test]
+    fn only_finished_movies_of_a_done_rip_can_be_rematched() {
+        let title_videos = vec![
+            create_movie_title_video(1),
+            create_movie_title_video(1),
+            create_tv_title_video(1, 10, 1, 1, 1),
+        ];
+        let mut job = Job::new(JobType::Ripping, None, JobStatus::Processing)
+            .with_title_videos(title_videos.clone());
+        for title_video in [&title_videos[0], &title_videos[2]] {
+            job.finish_title_video(title_video.read_or_recover().id);
+        }
+        assert!(!job.is_rematchable());
+
+        job.update_status(JobStatus::Finished);
+        let rematchable = job.rematchable_title_videos();
+        assert_eq!(rematchable.len(), 1);
+        assert!(Arc::ptr_eq(&rematchable[0], &title_videos[0]));
+    }
+
+    #[test]
+    fn joined_titles_share_the_progress() {
+        assert_eq!(combined_percent(40.0, None), 40.0);
+        assert_eq!(combined_percent(40.0, Some((0, 2))), 20.0);
+        assert_eq!(combined_percent(40.0, Some((1, 2))), 70.0);
+    }
+
+    #[test]
+    fn season_job_combines_the_progress_of_its_children() {
+        let upload = |status, percent| {
+            let mut job = Job::new(JobType::Uploading, None, status)
+                .with_title_videos(vec![create_tv_title_video(1, 10, 1, 1, 1)]);
+            job.progress.percent = percent;
+            job
+        };
+        let mut season = Job::new(JobType::Season, None, JobStatus::Pending);
+
+        season.aggregate(&[
+            upload(JobStatus::Finished, 100.0),
+            upload(JobStatus::Processing, 50.0),
+        ]);
+        assert!(season.is_processing());
+        assert_eq!(season.overall_progress_percent(), 75.0);
+        assert_eq!(season.subtitle.as_deref(), Some("1 of 2 episodes done"));
+
+        season.aggregate(&[
+            upload(JobStatus::Finished, 100.0),
+            upload(JobStatus::Error, 50.0),
+        ]);
+        assert!(season.is_error());
+    }
 }
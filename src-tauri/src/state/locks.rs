@@ -0,0 +1,75 @@
+use log::warn;
+use std::any::type_name;
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A thread that panics while holding a lock poisons it, after that every `.expect()` on the
+/// lock panics too and takes the whole app down. The data behind our locks is plain state
+/// (settings, jobs, disks) that is still usable, so these helpers log it and carry on.
+fn recover<G>(poisoned: PoisonError<G>, lock: &str) -> G {
+    warn!("Recovered a poisoned {lock}, a thread panicked while holding it");
+    poisoned.into_inner()
+}
+
+pub trait MutexExt<T> {
+    fn lock_or_recover(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> MutexExt<T> for Mutex<T> {
+    fn lock_or_recover(&self) -> MutexGuard<'_, T> {
+        self.lock()
+            .unwrap_or_else(|e| recover(e, type_name::<Mutex<T>>()))
+    }
+}
+
+pub trait RwLockExt<T> {
+    fn read_or_recover(&self) -> RwLockReadGuard<'_, T>;
+    fn write_or_recover(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T> RwLockExt<T> for RwLock<T> {
+    fn read_or_recover(&self) -> RwLockReadGuard<'_, T> {
+        self.read()
+            .unwrap_or_else(|e| recover(e, type_name::<RwLock<T>>()))
+    }
+
+    fn write_or_recover(&self) -> RwLockWriteGuard<'_, T> {
+        self.write()
+            .unwrap_or_else(|e| recover(e, type_name::<RwLock<T>>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn recovers_a_poisoned_mutex() {
+        let lock = Arc::new(Mutex::new(1));
+        let poisoner = lock.clone();
+        let _ = std::thread::spawn(move || {
+            let mut value = poisoner.lock().unwrap();
+            *value = 2;
+            panic!("poison the lock");
+        })
+        .join();
+
+        assert!(lock.is_poisoned());
+        assert_eq!(*lock.lock_or_recover(), 2);
+    }
+
+    #[test]
+    fn recovers_a_poisoned_rw_lock() {
+        let lock = Arc::new(RwLock::new(vec![1]));
+        let poisoner = lock.clone();
+        let _ = std::thread::spawn(move || {
+            poisoner.write().unwrap().push(2);
+            let _guard = poisoner.write();
+            panic!("poison the lock");
+        })
+        .join();
+
+        lock.write_or_recover().push(3);
+        assert_eq!(*lock.read_or_recover(), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::state::locks::MutexExt;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// The kinds of work that compete for the drive, the CPU or the network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Slot {
+    Rip,
+    PostProcess,
+    Upload,
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Slot::Rip => write!(f, "rip"),
+            Slot::PostProcess => write!(f, "post-process"),
+            Slot::Upload => write!(f, "upload"),
+        }
+    }
+}
+
+/// How many jobs of each kind may run at the same time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConcurrencyLimits {
+    pub rips: usize,
+    pub post_process: usize,
+    pub uploads: usize,
+}
+
+impl Default for ConcurrencyLimits {
+    fn default() -> Self {
+        ConcurrencyLimits {
+            rips: 1,
+            post_process: 1,
+            uploads: 2,
+        }
+    }
+}
+
+impl ConcurrencyLimits {
+    /// A limit of 0 would stall the queue forever, it is treated as 1.
+    pub fn limit(&self, slot: Slot) -> usize {
+        let limit = match slot {
+            Slot::Rip => self.rips,
+            Slot::PostProcess => self.post_process,
+            Slot::Upload => self.uploads,
+        };
+        limit.max(1)
+    }
+}
+
+struct Slots {
+    limits: ConcurrencyLimits,
+    running: HashMap<Slot, usize>,
+}
+
+/// Hands out slots so no more than `ConcurrencyLimits` jobs of a kind run at once.
+///
+/// Jobs waiting on a slot stay Pending, the slot is given back when the
+/// `SlotPermit` is dropped.
+pub struct ResourceGovernor {
+    slots: Mutex<Slots>,
+    slot_freed: Notify,
+}
+
+impl ResourceGovernor {
+    pub fn new(limits: ConcurrencyLimits) -> Self {
+        ResourceGovernor {
+            slots: Mutex::new(Slots {
+                limits,
+                running: HashMap::new(),
+            }),
+            slot_freed: Notify::new(),
+        }
+    }
+
+    /// Raising a limit starts queued jobs right away, lowering it lets the running ones finish.
+    pub fn set_limits(&self, limits: ConcurrencyLimits) {
+        self.slots.lock_or_recover().limits = limits;
+        self.slot_freed.notify_waiters();
+    }
+
+    pub fn try_acquire(self: &Arc<Self>, slot: Slot) -> Option<SlotPermit> {
+        let mut slots = self.slots.lock_or_recover();
+        let limit = slots.limits.limit(slot);
+        let running = slots.running.entry(slot).or_insert(0);
+        if *running >= limit {
+            return None;
+        }
+        *running += 1;
+        Some(SlotPermit {
+            governor: self.clone(),
+            slot,
+        })
+    }
+
+    /// Wait until a slot is free.
+    pub async fn acquire(self: &Arc<Self>, slot: Slot) -> SlotPermit {
+        loop {
+            // Created before checking so a slot freed in between still wakes us up
+            let slot_freed = self.slot_freed.notified();
+            if let Some(permit) = self.try_acquire(slot) {
+                return permit;
+            }
+            slot_freed.await;
+        }
+    }
+
+    fn release(&self, slot: Slot) {
+        {
+            let mut slots = self.slots.lock_or_recover();
+            if let Some(running) = slots.running.get_mut(&slot) {
+                *running = running.saturating_sub(1);
+            }
+        }
+        self.slot_freed.notify_waiters();
+    }
+}
+
+/// A running job of `slot` kind, the slot is freed on drop.
+pub struct SlotPermit {
+    governor: Arc<ResourceGovernor>,
+    slot: Slot,
+}
+
+impl Drop for SlotPermit {
+    fn drop(&mut self) {
+        self.governor.release(self.slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn governor() -> Arc<ResourceGovernor> {
+        Arc::new(ResourceGovernor::new(ConcurrencyLimits::default()))
+    }
+
+    impl ResourceGovernor {
+        fn running(&self, slot: Slot) -> usize {
+            let slots = self.slots.lock().expect("failed to lock governor slots");
+            slots.running.get(&slot).copied().unwrap_or(0)
+        }
+    }
+
+    #[test]
+    fn limits_each_kind_separately() {
+        let governor = governor();
+        let first_upload = governor.try_acquire(Slot::Upload);
+        let second_upload = governor.try_acquire(Slot::Upload);
+        assert!(first_upload.is_some());
+        assert!(second_upload.is_some());
+        assert!(governor.try_acquire(Slot::Upload).is_none());
+
+        let rip = governor.try_acquire(Slot::Rip);
+        assert!(rip.is_some());
+        assert!(governor.try_acquire(Slot::Rip).is_none());
+        assert_eq!(governor.running(Slot::Upload), 2);
+        assert_eq!(governor.running(Slot::Rip), 1);
+    }
+
+    #[test]
+    fn dropping_the_permit_frees_the_slot() {
+        let governor = governor();
+        let rip = governor.try_acquire(Slot::Rip);
+        assert!(governor.try_acquire(Slot::Rip).is_none());
+
+        drop(rip);
+        assert_eq!(governor.running(Slot::Rip), 0);
+        assert!(governor.try_acquire(Slot::Rip).is_some());
+    }
+
+    #[test]
+    fn zero_limit_still_runs_one() {
+        let governor = Arc::new(ResourceGovernor::new(ConcurrencyLimits {
+            rips: 0,
+            post_process: 0,
+            uploads: 0,
+        }));
+        let _rip = governor.try_acquire(Slot::Rip).expect("one rip slot");
+        assert!(governor.try_acquire(Slot::Rip).is_none());
+    }
+
+    #[tokio::test]
+    async fn queued_job_starts_when_a_slot_frees() {
+        let governor = governor();
+        let rip = governor.try_acquire(Slot::Rip).expect("rip slot");
+
+        let waiting = {
+            let governor = governor.clone();
+            tokio::spawn(async move { governor.acquire(Slot::Rip).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        drop(rip);
+        let permit = tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .expect("queued rip never started")
+            .expect("acquire task panicked");
+        assert_eq!(governor.running(Slot::Rip), 1);
+        drop(permit);
+    }
+
+    #[tokio::test]
+    async fn raising_the_limit_starts_queued_jobs() {
+        let governor = governor();
+        let _rip = governor.try_acquire(Slot::Rip).expect("rip slot");
+
+        let waiting = {
+            let governor = governor.clone();
+            tokio::spawn(async move { governor.acquire(Slot::Rip).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        governor.set_limits(ConcurrencyLimits {
+            rips: 2,
+            ..ConcurrencyLimits::default()
+        });
+
+        let _second = tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .expect("queued rip never started")
+            .expect("acquire task panicked");
+        assert_eq!(governor.running(Slot::Rip), 2);
+    }
+}
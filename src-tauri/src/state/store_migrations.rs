@@ -0,0 +1,173 @@
+use log::{info, warn};
+use serde_json::Value;
+use std::sync::Arc;
+use tauri::Runtime;
+use tauri_plugin_store::Store;
+
+/// Key holding the version of the settings in "store.json", it is the only non string value.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// The settings as `AppState::save` writes them today.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// What a migration step needs from the store, `serde_json::Map` stands in for it in tests.
+pub trait SettingsStore {
+    fn keys(&self) -> Vec<String>;
+    fn get(&self, key: &str) -> Option<Value>;
+    fn set(&mut self, key: &str, value: Value);
+    fn delete(&mut self, key: &str);
+}
+
+impl<R: Runtime> SettingsStore for Arc<Store<R>> {
+    fn keys(&self) -> Vec<String> {
+        Store::keys(self)
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        Store::get(self, key)
+    }
+
+    fn set(&mut self, key: &str, value: Value) {
+        Store::set(self, key, value);
+    }
+
+    fn delete(&mut self, key: &str) {
+        Store::delete(self, key);
+    }
+}
+
+/// One step from `version - 1` to `version`.
+///
+/// Settings changes (renamed keys, new value formats) add a step here instead of
+/// changing how `load_from_store` reads the old values, so nothing is silently dropped.
+struct Migration {
+    version: u64,
+    description: &'static str,
+    apply: fn(&mut dyn SettingsStore),
+}
+
+const MIGRATIONS: [Migration; 1] = [Migration {
+    version: 1,
+    description: "store every setting as a string",
+    apply: stringify_values,
+}];
+
+/// Run every step newer than the stored version, returns how many ran.
+///
+/// A store without a version predates versioning and starts at 0, one from a newer
+/// Reelix is left alone.
+pub fn migrate(store: &mut dyn SettingsStore) -> usize {
+    let stored_version = store
+        .get(SCHEMA_VERSION_KEY)
+        .and_then(|value| value.as_u64())
+        .unwrap_or(0);
+    if stored_version > SCHEMA_VERSION {
+        warn!(
+            "Settings are from a newer version (schema {stored_version} > {SCHEMA_VERSION}), not migrating"
+        );
+        return 0;
+    }
+
+    let mut applied = 0;
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > stored_version)
+    {
+        info!(
+            "Migrating settings to schema {}: {}",
+            migration.version, migration.description
+        );
+        (migration.apply)(store);
+        applied += 1;
+    }
+    if applied > 0 {
+        store.set(SCHEMA_VERSION_KEY, Value::from(SCHEMA_VERSION));
+    }
+    applied
+}
+
+/// `load_from_store` only reads strings, a hand edited `true` or `2` used to be ignored.
+fn stringify_values(store: &mut dyn SettingsStore) {
+    for key in store.keys() {
+        if key == SCHEMA_VERSION_KEY {
+            continue;
+        }
+        let stringified = match store.get(&key) {
+            Some(Value::Bool(value)) => value.to_string(),
+            Some(Value::Number(value)) => value.to_string(),
+            Some(Value::Null) => {
+                store.delete(&key);
+                continue;
+            }
+            _ => continue,
+        };
+        store.set(&key, Value::String(stringified));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Map};
+
+    impl SettingsStore for Map<String, Value> {
+        fn keys(&self) -> Vec<String> {
+            Map::keys(self).cloned().collect()
+        }
+
+        fn get(&self, key: &str) -> Option<Value> {
+            Map::get(self, key).cloned()
+        }
+
+        fn set(&mut self, key: &str, value: Value) {
+            self.insert(key.to_string(), value);
+        }
+
+        fn delete(&mut self, key: &str) {
+            self.remove(key);
+        }
+    }
+
+    fn store(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn unversioned_store_is_migrated() {
+        let mut store = store(json!({
+            "ftp_host": "nas.local",
+            "max_concurrent_uploads": 3,
+            "post_process_enabled": true,
+            "ftp_user": null,
+        }));
+
+        assert_eq!(migrate(&mut store), 1);
+        assert_eq!(store["ftp_host"], json!("nas.local"));
+        assert_eq!(store["max_concurrent_uploads"], json!("3"));
+        assert_eq!(store["post_process_enabled"], json!("true"));
+        assert!(!store.contains_key("ftp_user"));
+        assert_eq!(store[SCHEMA_VERSION_KEY], json!(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn current_store_is_left_alone() {
+        let mut store = store(json!({
+            "schema_version": SCHEMA_VERSION,
+            "max_concurrent_uploads": 3,
+        }));
+
+        assert_eq!(migrate(&mut store), 0);
+        assert_eq!(store["max_concurrent_uploads"], json!(3));
+    }
+
+    #[test]
+    fn newer_store_is_left_alone() {
+        let mut store = store(json!({
+            "schema_version": SCHEMA_VERSION + 1,
+            "max_concurrent_uploads": 3,
+        }));
+
+        assert_eq!(migrate(&mut store), 0);
+        assert_eq!(store[SCHEMA_VERSION_KEY], json!(SCHEMA_VERSION + 1));
+    }
+}
@@ -1,12 +1,13 @@
 use crate::{
     models::title_info::TitleInfo,
+    services::{file_move, library::Library, naming::NamingOptions, path_template},
     state::{job_state::Job, AppState},
     the_movie_db::{MovieResponse, SeasonEpisode, SeasonResponse, TvResponse},
 };
 use serde::Serialize;
 use std::{
     fmt, fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::atomic::{AtomicU64, Ordering},
 };
 
@@ -47,11 +48,49 @@ impl MoviePartEdition {
     }
 }
 
+/// A home-burned disc with no TMDB entry, named by hand.
+#[derive(Serialize, Clone)]
+pub struct PersonalVideo {
+    pub name: String,
+    /// Free-form, e.g. `2004` or `2004-07-04`, only the year ends up in the path
+    pub date: Option<String>,
+    pub part: Option<u16>,
+}
+
+impl PersonalVideo {
+    /// The first four digit run of the date, "July 2004" and "2004-07-04" both give 2004.
+    pub fn year(&self) -> Option<u32> {
+        self.date
+            .as_deref()?
+            .split(|c: char| !c.is_ascii_digit())
+            .find(|digits| digits.len() == 4)
+            .and_then(|year| year.parse().ok())
+    }
+
+    /// "Smith Wedding (2004)", the name is typed in so slashes can't make extra folders.
+    pub fn title_year(&self, naming: &NamingOptions) -> String {
+        naming.title_year(&self.name.replace(['/', '\\'], "-"), self.year())
+    }
+
+    pub fn title(&self) -> String {
+        self.title_year(&NamingOptions::default())
+    }
+}
+
 #[derive(Serialize, Clone)]
 pub struct TitleVideo {
     pub id: TitleVideoId,
     pub title: Option<TitleInfo>,
     pub video: Video,
+    /// Library profile picked for the rip, `None` is the main library
+    pub library: Option<String>,
+    /// Where the rip ended up, differs from `video_path` when that name was taken and the
+    /// file got a version suffix
+    pub video_file: Option<PathBuf>,
+    /// Titles ripped after `title` and appended to it, for discs that split a movie in two
+    pub joined_titles: Vec<TitleInfo>,
+    /// Kept in the local library only, the rip is renamed as usual but never uploaded
+    pub skip_upload: bool,
 }
 
 impl TitleVideo {
@@ -63,38 +102,29 @@ impl TitleVideo {
     //     matches!(self.video, Video::Movie(_))
     // }
 
-    /// Move the ripped video file to its final, Plex-compliant location and update the internal path.
+    /// The ripped video file and its final, Plex-compliant location.
     ///
     /// Purpose:
-    /// - After a video is ripped (e.g., from disc), it may be placed in a temporary or generic location.
-    /// - This function renames (moves) the ripped file to its correct, organized destination based on
-    ///   Plex naming conventions for movies or TV episodes.
-    /// - Ensures the file is discoverable by Plex and matches the expected library structure.
-    /// - Updates the `ripped_file` field to reflect the new location.
-    ///
-    /// Why use it?
-    /// - To automate the process of organizing ripped media for Plex or similar media servers.
-    /// - To avoid manual file renaming and moving, reducing human error and ensuring consistency.
-    /// - To prepare files for metadata scanning, artwork matching, and subtitle association.
+    /// - After a video is ripped (e.g., from disc), it is placed under makemkvcon's generic name.
+    /// - This function works out the correct, organized destination based on Plex naming
+    ///   conventions for movies or TV episodes, the caller moves the file there with
+    ///   `file_move::move_file` so the move can show progress when it has to copy.
     ///
     /// How it works:
     /// 1. Checks that a ripped file path is set and that the file exists.
     /// 2. Computes the target path using `video_path`, which generates the correct filename and directory.
-    /// 3. Moves (renames) the file to the target location using `fs::rename`.
-    /// 4. Updates the internal `ripped_file` field to the new path.
-    /// 5. Returns the new path, or an error if the operation fails.
+    /// 3. Returns `(ripped file, target path)`, or an error if the ripped file is missing.
     ///
     /// Examples:
-    /// - Ripped file: `/tmp/rip.mkv` for "Inception (2010)" ->
+    /// - Ripped file: `/Movies/Inception (2010)/title_t00.mkv` for "Inception (2010)" ->
     ///   Moves to `/Movies/Inception (2010)/Inception (2010).mkv`
-    /// - Ripped file: `/tmp/episode.mkv` for "Breaking Bad (2008)" S01E01 ->
+    /// - Ripped file: `/TV Shows/Breaking Bad (2008)/Season 01/title_t00.mkv` for S01E01 ->
     ///   Moves to `/TV Shows/Breaking Bad (2008)/Season 01/Breaking Bad (2008) - S01E01 - Pilot.mkv`
-    ///
-    /// Notes:
-    /// - Will fail if the ripped file does not exist or cannot be moved (e.g., permissions).
-    /// - Does not create parent directories; ensure they exist before calling.
-    /// - Returns a `Result<PathBuf, String>` for error handling in calling code.
-    pub fn rename_ripped_file(&self, app_state: &AppState, job: &Job) -> Result<PathBuf, String> {
+    pub fn ripped_file_move(
+        &self,
+        app_state: &AppState,
+        job: &Job,
+    ) -> Result<(PathBuf, PathBuf), String> {
         let target_path = self.video_path_for_job(app_state, job);
         let from_path = self.ripped_file_path(app_state)?;
 
@@ -104,10 +134,12 @@ impl TitleVideo {
                 from_path.display()
             ));
         }
+        Ok((from_path, target_path))
+    }
 
-        fs::rename(from_path.as_path(), &target_path)
-            .map_err(|e| format!("Failed to rename file: {e}"))?;
-        Ok(target_path)
+    /// Folders, upload paths and templates of the library profile this video goes to.
+    pub fn library(&self, app_state: &AppState) -> Library {
+        app_state.library(self.library.as_deref())
     }
 
     fn video_path_for_job(&self, app_state: &AppState, job: &Job) -> PathBuf {
@@ -123,7 +155,35 @@ impl TitleVideo {
             .filename
             .as_ref()
             .ok_or_else(|| "Filename is missing from title information".to_string())?;
-        Ok(self.create_video_dir(app_state).join(title_filename))
+        Ok(self.video_dir(app_state).join(title_filename))
+    }
+
+    /// Delete what makemkvcon wrote before the rip was interrupted.
+    ///
+    /// The video directory is removed as well when nothing else is in it, which
+    /// keeps an aborted first rip from leaving an empty movie folder behind.
+    /// Returns `true` when a partial file was found.
+    pub fn remove_partial_rip(&self, app_state: &AppState) -> Result<bool, String> {
+        let mut removed = false;
+        for joined_path in self.joined_file_paths(app_state) {
+            removed |= remove_partial_file(&joined_path)?;
+        }
+        Ok(remove_partial_file(&self.ripped_file_path(app_state)?)? || removed)
+    }
+
+    /// Where makemkvcon writes the `joined_titles`, they are appended to the ripped file.
+    pub fn joined_file_paths(&self, app_state: &AppState) -> Vec<PathBuf> {
+        let dir = self.video_dir(app_state);
+        self.joined_titles
+            .iter()
+            .filter_map(|title| title.filename.as_ref())
+            .map(|filename| dir.join(filename))
+            .collect()
+    }
+
+    /// Where makemkvcon writes this title while it rips, `None` before the title is known.
+    pub fn partial_rip_path(&self, app_state: &AppState) -> Option<PathBuf> {
+        self.ripped_file_path(app_state).ok()
     }
 
     /// Get the full FTP upload file path for this video (movie or TV episode).
@@ -136,6 +196,9 @@ impl TitleVideo {
     /// How it works:
     /// - For movies: gets the FTP movie upload directory, then appends the movie filename.
     /// - For TV episodes: gets the FTP TV upload directory, then appends the episode filename.
+    /// - The directory layout under the upload path comes from the remote path template
+    ///   (see `services::path_template`), evaluated per upload so the resolution of the
+    ///   ripped title is available.
     /// - Returns `None` if the FTP upload path is not configured.
     ///
     /// Examples:
@@ -149,14 +212,35 @@ impl TitleVideo {
     /// - Returns `None` if the FTP upload path is missing or not set in config.
     /// - Ensures uploads follow Plex directory and filename conventions for reliable parsing.
     pub fn upload_file_path(&self, app_state: &AppState, multiple_parts: bool) -> Option<PathBuf> {
+        let library = self.library(app_state);
         match &self.video {
-            Video::Movie(movie) => Self::upload_movie_dir(app_state, movie)
-                .map(|dir| dir.join(Self::movie_filename(movie))),
+            Video::Movie(movie) => Self::upload_movie_dir(&library, movie, self.title.as_ref())
+                .map(|dir| {
+                    dir.join(Self::movie_filename(
+                        movie,
+                        &library.naming,
+                        self.title.as_ref(),
+                    ))
+                }),
             Video::Tv(tv_season_episode) => {
-                Self::upload_tv_season_dir(app_state, tv_season_episode).map(|dir| {
-                    dir.join(Self::tv_episode_filename(tv_season_episode, multiple_parts))
-                })
+                Self::upload_tv_season_dir(&library, tv_season_episode, self.title.as_ref()).map(
+                    |dir| {
+                        dir.join(Self::tv_episode_filename(
+                            tv_season_episode,
+                            multiple_parts,
+                            &library.naming,
+                            self.title.as_ref(),
+                        ))
+                    },
+                )
             }
+            Video::Personal(personal) => Self::upload_personal_dir(&library, personal).map(|dir| {
+                dir.join(Self::personal_filename(
+                    personal,
+                    &library.naming,
+                    self.title.as_ref(),
+                ))
+            }),
         }
     }
 
@@ -172,11 +256,13 @@ impl TitleVideo {
     /// - Use this to determine the target directory for FTP uploads or external transfers.
     /// - Does not create the directory; only computes the path.
     pub fn upload_directory(&self, app_state: &AppState) -> Option<PathBuf> {
+        let library = self.library(app_state);
         match &self.video {
-            Video::Movie(movie) => Self::upload_movie_dir(app_state, movie),
+            Video::Movie(movie) => Self::upload_movie_dir(&library, movie, self.title.as_ref()),
             Video::Tv(tv_season_episode) => {
-                Self::upload_tv_season_dir(app_state, tv_season_episode)
+                Self::upload_tv_season_dir(&library, tv_season_episode, self.title.as_ref())
             }
+            Video::Personal(personal) => Self::upload_personal_dir(&library, personal),
         }
     }
 
@@ -220,19 +306,17 @@ impl TitleVideo {
     /// Purpose:
     /// - Primary method called before writing video files to disk to guarantee the parent
     ///   directory structure is in place.
-    /// - Handles both movies and TV episodes with appropriate Plex-compliant folder hierarchies.
+    /// - Handles movies, TV episodes and home videos with their Plex-compliant folder hierarchies.
     /// - Idempotent: safe to call multiple times; won't fail if directories already exist.
     ///
     /// How it works:
-    /// 1. Checks the `Video` enum variant (Movie or Tv) to determine content type.
-    /// 2. For movies: calls `create_movie_dir` which creates `/Movies/Movie Name (Year)/`
-    /// 3. For TV: calls `create_tv_season_episode_dir` which creates
-    ///    `/TV Shows/Show Name (Year)/Season 01/`
-    /// 4. Uses `fs::create_dir_all()` internally, which recursively creates all missing
+    /// 1. Resolves the folder with `video_dir`.
+    /// 2. Creates it with `file_move::create_dir`, which recursively creates all missing
     ///    parent directories in the path without error if they already exist.
     ///
     /// Returns:
-    /// - `PathBuf`: The created (or existing) directory path where the video file should be placed.
+    /// - `Ok(PathBuf)`: The created (or existing) directory path where the video file should be placed.
+    /// - `Err(String)`: Why the folder could not be created, e.g. the network share is not mounted.
     ///
     /// Examples:
     /// - Movie "Inception (2010)" returns and ensures:
@@ -242,23 +326,22 @@ impl TitleVideo {
     ///
     /// Note:
     /// - This creates the directory container, not the video file itself.
-    /// - Panics if directory creation fails (e.g., permission issues).
-    pub fn create_video_dir(&self, app_state: &AppState) -> PathBuf {
+    pub fn create_video_dir(&self, app_state: &AppState) -> Result<PathBuf, String> {
+        let dir = self.video_dir(app_state);
+        file_move::create_dir(&dir)?;
+        Ok(dir)
+    }
+
+    /// The folder this video is ripped into, without creating it.
+    pub fn video_dir(&self, app_state: &AppState) -> PathBuf {
+        let library = self.library(app_state);
         match &self.video {
-            Video::Movie(movie) => Self::create_movie_dir(app_state, movie),
-            Video::Tv(tv_season_episode) => {
-                Self::create_tv_season_episode_dir(app_state, tv_season_episode)
-            }
+            Video::Movie(movie) => Self::movie_dir(&library, &movie.movie),
+            Video::Tv(tv_season_episode) => Self::seasons_episode_dir(&library, tv_season_episode),
+            Video::Personal(personal) => Self::personal_dir(&library, personal),
         }
     }
 
-    // pub fn video_dir(&self, app_state: &AppState) -> PathBuf {
-    //     match &self.video {
-    //         Video::Movie(movie) => Self::movie_dir(app_state, movie),
-    //         Video::Tv(tv_season_episode) => Self::seasons_episode_dir(app_state, tv_season_episode),
-    //     }
-    // }
-
     /// Get the FTP upload directory for a movie, if configured.
     ///
     /// Purpose:
@@ -267,23 +350,25 @@ impl TitleVideo {
     /// - Returns `None` if no FTP path is set.
     ///
     /// How it works:
-    /// 1. Locks and reads the `ftp_movie_upload_path` from `AppState`.
-    /// 2. If set, appends the movie's title and year to form the destination directory.
+    /// 1. Reads the movie upload path of the video's library profile (the main one by default).
+    /// 2. If set, appends the rendered movie path template (defaults to `{title_year}`).
     /// 3. Returns the full path as `Some(PathBuf)`, or `None` if not configured.
     ///
     /// Example:
     /// - FTP path: `/mnt/ftp/Movies`, Movie: "Arrival (2016)" ->
     ///   `/mnt/ftp/Movies/Arrival (2016)/`
+    /// - Same movie with template `{first_letter}/{title} ({year})` ->
+    ///   `/mnt/ftp/Movies/A/Arrival (2016)/`
     ///
     /// Notes:
     /// - Does not create the directory; only computes the path.
     /// - Used for external transfers, not local Plex organization.
-    fn upload_movie_dir(app_state: &AppState, movie: &MoviePartEdition) -> Option<PathBuf> {
-        let ftp_config = app_state.lock_ftp_config();
-        let movies_dir = &ftp_config.movie_upload_path;
-        movies_dir
-            .as_ref()
-            .map(|dir| dir.join(movie.movie.title_year()))
+    fn upload_movie_dir(
+        library: &Library,
+        movie: &MoviePartEdition,
+        title: Option<&TitleInfo>,
+    ) -> Option<PathBuf> {
+        path_template::movie_upload_dir(&library.ftp_config, &library.naming, &movie.movie, title)
     }
 
     /// Get the FTP upload directory for a TV episode, if configured.
@@ -294,8 +379,8 @@ impl TitleVideo {
     /// - Returns `None` if no FTP path is set.
     ///
     /// How it works:
-    /// 1. Locks and reads the `ftp_tv_upload_path` from `AppState`.
-    /// 2. If set, appends the full Plex-compliant episode path (show, season, episode filename).
+    /// 1. Reads the TV upload path of the video's library profile (the main one by default).
+    /// 2. If set, appends the rendered TV path template (defaults to `{show_folder}/Season {season}`).
     /// 3. Returns the full path as `Some(PathBuf)`, or `None` if not configured.
     ///
     /// Example:
@@ -307,38 +392,48 @@ impl TitleVideo {
     /// - Used for external transfers, not local Plex organization.
     /// - Returns the full episode file path, not just the season folder.
     fn upload_tv_season_dir(
-        app_state: &AppState,
+        library: &Library,
         tv_season_episode: &TvSeasonEpisode,
+        title: Option<&TitleInfo>,
     ) -> Option<PathBuf> {
-        let ftp_config = app_state.lock_ftp_config();
-        let tv_shows_dir = &ftp_config.tv_upload_path;
-        tv_shows_dir.as_ref().map(|dir| {
-            dir.join(tv_season_episode.tv.title_year()).join(format!(
-                "Season {:02}",
-                tv_season_episode.season.season_number
-            ))
-        })
+        path_template::tv_upload_season_dir(
+            &library.ftp_config,
+            &library.naming,
+            &tv_season_episode.tv,
+            tv_season_episode.season.season_number,
+            title,
+        )
     }
 
-    fn create_movie_dir(app_state: &AppState, movie: &MoviePartEdition) -> PathBuf {
-        let dir = Self::movie_dir(app_state, &movie.movie);
-        if !dir.exists() {
-            fs::create_dir_all(&dir)
-                .unwrap_or_else(|_| panic!("Failed to create {}", dir.display()));
-        }
-        dir
+    /// `Home Videos/Smith Wedding (2004)/` next to the movie upload path, no path template
+    /// since the templates need TMDB data.
+    fn upload_personal_dir(library: &Library, personal: &PersonalVideo) -> Option<PathBuf> {
+        library
+            .home_videos_upload_path()
+            .map(|dir| dir.join(personal.title_year(&library.naming)))
     }
 
-    fn create_tv_season_episode_dir(
-        app_state: &AppState,
-        tv_season_episode: &TvSeasonEpisode,
-    ) -> PathBuf {
-        let dir = Self::seasons_episode_dir(app_state, tv_season_episode);
-        if !dir.exists() {
-            fs::create_dir_all(&dir)
-                .unwrap_or_else(|_| panic!("Failed to create {}", dir.display()));
+    /// `Home Videos/Smith Wedding (2004)/` next to the movies folder.
+    fn personal_dir(library: &Library, personal: &PersonalVideo) -> PathBuf {
+        file_move::existing_case(
+            &library
+                .home_videos_dir()
+                .join(personal.title_year(&library.naming)),
+        )
+    }
+
+    /// Named like a movie: `Smith Wedding (2004).mkv`, `Smith Wedding (2004)-pt2.mkv`
+    fn personal_filename(
+        personal: &PersonalVideo,
+        naming: &NamingOptions,
+        title: Option<&TitleInfo>,
+    ) -> String {
+        let mut base = personal.title_year(naming);
+        base.push_str(&naming.quality_suffix(title.and_then(TitleInfo::quality_tag).as_deref()));
+        match personal.part {
+            Some(part) => format!("{base}-pt{part}.mkv"),
+            None => format!("{base}.mkv"),
         }
-        dir
     }
 
     /// Resolve the filesystem directory for a movie following Plex's recommended structure.
@@ -350,12 +445,13 @@ impl TitleVideo {
     /// - Provides the directory path where a movie's video file should be stored.
     /// - Each movie gets its own subdirectory under the main movies folder, containing
     ///   the movie file and any associated assets (posters, subtitles, extras, etc.).
-    /// - Used by `movie_path` to construct the complete file path, by `create_movie_dir`
+    /// - Used by `movie_path` to construct the complete file path, by `create_video_dir`
     ///   to ensure the directory exists, and by `upload_directory` for FTP operations.
     ///
     /// Steps:
-    /// 1. Lock and read `movies_dir` from `AppState` (configured base path for all movies).
-    /// 2. Append the movie's title with year: `Movie Name (Year)`.
+    /// 1. Read `movies_dir` of the video's library profile (configured base path for all movies).
+    /// 2. Append the movie's title with year: `Movie Name (Year)`, after applying the
+    ///    configured `NamingOptions` (e.g. `Matrix, The (1999)`).
     /// 3. Return the composed `PathBuf` without filesystem interaction (no creation/validation).
     ///
     /// Examples:
@@ -368,13 +464,13 @@ impl TitleVideo {
     ///
     /// Note:
     /// - This only constructs the path; directory creation is handled separately by
-    ///   `create_movie_dir` when needed.
-    fn movie_dir(app_state: &AppState, movie: &MovieResponse) -> PathBuf {
-        let movies_dir = app_state
-            .movies_dir
-            .read()
-            .expect("failed to lock movies_dir");
-        movies_dir.join(movie.title_year())
+    ///   `create_video_dir` when needed.
+    fn movie_dir(library: &Library, movie: &MovieResponse) -> PathBuf {
+        file_move::existing_case(
+            &library
+                .movies_dir
+                .join(library.naming.title_year(&movie.title, movie.year())),
+        )
     }
 
     /// Resolve the filesystem directory for a specific TV season (used as the parent
@@ -389,10 +485,10 @@ impl TitleVideo {
     /// - Ensures consistent zero-padded season numbering ("Season 01" vs "Season 1") for
     ///   predictable lexical ordering and compatibility with typical Plex scanning patterns.
     /// - Used by `tv_season_episode_path` to append the episode filename, and by
-    ///   `create_tv_season_episode_dir` to ensure the directory exists on disk.
+    ///   `create_video_dir` to ensure the directory exists on disk.
     ///
     /// Steps:
-    /// 1. Lock and read `tv_shows_dir` from `AppState` (base root for all TV content).
+    /// 1. Read `tv_shows_dir` of the video's library profile (base root for all TV content).
     /// 2. Append the show directory using title + year: `Show Name (Year)`.
     /// 3. Append zero-padded season directory: `Season 01`.
     /// 4. Return the composed `PathBuf` without creating it (creation handled elsewhere).
@@ -402,22 +498,32 @@ impl TitleVideo {
     ///   /TV Shows/Example Show (2023)/Season 01/
     /// - Show: "Mystery Saga" (2019), Season: 11 ->
     ///   /TV Shows/Mystery Saga (2019)/Season 11/
+    /// - Show: "Mystery Saga" (2019), Season: 0 (specials) ->
+    ///   /TV Shows/Mystery Saga (2019)/Season 00/
     ///
     /// Note:
     /// - Only path construction occurs here; existence checks/creation are done in
-    ///   `create_tv_season_episode_dir`.
-    fn seasons_episode_dir(app_state: &AppState, tv_season_episode: &TvSeasonEpisode) -> PathBuf {
-        let tv_shows_dir = app_state
+    ///   `create_video_dir`.
+    fn seasons_episode_dir(library: &Library, tv_season_episode: &TvSeasonEpisode) -> PathBuf {
+        let dir = library
             .tv_shows_dir
-            .read()
-            .expect("failed to lock tv_shows_dir");
-        let dir = tv_shows_dir
-            .join(tv_season_episode.tv.title_year())
+            .join(library.naming.show_folder(
+                &tv_season_episode.tv.name,
+                tv_season_episode.tv.year(),
+                tv_season_episode.tv.id.into(),
+            ))
             .join(format!(
                 "Season {:02}",
                 tv_season_episode.season.season_number
             ));
-        dir
+        file_move::existing_case(&dir)
+    }
+
+    /// The video file on disk, `video_path` unless the rip had to take a versioned name.
+    pub fn local_video_path(&self, app_state: &AppState, multiple_parts: bool) -> PathBuf {
+        self.video_file
+            .clone()
+            .unwrap_or_else(|| self.video_path(app_state, multiple_parts))
     }
 
     /// Returns the full filesystem path for this video (movie or TV episode) following Plex naming conventions.
@@ -435,11 +541,18 @@ impl TitleVideo {
     /// Usage:
     /// - Use this when you need the absolute path for storing, moving, or referencing the video file on disk.
     pub fn video_path(&self, app_state: &AppState, multiple_parts: bool) -> PathBuf {
+        let library = self.library(app_state);
         match &self.video {
-            Video::Movie(movie) => Self::movie_path(app_state, movie),
-            Video::Tv(tv_season_episode) => {
-                Self::tv_season_episode_path(app_state, tv_season_episode, multiple_parts)
-            }
+            Video::Movie(movie) => Self::movie_path(&library, movie, self.title.as_ref()),
+            Video::Tv(tv_season_episode) => Self::tv_season_episode_path(
+                &library,
+                tv_season_episode,
+                multiple_parts,
+                self.title.as_ref(),
+            ),
+            Video::Personal(personal) => Self::personal_dir(&library, personal).join(
+                Self::personal_filename(personal, &library.naming, self.title.as_ref()),
+            ),
         }
     }
 
@@ -476,9 +589,13 @@ impl TitleVideo {
     ///   /Movies/Movie Name (Year)/Movie Name (Year) {edition-Final Cut}-pt1.mkv
     ///
     /// The directory does NOT include the edition tag, only the filename does.
-    fn movie_path(app_state: &AppState, movie: &MoviePartEdition) -> PathBuf {
-        let dir = Self::movie_dir(app_state, &movie.movie);
-        let file_name = Self::movie_filename(movie);
+    fn movie_path(
+        library: &Library,
+        movie: &MoviePartEdition,
+        title: Option<&TitleInfo>,
+    ) -> PathBuf {
+        let dir = Self::movie_dir(library, &movie.movie);
+        let file_name = Self::movie_filename(movie, &library.naming, title);
         dir.join(file_name)
     }
 
@@ -489,12 +606,21 @@ impl TitleVideo {
     /// With part: Movie Name (Year)-pt1.mkv
     /// With edition: Movie Name (Year) {edition-Final Cut}.mkv
     /// With both: Movie Name (Year) {edition-Final Cut}-pt1.mkv
-    fn movie_filename(movie: &MoviePartEdition) -> String {
-        let mut base = movie.movie.title_year();
+    ///
+    /// With quality tag: Movie Name (Year) {edition-Final Cut} [Bluray-1080p]-pt1.mkv
+    ///
+    /// The title is passed through `NamingOptions` so the filename matches the folder name.
+    fn movie_filename(
+        movie: &MoviePartEdition,
+        naming: &NamingOptions,
+        title: Option<&TitleInfo>,
+    ) -> String {
+        let mut base = naming.title_year(&movie.movie.title, movie.movie.year());
         // Add edition if present
         if let Some(ref edition) = movie.edition {
             base = format!("{base} {{edition-{edition}}}");
         }
+        base.push_str(&naming.quality_suffix(title.and_then(TitleInfo::quality_tag).as_deref()));
         let mut file_name = format!("{base}.mkv");
         // Add part if present
         if let Some(part) = movie.part {
@@ -535,12 +661,14 @@ impl TitleVideo {
     ///
     /// See `tv_episode_file_name` for detailed filename construction logic.
     fn tv_season_episode_path(
-        app_state: &AppState,
+        library: &Library,
         tv_season_episode: &TvSeasonEpisode,
         multiple_parts: bool,
+        title: Option<&TitleInfo>,
     ) -> PathBuf {
-        let dir = Self::seasons_episode_dir(app_state, tv_season_episode);
-        let file_name = Self::tv_episode_filename(tv_season_episode, multiple_parts);
+        let dir = Self::seasons_episode_dir(library, tv_season_episode);
+        let file_name =
+            Self::tv_episode_filename(tv_season_episode, multiple_parts, &library.naming, title);
         dir.join(file_name)
     }
 
@@ -564,15 +692,30 @@ impl TitleVideo {
     /// 3. If a `part` number exists and either `part > 1` or `multiple_parts == true`, strip the trailing
     ///    ".mkv", append the `-ptX` suffix, then restore the extension.
     /// 4. Return the final filename string.
-    fn tv_episode_filename(tv_season_episode: &TvSeasonEpisode, multiple_parts: bool) -> String {
-        let episode_title = tv_season_episode.episode.name.replace('/', "-");
+    ///
+    /// The show name is passed through `NamingOptions`, the episode title only gets the
+    /// ASCII transliteration since moving articles only makes sense for sortable names.
+    /// When `append_quality_tag` is enabled the quality of the ripped title is added
+    /// before the part suffix: `Show Name (Year) - S01E01 - Pilot [DVD]-pt1.mkv`
+    fn tv_episode_filename(
+        tv_season_episode: &TvSeasonEpisode,
+        multiple_parts: bool,
+        naming: &NamingOptions,
+        title: Option<&TitleInfo>,
+    ) -> String {
+        let mut episode_title =
+            crate::services::naming::nfc(&tv_season_episode.episode.name).replace('/', "-");
+        if naming.ascii_only {
+            episode_title = crate::services::naming::transliterate_ascii(&episode_title);
+        }
 
         let mut file_name = format!(
-            "{} - S{:02}E{:02} - {}.mkv",
-            tv_season_episode.tv.title_year(),
+            "{} - S{:02}E{:02} - {}{}.mkv",
+            naming.title_year(&tv_season_episode.tv.name, tv_season_episode.tv.year()),
             tv_season_episode.season.season_number,
             tv_season_episode.episode.episode_number,
-            episode_title
+            episode_title,
+            naming.quality_suffix(title.and_then(TitleInfo::quality_tag).as_deref())
         );
 
         if tv_season_episode.part > 1 || multiple_parts {
@@ -610,6 +753,8 @@ impl TitleVideo {
 pub enum Video {
     Tv(Box<TvSeasonEpisode>),
     Movie(Box<MoviePartEdition>),
+    /// Skips every metadata lookup, see `PersonalVideo`
+    Personal(Box<PersonalVideo>),
 }
 
 impl Video {
@@ -617,20 +762,24 @@ impl Video {
         match self {
             Video::Movie(movie) => Some(movie.runtime_seconds()),
             Video::Tv(tv) => tv.runtime_seconds(),
+            Video::Personal(_) => None,
         }
     }
 
     pub fn runtime_range(&self) -> Option<std::ops::Range<u64>> {
         match self {
             Video::Movie(movie) => Some(movie.runtime_range()),
-            Video::Tv(tv) => Some(tv.episode.runtime_range()),
+            // Specials often have no runtime on TMDB, there is nothing to match titles against
+            Video::Tv(tv) => tv.episode.runtime.map(|_| tv.episode.runtime_range()),
+            Video::Personal(_) => None,
         }
     }
 
-    pub fn mvdb_id(&self) -> u32 {
+    pub fn mvdb_id(&self) -> Option<u32> {
         match self {
-            Video::Movie(movie) => movie.movie.id,
-            Video::Tv(tv) => tv.tv.id.into(),
+            Video::Movie(movie) => Some(movie.movie.id),
+            Video::Tv(tv) => Some(tv.tv.id.into()),
+            Video::Personal(_) => None,
         }
     }
 }
@@ -667,6 +816,11 @@ impl TvSeasonEpisode {
         )
     }
 
+    /// The title of the season job its uploads are grouped under, e.g. "Breaking Bad (2008) - Season 1"
+    pub fn season_title(&self) -> String {
+        format!("{} - {}", self.tv.title_year(), self.season.name)
+    }
+
     /// Returns the runtime of this TV episode in seconds, if available.
     ///
     /// The runtime is extracted from the episode metadata and converted to u64.
@@ -676,9 +830,28 @@ impl TvSeasonEpisode {
     }
 }
 
+/// Delete a partial rip and its directory when nothing else is in it, `true` when there was one.
+pub fn remove_partial_file(partial_path: &Path) -> Result<bool, String> {
+    if !partial_path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(partial_path).map_err(|e| {
+        format!(
+            "Failed to remove partial rip {}: {e}",
+            partial_path.display()
+        )
+    })?;
+    if let Some(dir) = partial_path.parent() {
+        // Only succeeds when the directory is empty
+        let _ = fs::remove_dir(dir);
+    }
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::the_movie_db::TvId;
+    use crate::services::test_support::test_dir;
+    use crate::the_movie_db::{EpisodeId, SeasonId, TvId};
 
     use super::*;
 
@@ -748,7 +921,7 @@ mod tests {
             episodes: vec![],
             name: format!("Season {season_number}"),
             overview: String::new(),
-            id: 1,
+            id: SeasonId::from(1),
             poster_path: None,
             season_number,
             vote_average: 0.0,
@@ -760,13 +933,13 @@ mod tests {
             air_date: Some("2023-01-01".to_string()),
             episode_number,
             episode_type: "standard".to_string(),
-            id: 1,
+            id: EpisodeId::from(1),
             name: name.to_string(),
             overview: "Test episode".to_string(),
             production_code: None,
             runtime: Some(42),
             season_number: 1,
-            show_id: 1,
+            show_id: TvId::from(1),
             still_path: None,
             vote_average: 0.0,
             vote_count: 0,
@@ -797,7 +970,7 @@ mod tests {
             edition: None,
         };
 
-        let filename = TitleVideo::movie_filename(&movie);
+        let filename = TitleVideo::movie_filename(&movie, &NamingOptions::default(), None);
         assert_eq!(filename, "Inception (2010).mkv");
     }
 
@@ -809,7 +982,7 @@ mod tests {
             edition: None,
         };
 
-        let filename = TitleVideo::movie_filename(&movie);
+        let filename = TitleVideo::movie_filename(&movie, &NamingOptions::default(), None);
         assert_eq!(filename, "The Lord of the Rings (2001)-pt1.mkv");
     }
 
@@ -821,7 +994,7 @@ mod tests {
             edition: Some("Final Cut".to_string()),
         };
 
-        let filename = TitleVideo::movie_filename(&movie);
+        let filename = TitleVideo::movie_filename(&movie, &NamingOptions::default(), None);
         assert_eq!(filename, "Blade Runner (1982) {edition-Final Cut}.mkv");
     }
 
@@ -833,7 +1006,7 @@ mod tests {
             edition: Some("Uncut".to_string()),
         };
 
-        let filename = TitleVideo::movie_filename(&movie);
+        let filename = TitleVideo::movie_filename(&movie, &NamingOptions::default(), None);
         assert_eq!(filename, "Kill Bill (2003) {edition-Uncut}-pt2.mkv");
     }
 
@@ -841,7 +1014,8 @@ mod tests {
     fn test_tv_episode_filename_single_part_no_suffix() {
         let episode = create_test_tv_season_episode("Pilot", 1, 1, 1);
 
-        let filename = TitleVideo::tv_episode_filename(&episode, false);
+        let filename =
+            TitleVideo::tv_episode_filename(&episode, false, &NamingOptions::default(), None);
         assert_eq!(filename, "Example Show (2023) - S01E01 - Pilot.mkv");
     }
 
@@ -849,7 +1023,8 @@ mod tests {
     fn test_tv_episode_filename_part1_no_multiple_parts_no_suffix() {
         let episode = create_test_tv_season_episode("Pilot", 1, 1, 1);
 
-        let filename = TitleVideo::tv_episode_filename(&episode, false);
+        let filename =
+            TitleVideo::tv_episode_filename(&episode, false, &NamingOptions::default(), None);
         assert_eq!(filename, "Example Show (2023) - S01E01 - Pilot.mkv");
     }
 
@@ -857,7 +1032,8 @@ mod tests {
     fn test_tv_episode_filename_part1_with_multiple_parts_suffix() {
         let episode = create_test_tv_season_episode("Pilot", 1, 1, 1);
 
-        let filename = TitleVideo::tv_episode_filename(&episode, true);
+        let filename =
+            TitleVideo::tv_episode_filename(&episode, true, &NamingOptions::default(), None);
         assert_eq!(filename, "Example Show (2023) - S01E01 - Pilot-pt1.mkv");
     }
 
@@ -865,15 +1041,233 @@ mod tests {
     fn test_tv_episode_filename_part2_always_has_suffix() {
         let episode = create_test_tv_season_episode("Pilot", 1, 1, 2);
 
-        let filename = TitleVideo::tv_episode_filename(&episode, false);
+        let filename =
+            TitleVideo::tv_episode_filename(&episode, false, &NamingOptions::default(), None);
         assert_eq!(filename, "Example Show (2023) - S01E01 - Pilot-pt2.mkv");
     }
 
+    #[test]
+    fn test_specials_are_season_00() {
+        let app_state = AppState::new();
+        app_state.lock_ftp_config().tv_upload_path = Some(PathBuf::from("/tv"));
+        let mut episode = create_test_tv_season_episode("Behind the Scenes", 0, 2, 1);
+        episode.episode.runtime = None;
+
+        assert_eq!(
+            TitleVideo::tv_episode_filename(&episode, false, &NamingOptions::default(), None),
+            "Example Show (2023) - S00E02 - Behind the Scenes.mkv"
+        );
+        let title_video = TitleVideo {
+            id: TitleVideoId::new(),
+            title: None,
+            video: Video::Tv(Box::new(episode)),
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
+        };
+        assert_eq!(
+            title_video.upload_directory(&app_state),
+            Some(PathBuf::from("/tv/Example Show (2023)/Season 00"))
+        );
+        assert_eq!(title_video.video.runtime_range(), None);
+    }
+
+    #[test]
+    fn test_upload_file_path_uses_movie_path_template() {
+        let app_state = AppState::new();
+        {
+            let mut ftp_config = app_state.lock_ftp_config();
+            ftp_config.movie_upload_path = Some(PathBuf::from("/data/media/movies"));
+            ftp_config.movie_path_template =
+                Some("{first_letter}/{title} ({year}) [{resolution}]".to_string());
+        }
+        let mut title = TitleInfo::new(1);
+        title.video_size = Some("1920x1080".to_string());
+        let title_video = TitleVideo {
+            id: TitleVideoId::new(),
+            title: Some(title),
+            video: Video::Movie(Box::new(MoviePartEdition {
+                movie: create_test_movie("Arrival", 2016, 116),
+                part: None,
+                edition: None,
+            })),
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
+        };
+
+        assert_eq!(
+            title_video.upload_file_path(&app_state, false),
+            Some(PathBuf::from(
+                "/data/media/movies/A/Arrival (2016) [1080p]/Arrival (2016).mkv"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_upload_directory_defaults_to_plex_layout() {
+        let app_state = AppState::new();
+        app_state.lock_ftp_config().tv_upload_path = Some(PathBuf::from("/tv"));
+        let title_video = TitleVideo {
+            id: TitleVideoId::new(),
+            title: None,
+            video: Video::Tv(Box::new(create_test_tv_season_episode("Pilot", 1, 1, 1))),
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
+        };
+
+        assert_eq!(
+            title_video.upload_directory(&app_state),
+            Some(PathBuf::from("/tv/Example Show (2023)/Season 01"))
+        );
+    }
+
+    #[test]
+    fn test_personal_video_paths() {
+        let app_state = AppState::new();
+        *app_state.movies_dir.write().unwrap() = PathBuf::from("/rips/Movies");
+        app_state.lock_ftp_config().movie_upload_path = Some(PathBuf::from("/media/Movies"));
+        let title_video = TitleVideo {
+            id: TitleVideoId::new(),
+            title: None,
+            video: Video::Personal(Box::new(PersonalVideo {
+                name: "Smith/Jones Wedding".to_string(),
+                date: Some("July 4th, 2004".to_string()),
+                part: Some(2),
+            })),
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
+        };
+
+        assert_eq!(
+            title_video.video_path(&app_state, false),
+            PathBuf::from(
+                "/rips/Home Videos/Smith-Jones Wedding (2004)/Smith-Jones Wedding (2004)-pt2.mkv"
+            )
+        );
+        assert_eq!(
+            title_video.upload_directory(&app_state),
+            Some(PathBuf::from(
+                "/media/Home Videos/Smith-Jones Wedding (2004)"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_personal_video_dir_error_is_returned() {
+        let dir = test_dir("title-video-personal-dir");
+        fs::write(dir.join("Home Videos"), b"not a folder").unwrap();
+        let app_state = AppState::new();
+        *app_state.movies_dir.write().unwrap() = dir.join("Movies");
+        let title_video = TitleVideo {
+            id: TitleVideoId::new(),
+            title: None,
+            video: Video::Personal(Box::new(PersonalVideo {
+                name: "Birthday".to_string(),
+                date: None,
+                part: None,
+            })),
+            library: None,
+            video_file: None,
+            joined_titles: Vec::new(),
+            skip_upload: false,
+        };
+
+        let error = title_video.create_video_dir(&app_state).unwrap_err();
+        assert!(error.starts_with("Failed to create"), "{error}");
+    }
+
+    #[test]
+    fn test_personal_video_year() {
+        let personal = |date: Option<&str>| PersonalVideo {
+            name: "Birthday".to_string(),
+            date: date.map(str::to_string),
+            part: None,
+        };
+        assert_eq!(personal(Some("2004-07-04")).year(), Some(2004));
+        assert_eq!(personal(Some("04/07/1999")).year(), Some(1999));
+        assert_eq!(personal(Some("12345")).year(), None);
+        assert_eq!(personal(None).year(), None);
+        assert_eq!(personal(None).title(), "Birthday");
+    }
+
     #[test]
     fn test_tv_episode_filename_sanitizes_forward_slash() {
         let episode = create_test_tv_season_episode("Act 1/Act 2", 1, 3, 1);
 
-        let filename = TitleVideo::tv_episode_filename(&episode, false);
+        let filename =
+            TitleVideo::tv_episode_filename(&episode, false, &NamingOptions::default(), None);
         assert_eq!(filename, "Example Show (2023) - S01E03 - Act 1-Act 2.mkv");
     }
+
+    #[test]
+    fn test_movie_filename_with_naming_options() {
+        let movie = MoviePartEdition {
+            movie: create_test_movie("The Amélie Story", 2001, 122),
+            part: None,
+            edition: None,
+        };
+        let naming = NamingOptions {
+            move_leading_article: true,
+            ascii_only: true,
+            append_quality_tag: false,
+            tmdb_id_tag: false,
+        };
+
+        let filename = TitleVideo::movie_filename(&movie, &naming, None);
+        assert_eq!(filename, "Amelie Story, The (2001).mkv");
+    }
+
+    #[test]
+    fn test_tv_episode_filename_with_naming_options() {
+        let mut episode = create_test_tv_season_episode("Café", 1, 2, 1);
+        episode.tv = create_test_tv("The Show", 2020);
+        let naming = NamingOptions {
+            move_leading_article: true,
+            ascii_only: true,
+            append_quality_tag: false,
+            tmdb_id_tag: false,
+        };
+
+        let filename = TitleVideo::tv_episode_filename(&episode, false, &naming, None);
+        assert_eq!(filename, "Show, The (2020) - S01E02 - Cafe.mkv");
+    }
+
+    #[test]
+    fn test_filenames_with_quality_tag() {
+        let movie = MoviePartEdition {
+            movie: create_test_movie("Alien", 1979, 117),
+            part: Some(1),
+            edition: Some("Director's Cut".to_string()),
+        };
+        let naming = NamingOptions {
+            append_quality_tag: true,
+            ..NamingOptions::default()
+        };
+        let mut title = TitleInfo::new(1);
+        title.video_size = Some("1920x1080".to_string());
+
+        let filename = TitleVideo::movie_filename(&movie, &naming, Some(&title));
+        assert_eq!(
+            filename,
+            "Alien (1979) {edition-Director's Cut} [Bluray-1080p]-pt1.mkv"
+        );
+
+        title.video_size = Some("720x480".to_string());
+        let episode = create_test_tv_season_episode("Pilot", 1, 1, 2);
+        let filename = TitleVideo::tv_episode_filename(&episode, true, &naming, Some(&title));
+        assert_eq!(
+            filename,
+            "Example Show (2023) - S01E01 - Pilot [DVD]-pt2.mkv"
+        );
+
+        let filename = TitleVideo::tv_episode_filename(&episode, true, &naming, None);
+        assert_eq!(filename, "Example Show (2023) - S01E01 - Pilot-pt2.mkv");
+    }
 }
@@ -8,12 +8,18 @@ use std::sync::{Arc, RwLock};
 pub struct PendingUpload {
     pub video_path: String,
     pub upload_type: UploadType,
+    /// Failed upload attempts so far
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum UploadType {
     Movie,
     TvShow,
+    HomeVideo,
 }
 
 /// Manages the in-memory queue of pending uploads
@@ -47,13 +53,16 @@ impl UploadQueue {
         let upload = PendingUpload {
             video_path: video_path.clone(),
             upload_type,
+            attempts: 0,
+            last_error: None,
         };
 
         if let Ok(mut guard) = self.pending.write() {
-            if guard.insert(upload) {
-                debug!("Added {video_path} to upload queue");
-            } else {
+            if guard.iter().any(|pending| pending.video_path == video_path) {
                 debug!("File already in upload queue: {video_path}");
+            } else {
+                guard.insert(upload);
+                debug!("Added {video_path} to upload queue");
             }
             Ok(())
         } else {
@@ -76,6 +85,35 @@ impl UploadQueue {
         }
     }
 
+    /// Count a failed attempt for a queued video, it stays queued for the next try
+    pub fn record_failure(&self, video_path: &str, error: &str) -> Result<(), String> {
+        if let Ok(mut guard) = self.pending.write() {
+            let failed = guard
+                .iter()
+                .find(|upload| upload.video_path == video_path)
+                .cloned();
+            if let Some(mut upload) = failed {
+                guard.remove(&upload);
+                upload.attempts += 1;
+                upload.last_error = Some(error.to_string());
+                guard.insert(upload);
+            }
+            Ok(())
+        } else {
+            Err("Failed to acquire write lock on upload queue".to_string())
+        }
+    }
+
+    /// Find a queued video by its path
+    pub fn find(&self, video_path: &str) -> Option<PendingUpload> {
+        self.pending.read().ok().and_then(|guard| {
+            guard
+                .iter()
+                .find(|upload| upload.video_path == video_path)
+                .cloned()
+        })
+    }
+
     /// Get all pending uploads as a vector
     pub fn get_pending(&self) -> Vec<PendingUpload> {
         self.pending
@@ -134,6 +172,27 @@ mod tests {
         assert_eq!(queue.count(), 0);
     }
 
+    #[test]
+    fn test_record_failure_keeps_the_upload_queued() {
+        let queue = UploadQueue::new();
+
+        queue
+            .add("test.mkv".to_string(), UploadType::Movie)
+            .unwrap();
+        queue
+            .record_failure("test.mkv", "Connection refused")
+            .unwrap();
+        queue.record_failure("test.mkv", "Timed out").unwrap();
+        queue
+            .add("test.mkv".to_string(), UploadType::Movie)
+            .unwrap();
+
+        assert_eq!(queue.count(), 1);
+        let upload = queue.find("test.mkv").unwrap();
+        assert_eq!(upload.attempts, 2);
+        assert_eq!(upload.last_error.as_deref(), Some("Timed out"));
+    }
+
     #[test]
     fn test_has_pending() {
         let queue = UploadQueue::new();
@@ -1,5 +1,5 @@
 use crate::state::upload_state::{PendingUpload, UploadQueue, UploadType};
-use log::debug;
+use log::{debug, error};
 use serde_json::json;
 use std::sync::Arc;
 use tauri::AppHandle;
@@ -13,7 +13,6 @@ pub struct UploadedState {
 
 impl UploadedState {
     /// Clone the Arc for the UploadedState
-    #[allow(dead_code)]
     pub fn clone_arc(state: &Self) -> Arc<Self> {
         Arc::new(UploadedState {
             queue: Arc::clone(&state.queue),
@@ -44,6 +43,22 @@ impl UploadedState {
         Ok(UploadedState { queue })
     }
 
+    /// Load the queue, or start with an empty one so the app always has an upload queue
+    pub fn load(app_handle: &AppHandle) -> Self {
+        match Self::new(app_handle) {
+            Ok(state) => state,
+            Err(e) => {
+                error!(
+                    "{e}. Starting with an empty upload queue, videos that were waiting to upload \
+                     are still in the movies and TV shows folders"
+                );
+                UploadedState {
+                    queue: Arc::new(UploadQueue::new()),
+                }
+            }
+        }
+    }
+
     /// Add a video to the upload queue and persist to store
     pub fn add_upload(
         &self,
@@ -77,6 +92,22 @@ impl UploadedState {
         Ok(())
     }
 
+    /// Count a failed attempt and persist to store
+    pub fn record_failure(
+        &self,
+        app_handle: &AppHandle,
+        video_path: &str,
+        error: &str,
+    ) -> Result<(), String> {
+        self.queue.record_failure(video_path, error)?;
+        self.persist_to_store(app_handle)
+    }
+
+    /// Find a pending upload by its video path
+    pub fn find(&self, video_path: &str) -> Option<PendingUpload> {
+        self.queue.find(video_path)
+    }
+
     /// Get all pending uploads
     pub fn get_pending(&self) -> Vec<PendingUpload> {
         self.queue.get_pending()
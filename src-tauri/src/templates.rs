@@ -1,6 +1,9 @@
+use crate::state::locks::RwLockExt;
 use crate::{
-    models::title_info::TitleInfo,
+    models::title_info::{TitleId, TitleInfo},
+    reelix_error::{ErrorKind, ReelixError},
     state::{job_state::Job, title_video::Video},
+    the_movie_db::EpisodeId,
 };
 use askama::Template;
 use log::{debug, warn};
@@ -16,18 +19,28 @@ pub trait InlineTemplate: Template {
 // Blanket implementation for all Template types
 impl<T: Template> InlineTemplate for T {}
 
+pub mod context;
 pub mod disk_titles;
 pub mod disks;
+pub mod drive_health;
+pub mod filters;
+pub mod fix_match;
 pub mod ftp_settings;
 pub mod ftp_status;
+pub mod home_video;
 pub mod jobs;
+pub mod library_audit;
 pub mod movies;
+pub mod people;
+pub mod rip_plan;
 pub mod search;
 pub mod seasons;
+pub mod settings;
 pub mod the_movie_db;
 pub mod toast;
 pub mod tvs;
 pub mod update_indicator;
+pub mod uploads;
 
 // Common DOM IDs
 // To help organize the targets for turbo stream updates I have defined
@@ -47,6 +60,7 @@ pub const ERROR_ID: &str = "error";
 // Sub-IDs for specific sections within the content
 pub const SEARCH_SUGGESTION_ID: &str = "search-suggestion";
 pub const SEARCH_RESULTS_ID: &str = "search-results";
+pub const SEARCH_SUGGEST_ID: &str = "search-suggest";
 pub const MOVIE_CARDS_SELECTOR_DOM_ID: &str = "movie-cards-selector";
 pub const SEASONS_PARTS_SELECTOR_CLASS: &str = "seasons-parts-selector"; // targets="{{ .seasons-parts-selector }}" for multiple elements
 pub const DISK_SELECTOR_DOM_ID: &str = "disk-selector";
@@ -70,9 +84,11 @@ pub struct GenericErrorTurbo<'a> {
     pub generic_error: &'a GenericError<'a>,
 }
 
+/// What a command returns when it fails, `kind` lets the UI branch on it.
 #[derive(Serialize, Debug)]
 pub struct Error {
     pub message: String,
+    pub kind: ErrorKind,
 }
 
 impl std::fmt::Display for Error {
@@ -82,15 +98,24 @@ impl std::fmt::Display for Error {
 }
 impl std::error::Error for Error {}
 
+impl From<ReelixError> for Error {
+    fn from(error: ReelixError) -> Self {
+        Error {
+            message: error.to_string(),
+            kind: error.kind(),
+        }
+    }
+}
+
 pub fn render<T: Template>(template: T) -> Result<String, Error> {
     match template.render() {
         Ok(result) => Ok(result),
         Err(e) => {
             debug!("Template rendering error: {e:#?}");
-            let error = Error {
-                message: format!("An error occurred during template rendering: {e}"),
-            };
-            Err(error)
+            Err(
+                ReelixError::Template(format!("An error occurred during template rendering: {e}"))
+                    .into(),
+            )
         }
     }
 }
@@ -106,19 +131,25 @@ pub fn render_html<T: Template>(template: T) -> String {
     }
 }
 
-pub fn render_error(message: &str) -> Result<String, Error> {
-    let toast_msg = toast::Toast::danger("Error", message.to_string()).with_auto_hide(10_000);
+/// Shows `error` as a danger toast titled after the kind of error.
+pub fn render_reelix_error(error: &ReelixError) -> Result<String, Error> {
+    let toast_msg =
+        toast::Toast::danger(error.kind().title(), error.to_string()).with_auto_hide(10_000);
 
-    warn!("Rendering error template with message: {message}");
+    warn!("Rendering {:?} error: {error}", error.kind());
     toast::render_toast_append(toast_msg)
 }
 
 // Helper functions
 
 /// Same as `find_previous_value` but keyed by episode id.
-pub fn find_previous_value_by_episode_id(episode_id: &u32, part: &u16, job: &Job) -> Option<u32> {
+pub fn find_previous_value_by_episode_id(
+    episode_id: &EpisodeId,
+    part: &u16,
+    job: &Job,
+) -> Option<TitleId> {
     for title_video in job.title_videos.iter() {
-        let title_video = title_video.read().unwrap();
+        let title_video = title_video.read_or_recover();
         match &title_video.video {
             Video::Tv(tv) => {
                 if tv.part == *part && tv.episode.id == *episode_id {
@@ -127,7 +158,7 @@ pub fn find_previous_value_by_episode_id(episode_id: &u32, part: &u16, job: &Job
                     }
                 }
             }
-            Video::Movie(_) => { /* skip movies */ }
+            Video::Movie(_) | Video::Personal(_) => { /* skip movies */ }
         }
     }
     None
@@ -135,38 +166,38 @@ pub fn find_previous_value_by_episode_id(episode_id: &u32, part: &u16, job: &Job
 
 /// Same as `is_selected_title` but keyed by episode id.
 pub fn is_selected_title_by_episode_id(
-    episode_id: &u32,
+    episode_id: &EpisodeId,
     part: &u16,
     title_info: &TitleInfo,
     job: &Job,
 ) -> bool {
     job.title_videos.iter().any(|title_video| {
-        let title_video = title_video.read().unwrap();
+        let title_video = title_video.read_or_recover();
         match &title_video.video {
             Video::Tv(tv) => {
                 tv.part == *part
                     && tv.episode.id == *episode_id
                     && title_video.title.as_ref().map(|t| t.id) == Some(title_info.id)
             }
-            Video::Movie(_) => false,
+            Video::Movie(_) | Video::Personal(_) => false,
         }
     })
 }
 
 /// Same as `title_selected_by_other_episode` but keyed by episode id.
 pub fn title_selected_by_other_episode_id(
-    episode_id: &u32,
+    episode_id: &EpisodeId,
     title_info: &TitleInfo,
     job: &Job,
 ) -> bool {
     job.title_videos.iter().any(|title_video| {
-        let title_video = title_video.read().unwrap();
+        let title_video = title_video.read_or_recover();
         match &title_video.video {
             Video::Tv(tv) => {
                 tv.episode.id != *episode_id
                     && title_video.title.as_ref().map(|t| t.id) == Some(title_info.id)
             }
-            Video::Movie(_) => false,
+            Video::Movie(_) | Video::Personal(_) => false,
         }
     })
 }
@@ -0,0 +1,28 @@
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::services::version_checker::VersionState;
+use crate::state::locks::RwLockExt;
+use crate::state::AppState;
+use tauri::{AppHandle, Manager};
+
+/// The app state that pages show whatever they render: the selected disc, the library
+/// profiles and the version. Render functions start from `Context::new` instead of asking
+/// `AppState` for each value, so a new global value only has to be added here.
+pub struct Context {
+    pub selected_disk: Option<OpticalDiskInfo>,
+    /// The library profiles a rip can go to, empty with only the main library
+    pub library_names: Vec<String>,
+    pub version_state: VersionState,
+}
+
+impl Context {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        let app_state = app_handle.state::<AppState>();
+        Context {
+            selected_disk: app_state
+                .selected_disk()
+                .map(|disk| disk.read_or_recover().clone()),
+            library_names: app_state.library_profile_names(),
+            version_state: app_state.get_version_state(app_handle),
+        }
+    }
+}
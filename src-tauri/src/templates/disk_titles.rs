@@ -1,12 +1,12 @@
 use super::InlineTemplate;
-use crate::models::optical_disk_info::OpticalDiskInfo;
-use crate::state::background_process_state::copy_job_state;
+use crate::state::background_process_state::{copy_job_state, BackgroundProcessState};
 use crate::state::job_state::{JobStatus, JobType};
-use crate::state::{background_process_state, AppState};
+use crate::state::AppState;
+use crate::templates::context::Context;
 use crate::templates::movies::MoviesCards;
 use crate::templates::seasons::SeasonsParts;
 use askama::Template;
-use tauri::State;
+use tauri::{AppHandle, Manager};
 
 #[derive(Template)]
 #[template(path = "disk_titles/options.turbo.html")]
@@ -15,20 +15,17 @@ pub struct DiskTitlesOptionsTurbo<'a> {
     pub movies_cards: &'a MoviesCards<'a>,
 }
 
-pub fn render_options(
-    app_state: &State<'_, AppState>,
-    background_process_state: &State<'_, background_process_state::BackgroundProcessState>,
-) -> Result<String, super::Error> {
-    let selected_disk: Option<OpticalDiskInfo> = match app_state.selected_disk() {
-        Some(disk) => {
-            let read = disk.read().unwrap();
-            Some(read.clone())
-        }
-        None => None,
-    };
+pub fn render_options(app_handle: &AppHandle) -> Result<String, super::Error> {
+    let app_state = app_handle.state::<AppState>();
+    let background_process_state = app_handle.state::<BackgroundProcessState>();
+    let Context {
+        selected_disk,
+        library_names,
+        ..
+    } = Context::new(app_handle);
     let video = match app_state.current_video.lock() {
         Ok(guard) => guard.clone(),
-        Err(_) => return super::render_error("Failed to lock current video"),
+        Err(_) => return super::render_reelix_error(&"Failed to lock current video".into()),
     };
     let in_progress_job = match selected_disk {
         Some(ref disk) => {
@@ -67,6 +64,7 @@ pub fn render_options(
             in_progress_job: &in_progress_job,
             pending_job: &pending_job,
             video: video.as_ref(),
+            library_names: &library_names,
         },
     };
     super::render(template)
@@ -1,6 +1,8 @@
 use crate::state::background_process_state::BackgroundProcessState;
 use crate::state::job_state::JobStatus;
+use crate::state::locks::RwLockExt;
 use crate::state::AppState;
+use crate::templates::context::Context;
 use crate::templates::movies::MoviesCards;
 use crate::templates::seasons::SeasonsFab;
 use crate::templates::seasons::SeasonsParts;
@@ -37,6 +39,7 @@ pub fn emit_disk_change(app_handle: &AppHandle) {
     app_handle
         .emit("disks-changed", result)
         .expect("Failed to emit disks-changed");
+    crate::services::tray_menu::refresh(app_handle);
 }
 
 pub fn render_options(app_handle: &AppHandle) -> Result<String, super::Error> {
@@ -44,14 +47,11 @@ pub fn render_options(app_handle: &AppHandle) -> Result<String, super::Error> {
     let background_process_state = app_handle.state::<BackgroundProcessState>();
     let optical_disks = app_state.clone_optical_disks();
 
-    let selected_disk: Option<optical_disk_info::OpticalDiskInfo> = match app_state.selected_disk()
-    {
-        Some(disk_arc) => {
-            let guard = disk_arc.read().unwrap();
-            Some(guard.to_owned())
-        }
-        None => None,
-    };
+    let Context {
+        selected_disk,
+        library_names,
+        ..
+    } = Context::new(app_handle);
     let in_progress_job = background_process_state
         .find_job(
             selected_disk.as_ref().map(|d| d.id),
@@ -59,7 +59,7 @@ pub fn render_options(app_handle: &AppHandle) -> Result<String, super::Error> {
             &[JobStatus::Processing],
         )
         .map(|job_arc| {
-            let job_guard = job_arc.read().expect("lock job for read");
+            let job_guard = job_arc.read_or_recover();
             job_guard.clone()
         });
 
@@ -70,7 +70,7 @@ pub fn render_options(app_handle: &AppHandle) -> Result<String, super::Error> {
             &[JobStatus::Pending],
         )
         .map(|job_arc| {
-            let job_guard = job_arc.read().expect("lock job for read");
+            let job_guard = job_arc.read_or_recover();
             job_guard.clone()
         });
 
@@ -89,13 +89,14 @@ pub fn render_options(app_handle: &AppHandle) -> Result<String, super::Error> {
     };
     let video = match app_state.current_video.lock() {
         Ok(guard) => guard.clone(),
-        Err(_) => return super::render_error("Failed to lock current video"),
+        Err(_) => return super::render_reelix_error(&"Failed to lock current video".into()),
     };
     let movies_cards = MoviesCards {
         selected_disk: &selected_disk,
         in_progress_job: &in_progress_job,
         pending_job: &pending_job,
         video: video.as_ref(),
+        library_names: &library_names,
     };
     let disks_options_turbo = DisksOptionsTurbo {
         disks_options: &disks_options,
@@ -0,0 +1,35 @@
+use crate::services::makemkv_check::MakemkvCheck;
+use crate::state::drive_stats_state::DriveStats;
+use crate::templates::InlineTemplate;
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "drive_health/index.turbo.html")]
+pub struct DriveHealthIndexTurbo<'a> {
+    pub drive_health_index: &'a DriveHealthIndex<'a>,
+}
+
+#[derive(Template)]
+#[template(path = "drive_health/index.html")]
+pub struct DriveHealthIndex<'a> {
+    pub drives: &'a Vec<DriveStats>,
+    /// The startup version check of makemkvcon, `None` while it runs
+    pub makemkv: &'a Option<MakemkvCheck>,
+}
+
+impl DriveHealthIndex<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::INDEX_ID
+    }
+}
+
+pub fn render_index(
+    drives: &Vec<DriveStats>,
+    makemkv: &Option<MakemkvCheck>,
+) -> Result<String, super::Error> {
+    let drive_health_index = DriveHealthIndex { drives, makemkv };
+    let template = DriveHealthIndexTurbo {
+        drive_health_index: &drive_health_index,
+    };
+    super::render(template)
+}
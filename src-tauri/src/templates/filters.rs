@@ -0,0 +1,123 @@
+//! Askama filters for the values templates format, `{{ movie.runtime_seconds()|human_duration }}`.
+//! Askama looks filters up in a `filters` module where the template struct is derived, a
+//! template module brings this one in with `use super::filters;`.
+//!
+//! Sizes and runtimes are written the way the locale writes them, see `locale`.
+use crate::services::locale;
+
+/// The numbers the filters take, askama may hand them over by reference
+pub trait Number {
+    fn as_f64(&self) -> f64;
+}
+
+macro_rules! impl_number {
+    ($($number:ty),*) => {
+        $(impl Number for $number {
+            fn as_f64(&self) -> f64 {
+                *self as f64
+            }
+        })*
+    };
+}
+
+impl_number!(u32, u64, usize, f32, f64);
+
+impl<T: Number + ?Sized> Number for &T {
+    fn as_f64(&self) -> f64 {
+        (**self).as_f64()
+    }
+}
+
+/// TMDB dates, `YYYY-MM-DD` strings that may be missing
+pub trait DateValue {
+    fn date_str(&self) -> Option<&str>;
+}
+
+impl DateValue for str {
+    fn date_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl DateValue for String {
+    fn date_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T: DateValue + ?Sized> DateValue for Option<&T> {
+    fn date_str(&self) -> Option<&str> {
+        self.and_then(|date| date.date_str())
+    }
+}
+
+impl<T: DateValue + ?Sized> DateValue for &T {
+    fn date_str(&self) -> Option<&str> {
+        (**self).date_str()
+    }
+}
+
+/// "1h 52m" for seconds
+#[askama::filter_fn]
+pub fn human_duration<N: Number>(seconds: N, _: &dyn askama::Values) -> askama::Result<String> {
+    Ok(locale::current().format_runtime(seconds.as_f64().max(0.0) as u64))
+}
+
+/// "4.5 GB" for bytes
+#[askama::filter_fn]
+pub fn human_size<N: Number>(bytes: N, _: &dyn askama::Values) -> askama::Result<String> {
+    Ok(locale::current().format_size(bytes.as_f64().max(0.0) as u64))
+}
+
+/// "1999" for "1999-03-31", "N/A" without a date
+#[askama::filter_fn]
+pub fn year<D: DateValue>(date: D, _: &dyn askama::Values) -> askama::Result<String> {
+    Ok(date
+        .date_str()
+        .and_then(|date| date.get(..4))
+        .filter(|year| year.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or("N/A")
+        .to_string())
+}
+
+/// "42%" for 42.4
+#[askama::filter_fn]
+pub fn percent<N: Number>(value: N, _: &dyn askama::Values) -> askama::Result<String> {
+    Ok(format!("{:.0}%", value.as_f64()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::templates::filters;
+    use askama::Template;
+
+    #[derive(Template)]
+    #[template(
+        source = "{{ seconds|human_duration }} {{ bytes|human_size }} {{ date|year }} {{ missing|year }} {{ empty|year }} {{ value|percent }}",
+        ext = "txt"
+    )]
+    struct Formatted<'a> {
+        seconds: u64,
+        bytes: &'a u64,
+        date: &'a str,
+        missing: Option<&'a String>,
+        empty: &'a str,
+        value: f32,
+    }
+
+    #[test]
+    fn formats_durations_sizes_years_and_percents() {
+        let formatted = Formatted {
+            seconds: 6720,
+            bytes: &4_500_000_000,
+            date: "1999-03-31",
+            missing: None,
+            empty: "",
+            value: 42.4,
+        };
+        assert_eq!(
+            formatted.render().unwrap(),
+            "1h 52m 4.5 GB 1999 N/A N/A 42%"
+        );
+    }
+}
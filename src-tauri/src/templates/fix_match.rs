@@ -0,0 +1,47 @@
+use crate::templates::{filters, InlineTemplate};
+use crate::the_movie_db::SearchResult;
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "fix_match/index.turbo.html")]
+pub struct FixMatchIndexTurbo<'a> {
+    pub fix_match_index: &'a FixMatchIndex<'a>,
+}
+
+#[derive(Template)]
+#[template(path = "fix_match/index.html")]
+pub struct FixMatchIndex<'a> {
+    pub job_id: u64,
+    /// What the rip was matched to, e.g. "Dune (1984)"
+    pub title: &'a str,
+    pub files: &'a [String],
+    pub query: &'a str,
+    /// Movies only, a movie rip can't turn into an episode
+    pub results: &'a [&'a SearchResult],
+}
+
+impl FixMatchIndex<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::INDEX_ID
+    }
+}
+
+pub fn render_index(
+    job_id: u64,
+    title: &str,
+    files: &[String],
+    query: &str,
+    results: &[&SearchResult],
+) -> Result<String, super::Error> {
+    let fix_match_index = FixMatchIndex {
+        job_id,
+        title,
+        files,
+        query,
+        results,
+    };
+    let template = FixMatchIndexTurbo {
+        fix_match_index: &fix_match_index,
+    };
+    super::render(template)
+}
@@ -1,4 +1,5 @@
 use crate::services::ftp_validator::FtpChecker;
+use crate::state::locks::MutexExt;
 use crate::state::FtpConfig;
 use crate::templates::ftp_status::FtpStatusContainer;
 use crate::templates::InlineTemplate;
@@ -37,7 +38,7 @@ impl FtpSettingsStatusMessage<'_> {
 }
 
 pub fn render_show(state: &crate::state::AppState) -> Result<String, crate::templates::Error> {
-    let ftp_checker = state.ftp_config.lock().unwrap().checker.clone();
+    let ftp_checker = state.ftp_config.lock_or_recover().checker.clone();
     let ftp_status_container = FtpStatusContainer {
         ftp_checker: &ftp_checker,
     };
@@ -46,7 +47,7 @@ pub fn render_show(state: &crate::state::AppState) -> Result<String, crate::temp
         ftp_checker: &ftp_checker,
     };
     let ftp_settings_index = FtpSettingsIndex {
-        ftp_config: &state.ftp_config.lock().unwrap(),
+        ftp_config: &state.ftp_config.lock_or_recover(),
         ftp_status_container: &ftp_status_container,
         status_message: &status_message,
     };
@@ -1,4 +1,5 @@
 use crate::services::ftp_validator::{FtpChecker, FtpConnectionStatus};
+use crate::state::locks::MutexExt;
 use crate::templates::ftp_settings::FtpSettingsStatusMessage;
 use crate::templates::InlineTemplate;
 use askama::Template;
@@ -43,7 +44,7 @@ pub struct FtpStatusUpdate<'a> {
 
 pub fn render_update(app_handle: &tauri::AppHandle) -> Result<String, crate::templates::Error> {
     let app_state = app_handle.state::<crate::state::AppState>();
-    let ftp_checker = app_state.ftp_config.lock().unwrap().checker.clone();
+    let ftp_checker = app_state.ftp_config.lock_or_recover().checker.clone();
     let ftp_status = FtpStatusContainer {
         ftp_checker: &ftp_checker,
     };
@@ -0,0 +1,50 @@
+use crate::models::optical_disk_info::OpticalDiskInfo;
+use crate::models::title_info::TitleInfo;
+use crate::templates::context::Context;
+use crate::templates::InlineTemplate;
+use askama::Template;
+use std::cmp::Reverse;
+use tauri::AppHandle;
+
+#[derive(Template)]
+#[template(path = "home_video/index.turbo.html")]
+pub struct HomeVideoIndexTurbo<'a> {
+    pub home_video_index: &'a HomeVideoIndex<'a>,
+}
+
+#[derive(Template)]
+#[template(path = "home_video/index.html")]
+pub struct HomeVideoIndex<'a> {
+    pub selected_disk: &'a Option<OpticalDiskInfo>,
+    /// Longest first, the main title of a home-burned disc is usually the longest one
+    pub titles: &'a [TitleInfo],
+    pub library_names: &'a [String],
+}
+
+impl HomeVideoIndex<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::INDEX_ID
+    }
+}
+
+pub fn render_index(app_handle: &AppHandle) -> Result<String, super::Error> {
+    let Context {
+        selected_disk,
+        library_names,
+        ..
+    } = Context::new(app_handle);
+    let mut titles = selected_disk
+        .as_ref()
+        .map(|disk| disk.clone_titles())
+        .unwrap_or_default();
+    titles.sort_by_key(|title| Reverse(title.duration_seconds()));
+    let home_video_index = HomeVideoIndex {
+        selected_disk: &selected_disk,
+        titles: &titles,
+        library_names: &library_names,
+    };
+    let template = HomeVideoIndexTurbo {
+        home_video_index: &home_video_index,
+    };
+    super::render(template)
+}
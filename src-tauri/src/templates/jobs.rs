@@ -1,5 +1,5 @@
-use crate::state::job_state::Job;
-use crate::templates::InlineTemplate;
+use crate::state::job_state::{Job, JobId, JobType};
+use crate::templates::{filters, InlineTemplate};
 use askama::Template;
 
 #[derive(Template)]
@@ -19,6 +19,8 @@ impl<'a> JobsContainer<'a> {
 #[template(path = "jobs/item.html")]
 pub struct JobsItem<'a> {
     pub job: &'a Job,
+    /// The season job this item is listed under, it collapses and expands with it
+    pub parent_id: Option<JobId>,
     pub summary: &'a JobsItemSummary<'a>,
     pub details: &'a JobsItemDetails<'a>,
 }
@@ -43,6 +45,32 @@ impl<'a> JobsItemSummary<'a> {
     pub fn collapse_id(&self) -> String {
         format!("job-collapse-{}", self.job.id)
     }
+
+    /// A season job also expands its children
+    pub fn collapse_target(&self) -> String {
+        if self.job.job_type == JobType::Season {
+            format!("#{}, .job-children-{}", self.collapse_id(), self.job.id)
+        } else {
+            format!("#{}", self.collapse_id())
+        }
+    }
+
+    pub fn progress(&self) -> JobsSummaryProgress<'a> {
+        JobsSummaryProgress { job: self.job }
+    }
+}
+
+/// The progress bar, counts and ETA of the summary, updated on every progress tick
+#[derive(Template)]
+#[template(path = "jobs/summary_progress.html")]
+pub struct JobsSummaryProgress<'a> {
+    pub job: &'a Job,
+}
+
+impl<'a> JobsSummaryProgress<'a> {
+    pub fn dom_id(&self) -> String {
+        format!("job-summary-progress-{}", self.job.id)
+    }
 }
 
 #[derive(Template)]
@@ -55,6 +83,23 @@ impl<'a> JobsItemDetails<'a> {
     pub fn dom_id(&self) -> String {
         format!("job-details-{}", self.job.id)
     }
+
+    pub fn progress(&self) -> JobsDetailsProgress<'a> {
+        JobsDetailsProgress { job: self.job }
+    }
+}
+
+/// The subtitle, progress bars, message and ETA of the details
+#[derive(Template)]
+#[template(path = "jobs/details_progress.html")]
+pub struct JobsDetailsProgress<'a> {
+    pub job: &'a Job,
+}
+
+impl<'a> JobsDetailsProgress<'a> {
+    pub fn dom_id(&self) -> String {
+        format!("job-details-progress-{}", self.job.id)
+    }
 }
 
 #[derive(Template)]
@@ -64,6 +109,13 @@ pub struct JobsItemTurbo<'a> {
     pub details: &'a JobsItemDetails<'a>,
 }
 
+#[derive(Template)]
+#[template(path = "jobs/progress.turbo.html")]
+pub struct JobsProgressTurbo<'a> {
+    pub summary: &'a JobsSummaryProgress<'a>,
+    pub details: &'a JobsDetailsProgress<'a>,
+}
+
 #[derive(Template)]
 #[template(path = "jobs/completed_item.html")]
 pub struct JobsCompletedItem<'a> {
@@ -94,37 +146,64 @@ pub struct JobsUpdate<'a> {
     pub container: &'a JobsContainer<'a>,
 }
 
+/// The active jobs in the order they are listed, each season job followed by its children.
+///
+/// A child is listed with its season job whatever its own status, a job whose season job was
+/// deleted is listed on its own again.
+pub fn active_jobs<'a>(sorted_jobs: &[&'a Job]) -> Vec<(&'a Job, Option<JobId>)> {
+    let mut active = Vec::new();
+    for job in sorted_jobs
+        .iter()
+        .filter(|job| !has_parent(job, sorted_jobs) && !job.is_completed())
+    {
+        active.push((*job, None));
+        active.extend(
+            sorted_jobs
+                .iter()
+                .filter(|child| child.parent_id == Some(job.id))
+                .map(|child| (*child, Some(job.id))),
+        );
+    }
+    active
+}
+
+/// True when the season job of `job` is one of `jobs`
+pub fn has_parent(job: &Job, jobs: &[&Job]) -> bool {
+    matches!(job.parent_id, Some(parent_id) if jobs.iter().any(|parent| parent.id == parent_id))
+}
+
 pub fn render_container(jobs: &[Job]) -> Result<String, crate::templates::Error> {
     let mut sorted_jobs: Vec<&Job> = jobs.iter().collect();
-    sorted_jobs.sort_by(|a, b| b.id.cmp(&a.id));
+    sorted_jobs.sort_by_key(|job| std::cmp::Reverse(job.id));
+
+    let active_jobs = active_jobs(&sorted_jobs);
 
-    let summaries: Vec<JobsItemSummary> = sorted_jobs
+    let summaries: Vec<JobsItemSummary> = active_jobs
         .iter()
-        .filter(|job| !job.is_completed())
-        .map(|job| JobsItemSummary { job })
+        .map(|(job, _)| JobsItemSummary { job })
         .collect();
 
-    let details: Vec<JobsItemDetails> = sorted_jobs
+    let details: Vec<JobsItemDetails> = active_jobs
         .iter()
-        .filter(|job| !job.is_completed())
-        .map(|job| JobsItemDetails { job })
+        .map(|(job, _)| JobsItemDetails { job })
         .collect();
 
-    let items: Vec<JobsItem> = sorted_jobs
+    let items: Vec<JobsItem> = active_jobs
         .iter()
-        .filter(|job| !job.is_completed())
         .enumerate()
-        .map(|(index, job)| JobsItem {
+        .map(|(index, (job, parent_id))| JobsItem {
             job,
+            parent_id: *parent_id,
             summary: &summaries[index],
             details: &details[index],
         })
         .collect();
 
+    // The children of a season job are part of its row
     let completed_jobs: Vec<&Job> = sorted_jobs
         .iter()
         .copied()
-        .filter(|job| job.is_completed())
+        .filter(|job| job.is_completed() && !has_parent(job, &sorted_jobs))
         .collect();
 
     let completed_items: Vec<JobsCompletedItem> = completed_jobs
@@ -164,3 +243,15 @@ pub fn render_job_item(job: &Job) -> Result<String, crate::templates::Error> {
     };
     crate::templates::render(template)
 }
+
+/// Only the parts of the job item that change while it makes progress, a lot less for the
+/// webview to swap than `render_job_item`.
+pub fn render_job_progress(job: &Job) -> Result<String, crate::templates::Error> {
+    let summary = JobsSummaryProgress { job };
+    let details = JobsDetailsProgress { job };
+    let template = JobsProgressTurbo {
+        summary: &summary,
+        details: &details,
+    };
+    crate::templates::render(template)
+}
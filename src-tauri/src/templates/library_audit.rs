@@ -0,0 +1,29 @@
+use crate::services::library_audit::LibraryAudit;
+use crate::templates::InlineTemplate;
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "library_audit/index.turbo.html")]
+pub struct LibraryAuditIndexTurbo<'a> {
+    pub library_audit_index: &'a LibraryAuditIndex<'a>,
+}
+
+#[derive(Template)]
+#[template(path = "library_audit/index.html")]
+pub struct LibraryAuditIndex<'a> {
+    pub audit: &'a LibraryAudit,
+}
+
+impl LibraryAuditIndex<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::INDEX_ID
+    }
+}
+
+pub fn render_index(audit: &LibraryAudit) -> Result<String, super::Error> {
+    let library_audit_index = LibraryAuditIndex { audit };
+    let template = LibraryAuditIndexTurbo {
+        library_audit_index: &library_audit_index,
+    };
+    super::render(template)
+}
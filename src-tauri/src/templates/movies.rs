@@ -1,14 +1,16 @@
-use super::InlineTemplate;
+use super::{filters, InlineTemplate};
 use crate::models::optical_disk_info::OpticalDiskInfo;
-use crate::services::ftp_uploader;
+use crate::services::title_analysis::TitleAnalysis;
+use crate::services::{ftp_uploader, path_template, trakt};
 use crate::state::background_process_state::{copy_job_state, BackgroundProcessState};
 use crate::state::job_state::{Job, JobStatus};
 use crate::state::title_video::Video;
-use crate::state::{background_process_state, AppState};
+use crate::state::AppState;
+use crate::templates::context::Context;
 use crate::the_movie_db;
 use askama::Template;
 use log::debug;
-use tauri::{Manager, State};
+use tauri::{AppHandle, Manager};
 
 #[derive(Template)]
 #[template(path = "movies/cards.html")]
@@ -17,12 +19,21 @@ pub struct MoviesCards<'a> {
     pub in_progress_job: &'a Option<Job>,
     pub pending_job: &'a Option<Job>,
     pub video: Option<&'a Video>,
+    pub library_names: &'a [String],
 }
 
 impl MoviesCards<'_> {
     pub fn dom_id(&self) -> &'static str {
         super::MOVIE_CARDS_SELECTOR_DOM_ID
     }
+
+    /// Content hints for the titles of the selected disc, see `title_analysis`
+    pub fn title_analysis(&self) -> TitleAnalysis {
+        match self.selected_disk {
+            Some(disk) => TitleAnalysis::analyze(&disk.clone_titles()),
+            None => TitleAnalysis::default(),
+        }
+    }
 }
 
 #[derive(Template)]
@@ -42,6 +53,8 @@ pub struct MoviesShow<'a> {
     pub movie: &'a the_movie_db::MovieResponse,
     pub certification: &'a Option<String>,
     pub ripped: &'a bool,
+    /// In the imported collection, see `owned_collection`
+    pub owned: &'a bool,
     pub movies_cards: &'a MoviesCards<'a>,
 }
 
@@ -49,22 +62,40 @@ impl MoviesShow<'_> {
     pub fn dom_id(&self) -> &'static str {
         super::SEARCH_RESULTS_ID
     }
+
+    /// On the Trakt watchlist, see `trakt`
+    pub fn on_watchlist(&self) -> bool {
+        trakt::on_watchlist("movie", self.movie.id)
+    }
 }
 
 pub fn render_show(
-    app_state: &State<'_, AppState>,
-    background_process_state: &State<'_, background_process_state::BackgroundProcessState>,
+    app_handle: &AppHandle,
     movie: &the_movie_db::MovieResponse,
     certification: &Option<String>,
+    owned: bool,
 ) -> Result<String, super::Error> {
-    let ripped = ftp_uploader::file_exists(&movie.to_file_path(), app_state);
-    let selected_disk = match app_state.selected_disk() {
-        Some(disk) => {
-            let disk_lock = disk.read().unwrap();
-            Some(disk_lock.clone())
-        }
-        None => None,
-    };
+    let app_state = app_handle.state::<AppState>();
+    let background_process_state = app_handle.state::<BackgroundProcessState>();
+    let naming = app_state.naming_options();
+    let Context {
+        selected_disk,
+        library_names,
+        ..
+    } = Context::new(app_handle);
+    // The title the cards highlight, the one closest to the runtime
+    let title = selected_disk.as_ref().and_then(|disk| {
+        disk.titles_sorted(Some(movie.runtime_seconds()))
+            .into_iter()
+            .next()
+    });
+    let relative_file_path = path_template::movie_relative_file_path(
+        &app_state.lock_ftp_config(),
+        &naming,
+        movie,
+        title.as_ref(),
+    );
+    let ripped = ftp_uploader::file_exists(&relative_file_path, &app_state);
 
     let in_progress_job = match &selected_disk {
         Some(disk) => background_process_state
@@ -89,31 +120,31 @@ pub fn render_show(
             movie,
             certification,
             ripped: &ripped,
+            owned: &owned,
             movies_cards: &MoviesCards {
                 selected_disk: &selected_disk,
                 in_progress_job: &in_progress_job,
                 pending_job: &pending_job,
                 video: Some(&video),
+                library_names: &library_names,
             },
         },
     };
     super::render(template)
 }
 
-pub fn render_cards(app_handle: &tauri::AppHandle) -> Result<String, super::Error> {
+pub fn render_cards(app_handle: &AppHandle) -> Result<String, super::Error> {
     let app_state = app_handle.state::<AppState>();
     let background_process_state = app_handle.state::<BackgroundProcessState>();
-    let selected_disk = match app_state.selected_disk() {
-        Some(disk) => {
-            let disk_lock = disk.read().unwrap();
-            Some(disk_lock.clone())
-        }
-        None => None,
-    };
+    let Context {
+        selected_disk,
+        library_names,
+        ..
+    } = Context::new(app_handle);
 
     let video = match app_state.current_video.lock() {
         Ok(guard) => guard.clone(),
-        Err(_) => return super::render_error("Failed to lock current video"),
+        Err(_) => return super::render_reelix_error(&"Failed to lock current video".into()),
     };
 
     let in_progress_job = match &selected_disk {
@@ -178,6 +209,7 @@ pub fn render_cards(app_handle: &tauri::AppHandle) -> Result<String, super::Erro
             tv.title(),
             tv.part
         ),
+        Video::Personal(personal) => format!("Personal(title={})", personal.title()),
     });
 
     debug!(
@@ -194,6 +226,7 @@ pub fn render_cards(app_handle: &tauri::AppHandle) -> Result<String, super::Erro
             in_progress_job: &in_progress_job,
             pending_job: &pending_job,
             video: video.as_ref(),
+            library_names: &library_names,
         },
     };
     super::render(template)
@@ -0,0 +1,42 @@
+use super::{filters, InlineTemplate};
+use crate::services::owned_collection::OwnedCollection;
+use crate::the_movie_db::{PersonCredit, PersonResponse};
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "people/show.turbo.html")]
+pub struct PeopleShowTurbo<'a> {
+    pub people_show: &'a PeopleShow<'a>,
+}
+
+#[derive(Template)]
+#[template(path = "people/show.html")]
+pub struct PeopleShow<'a> {
+    pub person: &'a PersonResponse,
+    pub owned: &'a OwnedCollection,
+}
+
+impl PeopleShow<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::SEARCH_RESULTS_ID
+    }
+
+    pub fn owns(&self, credit: &PersonCredit) -> bool {
+        self.owned.owns(
+            &credit.media_type,
+            credit.id,
+            &credit.get_title(),
+            credit.year(),
+        )
+    }
+}
+
+pub fn render_show(
+    person: &PersonResponse,
+    owned: &OwnedCollection,
+) -> Result<String, super::Error> {
+    let template = PeopleShowTurbo {
+        people_show: &PeopleShow { person, owned },
+    };
+    super::render(template)
+}
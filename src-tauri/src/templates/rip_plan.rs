@@ -0,0 +1,52 @@
+use crate::services::rip_plan::{DriveAffinity, RipPlan};
+use crate::state::AppState;
+use crate::templates::InlineTemplate;
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "rip_plan/index.turbo.html")]
+pub struct RipPlanIndexTurbo<'a> {
+    pub rip_plan_index: &'a RipPlanIndex<'a>,
+}
+
+#[derive(Template)]
+#[template(path = "rip_plan/index.html")]
+pub struct RipPlanIndex<'a> {
+    pub plan: &'a RipPlan,
+    /// The drives a season can be pinned to, the ones holding a disc and the pinned ones
+    pub drives: &'a [String],
+}
+
+impl RipPlanIndex<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::INDEX_ID
+    }
+
+    /// Drives are only worth choosing with more than one
+    pub fn shows_drives(&self) -> bool {
+        self.drives.len() > 1
+    }
+}
+
+pub fn render_index(app_state: &AppState) -> Result<String, super::Error> {
+    let plan = app_state.rip_plan();
+    let mut drives: Vec<String> = app_state
+        .clone_optical_disks()
+        .iter()
+        .map(|disk| disk.drive_key())
+        .chain(plan.items.iter().filter_map(|item| match &item.drive {
+            DriveAffinity::Drive(drive) => Some(drive.clone()),
+            _ => None,
+        }))
+        .collect();
+    drives.sort();
+    drives.dedup();
+    let rip_plan_index = RipPlanIndex {
+        plan: &plan,
+        drives: &drives,
+    };
+    let template = RipPlanIndexTurbo {
+        rip_plan_index: &rip_plan_index,
+    };
+    super::render(template)
+}
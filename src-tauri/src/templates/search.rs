@@ -1,18 +1,21 @@
-use crate::models::optical_disk_info;
 use crate::services::auto_complete::suggestion;
+use crate::services::owned_collection::{self, OwnedCollection};
 use crate::services::plex::search_multi;
+use crate::services::trakt;
 use crate::state::background_process_state::BackgroundProcessState;
 use crate::state::job_state::Job;
+use crate::state::locks::{MutexExt, RwLockExt};
 use crate::state::AppState;
 use crate::templates::disks::DisksOptions;
 use crate::templates::jobs::{
-    JobsCompletedItem, JobsCompletedSection, JobsContainer, JobsItem, JobsItemDetails,
-    JobsItemSummary,
+    active_jobs, has_parent, JobsCompletedItem, JobsCompletedSection, JobsContainer, JobsItem,
+    JobsItemDetails, JobsItemSummary,
 };
 use crate::templates::{
-    ftp_status, the_movie_db, update_indicator::UpdateIndicator, GenericError, InlineTemplate,
+    context::Context, filters, ftp_status, the_movie_db, update_indicator::UpdateIndicator,
+    GenericError, InlineTemplate,
 };
-use crate::the_movie_db::SearchResponse;
+use crate::the_movie_db::{SearchResponse, SearchResult};
 use askama::Template;
 use tauri::Manager;
 
@@ -28,6 +31,7 @@ pub struct SearchIndex<'a> {
     pub disks_options: &'a DisksOptions<'a>,
     pub query: &'a str,
     pub suggestion: &'a SearchSuggestion<'a>,
+    pub search_suggest: &'a SearchSuggest<'a>,
     pub search_results: &'a SearchResults<'a>,
     pub generic_error: &'a GenericError<'a>,
     pub disks_toast_progress: &'a JobsContainer<'a>,
@@ -68,11 +72,30 @@ pub struct SearchSuggestionTurbo<'a> {
     pub search_suggestion: &'a SearchSuggestion<'a>,
 }
 
+#[derive(Template)]
+#[template(path = "search/suggest.html")]
+pub struct SearchSuggest<'a> {
+    pub results: &'a [SearchResult],
+}
+
+impl SearchSuggest<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::SEARCH_SUGGEST_ID
+    }
+}
+
+#[derive(Template)]
+#[template(path = "search/suggest.turbo.html")]
+pub struct SearchSuggestTurbo<'a> {
+    pub search_suggest: &'a SearchSuggest<'a>,
+}
+
 #[derive(Template)]
 #[template(path = "search/results.html")]
 pub struct SearchResults<'a> {
     pub query: &'a str,
     pub search: &'a SearchResponse,
+    pub owned: &'a OwnedCollection,
     pub update_indicator: &'a UpdateIndicator<'a>,
     pub ftp_status: &'a ftp_status::FtpStatusContainer<'a>,
 }
@@ -80,6 +103,11 @@ impl SearchResults<'_> {
     pub fn dom_id(&self) -> &'static str {
         super::SEARCH_RESULTS_ID
     }
+
+    /// On the Trakt watchlist, see `trakt`
+    pub fn on_watchlist(&self, result: &SearchResult) -> bool {
+        trakt::on_watchlist(&result.media_type, result.id)
+    }
 }
 
 #[derive(Template)]
@@ -88,33 +116,27 @@ pub struct SearchResultsTurbo<'a> {
     pub search_results: &'a SearchResults<'a>,
 }
 
-pub fn render_index(app_handle: &tauri::AppHandle) -> Result<String, super::Error> {
+pub async fn render_index(app_handle: &tauri::AppHandle) -> Result<String, super::Error> {
     let app_state = app_handle.state::<AppState>();
     let background_process_state = app_handle.state::<BackgroundProcessState>();
-    let query = app_state.query.lock().unwrap().to_string();
-    let search = match search_multi(&app_state, &query) {
+    let query = app_state.query.lock_or_recover().to_string();
+    let search = match search_multi(app_handle, &query).await {
         Ok(resp) => resp,
         Err(e) => return the_movie_db::render_index(&app_state, &e.message),
     };
     let suggestion = suggestion(&query);
-    let selected_disk: Option<optical_disk_info::OpticalDiskInfo> = match app_state.selected_disk()
-    {
-        Some(disk_arc) => {
-            let guard = disk_arc.read().unwrap();
-            Some(guard.to_owned())
-        }
-        None => None,
-    };
+    let Context {
+        selected_disk,
+        version_state,
+        ..
+    } = Context::new(app_handle);
 
     // Get all jobs from background_process_state
     let jobs_vec: Vec<crate::state::job_state::Job> = {
-        let jobs = background_process_state
-            .jobs
-            .read()
-            .expect("lock jobs for read");
+        let jobs = background_process_state.jobs.read_or_recover();
         jobs.iter()
             .map(|job_arc| {
-                let job_guard = job_arc.read().expect("lock job for read");
+                let job_guard = job_arc.read_or_recover();
                 job_guard.clone()
             })
             .collect()
@@ -137,26 +159,26 @@ pub fn render_index(app_handle: &tauri::AppHandle) -> Result<String, super::Erro
     let background_process_state = app_handle.state::<BackgroundProcessState>();
     let jobs = background_process_state.clone_all_jobs();
     let mut sorted_jobs: Vec<&Job> = jobs.iter().collect();
-    sorted_jobs.sort_by(|a, b| b.id.cmp(&a.id));
+    sorted_jobs.sort_by_key(|job| std::cmp::Reverse(job.id));
+
+    let active_jobs = active_jobs(&sorted_jobs);
 
-    let summaries: Vec<JobsItemSummary> = sorted_jobs
+    let summaries: Vec<JobsItemSummary> = active_jobs
         .iter()
-        .filter(|job| !job.is_completed())
-        .map(|job| JobsItemSummary { job })
+        .map(|(job, _)| JobsItemSummary { job })
         .collect();
 
-    let details: Vec<JobsItemDetails> = sorted_jobs
+    let details: Vec<JobsItemDetails> = active_jobs
         .iter()
-        .filter(|job| !job.is_completed())
-        .map(|job| JobsItemDetails { job })
+        .map(|(job, _)| JobsItemDetails { job })
         .collect();
 
-    let items: Vec<JobsItem> = sorted_jobs
+    let items: Vec<JobsItem> = active_jobs
         .iter()
-        .filter(|job| !job.is_completed())
         .enumerate()
-        .map(|(index, job)| JobsItem {
+        .map(|(index, (job, parent_id))| JobsItem {
             job,
+            parent_id: *parent_id,
             summary: &summaries[index],
             details: &details[index],
         })
@@ -165,7 +187,7 @@ pub fn render_index(app_handle: &tauri::AppHandle) -> Result<String, super::Erro
     let completed_jobs: Vec<&Job> = sorted_jobs
         .iter()
         .copied()
-        .filter(|job| job.is_completed())
+        .filter(|job| job.is_completed() && !has_parent(job, &sorted_jobs))
         .collect();
 
     let completed_items: Vec<JobsCompletedItem> = completed_jobs
@@ -185,13 +207,12 @@ pub fn render_index(app_handle: &tauri::AppHandle) -> Result<String, super::Erro
         failure_count,
     };
 
-    let version_state = app_state.get_version_state(app_handle);
     let update_indicator = UpdateIndicator {
         version_state: &version_state,
     };
 
     let ftp_status_display = ftp_status::FtpStatusContainer {
-        ftp_checker: &app_state.ftp_config.lock().unwrap().checker.clone(),
+        ftp_checker: &app_state.ftp_config.lock_or_recover().checker.clone(),
     };
 
     let template = SearchIndexTurbo {
@@ -202,9 +223,11 @@ pub fn render_index(app_handle: &tauri::AppHandle) -> Result<String, super::Erro
                 query: &query,
                 suggestion: &suggestion,
             },
+            search_suggest: &SearchSuggest { results: &[] },
             search_results: &SearchResults {
                 query: &query,
                 search: &search,
+                owned: &owned_collection::load_or_empty(app_handle),
                 update_indicator: &update_indicator,
                 ftp_status: &ftp_status_display,
             },
@@ -224,12 +247,11 @@ pub fn render_results(
     search: &SearchResponse,
 ) -> Result<String, super::Error> {
     let app_state = app_handle.state::<AppState>();
-    let version_state = app_state.get_version_state(app_handle);
     let update_indicator = UpdateIndicator {
-        version_state: &version_state,
+        version_state: &Context::new(app_handle).version_state,
     };
 
-    let ftp_checker = app_state.ftp_config.lock().unwrap().checker.clone();
+    let ftp_checker = app_state.ftp_config.lock_or_recover().checker.clone();
     let ftp_status_display = ftp_status::FtpStatusContainer {
         ftp_checker: &ftp_checker,
     };
@@ -238,6 +260,7 @@ pub fn render_results(
         search_results: &SearchResults {
             query,
             search,
+            owned: &owned_collection::load_or_empty(app_handle),
             update_indicator: &update_indicator,
             ftp_status: &ftp_status_display,
         },
@@ -254,3 +277,10 @@ pub async fn render_suggestion(
     };
     super::render(template)
 }
+
+pub fn render_suggest(results: &[SearchResult]) -> Result<String, super::Error> {
+    let template = SearchSuggestTurbo {
+        search_suggest: &SearchSuggest { results },
+    };
+    super::render(template)
+}
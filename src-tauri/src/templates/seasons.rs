@@ -1,12 +1,17 @@
 use crate::models::optical_disk_info::OpticalDiskInfo;
-use crate::services::ftp_uploader;
+use crate::models::title_info::TitleId;
+use crate::services::title_analysis::{RuntimeConflict, TitleAnalysis};
+use crate::services::{assignment_patterns, ftp_uploader, tmdb_cache};
 use crate::state::background_process_state::{copy_job_state, BackgroundProcessState};
 use crate::state::job_state::{Job, JobStatus};
+use crate::state::locks::RwLockExt;
 use crate::state::AppState;
+use crate::templates::context::Context;
 use crate::templates::disks::DisksOptions;
-use crate::templates::InlineTemplate;
-use crate::the_movie_db::{SeasonEpisode, SeasonResponse, TvResponse};
+use crate::templates::{filters, InlineTemplate};
+use crate::the_movie_db::{EpisodeId, SeasonEpisode, SeasonResponse, TvResponse};
 use askama::Template;
+use std::collections::HashSet;
 use tauri::Manager;
 
 #[derive(Template)]
@@ -14,7 +19,7 @@ use tauri::Manager;
 pub struct SeasonsParts<'a> {
     pub selected_disk: &'a Option<OpticalDiskInfo>,
     pub job: &'a Option<Job>,
-    pub episode_id: Option<u32>,
+    pub episode_id: Option<EpisodeId>,
 }
 
 impl SeasonsParts<'_> {
@@ -26,6 +31,14 @@ impl SeasonsParts<'_> {
         self.job.as_ref().is_some_and(Job::is_ripping)
     }
 
+    /// Content hints for the titles of the selected disc, see `title_analysis`
+    pub fn title_analysis(&self) -> TitleAnalysis {
+        match self.selected_disk {
+            Some(disk) => TitleAnalysis::analyze(&disk.clone_titles()),
+            None => TitleAnalysis::default(),
+        }
+    }
+
     /// Resolves the episode from the job's title_videos based on the episode ID.
     ///
     /// Purpose:
@@ -39,7 +52,7 @@ impl SeasonsParts<'_> {
         let episode_id = self.episode_id?; // Return None if no episode_id is set
         match &self.job {
             Some(job) => job.title_videos.iter().find_map(|title_video| {
-                let tv = title_video.read().unwrap();
+                let tv = title_video.read_or_recover();
                 match &tv.video {
                     crate::state::title_video::Video::Tv(tv_episode) => {
                         // Only return the episode if its ID matches this SeasonsParts instance's episode_id
@@ -49,7 +62,8 @@ impl SeasonsParts<'_> {
                             None
                         }
                     }
-                    crate::state::title_video::Video::Movie(_) => None,
+                    crate::state::title_video::Video::Movie(_)
+                    | crate::state::title_video::Video::Personal(_) => None,
                 }
             }),
             None => None,
@@ -82,6 +96,12 @@ pub struct SeasonsShow<'a> {
     pub season: &'a SeasonResponse,
     pub seasons_episodes: &'a SeasonsEpisodes<'a>,
     pub _seasons_fab: &'a SeasonsFab<'a>,
+    pub library_names: &'a [String],
+    /// Titles suggested from the last disc of this season, see `assignment_patterns`
+    pub has_suggestions: bool,
+    /// The posters to show, the cached copies once they were prefetched
+    pub season_poster_src: Option<String>,
+    pub tv_poster_src: Option<String>,
 }
 
 impl SeasonsShow<'_> {
@@ -108,13 +128,80 @@ pub struct SeasonsEpisode<'a> {
     pub episode: &'a SeasonEpisode,
     pub seasons_parts: &'a SeasonsParts<'a>,
     pub ripped: bool,
+    /// Ripped locally or on the FTP server
+    pub owned: bool,
     pub season: &'a SeasonResponse,
+    /// The disc title the previous disc of this season suggests
+    pub suggested_title: Option<TitleId>,
+    /// The still to show, the cached copy once it was prefetched, see `tmdb_cache`
+    pub still_src: Option<String>,
 }
 
 impl SeasonsEpisode<'_> {
     pub fn dom_id(&self) -> String {
         format!("episode-{}", self.episode.id)
     }
+
+    pub fn runtime_conflict_dom_id(&self) -> String {
+        runtime_conflict_dom_id(self.episode.id)
+    }
+
+    /// Owned episodes can't be assigned again, unless they already were before the file showed up.
+    pub fn is_skipped(&self) -> bool {
+        self.owned && !self.is_assigned()
+    }
+
+    fn is_assigned(&self) -> bool {
+        self.seasons_parts.job.as_ref().is_some_and(|job| {
+            job.title_videos.iter().any(|title_video| {
+                matches!(
+                    &title_video.read_or_recover().video,
+                    crate::state::title_video::Video::Tv(tv_episode)
+                        if tv_episode.episode.id == self.episode.id
+                )
+            })
+        })
+    }
+}
+
+fn runtime_conflict_dom_id(episode_id: EpisodeId) -> String {
+    format!("episode-{episode_id}-runtime-conflict")
+}
+
+#[derive(Template)]
+#[template(path = "seasons/runtime_conflict.turbo.html")]
+pub struct SeasonsRuntimeConflictTurbo<'a> {
+    pub seasons_runtime_conflict: &'a SeasonsRuntimeConflict<'a>,
+}
+
+/// Asks before a title is assigned to an episode it doesn't fit, see `RuntimeConflict`
+#[derive(Template)]
+#[template(path = "seasons/runtime_conflict.html")]
+pub struct SeasonsRuntimeConflict<'a> {
+    pub episode: &'a SeasonEpisode,
+    pub part: u16,
+    pub conflict: &'a RuntimeConflict,
+}
+
+impl SeasonsRuntimeConflict<'_> {
+    pub fn dom_id(&self) -> String {
+        runtime_conflict_dom_id(self.episode.id)
+    }
+}
+
+pub fn render_runtime_conflict(
+    episode: &SeasonEpisode,
+    part: u16,
+    conflict: &RuntimeConflict,
+) -> Result<String, super::Error> {
+    let template = SeasonsRuntimeConflictTurbo {
+        seasons_runtime_conflict: &SeasonsRuntimeConflict {
+            episode,
+            part,
+            conflict,
+        },
+    };
+    super::render(template)
 }
 
 pub fn render_show(
@@ -124,14 +211,23 @@ pub fn render_show(
 ) -> Result<String, super::Error> {
     let app_state = app_handle.state::<AppState>();
     let ripped_episode_numbers = ftp_uploader::tv_ripped_episode_numbers(tv, season, &app_state);
-    let selected_disk = match app_state.selected_disk() {
-        Some(disk) => {
-            let disk_lock = disk.read().unwrap();
-            Some(disk_lock.clone())
-        }
-        None => None,
-    };
+    let mut owned_episode_numbers =
+        ftp_uploader::local_ripped_episode_numbers(tv, season, &app_state);
+    owned_episode_numbers.extend(&ripped_episode_numbers);
+    let Context {
+        selected_disk,
+        library_names,
+        ..
+    } = Context::new(app_handle);
     let job = get_job(app_handle, &selected_disk);
+    let suggestions = suggestions(
+        &app_state,
+        tv,
+        season,
+        &selected_disk,
+        &job,
+        &owned_episode_numbers,
+    );
 
     // Create individual SeasonsParts for each episode to ensure proper episode-specific resolution
     let episodes_with_parts: Vec<(SeasonsParts, &SeasonEpisode)> = season
@@ -153,7 +249,13 @@ pub fn render_show(
             episode: ep,
             seasons_parts: parts,
             ripped: ripped_episode_numbers.contains(&ep.episode_number),
+            owned: owned_episode_numbers.contains(&ep.episode_number),
             season,
+            suggested_title: suggested_title(&suggestions, ep),
+            still_src: ep
+                .still_path
+                .as_deref()
+                .map(|path| tmdb_cache::image_src(app_handle, tmdb_cache::STILL_SIZE, path)),
         })
         .collect();
 
@@ -165,6 +267,10 @@ pub fn render_show(
                 episodes: &episodes,
             },
             _seasons_fab: &SeasonsFab { job: &job },
+            library_names: &library_names,
+            has_suggestions: !suggestions.is_empty(),
+            season_poster_src: poster_src(app_handle, &season.poster_path),
+            tv_poster_src: poster_src(app_handle, &tv.poster_path),
         },
     };
     super::render(seasons_show_turbo)
@@ -177,16 +283,21 @@ pub fn render_title_selected(
 ) -> Result<String, super::Error> {
     let app_state = app_handle.state::<AppState>();
     let ripped_episode_numbers = ftp_uploader::tv_ripped_episode_numbers(tv, &season, &app_state);
+    let mut owned_episode_numbers =
+        ftp_uploader::local_ripped_episode_numbers(tv, &season, &app_state);
+    owned_episode_numbers.extend(&ripped_episode_numbers);
 
-    let selected_disk = match app_state.selected_disk() {
-        Some(disk) => {
-            let disk_lock = disk.read().unwrap();
-            Some(disk_lock.clone())
-        }
-        None => None,
-    };
+    let selected_disk = Context::new(app_handle).selected_disk;
     let optical_disks = app_state.clone_optical_disks();
     let job = get_job(app_handle, &selected_disk);
+    let suggestions = suggestions(
+        &app_state,
+        tv,
+        &season,
+        &selected_disk,
+        &job,
+        &owned_episode_numbers,
+    );
 
     // Create individual SeasonsParts for each episode to ensure proper episode-specific resolution
     let episodes_with_parts: Vec<(SeasonsParts, &SeasonEpisode)> = season
@@ -208,7 +319,13 @@ pub fn render_title_selected(
             episode: ep,
             seasons_parts: parts,
             ripped: ripped_episode_numbers.contains(&ep.episode_number),
+            owned: owned_episode_numbers.contains(&ep.episode_number),
             season: &season,
+            suggested_title: suggested_title(&suggestions, ep),
+            still_src: ep
+                .still_path
+                .as_deref()
+                .map(|path| tmdb_cache::image_src(app_handle, tmdb_cache::STILL_SIZE, path)),
         })
         .collect::<Vec<SeasonsEpisode>>();
 
@@ -227,6 +344,40 @@ pub fn render_title_selected(
     super::render(template)
 }
 
+fn poster_src(app_handle: &tauri::AppHandle, poster_path: &Option<String>) -> Option<String> {
+    poster_path
+        .as_deref()
+        .map(|path| tmdb_cache::image_src(app_handle, tmdb_cache::POSTER_SIZE, path))
+}
+
+fn suggestions(
+    app_state: &AppState,
+    tv: &TvResponse,
+    season: &SeasonResponse,
+    selected_disk: &Option<OpticalDiskInfo>,
+    job: &Option<Job>,
+    owned_episode_numbers: &HashSet<u32>,
+) -> Vec<(u32, TitleId)> {
+    match selected_disk {
+        Some(disk) => assignment_patterns::suggestions(
+            &app_state.lock_assignment_patterns(),
+            tv.id,
+            season,
+            disk,
+            job.as_ref(),
+            owned_episode_numbers,
+        ),
+        None => Vec::new(),
+    }
+}
+
+fn suggested_title(suggestions: &[(u32, TitleId)], episode: &SeasonEpisode) -> Option<TitleId> {
+    suggestions
+        .iter()
+        .find(|(episode_number, _)| *episode_number == episode.episode_number)
+        .map(|(_, title_id)| *title_id)
+}
+
 fn get_job(app_handle: &tauri::AppHandle, selected_disk: &Option<OpticalDiskInfo>) -> Option<Job> {
     let background_process_state = app_handle.state::<BackgroundProcessState>();
     match selected_disk {
@@ -248,13 +399,15 @@ fn get_job(app_handle: &tauri::AppHandle, selected_disk: &Option<OpticalDiskInfo
 mod tests {
     use super::*;
     use crate::state::title_video::{TitleVideo, TvSeasonEpisode, Video};
-    use crate::the_movie_db::{SeasonEpisode, SeasonResponse, TvId, TvResponse};
+    use crate::the_movie_db::{
+        EpisodeId, SeasonEpisode, SeasonId, SeasonResponse, TvId, TvResponse,
+    };
     use std::sync::{Arc, RwLock};
 
     /// Helper function to create a minimal mock SeasonEpisode for testing
     fn create_mock_episode(id: u32, episode_number: u32, name: &str) -> SeasonEpisode {
         SeasonEpisode {
-            id,
+            id: EpisodeId::from(id),
             episode_number,
             episode_type: "standard".to_string(),
             name: name.to_string(),
@@ -263,7 +416,7 @@ mod tests {
             production_code: None,
             runtime: Some(45),
             season_number: 1,
-            show_id: 1,
+            show_id: TvId::from(1),
             still_path: None,
             vote_average: 8.0,
             vote_count: 100,
@@ -276,7 +429,7 @@ mod tests {
     fn create_mock_season_response() -> SeasonResponse {
         SeasonResponse {
             _id: "test_id".to_string(),
-            id: 100,
+            id: SeasonId::from(100),
             season_number: 1,
             name: "Season 1".to_string(),
             overview: "Test season".to_string(),
@@ -341,6 +494,10 @@ mod tests {
                     id: crate::state::title_video::TitleVideoId::new(),
                     title: None,
                     video: Video::Tv(Box::new(tv_season_episode)),
+                    library: None,
+                    video_file: None,
+                    joined_titles: Vec::new(),
+                    skip_upload: false,
                 }))
             })
             .collect();
@@ -362,14 +519,14 @@ mod tests {
         let parts = SeasonsParts {
             selected_disk: &None,
             job: &Some(job),
-            episode_id: Some(2),
+            episode_id: Some(EpisodeId::from(2)),
         };
 
         // Should resolve to episode 2 only
         let resolved = parts.resolve_episode_from_job();
         assert!(resolved.is_some());
         let episode = resolved.unwrap();
-        assert_eq!(episode.id, 2);
+        assert_eq!(episode.id, EpisodeId::from(2));
         assert_eq!(episode.name, "Episode 2");
     }
 
@@ -382,7 +539,7 @@ mod tests {
         let parts = SeasonsParts {
             selected_disk: &None,
             job: &Some(job),
-            episode_id: Some(99),
+            episode_id: Some(EpisodeId::from(99)),
         };
 
         // Should not resolve any episode
@@ -396,7 +553,7 @@ mod tests {
         let parts = SeasonsParts {
             selected_disk: &None,
             job: &None,
-            episode_id: Some(1),
+            episode_id: Some(EpisodeId::from(1)),
         };
 
         // Should not resolve any episode
@@ -413,14 +570,14 @@ mod tests {
         let parts = SeasonsParts {
             selected_disk: &None,
             job: &Some(job),
-            episode_id: Some(1),
+            episode_id: Some(EpisodeId::from(1)),
         };
 
         // Should resolve to episode 1
         let resolved = parts.resolve_episode_from_job();
         assert!(resolved.is_some());
         let episode = resolved.unwrap();
-        assert_eq!(episode.id, 1);
+        assert_eq!(episode.id, EpisodeId::from(1));
         assert_eq!(episode.name, "Episode 1");
     }
 
@@ -433,14 +590,14 @@ mod tests {
         let parts = SeasonsParts {
             selected_disk: &None,
             job: &Some(job),
-            episode_id: Some(3),
+            episode_id: Some(EpisodeId::from(3)),
         };
 
         // Should resolve to episode 3
         let resolved = parts.resolve_episode_from_job();
         assert!(resolved.is_some());
         let episode = resolved.unwrap();
-        assert_eq!(episode.id, 3);
+        assert_eq!(episode.id, EpisodeId::from(3));
         assert_eq!(episode.name, "Episode 3");
     }
 
@@ -454,13 +611,13 @@ mod tests {
             let parts = SeasonsParts {
                 selected_disk: &None,
                 job: &Some(job.clone()),
-                episode_id: Some(episode_id),
+                episode_id: Some(EpisodeId::from(episode_id)),
             };
 
             let resolved = parts.resolve_episode_from_job();
             assert!(resolved.is_some());
             let episode = resolved.unwrap();
-            assert_eq!(episode.id, episode_id);
+            assert_eq!(episode.id, EpisodeId::from(episode_id));
             assert_eq!(episode.name, format!("Episode {episode_id}"));
         }
     }
@@ -474,19 +631,19 @@ mod tests {
         let parts1 = SeasonsParts {
             selected_disk: &None,
             job: &Some(job.clone()),
-            episode_id: Some(1),
+            episode_id: Some(EpisodeId::from(1)),
         };
 
         let parts2 = SeasonsParts {
             selected_disk: &None,
             job: &Some(job.clone()),
-            episode_id: Some(2),
+            episode_id: Some(EpisodeId::from(2)),
         };
 
         let parts3 = SeasonsParts {
             selected_disk: &None,
             job: &Some(job),
-            episode_id: Some(3),
+            episode_id: Some(EpisodeId::from(3)),
         };
 
         // Each should resolve to its own episode
@@ -494,9 +651,9 @@ mod tests {
         let resolved2 = parts2.resolve_episode_from_job().unwrap();
         let resolved3 = parts3.resolve_episode_from_job().unwrap();
 
-        assert_eq!(resolved1.id, 1);
-        assert_eq!(resolved2.id, 2);
-        assert_eq!(resolved3.id, 3);
+        assert_eq!(resolved1.id, EpisodeId::from(1));
+        assert_eq!(resolved2.id, EpisodeId::from(2));
+        assert_eq!(resolved3.id, EpisodeId::from(3));
 
         // Verify they are truly independent
         assert_ne!(resolved1.id, resolved2.id);
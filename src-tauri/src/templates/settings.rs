@@ -0,0 +1,104 @@
+use crate::services::archive::ArchiveOptions;
+use crate::services::arr::ArrOptions;
+use crate::services::disk_manager::TrayOptions;
+use crate::services::locale::{self, Locale};
+use crate::services::naming::NamingOptions;
+use crate::services::network::NetworkOptions;
+use crate::services::notifications::NotificationPreferences;
+use crate::services::post_process::PostProcessOptions;
+use crate::services::push::PushOptions;
+use crate::services::remote_api::RemoteApiOptions;
+use crate::services::ripper_backend::{self, RipperOptions};
+use crate::services::trakt::TraktOptions;
+use crate::services::unix_time;
+use crate::services::upload_review::ReviewOptions;
+use crate::state::locks::RwLockExt;
+use crate::state::resource_governor::ConcurrencyLimits;
+use crate::state::AppState;
+use crate::templates::InlineTemplate;
+use askama::Template;
+use std::path::Path;
+
+#[derive(Template)]
+#[template(path = "settings/index.turbo.html")]
+pub struct SettingsIndexTurbo<'a> {
+    pub settings_index: &'a SettingsIndex<'a>,
+}
+
+#[derive(Template)]
+#[template(path = "settings/index.html")]
+pub struct SettingsIndex<'a> {
+    pub naming: &'a NamingOptions,
+    pub post_process: &'a PostProcessOptions,
+    pub concurrency: &'a ConcurrencyLimits,
+    pub notifications: &'a NotificationPreferences,
+    pub do_not_disturb_active: bool,
+    pub push: &'a PushOptions,
+    pub network: &'a NetworkOptions,
+    pub library_profiles_json: &'a str,
+    pub ripper: &'a RipperOptions,
+    pub ripper_env: &'a str,
+    pub tray: &'a TrayOptions,
+    pub archive: &'a ArchiveOptions,
+    pub movies_dir: &'a Path,
+    pub tv_shows_dir: &'a Path,
+    pub remote_api: &'a RemoteApiOptions,
+    pub trakt: &'a TraktOptions,
+    pub arr: &'a ArrOptions,
+    pub locale: &'a Locale,
+    pub review: &'a ReviewOptions,
+}
+
+impl SettingsIndex<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::INDEX_ID
+    }
+}
+
+pub fn render_index(state: &AppState) -> Result<String, super::Error> {
+    let naming = state.naming_options();
+    let post_process = state.post_process_options();
+    let concurrency = state.concurrency_limits();
+    let notifications = state.notification_preferences();
+    let do_not_disturb_active = state.do_not_disturb().is_active_at(unix_time::now());
+    let push = state.push_options();
+    let network = state.network_options();
+    let library_profiles_json =
+        serde_json::to_string_pretty(&state.library_profiles()).unwrap_or_default();
+    let ripper = state.ripper_options();
+    let ripper_env = ripper_backend::env_text(&ripper.local_env);
+    let tray = state.tray_options();
+    let archive = state.archive_options();
+    let movies_dir = state.movies_dir.read_or_recover().clone();
+    let tv_shows_dir = state.tv_shows_dir.read_or_recover().clone();
+    let remote_api = state.remote_api_options();
+    let trakt = state.trakt_options();
+    let arr = state.arr_options();
+    let locale = locale::current();
+    let review = state.review_options();
+    let settings_index = SettingsIndex {
+        naming: &naming,
+        post_process: &post_process,
+        concurrency: &concurrency,
+        notifications: &notifications,
+        do_not_disturb_active,
+        push: &push,
+        network: &network,
+        library_profiles_json: &library_profiles_json,
+        ripper: &ripper,
+        ripper_env: &ripper_env,
+        tray: &tray,
+        archive: &archive,
+        movies_dir: &movies_dir,
+        tv_shows_dir: &tv_shows_dir,
+        remote_api: &remote_api,
+        trakt: &trakt,
+        arr: &arr,
+        locale: &locale,
+        review: &review,
+    };
+    let template = SettingsIndexTurbo {
+        settings_index: &settings_index,
+    };
+    super::render(template)
+}
@@ -75,12 +75,10 @@ impl Toast {
         Self::new(title, message, ToastVariant::Danger)
     }
 
-    #[allow(dead_code)]
     pub fn warning(title: impl Into<String>, message: impl Into<String>) -> Self {
         Self::new(title, message, ToastVariant::Warning)
     }
 
-    #[allow(dead_code)]
     pub fn info(title: impl Into<String>, message: impl Into<String>) -> Self {
         Self::new(title, message, ToastVariant::Info)
     }
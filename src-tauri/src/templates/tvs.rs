@@ -12,6 +12,7 @@ pub struct TvsShowTurbo<'a> {
 #[template(path = "tvs/show.html")]
 pub struct TvsShow<'a> {
     pub tv: &'a TvResponse,
+    pub certification: &'a Option<String>,
 }
 
 impl TvsShow<'_> {
@@ -20,9 +21,12 @@ impl TvsShow<'_> {
     }
 }
 
-pub fn render_show(tv: &TvResponse) -> Result<String, super::Error> {
+pub fn render_show(
+    tv: &TvResponse,
+    certification: &Option<String>,
+) -> Result<String, super::Error> {
     let template = TvsShowTurbo {
-        tv_show: &TvsShow { tv },
+        tv_show: &TvsShow { tv, certification },
     };
     super::render(template)
 }
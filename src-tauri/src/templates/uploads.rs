@@ -0,0 +1,74 @@
+use crate::state::upload_state::{PendingUpload, UploadType};
+use crate::state::AppState;
+use crate::templates::{filters, InlineTemplate};
+use askama::Template;
+use std::path::Path;
+
+/// A queued upload as the upload queue page shows it.
+pub struct PendingUploadRow {
+    pub video_path: String,
+    pub file_name: String,
+    /// `None` once the file is gone, the next try drops it from the queue
+    pub size: Option<u64>,
+    /// The FTP folder the upload goes into, `None` when it is not configured
+    pub destination: Option<String>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl PendingUploadRow {
+    pub fn new(app_state: &AppState, upload: &PendingUpload) -> Self {
+        let path = Path::new(&upload.video_path);
+        let library = app_state.library(app_state.library_name_for_path(path).as_deref());
+        let destination = match upload.upload_type {
+            UploadType::Movie => library.ftp_config.movie_upload_path.clone(),
+            UploadType::TvShow => library.ftp_config.tv_upload_path.clone(),
+            UploadType::HomeVideo => library.home_videos_upload_path(),
+        };
+        PendingUploadRow {
+            video_path: upload.video_path.clone(),
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| upload.video_path.clone()),
+            size: std::fs::metadata(path).ok().map(|metadata| metadata.len()),
+            destination: destination.map(|dir| dir.display().to_string()),
+            attempts: upload.attempts,
+            last_error: upload.last_error.clone(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "uploads/index.turbo.html")]
+pub struct UploadsIndexTurbo<'a> {
+    pub uploads_index: &'a UploadsIndex<'a>,
+}
+
+#[derive(Template)]
+#[template(path = "uploads/index.html")]
+pub struct UploadsIndex<'a> {
+    pub uploads: &'a [PendingUploadRow],
+}
+
+impl UploadsIndex<'_> {
+    pub fn dom_id(&self) -> &'static str {
+        super::INDEX_ID
+    }
+}
+
+pub fn render_index(
+    app_state: &AppState,
+    pending: &[PendingUpload],
+) -> Result<String, super::Error> {
+    let mut uploads: Vec<PendingUploadRow> = pending
+        .iter()
+        .map(|upload| PendingUploadRow::new(app_state, upload))
+        .collect();
+    uploads.sort_by(|a, b| a.video_path.cmp(&b.video_path));
+    let uploads_index = UploadsIndex { uploads: &uploads };
+    let template = UploadsIndexTurbo {
+        uploads_index: &uploads_index,
+    };
+    super::render(template)
+}
@@ -1,9 +1,12 @@
+use crate::services::metrics;
 use crate::the_movie_db::models::{
-    MovieReleaseDatesResponse, MovieResponse, SearchResponse, SeasonResponse, TvResponse,
+    MovieReleaseDatesResponse, MovieResponse, PersonResponse, SearchResponse, SeasonResponse,
+    TvContentRatingsResponse, TvResponse,
 };
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use tauri_plugin_http::reqwest::blocking::{Client, RequestBuilder};
 
 // Struct for the TMDB Client
@@ -28,11 +31,15 @@ pub struct SearchError {
 static URL_ENDPOINT: &str = "https://api.themoviedb.org/3";
 
 impl TheMovieDb {
-    pub fn new(api_key: &String, language: &str) -> Self {
+    /// `timeout` covers connecting and reading the whole response
+    pub fn new(api_key: &String, language: &str, timeout: Duration) -> Self {
         TheMovieDb {
             api_key: api_key.to_owned(),
             language: language.to_owned(),
-            client: Client::new(),
+            client: Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_else(|_| Client::new()),
         }
     }
 
@@ -144,6 +151,19 @@ impl TheMovieDb {
         self.send_request(request)
     }
 
+    /// The person with the movies and shows they are credited in
+    pub fn person(&self, id: u32) -> Result<PersonResponse, Error> {
+        let url = format!("{URL_ENDPOINT}/person/{id}");
+
+        let mut params: HashMap<&str, &str> = HashMap::new();
+        params.insert("api_key", self.api_key.as_str());
+        params.insert("language", self.language.as_str());
+        params.insert("append_to_response", "combined_credits");
+
+        let request = self.client.get(url).query(&params);
+        self.send_request(request)
+    }
+
     pub fn movie_release_dates(&self, id: &u32) -> Result<MovieReleaseDatesResponse, Error> {
         let url = format!("https://api.themoviedb.org/3/movie/{id}/release_dates");
 
@@ -155,12 +175,26 @@ impl TheMovieDb {
         self.send_request(request)
     }
 
+    pub fn tv_content_ratings(&self, id: u32) -> Result<TvContentRatingsResponse, Error> {
+        let url = format!("{URL_ENDPOINT}/tv/{id}/content_ratings");
+
+        let mut params: HashMap<&str, &str> = HashMap::new();
+        params.insert("api_key", self.api_key.as_str());
+        let request = self.client.get(url).query(&params);
+
+        self.send_request(request)
+    }
+
     fn send_request<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, Error> {
-        let response = request.send().map_err(|e| Error {
-            code: 500,
-            message: format!("Request error: {e:?}"),
+        let response = request.send().map_err(|e| {
+            metrics::record_tmdb_request(false);
+            Error {
+                code: 500,
+                message: format!("Request error: {e:?}"),
+            }
         })?;
         let status = response.status();
+        metrics::record_tmdb_request(status.is_success());
         let text_body = response.text().map_err(|e| Error {
             code: 500,
             message: format!("Request error reading text: {e:?}"),
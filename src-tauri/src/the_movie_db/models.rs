@@ -1,13 +1,13 @@
+use crate::services::locale;
 use chrono::NaiveDate;
-use humantime::format_duration;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::collections::HashSet;
 
 // -------------------------
 // -------- Movies ---------
 // -------------------------
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct MovieResponse {
     pub adult: bool,
     pub backdrop_path: Option<String>,
@@ -60,16 +60,6 @@ impl MovieResponse {
             .saturating_sub(Self::MOVIE_RUNTIME_MARGIN)
             ..self.runtime_seconds() + Self::MOVIE_RUNTIME_MARGIN
     }
-
-    pub fn human_runtime(&self) -> String {
-        let duration = Duration::from_secs(self.runtime_seconds());
-        format!("{}", format_duration(duration))
-    }
-
-    // returns a basic file path for example Alien (1979)/Alien (1979).mkv
-    pub fn to_file_path(&self) -> String {
-        format!("{}/{}.mkv", self.title_year(), self.title_year())
-    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -101,6 +91,38 @@ pub struct ReleaseDate {
     pub release_type: u32,
 }
 
+impl MovieReleaseDatesResponse {
+    /// The rating in `country`, the US rating when that country has none
+    pub fn certification(&self, country: &str) -> Option<String> {
+        select_certification(
+            country,
+            self.results.iter().flat_map(|entry| {
+                entry
+                    .release_dates
+                    .iter()
+                    .map(move |release| (entry.iso_3166_1.as_str(), release.certification.as_str()))
+            }),
+        )
+    }
+}
+
+/// The first rating of `country`, or of the US as TMDB has the most of those. Releases
+/// without a rating are skipped, TMDB lists them with an empty one.
+fn select_certification<'a>(
+    country: &str,
+    ratings: impl Iterator<Item = (&'a str, &'a str)> + Clone,
+) -> Option<String> {
+    let rating_in = |wanted: &str| {
+        ratings
+            .clone()
+            .filter(|(iso_3166_1, _)| iso_3166_1.eq_ignore_ascii_case(wanted))
+            .map(|(_, rating)| rating.trim())
+            .find(|rating| !rating.is_empty())
+            .map(str::to_string)
+    };
+    rating_in(country).or_else(|| rating_in("US"))
+}
+
 // -------------------------
 // -------- Search ---------
 // -------------------------
@@ -147,6 +169,19 @@ pub struct SearchResult {
     vote_count: u32,
 }
 
+impl SearchResponse {
+    /// True when another result of the same kind has the same title, e.g. the UK and US
+    /// The Office, those get their TMDB id shown to tell them apart.
+    pub fn shares_title(&self, result: &SearchResult) -> bool {
+        let title = result.get_title();
+        self.results.iter().any(|other| {
+            other.id != result.id
+                && other.media_type == result.media_type
+                && other.get_title().eq_ignore_ascii_case(&title)
+        })
+    }
+}
+
 impl SearchResult {
     pub fn get_title(&self) -> String {
         self.title
@@ -155,18 +190,20 @@ impl SearchResult {
             .unwrap_or_else(|| "Unknown".to_string())
     }
 
-    pub fn get_date(&self) -> String {
+    /// The poster, the photo of a person
+    pub fn image_path(&self) -> Option<&String> {
+        self.poster_path.as_ref().or(self.profile_path.as_ref())
+    }
+
+    /// The release date of a movie, the first air date of a show
+    pub fn date(&self) -> Option<&str> {
         self.release_date
-            .clone()
-            .or_else(|| self.first_air_date.clone())
-            .map(|date| {
-                if date.len() >= 4 {
-                    date[..4].to_string()
-                } else {
-                    "N/A".to_string()
-                }
-            })
-            .unwrap_or_else(|| "N/A".to_string())
+            .as_deref()
+            .or(self.first_air_date.as_deref())
+    }
+
+    pub fn year(&self) -> Option<u32> {
+        self.date()?.get(..4)?.parse().ok()
     }
 }
 
@@ -174,7 +211,7 @@ impl SearchResult {
 // ---------- TV -----------
 // -------------------------
 
-#[derive(Serialize, Clone, PartialEq, Eq, Copy, PartialOrd, Ord, Deserialize, Debug)]
+#[derive(Serialize, Clone, PartialEq, Eq, Copy, PartialOrd, Ord, Deserialize, Debug, Default)]
 
 pub struct TvId(u32);
 
@@ -273,7 +310,56 @@ impl TryFrom<&str> for TvId {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+/// TMDB id of a season, not its season number. Both are plain numbers in the API, the types
+/// keep one from being compared to the other.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug,
+)]
+pub struct SeasonId(u32);
+
+impl std::fmt::Display for SeasonId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u32> for SeasonId {
+    fn from(id: u32) -> Self {
+        SeasonId(id)
+    }
+}
+
+impl From<SeasonId> for u32 {
+    fn from(id: SeasonId) -> Self {
+        id.0
+    }
+}
+
+/// TMDB id of an episode, not its episode number.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug,
+)]
+pub struct EpisodeId(u32);
+
+impl std::fmt::Display for EpisodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u32> for EpisodeId {
+    fn from(id: u32) -> Self {
+        EpisodeId(id)
+    }
+}
+
+impl From<EpisodeId> for u32 {
+    fn from(id: EpisodeId) -> Self {
+        id.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct TvResponse {
     pub adult: bool,
     pub backdrop_path: Option<String>,
@@ -311,6 +397,32 @@ pub struct TvResponse {
     pub vote_count: u32,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct TvContentRatingsResponse {
+    pub id: u32,
+    pub results: Vec<TvContentRating>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TvContentRating {
+    pub iso_3166_1: String,
+    pub rating: String,
+    #[serde(default)]
+    pub descriptors: Vec<String>,
+}
+
+impl TvContentRatingsResponse {
+    /// The rating in `country`, the US rating when that country has none
+    pub fn certification(&self, country: &str) -> Option<String> {
+        select_certification(
+            country,
+            self.results
+                .iter()
+                .map(|entry| (entry.iso_3166_1.as_str(), entry.rating.as_str())),
+        )
+    }
+}
+
 impl TvResponse {
     pub fn year(&self) -> Option<u32> {
         self.first_air_date.as_ref().and_then(|date_str| {
@@ -333,8 +445,14 @@ impl TvResponse {
         }
         let total: u32 = self.episode_run_time.iter().sum();
         let average = total as f64 / self.episode_run_time.len() as f64;
-        let duration = Duration::from_secs((average * 60.0) as u64);
-        format!("{}", format_duration(duration))
+        locale::current().format_runtime((average * 60.0) as u64)
+    }
+
+    /// The seasons with the specials after the regular ones, TMDB lists them first.
+    pub fn seasons_in_order(&self) -> Vec<&TvSeason> {
+        let mut seasons: Vec<&TvSeason> = self.seasons.iter().collect();
+        seasons.sort_by_key(|season| (season.is_specials(), season.season_number));
+        seasons
     }
 }
 
@@ -356,7 +474,7 @@ pub struct TvGenre {
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct TvEpisode {
-    pub id: u32,
+    pub id: EpisodeId,
     pub name: String,
     pub overview: String,
     pub vote_average: f64,
@@ -367,7 +485,7 @@ pub struct TvEpisode {
     pub production_code: String,
     pub runtime: u32,
     pub season_number: u32,
-    pub show_id: u32,
+    pub show_id: TvId,
     pub still_path: Option<String>,
 }
 
@@ -397,7 +515,7 @@ pub struct TvProductionCountry {
 pub struct TvSeason {
     pub air_date: Option<String>,
     pub episode_count: u32,
-    pub id: u32,
+    pub id: SeasonId,
     pub name: String,
     pub overview: String,
     pub poster_path: Option<String>,
@@ -405,6 +523,13 @@ pub struct TvSeason {
     pub vote_average: f64,
 }
 
+impl TvSeason {
+    /// TMDB keeps the specials of a show in season 0.
+    pub fn is_specials(&self) -> bool {
+        self.season_number == 0
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct TvSpokenLanguage {
     pub english_name: String,
@@ -416,31 +541,38 @@ pub struct TvSpokenLanguage {
 // ------- TV Season Response ---------
 // ------------------------------------
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct SeasonResponse {
     pub _id: String,
     pub air_date: Option<String>,
     pub episodes: Vec<SeasonEpisode>,
     pub name: String,
     pub overview: String,
-    pub id: u32,
+    pub id: SeasonId,
     pub poster_path: Option<String>,
     pub season_number: u32,
     pub vote_average: f32,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+impl SeasonResponse {
+    /// Season 0, ripped into `Season 00` like Plex expects.
+    pub fn is_specials(&self) -> bool {
+        self.season_number == 0
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct SeasonEpisode {
     pub air_date: Option<String>,
     pub episode_number: u32,
     pub episode_type: String,
-    pub id: u32,
+    pub id: EpisodeId,
     pub name: String,
     pub overview: String,
     pub production_code: Option<String>,
     pub runtime: Option<u32>,
     pub season_number: u32,
-    pub show_id: u32,
+    pub show_id: TvId,
     pub still_path: Option<String>,
     pub vote_average: f32,
     pub vote_count: u32,
@@ -475,19 +607,9 @@ impl SeasonEpisode {
         self.air_date
             .as_ref()
             .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
-            .map(|date| date.format("%B %-d, %Y").to_string())
+            .map(|date| locale::current().format_date(date))
             .unwrap_or_default()
     }
-
-    pub fn formatted_runtime(&self) -> String {
-        let minutes = self.runtime.unwrap_or(0);
-        let hours = minutes / 60;
-        if hours > 0 {
-            format!("{hours}h {}m", minutes % 60)
-        } else {
-            format!("{minutes}m")
-        }
-    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -519,3 +641,158 @@ pub struct SeasonGuestStar {
     pub popularity: f32,
     pub profile_path: Option<String>,
 }
+
+// ------------------------------------
+// ------------- People ---------------
+// ------------------------------------
+
+/// `person/{id}` with `combined_credits` appended, the movies and shows of an actor or a
+/// director in one request
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PersonResponse {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub biography: String,
+    #[serde(default)]
+    pub known_for_department: String,
+    pub birthday: Option<String>,
+    pub deathday: Option<String>,
+    pub profile_path: Option<String>,
+    #[serde(default)]
+    pub combined_credits: PersonCombinedCredits,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct PersonCombinedCredits {
+    #[serde(default)]
+    pub cast: Vec<PersonCredit>,
+    #[serde(default)]
+    pub crew: Vec<PersonCredit>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PersonCredit {
+    pub id: u32,
+    #[serde(default)]
+    pub media_type: String,
+    pub title: Option<String>,
+    pub name: Option<String>,
+    pub release_date: Option<String>,
+    pub first_air_date: Option<String>,
+    pub poster_path: Option<String>,
+    /// Cast credits only
+    pub character: Option<String>,
+    /// Crew credits only
+    pub job: Option<String>,
+}
+
+impl PersonCredit {
+    pub fn get_title(&self) -> String {
+        self.title
+            .clone()
+            .or_else(|| self.name.clone())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+
+    /// The release date of a movie, the first air date of a show, TMDB sends empty strings
+    /// for titles that didn't come out yet
+    pub fn date(&self) -> Option<&str> {
+        self.release_date
+            .as_deref()
+            .or(self.first_air_date.as_deref())
+            .filter(|date| !date.is_empty())
+    }
+
+    pub fn year(&self) -> Option<u32> {
+        self.date()?.get(..4)?.parse().ok()
+    }
+
+    /// "Neo", "Director"
+    pub fn role(&self) -> Option<&str> {
+        self.character
+            .as_deref()
+            .or(self.job.as_deref())
+            .filter(|role| !role.is_empty())
+    }
+}
+
+impl PersonResponse {
+    /// Every movie and show once, newest first and the unreleased ones at the top. A title
+    /// the person acted in and also worked on keeps the cast credit.
+    pub fn filmography(&self) -> Vec<&PersonCredit> {
+        let mut seen = HashSet::new();
+        let mut credits: Vec<&PersonCredit> = self
+            .combined_credits
+            .cast
+            .iter()
+            .chain(&self.combined_credits.crew)
+            .filter(|credit| credit.media_type == "movie" || credit.media_type == "tv")
+            .filter(|credit| seen.insert((credit.media_type.as_str(), credit.id)))
+            .collect();
+        credits.sort_by(|a, b| match (a.date(), b.date()) {
+            (Some(a), Some(b)) => b.cmp(a),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        });
+        credits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_rating_of_the_country() {
+        let ratings: TvContentRatingsResponse = serde_json::from_str(
+            r#"{"id": 1396, "results": [
+                {"iso_3166_1": "DE", "rating": "16"},
+                {"iso_3166_1": "FR", "rating": ""},
+                {"iso_3166_1": "US", "rating": "TV-MA"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(ratings.certification("DE").as_deref(), Some("16"));
+        assert_eq!(ratings.certification("FR").as_deref(), Some("TV-MA"));
+        assert_eq!(ratings.certification("JP").as_deref(), Some("TV-MA"));
+    }
+
+    #[test]
+    fn lists_each_credit_once_newest_first() {
+        let person: PersonResponse = serde_json::from_str(
+            r#"{
+                "id": 6384,
+                "name": "Keanu Reeves",
+                "birthday": "1964-09-02",
+                "deathday": null,
+                "profile_path": null,
+                "combined_credits": {
+                    "cast": [
+                        {"id": 603, "media_type": "movie", "title": "The Matrix", "release_date": "1999-03-31", "character": "Neo"},
+                        {"id": 1, "media_type": "movie", "title": "Untitled", "release_date": ""},
+                        {"id": 245891, "media_type": "movie", "title": "John Wick", "release_date": "2014-10-22", "character": "John Wick"}
+                    ],
+                    "crew": [
+                        {"id": 603, "media_type": "movie", "title": "The Matrix", "release_date": "1999-03-31", "job": "Stunts"},
+                        {"id": 2, "media_type": "tv", "name": "Show", "first_air_date": "2020-01-01", "job": "Producer"}
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+        let titles: Vec<(String, Option<&str>)> = person
+            .filmography()
+            .into_iter()
+            .map(|credit| (credit.get_title(), credit.role()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("Untitled".to_string(), None),
+                ("Show".to_string(), Some("Producer")),
+                ("John Wick".to_string(), Some("John Wick")),
+                ("The Matrix".to_string(), Some("Neo")),
+            ]
+        );
+    }
+}
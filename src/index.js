@@ -5,6 +5,7 @@ import "./stylesheets/application.scss";
 import "./javascripts/turbo.js";
 import "./javascripts/controllers/index.js";
 import "./javascripts/disk_listener.js";
+import "./javascripts/notification_actions.js";
 import * as bootstrap from "bootstrap/dist/js/bootstrap.min.js";
 
 window.bootstrap = bootstrap;
@@ -15,12 +15,25 @@ export default class extends Controller {
     const selectedTitle = event.currentTarget;
     const part = parseInt(selectedTitle.dataset.episodePart);
     const titleId = parseInt(selectedTitle.value);
+    this.assignTitle({ titleId, part });
+  }
+
+  // data-action="episode#assign" data-episode-title-id-param="3"
+  // data-episode-part-param="1" data-episode-confirmed-param="true"
+  assign({ params: { titleId, part, confirmed } }) {
+    this.assignTitle({ titleId, part, confirmed });
+  }
+
+  assignTitle({ titleId, part, confirmed }) {
     window.turboInvoke("assign_episode_to_title", {
-      mvdbId: this.mvdbIdValue,
-      seasonNumber: this.seasonNumberValue,
-      episodeNumber: this.episodeNumberValue,
-      titleId: titleId,
-      part: part,
+      request: {
+        mvdbId: this.mvdbIdValue,
+        seasonNumber: this.seasonNumberValue,
+        episodeNumber: this.episodeNumberValue,
+        titleId: titleId,
+        part: part,
+        confirmed: confirmed,
+      },
     });
   }
 }
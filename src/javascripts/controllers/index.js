@@ -15,6 +15,9 @@ application.register("disk-select", DiskSelectController);
 import EpisodeController from "./episode_controller.js";
 application.register("episode", EpisodeController);
 
+import RipSeasonController from "./rip_season_controller.js";
+application.register("rip-season", RipSeasonController);
+
 import EpisodeReorderController from "./episode_reorder_controller.js";
 application.register("episode-reorder", EpisodeReorderController);
 
@@ -29,3 +32,11 @@ application.register("inline-autocomplete", InlineAutocompleteController);
 
 import ToastController from "./toast_controller.js";
 application.register("toast", ToastController);
+
+import JobNoteController from "./job_note_controller.js";
+application.register("job-note", JobNoteController);
+import TitlePreviewController from "./title_preview_controller.js";
+application.register("title-preview", TitlePreviewController);
+
+import SettingsFormController from "./settings_form_controller.js";
+application.register("settings-form", SettingsFormController);
@@ -17,6 +17,9 @@ export default class extends Controller {
     if (event.key !== "Tab") {
       const value = input.value;
       window.turboInvoke("suggestion", { search: value });
+      // Debounced in the backend, an empty query clears the list once the search is sent
+      const closesList = event.key === "Enter" || event.key === "Escape";
+      window.turboInvoke("search_suggest", { query: closesList ? "" : value });
     }
   }
 
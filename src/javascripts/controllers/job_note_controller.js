@@ -0,0 +1,19 @@
+import { Controller } from "@hotwired/stimulus";
+
+// Connects to data-controller="job-note"
+//
+// Saves the note of a job, e.g. where the disc is stored, when the input loses focus
+// or Enter is pressed.
+export default class extends Controller {
+  static values = {
+    id: Number,
+  };
+
+  // data-action="change->job-note#save"
+  save(event) {
+    window.turboInvoke("update_job_note", {
+      id: this.idValue,
+      note: event.currentTarget.value,
+    });
+  }
+}
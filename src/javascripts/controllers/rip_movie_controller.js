@@ -2,7 +2,15 @@ import { Controller } from "@hotwired/stimulus";
 
 // Connects to data-controller="rip-movie"
 export default class extends Controller {
-  static targets = ["movieId", "link", "part", "edition"];
+  static targets = [
+    "movieId",
+    "link",
+    "part",
+    "edition",
+    "library",
+    "join",
+    "skipUpload",
+  ];
 
   rip(event) {
     event.preventDefault();
@@ -14,6 +22,13 @@ export default class extends Controller {
     const editionInput = card.querySelector(
       '[data-rip-movie-target="edition"]',
     );
+    const libraryInput = card.querySelector(
+      '[data-rip-movie-target="library"]',
+    );
+    const joinInput = card.querySelector('[data-rip-movie-target="join"]');
+    const skipUploadInput = card.querySelector(
+      '[data-rip-movie-target="skipUpload"]',
+    );
 
     const commandArgs = {
       diskId: parseInt(button.dataset.diskId),
@@ -24,8 +39,12 @@ export default class extends Controller {
         editionInput && editionInput.value.trim()
           ? editionInput.value.trim()
           : null,
+      library: libraryInput && libraryInput.value ? libraryInput.value : null,
+      joinTitleId:
+        joinInput && joinInput.value ? parseInt(joinInput.value) : null,
+      skipUpload: skipUploadInput ? skipUploadInput.checked : false,
     };
 
-    turboInvoke("rip_movie", commandArgs);
+    turboInvoke("rip_movie", { request: commandArgs });
   }
 }
@@ -0,0 +1,13 @@
+import { Controller } from "@hotwired/stimulus";
+
+// Connects to data-controller="rip-season"
+export default class extends Controller {
+  // The library name is sent as typed, a link would turn "2024 Kids" into 2024
+  // data-action="click->rip-season#rip"
+  rip(event) {
+    event.preventDefault();
+    window.turboInvoke("rip_season", {
+      library: event.currentTarget.dataset.library,
+    });
+  }
+}
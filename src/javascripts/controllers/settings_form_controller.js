@@ -0,0 +1,72 @@
+import { Controller } from "@hotwired/stimulus";
+
+// Connects to data-controller="settings-form"
+//
+// Saves a settings form with the command in data-settings-form-command-value whenever a
+// field changes. The commands take typed arguments, so checkboxes are sent as booleans and
+// number fields and selects marked data-number as numbers, an empty one as null. Fields
+// marked data-json, e.g. a list of library profiles, are parsed before sending. A command
+// that takes the fields as one struct names its argument in data-settings-form-argument-value.
+// A plain text answer, e.g. "Naming settings updated successfully", is shown in the status
+// target, errors come back as toasts.
+export default class extends Controller {
+  static targets = ["status"];
+  static values = {
+    command: String,
+    argument: String,
+  };
+
+  // data-action="change->settings-form#save submit->settings-form#save"
+  save(event) {
+    event?.preventDefault();
+    let args;
+    try {
+      args = this.args();
+    } catch (e) {
+      this.showStatus(`${e.message}, nothing was saved`);
+      return;
+    }
+    if (this.argumentValue) args = { [this.argumentValue]: args };
+    window
+      .turboInvoke(this.commandValue, args)
+      .then((response) => response.text())
+      .then((text) => this.showStatus(text));
+  }
+
+  // data-action="settings-form#pick" data-settings-form-kind-param="movies"
+  // data-settings-form-input-param="movies-dir"
+  pick({ params: { kind, input } }) {
+    window
+      .turboInvoke("pick_library_dir", { kind })
+      .then((response) => (response.ok ? response.text() : ""))
+      .then((path) => {
+        if (!path) return;
+        document.getElementById(input).value = path;
+        this.save();
+      });
+  }
+
+  args() {
+    const args = {};
+    for (const field of this.element.elements) {
+      if (!field.name || field.disabled) continue;
+      if (field.type === "checkbox") {
+        args[field.name] = field.checked;
+      } else if (field.type === "radio") {
+        if (field.checked) args[field.name] = field.value;
+      } else if (field.dataset.json !== undefined) {
+        args[field.name] = JSON.parse(field.value || "null");
+      } else if (field.type === "number" || field.dataset.number !== undefined) {
+        args[field.name] = field.value === "" ? null : Number(field.value);
+      } else {
+        args[field.name] = field.value;
+      }
+    }
+    return args;
+  }
+
+  showStatus(text) {
+    if (!this.hasStatusTarget || text.includes("<turbo-stream")) return;
+    this.statusTarget.textContent = text;
+  }
+}
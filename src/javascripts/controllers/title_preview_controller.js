@@ -0,0 +1,25 @@
+import { Controller } from "@hotwired/stimulus";
+
+// Connects to data-controller="title-preview"
+//
+// Rips the first minutes of a title and opens them in the default player. The title is
+// taken from data-title-id, or from the title select next to the button.
+export default class extends Controller {
+  preview(event) {
+    event.preventDefault();
+    const button = event.currentTarget;
+    const select = button.closest(".row")?.querySelector("select");
+    const titleId = button.dataset.titleId || select?.value;
+    if (!titleId) return;
+
+    button.disabled = true;
+    window
+      .turboInvoke("preview_title", {
+        diskId: parseInt(button.dataset.diskId),
+        titleId: parseInt(titleId),
+      })
+      .finally(() => {
+        button.disabled = false;
+      });
+  }
+}
@@ -0,0 +1,25 @@
+import { onAction, registerActionTypes } from "@tauri-apps/plugin-notification";
+import { trace } from "@tauri-apps/plugin-log";
+
+// Buttons on failure notifications, the ids match NotificationActions in notifications.rs.
+// Only the mobile notification centers have actions, on the desktop this fails and the
+// notifications have no buttons.
+const RETRY = { id: "retry", title: "Retry", foreground: true };
+const OPEN_FOLDER = { id: "open-folder", title: "Open folder", foreground: true };
+const VIEW_LOG = { id: "view-log", title: "View log", foreground: true };
+
+try {
+  await registerActionTypes([
+    { id: "rip-failed", actions: [VIEW_LOG] },
+    { id: "upload-failed", actions: [RETRY, OPEN_FOLDER, VIEW_LOG] },
+  ]);
+  await onAction((event) => {
+    const extra = event.notification?.extra ?? event.extra ?? {};
+    window.turboInvoke("notification_action", {
+      actionId: event.actionId,
+      jobId: extra.job_id,
+    });
+  });
+} catch (e) {
+  trace(`Notification actions are not available: ${e}`);
+}
@@ -38,6 +38,8 @@ window.turboInvoke = async function turboInvoke(command, commandArgs) {
     if (errorElem) {
       // avoid innerHTML, just set plain text
       errorElem.textContent = e?.message?.toString() ?? String(e);
+      // e.g. "validation" or "the_movie_db", see ErrorKind in reelix_error.rs
+      errorElem.dataset.errorKind = e?.kind ?? "internal";
     }
     return new Response(String(e), { status: 500 });
   }